@@ -25,6 +25,20 @@ fn main() -> Result<(), hotline_rs::Error> {
         ..Default::default()
     })?;
 
+    // client --dump-render-graph <graph_name> [dot|json] prints the built render graph and exits
+    let args = std::env::args().collect::<Vec<String>>();
+    if let Some(pos) = args.iter().position(|arg| arg == "--dump-render-graph") {
+        let graph_name = args.get(pos + 1).ok_or(hotline_rs::Error {
+            msg: "usage: client --dump-render-graph <graph_name> [dot|json]".to_string()
+        })?;
+        let export = ctx.export_render_graph(graph_name)?;
+        match args.get(pos + 2).map(|format| format.as_str()) {
+            Some("json") => println!("{}", export.to_json()?),
+            _ => println!("{}", export.to_dot())
+        }
+        return Ok(());
+    }
+
     // run
     if let Err(e) = ctx.run() {
         println!("error: {}", e.msg);
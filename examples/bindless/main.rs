@@ -30,7 +30,7 @@ fn main() -> Result<(), hotline_rs::Error> {
         shader_heap_size: 100,
         render_target_heap_size: 100,
         depth_stencil_heap_size: 100,
-    });
+    })?;
     print!("{}", device.get_adapter_info());
 
     let mut dev = device;
@@ -124,7 +124,7 @@ fn main() -> Result<(), hotline_rs::Error> {
         hotline_rs::get_src_data_path("textures/monsters/laptop.jpg"),
     ];
     for file in files {
-        let image = image::load_from_file(&file)?;
+        let image = image::load_from_file(&file, None)?;
         let tex = dev.create_texture(&image.info, data![image.data.as_slice()])?;
         textures.push(tex);
     }
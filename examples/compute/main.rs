@@ -32,7 +32,7 @@ fn main() -> Result<(), hotline_rs::Error> {
         shader_heap_size: 100,
         render_target_heap_size: 100,
         depth_stencil_heap_size: 100,
-    });
+    })?;
     print!("{}", dev.get_adapter_info());
 
     let mut win = app.create_window(os::WindowInfo {
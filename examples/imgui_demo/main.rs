@@ -18,7 +18,7 @@ fn main() -> Result<(), hotline_rs::Error> {
         shader_heap_size: 100,
         render_target_heap_size: 100,
         depth_stencil_heap_size: 100,
-    });
+    })?;
 
     // window
     let mut win = app.create_window(os::WindowInfo {
@@ -46,7 +46,7 @@ fn main() -> Result<(), hotline_rs::Error> {
         render_target_heap_size: num_buffers as usize,
         shader_heap_size: 32,
         ..Default::default()
-    });
+    })?;
     println!("{}", device.get_adapter_info());
     println!("features: {:?}", device.get_feature_flags());
 
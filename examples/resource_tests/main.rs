@@ -24,7 +24,7 @@ fn main() -> Result<(), hotline_rs::Error> {
         shader_heap_size: 100,
         render_target_heap_size: 100,
         depth_stencil_heap_size: 100,
-    });
+    })?;
     print!("{}", device.get_adapter_info());
 
     let mut dev = device;
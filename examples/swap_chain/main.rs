@@ -34,7 +34,7 @@ fn main() -> Result<(), hotline_rs::Error> {
     let mut device = gfx_platform::Device::create(&gfx::DeviceInfo {
         render_target_heap_size: num_buffers as usize,
         ..Default::default()
-    });
+    })?;
 
     // create a swap chain
     println!("create swap chain!");
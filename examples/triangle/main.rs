@@ -29,7 +29,7 @@ fn main() -> Result<(), hotline_rs::Error> {
     let mut device = gfx_platform::Device::create(&gfx::DeviceInfo {
         render_target_heap_size: num_buffers as usize,
         ..Default::default()
-    });
+    })?;
     println!("{}", device.get_adapter_info());
 
     let mut window = app.create_window(os::WindowInfo {
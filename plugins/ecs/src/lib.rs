@@ -5,7 +5,9 @@ use maths_rs::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_ecs::schedule::SystemConfigs;
 
-use std::collections::HashMap;
+use export_macros::export_plugin_state;
+
+use std::collections::{HashMap, HashSet};
 
 macro_rules! log_error {
     ($map:expr, $name:expr) => {
@@ -24,7 +26,10 @@ struct BevyPlugin {
     run_setup: bool,
     session_info: SessionInfo,
     errors: HashMap<String, Vec<String>>,
-    render_graph_hash: pmfx::PmfxHash
+    render_graph_hash: pmfx::PmfxHash,
+    inspector: hotline_rs::inspector::Inspector,
+    gizmo: hotline_rs::gizmo::Gizmo,
+    history: hotline_rs::history::History
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
@@ -37,17 +42,6 @@ pub enum CoreSystemSets {
 type PlatformClient = Client<gfx_platform::Device, os_platform::App>;
 type PlatformImgui = imgui::ImGui<gfx_platform::Device, os_platform::App>;
 
-fn update_world_matrices(
-    mut query: Query<(&Position, &mut Rotation, &Scale, &mut WorldMatrix)>) {
-    // bake a local matrix from position, rotation and scale
-    for (position, rotation, scale, mut world_matrix) in &mut query {
-        let translate = Mat34f::from_translation(position.0);
-        let rotate = Mat34f::from(rotation.0);
-        let scale = Mat34f::from_scale(scale.0);
-        world_matrix.0 = translate * rotate * scale;
-    }
-}
-
 fn update_main_camera_config(
     main_window: Res<MainWindowRes>,
     mut info: ResMut<SessionInfo>,
@@ -341,6 +335,28 @@ fn update_cameras(
     }
 }
 
+/// Creates meshes for entities spawned with a `MeshAsset`, assets which fail to load are reported once and skipped
+fn resolve_mesh_assets(
+    mut device: ResMut<DeviceRes>,
+    mut commands: Commands,
+    mut failed: Local<HashSet<Entity>>,
+    query: Query<(Entity, &MeshAsset), Without<MeshComponent>>) {
+    for (entity, asset) in &query {
+        if failed.contains(&entity) {
+            continue;
+        }
+        match hotline_rs::scene::create_mesh_asset(&mut device.0, &asset.0) {
+            Ok(mesh) => {
+                commands.entity(entity).insert(MeshComponent(mesh));
+            }
+            Err(err) => {
+                println!("{}", err.msg);
+                failed.insert(entity);
+            }
+        }
+    }
+}
+
 impl BevyPlugin {
     /// Finds get_system calls inside ecs compatible plugins, call the function `get_system_<lib_name>` to disambiguate
     fn get_system_function(&self, name: &str, view_name: &str, client: &PlatformClient) -> Option<SystemConfigs> {
@@ -437,6 +453,16 @@ impl BevyPlugin {
         self.spawn_main_camera();
     }
 
+    /// Applies a `SessionInfo` restored by undo or redo, switching demo or respawning the main camera if they changed
+    fn restore_session(&mut self, client: &mut PlatformClient, previous: &SessionInfo) {
+        if self.session_info.active_demo != previous.active_demo {
+            self.resetup(client);
+        }
+        else if self.session_info.main_camera != previous.main_camera {
+            self.spawn_main_camera();
+        }
+    }
+
     /// Custom function to handle custome data change events which can trigger resetup
     fn check_for_changes(&mut self, client: &mut PlatformClient) {
         // render graph itself has chaned
@@ -584,6 +610,7 @@ impl BevyPlugin {
     }
 }
 
+#[export_plugin_state(version = 1, history)]
 impl Plugin<gfx_platform::Device, os_platform::App> for BevyPlugin {
     fn create() -> Self {
         BevyPlugin {
@@ -595,7 +622,10 @@ impl Plugin<gfx_platform::Device, os_platform::App> for BevyPlugin {
             render_graph_hash: 0,
             run_setup: false,
             session_info: SessionInfo::default(),
-            errors: HashMap::new()
+            errors: HashMap::new(),
+            inspector: hotline_rs::inspector::Inspector::default(),
+            gizmo: hotline_rs::gizmo::Gizmo::default(),
+            history: hotline_rs::history::History::default()
         }
     }
 
@@ -638,9 +668,12 @@ impl Plugin<gfx_platform::Device, os_platform::App> for BevyPlugin {
         // core update
         self.schedule.add_systems(update_cameras.in_set(CoreSystemSets::Update));
         self.schedule.add_systems(update_main_camera_config.in_set(CoreSystemSets::Update));
+        self.schedule.add_systems(hotline_rs::gizmo::update_gizmo.after(update_cameras).in_set(CoreSystemSets::Update));
+        self.schedule.add_systems((hotline_rs::scene::reload_scenes, resolve_mesh_assets).chain().in_set(CoreSystemSets::Update));
 
-        // core batch functions do syncronised work to prepare buffers / matrices for drawing
-        self.schedule.add_systems(update_world_matrices.in_set(SystemSets::Batch).after(SystemSets::Update));
+        // core batch functions do syncronised work to prepare buffers / matrices for drawing, world matrices are
+        // propagated through the transform hierarchy before any demo batch functions read them
+        self.schedule.add_systems(transform_hierarchy_systems().in_set(CoreSystemSets::Batch));
 
         // hook in setup funcs
         for func_name in &info.setup {
@@ -715,6 +748,10 @@ impl Plugin<gfx_platform::Device, os_platform::App> for BevyPlugin {
         self.world.insert_resource(ImGuiRes(client.imgui));
         self.world.insert_resource(viewport_info);
 
+        // the gizmo and inspector share a selection, the gizmo can change it by picking entities
+        self.gizmo.selected = self.inspector.selected;
+        self.world.insert_resource(std::mem::take(&mut self.gizmo));
+
         // run setup if requested, we did it here so hotline resources are inserted into World
         if self.run_setup {
             self.spawn_main_camera();
@@ -735,6 +772,8 @@ impl Plugin<gfx_platform::Device, os_platform::App> for BevyPlugin {
         client.time = self.world.remove_resource::<TimeRes>().unwrap().0;
         client.imgui = self.world.remove_resource::<ImGuiRes>().unwrap().0;
         self.session_info = self.world.remove_resource::<SessionInfo>().unwrap();
+        self.gizmo = self.world.remove_resource::<hotline_rs::gizmo::Gizmo>().unwrap();
+        self.inspector.selected = self.gizmo.selected;
 
         // write back session info which will be serialised to disk and reloaded between sessions
         client.serialise_plugin_data("ecs", &self.session_info);
@@ -912,6 +951,39 @@ impl Plugin<gfx_platform::Device, os_platform::App> for BevyPlugin {
         }
 
         client.imgui.end();
+
+        // entity inspector
+        let mut open = true;
+        if client.imgui.begin("Scene", &mut open, imgui::WindowFlags::NONE) {
+            self.gizmo.ui(&mut client.imgui);
+            client.imgui.separator();
+            self.inspector.ui(&mut client.imgui, &mut self.world, &mut self.history);
+        }
+        client.imgui.end();
+
+        // undo history
+        let session_info = self.session_info.clone();
+        let (enable_keyboard, _) = client.app.get_input_enabled();
+        if enable_keyboard && client.app.is_sys_key_down(os::SysKey::Ctrl) {
+            let keys = client.app.get_keys_pressed();
+            if keys['Z' as usize] {
+                self.history.undo(&mut self.world, &mut self.session_info);
+            }
+            else if keys['Y' as usize] {
+                self.history.redo(&mut self.world, &mut self.session_info);
+            }
+        }
+
+        let mut open = true;
+        if client.imgui.begin("History", &mut open, imgui::WindowFlags::NONE) {
+            self.history.ui(&mut client.imgui, &mut self.world, &mut self.session_info);
+        }
+        client.imgui.end();
+        self.restore_session(client, &session_info);
+
+        // edits are recorded once the mouse is released and no widget is active, so each drag is a single command
+        let idle = !client.app.get_mouse_buttons().iter().any(|down| *down) && !client.imgui.is_any_item_active();
+        self.history.track(&self.world, self.inspector.selected, &self.session_info, idle);
     }
 }
 
@@ -919,4 +991,7 @@ impl Plugin<gfx_platform::Device, os_platform::App> for BevyPlugin {
 // Plugin
 //
 
-hotline_plugin![BevyPlugin];
\ No newline at end of file
+hotline_plugin![BevyPlugin];
+
+// the demo systems are looked up from the ecs_examples lib, so ecs is setup again whenever it reloads
+hotline_plugin_dependencies!["ecs_examples"];
\ No newline at end of file
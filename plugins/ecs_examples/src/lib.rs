@@ -32,6 +32,7 @@ mod claude;
 
 use prelude::*;
 use hotline_rs::gfx::{RaytracingTLAS};
+use hotline_rs::gizmo::Gizmo;
 
 pub fn load_material(
     device: &mut gfx_platform::Device,
@@ -368,12 +369,13 @@ pub fn render_debug(
     mut imdraw: ResMut<ImDrawRes>,
     mut device: ResMut<DeviceRes>,
     session_info: ResMut<SessionInfo>,
+    gizmo: Res<Gizmo>,
     draw_query: Query<(&WorldMatrix, &Extents)>,
     camera_query: Query<(&Name, &Camera)>
 ) -> Result<(), hotline_rs::Error> {
 
-    // skip over rendering if we supply no flags
-    if session_info.debug_draw_flags.is_empty() {
+    // skip over rendering if we supply no flags and have no gizmo
+    if session_info.debug_draw_flags.is_empty() && gizmo.origin.is_none() {
         return Ok(());
     }
 
@@ -434,6 +436,9 @@ pub fn render_debug(
         }
     }
 
+    // selection and gizmo
+    gizmo.draw(&mut imdraw.0);
+
     // submit the buffers
     imdraw.submit(&mut device.0, bb as usize).unwrap();
 
@@ -29,7 +29,7 @@ pub fn setup_texture2d_array(
 
     let texture_array_filepath = hotline_rs::get_data_path("textures/bear.dds");
 
-    let texture_array_info = image::load_from_file(&texture_array_filepath)?;
+    let texture_array_info = image::load_from_file(&texture_array_filepath, None)?;
     let texture_array = device.0.create_texture_with_heaps(
         &texture_array_info.info,
         gfx::TextureHeapInfo {
@@ -25,7 +25,7 @@ pub fn setup_texture3d(
 
     let cube_mesh = hotline_rs::primitives::create_cube_mesh(&mut device.0);
 
-    let volume_info = image::load_from_file(&hotline_rs::get_data_path("textures/sdf_shadow.dds")).unwrap();
+    let volume_info = image::load_from_file(&hotline_rs::get_data_path("textures/sdf_shadow.dds"), None).unwrap();
     let volume = device.0.create_texture_with_heaps(
         &volume_info.info,
         gfx::TextureHeapInfo {
@@ -270,4 +270,63 @@ pub fn export_compute_fn(attr: TokenStream, item: TokenStream) -> TokenStream {
     let mut output = TokenStream::from(expanded);
     output.extend(export_tokens);
     output
-}
\ No newline at end of file
+}
+
+/// Implements `Plugin::state_version`, `Plugin::save_state` and `Plugin::restore_state` inside an `impl Plugin` block.
+/// The listed fields are serialised with serde to preserve them across hot reloads, fields must implement
+/// `Serialize` and `Deserialize`. If restoring fails the instance is reset with `Plugin::create`. Bump the version when
+/// the listed fields or their types change:
+/// #[export_plugin_state(version = 1, session_info, camera)]
+/// impl Plugin<gfx_platform::Device, os_platform::App> for MyPlugin {
+/// ..
+/// }
+#[proc_macro_attribute]
+pub fn export_plugin_state(attr: TokenStream, item: TokenStream) -> TokenStream {
+    // parse version and field names
+    let mut version = "0".to_string();
+    let mut fields = Vec::new();
+    for arg in attr.to_string().split(',') {
+        let arg = arg.trim();
+        if arg.is_empty() {
+            continue;
+        }
+        if let Some((key, value)) = arg.split_once('=') {
+            if key.trim() == "version" {
+                version = value.trim().to_string();
+            }
+            else {
+                panic!("export_plugin_state: unknown argument `{}`", arg);
+            }
+        }
+        else {
+            fields.push(arg.to_string());
+        }
+    }
+
+    // fields are saved and restored as a tuple
+    let save_refs = fields.iter().map(|f| format!("&self.{},", f)).collect::<String>();
+    let restore_names = fields.iter().map(|f| format!("{},", f)).collect::<String>();
+    let restore_assigns = fields.iter().map(|f| format!("self.{} = {};", f, f)).collect::<String>();
+
+    let state_fns = format!("
+        fn state_version(&self) -> u32 {{
+            {}
+        }}
+
+        fn save_state(&mut self) -> Vec<u8> {{
+            hotline_rs::plugin::serialise_state(&({}))
+        }}
+
+        fn restore_state(&mut self, state: &[u8]) -> Result<(), hotline_rs::Error> {{
+            let ({}) = hotline_rs::plugin::deserialise_state(state)?;
+            {}
+            Ok(())
+        }}
+    ", version, save_refs, restore_names, restore_assigns);
+
+    // insert the state functions before the closing brace of the impl block
+    let item = item.to_string();
+    let close = item.rfind('}').expect("export_plugin_state: expected an impl block");
+    let output = format!("{}{}}}", &item[..close], state_fns);
+    output.parse().unwrap()
+}
@@ -142,7 +142,8 @@ pub struct Client<D: gfx::Device, A: os::App> {
     plugins: Vec<PluginCollection>,
     delta_history: VecDeque<f32>,
     instance_name: String,
-    status_bar_height: f32
+    status_bar_height: f32,
+    show_build_diagnostics: bool
 }
 
 /// Serialisable plugin
@@ -175,7 +176,115 @@ struct PluginCollection {
     name: String,
     reloader: reloader::Reloader,
     instance: PluginInstance,
-    state: PluginState
+    state: PluginState,
+    dependencies: Vec<String>,
+    manifest: plugin::PluginManifest,
+    saved_state: Option<plugin::SavedPluginState>
+}
+
+/// Calls `Plugin::save_state` on `instance` if the plugin exports it, returns `None` if there is no state to restore
+fn save_plugin_state(lib: &hot_lib_reloader::LibReloader, instance: PluginInstance) -> Option<plugin::SavedPluginState> {
+    if instance.is_null() {
+        return None;
+    }
+    unsafe {
+        let state_version = lib.get_symbol::<unsafe extern "C" fn(PluginInstance) -> u32>("state_version".as_bytes());
+        let save_state = lib.get_symbol::<unsafe extern "C" fn(PluginInstance, *mut Vec<u8>)>("save_state".as_bytes());
+        if let (Ok(state_version_fn), Ok(save_state_fn)) = (state_version, save_state) {
+            let mut data = Vec::new();
+            save_state_fn(instance, &mut data);
+            if !data.is_empty() {
+                return Some(plugin::SavedPluginState {
+                    version: state_version_fn(instance),
+                    data
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Calls `Plugin::restore_state` on a newly created `instance` with state saved before a reload. Returns false if
+/// restoring failed and the instance may be in an invalid state, incompatible state versions are skipped and return true
+fn restore_plugin_state(
+    lib: &hot_lib_reloader::LibReloader,
+    name: &str,
+    instance: PluginInstance,
+    state: &plugin::SavedPluginState) -> bool {
+    if instance.is_null() {
+        return true;
+    }
+    unsafe {
+        let state_version = lib.get_symbol::<unsafe extern "C" fn(PluginInstance) -> u32>("state_version".as_bytes());
+        let restore_state = lib.get_symbol::<unsafe extern "C" fn(PluginInstance, *const u8, usize) -> bool>("restore_state".as_bytes());
+        if let (Ok(state_version_fn), Ok(restore_state_fn)) = (state_version, restore_state) {
+            let version = state_version_fn(instance);
+            if version != state.version {
+                println!(
+                    "hotline_rs::client:: plugin: {} state version changed ({} -> {}), starting fresh",
+                    name, state.version, version
+                );
+                return true;
+            }
+            return restore_state_fn(instance, state.data.as_ptr(), state.data.len());
+        }
+    }
+    true
+}
+
+/// Calls the `reset_state` export of `lib` to replace `instance` in place with a freshly created plugin, so no new
+/// instance is allocated when restoring state fails
+fn reset_plugin_state(lib: &hot_lib_reloader::LibReloader, instance: PluginInstance) {
+    if instance.is_null() {
+        return;
+    }
+    unsafe {
+        let reset_state = lib.get_symbol::<unsafe extern "C" fn(PluginInstance)>("reset_state".as_bytes());
+        if let Ok(reset_state_fn) = reset_state {
+            reset_state_fn(instance);
+        }
+    }
+}
+
+/// Returns the plugin dependencies declared in the `manifest` and exported by `lib` through `hotline_plugin_dependencies!`
+fn get_plugin_dependencies(lib: &hot_lib_reloader::LibReloader, manifest: &plugin::PluginManifest) -> Vec<String> {
+    let mut dependencies = manifest.dependencies.to_vec();
+    unsafe {
+        let exported = lib.get_symbol::<unsafe extern "C" fn(*mut Vec<String>)>(plugin::DEPENDENCIES_SYMBOL.as_bytes());
+        if let Ok(exported_fn) = exported {
+            let mut exported_dependencies = Vec::new();
+            exported_fn(&mut exported_dependencies);
+            for dependency in exported_dependencies {
+                if !dependencies.contains(&dependency) {
+                    dependencies.push(dependency);
+                }
+            }
+        }
+    }
+    dependencies
+}
+
+/// Returns dependency graph info for `plugins`
+fn get_plugin_dependency_infos(plugins: &[PluginCollection]) -> Vec<plugin::PluginDependencyInfo> {
+    plugins.iter().map(|p| plugin::PluginDependencyInfo {
+        name: p.name.to_string(),
+        dependencies: p.dependencies.to_vec()
+    }).collect()
+}
+
+/// Sorts `plugins` so dependencies come before their dependents, on error the order is unchanged and the error is printed
+fn sort_plugins(plugins: &mut Vec<PluginCollection>) {
+    match plugin::sort_plugin_dependencies(&get_plugin_dependency_infos(plugins)) {
+        Ok(order) => {
+            let mut unsorted = std::mem::take(plugins).into_iter().map(Some).collect::<Vec<_>>();
+            for i in order {
+                plugins.push(unsorted[i].take().unwrap());
+            }
+        }
+        Err(err) => {
+            println!("hotline_rs::client:: [error] {:?}", err);
+        }
+    }
 }
 
 /// Hotline `Client` implementation
@@ -217,7 +326,7 @@ impl<D, A> Client<D, A> where D: gfx::Device, A: os::App, D::RenderPipeline: gfx
             shader_heap_size: info.shader_heap_size,
             render_target_heap_size: info.render_target_heap_size,
             depth_stencil_heap_size: info.depth_stencil_heap_size,
-        });
+        })?;
 
         // validate saved window rect is visible on a connected monitor and clamp to keep title bar accessible
         let monitors = app.enumerate_display_monitors();
@@ -312,7 +421,8 @@ impl<D, A> Client<D, A> where D: gfx::Device, A: os::App, D::RenderPipeline: gfx
             time: Time::new(),
             delta_history: VecDeque::new(),
             instance_name: info.name,
-            status_bar_height: STATUS_BAR_HEIGHT
+            status_bar_height: STATUS_BAR_HEIGHT,
+            show_build_diagnostics: false
         };
 
         // automatically load plugins from prev session
@@ -520,47 +630,47 @@ impl<D, A> Client<D, A> where D: gfx::Device, A: os::App, D::RenderPipeline: gfx
         self.swap_chain.swap(&mut self.device);
     }
 
-    /// This assumes you pass the path to a `Cargo.toml` for a `dylib` which you want to load dynamically
+    /// Loads the plugin `name` located at `path`, which can be the plugin directory containing a `Cargo.toml` for a
+    /// `dylib` or a file inside it. Relative paths are relative to the hotline root directory. The plugin directory can
+    /// contain a `plugin::MANIFEST_FILE` to configure the cargo package, profile and files to watch, see `PluginManifest`
     /// The lib can implement the `hotline_plugin!` and `Plugin` trait, but that is not required
     /// You can also just load libs and use `lib.get_symbol` to find custom callable code for other plugins.
-    pub fn add_plugin_lib(&mut self, name: &str, _path: &str) {
-        let abs_path = super::get_data_path("../..");
+    pub fn add_plugin_lib(&mut self, name: &str, path: &str) {
+        let hotline_path = super::get_data_path("../..");
+        let plugin_dir = plugin::resolve_plugin_dir(&hotline_path, name, path);
 
-        let lib_path = PathBuf::from(abs_path.to_string())
-            .join("target")
-            .join(crate::get_config_name())
-            .to_str().unwrap().to_string();
+        let manifest = plugin::PluginManifest::load(&plugin_dir).and_then(|manifest| {
+            manifest.check_hotline_version()?;
+            Ok(manifest)
+        });
+        let manifest = match manifest {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                println!("hotline_rs::client:: [error] failed to load plugin {}: {}", name, err.msg);
+                return;
+            }
+        };
 
-        let src_path = PathBuf::from(abs_path.to_string())
-            .join("plugins")
-            .join(name)
-            .join("src")
-            .join("lib.rs")
-            .to_str().unwrap().to_string();
+        let name = manifest.name.to_string();
+        let lib_filepath = manifest.get_lib_filepath();
+        let lib_path = manifest.get_lib_dir().to_str().unwrap().to_string();
 
         let plugin = PluginReloadResponder {
             name: name.to_string(),
-            path: abs_path.to_string(),
-            output_filepath: lib_path.to_string(),
-            files: vec![
-                src_path
-            ],
+            path: manifest.get_workspace_dir().to_str().unwrap().to_string(),
+            output_filepath: lib_filepath.to_str().unwrap().to_string(),
+            files: Vec::new(),
+            manifest: manifest.clone(),
+            diagnostics: Vec::new()
         };
 
-        #[cfg(target_os = "windows")]
-        let lib_file = format!("{}.dll", name);
-        #[cfg(target_os = "macos")]
-        let lib_file = format!("lib{}.dylib", name);
-        #[cfg(target_os = "linux")]
-        let lib_file = format!("lib{}.so", name);
-
-        if !std::path::Path::new(&lib_path).join(&lib_file).exists() {
-            println!("hotline_rs::client:: plugin not found: {}/{}", lib_path, lib_file);
+        if !lib_filepath.exists() {
+            println!("hotline_rs::client:: [error] plugin not found: {}", lib_filepath.display());
             return;
         }
 
         println!("hotline_rs::client:: loading plugin: {}/{}", lib_path, name);
-        let lib = hot_lib_reloader::LibReloader::new(&lib_path, name, None).unwrap();
+        let lib = hot_lib_reloader::LibReloader::new(&lib_path, manifest.get_lib_name(), None).unwrap();
         unsafe {
             // create instance if it is a Plugin trait
             let create = lib.get_symbol::<unsafe extern "C" fn() -> *mut core::ffi::c_void>("create".as_bytes());
@@ -579,23 +689,30 @@ impl<D, A> Client<D, A> where D: gfx::Device, A: os::App, D::RenderPipeline: gfx
                 name: name.to_string(),
                 instance,
                 reloader: Reloader::create(Box::new(plugin)),
-                state: PluginState::Setup
+                state: PluginState::Setup,
+                dependencies: get_plugin_dependencies(&lib, &manifest),
+                manifest: manifest.clone(),
+                saved_state: None
             });
             self.libs.insert(name.to_string(), lib);
         }
 
+        // dependencies must be setup first
+        sort_plugins(&mut self.plugins);
+
         // Track the plugin for auto re-loading
         if self.user_config.plugins.is_none() {
             self.user_config.plugins = Some(HashMap::new());
         }
 
         // plugins inside the main repro can have the abs path truncated so they are portable
-        let hotline_path = super::get_data_path("../..").replace('\\', "/");
-        let path = abs_path.replace(&hotline_path, "").replace('\\', "/");
+        let hotline_path = hotline_path.replace('\\', "/") + "/";
+        let path = manifest.dir.replace('\\', "/");
+        let path = path.strip_prefix(&hotline_path).unwrap_or(&path).to_string();
 
         if let Some(plugin_info) = &mut self.user_config.plugins {
-            if plugin_info.contains_key(name) {
-                plugin_info.remove(name);
+            if plugin_info.contains_key(&name) {
+                plugin_info.remove(&name);
             }
             plugin_info.insert(name.to_string(), PluginInfo { path });
         }
@@ -610,13 +727,12 @@ impl<D, A> Client<D, A> where D: gfx::Device, A: os::App, D::RenderPipeline: gfx
             if self.imgui.begin_menu("File") {
                 // allow us to add plugins from files (libs)
                 if self.imgui.menu_item("Open") {
-                    let file = A::open_file_dialog(os::OpenFileDialogFlags::FILES, vec![".toml"]);
+                    let file = A::open_file_dialog(os::OpenFileDialogFlags::FILES, vec![".toml", ".json"]);
                     if let Ok(file) = file {
                         if !file.is_empty() {
-                            // add plugin from dll
+                            // add plugin from dll, the name is taken from the manifest or the plugin directory
                             let plugin_path = PathBuf::from(file[0].to_string());
                             let plugin_name = plugin_path.parent().unwrap().file_name().unwrap();
-                            let plugin_path = plugin_path.parent().unwrap().parent().unwrap();
                             self.add_plugin_lib(plugin_name.to_str().unwrap(), plugin_path.to_str().unwrap());
                         }
                     }
@@ -654,6 +770,10 @@ impl<D, A> Client<D, A> where D: gfx::Device, A: os::App, D::RenderPipeline: gfx
                         self.imgui.end_menu();
                     }
                 }
+                self.imgui.separator();
+                if self.imgui.menu_item("Build Diagnostics") {
+                    self.show_build_diagnostics = !self.show_build_diagnostics;
+                }
                 self.imgui.end_menu();
             }
 
@@ -744,11 +864,56 @@ impl<D, A> Client<D, A> where D: gfx::Device, A: os::App, D::RenderPipeline: gfx
                 col = vec4f(1.0, 0.0, 0.0, 1.0);
             }
 
+            // failed builds block reloading until fixed
+            if self.plugins.iter().any(|p| p.reloader.is_build_failed()) {
+                hot_name += " (build failed)";
+            }
+
             let hot_text = format!("{} {}", hot_name, font_awesome::strs::FIRE);
             self.imgui.right_align(self.imgui.calc_text_size(&hot_text).0 + 10.0);
             self.imgui.colour_text(&hot_text, col);
         }
         self.imgui.end();
+
+        self.build_diagnostics_ui();
+    }
+
+    /// Internal core-ui function, displays the compiler diagnostics from the last build of each plugin. The window
+    /// is opened automatically while a plugin build has failed, because the plugin will not reload until fixed
+    fn build_diagnostics_ui(&mut self) {
+        let build_failed = self.plugins.iter().any(|p| p.reloader.is_build_failed());
+        if !self.show_build_diagnostics && !build_failed {
+            return;
+        }
+
+        let mut open = true;
+        if self.imgui.begin("build diagnostics", &mut open, imgui::WindowFlags::ALWAYS_HORIZONTAL_SCROLLBAR) {
+            for plugin in &self.plugins {
+                let diagnostics = plugin.reloader.get_diagnostics();
+                if plugin.reloader.is_build_failed() {
+                    self.imgui.colour_text(&format!("{}: build failed", plugin.name), vec4f(1.0, 0.3, 0.3, 1.0));
+                }
+                else {
+                    self.imgui.text(&plugin.name);
+                }
+                self.imgui.separator();
+                for diagnostic in diagnostics {
+                    // imgui text is printf formatted
+                    let text = diagnostic.to_string().replace('%', "%%");
+                    match diagnostic.level {
+                        reloader::DiagnosticLevel::Error => self.imgui.colour_text(&text, vec4f(1.0, 0.3, 0.3, 1.0)),
+                        reloader::DiagnosticLevel::Warning => self.imgui.colour_text(&text, vec4f(1.0, 0.8, 0.3, 1.0)),
+                        _ => self.imgui.text(&text)
+                    }
+                }
+                self.imgui.separator();
+            }
+        }
+        self.imgui.end();
+
+        if !open {
+            self.show_build_diagnostics = false;
+        }
     }
 
     /// Internal plugin yupdate function process reloads, setups and updates of hooked in plugins
@@ -772,25 +937,37 @@ impl<D, A> Client<D, A> where D: gfx::Device, A: os::App, D::RenderPipeline: gfx
         let mut reload = false;
         for plugin in &mut plugins {
             if plugin.reloader.check_for_reload() == reloader::ReloadState::Available || plugin.state == PluginState::Reload {
-                    self.swap_chain.wait_for_last_frame();
                     reload = true;
                     plugin.state = PluginState::Reload;
-                    break;
             }
         }
 
-        // if we require a reload, we also re-setup all the other plugins
-        // this could be configured to only re-setup necessary plugins that are dependent
         if reload {
-            for plugin in &mut plugins {
-                if plugin.state == PluginState::None {
+            self.swap_chain.wait_for_last_frame();
+        }
+
+        // any plugins which are reloading, setting up or unloading also require their dependents to re-setup
+        let changed = plugins.iter().map(|p| p.state != PluginState::None).collect::<Vec<bool>>();
+        if changed.contains(&true) {
+            let resetup = plugin::get_plugins_to_resetup(&get_plugin_dependency_infos(&plugins), &changed);
+            for (plugin, resetup) in plugins.iter_mut().zip(resetup) {
+                if resetup && plugin.state == PluginState::None {
                     plugin.state = PluginState::Setup
                 }
             }
         }
 
+        // save the state of reloading plugins before unloading, to hand-off to the new instances
+        for plugin in &mut plugins {
+            if plugin.state == PluginState::Reload {
+                let lib = self.libs.get(&plugin.name).expect("hotline::client: lib missing for plugin");
+                plugin.saved_state = save_plugin_state(lib, plugin.instance);
+            }
+        }
+
         // perfrom unloads this will clean up memory, setup will be called again afterwards
-        for plugin in &plugins {
+        // dependents are unloaded before their dependencies
+        for plugin in plugins.iter().rev() {
             if plugin.state != PluginState::None {
                 unsafe {
                     let lib = self.libs.get(&plugin.name).expect("hotline::client: lib missing for plugin");
@@ -846,13 +1023,29 @@ impl<D, A> Client<D, A> where D: gfx::Device, A: os::App, D::RenderPipeline: gfx
                     let create = lib.get_symbol::<unsafe extern "C" fn() -> *mut core::ffi::c_void>("create".as_bytes());
                     if let Ok(create_fn) = create {
                         plugin.instance = create_fn();
+
+                        // restore saved state, if it fails reset the instance to a fresh one
+                        if let Some(saved_state) = plugin.saved_state.take() {
+                            if !restore_plugin_state(lib, &plugin.name, plugin.instance, &saved_state) {
+                                println!("hotline_rs::client:: plugin: {} failed to restore state, starting fresh", plugin.name);
+                                reset_plugin_state(lib, plugin.instance);
+                            }
+                        }
                     }
                 }
+                plugin.saved_state = None;
+
                 // after reload, setup everything again
                 plugin.state = PluginState::Setup;
+                plugin.dependencies = get_plugin_dependencies(lib, &plugin.manifest);
             }
         }
 
+        // dependencies may have changed after reloading, setup happens in dependency order
+        if reload {
+            sort_plugins(&mut plugins);
+        }
+
         // setup
         for plugin in &plugins {
             let lib = self.libs.get(&plugin.name).expect("hotline::client: lib missing for plugin");
@@ -888,7 +1081,7 @@ impl<D, A> Client<D, A> where D: gfx::Device, A: os::App, D::RenderPipeline: gfx
     /// Unloads all plugins and drops all mem
     fn unload(mut self) {
         let plugins = std::mem::take(&mut self.plugins);
-        for plugin in &plugins {
+        for plugin in plugins.iter().rev() {
             unsafe {
                 let lib = self.libs.get(&plugin.name).expect("hotline::client: lib missing for plugin");
                 let unload = lib.get_symbol::<unsafe extern "C" fn(*mut Self, PluginInstance)>("unload".as_bytes());
@@ -936,6 +1129,34 @@ impl<D, A> Client<D, A> where D: gfx::Device, A: os::App, D::RenderPipeline: gfx
         }
     }
 
+    /// Loads every compiled pmfx in `data/shaders`, builds the render graph `graph_name` and returns it in execute order
+    /// with the auto-generated barriers. Used by the `client --dump-render-graph` command line to inspect a graph
+    pub fn export_render_graph(mut self, graph_name: &str) -> Result<pmfx::RenderGraphExport, super::Error> {
+        let shaders_path = super::get_data_path("shaders");
+        let mut folders = std::fs::read_dir(&shaders_path)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .map(|name| path.join(name).with_extension("json").exists())
+                    .unwrap_or(false)
+            })
+            .collect::<Vec<_>>();
+        folders.sort();
+
+        for folder in folders {
+            self.pmfx.load(folder.to_str().unwrap())?;
+        }
+
+        self.pmfx.create_render_graph(&mut self.device, graph_name)?;
+        let export = self.pmfx.export_render_graph();
+
+        self.swap_chain.wait_for_last_frame();
+        self.unload();
+
+        Ok(export)
+    }
+
     /// Very simple run loop which can take control of your application, you could roll your own
     pub fn run(mut self) -> Result<(), super::Error> {
         while self.app.run() {
@@ -1057,7 +1278,7 @@ impl<D, A> Client<D, A> where D: gfx::Device, A: os::App, D::RenderPipeline: gfx
                     read_end: usize::MAX
                 })?;
 
-                let output_dir = "target/test_output";
+                let output_dir = super::image_diff::TEST_OUTPUT_DIR;
                 if !std::path::PathBuf::from(output_dir.to_string()).exists() {
                     std::fs::create_dir(output_dir)?;
                 }
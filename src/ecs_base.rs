@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 use std::ops::Deref;
 use std::ops::DerefMut;
 use std::collections::HashMap;
+use std::collections::HashSet;
 
 /// Schedule info can be filled out and passed to the `ecs` plugin to build a schedulre for a running demo
 pub struct ScheduleInfo {
@@ -32,7 +33,7 @@ impl Default for ScheduleInfo {
 }
 
 /// Serialisable camera info
-#[derive(Serialize, Deserialize, Clone, Copy)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub struct CameraInfo {
     pub camera_type: CameraType,
     pub pos: (f32, f32, f32),
@@ -79,7 +80,7 @@ bitflags! {
 }
 
 /// Seriablisable user info for maintaining state between reloads and sessions
-#[derive(Serialize, Deserialize, Default, Resource, Clone)]
+#[derive(Serialize, Deserialize, Default, Resource, Clone, PartialEq)]
 pub struct SessionInfo {
     /// The active running demo will be saved between sessions
     pub active_demo: String,
@@ -151,6 +152,9 @@ hotline_ecs!(Component, TextureInstance, u32);
 hotline_ecs!(Component, TimeComponent, f32);
 hotline_ecs!(Component, CommandSignatureComponent, gfx_platform::CommandSignature);
 hotline_ecs!(Component, BLASComponent, gfx_platform::RaytracingBLAS);
+hotline_ecs!(Component, MeshAsset, String);
+hotline_ecs!(Component, MaterialAsset, String);
+hotline_ecs!(Component, SceneSource, String);
 
 #[derive(Component)]
 pub struct InstanceBuffer {
@@ -217,14 +221,14 @@ pub struct Billboard;
 #[derive(Component)]
 pub struct CylindricalBillboard;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum LightType {
     Point,
     Spot,
     Directional
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum CameraType {
     None,
     Fly,
@@ -263,6 +267,95 @@ impl Default for LightComponent {
     }
 }
 
+//
+// Transform hierarchy
+//
+
+/// Filters entities with a full transform which have not yet been given a `LocalMatrix`
+type WithoutLocalMatrix = (With<Position>, With<Rotation>, With<Scale>, With<WorldMatrix>, Without<LocalMatrix>);
+
+/// Adds a `LocalMatrix` to entities with a `Position`, `Rotation`, `Scale` and `WorldMatrix` so they become part of
+/// the transform hierarchy. Entities with only a `WorldMatrix` keep the matrix they were spawned with
+pub fn insert_local_matrices(
+    mut commands: Commands,
+    query: Query<Entity, WithoutLocalMatrix>) {
+    for entity in &query {
+        commands.entity(entity).insert(LocalMatrix(Mat34f::identity()));
+    }
+}
+
+/// Bakes a `LocalMatrix` from position, rotation and scale
+pub fn update_local_matrices(
+    mut query: Query<(&Position, &Rotation, &Scale, &mut LocalMatrix)>) {
+    for (position, rotation, scale, mut local_matrix) in &mut query {
+        let translate = Mat34f::from_translation(position.0);
+        let rotate = Mat34f::from(rotation.0);
+        let scale = Mat34f::from_scale(scale.0);
+        local_matrix.0 = translate * rotate * scale;
+    }
+}
+
+/// Propagates `WorldMatrix` from the roots of the hierarchy down to the leaves so parents are always updated before
+/// their children. An entity is a root if it has no `Parent`, or its parent has been despawned or has no `WorldMatrix`
+/// (ie. the `InstanceBatch` of an instance), parents with a `WorldMatrix` but no `LocalMatrix` are not updated and
+/// their matrix is used as is. The hierarchy is walked from scratch each update so reparenting takes effect
+/// immediately, entities which are part of a parent cycle fall back to their `LocalMatrix`
+pub fn propagate_world_matrices(
+    nodes: Query<(Entity, &LocalMatrix, Option<&Parent>), With<WorldMatrix>>,
+    mut world_matrices: Query<&mut WorldMatrix>) {
+    // find the children of each node and the roots along with the matrix of their parent if they have one
+    let mut children: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    let mut stack = Vec::new();
+    for (entity, _, parent) in &nodes {
+        match parent.map(|parent| parent.0).filter(|parent| *parent != entity) {
+            Some(parent) if nodes.contains(parent) => {
+                children.entry(parent).or_default().push(entity);
+            }
+            Some(parent) => {
+                stack.push((entity, world_matrices.get(parent).ok().map(|world_matrix| world_matrix.0)));
+            }
+            None => {
+                stack.push((entity, None));
+            }
+        }
+    }
+
+    // depth first from the roots
+    let mut visited = HashSet::new();
+    while let Some((entity, parent_matrix)) = stack.pop() {
+        visited.insert(entity);
+        if let Ok((_, local_matrix, _)) = nodes.get(entity) {
+            let world = if let Some(parent_matrix) = parent_matrix {
+                parent_matrix * local_matrix.0
+            }
+            else {
+                local_matrix.0
+            };
+            if let Ok(mut world_matrix) = world_matrices.get_mut(entity) {
+                world_matrix.0 = world;
+            }
+            if let Some(entity_children) = children.get(&entity) {
+                stack.extend(entity_children.iter().map(|child| (*child, Some(world))));
+            }
+        }
+    }
+
+    // cycles can not be reached from a root
+    for (entity, local_matrix, _) in &nodes {
+        if !visited.contains(&entity) {
+            if let Ok(mut world_matrix) = world_matrices.get_mut(entity) {
+                world_matrix.0 = local_matrix.0;
+            }
+        }
+    }
+}
+
+/// Returns the transform hierarchy systems chained in order, these need to run after anything which moves entities
+/// and before anything which reads `WorldMatrix`
+pub fn transform_hierarchy_systems() -> bevy_ecs::schedule::SystemConfigs {
+    (insert_local_matrices, update_local_matrices, propagate_world_matrices).chain()
+}
+
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
 pub enum SystemSets {
     Setup,
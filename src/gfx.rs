@@ -1,6 +1,9 @@
 // A null / stubbed implementation
 pub mod null;
 
+/// Software rasteriser used by the null backend to execute draw calls on the CPU.
+pub mod cpu;
+
 /// Implemets this interface with a Direct3D12 backend.
 #[cfg(target_os = "windows")]
 pub mod d3d12;
@@ -9,6 +12,10 @@ pub mod d3d12;
 #[cfg(target_os = "macos")]
 pub mod mtl;
 
+/// Implements this interface with a Vulkan 1.3 backend, enabled with the `vulkan` feature.
+#[cfg(feature = "vulkan")]
+pub mod vk;
+
 use crate::os;
 use std::any::Any;
 use serde::{Deserialize, Serialize};
@@ -268,6 +275,7 @@ pub enum Format {
     BGRX8n,
     BGRA8nSRGB,
     BGRX8nSRGB,
+    RGBA16n,
     RGBA16u,
     RGBA16i,
     RGBA16f,
@@ -355,6 +363,22 @@ pub struct TextureHeapInfo<'stack, D: Device> {
     pub depth_stencil: Option<&'stack mut D::Heap>,
 }
 
+/// Hardware queues a `Device` can submit work to, `Graphics` is always available and backends without
+/// dedicated hardware queues map `Compute` and `Copy` onto the graphics queue
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum QueueType {
+    /// Supports all commands, this is the queue `Device::execute` submits to
+    #[default]
+    #[serde(alias = "graphics")]
+    Graphics,
+    /// Supports compute and copy commands and can run asynchronously alongside the graphics queue
+    #[serde(alias = "compute", alias = "async_compute")]
+    Compute,
+    /// Supports copy commands only
+    #[serde(alias = "copy")]
+    Copy
+}
+
 /// Information to create a query heap.
 pub struct QueryHeapInfo {
     /// ie: Timestamp, Occlusion, PipelineStatistics
@@ -436,7 +460,7 @@ pub struct ShaderCompileInfo {
 }
 
 /// The stage to which a shader will bind itself.
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum ShaderType {
     Vertex,
     Fragment,
@@ -632,7 +656,7 @@ pub struct DescriptorBinding {
 }
 
 /// Describes the type of descriptor binding to create.
-#[derive(Clone, Copy, Serialize, Deserialize, Hash, PartialEq, Eq)]
+#[derive(Clone, Copy, Serialize, Deserialize, Hash, PartialEq, Eq, Debug)]
 pub enum DescriptorType {
     /// Used for textures or structured buffers.
     ShaderResource,
@@ -1236,6 +1260,7 @@ pub struct UnmapInfo {
 }
 
 /// Enum to differentiate between render and compute pipelines but also still work on them generically
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum PipelineType {
     Render,
     Compute
@@ -1401,9 +1426,9 @@ pub enum Resource<'stack, D: Device> {
     Buffer(&'stack D::Buffer),
 }
 
-/// A GPU device is used to create GPU resources, the device also contains a single a single command queue
-/// to which all command buffers will submitted and executed each frame. Default heaps for shader resources,
-/// render targets and depth stencils are also provided
+/// A GPU device is used to create GPU resources, the device also contains a graphics command queue
+/// to which all command buffers will submitted and executed each frame, and compute / copy queues for async work.
+/// Default heaps for shader resources, render targets and depth stencils are also provided
 pub trait Device: 'static + Send + Sync + Sized + Any + Clone {
     type SwapChain: SwapChain<Self>;
     type CmdBuf: CmdBuf<Self>;
@@ -1421,8 +1446,9 @@ pub trait Device: 'static + Send + Sync + Sized + Any + Clone {
     type RaytracingShaderBindingTable: RaytracingShaderBindingTable<Self>;
     type RaytracingBLAS: RaytracingBLAS<Self>;
     type RaytracingTLAS: RaytracingTLAS<Self>;
-    /// Create a new GPU `Device` from `Device Info`
-    fn create(info: &DeviceInfo) -> Self;
+    type Fence: Fence<Self>;
+    /// Create a new GPU `Device` from `Device Info`, returns an error if no suitable device or driver is available
+    fn create(info: &DeviceInfo) -> Result<Self, Error>;
     /// Create a new resource `Heap` from `HeapInfo`
     fn create_heap(&mut self, info: &HeapInfo) -> Self::Heap;
     /// Create a new `QueryHeap` from `QueryHeapInfo`
@@ -1436,6 +1462,10 @@ pub trait Device: 'static + Send + Sync + Sized + Any + Clone {
     /// Create a new `CmdBuf` with `num_buffers` internal buffers, the buffers can be swapped and syncronised
     /// with a new `SwapChain` to allow in-flight gpu/cpu overlapped prodicer consumers
     fn create_cmd_buf(&self, num_buffers: u32) -> Self::CmdBuf;
+    /// Create a new `CmdBuf` the same as `create_cmd_buf` which can be submitted to the specified `queue`
+    fn create_cmd_buf_for_queue(&self, num_buffers: u32, queue: QueueType) -> Self::CmdBuf;
+    /// Create a new `Fence` with an initial value of 0 used to syncronise work between queues, or the CPU and GPU
+    fn create_fence(&self) -> Result<Self::Fence, Error>;
     /// Create a new `Shader` from `ShaderInfo`
     fn create_shader<T: Sized>(&self, info: &ShaderInfo, src: &[T]) -> Result<Self::Shader, Error>;
     /// Create a new `Buffer` from `BufferInfo` with any resource views allocated on the devices `shader_heap`
@@ -1487,6 +1517,16 @@ pub trait Device: 'static + Send + Sync + Sized + Any + Clone {
         resource: Resource<Self>,
         heap: &mut Self::Heap
     ) -> Result<usize, Error>;
+    /// Writes the shader resource view of `texture` into the shader resource view slot of `target` within `heap` and
+    /// exchanges the srv indices of the two textures, so `texture` can replace `target` while bindless indices which
+    /// refer to `target` remain valid. `target` takes the slot originally allocated for `texture` and releases it when
+    /// dropped. The GPU must not be accessing `target` when this is called
+    fn replace_texture_srv(
+        &mut self,
+        target: &mut Self::Texture,
+        texture: &mut Self::Texture,
+        heap: &mut Self::Heap
+    ) -> Result<(), Error>;
     /// Create a new render pipeline state object from the supplied `RenderPipelineInfo`
     fn create_render_pipeline(
         &self,
@@ -1533,6 +1573,14 @@ pub trait Device: 'static + Send + Sync + Sized + Any + Clone {
     ) -> Result<Self::CommandSignature, super::Error>;
     /// Execute a command buffer on the internal device command queue which still hold references
     fn execute(&mut self, cmd: &Self::CmdBuf);
+    /// Execute a command buffer on the specified `queue`, the command buffer must have been created for the same queue
+    /// with `create_cmd_buf_for_queue`
+    fn execute_on_queue(&mut self, queue: QueueType, cmd: &Self::CmdBuf);
+    /// Signal `fence` with `value` on `queue` once all work previously submitted to `queue` has completed
+    fn signal_fence(&mut self, queue: QueueType, fence: &Self::Fence, value: u64);
+    /// Make `queue` wait on the GPU until `fence` has reached `value` before executing any further submitted work,
+    /// the value must be signalled by a previous call to `signal_fence` on another queue
+    fn wait_fence(&mut self, queue: QueueType, fence: &Self::Fence, value: u64);
     /// Borrow the internally managed shader resource heap the device creates, for binding buffers / textures in shaders
     fn get_shader_heap(&self) -> &Self::Heap;
     /// Mutably borrow the internally managed shader resource heap the device creates, for binding buffers / textures in shaders
@@ -1789,6 +1837,15 @@ pub trait Heap<D: Device>: Send + Sync {
     fn get_heap_id(&self) -> u16;
 }
 
+/// A monotonically increasing 64-bit fence value which is signalled and waited on by the GPU queues with
+/// `Device::signal_fence` and `Device::wait_fence`, the CPU can query or wait for values to complete
+pub trait Fence<D: Device>: Send + Sync {
+    /// Returns the last value the GPU has signalled
+    fn get_completed_value(&self) -> u64;
+    /// Blocks the calling thread until the GPU has signalled `value`
+    fn wait_for_value(&self, value: u64);
+}
+
 /// An opaque query heap type, use to create queries
 pub trait QueryHeap<D: Device>: Send + Sync {
     /// Reset queries at the start of the frame, each query requested will bump the allocation index
@@ -1867,6 +1924,7 @@ pub const fn block_size_for_format(format: Format) -> u32 {
         Format::RGB32u => 12,
         Format::RGB32i => 12,
         Format::RGB32f => 12,
+        Format::RGBA16n => 8,
         Format::RGBA16u => 8,
         Format::RGBA16i => 8,
         Format::RGBA16f => 8,
@@ -1931,6 +1989,7 @@ pub const fn components_for_format(format: Format) -> u32 {
         Format::RGB32u => 3,
         Format::RGB32i => 3,
         Format::RGB32f => 3,
+        Format::RGBA16n => 4,
         Format::RGBA16u => 4,
         Format::RGBA16i => 4,
         Format::RGBA16f => 4,
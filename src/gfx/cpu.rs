@@ -0,0 +1,948 @@
+//! A software rasteriser used by the `null` backend to execute draw calls on the CPU so it can produce pixels for
+//! golden image tests without a GPU. Shaders are opaque byte code blobs, so instead of interpreting them a fixed
+//! set of built-in `Program`s replicates the hlsl shipped in `shaders/`; the program is selected from the pipeline
+//! input layout and push constants when a `RenderPipeline` is created.
+//! Rasterisation follows d3d conventions: pixel centre sampling, top-left fill rule, clockwise front faces
+//! (unless `front_ccw`), clip space z in 0-1 and perspective correct interpolation of varyings.
+
+use super::Format;
+use super::TextureInfo;
+use super::InputLayout;
+use super::InputSlotClass;
+use super::PipelineLayout;
+use super::RasterInfo;
+use super::DepthStencilInfo;
+use super::DepthWriteMask;
+use super::StencilInfo;
+use super::StencilOp;
+use super::RenderTargetBlendInfo;
+use super::BlendFactor;
+use super::BlendOp;
+use super::WriteMask;
+use super::ComparisonFunc;
+use super::Topology;
+use super::FillMode;
+use super::CullMode;
+use super::Viewport;
+use super::ScissorRect;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Number of 4 component varyings passed from vertex to pixel programs
+const NUM_VARYINGS: usize = 3;
+
+/// Built-in programs which replicate the vertex and pixel shaders in `shaders/`
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Program {
+    /// `imdraw.hlsl` vs_2d + ps_main: float2 position, float4 colour transformed by `view_push_constants`
+    ImDraw2D,
+    /// `imdraw.hlsl` vs_3d + ps_main: float3 position, float4 colour transformed by `view_push_constants`
+    ImDraw3D,
+    /// `imdraw.hlsl` vs_blit + ps_blit: float2 position, float2 texcoord loading from texture t1
+    ImDrawBlit,
+    /// `imgui.hlsl` vs_main + ps_main: float2 position, float2 uv, rgba8 colour modulated by texture t0
+    ImGui,
+    /// `draw.hlsl` vs_mesh_identity + ps_checkerboard: mesh vertices transformed by `view_push_constants` only
+    MeshIdentity,
+    /// `draw.hlsl` vs_mesh + ps_checkerboard: mesh vertices transformed by `view_push_constants` and `draw_push_constants`
+    Mesh,
+    /// `draw.hlsl` vs_mesh + ps_wireframe: constant colour wireframe overlay
+    MeshWireframe,
+    /// The input layout did not match a built-in program, draws are skipped
+    Unknown,
+}
+
+/// Render state captured from `RenderPipelineInfo` required to rasterise draws
+#[derive(Clone)]
+pub struct PipelineState {
+    pub program: Program,
+    pub input_layout: InputLayout,
+    pub raster_info: RasterInfo,
+    pub depth_stencil_info: DepthStencilInfo,
+    pub blend: Vec<RenderTargetBlendInfo>,
+    pub topology: Topology,
+}
+
+/// A render target or depth stencil texture locked for writing
+pub struct Target<'a> {
+    pub data: &'a mut [u8],
+    pub info: TextureInfo,
+    pub slice: usize,
+}
+
+/// Bound state gathered while executing a command buffer
+#[derive(Clone, Default)]
+pub struct DrawState {
+    pub pipeline: Option<Arc<PipelineState>>,
+    /// Vertex buffer snapshot and stride keyed by slot
+    pub vertex_buffers: HashMap<u32, (Arc<Vec<u8>>, usize)>,
+    /// Index buffer snapshot and index format
+    pub index_buffer: Option<(Arc<Vec<u8>>, Format)>,
+    /// Push constants as 32-bit values keyed by register
+    pub constants: HashMap<u32, Vec<u32>>,
+    /// Shader resource textures keyed by register, contents are snapshot when bound
+    pub textures: HashMap<u32, (Arc<Vec<u8>>, TextureInfo)>,
+    pub viewport: Option<Viewport>,
+    pub scissor: Option<ScissorRect>,
+}
+
+/// Arguments for a single `draw_instanced` or `draw_indexed_instanced` call
+pub struct DrawArgs {
+    pub vertex_count: u32,
+    pub instance_count: u32,
+    pub start_vertex: u32,
+    pub start_instance: u32,
+    /// `Some((start_index, base_vertex))` to fetch vertices through the bound index buffer
+    pub indexed: Option<(u32, i32)>,
+}
+
+/// Post vertex program output
+#[derive(Copy, Clone)]
+struct Vertex {
+    pos: [f32; 4],
+    vary: [[f32; 4]; NUM_VARYINGS],
+}
+
+/// Vertex in screen space after perspective divide, varyings are pre-multiplied by `inv_w`
+#[derive(Copy, Clone)]
+struct ScreenVertex {
+    x: f32,
+    y: f32,
+    z: f32,
+    inv_w: f32,
+    vary: [[f32; 4]; NUM_VARYINGS],
+}
+
+impl Program {
+    /// Selects the built-in program which matches the vertex input layout and push constants of a pipeline
+    pub fn from_pipeline(input_layout: &InputLayout, pipeline_layout: &PipelineLayout, raster_info: &RasterInfo) -> Program {
+        let find = |semantic: &str, index: u32| {
+            input_layout.iter().find(|e| e.semantic == semantic && e.index == index).map(|e| e.format)
+        };
+        let has_draw_constants = pipeline_layout.push_constants.as_ref()
+            .map(|pc| pc.iter().any(|c| c.shader_register == 1))
+            .unwrap_or(false);
+        match (find("POSITION", 0), find("TEXCOORD", 0), find("COLOR", 0)) {
+            (Some(Format::RG32f), Some(Format::RG32f), Some(_)) => Program::ImGui,
+            (Some(Format::RG32f), Some(Format::RGBA32f), None) => Program::ImDraw2D,
+            (Some(Format::RG32f), Some(Format::RG32f), None) => Program::ImDrawBlit,
+            (Some(Format::RGB32f), Some(Format::RGBA32f), None) => Program::ImDraw3D,
+            (Some(Format::RGB32f), Some(Format::RG32f), None) => {
+                if !has_draw_constants {
+                    Program::MeshIdentity
+                }
+                else if matches!(raster_info.fill_mode, FillMode::Wireframe) {
+                    Program::MeshWireframe
+                }
+                else {
+                    Program::Mesh
+                }
+            }
+            _ => Program::Unknown
+        }
+    }
+}
+
+/// Returns the byte offset of array `slice` within a texture's storage, data is laid out array layer major
+pub fn texture_slice_offset(info: &TextureInfo, slice: usize) -> usize {
+    let layer_size = super::size_for_format_mipped(
+        info.format, info.width, info.height, info.depth, 1, info.mip_levels) as usize;
+    layer_size * slice
+}
+
+/// Converts a float to an IEEE 754 half float stored in a u16
+pub fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32 - 127 + 15;
+    let mantissa = bits & 0x7fffff;
+    if exp <= 0 {
+        sign
+    }
+    else if exp >= 31 {
+        sign | 0x7c00
+    }
+    else {
+        sign | ((exp as u16) << 10) | ((mantissa >> 13) as u16)
+    }
+}
+
+/// Converts an IEEE 754 half float stored in a u16 to a float
+fn f16_to_f32(value: u16) -> f32 {
+    let sign = ((value & 0x8000) as u32) << 16;
+    let exp = ((value >> 10) & 0x1f) as u32;
+    let mantissa = (value & 0x3ff) as u32;
+    if exp == 0 {
+        // zero and denormals
+        let v = mantissa as f32 / 1024.0 / 16384.0;
+        if sign != 0 { -v } else { v }
+    }
+    else if exp == 31 {
+        f32::from_bits(sign | 0x7f800000 | (mantissa << 13))
+    }
+    else {
+        f32::from_bits(sign | ((exp + 127 - 15) << 23) | (mantissa << 13))
+    }
+}
+
+fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 { v * 12.92 } else { 1.055 * v.powf(1.0 / 2.4) - 0.055 }
+}
+
+fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) }
+}
+
+fn is_srgb(format: Format) -> bool {
+    matches!(format, Format::RGBA8nSRGB | Format::BGRA8nSRGB | Format::BGRX8nSRGB)
+}
+
+/// Encodes a linear rgba colour into the texel representation of `format`, unsupported formats encode to zero
+pub fn encode_texel(format: Format, rgba: [f32; 4]) -> Vec<u8> {
+    let unorm8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+    let unorm16 = |v: f32| (v.clamp(0.0, 1.0) * 65535.0 + 0.5) as u16;
+    let rgba = if is_srgb(format) {
+        [linear_to_srgb(rgba[0]), linear_to_srgb(rgba[1]), linear_to_srgb(rgba[2]), rgba[3]]
+    }
+    else {
+        rgba
+    };
+    match format {
+        Format::RGBA8n | Format::RGBA8nSRGB | Format::RGBA8u => {
+            rgba.iter().map(|v| unorm8(*v)).collect()
+        }
+        Format::BGRA8n | Format::BGRA8nSRGB | Format::BGRX8n | Format::BGRX8nSRGB => {
+            vec![unorm8(rgba[2]), unorm8(rgba[1]), unorm8(rgba[0]), unorm8(rgba[3])]
+        }
+        Format::RGBA16n => {
+            rgba.iter().flat_map(|v| unorm16(*v).to_ne_bytes()).collect()
+        }
+        Format::RGBA16f => {
+            rgba.iter().flat_map(|v| f32_to_f16(*v).to_ne_bytes()).collect()
+        }
+        Format::RG16f => {
+            rgba[0..2].iter().flat_map(|v| f32_to_f16(*v).to_ne_bytes()).collect()
+        }
+        Format::R16f => {
+            f32_to_f16(rgba[0]).to_ne_bytes().to_vec()
+        }
+        Format::RGBA32f => {
+            rgba.iter().flat_map(|v| v.to_ne_bytes()).collect()
+        }
+        Format::RGB32f => {
+            rgba[0..3].iter().flat_map(|v| v.to_ne_bytes()).collect()
+        }
+        Format::RG32f => {
+            rgba[0..2].iter().flat_map(|v| v.to_ne_bytes()).collect()
+        }
+        Format::R32f => {
+            rgba[0].to_ne_bytes().to_vec()
+        }
+        _ => {
+            vec![0; super::block_size_for_format(format) as usize]
+        }
+    }
+}
+
+/// Decodes the texel `data` of `format` into a linear rgba colour, unsupported formats decode to zero
+pub fn decode_texel(format: Format, data: &[u8]) -> [f32; 4] {
+    let f32_at = |i: usize| f32::from_ne_bytes([data[i * 4], data[i * 4 + 1], data[i * 4 + 2], data[i * 4 + 3]]);
+    let f16_at = |i: usize| f16_to_f32(u16::from_ne_bytes([data[i * 2], data[i * 2 + 1]]));
+    let unorm8 = |i: usize| data[i] as f32 / 255.0;
+    let unorm16 = |i: usize| u16::from_ne_bytes([data[i * 2], data[i * 2 + 1]]) as f32 / 65535.0;
+    let rgba = match format {
+        Format::RGBA8n | Format::RGBA8nSRGB | Format::RGBA8u => [unorm8(0), unorm8(1), unorm8(2), unorm8(3)],
+        Format::BGRA8n | Format::BGRA8nSRGB => [unorm8(2), unorm8(1), unorm8(0), unorm8(3)],
+        Format::BGRX8n | Format::BGRX8nSRGB => [unorm8(2), unorm8(1), unorm8(0), 1.0],
+        Format::RGBA16n => [unorm16(0), unorm16(1), unorm16(2), unorm16(3)],
+        Format::RGBA16f => [f16_at(0), f16_at(1), f16_at(2), f16_at(3)],
+        Format::RG16f => [f16_at(0), f16_at(1), 0.0, 1.0],
+        Format::R16f => [f16_at(0), 0.0, 0.0, 1.0],
+        Format::RGBA32f => [f32_at(0), f32_at(1), f32_at(2), f32_at(3)],
+        Format::RGB32f => [f32_at(0), f32_at(1), f32_at(2), 1.0],
+        Format::RG32f => [f32_at(0), f32_at(1), 0.0, 1.0],
+        Format::R32f => [f32_at(0), 0.0, 0.0, 1.0],
+        _ => [0.0; 4]
+    };
+    if is_srgb(format) {
+        [srgb_to_linear(rgba[0]), srgb_to_linear(rgba[1]), srgb_to_linear(rgba[2]), rgba[3]]
+    }
+    else {
+        rgba
+    }
+}
+
+/// Encodes a depth and stencil value into the texel representation of a depth `format`
+pub fn encode_depth(format: Format, depth: f32, stencil: u8) -> Vec<u8> {
+    let mut texel = vec![0; super::block_size_for_format(format) as usize];
+    let depth = depth.clamp(0.0, 1.0);
+    match format {
+        Format::D32f => {
+            texel[0..4].copy_from_slice(&depth.to_ne_bytes());
+        }
+        Format::D32fS8X24u => {
+            texel[0..4].copy_from_slice(&depth.to_ne_bytes());
+            texel[4] = stencil;
+        }
+        Format::D24nS8u => {
+            let packed = ((depth * 16777215.0 + 0.5) as u32 & 0xffffff) | ((stencil as u32) << 24);
+            texel[0..4].copy_from_slice(&packed.to_ne_bytes());
+        }
+        Format::D16n => {
+            texel[0..2].copy_from_slice(&((depth * 65535.0 + 0.5) as u16).to_ne_bytes());
+        }
+        _ => ()
+    }
+    texel
+}
+
+/// Decodes a depth texel into (depth, stencil)
+pub fn decode_depth(format: Format, data: &[u8]) -> (f32, u8) {
+    match format {
+        Format::D32f => (f32::from_ne_bytes([data[0], data[1], data[2], data[3]]), 0),
+        Format::D32fS8X24u => (f32::from_ne_bytes([data[0], data[1], data[2], data[3]]), data[4]),
+        Format::D24nS8u => {
+            let packed = u32::from_ne_bytes([data[0], data[1], data[2], data[3]]);
+            ((packed & 0xffffff) as f32 / 16777215.0, (packed >> 24) as u8)
+        }
+        Format::D16n => (u16::from_ne_bytes([data[0], data[1]]) as f32 / 65535.0, 0),
+        _ => (0.0, 0)
+    }
+}
+
+/// Reads a vertex element of `format` from `data`, missing components default to (0, 0, 0, 1)
+fn decode_vertex_element(format: Format, data: &[u8]) -> [f32; 4] {
+    let f32_at = |i: usize| f32::from_ne_bytes([data[i * 4], data[i * 4 + 1], data[i * 4 + 2], data[i * 4 + 3]]);
+    let u32_at = |i: usize| u32::from_ne_bytes([data[i * 4], data[i * 4 + 1], data[i * 4 + 2], data[i * 4 + 3]]) as f32;
+    match format {
+        Format::R32f => [f32_at(0), 0.0, 0.0, 1.0],
+        Format::RG32f => [f32_at(0), f32_at(1), 0.0, 1.0],
+        Format::RGB32f => [f32_at(0), f32_at(1), f32_at(2), 1.0],
+        Format::RGBA32f => [f32_at(0), f32_at(1), f32_at(2), f32_at(3)],
+        Format::R32u => [u32_at(0), 0.0, 0.0, 1.0],
+        Format::RGBA32u => [u32_at(0), u32_at(1), u32_at(2), u32_at(3)],
+        _ => decode_texel(format, data)
+    }
+}
+
+/// Returns the push constants in `register` as floats, padded with zero to `count`
+fn constants_f32(state: &DrawState, register: u32, count: usize) -> Vec<f32> {
+    let mut values = vec![0.0; count];
+    if let Some(constants) = state.constants.get(&register) {
+        for (i, v) in constants.iter().take(count).enumerate() {
+            values[i] = f32::from_bits(*v);
+        }
+    }
+    values
+}
+
+/// mul(m, v) for a row major matrix in hlsl
+fn mul_mv(m: &[f32], v: [f32; 4]) -> [f32; 4] {
+    let mut r = [0.0; 4];
+    for (row, out) in r.iter_mut().enumerate() {
+        *out = (0..4).map(|c| m[row * 4 + c] * v[c]).sum();
+    }
+    r
+}
+
+/// mul(v, m) for a row major matrix in hlsl
+fn mul_vm(v: [f32; 4], m: &[f32]) -> [f32; 4] {
+    let mut r = [0.0; 4];
+    for (col, out) in r.iter_mut().enumerate() {
+        *out = (0..4).map(|row| v[row] * m[row * 4 + col]).sum();
+    }
+    r
+}
+
+fn normalize3(v: [f32; 4]) -> [f32; 4] {
+    let len = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if len > 0.0 {
+        [v[0] / len, v[1] / len, v[2] / len, 0.0]
+    }
+    else {
+        [0.0; 4]
+    }
+}
+
+/// Fetches vertex elements from the bound vertex buffers
+struct VertexFetch<'a> {
+    state: &'a DrawState,
+    layout: &'a InputLayout,
+}
+
+impl<'a> VertexFetch<'a> {
+    fn fetch(&self, semantic: &str, index: u32, vertex_id: u32, instance_id: u32) -> [f32; 4] {
+        let element = self.layout.iter().find(|e| e.semantic == semantic && e.index == index);
+        if let Some(element) = element {
+            if let Some((data, stride)) = self.state.vertex_buffers.get(&element.input_slot) {
+                let id = match element.input_slot_class {
+                    InputSlotClass::PerVertex => vertex_id,
+                    InputSlotClass::PerInstance => instance_id / element.step_rate.max(1)
+                };
+                let size = super::block_size_for_format(element.format) as usize;
+                let offset = id as usize * stride + element.aligned_byte_offset as usize;
+                if offset + size <= data.len() {
+                    return decode_vertex_element(element.format, &data[offset..offset + size]);
+                }
+            }
+        }
+        [0.0, 0.0, 0.0, 1.0]
+    }
+}
+
+/// Runs the vertex program for a single vertex
+fn run_vertex_program(program: Program, state: &DrawState, fetch: &VertexFetch, vertex_id: u32, instance_id: u32) -> Vertex {
+    let position = fetch.fetch("POSITION", 0, vertex_id, instance_id);
+    let mut out = Vertex {
+        pos: [0.0, 0.0, 0.0, 1.0],
+        vary: [[0.0; 4]; NUM_VARYINGS],
+    };
+    match program {
+        Program::ImDraw2D | Program::ImDraw3D => {
+            let projection_matrix = constants_f32(state, 0, 16);
+            let z = if program == Program::ImDraw2D { 0.0 } else { position[2] };
+            out.pos = mul_mv(&projection_matrix, [position[0], position[1], z, 1.0]);
+            out.vary[0] = fetch.fetch("TEXCOORD", 0, vertex_id, instance_id);
+        }
+        Program::ImDrawBlit => {
+            out.pos = [position[0], position[1], 0.0, 1.0];
+            out.vary[0] = fetch.fetch("TEXCOORD", 0, vertex_id, instance_id);
+        }
+        Program::ImGui => {
+            let projection_matrix = constants_f32(state, 0, 16);
+            out.pos = mul_vm([position[0], position[1], 0.0, 1.0], &projection_matrix);
+            out.vary[0] = fetch.fetch("COLOR", 0, vertex_id, instance_id);
+            out.vary[1] = fetch.fetch("TEXCOORD", 0, vertex_id, instance_id);
+        }
+        Program::MeshIdentity | Program::Mesh | Program::MeshWireframe => {
+            let view_projection_matrix = constants_f32(state, 0, 16);
+            let mut pos = [position[0], position[1], position[2], 1.0];
+            let mut normal = fetch.fetch("TEXCOORD", 1, vertex_id, instance_id);
+            let mut colour = [1.0; 4];
+            if program != Program::MeshIdentity {
+                // draw_push_constants: row_major float3x4 world_matrix, float4 material_colour
+                let draw = constants_f32(state, 1, 16);
+                let world = [&draw[0..4], &draw[4..8], &draw[8..12]];
+                let p = pos;
+                for row in 0..3 {
+                    pos[row] = (0..4).map(|c| world[row][c] * p[c]).sum();
+                }
+                let n = normal;
+                for row in 0..3 {
+                    normal[row] = (0..3).map(|c| world[row][c] * n[c]).sum();
+                }
+                normal = normalize3(normal);
+                colour = [draw[12], draw[13], draw[14], draw[15]];
+            }
+            out.pos = mul_mv(&view_projection_matrix, pos);
+            out.vary[0] = fetch.fetch("TEXCOORD", 0, vertex_id, instance_id);
+            out.vary[1] = normal;
+            out.vary[2] = colour;
+        }
+        Program::Unknown => ()
+    }
+    out
+}
+
+/// Loads a texel from mip 0 of the first array slice, coordinates are clamped to the texture bounds
+fn texture_load(texture: &(Arc<Vec<u8>>, TextureInfo), x: i64, y: i64) -> [f32; 4] {
+    let (data, info) = texture;
+    let x = x.clamp(0, info.width as i64 - 1) as u64;
+    let y = y.clamp(0, info.height as i64 - 1) as u64;
+    let block_size = super::block_size_for_format(info.format) as usize;
+    let offset = (y * info.width + x) as usize * block_size;
+    if offset + block_size <= data.len() {
+        decode_texel(info.format, &data[offset..offset + block_size])
+    }
+    else {
+        [0.0; 4]
+    }
+}
+
+/// Point samples a texture with wrapped uv coordinates
+fn texture_sample(texture: &(Arc<Vec<u8>>, TextureInfo), uv: [f32; 4]) -> [f32; 4] {
+    let info = &texture.1;
+    let u = uv[0] - uv[0].floor();
+    let v = uv[1] - uv[1].floor();
+    texture_load(texture, (u * info.width as f32) as i64, (v * info.height as f32) as i64)
+}
+
+/// Runs the pixel program for a single pixel, returns `None` if the pixel is discarded
+fn run_pixel_program(program: Program, state: &DrawState, vary: &[[f32; 4]; NUM_VARYINGS]) -> Option<[f32; 4]> {
+    match program {
+        Program::ImDraw2D | Program::ImDraw3D => Some(vary[0]),
+        Program::ImDrawBlit => {
+            let blit_dimension = constants_f32(state, 0, 2);
+            // blit_texture is t1 but bindings are made through a descriptor table starting at register 0
+            let texture = state.textures.get(&1).or_else(|| state.textures.get(&0))?;
+            Some(texture_load(texture, (vary[0][0] * blit_dimension[0]) as i64, (vary[0][1] * blit_dimension[1]) as i64))
+        }
+        Program::ImGui => {
+            let tex = state.textures.get(&0).map(|t| texture_sample(t, vary[1])).unwrap_or([1.0; 4]);
+            Some([vary[0][0] * tex[0], vary[0][1] * tex[1], vary[0][2] * tex[2], vary[0][3] * tex[3]])
+        }
+        Program::MeshIdentity | Program::Mesh => {
+            // ps_checkerboard
+            let normal = vary[1];
+            let mut output = [normal[0] * 0.5 + 0.5, normal[1] * 0.5 + 0.5, normal[2] * 0.5 + 0.5, 1.0];
+            let size = 8.0;
+            let ix = (vary[0][0] * size).trunc();
+            let iy = (vary[0][1] * size).trunc();
+            let rx = if ix % 2.0 == 0.0 { 0.0 } else { 1.0 };
+            let ry = if iy % 2.0 == 0.0 { 0.0 } else { 1.0 };
+            let rxy = if rx + ry > 1.0 { 0.0 } else { rx + ry };
+            let scale = if rxy < 0.001 { 0.66 } else { 1.0 };
+            for c in output.iter_mut().take(3) {
+                *c *= scale;
+            }
+            Some(output)
+        }
+        Program::MeshWireframe => Some([0.2, 0.2, 0.2, 1.0]),
+        Program::Unknown => None
+    }
+}
+
+fn compare(func: ComparisonFunc, src: f32, dst: f32) -> bool {
+    match func {
+        ComparisonFunc::Never => false,
+        ComparisonFunc::Less => src < dst,
+        ComparisonFunc::Equal => src == dst,
+        ComparisonFunc::LessEqual => src <= dst,
+        ComparisonFunc::Greater => src > dst,
+        ComparisonFunc::NotEqual => src != dst,
+        ComparisonFunc::GreaterEqual => src >= dst,
+        ComparisonFunc::Always => true,
+    }
+}
+
+fn stencil_op(op: StencilOp, value: u8, reference: u8) -> u8 {
+    match op {
+        StencilOp::Keep => value,
+        StencilOp::Zero => 0,
+        StencilOp::Replace => reference,
+        StencilOp::IncrSat => value.saturating_add(1),
+        StencilOp::DecrSat => value.saturating_sub(1),
+        StencilOp::Invert => !value,
+        StencilOp::Incr => value.wrapping_add(1),
+        StencilOp::Decr => value.wrapping_sub(1),
+    }
+}
+
+fn blend_factor(factor: BlendFactor, src: [f32; 4], dst: [f32; 4], c: usize) -> f32 {
+    match factor {
+        BlendFactor::Zero => 0.0,
+        BlendFactor::One => 1.0,
+        BlendFactor::SrcColour | BlendFactor::Src1Colour => src[c],
+        BlendFactor::InvSrcColour | BlendFactor::InvSrc1Colour => 1.0 - src[c],
+        BlendFactor::SrcAlpha | BlendFactor::Src1Alpha => src[3],
+        BlendFactor::InvSrcAlpha | BlendFactor::InvSrc1Alpha => 1.0 - src[3],
+        BlendFactor::DstAlpha => dst[3],
+        BlendFactor::InvDstAlpha => 1.0 - dst[3],
+        BlendFactor::DstColour => dst[c],
+        BlendFactor::InvDstColour => 1.0 - dst[c],
+        BlendFactor::SrcAlphaSat => if c == 3 { 1.0 } else { src[3].min(1.0 - dst[3]) },
+        // there is no api to set the blend factor so it is the default of 1
+        BlendFactor::BlendFactor => 1.0,
+        BlendFactor::InvBlendFactor => 0.0,
+    }
+}
+
+fn blend_op(op: BlendOp, src: f32, dst: f32) -> f32 {
+    match op {
+        BlendOp::Add => src + dst,
+        BlendOp::Subtract => src - dst,
+        BlendOp::RevSubtract => dst - src,
+        BlendOp::Min => src.min(dst),
+        BlendOp::Max => src.max(dst),
+    }
+}
+
+fn blend(info: &RenderTargetBlendInfo, src: [f32; 4], dst: [f32; 4]) -> [f32; 4] {
+    let mut out = src;
+    if info.blend_enabled {
+        for c in 0..4 {
+            let (sf, df, op) = if c < 3 {
+                (info.src_blend, info.dst_blend, info.blend_op)
+            }
+            else {
+                (info.src_blend_alpha, info.dst_blend_alpha, info.blend_op_alpha)
+            };
+            let s = src[c] * if matches!(op, BlendOp::Min | BlendOp::Max) { 1.0 } else { blend_factor(sf, src, dst, c) };
+            let d = dst[c] * if matches!(op, BlendOp::Min | BlendOp::Max) { 1.0 } else { blend_factor(df, src, dst, c) };
+            out[c] = blend_op(op, s, d);
+        }
+    }
+    let masks = [WriteMask::RED, WriteMask::GREEN, WriteMask::BLUE, WriteMask::ALPHA];
+    for c in 0..4 {
+        if !info.write_mask.contains(masks[c]) {
+            out[c] = dst[c];
+        }
+    }
+    out
+}
+
+/// Rasterises primitives into the targets and runs the per pixel tests and programs
+struct Rasteriser<'a, 'b, 'c> {
+    pipeline: &'a PipelineState,
+    state: &'a DrawState,
+    targets: &'a mut [Target<'b>],
+    depth: Option<&'a mut Target<'c>>,
+    viewport: Viewport,
+    bounds: (i64, i64, i64, i64),
+}
+
+impl<'a, 'b, 'c> Rasteriser<'a, 'b, 'c> {
+    fn to_screen(&self, v: &Vertex) -> ScreenVertex {
+        let inv_w = 1.0 / v.pos[3];
+        let ndc = [v.pos[0] * inv_w, v.pos[1] * inv_w, v.pos[2] * inv_w];
+        let vp = &self.viewport;
+        let mut vary = v.vary;
+        for varying in vary.iter_mut() {
+            for c in varying.iter_mut() {
+                *c *= inv_w;
+            }
+        }
+        ScreenVertex {
+            x: vp.x + (ndc[0] + 1.0) * 0.5 * vp.width,
+            y: vp.y + (1.0 - ndc[1]) * 0.5 * vp.height,
+            z: vp.min_depth + ndc[2] * (vp.max_depth - vp.min_depth),
+            inv_w,
+            vary
+        }
+    }
+
+    /// Depth, stencil, pixel program, blend and write for a single pixel
+    fn shade(&mut self, x: i64, y: i64, z: f32, inv_w: f32, vary: &[[f32; 4]; NUM_VARYINGS], front_facing: bool) {
+        let (left, top, right, bottom) = self.bounds;
+        if x < left || y < top || x >= right || y >= bottom {
+            return;
+        }
+
+        // depth stencil test
+        let ds = &self.pipeline.depth_stencil_info;
+        let mut depth_write = None;
+        if let Some(depth) = &mut self.depth {
+            let block_size = super::block_size_for_format(depth.info.format) as usize;
+            let offset = texture_slice_offset(&depth.info, depth.slice) + (y as usize * depth.info.width as usize + x as usize) * block_size;
+            if offset + block_size > depth.data.len() {
+                return;
+            }
+            let (dst_depth, dst_stencil) = decode_depth(depth.info.format, &depth.data[offset..offset + block_size]);
+            let bias = self.pipeline.raster_info.depth_bias as f32 / 16777216.0;
+            let src_depth = (z + bias).clamp(0.0, 1.0);
+            let depth_pass = !ds.depth_enabled || compare(ds.depth_func, src_depth, dst_depth);
+            let mut stencil = dst_stencil;
+            let mut stencil_pass = true;
+            if ds.stencil_enabled {
+                let face: &StencilInfo = if front_facing { &ds.front_face } else { &ds.back_face };
+                // there is no api to set the stencil ref so it is the default of 0
+                let reference = 0;
+                stencil_pass = compare(face.func, (reference & ds.stencil_read_mask) as f32, (dst_stencil & ds.stencil_read_mask) as f32);
+                let op = if !stencil_pass { face.fail } else if !depth_pass { face.depth_fail } else { face.pass };
+                let new_stencil = stencil_op(op, dst_stencil, reference);
+                stencil = (dst_stencil & !ds.stencil_write_mask) | (new_stencil & ds.stencil_write_mask);
+            }
+            let write_depth = depth_pass && stencil_pass && ds.depth_enabled && ds.depth_write_mask == DepthWriteMask::All;
+            if write_depth || stencil != dst_stencil {
+                let out_depth = if write_depth { src_depth } else { dst_depth };
+                depth_write = Some((offset, encode_depth(depth.info.format, out_depth, stencil)));
+            }
+            if !depth_pass || !stencil_pass {
+                if let Some((offset, texel)) = depth_write {
+                    depth.data[offset..offset + texel.len()].copy_from_slice(&texel);
+                }
+                return;
+            }
+        }
+
+        // perspective correct varyings
+        let w = 1.0 / inv_w;
+        let mut v = *vary;
+        for varying in v.iter_mut() {
+            for c in varying.iter_mut() {
+                *c *= w;
+            }
+        }
+
+        let colour = run_pixel_program(self.pipeline.program, self.state, &v);
+        if let (Some(depth), Some((offset, texel))) = (&mut self.depth, depth_write) {
+            depth.data[offset..offset + texel.len()].copy_from_slice(&texel);
+        }
+
+        // the built-in programs only output to SV_Target0
+        if let (Some(colour), Some(target)) = (colour, self.targets.first_mut()) {
+            let block_size = super::block_size_for_format(target.info.format) as usize;
+            let offset = texture_slice_offset(&target.info, target.slice) + (y as usize * target.info.width as usize + x as usize) * block_size;
+            if offset + block_size <= target.data.len() {
+                let dst = decode_texel(target.info.format, &target.data[offset..offset + block_size]);
+                let out = if let Some(info) = self.pipeline.blend.first() {
+                    blend(info, colour, dst)
+                }
+                else {
+                    colour
+                };
+                let texel = encode_texel(target.info.format, out);
+                target.data[offset..offset + block_size].copy_from_slice(&texel[..block_size]);
+            }
+        }
+    }
+
+    fn point(&mut self, v: &ScreenVertex) {
+        self.shade(v.x.floor() as i64, v.y.floor() as i64, v.z, v.inv_w, &v.vary, true);
+    }
+
+    fn line(&mut self, a: &ScreenVertex, b: &ScreenVertex) {
+        let dx = b.x - a.x;
+        let dy = b.y - a.y;
+        let steps = dx.abs().max(dy.abs()).ceil().max(1.0) as i64;
+        for i in 0..=steps {
+            let t = i as f32 / steps as f32;
+            let lerp = |a: f32, b: f32| a + (b - a) * t;
+            let mut vary = a.vary;
+            for (k, varying) in vary.iter_mut().enumerate() {
+                for (c, value) in varying.iter_mut().enumerate() {
+                    *value = lerp(a.vary[k][c], b.vary[k][c]);
+                }
+            }
+            self.shade(
+                lerp(a.x, b.x).floor() as i64,
+                lerp(a.y, b.y).floor() as i64,
+                lerp(a.z, b.z),
+                lerp(a.inv_w, b.inv_w),
+                &vary,
+                true
+            );
+        }
+    }
+
+    fn triangle(&mut self, v0: &ScreenVertex, v1: &ScreenVertex, v2: &ScreenVertex) {
+        let area = (v1.x - v0.x) * (v2.y - v0.y) - (v1.y - v0.y) * (v2.x - v0.x);
+        if area == 0.0 {
+            return;
+        }
+
+        // clockwise winding in screen space (y down) gives a positive area
+        let front_facing = if self.pipeline.raster_info.front_ccw { area < 0.0 } else { area > 0.0 };
+        let culled = match self.pipeline.raster_info.cull_mode {
+            CullMode::None => false,
+            CullMode::Front => front_facing,
+            CullMode::Back => !front_facing,
+        };
+        if culled {
+            return;
+        }
+
+        if matches!(self.pipeline.raster_info.fill_mode, FillMode::Wireframe) {
+            self.line(v0, v1);
+            self.line(v1, v2);
+            self.line(v2, v0);
+            return;
+        }
+
+        // ensure positive area so edge functions are positive inside
+        let (v1, v2) = if area < 0.0 { (v2, v1) } else { (v1, v2) };
+        let area = area.abs();
+
+        let (left, top, right, bottom) = self.bounds;
+        let min_x = (v0.x.min(v1.x).min(v2.x).floor() as i64).max(left);
+        let min_y = (v0.y.min(v1.y).min(v2.y).floor() as i64).max(top);
+        let max_x = (v0.x.max(v1.x).max(v2.x).ceil() as i64).min(right - 1);
+        let max_y = (v0.y.max(v1.y).max(v2.y).ceil() as i64).min(bottom - 1);
+
+        // edge functions are positive inside a triangle with positive area
+        let edge = |a: &ScreenVertex, b: &ScreenVertex, px: f32, py: f32| (b.x - a.x) * (py - a.y) - (b.y - a.y) * (px - a.x);
+
+        // top-left rule: pixels exactly on an edge are only filled for top or left edges
+        let top_left = |a: &ScreenVertex, b: &ScreenVertex| {
+            let dy = b.y - a.y;
+            let dx = b.x - a.x;
+            (dy == 0.0 && dx > 0.0) || dy < 0.0
+        };
+        let tl = [top_left(v1, v2), top_left(v2, v0), top_left(v0, v1)];
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let px = x as f32 + 0.5;
+                let py = y as f32 + 0.5;
+                let w = [edge(v1, v2, px, py), edge(v2, v0, px, py), edge(v0, v1, px, py)];
+                if !(0..3).all(|i| w[i] > 0.0 || (w[i] == 0.0 && tl[i])) {
+                    continue;
+                }
+                let (b0, b1, b2) = (w[0] / area, w[1] / area, w[2] / area);
+                let z = v0.z * b0 + v1.z * b1 + v2.z * b2;
+                let inv_w = v0.inv_w * b0 + v1.inv_w * b1 + v2.inv_w * b2;
+                let mut vary = [[0.0; 4]; NUM_VARYINGS];
+                for (k, varying) in vary.iter_mut().enumerate() {
+                    for (c, value) in varying.iter_mut().enumerate() {
+                        *value = v0.vary[k][c] * b0 + v1.vary[k][c] * b1 + v2.vary[k][c] * b2;
+                    }
+                }
+                self.shade(x, y, z, inv_w, &vary, front_facing);
+            }
+        }
+    }
+}
+
+/// Clips a polygon in clip space against the near plane (z >= 0)
+fn clip_near(poly: &[Vertex]) -> Vec<Vertex> {
+    let mut out = Vec::new();
+    for i in 0..poly.len() {
+        let a = &poly[i];
+        let b = &poly[(i + 1) % poly.len()];
+        let a_in = a.pos[2] >= 0.0;
+        let b_in = b.pos[2] >= 0.0;
+        if a_in {
+            out.push(*a);
+        }
+        if a_in != b_in {
+            let t = a.pos[2] / (a.pos[2] - b.pos[2]);
+            let mut v = *a;
+            for c in 0..4 {
+                v.pos[c] = a.pos[c] + (b.pos[c] - a.pos[c]) * t;
+            }
+            for k in 0..NUM_VARYINGS {
+                for c in 0..4 {
+                    v.vary[k][c] = a.vary[k][c] + (b.vary[k][c] - a.vary[k][c]) * t;
+                }
+            }
+            out.push(v);
+        }
+    }
+    out
+}
+
+/// Returns the vertex index for the `i`th vertex of a draw, fetching through the index buffer when indexed
+fn vertex_index(state: &DrawState, args: &DrawArgs, i: u32) -> Option<u32> {
+    if let Some((start_index, base_vertex)) = args.indexed {
+        let (data, format) = state.index_buffer.as_ref()?;
+        let index = (start_index + i) as usize;
+        let value = match format {
+            Format::R16u => {
+                let o = index * 2;
+                u16::from_ne_bytes([*data.get(o)?, *data.get(o + 1)?]) as i64
+            }
+            _ => {
+                let o = index * 4;
+                u32::from_ne_bytes([*data.get(o)?, *data.get(o + 1)?, *data.get(o + 2)?, *data.get(o + 3)?]) as i64
+            }
+        };
+        Some((value + base_vertex as i64).max(0) as u32)
+    }
+    else {
+        Some(args.start_vertex + i)
+    }
+}
+
+/// Executes a draw call with the bound `state` writing into colour `targets` and the optional `depth` target.
+/// Returns the number of primitives submitted to the rasteriser
+pub fn draw(state: &DrawState, targets: &mut [Target], depth: Option<&mut Target>, args: &DrawArgs) -> usize {
+    let pipeline = match &state.pipeline {
+        Some(pipeline) if pipeline.program != Program::Unknown => pipeline.clone(),
+        _ => return 0
+    };
+
+    // the target size is the extent of the first colour or depth target
+    let (width, height) = if let Some(t) = targets.first() {
+        (t.info.width, t.info.height)
+    }
+    else if let Some(d) = &depth {
+        (d.info.width, d.info.height)
+    }
+    else {
+        return 0;
+    };
+
+    let viewport = state.viewport.unwrap_or(Viewport {
+        x: 0.0,
+        y: 0.0,
+        width: width as f32,
+        height: height as f32,
+        min_depth: 0.0,
+        max_depth: 1.0
+    });
+
+    let mut bounds = (0, 0, width as i64, height as i64);
+    if let Some(scissor) = &state.scissor {
+        bounds = (
+            bounds.0.max(scissor.left as i64),
+            bounds.1.max(scissor.top as i64),
+            bounds.2.min(scissor.right as i64),
+            bounds.3.min(scissor.bottom as i64)
+        );
+    }
+
+    let mut rasteriser = Rasteriser {
+        pipeline: &pipeline,
+        state,
+        targets,
+        depth,
+        viewport,
+        bounds,
+    };
+
+    let fetch = VertexFetch {
+        state,
+        layout: &pipeline.input_layout,
+    };
+
+    let mut primitives = 0;
+    for instance in 0..args.instance_count {
+        let instance_id = args.start_instance + instance;
+        let vertices : Vec<Vertex> = (0..args.vertex_count)
+            .filter_map(|i| vertex_index(state, args, i))
+            .map(|vertex_id| run_vertex_program(pipeline.program, state, &fetch, vertex_id, instance_id))
+            .collect();
+
+        match pipeline.topology {
+            Topology::PointList => {
+                for v in &vertices {
+                    if v.pos[2] >= 0.0 && v.pos[3] > 0.0 {
+                        let s = rasteriser.to_screen(v);
+                        rasteriser.point(&s);
+                    }
+                    primitives += 1;
+                }
+            }
+            Topology::LineList | Topology::LineStrip => {
+                let step = if matches!(pipeline.topology, Topology::LineList) { 2 } else { 1 };
+                let mut i = 0;
+                while i + 1 < vertices.len() {
+                    let clipped = clip_near(&[vertices[i], vertices[i + 1]]);
+                    if clipped.len() >= 2 {
+                        let a = rasteriser.to_screen(&clipped[0]);
+                        let b = rasteriser.to_screen(&clipped[clipped.len() - 1]);
+                        rasteriser.line(&a, &b);
+                    }
+                    primitives += 1;
+                    i += step;
+                }
+            }
+            Topology::TriangleList | Topology::TriangleStrip => {
+                let strip = matches!(pipeline.topology, Topology::TriangleStrip);
+                let step = if strip { 1 } else { 3 };
+                let mut i = 0;
+                while i + 2 < vertices.len() {
+                    // odd triangles in a strip have reversed winding
+                    let tri = if strip && i % 2 == 1 {
+                        [vertices[i + 1], vertices[i], vertices[i + 2]]
+                    }
+                    else {
+                        [vertices[i], vertices[i + 1], vertices[i + 2]]
+                    };
+                    let poly = clip_near(&tri);
+                    if poly.len() >= 3 {
+                        let screen : Vec<ScreenVertex> = poly.iter().map(|v| rasteriser.to_screen(v)).collect();
+                        for j in 1..screen.len() - 1 {
+                            rasteriser.triangle(&screen[0], &screen[j], &screen[j + 1]);
+                        }
+                    }
+                    primitives += 1;
+                    i += step;
+                }
+            }
+            _ => ()
+        }
+    }
+    primitives
+}
@@ -136,6 +136,8 @@ pub struct Device {
     command_allocator: ID3D12CommandAllocator,
     command_list: ID3D12GraphicsCommandList,
     command_queue: ID3D12CommandQueue,
+    compute_queue: ID3D12CommandQueue,
+    copy_queue: ID3D12CommandQueue,
     pix: Option<WinPixEventRuntime>,
     shader_heap: Option<Heap>,
     rtv_heap: Heap,
@@ -220,6 +222,8 @@ pub struct Texture {
     rtv: Vec<TextureTarget>,
     dsv: Vec<TextureTarget>,
     srv_index: Option<usize>,
+    /// description of the shader resource view of the texture so it can be re-created in another slot
+    srv_desc: Option<D3D12_SHADER_RESOURCE_VIEW_DESC>,
     resolved_srv_index: Option<usize>,
     uav_index: Option<usize>,
     subresource_uav_index: Vec<usize>,
@@ -328,6 +332,7 @@ const fn to_dxgi_format(format: super::Format) -> DXGI_FORMAT {
         super::Format::BGRX8n => DXGI_FORMAT_B8G8R8X8_UNORM,
         super::Format::BGRA8nSRGB => DXGI_FORMAT_B8G8R8A8_UNORM_SRGB,
         super::Format::BGRX8nSRGB => DXGI_FORMAT_B8G8R8X8_UNORM_SRGB,
+        super::Format::RGBA16n => DXGI_FORMAT_R16G16B16A16_UNORM,
         super::Format::RGBA16u => DXGI_FORMAT_R16G16B16A16_UINT,
         super::Format::RGBA16i => DXGI_FORMAT_R16G16B16A16_SINT,
         super::Format::RGBA16f => DXGI_FORMAT_R16G16B16A16_FLOAT,
@@ -497,6 +502,14 @@ const fn to_d3d12_descriptor_heap_flags(heap_type: super::HeapType) -> D3D12_DES
     }
 }
 
+fn to_d3d12_command_list_type(queue: super::QueueType) -> D3D12_COMMAND_LIST_TYPE {
+    match queue {
+        super::QueueType::Graphics => D3D12_COMMAND_LIST_TYPE_DIRECT,
+        super::QueueType::Compute => D3D12_COMMAND_LIST_TYPE_COMPUTE,
+        super::QueueType::Copy => D3D12_COMMAND_LIST_TYPE_COPY,
+    }
+}
+
 fn to_d3d12_texture_usage_flags(usage: super::TextureUsage) -> D3D12_RESOURCE_FLAGS {
     let mut flags = D3D12_RESOURCE_FLAG_NONE;
     if usage.contains(super::TextureUsage::RENDER_TARGET) {
@@ -698,6 +711,47 @@ const fn to_d3d12_texture_srv_dimension(tex_type: super::TextureType, samples: u
     }
 }
 
+fn to_d3d12_texture_srv_desc(info: &TextureInfo) -> D3D12_SHADER_RESOURCE_VIEW_DESC {
+    let format = to_dxgi_format_srv(info.format);
+    let srv_dimension = to_d3d12_texture_srv_dimension(info.tex_type, info.samples);
+    match info.tex_type {
+        // technically these should use thier own struct, but the members are equivalent within the union
+        // so we can just minimise code duplocation
+        super::TextureType::Texture2D | super::TextureType::TextureCube | super::TextureType::Texture3D => {
+            D3D12_SHADER_RESOURCE_VIEW_DESC {
+                Format: format,
+                ViewDimension: srv_dimension,
+                Anonymous: D3D12_SHADER_RESOURCE_VIEW_DESC_0 {
+                    Texture2D: D3D12_TEX2D_SRV {
+                        MipLevels: info.mip_levels,
+                        MostDetailedMip: 0,
+                        ..Default::default()
+                    },
+                },
+                Shader4ComponentMapping: D3D12_DEFAULT_SHADER_4_COMPONENT_MAPPING,
+            }
+        }
+        super::TextureType::Texture2DArray => {
+            D3D12_SHADER_RESOURCE_VIEW_DESC {
+                Format: format,
+                ViewDimension: srv_dimension,
+                Anonymous: D3D12_SHADER_RESOURCE_VIEW_DESC_0 {
+                    Texture2DArray: D3D12_TEX2D_ARRAY_SRV {
+                        MostDetailedMip: 0,
+                        MipLevels: info.mip_levels,
+                        FirstArraySlice: 0,
+                        ArraySize: info.mip_levels,
+                        PlaneSlice: 0,
+                        ResourceMinLODClamp: 0.0,
+                    },
+                },
+                Shader4ComponentMapping: D3D12_DEFAULT_SHADER_4_COMPONENT_MAPPING,
+            }
+        }
+        _ => panic!("hotline_rs::gfx::d3d12:: not implemented shader resource view for type {:?}", info.tex_type)
+    }
+}
+
 const fn to_d3d12_resource_dimension(tex_type: super::TextureType) -> D3D12_RESOURCE_DIMENSION {
     match tex_type {
         super::TextureType::Texture1D => D3D12_RESOURCE_DIMENSION_TEXTURE1D,
@@ -1083,6 +1137,7 @@ fn create_swap_chain_rtv(
                 resolved_format: DXGI_FORMAT_UNKNOWN,
                 dsv: Vec::new(),
                 srv_index: None,
+                srv_desc: None,
                 resolved_srv_index: None,
                 uav_index: None,
                 shared_handle: None,
@@ -1238,6 +1293,14 @@ impl Buffer {
 }
 
 impl Device {
+    fn get_queue(&self, queue: super::QueueType) -> &ID3D12CommandQueue {
+        match queue {
+            super::QueueType::Graphics => &self.command_queue,
+            super::QueueType::Compute => &self.compute_queue,
+            super::QueueType::Copy => &self.copy_queue,
+        }
+    }
+
     fn create_d3d12_input_element_desc(
         layout: &super::InputLayout,
         null_terminated_semantics: &[CString],
@@ -1696,6 +1759,11 @@ pub struct RaytracingTLAS {
     pub(crate) shader_heap_id: u16
 }
 
+pub struct Fence {
+    fence: ID3D12Fence,
+    event: HANDLE
+}
+
 impl super::Device for Device {
     type SwapChain = SwapChain;
     type CmdBuf = CmdBuf;
@@ -1713,7 +1781,8 @@ impl super::Device for Device {
     type RaytracingShaderBindingTable = RaytracingShaderBindingTable;
     type RaytracingBLAS = RaytracingBLAS;
     type RaytracingTLAS = RaytracingTLAS;
-    fn create(info: &super::DeviceInfo) -> Device {
+    type Fence = Fence;
+    fn create(info: &super::DeviceInfo) -> result::Result<Device, super::Error> {
         unsafe {
             // enable debug layer
             let mut dxgi_factory_flags: u32 = 0;
@@ -1767,6 +1836,25 @@ impl super::Device for Device {
                 .CreateCommandQueue(&desc)
                 .expect("hotline_rs::gfx::d3d12: failed to create command queue");
 
+            // create async queues
+            let desc = D3D12_COMMAND_QUEUE_DESC {
+                Type: D3D12_COMMAND_LIST_TYPE_COMPUTE,
+                NodeMask: 1,
+                ..Default::default()
+            };
+            let compute_queue : ID3D12CommandQueue = device
+                .CreateCommandQueue(&desc)
+                .expect("hotline_rs::gfx::d3d12: failed to create compute command queue");
+
+            let desc = D3D12_COMMAND_QUEUE_DESC {
+                Type: D3D12_COMMAND_LIST_TYPE_COPY,
+                NodeMask: 1,
+                ..Default::default()
+            };
+            let copy_queue : ID3D12CommandQueue = device
+                .CreateCommandQueue(&desc)
+                .expect("hotline_rs::gfx::d3d12: failed to create copy command queue");
+
             let timestamp_frequency = command_queue
                 .GetTimestampFrequency()
                 .expect("hotline_rs::gfx::d3d12: failed to obtain timestamp frquency") as f64;
@@ -1846,6 +1934,8 @@ impl super::Device for Device {
                 command_allocator,
                 command_list,
                 command_queue,
+                compute_queue,
+                copy_queue,
                 pix: WinPixEventRuntime::create(),
                 shader_heap: Some(shader_heap),
                 rtv_heap,
@@ -1859,7 +1949,7 @@ impl super::Device for Device {
                 device.generate_mip_maps_pipeline = Some(pipeline);
             }
             
-            device
+            Ok(device)
         }
     }
 
@@ -1960,6 +2050,11 @@ impl super::Device for Device {
     }
 
     fn create_cmd_buf(&self, num_buffers: u32) -> CmdBuf {
+        self.create_cmd_buf_for_queue(num_buffers, super::QueueType::Graphics)
+    }
+
+    fn create_cmd_buf_for_queue(&self, num_buffers: u32, queue: super::QueueType) -> CmdBuf {
+        let list_type = to_d3d12_command_list_type(queue);
         unsafe {
             let mut command_allocators: Vec<ID3D12CommandAllocator> = Vec::new();
             let mut command_lists: Vec<ID3D12GraphicsCommandList> = Vec::new();
@@ -1970,13 +2065,13 @@ impl super::Device for Device {
                 // create command allocator
                 let command_allocator = self
                     .device
-                    .CreateCommandAllocator(D3D12_COMMAND_LIST_TYPE_DIRECT)
+                    .CreateCommandAllocator(list_type)
                     .expect("hotline_rs::gfx::d3d12: failed to create command allocator");
 
                 // create command list
                 let command_list = self
                     .device
-                    .CreateCommandList(0, D3D12_COMMAND_LIST_TYPE_DIRECT, &command_allocator, None)
+                    .CreateCommandList(0, list_type, &command_allocator, None)
                     .expect("hotline_rs::gfx::d3d12: failed to create command list");
 
                 command_allocators.push(command_allocator);
@@ -2667,58 +2762,13 @@ impl super::Device for Device {
 
             // create srv
             let mut srv_index = None;
+            let mut srv_desc = None;
             if info.usage.contains(super::TextureUsage::SHADER_RESOURCE) {
                 let h = shader_heap.allocate();
-
-                let dxgi_dormat_srv = to_dxgi_format_srv(info.format);
-                let srv_dimension = to_d3d12_texture_srv_dimension(info.tex_type, info.samples);
-
-                match info.tex_type {
-                    // technically these should use thier own struct, but the members are equivalent within the union
-                    // so we can just minimise code duplocation
-                    super::TextureType::Texture2D | super::TextureType::TextureCube | super::TextureType::Texture3D => {
-                        self.device.CreateShaderResourceView(
-                            &resource,
-                            Some(&D3D12_SHADER_RESOURCE_VIEW_DESC {
-                                Format: dxgi_dormat_srv,
-                                ViewDimension: srv_dimension,
-                                Anonymous: D3D12_SHADER_RESOURCE_VIEW_DESC_0 {
-                                    Texture2D: D3D12_TEX2D_SRV {
-                                        MipLevels: info.mip_levels,
-                                        MostDetailedMip: 0,
-                                        ..Default::default()
-                                    },
-                                },
-                                Shader4ComponentMapping: D3D12_DEFAULT_SHADER_4_COMPONENT_MAPPING,
-                            }),
-                            h,
-                        );
-                    }
-                    super::TextureType::Texture2DArray => {
-                        self.device.CreateShaderResourceView(
-                            &resource,
-                            Some(&D3D12_SHADER_RESOURCE_VIEW_DESC {
-                                Format: dxgi_dormat_srv,
-                                ViewDimension: srv_dimension,
-                                Anonymous: D3D12_SHADER_RESOURCE_VIEW_DESC_0 {
-                                    Texture2DArray: D3D12_TEX2D_ARRAY_SRV {
-                                        MostDetailedMip: 0,
-                                        MipLevels: info.mip_levels,
-                                        FirstArraySlice: 0,
-                                        ArraySize: info.mip_levels,
-                                        PlaneSlice: 0,
-                                        ResourceMinLODClamp: 0.0,
-                                    },
-                                },
-                                Shader4ComponentMapping: D3D12_DEFAULT_SHADER_4_COMPONENT_MAPPING,
-                            }),
-                            h,
-                        );
-                    }
-                    _ => panic!("hotline_rs::gfx::d3d12:: not implemented shader resource view for type {:?}", info.tex_type)
-                }
-
+                let desc = to_d3d12_texture_srv_desc(info);
+                self.device.CreateShaderResourceView(&resource, Some(&desc), h);
                 srv_index = Some(shader_heap.get_handle_index(&h));
+                srv_desc = Some(desc);
             }
             
             // create a srv for resolve texture for msaa
@@ -2883,6 +2933,7 @@ impl super::Device for Device {
                 rtv,
                 dsv,
                 srv_index,
+                srv_desc,
                 resolved_srv_index,
                 uav_index,
                 shared_handle,
@@ -3531,6 +3582,35 @@ impl super::Device for Device {
         }
     }
 
+    fn replace_texture_srv(
+        &mut self,
+        target: &mut Texture,
+        texture: &mut Texture,
+        heap: &mut Heap
+    ) -> result::Result<(), super::Error> {
+        let in_heap = |tex: &Texture| tex.shader_heap_id == Some(heap.id) && tex.resolved_srv_index.is_none();
+        match (target.srv_index, texture.srv_index, &texture.resource, &texture.srv_desc) {
+            (Some(target_index), Some(index), Some(resource), Some(desc)) if in_heap(target) && in_heap(texture) => {
+                // re-create the view of `texture` in the slot of `target`
+                unsafe {
+                    let h = D3D12_CPU_DESCRIPTOR_HANDLE {
+                        ptr: heap.base_address + heap.increment_size * target_index
+                    };
+                    self.device.CreateShaderResourceView(resource, Some(desc), h);
+                }
+                // exchange the slots so they are released by the other texture when dropped
+                target.srv_index = Some(index);
+                texture.srv_index = Some(target_index);
+                Ok(())
+            }
+            _ => {
+                Err(super::Error {
+                    msg: "hotline_rs::gfx::d3d12: replace_texture_srv requires single sample textures with a shader resource view in `heap`".to_string()
+                })
+            }
+        }
+    }
+
     fn create_raytracing_instance_buffer(
         &mut self,
         instances: &Vec<RaytracingInstanceInfo<Self>>
@@ -3731,9 +3811,38 @@ impl super::Device for Device {
     }    
 
     fn execute(&mut self, cmd: &CmdBuf) {
+        self.execute_on_queue(super::QueueType::Graphics, cmd);
+    }
+
+    fn execute_on_queue(&mut self, queue: super::QueueType, cmd: &CmdBuf) {
         unsafe {
             let command_list = Some(cmd.command_list[cmd.bb_index].cast().unwrap());
-            self.command_queue.ExecuteCommandLists(&[command_list]);
+            self.get_queue(queue).ExecuteCommandLists(&[command_list]);
+        }
+    }
+
+    fn create_fence(&self) -> result::Result<Fence, super::Error> {
+        unsafe {
+            Ok(Fence {
+                fence: self.device.CreateFence(0, D3D12_FENCE_FLAG_NONE)?,
+                event: CreateEventA(None, false, false, None)?
+            })
+        }
+    }
+
+    fn signal_fence(&mut self, queue: super::QueueType, fence: &Fence, value: u64) {
+        unsafe {
+            self.get_queue(queue)
+                .Signal(&fence.fence, value)
+                .expect("hotline_rs::gfx::d3d12: warning: command_queue.Signal failed!");
+        }
+    }
+
+    fn wait_fence(&mut self, queue: super::QueueType, fence: &Fence, value: u64) {
+        unsafe {
+            self.get_queue(queue)
+                .Wait(&fence.fence, value)
+                .expect("hotline_rs::gfx::d3d12: warning: command_queue.Wait failed!");
         }
     }
 
@@ -4961,6 +5070,33 @@ impl super::Texture<Device> for Texture {
     }
 }
 
+impl super::Fence<Device> for Fence {
+    fn get_completed_value(&self) -> u64 {
+        unsafe {
+            self.fence.GetCompletedValue()
+        }
+    }
+
+    fn wait_for_value(&self, value: u64) {
+        unsafe {
+            if self.fence.GetCompletedValue() < value {
+                self.fence
+                    .SetEventOnCompletion(value, self.event)
+                    .expect("hotline_rs::gfx::d3d12: failed to set on completion event!");
+                WaitForSingleObject(self.event, INFINITE);
+            }
+        }
+    }
+}
+
+impl Drop for Fence {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseHandle(self.event);
+        }
+    }
+}
+
 impl super::RaytracingTLAS<Device> for RaytracingTLAS {
     fn get_srv_index(&self) -> Option<usize> {
         self.tlas_buffer.srv_index
@@ -5198,6 +5334,8 @@ unsafe impl Send for QueryHeap {}
 unsafe impl Sync for QueryHeap {}
 unsafe impl Send for CommandSignature {}
 unsafe impl Sync for CommandSignature {}
+unsafe impl Send for Fence {}
+unsafe impl Sync for Fence {}
 
 impl super::Shader<Device> for Shader {}
 impl super::RenderPipeline<Device> for RenderPipeline {}
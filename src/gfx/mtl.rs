@@ -57,6 +57,7 @@ const fn to_mtl_vertex_format(format: super::Format) -> MTLVertexFormat {
         super::Format::RGBA8n => MTLVertexFormat::UChar4Normalized,
         super::Format::RGBA8u => MTLVertexFormat::UChar4,
         super::Format::RGBA8i => MTLVertexFormat::Char4,
+        super::Format::RGBA16n => MTLVertexFormat::UShort4Normalized,
         super::Format::RGBA16u => MTLVertexFormat::UShort4,
         super::Format::RGBA16i => MTLVertexFormat::Short4,
         super::Format::RGBA16f => MTLVertexFormat::Half4,
@@ -279,6 +280,7 @@ fn to_mtl_pixel_format(format: super::Format) -> metal::MTLPixelFormat {
         super::Format::BGRX8n => metal::MTLPixelFormat::BGRA8Unorm,
         super::Format::BGRA8nSRGB => metal::MTLPixelFormat::BGRA8Unorm_sRGB,
         super::Format::BGRX8nSRGB => metal::MTLPixelFormat::BGRA8Unorm_sRGB,
+        super::Format::RGBA16n => metal::MTLPixelFormat::RGBA16Unorm,
         super::Format::RGBA16u => metal::MTLPixelFormat::RGBA16Uint,
         super::Format::RGBA16i => metal::MTLPixelFormat::RGBA16Sint,
         super::Format::RGBA16f => metal::MTLPixelFormat::RGBA16Float,
@@ -329,6 +331,8 @@ fn descriptor_register_kind(ty: super::DescriptorType) -> char {
 pub struct Device {
     metal_device: metal::Device,
     command_queue: metal::CommandQueue,
+    compute_queue: metal::CommandQueue,
+    copy_queue: metal::CommandQueue,
     shader_heap: Heap,
     adapter_info: AdapterInfo,
     heap_alloc_id: u16,
@@ -1656,7 +1660,20 @@ pub struct RaytracingTLAS {
 
 }
 
+/// Shared events can be signalled and waited on from any queue and polled from the CPU
+pub struct Fence {
+    event: metal::SharedEvent
+}
+
 impl Device {
+    fn get_queue(&self, queue: super::QueueType) -> &metal::CommandQueue {
+        match queue {
+            super::QueueType::Graphics => &self.command_queue,
+            super::QueueType::Compute => &self.compute_queue,
+            super::QueueType::Copy => &self.copy_queue,
+        }
+    }
+
     /// Largest texture sample count <= `requested` that this device supports (always >= 1).
     /// Apple GPUs commonly cap at 4x, so an 8x request is clamped down rather than asserting.
     fn supported_sample_count(&self, requested: u32) -> u32 {
@@ -2101,12 +2118,16 @@ impl super::Device for Device {
     type RaytracingShaderBindingTable = RaytracingShaderBindingTable;
     type RaytracingBLAS = RaytracingBLAS;
     type RaytracingTLAS = RaytracingTLAS;
+    type Fence = Fence;
 
-    fn create(info: &super::DeviceInfo) -> Device {
+    fn create(info: &super::DeviceInfo) -> result::Result<Device, super::Error> {
         objc::rc::autoreleasepool(|| {
-            let device = metal::Device::system_default()
-                .expect("hotline_rs::gfx::mtl: failed to create metal device");
+            let device = metal::Device::system_default().ok_or(super::Error {
+                msg: "hotline_rs::gfx::mtl: failed to create metal device".to_string()
+            })?;
             let command_queue = device.new_command_queue();
+            let compute_queue = device.new_command_queue();
+            let copy_queue = device.new_command_queue();
 
             // adapter info
             let adapter_info = AdapterInfo {
@@ -2128,8 +2149,10 @@ impl super::Device for Device {
                 msg_send![&*device, supportsCounterSampling: MTL_COUNTER_SAMPLING_POINT_AT_STAGE_BOUNDARY]
             };
 
-            Device {
+            Ok(Device {
                 command_queue: command_queue,
+                compute_queue,
+                copy_queue,
                 shader_heap: Self::create_heap_mtl(&device, &HeapInfo{
                     heap_type: HeapType::Shader,
                     num_descriptors: info.shader_heap_size,
@@ -2139,7 +2162,7 @@ impl super::Device for Device {
                 metal_device: device,
                 heap_alloc_id: 2,
                 supports_stage_boundary_timestamps,
-            }
+            })
        })
     }
 
@@ -2233,8 +2256,12 @@ impl super::Device for Device {
     }
 
     fn create_cmd_buf(&self, num_buffers: u32) -> CmdBuf {
+        self.create_cmd_buf_for_queue(num_buffers, super::QueueType::Graphics)
+    }
+
+    fn create_cmd_buf_for_queue(&self, num_buffers: u32, queue: super::QueueType) -> CmdBuf {
         objc::rc::autoreleasepool(|| {
-            let cmd_queue = self.command_queue.clone();
+            let cmd_queue = self.get_queue(queue).clone();
             let cmd = cmd_queue.new_command_buffer().to_owned();
 
             CmdBuf {
@@ -3017,6 +3044,10 @@ impl super::Device for Device {
     }
 
     fn execute(&mut self, cmd: &CmdBuf) {
+        self.execute_on_queue(super::QueueType::Graphics, cmd);
+    }
+
+    fn execute_on_queue(&mut self, queue: super::QueueType, cmd: &CmdBuf) {
         // Pass command buffers commit themselves in CmdBuf::close, so there is nothing to submit
         // here for them. Barrier command buffers instead carry deferred ops (transition / resolve /
         // generate mips) which we replay into a fresh command buffer every frame, mirroring how
@@ -3026,7 +3057,7 @@ impl super::Device for Device {
         }
 
         objc::rc::autoreleasepool(|| {
-            let metal_cmd = self.command_queue.new_command_buffer();
+            let metal_cmd = self.get_queue(queue).new_command_buffer();
             for op in &cmd.deferred_ops {
                 match op {
                     DeferredBarrierOp::Resolve { msaa, resolve } => {
@@ -3071,6 +3102,28 @@ impl super::Device for Device {
         });
     }
 
+    fn create_fence(&self) -> result::Result<Fence, super::Error> {
+        Ok(Fence {
+            event: self.metal_device.new_shared_event()
+        })
+    }
+
+    fn signal_fence(&mut self, queue: super::QueueType, fence: &Fence, value: u64) {
+        objc::rc::autoreleasepool(|| {
+            let cmd = self.get_queue(queue).new_command_buffer();
+            cmd.encode_signal_event(&fence.event, value);
+            cmd.commit();
+        });
+    }
+
+    fn wait_fence(&mut self, queue: super::QueueType, fence: &Fence, value: u64) {
+        objc::rc::autoreleasepool(|| {
+            let cmd = self.get_queue(queue).new_command_buffer();
+            cmd.encode_wait_for_event(&fence.event, value);
+            cmd.commit();
+        });
+    }
+
     fn report_live_objects(&self) -> result::Result<(), super::Error> {
         Ok(())
     }
@@ -3195,6 +3248,40 @@ impl super::Device for Device {
         unimplemented!()
     }
 
+    fn replace_texture_srv(
+        &mut self,
+        target: &mut Texture,
+        texture: &mut Texture,
+        heap: &mut Heap
+    ) -> Result<(), super::Error> {
+        let (target_index, index) = match (target.srv_index, texture.srv_index) {
+            (Some(target_index), Some(index)) if target.heap_id == Some(heap.id) && texture.heap_id == Some(heap.id) => {
+                (target_index, index)
+            }
+            _ => {
+                return Err(super::Error {
+                    msg: "hotline_rs::gfx::mtl: replace_texture_srv requires both textures to have a shader resource view in `heap`".to_string()
+                });
+            }
+        };
+
+        // encode the srv texture of `texture` into the slot of `target`
+        let srv_texture = texture.resolved_texture.as_ref().unwrap_or(&texture.metal_texture);
+        heap.texture_slots[target_index] = Some(srv_texture.to_owned());
+        heap.encode_texture(target_index, srv_texture);
+
+        // the uav of non msaa textures shares the srv slot
+        if target.uav_index == Some(target_index) {
+            target.uav_index = Some(index);
+        }
+        if texture.uav_index == Some(index) {
+            texture.uav_index = Some(target_index);
+        }
+        target.srv_index = Some(index);
+        texture.srv_index = Some(target_index);
+        Ok(())
+    }
+
     fn create_raytracing_tlas_with_heap(
         &mut self,
         info: &RaytracingTLASInfo<Self>,
@@ -3207,6 +3294,8 @@ impl super::Device for Device {
 
 unsafe impl Send for Device {}
 unsafe impl Sync for Device {}
+unsafe impl Send for Fence {}
+unsafe impl Sync for Fence {}
 unsafe impl Send for SwapChain {}
 unsafe impl Sync for SwapChain {}
 unsafe impl Send for RenderPass {}
@@ -3237,6 +3326,18 @@ impl super::RaytracingPipeline<Device> for RaytracingPipeline {}
 impl super::RaytracingShaderBindingTable<Device> for RaytracingShaderBindingTable {}
 impl super::RaytracingBLAS<Device> for RaytracingBLAS {}
 
+impl super::Fence<Device> for Fence {
+    fn get_completed_value(&self) -> u64 {
+        self.event.signaled_value()
+    }
+
+    fn wait_for_value(&self, value: u64) {
+        while self.event.signaled_value() < value {
+            std::thread::yield_now();
+        }
+    }
+}
+
 impl super::RaytracingTLAS<Device> for RaytracingTLAS {
     fn get_srv_index(&self) -> Option<usize> {
         unimplemented!()
@@ -1,7 +1,8 @@
-#![allow(warnings)]
 
-#[derive(Clone)]
-pub struct Device;
+//! A headless gfx backend which allocates CPU side storage for resources and records every `CmdBuf`
+//! call into an inspectable command list. Data movement commands (clears, copies, resolves, mip generation,
+//! read backs and queries) are executed on the CPU by `Device::execute` so read backs return real bytes.
+//! Misuse of the API is validated and reported through `Device::get_info_queue_messages` instead of panicking.
 
 use super::Error;
 use super::DeviceInfo;
@@ -12,6 +13,7 @@ use super::TextureInfo;
 use super::TextureHeapInfo;
 use super::IndirectArgumentType;
 use super::HeapInfo;
+use super::HeapType;
 use super::QueryHeapInfo;
 use super::PipelineStatistics;
 use super::ReadBackData;
@@ -24,13 +26,19 @@ use super::RaytracingShaderBindingTableInfo;
 use super::RenderPassInfo;
 use super::RenderPipelineInfo;
 use super::BufferInfo;
+use super::BufferUsage;
+use super::CpuAccessFlags;
 use super::ShaderInfo;
+use super::ShaderType;
 use super::QueryType;
+use super::QueueType;
 use super::TransitionBarrier;
 use super::Subresource;
 use super::Viewport;
 use super::ScissorRect;
 use super::Pipeline;
+use super::PipelineLayout;
+use super::PipelineSlotInfo;
 use super::Size3;
 use super::Region;
 use super::MapInfo;
@@ -42,183 +50,968 @@ use super::AccelerationStructureRebuildMode;
 use super::Resource;
 use super::RaytracingInstanceInfo;
 use super::ResourceViewInfo;
+use super::ResourceView;
+use super::ResourceState;
 use super::DescriptorType;
 use super::PipelineType;
-
+use super::TextureUsage;
+use super::TextureType;
+use super::Format;
+use super::ClearColour;
+use super::ClearDepthStencil;
+use super::InputLayout;
+use super::RasterInfo;
+use super::DepthStencilInfo;
+use super::Topology;
+use super::FreeList;
+use super::FreeListRef;
+use super::DropList;
+use super::DropListRef;
+use super::DropResource;
+use super::cpu;
+use super::cpu::texture_slice_offset;
+
+use super::Device as _;
+use super::Heap as _;
+use super::Texture as _;
+use super::ReadBackRequest as _;
 
 use crate::os::Window;
 use crate::os::App;
 
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, AtomicU16, Ordering};
+
+/// CPU side storage for the contents of a buffer or texture, shared between resources, commands and read backs
+pub type ResourceData = Arc<Mutex<Vec<u8>>>;
+
+/// Messages produced by validation, drained by `Device::get_info_queue_messages`
+type InfoQueueRef = Arc<Mutex<Vec<String>>>;
+
+/// Unique heap ids so resources can be correlated with the heap their views live in
+static HEAP_ID: AtomicU16 = AtomicU16::new(0);
+
+/// Alignment for append buffer counters, matches d3d12 `D3D12_UAV_COUNTER_PLACEMENT_ALIGNMENT`
+const COUNTER_ALIGNMENT: usize = 4096;
+
+/// Key for slot lookup: (register, space, descriptor_type)
+type SlotKey = (u32, u32, DescriptorType);
+
+fn new_resource_data(size: usize) -> ResourceData {
+    Arc::new(Mutex::new(vec![0; size]))
+}
+
+fn new_info_queue() -> InfoQueueRef {
+    Arc::new(Mutex::new(Vec::new()))
+}
+
+fn report(info_queue: &InfoQueueRef, severity: &str, msg: String) {
+    info_queue.lock().unwrap().push(format!("hotline_rs::gfx::null: [{}] {}", severity, msg));
+}
+
+/// Returns a monotonic timestamp in nanoseconds, used to resolve timestamp queries
+fn timestamp_ns() -> u64 {
+    static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+    START.get_or_init(std::time::Instant::now).elapsed().as_nanos() as u64
+}
+
+/// A single recorded command, obtained from `CmdBuf::get_commands` so tests can inspect what was submitted
+#[derive(Clone)]
+pub enum Command {
+    BeginRenderPass {
+        /// Formats of the colour targets and depth stencil target bound to the pass
+        formats: Vec<Format>,
+        depth_stencil_format: Option<Format>,
+        rt_clear: Option<ClearColour>,
+        ds_clear: Option<ClearDepthStencil>,
+        format_hash: u64,
+        /// Storage of targets to apply the clears to on execute
+        targets: Vec<(ResourceData, TextureInfo, usize)>,
+        depth_stencil: Option<(ResourceData, TextureInfo, usize)>,
+    },
+    EndRenderPass,
+    BeginEvent {
+        colour: u32,
+        name: String
+    },
+    EndEvent,
+    SetMarker {
+        colour: u32,
+        name: String
+    },
+    TimestampQuery {
+        index: usize,
+        resolve_buffer: ResourceData
+    },
+    BeginQuery {
+        query_type: QueryType,
+        index: usize
+    },
+    EndQuery {
+        query_type: QueryType,
+        index: usize,
+        resolve_buffer: ResourceData
+    },
+    TransitionBarrier {
+        state_before: ResourceState,
+        state_after: ResourceState,
+        subresource: Option<Subresource>
+    },
+    UavBarrier,
+    SetViewport(Viewport),
+    SetScissorRect(ScissorRect),
+    SetIndexBuffer {
+        format: Format,
+        size_bytes: usize,
+        data: ResourceData
+    },
+    SetVertexBuffer {
+        slot: u32,
+        stride: usize,
+        size_bytes: usize,
+        data: ResourceData
+    },
+    SetRenderPipeline {
+        state: Arc<cpu::PipelineState>
+    },
+    SetComputePipeline,
+    SetRaytracingPipeline,
+    SetHeap {
+        heap_id: u16
+    },
+    SetBinding {
+        register: u32,
+        space: u32,
+        descriptor_type: DescriptorType,
+        heap_id: u16,
+        offset: usize,
+        /// Texture bound to the slot if `offset` is a texture shader resource view
+        texture: Option<(ResourceData, TextureInfo)>
+    },
+    PushConstants {
+        pipeline_type: PipelineType,
+        register: u32,
+        space: u32,
+        dest_offset: u32,
+        data: Vec<u32>
+    },
+    DrawInstanced {
+        vertex_count: u32,
+        instance_count: u32,
+        start_vertex: u32,
+        start_instance: u32
+    },
+    DrawIndexedInstanced {
+        index_count: u32,
+        instance_count: u32,
+        start_index: u32,
+        base_vertex: i32,
+        start_instance: u32
+    },
+    Dispatch {
+        group_count: Size3,
+        numthreads: Size3
+    },
+    ExecuteIndirect {
+        max_command_count: u32,
+        argument_buffer_offset: usize,
+        counter_buffer_offset: usize
+    },
+    DispatchRays {
+        numthreads: Size3
+    },
+    UpdateRaytracingTLAS {
+        instance_count: usize
+    },
+    ResolveTextureSubresource {
+        subresource: u32,
+        src: ResourceData,
+        dst: ResourceData
+    },
+    GenerateMipMaps {
+        data: ResourceData,
+        info: TextureInfo
+    },
+    ReadBackBackbuffer {
+        src: ResourceData,
+        dst: ResourceData
+    },
+    CopyBufferRegion {
+        dst: ResourceData,
+        dst_offset: usize,
+        src: ResourceData,
+        src_offset: usize,
+        num_bytes: usize
+    },
+    CopyTextureRegion {
+        dst: ResourceData,
+        dst_info: TextureInfo,
+        subresource_index: u32,
+        dst_x: u32,
+        dst_y: u32,
+        dst_z: u32,
+        src: ResourceData,
+        src_info: TextureInfo,
+        src_region: Option<Region>
+    }
+}
+
+impl Command {
+    /// Returns the name of the command, useful for concise assertions in tests
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::BeginRenderPass { .. } => "begin_render_pass",
+            Command::EndRenderPass => "end_render_pass",
+            Command::BeginEvent { .. } => "begin_event",
+            Command::EndEvent => "end_event",
+            Command::SetMarker { .. } => "set_marker",
+            Command::TimestampQuery { .. } => "timestamp_query",
+            Command::BeginQuery { .. } => "begin_query",
+            Command::EndQuery { .. } => "end_query",
+            Command::TransitionBarrier { .. } => "transition_barrier",
+            Command::UavBarrier => "uav_barrier",
+            Command::SetViewport(_) => "set_viewport",
+            Command::SetScissorRect(_) => "set_scissor_rect",
+            Command::SetIndexBuffer { .. } => "set_index_buffer",
+            Command::SetVertexBuffer { .. } => "set_vertex_buffer",
+            Command::SetRenderPipeline { .. } => "set_render_pipeline",
+            Command::SetComputePipeline => "set_compute_pipeline",
+            Command::SetRaytracingPipeline => "set_raytracing_pipeline",
+            Command::SetHeap { .. } => "set_heap",
+            Command::SetBinding { .. } => "set_binding",
+            Command::PushConstants { .. } => "push_constants",
+            Command::DrawInstanced { .. } => "draw_instanced",
+            Command::DrawIndexedInstanced { .. } => "draw_indexed_instanced",
+            Command::Dispatch { .. } => "dispatch",
+            Command::ExecuteIndirect { .. } => "execute_indirect",
+            Command::DispatchRays { .. } => "dispatch_rays",
+            Command::UpdateRaytracingTLAS { .. } => "update_raytracing_tlas",
+            Command::ResolveTextureSubresource { .. } => "resolve_texture_subresource",
+            Command::GenerateMipMaps { .. } => "generate_mip_maps",
+            Command::ReadBackBackbuffer { .. } => "read_back_backbuffer",
+            Command::CopyBufferRegion { .. } => "copy_buffer_region",
+            Command::CopyTextureRegion { .. } => "copy_texture_region",
+        }
+    }
+
+    /// Returns true if the command can be recorded into a command buffer submitted to `queue`
+    pub fn is_supported_on(&self, queue: QueueType) -> bool {
+        match queue {
+            QueueType::Graphics => true,
+            QueueType::Compute => !matches!(self,
+                Command::BeginRenderPass { .. } |
+                Command::EndRenderPass |
+                Command::SetViewport(_) |
+                Command::SetScissorRect(_) |
+                Command::SetIndexBuffer { .. } |
+                Command::SetVertexBuffer { .. } |
+                Command::SetRenderPipeline { .. } |
+                Command::DrawInstanced { .. } |
+                Command::DrawIndexedInstanced { .. } |
+                Command::ResolveTextureSubresource { .. } |
+                Command::ReadBackBackbuffer { .. }
+            ),
+            QueueType::Copy => matches!(self,
+                Command::BeginEvent { .. } |
+                Command::EndEvent |
+                Command::SetMarker { .. } |
+                Command::TimestampQuery { .. } |
+                Command::TransitionBarrier { .. } |
+                Command::CopyBufferRegion { .. } |
+                Command::CopyTextureRegion { .. }
+            )
+        }
+    }
+}
+
+/// A queue submission recorded by the null device, which can be inspected with `Device::get_queue_ops`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueueOp {
+    /// A command buffer was executed on the queue
+    Execute(QueueType),
+    /// `fence` was signalled with `value` on `queue`
+    Signal { queue: QueueType, fence: u64, value: u64 },
+    /// `queue` waited for `fence` to reach `value`
+    Wait { queue: QueueType, fence: u64, value: u64 }
+}
+
+#[derive(Clone)]
+pub struct Device {
+    adapter_info: AdapterInfo,
+    feature_flags: DeviceFeatureFlags,
+    shader_heap: Heap,
+    rtv_heap: Heap,
+    dsv_heap: Heap,
+    info_queue: InfoQueueRef,
+    /// Total number of command buffers executed by the device
+    execute_count: Arc<AtomicU64>,
+    /// Submissions and fence operations in the order they were issued to the device queues
+    queue_ops: Arc<Mutex<Vec<QueueOp>>>,
+    /// Used to assign unique ids to fences
+    fence_count: Arc<AtomicU64>,
+}
+
+#[derive(Clone)]
+pub struct SwapChain {
+    num_buffers: u32,
+    format: Format,
+    width: i32,
+    height: i32,
+    clear_colour: Option<ClearColour>,
+    bb_index: usize,
+    /// Incremented each `swap`, the null device executes immediately so this is also the completed value
+    frame_fence_value: u64,
+    /// Frame index starts at 1 because `DropList` uses 0 to signify an untagged resource
+    frame_index: usize,
+    backbuffer_textures: Vec<Texture>,
+    backbuffer_passes: Vec<RenderPass>,
+    backbuffer_passes_no_clear: Vec<RenderPass>,
+}
+
+/// Tracks state while recording to validate usage
+#[derive(Clone, Default)]
+struct CmdBufState {
+    in_render_pass: bool,
+    event_depth: i32,
+    render_pipeline: bool,
+    compute_pipeline: bool,
+    raytracing_pipeline: bool,
+    index_buffer: bool,
+}
+
+#[derive(Clone)]
+pub struct CmdBuf {
+    bb_index: usize,
+    queue: QueueType,
+    commands: Vec<Vec<Command>>,
+    state: CmdBufState,
+    closed: bool,
+    info_queue: InfoQueueRef,
+}
+
+#[derive(Clone)]
+pub struct Shader {
+    shader_type: ShaderType,
+    data: Vec<u8>,
+}
+
+#[derive(Clone)]
+pub struct RenderPipeline {
+    slots: Vec<u32>,
+    slot_lookup: HashMap<SlotKey, PipelineSlotInfo>,
+    input_layout: InputLayout,
+    pipeline_layout: PipelineLayout,
+    raster_info: RasterInfo,
+    depth_stencil_info: DepthStencilInfo,
+    topology: Topology,
+    format_hash: Option<u64>,
+    /// State used by the software rasteriser to execute draws
+    cpu_state: Arc<cpu::PipelineState>,
+}
+
+/// Tracks the drop list and view allocations of a resource within a single heap
+struct HeapAllocs {
+    drop_list: DropListRef<ResourceData>,
+    indices: Vec<usize>,
+}
+
+pub struct Texture {
+    info: TextureInfo,
+    data: ResourceData,
+    /// Single sample copy for msaa textures which are resolved into
+    resolved_data: Option<ResourceData>,
+    srv_index: Option<usize>,
+    msaa_srv_index: Option<usize>,
+    uav_index: Option<usize>,
+    subresource_uav_index: Vec<usize>,
+    rtv_index: Vec<usize>,
+    dsv_index: Vec<usize>,
+    heap_id: Option<u16>,
+    allocs: Vec<HeapAllocs>,
+}
+
+pub struct Buffer {
+    info: BufferInfo,
+    data: ResourceData,
+    size_bytes: usize,
+    srv_index: Option<usize>,
+    uav_index: Option<usize>,
+    cbv_index: Option<usize>,
+    counter_offset: Option<usize>,
+    allocs: Option<HeapAllocs>,
+}
+
 #[derive(Clone)]
-pub struct SwapChain;
+pub struct ReadBackRequest {
+    data: ResourceData,
+    fence_value: u64,
+    format: Format,
+    size: usize,
+    row_pitch: usize,
+    slice_pitch: usize,
+}
 
 #[derive(Clone)]
-pub struct CmdBuf;
-
-pub struct Shader;
-pub struct RenderPipeline;
-pub struct Texture;
-pub struct Buffer;
-pub struct ReadBackRequest;
-pub struct RenderPass;
-pub struct Heap;
-pub struct QueryHeap;
-pub struct ComputePipeline;
-pub struct RaytracingPipeline;
-pub struct CommandSignature;
+pub struct RenderPass {
+    targets: Vec<(ResourceData, TextureInfo, usize)>,
+    depth_stencil: Option<(ResourceData, TextureInfo, usize)>,
+    rt_clear: Option<ClearColour>,
+    ds_clear: Option<ClearDepthStencil>,
+    sample_count: u32,
+    format_hash: u64,
+}
+
+#[derive(Clone)]
+pub struct Heap {
+    heap_type: HeapType,
+    capacity: usize,
+    offset: usize,
+    free_list: FreeListRef,
+    drop_list: DropListRef<ResourceData>,
+    /// Texture shader resource views by heap index so bindings can be resolved for the rasteriser
+    views: Arc<Mutex<HashMap<usize, (ResourceData, TextureInfo)>>>,
+    id: u16,
+}
+
+#[derive(Clone)]
+pub struct QueryHeap {
+    heap_type: QueryType,
+    alloc_index: usize,
+    capacity: usize,
+}
+
+#[derive(Clone)]
+pub struct ComputePipeline {
+    slots: Vec<u32>,
+    slot_lookup: HashMap<SlotKey, PipelineSlotInfo>,
+    pipeline_layout: PipelineLayout,
+}
+
+#[derive(Clone)]
+pub struct RaytracingPipeline {
+    slots: Vec<u32>,
+    slot_lookup: HashMap<SlotKey, PipelineSlotInfo>,
+}
+
+#[derive(Clone)]
+pub struct CommandSignature {
+    arguments: Vec<IndirectArgumentType>,
+}
+
 pub struct RaytracingShaderBindingTable;
 pub struct RaytracingBLAS;
 pub struct RaytracingTLAS;
 
+/// The null device executes work immediately, so a fence reaches its value as soon as it is signalled
+pub struct Fence {
+    id: u64,
+    value: Arc<AtomicU64>
+}
+
+impl Heap {
+    fn create(info: &HeapInfo) -> Heap {
+        Heap {
+            heap_type: info.heap_type,
+            capacity: info.num_descriptors,
+            offset: 0,
+            free_list: FreeList::new(),
+            drop_list: DropList::new(),
+            views: Arc::new(Mutex::new(HashMap::new())),
+            id: HEAP_ID.fetch_add(1, Ordering::SeqCst),
+        }
+    }
+
+    /// Allocates a slot from the free list or grows the heap, returns an error if the heap is full
+    fn allocate(&mut self) -> Result<usize, Error> {
+        if let Some(index) = self.free_list.pop() {
+            Ok(index)
+        }
+        else if self.offset < self.capacity {
+            let index = self.offset;
+            self.offset += 1;
+            Ok(index)
+        }
+        else {
+            Err(Error {
+                msg: format!("hotline_rs::gfx::null: heap {} is full! (capacity: {})", self.id, self.capacity)
+            })
+        }
+    }
+
+    /// Returns the number of slots currently allocated in the heap
+    pub fn get_num_allocated(&self) -> usize {
+        self.offset - self.free_list.list.lock().unwrap().len()
+    }
+
+    /// Returns the total number of slots in the heap
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl HeapAllocs {
+    fn new(heap: &Heap) -> Self {
+        HeapAllocs {
+            drop_list: heap.drop_list.clone(),
+            indices: Vec::new(),
+        }
+    }
+
+    /// Replaces the allocation `index` with `replacement` if these are allocations from `heap`
+    fn replace(&mut self, heap: &Heap, index: usize, replacement: usize) {
+        if Arc::ptr_eq(&self.drop_list, &heap.drop_list) {
+            if let Some(alloc) = self.indices.iter_mut().find(|alloc| **alloc == index) {
+                *alloc = replacement;
+            }
+        }
+    }
+}
+
+/// Returns the total size in bytes required to store all mips and array layers of a texture
+fn texture_size_bytes(info: &TextureInfo) -> usize {
+    super::size_for_format_mipped(
+        info.format, info.width, info.height, info.depth, info.array_layers, info.mip_levels) as usize
+}
+
+/// Returns the byte offset and dimensions for the subresource `array_slice * mips + mip`
+fn texture_subresource_offset(info: &TextureInfo, subresource: u32) -> (usize, u64, u64, u32) {
+    let mips = info.mip_levels.max(1);
+    let slice = subresource / mips;
+    let mip = subresource % mips;
+    let mut offset = texture_slice_offset(info, slice as usize);
+    let mut width = info.width;
+    let mut height = info.height;
+    let mut depth = info.depth;
+    for _ in 0..mip {
+        offset += super::size_for_format(info.format, width, height, depth) as usize;
+        width = (width / 2).max(1);
+        height = (height / 2).max(1);
+        depth = (depth / 2).max(1);
+    }
+    (offset, width, height, depth)
+}
+
+/// Fills the first mip of `slice` in `data` with the repeated `texel`
+fn fill_texture_slice(data: &ResourceData, info: &TextureInfo, slice: usize, texel: &[u8]) {
+    if texel.is_empty() {
+        return;
+    }
+    let mut data = data.lock().unwrap();
+    let start = texture_slice_offset(info, slice);
+    let size = super::size_for_format(info.format, info.width, info.height, info.depth) as usize;
+    let end = (start + size).min(data.len());
+    if start < end {
+        for chunk in data[start..end].chunks_mut(texel.len()) {
+            let n = chunk.len();
+            chunk.copy_from_slice(&texel[..n]);
+        }
+    }
+}
+
+/// Builds a slot lookup in the same order as the d3d12 root signature: push constants first, then bindings
+fn create_slot_lookup(layout: &PipelineLayout) -> (Vec<u32>, HashMap<SlotKey, PipelineSlotInfo>) {
+    let mut slots = Vec::new();
+    let mut lookup = HashMap::new();
+    let mut slot_iter = 0;
+    if let Some(push_constants) = &layout.push_constants {
+        for constants in push_constants {
+            lookup.insert((constants.shader_register, constants.register_space, DescriptorType::PushConstants), PipelineSlotInfo {
+                index: slot_iter,
+                count: Some(constants.num_values)
+            });
+            slots.push(slot_iter);
+            slot_iter += 1;
+        }
+    }
+    if let Some(bindings) = &layout.bindings {
+        for binding in bindings {
+            lookup.insert((binding.shader_register, binding.register_space, binding.binding_type), PipelineSlotInfo {
+                index: slot_iter,
+                count: binding.num_descriptors
+            });
+            slots.push(slot_iter);
+            slot_iter += 1;
+        }
+    }
+    (slots, lookup)
+}
+
+impl SwapChain {
+    fn create_backbuffers(&mut self, device: &mut Device) -> Result<(), Error> {
+        self.backbuffer_textures.clear();
+        self.backbuffer_passes.clear();
+        self.backbuffer_passes_no_clear.clear();
+        for _ in 0..self.num_buffers {
+            let texture = device.create_texture::<u8>(&TextureInfo {
+                tex_type: TextureType::Texture2D,
+                format: self.format,
+                width: self.width.max(1) as u64,
+                height: self.height.max(1) as u64,
+                depth: 1,
+                array_layers: 1,
+                mip_levels: 1,
+                samples: 1,
+                usage: TextureUsage::RENDER_TARGET,
+                initial_state: ResourceState::Present
+            }, None)?;
+            self.backbuffer_passes.push(device.create_render_pass(&RenderPassInfo {
+                render_targets: vec![&texture],
+                rt_clear: self.clear_colour,
+                depth_stencil: None,
+                ds_clear: None,
+                resolve: false,
+                discard: false,
+                array_slice: 0
+            })?);
+            self.backbuffer_passes_no_clear.push(device.create_render_pass(&RenderPassInfo {
+                render_targets: vec![&texture],
+                rt_clear: None,
+                depth_stencil: None,
+                ds_clear: None,
+                resolve: false,
+                discard: false,
+                array_slice: 0
+            })?);
+            self.backbuffer_textures.push(texture);
+        }
+        Ok(())
+    }
+
+    /// Returns the dimensions of the backbuffer (width, height)
+    pub fn get_size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+}
+
 impl super::SwapChain<Device> for SwapChain {
     fn new_frame(&mut self) {
-        unimplemented!()
     }
 
     fn update<A: App>(&mut self, device: &mut Device, window: &A::Window, cmd: &mut CmdBuf) -> bool {
-        unimplemented!()
+        let size = window.get_size();
+        if size.x != self.width || size.y != self.height {
+            self.width = size.x;
+            self.height = size.y;
+            if let Err(err) = self.create_backbuffers(device) {
+                report(&device.info_queue, "error", format!("failed to resize swap chain: {}", err.msg));
+            }
+            true
+        }
+        else {
+            false
+        }
     }
 
     fn wait_for_last_frame(&self) {
-        unimplemented!()
+        // commands are executed synchronously on the cpu so there is nothing in flight
     }
 
     fn get_frame_fence_value(&self) -> u64 {
-        unimplemented!()
+        self.frame_fence_value
     }
 
     fn get_num_buffers(&self) -> u32 {
-        unimplemented!()
+        self.num_buffers
     }
 
     fn get_backbuffer_index(&self) -> u32 {
-        unimplemented!()
+        self.bb_index as u32
     }
 
     fn get_backbuffer_texture(&self) -> &Texture {
-        unimplemented!()
+        &self.backbuffer_textures[self.bb_index]
     }
 
     fn get_backbuffer_pass(&self) -> &RenderPass {
-        unimplemented!()
+        &self.backbuffer_passes[self.bb_index]
     }
 
     fn get_backbuffer_pass_mut(&mut self) -> &mut RenderPass {
-        unimplemented!()
+        &mut self.backbuffer_passes[self.bb_index]
     }
 
     fn get_backbuffer_pass_no_clear(&self) -> &RenderPass {
-        unimplemented!()
+        &self.backbuffer_passes_no_clear[self.bb_index]
     }
 
     fn get_backbuffer_pass_no_clear_mut(&mut self) -> &mut RenderPass {
-        unimplemented!()
+        &mut self.backbuffer_passes_no_clear[self.bb_index]
     }
 
     fn swap(&mut self, device: &mut Device) {
-        unimplemented!()
+        self.frame_fence_value += 1;
+        self.frame_index += 1;
+        self.bb_index = (self.bb_index + 1) % self.num_buffers as usize;
+    }
+}
+
+impl CmdBuf {
+    fn push(&mut self, command: Command) {
+        if self.closed {
+            report(&self.info_queue, "error", format!("{} recorded into a closed command buffer, call reset first", command.name()));
+        }
+        if !command.is_supported_on(self.queue) {
+            report(&self.info_queue, "error", format!("{} recorded into a command buffer for the {:?} queue", command.name(), self.queue));
+        }
+        self.commands[self.bb_index].push(command);
+    }
+
+    fn validate(&self, condition: bool, msg: &str) {
+        if !condition {
+            report(&self.info_queue, "error", msg.to_string());
+        }
+    }
+
+    /// Returns the commands recorded into the current buffer since the last `reset`
+    pub fn get_commands(&self) -> &Vec<Command> {
+        &self.commands[self.bb_index]
+    }
+
+    /// Returns the number of recorded commands with the `name` in the current buffer, see `Command::name`
+    pub fn count_commands(&self, name: &str) -> usize {
+        self.commands[self.bb_index].iter().filter(|c| c.name() == name).count()
     }
 }
 
 impl super::CmdBuf<Device> for CmdBuf {
     fn reset(&mut self, swap_chain: &SwapChain) {
-        unimplemented!()
+        self.bb_index = swap_chain.bb_index % self.commands.len();
+        self.commands[self.bb_index].clear();
+        self.state = CmdBufState::default();
+        self.closed = false;
     }
 
     fn close(&mut self) -> Result<(), Error> {
-        unimplemented!()
+        self.closed = true;
+        if self.state.in_render_pass {
+            return Err(Error {
+                msg: "hotline_rs::gfx::null: command buffer closed inside a render pass, missing end_render_pass".to_string()
+            });
+        }
+        if self.state.event_depth != 0 {
+            return Err(Error {
+                msg: format!("hotline_rs::gfx::null: command buffer closed with unbalanced events (depth: {})", self.state.event_depth)
+            });
+        }
+        Ok(())
     }
 
     fn get_backbuffer_index(&self) -> u32 {
-        unimplemented!()
+        self.bb_index as u32
     }
 
     fn begin_render_pass(&mut self, render_pass: &RenderPass) {
-        unimplemented!()
+        self.validate(!self.state.in_render_pass, "begin_render_pass called inside an active render pass");
+        self.state.in_render_pass = true;
+        self.push(Command::BeginRenderPass {
+            formats: render_pass.targets.iter().map(|t| t.1.format).collect(),
+            depth_stencil_format: render_pass.depth_stencil.as_ref().map(|ds| ds.1.format),
+            rt_clear: render_pass.rt_clear,
+            ds_clear: render_pass.ds_clear,
+            format_hash: render_pass.format_hash,
+            targets: render_pass.targets.clone(),
+            depth_stencil: render_pass.depth_stencil.clone()
+        });
     }
 
     fn end_render_pass(&mut self) {
-        unimplemented!()
+        self.validate(self.state.in_render_pass, "end_render_pass called without a matching begin_render_pass");
+        self.state.in_render_pass = false;
+        self.push(Command::EndRenderPass);
     }
 
     fn begin_event(&mut self, colour: u32, name: &str) {
-        unimplemented!()
+        self.state.event_depth += 1;
+        self.push(Command::BeginEvent {
+            colour,
+            name: name.to_string()
+        });
     }
 
     fn end_event(&mut self) {
-        unimplemented!()
+        self.validate(self.state.event_depth > 0, "end_event called without a matching begin_event");
+        self.state.event_depth -= 1;
+        self.push(Command::EndEvent);
     }
 
     fn set_marker(&mut self, colour: u32, name: &str) {
-        unimplemented!()
+        self.push(Command::SetMarker {
+            colour,
+            name: name.to_string()
+        });
     }
 
     fn timestamp_query(&mut self, heap: &mut QueryHeap, resolve_buffer: &mut Buffer) {
-        unimplemented!()
+        let index = heap.alloc_index;
+        heap.alloc_index += 1;
+        self.validate(index < heap.capacity, "timestamp_query query heap is full");
+        self.push(Command::TimestampQuery {
+            index,
+            resolve_buffer: resolve_buffer.data.clone()
+        });
     }
 
     fn begin_query(&mut self, heap: &mut QueryHeap, query_type: QueryType) -> usize {
-        unimplemented!()
+        let index = heap.alloc_index;
+        heap.alloc_index += 1;
+        self.validate(index < heap.capacity, "begin_query query heap is full");
+        self.push(Command::BeginQuery {
+            query_type,
+            index
+        });
+        index
     }
 
     fn end_query(&mut self, heap: &mut QueryHeap, query_type: QueryType, index: usize, resolve_buffer: &mut Buffer) {
-        unimplemented!()
+        self.push(Command::EndQuery {
+            query_type,
+            index,
+            resolve_buffer: resolve_buffer.data.clone()
+        });
     }
 
     fn transition_barrier(&mut self, barrier: &TransitionBarrier<Device>) {
-        unimplemented!()
+        self.validate(barrier.texture.is_some() != barrier.buffer.is_some(),
+            "transition_barrier requires either a texture xor a buffer");
+        self.validate(barrier.state_before != barrier.state_after,
+            &format!("transition_barrier state_before and state_after are both {:?}", barrier.state_before));
+        self.push(Command::TransitionBarrier {
+            state_before: barrier.state_before,
+            state_after: barrier.state_after,
+            subresource: None
+        });
     }
 
     fn transition_barrier_subresource(&mut self, barrier: &TransitionBarrier<Device>, subresource: Subresource) {
-        unimplemented!()
+        if subresource == Subresource::ResolveResource {
+            if let Some(tex) = barrier.texture {
+                self.validate(tex.resolved_data.is_some(),
+                    "transition_barrier_subresource requested ResolveResource on a texture which is not resolvable");
+            }
+        }
+        self.push(Command::TransitionBarrier {
+            state_before: barrier.state_before,
+            state_after: barrier.state_after,
+            subresource: Some(subresource)
+        });
     }
 
     fn uav_barrier(&mut self, resource: UavResource<Device>) {
-        unimplemented!()
+        self.push(Command::UavBarrier);
     }
 
     fn set_viewport(&mut self, viewport: &Viewport) {
-        unimplemented!()
+        self.push(Command::SetViewport(*viewport));
     }
 
     fn set_scissor_rect(&mut self, scissor_rect: &ScissorRect) {
-        unimplemented!()
+        self.push(Command::SetScissorRect(*scissor_rect));
     }
 
     fn set_index_buffer(&mut self, buffer: &Buffer) {
-        unimplemented!()
+        self.validate(buffer.info.usage.contains(BufferUsage::INDEX), "set_index_buffer buffer was not created with BufferUsage::INDEX");
+        self.state.index_buffer = true;
+        self.push(Command::SetIndexBuffer {
+            format: buffer.info.format,
+            size_bytes: buffer.size_bytes,
+            data: buffer.data.clone()
+        });
     }
 
     fn set_vertex_buffer(&mut self, buffer: &Buffer, slot: u32) {
-        unimplemented!()
+        self.validate(buffer.info.usage.contains(BufferUsage::VERTEX), "set_vertex_buffer buffer was not created with BufferUsage::VERTEX");
+        self.push(Command::SetVertexBuffer {
+            slot,
+            stride: buffer.info.stride,
+            size_bytes: buffer.size_bytes,
+            data: buffer.data.clone()
+        });
     }
 
     fn set_render_pipeline(&mut self, pipeline: &RenderPipeline) {
-        unimplemented!()
+        self.state.render_pipeline = true;
+        self.push(Command::SetRenderPipeline {
+            state: pipeline.cpu_state.clone()
+        });
     }
 
     fn set_compute_pipeline(&mut self, pipeline: &ComputePipeline) {
-        unimplemented!()
+        self.state.compute_pipeline = true;
+        self.push(Command::SetComputePipeline);
     }
 
     fn set_raytracing_pipeline(&mut self, pipeline: &RaytracingPipeline) {
-        unimplemented!()
+        self.state.raytracing_pipeline = true;
+        self.push(Command::SetRaytracingPipeline);
     }
 
     fn set_heap<T: Pipeline>(&mut self, pipeline: &T, heap: &Heap) {
-        unimplemented!()
+        self.validate(heap.heap_type == HeapType::Shader, "set_heap requires a heap of HeapType::Shader");
+        self.push(Command::SetHeap {
+            heap_id: heap.id
+        });
     }
 
     fn set_binding<T: Pipeline>(&mut self, pipeline: &T, register: u32, space: u32, descriptor_type: DescriptorType, heap: &Heap, offset: usize) -> Option<()> {
-        unimplemented!()
-    }
-
-    fn push_render_constants<P: Pipeline, T: Sized>(&mut self, _pipeline: &P, _register: u32, _space: u32, _num_values: u32, _dest_offset: u32, _data: &[T]) -> Option<()> {
-        None
-    }
-
-    fn push_compute_constants<P: Pipeline, T: Sized>(&mut self, _pipeline: &P, _register: u32, _space: u32, _num_values: u32, _dest_offset: u32, _data: &[T]) -> Option<()> {
-        None
+        pipeline.get_pipeline_slot(register, space, descriptor_type)?;
+        self.validate(offset < heap.capacity,
+            &format!("set_binding offset {} is out of range of heap {} (capacity: {})", offset, heap.id, heap.capacity));
+        self.push(Command::SetBinding {
+            register,
+            space,
+            descriptor_type,
+            heap_id: heap.id,
+            offset,
+            texture: heap.views.lock().unwrap().get(&offset).cloned()
+        });
+        Some(())
+    }
+
+    fn push_render_constants<P: Pipeline, T: Sized>(&mut self, pipeline: &P, register: u32, space: u32, num_values: u32, dest_offset: u32, data: &[T]) -> Option<()> {
+        let slot = pipeline.get_pipeline_slot(register, space, DescriptorType::PushConstants)?;
+        self.validate(!matches!(P::get_pipeline_type(), PipelineType::Compute), "push_render_constants called with a compute pipeline");
+        if let Some(count) = slot.count {
+            self.validate(dest_offset + num_values <= count,
+                &format!("push_render_constants writes {} values at offset {} exceeding the slot size {}", num_values, dest_offset, count));
+        }
+        let bytes = super::slice_as_u8_slice(data);
+        let values = bytes.chunks_exact(4)
+            .take(num_values as usize)
+            .map(|c| u32::from_ne_bytes(c.try_into().unwrap()))
+            .collect();
+        self.push(Command::PushConstants {
+            pipeline_type: PipelineType::Render,
+            register,
+            space,
+            dest_offset,
+            data: values
+        });
+        Some(())
+    }
+
+    fn push_compute_constants<P: Pipeline, T: Sized>(&mut self, pipeline: &P, register: u32, space: u32, num_values: u32, dest_offset: u32, data: &[T]) -> Option<()> {
+        let slot = pipeline.get_pipeline_slot(register, space, DescriptorType::PushConstants)?;
+        self.validate(!matches!(P::get_pipeline_type(), PipelineType::Render), "push_compute_constants called with a render pipeline");
+        if let Some(count) = slot.count {
+            self.validate(dest_offset + num_values <= count,
+                &format!("push_compute_constants writes {} values at offset {} exceeding the slot size {}", num_values, dest_offset, count));
+        }
+        let bytes = super::slice_as_u8_slice(data);
+        let values = bytes.chunks_exact(4)
+            .take(num_values as usize)
+            .map(|c| u32::from_ne_bytes(c.try_into().unwrap()))
+            .collect();
+        self.push(Command::PushConstants {
+            pipeline_type: PipelineType::Compute,
+            register,
+            space,
+            dest_offset,
+            data: values
+        });
+        Some(())
     }
 
     fn draw_instanced(
@@ -228,7 +1021,14 @@ impl super::CmdBuf<Device> for CmdBuf {
         start_vertex: u32,
         start_instance: u32,
     ) {
-        unimplemented!()
+        self.validate(self.state.in_render_pass, "draw_instanced called outside of a render pass");
+        self.validate(self.state.render_pipeline, "draw_instanced called with no render pipeline set");
+        self.push(Command::DrawInstanced {
+            vertex_count,
+            instance_count,
+            start_vertex,
+            start_instance
+        });
     }
 
     fn draw_indexed_instanced(
@@ -239,11 +1039,25 @@ impl super::CmdBuf<Device> for CmdBuf {
         base_vertex: i32,
         start_instance: u32,
     ) {
-        unimplemented!()
+        self.validate(self.state.in_render_pass, "draw_indexed_instanced called outside of a render pass");
+        self.validate(self.state.render_pipeline, "draw_indexed_instanced called with no render pipeline set");
+        self.validate(self.state.index_buffer, "draw_indexed_instanced called with no index buffer set");
+        self.push(Command::DrawIndexedInstanced {
+            index_count,
+            instance_count,
+            start_index,
+            base_vertex,
+            start_instance
+        });
     }
 
     fn dispatch(&mut self, group_count: Size3, numthreads: Size3) {
-        unimplemented!()
+        self.validate(!self.state.in_render_pass, "dispatch called inside a render pass");
+        self.validate(self.state.compute_pipeline, "dispatch called with no compute pipeline set");
+        self.push(Command::Dispatch {
+            group_count,
+            numthreads
+        });
     }
 
     fn execute_indirect(
@@ -255,27 +1069,77 @@ impl super::CmdBuf<Device> for CmdBuf {
         counter_buffer: Option<&Buffer>,
         counter_buffer_offset: usize
     ) {
-        unimplemented!()
+        self.validate(argument_buffer.info.usage.contains(BufferUsage::INDIRECT_ARGUMENT_BUFFER),
+            "execute_indirect argument buffer was not created with BufferUsage::INDIRECT_ARGUMENT_BUFFER");
+        self.validate(argument_buffer_offset <= argument_buffer.size_bytes,
+            "execute_indirect argument_buffer_offset is out of range");
+        self.push(Command::ExecuteIndirect {
+            max_command_count,
+            argument_buffer_offset,
+            counter_buffer_offset
+        });
     }
 
     fn dispatch_rays(&mut self, sbt: &RaytracingShaderBindingTable, numthreads: Size3) {
-        unimplemented!()
+        self.validate(self.state.raytracing_pipeline, "dispatch_rays called with no raytracing pipeline set");
+        self.push(Command::DispatchRays {
+            numthreads
+        });
     }
 
     fn update_raytracing_tlas(&mut self, tlas: &RaytracingTLAS, instance_buffer: &Buffer, instance_count: usize, mode: AccelerationStructureRebuildMode) {
-        unimplemented!()
+        self.push(Command::UpdateRaytracingTLAS {
+            instance_count
+        });
     }
 
     fn resolve_texture_subresource(&mut self, texture: &Texture, subresource: u32) -> Result<(), Error> {
-        unimplemented!()
+        if let Some(resolved_data) = &texture.resolved_data {
+            self.push(Command::ResolveTextureSubresource {
+                subresource,
+                src: texture.data.clone(),
+                dst: resolved_data.clone()
+            });
+            Ok(())
+        }
+        else {
+            Err(Error {
+                msg: "hotline_rs::gfx::null: texture is not resolvable".to_string()
+            })
+        }
     }
 
     fn generate_mip_maps(&mut self, texture: &Texture, device: &Device, heap: &Heap) -> Result<(), Error> {
-        unimplemented!()
+        if !texture.info.usage.contains(TextureUsage::GENERATE_MIP_MAPS) {
+            return Err(Error {
+                msg: "hotline_rs::gfx::null: texture was not created with TextureUsage::GENERATE_MIP_MAPS".to_string()
+            });
+        }
+        self.push(Command::GenerateMipMaps {
+            data: texture.data.clone(),
+            info: texture.info
+        });
+        Ok(())
     }
 
     fn read_back_backbuffer(&mut self, swap_chain: &SwapChain) -> Result<ReadBackRequest, Error> {
-        unimplemented!()
+        let bb = &swap_chain.backbuffer_textures[swap_chain.bb_index];
+        let size = texture_size_bytes(&bb.info);
+        let row_pitch = super::row_pitch_for_format(bb.info.format, bb.info.width) as usize;
+        let dst = new_resource_data(size);
+        self.push(Command::ReadBackBackbuffer {
+            src: bb.data.clone(),
+            dst: dst.clone()
+        });
+        Ok(ReadBackRequest {
+            data: dst,
+            // the request is complete once this frame has been swapped
+            fence_value: swap_chain.frame_fence_value + 1,
+            format: bb.info.format,
+            size,
+            row_pitch,
+            slice_pitch: size
+        })
     }
 
     fn copy_buffer_region(
@@ -286,7 +1150,15 @@ impl super::CmdBuf<Device> for CmdBuf {
         src_offset: usize,
         num_bytes: usize
     ) {
-        unimplemented!()
+        self.validate(dst_offset + num_bytes <= dst_buffer.size_bytes, "copy_buffer_region destination range is out of bounds");
+        self.validate(src_offset + num_bytes <= src_buffer.size_bytes, "copy_buffer_region source range is out of bounds");
+        self.push(Command::CopyBufferRegion {
+            dst: dst_buffer.data.clone(),
+            dst_offset,
+            src: src_buffer.data.clone(),
+            src_offset,
+            num_bytes
+        });
     }
 
     fn copy_texture_region(
@@ -299,7 +1171,205 @@ impl super::CmdBuf<Device> for CmdBuf {
         src_texture: &Texture,
         src_region: Option<Region>
     ) {
-        unimplemented!()
+        self.validate(dst_texture.info.format == src_texture.info.format, "copy_texture_region source and destination formats do not match");
+        self.push(Command::CopyTextureRegion {
+            dst: dst_texture.data.clone(),
+            dst_info: dst_texture.info,
+            subresource_index,
+            dst_x,
+            dst_y,
+            dst_z,
+            src: src_texture.data.clone(),
+            src_info: src_texture.info,
+            src_region
+        });
+    }
+}
+
+impl Device {
+    /// Allocates views for a buffer into `heap` based on its usage flags
+    fn create_buffer_views(info: &BufferInfo, heap: &mut Heap, buffer: &mut Buffer) -> Result<(), Error> {
+        if info.usage.contains(BufferUsage::BUFFER_ONLY) {
+            return Ok(());
+        }
+        let mut allocs = HeapAllocs::new(heap);
+        if info.usage.contains(BufferUsage::CONSTANT_BUFFER) {
+            let index = heap.allocate()?;
+            buffer.cbv_index = Some(index);
+            allocs.indices.push(index);
+        }
+        if info.usage.contains(BufferUsage::SHADER_RESOURCE) {
+            let index = heap.allocate()?;
+            buffer.srv_index = Some(index);
+            allocs.indices.push(index);
+        }
+        if info.usage.contains(BufferUsage::UNORDERED_ACCESS) {
+            let index = heap.allocate()?;
+            buffer.uav_index = Some(index);
+            allocs.indices.push(index);
+        }
+        buffer.allocs = Some(allocs);
+        Ok(())
+    }
+
+    /// Creates a buffer without any views
+    fn create_buffer_storage<T: Sized>(info: &BufferInfo, data: Option<&[T]>) -> Result<Buffer, Error> {
+        let mut size_bytes = info.stride * info.num_elements;
+        let mut counter_offset = None;
+        if info.usage.contains(BufferUsage::APPEND_COUNTER) {
+            let offset = super::align_pow2(size_bytes as u64, COUNTER_ALIGNMENT as u64) as usize;
+            counter_offset = Some(offset);
+            size_bytes = offset + 4;
+        }
+        let storage = new_resource_data(size_bytes);
+        if let Some(data) = data {
+            let bytes = super::slice_as_u8_slice(data);
+            if bytes.len() > size_bytes {
+                return Err(Error {
+                    msg: format!("hotline_rs::gfx::null: buffer data size {} exceeds buffer size {}", bytes.len(), size_bytes)
+                });
+            }
+            storage.lock().unwrap()[..bytes.len()].copy_from_slice(bytes);
+        }
+        Ok(Buffer {
+            info: *info,
+            data: storage,
+            size_bytes,
+            srv_index: None,
+            uav_index: None,
+            cbv_index: None,
+            counter_offset,
+            allocs: None
+        })
+    }
+
+    /// Returns the number of command buffers which have been executed by the device
+    pub fn get_execute_count(&self) -> u64 {
+        self.execute_count.load(Ordering::SeqCst)
+    }
+
+    /// Returns the submissions and fence operations issued to the device queues since the last call to `clear_queue_ops`
+    pub fn get_queue_ops(&self) -> Vec<QueueOp> {
+        self.queue_ops.lock().unwrap().clone()
+    }
+
+    /// Clears the recorded queue operations
+    pub fn clear_queue_ops(&self) {
+        self.queue_ops.lock().unwrap().clear();
+    }
+
+    fn execute_copy_texture_region(
+        dst: &ResourceData,
+        dst_info: &TextureInfo,
+        subresource_index: u32,
+        dst_pos: (u32, u32, u32),
+        src: &ResourceData,
+        src_info: &TextureInfo,
+        src_region: &Option<Region>
+    ) {
+        let region = src_region.unwrap_or(Region {
+            left: 0,
+            top: 0,
+            front: 0,
+            right: src_info.width as u32,
+            bottom: src_info.height as u32,
+            back: src_info.depth
+        });
+        let block_size = super::block_size_for_format(src_info.format) as usize;
+        let src_row_pitch = super::row_pitch_for_format(src_info.format, src_info.width) as usize;
+        let src_slice_pitch = super::slice_pitch_for_format(src_info.format, src_info.width, src_info.height) as usize;
+        let (dst_offset, dst_width, dst_height, _) = texture_subresource_offset(dst_info, subresource_index);
+        let dst_row_pitch = super::row_pitch_for_format(dst_info.format, dst_width) as usize;
+        let dst_slice_pitch = super::slice_pitch_for_format(dst_info.format, dst_width, dst_height) as usize;
+        let row_bytes = (region.right.saturating_sub(region.left)) as usize * block_size;
+
+        // take a copy of the source first, src and dst may be the same resource
+        let src_data = src.lock().unwrap().clone();
+        let mut dst_data = dst.lock().unwrap();
+        for z in region.front..region.back.max(region.front + 1) {
+            for y in region.top..region.bottom {
+                let src_start = z as usize * src_slice_pitch + y as usize * src_row_pitch + region.left as usize * block_size;
+                let dz = (dst_pos.2 + z - region.front) as usize;
+                let dy = (dst_pos.1 + y - region.top) as usize;
+                let dst_start = dst_offset + dz * dst_slice_pitch + dy * dst_row_pitch + dst_pos.0 as usize * block_size;
+                if src_start + row_bytes <= src_data.len() && dst_start + row_bytes <= dst_data.len() {
+                    dst_data[dst_start..dst_start + row_bytes].copy_from_slice(&src_data[src_start..src_start + row_bytes]);
+                }
+            }
+        }
+    }
+
+    /// Generates mips with a 2x2 box filter for 8-bit 4 component formats, other formats are left untouched
+    /// Clears the first mip of a depth stencil `slice`, `None` values preserve the existing depth or stencil
+    fn execute_clear_depth_stencil(data: &ResourceData, info: &TextureInfo, slice: usize, clear: &ClearDepthStencil) {
+        if clear.depth.is_none() && clear.stencil.is_none() {
+            return;
+        }
+        let mut data = data.lock().unwrap();
+        let block_size = super::block_size_for_format(info.format) as usize;
+        let start = texture_slice_offset(info, slice);
+        let size = super::size_for_format(info.format, info.width, info.height, info.depth) as usize;
+        let end = (start + size).min(data.len());
+        if start < end {
+            for texel in data[start..end].chunks_exact_mut(block_size) {
+                let (depth, stencil) = cpu::decode_depth(info.format, texel);
+                let cleared = cpu::encode_depth(info.format, clear.depth.unwrap_or(depth), clear.stencil.unwrap_or(stencil));
+                texel.copy_from_slice(&cleared);
+            }
+        }
+    }
+
+    /// Rasterises a draw into the targets of the current render pass, returns the number of primitives drawn
+    fn execute_draw(
+        draw_state: &cpu::DrawState,
+        targets: &[(ResourceData, TextureInfo, usize)],
+        depth_stencil: &Option<(ResourceData, TextureInfo, usize)>,
+        args: &cpu::DrawArgs
+    ) -> usize {
+        let mut target_guards : Vec<_> = targets.iter().map(|(data, info, slice)| (data.lock().unwrap(), info, *slice)).collect();
+        let mut cpu_targets : Vec<cpu::Target> = target_guards.iter_mut().map(|(data, info, slice)| cpu::Target {
+            data: data.as_mut_slice(),
+            info: **info,
+            slice: *slice
+        }).collect();
+        if let Some((data, info, slice)) = depth_stencil {
+            let mut data = data.lock().unwrap();
+            let mut depth = cpu::Target {
+                data: data.as_mut_slice(),
+                info: *info,
+                slice: *slice
+            };
+            cpu::draw(draw_state, &mut cpu_targets, Some(&mut depth), args)
+        }
+        else {
+            cpu::draw(draw_state, &mut cpu_targets, None, args)
+        }
+    }
+
+    fn execute_generate_mip_maps(data: &ResourceData, info: &TextureInfo) {
+        if super::block_size_for_format(info.format) != 4 || super::components_for_format(info.format) != 4 {
+            return;
+        }
+        let mut data = data.lock().unwrap();
+        for slice in 0..info.array_layers {
+            for mip in 1..info.mip_levels {
+                let (src_offset, src_w, src_h, _) = texture_subresource_offset(info, slice * info.mip_levels + mip - 1);
+                let (dst_offset, dst_w, dst_h, _) = texture_subresource_offset(info, slice * info.mip_levels + mip);
+                for y in 0..dst_h as usize {
+                    for x in 0..dst_w as usize {
+                        for c in 0..4 {
+                            let mut sum = 0;
+                            for (ox, oy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                                let sx = (x * 2 + ox).min(src_w as usize - 1);
+                                let sy = (y * 2 + oy).min(src_h as usize - 1);
+                                sum += data[src_offset + (sy * src_w as usize + sx) * 4 + c] as u32;
+                            }
+                            data[dst_offset + (y * dst_w as usize + x) * 4 + c] = (sum / 4) as u8;
+                        }
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -320,17 +1390,52 @@ impl super::Device for Device {
     type RaytracingShaderBindingTable = RaytracingShaderBindingTable;
     type RaytracingBLAS = RaytracingBLAS;
     type RaytracingTLAS = RaytracingTLAS;
-
-    fn create(info: &DeviceInfo) -> Self {
-        unimplemented!()
+    type Fence = Fence;
+
+    fn create(info: &DeviceInfo) -> Result<Self, Error> {
+        let name = "hotline_rs::gfx::null::Device".to_string();
+        Ok(Device {
+            adapter_info: AdapterInfo {
+                name: name.to_string(),
+                description: "Headless null device (CPU)".to_string(),
+                dedicated_video_memory: 0,
+                dedicated_system_memory: 0,
+                shared_system_memory: 0,
+                available: vec![name]
+            },
+            feature_flags: DeviceFeatureFlags::NONE,
+            shader_heap: Heap::create(&HeapInfo {
+                heap_type: HeapType::Shader,
+                num_descriptors: info.shader_heap_size,
+                debug_name: Some("device_shader_heap".to_string())
+            }),
+            rtv_heap: Heap::create(&HeapInfo {
+                heap_type: HeapType::RenderTarget,
+                num_descriptors: info.render_target_heap_size,
+                debug_name: Some("device_rtv_heap".to_string())
+            }),
+            dsv_heap: Heap::create(&HeapInfo {
+                heap_type: HeapType::DepthStencil,
+                num_descriptors: info.depth_stencil_heap_size,
+                debug_name: Some("device_dsv_heap".to_string())
+            }),
+            info_queue: new_info_queue(),
+            execute_count: Arc::new(AtomicU64::new(0)),
+            queue_ops: Arc::new(Mutex::new(Vec::new())),
+            fence_count: Arc::new(AtomicU64::new(0))
+        })
     }
 
     fn create_heap(&mut self, info: &HeapInfo) -> Self::Heap {
-        unimplemented!()
+        Heap::create(info)
     }
 
     fn create_query_heap(&self, info: &QueryHeapInfo) -> Self::QueryHeap {
-        unimplemented!()
+        QueryHeap {
+            heap_type: info.heap_type,
+            alloc_index: 0,
+            capacity: info.num_queries
+        }
     }
 
     fn create_swap_chain<A: App>(
@@ -338,15 +1443,56 @@ impl super::Device for Device {
         info: &SwapChainInfo,
         window: &A::Window,
     ) -> Result<Self::SwapChain, Error> {
-        unimplemented!()
+        if info.num_buffers == 0 {
+            return Err(Error {
+                msg: "hotline_rs::gfx::null: swap chain requires at least 1 buffer".to_string()
+            });
+        }
+        let size = window.get_size();
+        let mut swap_chain = SwapChain {
+            num_buffers: info.num_buffers,
+            format: info.format,
+            width: size.x,
+            height: size.y,
+            clear_colour: info.clear_colour,
+            bb_index: 0,
+            frame_fence_value: 0,
+            frame_index: 1,
+            backbuffer_textures: Vec::new(),
+            backbuffer_passes: Vec::new(),
+            backbuffer_passes_no_clear: Vec::new()
+        };
+        swap_chain.create_backbuffers(self)?;
+        Ok(swap_chain)
     }
 
     fn create_cmd_buf(&self, num_buffers: u32) -> Self::CmdBuf {
-        unimplemented!()
+        self.create_cmd_buf_for_queue(num_buffers, QueueType::Graphics)
+    }
+
+    fn create_cmd_buf_for_queue(&self, num_buffers: u32, queue: QueueType) -> Self::CmdBuf {
+        CmdBuf {
+            bb_index: 0,
+            queue,
+            commands: vec![Vec::new(); num_buffers.max(1) as usize],
+            state: CmdBufState::default(),
+            closed: false,
+            info_queue: self.info_queue.clone()
+        }
+    }
+
+    fn create_fence(&self) -> Result<Self::Fence, Error> {
+        Ok(Fence {
+            id: self.fence_count.fetch_add(1, Ordering::SeqCst),
+            value: Arc::new(AtomicU64::new(0))
+        })
     }
 
     fn create_shader<T: Sized>(&self, info: &ShaderInfo, src: &[T]) -> Result<Self::Shader, Error> {
-        unimplemented!()
+        Ok(Shader {
+            shader_type: info.shader_type,
+            data: super::slice_as_u8_slice(src).to_vec()
+        })
     }
 
     fn create_buffer<T: Sized>(
@@ -354,7 +1500,9 @@ impl super::Device for Device {
         info: &BufferInfo,
         data: Option<&[T]>,
     ) -> Result<Self::Buffer, Error> {
-        unimplemented!()
+        let mut buffer = Self::create_buffer_storage(info, data)?;
+        Self::create_buffer_views(info, &mut self.shader_heap, &mut buffer)?;
+        Ok(buffer)
     }
 
     fn create_buffer_with_heap<T: Sized>(
@@ -363,14 +1511,23 @@ impl super::Device for Device {
         data: Option<&[T]>,
         heap: &mut Self::Heap
     ) -> Result<Self::Buffer, Error> {
-        unimplemented!()
+        let mut buffer = Self::create_buffer_storage(info, data)?;
+        Self::create_buffer_views(info, heap, &mut buffer)?;
+        Ok(buffer)
     }
 
     fn create_read_back_buffer(
         &mut self,
         size: usize,
     ) -> Result<Self::Buffer, Error> {
-        unimplemented!()
+        Self::create_buffer_storage::<u8>(&BufferInfo {
+            usage: BufferUsage::NONE,
+            cpu_access: CpuAccessFlags::READ,
+            format: Format::Unknown,
+            stride: size,
+            num_elements: 1,
+            initial_state: ResourceState::CopyDst
+        }, None)
     }
 
     fn create_texture<T: Sized>(
@@ -378,7 +1535,7 @@ impl super::Device for Device {
         info: &TextureInfo,
         data: Option<&[T]>,
     ) -> Result<Self::Texture, Error> {
-        unimplemented!()
+        self.create_texture_with_heaps(info, TextureHeapInfo::default(), data)
     }
 
     fn create_texture_with_heaps<T: Sized>(
@@ -387,67 +1544,247 @@ impl super::Device for Device {
         heaps: TextureHeapInfo<Self>,
         data: Option<&[T]>,
     ) -> Result<Self::Texture, Error> {
-        unimplemented!()
+        if info.width == 0 || info.height == 0 {
+            return Err(Error {
+                msg: format!("hotline_rs::gfx::null: invalid texture dimensions {}x{}", info.width, info.height)
+            });
+        }
+        let size = texture_size_bytes(info);
+        let storage = new_resource_data(size);
+        if let Some(data) = data {
+            let bytes = super::slice_as_u8_slice(data);
+            let len = bytes.len().min(size);
+            storage.lock().unwrap()[..len].copy_from_slice(&bytes[..len]);
+        }
+
+        let mut tex = Texture {
+            info: *info,
+            data: storage,
+            resolved_data: if info.samples > 1 { Some(new_resource_data(size)) } else { None },
+            srv_index: None,
+            msaa_srv_index: None,
+            uav_index: None,
+            subresource_uav_index: Vec::new(),
+            rtv_index: Vec::new(),
+            dsv_index: Vec::new(),
+            heap_id: None,
+            allocs: Vec::new()
+        };
+
+        let num_slices = info.array_layers.max(info.depth) as usize;
+
+        // shader resource and unordered access views
+        let shader_heap = if let Some(heap) = heaps.shader { heap } else { &mut self.shader_heap };
+        let mut allocs = HeapAllocs::new(shader_heap);
+        if info.usage.contains(TextureUsage::SHADER_RESOURCE) {
+            let index = shader_heap.allocate()?;
+            tex.srv_index = Some(index);
+            allocs.indices.push(index);
+            let srv_data = tex.resolved_data.as_ref().unwrap_or(&tex.data).clone();
+            shader_heap.views.lock().unwrap().insert(index, (srv_data, tex.info));
+            if info.samples > 1 {
+                let index = shader_heap.allocate()?;
+                tex.msaa_srv_index = Some(index);
+                allocs.indices.push(index);
+                shader_heap.views.lock().unwrap().insert(index, (tex.data.clone(), tex.info));
+            }
+        }
+        let generate_mips = info.usage.contains(TextureUsage::GENERATE_MIP_MAPS) && info.samples == 1;
+        if info.usage.contains(TextureUsage::UNORDERED_ACCESS) || generate_mips {
+            let index = shader_heap.allocate()?;
+            tex.uav_index = Some(index);
+            allocs.indices.push(index);
+            for _ in 0..info.mip_levels as usize * info.array_layers as usize {
+                let index = shader_heap.allocate()?;
+                tex.subresource_uav_index.push(index);
+                allocs.indices.push(index);
+            }
+        }
+        tex.heap_id = Some(shader_heap.id);
+        tex.allocs.push(allocs);
+
+        // render target views per array slice
+        if info.usage.contains(TextureUsage::RENDER_TARGET) {
+            let rtv_heap = if let Some(heap) = heaps.render_target { heap } else { &mut self.rtv_heap };
+            let mut allocs = HeapAllocs::new(rtv_heap);
+            for _ in 0..num_slices {
+                let index = rtv_heap.allocate()?;
+                tex.rtv_index.push(index);
+                allocs.indices.push(index);
+            }
+            tex.allocs.push(allocs);
+        }
+
+        // depth stencil views per array slice
+        if info.usage.contains(TextureUsage::DEPTH_STENCIL) {
+            let dsv_heap = if let Some(heap) = heaps.depth_stencil { heap } else { &mut self.dsv_heap };
+            let mut allocs = HeapAllocs::new(dsv_heap);
+            for _ in 0..num_slices {
+                let index = dsv_heap.allocate()?;
+                tex.dsv_index.push(index);
+                allocs.indices.push(index);
+            }
+            tex.allocs.push(allocs);
+        }
+
+        Ok(tex)
     }
 
     fn create_render_pipeline(
         &self,
         info: &RenderPipelineInfo<Self>,
     ) -> Result<Self::RenderPipeline, Error> {
-        unimplemented!()
+        if let Some(vs) = info.vs {
+            if vs.shader_type != ShaderType::Vertex {
+                return Err(Error {
+                    msg: "hotline_rs::gfx::null: render pipeline vs is not a vertex shader".to_string()
+                });
+            }
+        }
+        let (slots, slot_lookup) = create_slot_lookup(&info.pipeline_layout);
+        Ok(RenderPipeline {
+            slots,
+            slot_lookup,
+            input_layout: info.input_layout.clone(),
+            pipeline_layout: info.pipeline_layout.clone(),
+            raster_info: info.raster_info,
+            depth_stencil_info: info.depth_stencil_info,
+            topology: info.topology,
+            format_hash: info.pass.map(|pass| pass.format_hash),
+            cpu_state: Arc::new(cpu::PipelineState {
+                program: cpu::Program::from_pipeline(&info.input_layout, &info.pipeline_layout, &info.raster_info),
+                input_layout: info.input_layout.clone(),
+                raster_info: info.raster_info,
+                depth_stencil_info: info.depth_stencil_info,
+                blend: info.blend_info.render_target.clone(),
+                topology: info.topology
+            })
+        })
     }
 
     fn create_render_pass(&self, info: &RenderPassInfo<Self>) -> Result<Self::RenderPass, Error> {
-        unimplemented!()
+        let mut sample_count = None;
+        let mut targets = Vec::new();
+        for target in &info.render_targets {
+            if !target.info.usage.contains(TextureUsage::RENDER_TARGET) {
+                return Err(Error {
+                    msg: "hotline_rs::gfx::null: render pass target was not created with TextureUsage::RENDER_TARGET".to_string()
+                });
+            }
+            if let Some(samples) = sample_count {
+                if samples != target.info.samples {
+                    return Err(Error {
+                        msg: format!("Sample counts must match on all targets: expected {} samples, found {}",
+                            samples,
+                            target.info.samples
+                        )
+                    });
+                }
+            }
+            sample_count = Some(target.info.samples);
+            targets.push((target.data.clone(), target.info, info.array_slice));
+        }
+
+        let depth_stencil = if let Some(ds) = info.depth_stencil {
+            if !ds.info.usage.contains(TextureUsage::DEPTH_STENCIL) {
+                return Err(Error {
+                    msg: "hotline_rs::gfx::null: render pass depth stencil was not created with TextureUsage::DEPTH_STENCIL".to_string()
+                });
+            }
+            Some((ds.data.clone(), ds.info, info.array_slice))
+        }
+        else {
+            None
+        };
+
+        // hash together the rt, ds and sample count to get a unique hash for format combo
+        let mut fmthash = DefaultHasher::new();
+        let sample_count = sample_count.unwrap_or(1);
+        sample_count.hash(&mut fmthash);
+        depth_stencil.as_ref().map(|ds| ds.1.format).hash(&mut fmthash);
+        for target in &targets {
+            target.1.format.hash(&mut fmthash);
+        }
+
+        Ok(RenderPass {
+            targets,
+            depth_stencil,
+            rt_clear: info.rt_clear,
+            ds_clear: info.ds_clear,
+            sample_count,
+            format_hash: fmthash.finish()
+        })
     }
 
     fn create_compute_pipeline(
         &self,
         info: &ComputePipelineInfo<Self>,
     ) -> Result<Self::ComputePipeline, Error> {
-        unimplemented!()
+        if info.cs.shader_type != ShaderType::Compute {
+            return Err(Error {
+                msg: "hotline_rs::gfx::null: compute pipeline cs is not a compute shader".to_string()
+            });
+        }
+        let (slots, slot_lookup) = create_slot_lookup(&info.pipeline_layout);
+        Ok(ComputePipeline {
+            slots,
+            slot_lookup,
+            pipeline_layout: info.pipeline_layout.clone()
+        })
     }
 
     fn create_raytracing_pipeline(
         &self,
         info: &RaytracingPipelineInfo<Self>,
     ) -> Result<Self::RaytracingPipeline, Error> {
-        unimplemented!()
+        let (slots, slot_lookup) = create_slot_lookup(&info.pipeline_layout);
+        Ok(RaytracingPipeline {
+            slots,
+            slot_lookup
+        })
     }
 
     fn create_raytracing_shader_binding_table(
         &self,
         info: &RaytracingShaderBindingTableInfo<Self>
     ) -> Result<Self::RaytracingShaderBindingTable, Error> {
-        unimplemented!()
+        Ok(RaytracingShaderBindingTable)
     }
 
     fn create_raytracing_blas(
         &mut self,
         info: &RaytracingBLASInfo<Self>
     ) -> Result<Self::RaytracingBLAS, Error> {
-        unimplemented!()
+        Ok(RaytracingBLAS)
     }
 
     fn create_upload_buffer<T: Sized>(
         &mut self,
         data: &[T]
     ) -> Result<Buffer, Error> {
-        unimplemented!()
+        Self::create_buffer_storage(&BufferInfo {
+            usage: BufferUsage::UPLOAD,
+            cpu_access: CpuAccessFlags::WRITE,
+            format: Format::Unknown,
+            stride: std::mem::size_of::<T>(),
+            num_elements: data.len(),
+            initial_state: ResourceState::GenericRead
+        }, Some(data))
     }
 
     fn create_raytracing_instance_buffer(
         &mut self,
         instances: &Vec<RaytracingInstanceInfo<Self>>
     ) -> Result<Buffer, Error> {
-        unimplemented!()
+        let transforms : Vec<[f32; 12]> = instances.iter().map(|i| i.transform).collect();
+        self.create_upload_buffer(&transforms)
     }
 
     fn create_raytracing_tlas(
         &mut self,
         info: &RaytracingTLASInfo<Self>
     ) -> Result<Self::RaytracingTLAS, Error> {
-        unimplemented!()
+        Ok(RaytracingTLAS)
     }
 
     fn create_resource_view(
@@ -456,7 +1793,56 @@ impl super::Device for Device {
         resource: Resource<Device>,
         heap: &mut Heap
     ) -> Result<usize, super::Error> {
-        unimplemented!()
+        if heap.heap_type != HeapType::Shader {
+            return Err(Error {
+                msg: "hotline_rs::gfx::null: resource views must be created in a heap of HeapType::Shader".to_string()
+            });
+        }
+        if let Resource::Buffer(buffer) = resource {
+            let end = (info.first_element + info.num_elements) * info.structure_byte_size;
+            if end > buffer.size_bytes {
+                return Err(Error {
+                    msg: format!("hotline_rs::gfx::null: resource view range {} exceeds buffer size {}", end, buffer.size_bytes)
+                });
+            }
+        }
+        heap.allocate()
+    }
+
+    fn replace_texture_srv(
+        &mut self,
+        target: &mut Texture,
+        texture: &mut Texture,
+        heap: &mut Heap
+    ) -> Result<(), super::Error> {
+        let (target_index, index) = match (target.srv_index, texture.srv_index) {
+            (Some(target_index), Some(index)) if target.heap_id == Some(heap.id) && texture.heap_id == Some(heap.id) => {
+                (target_index, index)
+            }
+            _ => {
+                return Err(Error {
+                    msg: "hotline_rs::gfx::null: replace_texture_srv requires both textures to have a shader resource view in `heap`".to_string()
+                });
+            }
+        };
+
+        // the view of `texture` is written into the slot of `target`
+        let mut views = heap.views.lock().unwrap();
+        if let Some(view) = views.get(&index).cloned() {
+            views.insert(target_index, view);
+        }
+        drop(views);
+
+        // exchange ownership of the slots so they are released by the other texture
+        for allocs in &mut target.allocs {
+            allocs.replace(heap, target_index, index);
+        }
+        for allocs in &mut texture.allocs {
+            allocs.replace(heap, index, target_index);
+        }
+        target.srv_index = Some(index);
+        texture.srv_index = Some(target_index);
+        Ok(())
     }
 
     fn create_raytracing_tlas_with_heap(
@@ -464,7 +1850,8 @@ impl super::Device for Device {
         info: &RaytracingTLASInfo<Self>,
         heap: &mut Heap
     ) -> Result<RaytracingTLAS, Error> {
-        unimplemented!()
+        heap.allocate()?;
+        Ok(RaytracingTLAS)
     }
 
     fn create_indirect_render_command<T: Sized>(
@@ -472,231 +1859,609 @@ impl super::Device for Device {
         arguments: Vec<IndirectArgument>,
         pipeline: Option<&Self::RenderPipeline>
     ) -> Result<Self::CommandSignature, super::Error> {
-        unimplemented!()
+        Ok(CommandSignature {
+            arguments: arguments.iter().map(|a| a.argument_type).collect()
+        })
     }
 
     fn execute(&mut self, cmd: &Self::CmdBuf) {
-        unimplemented!()
+        self.execute_on_queue(QueueType::Graphics, cmd);
+    }
+
+    fn execute_on_queue(&mut self, queue: QueueType, cmd: &Self::CmdBuf) {
+        if cmd.queue != queue {
+            report(&self.info_queue, "error", format!("command buffer created for the {:?} queue executed on the {:?} queue", cmd.queue, queue));
+        }
+        self.queue_ops.lock().unwrap().push(QueueOp::Execute(queue));
+        if !cmd.closed {
+            report(&self.info_queue, "error", "execute called on a command buffer which has not been closed".to_string());
+        }
+        self.execute_count.fetch_add(1, Ordering::SeqCst);
+
+        let mut stats : HashMap<usize, PipelineStatistics> = HashMap::new();
+        let mut draw_state = cpu::DrawState::default();
+        let mut pass_targets = Vec::new();
+        let mut pass_depth_stencil = None;
+        for command in &cmd.commands[cmd.bb_index] {
+            match command {
+                Command::BeginRenderPass { rt_clear, ds_clear, targets, depth_stencil, .. } => {
+                    if let Some(clear) = rt_clear {
+                        for (data, info, slice) in targets {
+                            fill_texture_slice(data, info, *slice, &cpu::encode_texel(info.format, [clear.r, clear.g, clear.b, clear.a]));
+                        }
+                    }
+                    if let (Some(clear), Some((data, info, slice))) = (ds_clear, depth_stencil) {
+                        Self::execute_clear_depth_stencil(data, info, *slice, clear);
+                    }
+                    pass_targets = targets.clone();
+                    pass_depth_stencil = depth_stencil.clone();
+                    draw_state.viewport = None;
+                    draw_state.scissor = None;
+                }
+                Command::EndRenderPass => {
+                    pass_targets.clear();
+                    pass_depth_stencil = None;
+                }
+                Command::SetViewport(viewport) => {
+                    draw_state.viewport = Some(*viewport);
+                }
+                Command::SetScissorRect(scissor_rect) => {
+                    draw_state.scissor = Some(*scissor_rect);
+                }
+                Command::SetRenderPipeline { state } => {
+                    draw_state.pipeline = Some(state.clone());
+                }
+                Command::SetVertexBuffer { slot, stride, data, .. } => {
+                    draw_state.vertex_buffers.insert(*slot, (Arc::new(data.lock().unwrap().clone()), *stride));
+                }
+                Command::SetIndexBuffer { format, data, .. } => {
+                    draw_state.index_buffer = Some((Arc::new(data.lock().unwrap().clone()), *format));
+                }
+                Command::SetBinding { register, texture: Some((data, info)), .. } => {
+                    draw_state.textures.insert(*register, (Arc::new(data.lock().unwrap().clone()), *info));
+                }
+                Command::PushConstants { pipeline_type: PipelineType::Render, register, dest_offset, data, .. } => {
+                    let constants = draw_state.constants.entry(*register).or_default();
+                    let end = *dest_offset as usize + data.len();
+                    if constants.len() < end {
+                        constants.resize(end, 0);
+                    }
+                    constants[*dest_offset as usize..end].copy_from_slice(data);
+                }
+                Command::TimestampQuery { index, resolve_buffer } => {
+                    let mut data = resolve_buffer.lock().unwrap();
+                    let offset = index * 8;
+                    if offset + 8 <= data.len() {
+                        data[offset..offset + 8].copy_from_slice(&timestamp_ns().to_ne_bytes());
+                    }
+                }
+                Command::BeginQuery { query_type: QueryType::PipelineStatistics, index } => {
+                    stats.insert(*index, PipelineStatistics {
+                        input_assembler_vertices: 0,
+                        input_assembler_primitives: 0,
+                        vertex_shader_invocations: 0,
+                        pixel_shader_primitives: 0,
+                        compute_shader_invocations: 0
+                    });
+                }
+                Command::EndQuery { query_type: QueryType::PipelineStatistics, index, resolve_buffer } => {
+                    if let Some(result) = stats.remove(index) {
+                        let mut data = resolve_buffer.lock().unwrap();
+                        let size = std::mem::size_of::<PipelineStatistics>();
+                        if size <= data.len() {
+                            data[..size].copy_from_slice(super::as_u8_slice(&result));
+                        }
+                    }
+                }
+                Command::DrawInstanced { vertex_count, instance_count, start_vertex, start_instance } => {
+                    let primitives = Self::execute_draw(&draw_state, &pass_targets, &pass_depth_stencil, &cpu::DrawArgs {
+                        vertex_count: *vertex_count,
+                        instance_count: *instance_count,
+                        start_vertex: *start_vertex,
+                        start_instance: *start_instance,
+                        indexed: None
+                    });
+                    for s in stats.values_mut() {
+                        s.input_assembler_vertices += (*vertex_count * *instance_count) as u64;
+                        s.input_assembler_primitives += (*vertex_count / 3 * *instance_count) as u64;
+                        s.vertex_shader_invocations += (*vertex_count * *instance_count) as u64;
+                        s.pixel_shader_primitives += primitives as u64;
+                    }
+                }
+                Command::DrawIndexedInstanced { index_count, instance_count, start_index, base_vertex, start_instance } => {
+                    let primitives = Self::execute_draw(&draw_state, &pass_targets, &pass_depth_stencil, &cpu::DrawArgs {
+                        vertex_count: *index_count,
+                        instance_count: *instance_count,
+                        start_vertex: 0,
+                        start_instance: *start_instance,
+                        indexed: Some((*start_index, *base_vertex))
+                    });
+                    for s in stats.values_mut() {
+                        s.input_assembler_vertices += (*index_count * *instance_count) as u64;
+                        s.input_assembler_primitives += (*index_count / 3 * *instance_count) as u64;
+                        s.vertex_shader_invocations += (*index_count * *instance_count) as u64;
+                        s.pixel_shader_primitives += primitives as u64;
+                    }
+                }
+                Command::Dispatch { group_count, numthreads } => {
+                    let groups = (group_count.x * group_count.y * group_count.z) as u64;
+                    let threads = (numthreads.x * numthreads.y * numthreads.z) as u64;
+                    for s in stats.values_mut() {
+                        s.compute_shader_invocations += groups * threads;
+                    }
+                }
+                Command::ResolveTextureSubresource { src, dst, .. } => {
+                    let src = src.lock().unwrap().clone();
+                    let mut dst = dst.lock().unwrap();
+                    let len = src.len().min(dst.len());
+                    dst[..len].copy_from_slice(&src[..len]);
+                }
+                Command::GenerateMipMaps { data, info } => {
+                    Self::execute_generate_mip_maps(data, info);
+                }
+                Command::ReadBackBackbuffer { src, dst } => {
+                    let src = src.lock().unwrap();
+                    let mut dst = dst.lock().unwrap();
+                    let len = src.len().min(dst.len());
+                    dst[..len].copy_from_slice(&src[..len]);
+                }
+                Command::CopyBufferRegion { dst, dst_offset, src, src_offset, num_bytes } => {
+                    let src = src.lock().unwrap().clone();
+                    let mut dst = dst.lock().unwrap();
+                    if src_offset + num_bytes <= src.len() && dst_offset + num_bytes <= dst.len() {
+                        dst[*dst_offset..dst_offset + num_bytes].copy_from_slice(&src[*src_offset..src_offset + num_bytes]);
+                    }
+                }
+                Command::CopyTextureRegion {
+                    dst, dst_info, subresource_index, dst_x, dst_y, dst_z, src, src_info, src_region
+                } => {
+                    Self::execute_copy_texture_region(
+                        dst, dst_info, *subresource_index, (*dst_x, *dst_y, *dst_z), src, src_info, src_region);
+                }
+                _ => ()
+            }
+        }
+    }
+
+    fn signal_fence(&mut self, queue: QueueType, fence: &Self::Fence, value: u64) {
+        self.queue_ops.lock().unwrap().push(QueueOp::Signal { queue, fence: fence.id, value });
+        if fence.value.fetch_max(value, Ordering::SeqCst) > value {
+            report(&self.info_queue, "warning", format!("fence {} signalled with {} which is less than its current value", fence.id, value));
+        }
+    }
+
+    fn wait_fence(&mut self, queue: QueueType, fence: &Self::Fence, value: u64) {
+        self.queue_ops.lock().unwrap().push(QueueOp::Wait { queue, fence: fence.id, value });
+        // work is executed immediately in submission order, so a wait for an unsignalled value would deadlock a real gpu
+        if fence.value.load(Ordering::SeqCst) < value {
+            report(&self.info_queue, "error", format!("{:?} queue waits for fence {} to reach {} which has not been signalled", queue, fence.id, value));
+        }
     }
 
     fn get_shader_heap(&self) -> &Self::Heap {
-        unimplemented!()
+        &self.shader_heap
     }
 
     fn get_shader_heap_mut(&mut self) -> &mut Self::Heap {
-        unimplemented!()
+        &mut self.shader_heap
     }
 
     fn cleanup_dropped_resources(&mut self, swap_chain: &Self::SwapChain) {
-        unimplemented!()
+        self.shader_heap.cleanup_dropped_resources(swap_chain);
+        self.rtv_heap.cleanup_dropped_resources(swap_chain);
+        self.dsv_heap.cleanup_dropped_resources(swap_chain);
     }
 
     fn get_adapter_info(&self) -> &AdapterInfo {
-        unimplemented!()
+        &self.adapter_info
     }
 
     fn get_feature_flags(&self) -> &DeviceFeatureFlags {
-        unimplemented!()
+        &self.feature_flags
     }
 
     fn read_buffer(&self, swap_chain: &Self::SwapChain, buffer: &Self::Buffer, size_bytes: usize, frame_written_fence: u64) -> Option<ReadBackData> {
-        unimplemented!()
+        if swap_chain.frame_fence_value < frame_written_fence {
+            return None;
+        }
+        let rr = ReadBackRequest {
+            data: buffer.data.clone(),
+            fence_value: frame_written_fence,
+            format: Format::Unknown,
+            size: size_bytes.min(buffer.size_bytes),
+            row_pitch: size_bytes,
+            slice_pitch: size_bytes
+        };
+        rr.map(&MapInfo {
+            subresource: 0,
+            read_start: 0,
+            read_end: size_bytes
+        }).ok()
     }
 
     fn read_timestamps(&self, swap_chain: &Self::SwapChain, buffer: &Self::Buffer, size_bytes: usize, frame_written_fence: u64) -> Vec<f64> {
-        unimplemented!()
+        let mut results = Vec::new();
+        if let Some(data) = self.read_buffer(swap_chain, buffer, size_bytes, frame_written_fence) {
+            let elem_size = Self::get_timestamp_size_bytes();
+            for chunk in data.data.chunks_exact(elem_size) {
+                let value = u64::from_ne_bytes(chunk.try_into().unwrap());
+                results.push(value as f64 / 1_000_000_000.0);
+            }
+        }
+        results
     }
 
     fn read_pipeline_statistics(&self, swap_chain: &Self::SwapChain, buffer: &Self::Buffer, frame_written_fence: u64) -> Option<PipelineStatistics> {
-        unimplemented!()
+        let size = Self::get_pipeline_statistics_size_bytes();
+        let data = self.read_buffer(swap_chain, buffer, size, frame_written_fence)?;
+        if data.data.len() < size {
+            return None;
+        }
+        let values : Vec<u64> = data.data[..size].chunks_exact(8)
+            .map(|c| u64::from_ne_bytes(c.try_into().unwrap()))
+            .collect();
+        Some(PipelineStatistics {
+            input_assembler_vertices: values[0],
+            input_assembler_primitives: values[1],
+            vertex_shader_invocations: values[2],
+            pixel_shader_primitives: values[3],
+            compute_shader_invocations: values[4]
+        })
     }
 
     fn report_live_objects(&self) -> Result<(), Error> {
-        unimplemented!()
+        println!("hotline_rs::gfx::null: live views (shader: {}, render target: {}, depth stencil: {})",
+            self.shader_heap.get_num_allocated(),
+            self.rtv_heap.get_num_allocated(),
+            self.dsv_heap.get_num_allocated()
+        );
+        Ok(())
     }
 
     fn get_info_queue_messages(&self) -> Result<Vec<String>, Error> {
-        unimplemented!()
+        let mut info_queue = self.info_queue.lock().unwrap();
+        Ok(std::mem::take(&mut *info_queue))
     }
 
     fn get_timestamp_size_bytes() -> usize {
-        unimplemented!()
+        std::mem::size_of::<u64>()
     }
 
     fn get_pipeline_statistics_size_bytes() -> usize {
-        unimplemented!()
+        std::mem::size_of::<PipelineStatistics>()
     }
 
     fn get_indirect_command_size(argument_type: IndirectArgumentType) -> usize {
-        unimplemented!()
+        match argument_type {
+            IndirectArgumentType::Draw => std::mem::size_of::<super::DrawArguments>(),
+            IndirectArgumentType::DrawIndexed => std::mem::size_of::<super::DrawIndexedArguments>(),
+            IndirectArgumentType::Dispatch => std::mem::size_of::<super::DispatchArguments>(),
+            IndirectArgumentType::VertexBuffer => std::mem::size_of::<VertexBufferView>(),
+            IndirectArgumentType::IndexBuffer => std::mem::size_of::<IndexBufferView>(),
+            IndirectArgumentType::PushConstants => std::mem::size_of::<u32>(),
+            IndirectArgumentType::ConstantBuffer => std::mem::size_of::<u64>(),
+            IndirectArgumentType::ShaderResource => std::mem::size_of::<u64>(),
+            IndirectArgumentType::UnorderedAccess => std::mem::size_of::<u64>()
+        }
     }
 
     fn get_counter_alignment() -> usize {
-        unimplemented!()
+        COUNTER_ALIGNMENT
+    }
+}
+
+impl Texture {
+    /// Returns the `TextureInfo` the texture was created with
+    pub fn get_info(&self) -> &TextureInfo {
+        &self.info
+    }
+
+    /// Returns a copy of the CPU side contents of the texture (all array layers and mips)
+    pub fn get_data(&self) -> Vec<u8> {
+        self.data.lock().unwrap().clone()
+    }
+}
+
+/// Clones share the same storage but do not own any heap allocations, so only the original releases views on drop
+impl Clone for Texture {
+    fn clone(&self) -> Self {
+        self.clone_inner()
     }
 }
 
 impl super::Texture<Device> for Texture {
     fn get_srv_index(&self) -> Option<usize> {
-        unimplemented!()
+        self.srv_index
     }
 
     fn get_uav_index(&self) -> Option<usize> {
-        unimplemented!()
+        self.uav_index
     }
 
     fn get_subresource_uav_index(&self, subresource: u32) -> Option<usize> {
-        unimplemented!()
+        self.subresource_uav_index.get(subresource as usize).copied()
     }
 
     fn get_msaa_srv_index(&self) -> Option<usize> {
-        unimplemented!()
+        self.msaa_srv_index
     }
 
     fn clone_inner(&self) -> Self {
-        unimplemented!()
+        Texture {
+            info: self.info,
+            data: self.data.clone(),
+            resolved_data: self.resolved_data.clone(),
+            srv_index: self.srv_index,
+            msaa_srv_index: self.msaa_srv_index,
+            uav_index: self.uav_index,
+            subresource_uav_index: self.subresource_uav_index.clone(),
+            rtv_index: self.rtv_index.clone(),
+            dsv_index: self.dsv_index.clone(),
+            heap_id: self.heap_id,
+            allocs: Vec::new()
+        }
     }
 
     fn is_resolvable(&self) -> bool {
-        unimplemented!()
+        self.resolved_data.is_some()
     }
 
     fn get_shader_heap_id(&self) -> Option<u16> {
-        unimplemented!()
+        self.heap_id
+    }
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        for allocs in &self.allocs {
+            if !allocs.indices.is_empty() {
+                allocs.drop_list.push(DropResource::new(vec![self.data.clone()], allocs.indices.clone()));
+            }
+        }
+    }
+}
+
+impl Buffer {
+    /// Returns the size of the buffer in bytes, including any append counter
+    pub fn get_size_bytes(&self) -> usize {
+        self.size_bytes
+    }
+
+    /// Returns a copy of the CPU side contents of the buffer
+    pub fn get_data(&self) -> Vec<u8> {
+        self.data.lock().unwrap().clone()
+    }
+}
+
+/// Clones share the same storage but do not own any heap allocations, so only the original releases views on drop
+impl Clone for Buffer {
+    fn clone(&self) -> Self {
+        Buffer {
+            info: self.info,
+            data: self.data.clone(),
+            size_bytes: self.size_bytes,
+            srv_index: self.srv_index,
+            uav_index: self.uav_index,
+            cbv_index: self.cbv_index,
+            counter_offset: self.counter_offset,
+            allocs: None
+        }
     }
 }
 
 impl super::Buffer<Device> for Buffer {
     fn update<T: Sized>(&mut self, offset: usize, data: &[T]) -> Result<(), Error> {
-        unimplemented!()
+        let bytes = super::slice_as_u8_slice(data);
+        let mut storage = self.data.lock().unwrap();
+        if offset + bytes.len() > storage.len() {
+            return Err(Error {
+                msg: format!("hotline_rs::gfx::null: buffer update of {} bytes at offset {} exceeds buffer size {}",
+                    bytes.len(), offset, storage.len())
+            });
+        }
+        storage[offset..offset + bytes.len()].copy_from_slice(bytes);
+        Ok(())
     }
 
     fn write<T: Sized>(&mut self, offset: usize, data: &[T]) -> Result<(), Error> {
-        unimplemented!()
+        if !self.info.cpu_access.contains(CpuAccessFlags::PERSISTENTLY_MAPPED) {
+            return Err(Error {
+                msg: "hotline_rs::gfx::null: buffer write requires CpuAccessFlags::PERSISTENTLY_MAPPED".to_string()
+            });
+        }
+        self.update(offset, data)
     }
 
     fn map(&mut self, info: &MapInfo) -> *mut u8 {
-        unimplemented!()
+        // the storage is never resized, so the pointer remains valid for the lifetime of the buffer
+        self.data.lock().unwrap().as_mut_ptr()
     }
 
     fn unmap(&mut self, info: &UnmapInfo) {
-        unimplemented!()
     }
 
     fn get_srv_index(&self) -> Option<usize> {
-        unimplemented!()
+        self.srv_index
     }
 
     fn get_cbv_index(&self) -> Option<usize> {
-        unimplemented!()
+        self.cbv_index
     }
 
     fn get_uav_index(&self) -> Option<usize> {
-        unimplemented!()
+        self.uav_index
     }
 
     fn get_vbv(&self) -> Option<VertexBufferView> {
-        unimplemented!()
+        if self.info.usage.contains(BufferUsage::VERTEX) {
+            Some(VertexBufferView {
+                location: self.data.lock().unwrap().as_ptr() as u64,
+                size_bytes: self.size_bytes as u32,
+                stride_bytes: self.info.stride as u32
+            })
+        }
+        else {
+            None
+        }
     }
 
     fn get_ibv(&self) -> Option<IndexBufferView> {
-        unimplemented!()
+        if self.info.usage.contains(BufferUsage::INDEX) {
+            Some(IndexBufferView {
+                location: self.data.lock().unwrap().as_ptr() as u64,
+                size_bytes: self.size_bytes as u32,
+                format: super::block_size_for_format(self.info.format)
+            })
+        }
+        else {
+            None
+        }
     }
 
     fn get_counter_offset(&self) -> Option<usize> {
-        unimplemented!()
+        self.counter_offset
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        if let Some(allocs) = &self.allocs {
+            if !allocs.indices.is_empty() {
+                allocs.drop_list.push(DropResource::new(vec![self.data.clone()], allocs.indices.clone()));
+            }
+        }
     }
 }
 
 impl super::Heap<Device> for Heap {
     fn deallocate(&mut self, index: usize) {
-        unimplemented!()
+        self.free_list.push(index);
     }
 
     fn cleanup_dropped_resources(&mut self, swap_chain: &SwapChain) {
-        unimplemented!()
+        self.drop_list.cleanup(swap_chain.frame_index, swap_chain.num_buffers as usize, &self.free_list);
     }
 
     fn get_heap_id(&self) -> u16 {
-        unimplemented!()
+        self.id
     }
 }
 
 impl super::QueryHeap<Device> for QueryHeap {
     fn reset(&mut self) {
-        unimplemented!()
+        self.alloc_index = 0;
     }
 }
 
 impl super::ReadBackRequest<Device> for ReadBackRequest {
     fn is_complete(&self, swap_chain: &SwapChain) -> bool {
-        unimplemented!()
+        swap_chain.frame_fence_value >= self.fence_value
     }
 
     fn map(&self, info: &MapInfo) -> Result<ReadBackData, Error> {
-        unimplemented!()
+        let data = self.data.lock().unwrap();
+        let start = info.read_start.min(self.size);
+        let end = info.read_end.min(self.size).max(start);
+        // storage outlives the mapped slice as long as the request or buffer is alive, matching gpu mapped memory
+        let slice = unsafe {
+            std::slice::from_raw_parts(data.as_ptr().add(start), end - start)
+        };
+        Ok(ReadBackData {
+            data: slice,
+            format: self.format,
+            size: end - start,
+            row_pitch: self.row_pitch,
+            slice_pitch: self.slice_pitch
+        })
     }
 
     fn unmap(&self) {
-        unimplemented!()
     }
 }
 
 impl super::RenderPass<Device> for RenderPass {
     fn get_format_hash(&self) -> u64 {
-        unimplemented!()
+        self.format_hash
+    }
+}
+
+impl super::Fence<Device> for Fence {
+    fn get_completed_value(&self) -> u64 {
+        self.value.load(Ordering::SeqCst)
+    }
+
+    fn wait_for_value(&self, _value: u64) {
+        // work has already completed when it was submitted
     }
 }
 
 impl super::RaytracingTLAS<Device> for RaytracingTLAS {
     fn get_srv_index(&self) -> Option<usize> {
-        unimplemented!()
+        None
     }
 
     fn get_shader_heap_id(&self) -> u16 {
-        unimplemented!()
+        0
+    }
+}
+
+impl RenderPipeline {
+    /// Returns the input layout the pipeline was created with
+    pub fn get_input_layout(&self) -> &InputLayout {
+        &self.input_layout
+    }
+
+    /// Returns the pipeline layout the pipeline was created with
+    pub fn get_pipeline_layout(&self) -> &PipelineLayout {
+        &self.pipeline_layout
+    }
+
+    /// Returns the format hash of the pass the pipeline was created for, if one was supplied
+    pub fn get_format_hash(&self) -> Option<u64> {
+        self.format_hash
     }
 }
 
 impl super::Pipeline for RenderPipeline {
     fn get_pipeline_slot(&self, register: u32, space: u32, descriptor_type: DescriptorType) -> Option<&super::PipelineSlotInfo> {
-        unimplemented!()
+        self.slot_lookup.get(&(register, space, descriptor_type))
     }
 
     fn get_pipeline_slots(&self) -> &Vec<u32> {
-        unimplemented!()
+        &self.slots
     }
 
     fn get_pipeline_type() -> PipelineType {
-        unimplemented!()
+        PipelineType::Render
     }
 }
 
 impl super::Pipeline for ComputePipeline {
     fn get_pipeline_slot(&self, register: u32, space: u32, descriptor_type: DescriptorType) -> Option<&super::PipelineSlotInfo> {
-        unimplemented!()
+        self.slot_lookup.get(&(register, space, descriptor_type))
     }
 
     fn get_pipeline_slots(&self) -> &Vec<u32> {
-        unimplemented!()
+        &self.slots
     }
 
     fn get_pipeline_type() -> PipelineType {
-        unimplemented!()
+        PipelineType::Compute
     }
 }
 
 impl super::Pipeline for RaytracingPipeline {
     fn get_pipeline_slot(&self, register: u32, space: u32, descriptor_type: DescriptorType) -> Option<&super::PipelineSlotInfo> {
-        unimplemented!()
+        self.slot_lookup.get(&(register, space, descriptor_type))
     }
 
     fn get_pipeline_slots(&self) -> &Vec<u32> {
-        unimplemented!()
+        &self.slots
     }
 
     fn get_pipeline_type() -> PipelineType {
-        unimplemented!()
+        // raytracing pipelines bind through the compute root signature
+        PipelineType::Compute
     }
 }
 
@@ -706,4 +2471,4 @@ impl super::ComputePipeline<Device> for ComputePipeline {}
 impl super::RaytracingPipeline<Device> for RaytracingPipeline {}
 impl super::CommandSignature<Device> for CommandSignature {}
 impl super::RaytracingShaderBindingTable<Device> for RaytracingShaderBindingTable {}
-impl super::RaytracingBLAS<Device> for RaytracingBLAS {}
\ No newline at end of file
+impl super::RaytracingBLAS<Device> for RaytracingBLAS {}
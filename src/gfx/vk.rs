@@ -0,0 +1,4490 @@
+#![cfg(feature = "vulkan")]
+
+//! A Vulkan 1.3 gfx backend built on `ash`. Rendering uses dynamic rendering, `synchronization2` and a timeline
+//! semaphore to track frames. Swap chains are off-screen: backbuffers are regular textures which can be read back,
+//! which allows the backend to run under the Mesa lavapipe software driver without a physical GPU or surface.
+//!
+//! Hotline's bindless model (see `docs/binding-architecture.md`) maps onto descriptor indexing as follows:
+//! `register_space` selects the descriptor set and the shader register selects the binding, offset by a per register
+//! kind shift to match compiling HLSL with `dxc -spirv -fvk-t-shift 0 all -fvk-s-shift 128 all -fvk-u-shift 256 all
+//! -fvk-b-shift 384 all`. A `Heap` holds descriptors by index; descriptor sets are created lazily per heap offset and
+//! kept up to date as views are allocated, using `PARTIALLY_BOUND` and `UPDATE_AFTER_BIND` bindings so unbounded
+//! arrays behave like d3d12 descriptor tables.
+
+use super::*;
+use super::Device as SuperDevice;
+use super::Heap as SuperHeap;
+use super::Texture as SuperTexture;
+use super::Buffer as SuperBuffer;
+use super::ReadBackRequest as SuperReadBackRequest;
+use super::Pipeline as SuperPipeline;
+
+use crate::os::Window;
+use crate::os::App;
+
+use ash::vk;
+
+use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::ffi::{c_void, CStr, CString};
+
+/// Binding shift applied to `t` registers (shader resources), matches `-fvk-t-shift 0 all`
+pub const SHADER_RESOURCE_BINDING_SHIFT: u32 = 0;
+
+/// Binding shift applied to `s` registers (samplers), matches `-fvk-s-shift 128 all`
+pub const SAMPLER_BINDING_SHIFT: u32 = 128;
+
+/// Binding shift applied to `u` registers (unordered access), matches `-fvk-u-shift 256 all`
+pub const UNORDERED_ACCESS_BINDING_SHIFT: u32 = 256;
+
+/// Binding shift applied to `b` registers (constant buffers), matches `-fvk-b-shift 384 all`
+pub const CONSTANT_BUFFER_BINDING_SHIFT: u32 = 384;
+
+/// Messages produced by the validation layers, drained by `Device::get_info_queue_messages`
+type InfoQueueRef = Arc<Mutex<Vec<String>>>;
+
+/// Unique heap ids so resources can be correlated with the heap their views live in
+static HEAP_ID: AtomicU16 = AtomicU16::new(0);
+
+/// Alignment for append buffer counters, matches d3d12 `D3D12_UAV_COUNTER_PLACEMENT_ALIGNMENT`
+const COUNTER_ALIGNMENT: usize = 4096;
+
+/// SPIR-V `OpEntryPoint` opcode, used to find the stage and name of a shader module
+const SPIRV_OP_ENTRY_POINT: u32 = 15;
+
+/// Key for slot lookup: (register, space, descriptor_type)
+type SlotKey = (u32, u32, DescriptorType);
+
+/// Owns the `ash` instance and device, shared by all objects created from a `Device`
+struct DeviceInner {
+    entry: ash::Entry,
+    instance: ash::Instance,
+    physical_device: vk::PhysicalDevice,
+    device: ash::Device,
+    queue: Mutex<vk::Queue>,
+    /// A second queue from the graphics family used for `QueueType::Compute` and `QueueType::Copy` submissions,
+    /// if the family only exposes a single queue all work is submitted to `queue`
+    async_queue: Option<Mutex<vk::Queue>>,
+    queue_family_index: u32,
+    memory_properties: vk::PhysicalDeviceMemoryProperties,
+    limits: vk::PhysicalDeviceLimits,
+    features: EnabledFeatures,
+    debug_utils_instance: Option<ash::ext::debug_utils::Instance>,
+    debug_utils_device: Option<ash::ext::debug_utils::Device>,
+    debug_messenger: vk::DebugUtilsMessengerEXT,
+    info_queue: InfoQueueRef,
+    /// Command pool for immediate submissions used during resource creation
+    upload_pool: Mutex<vk::CommandPool>,
+    /// Descriptor set layouts are shared between pipelines with identical bindings in a space
+    set_layouts: Mutex<HashMap<Vec<SetLayoutBinding>, Arc<SetLayout>>>,
+    /// Immutable samplers keyed by their serialised `SamplerInfo`
+    samplers: Mutex<HashMap<String, vk::Sampler>>,
+    /// Number of descriptors to use for unbounded descriptor arrays
+    unbounded_descriptor_count: u32,
+}
+
+/// Optional features which were available and enabled on the device
+#[derive(Clone, Copy, Default)]
+struct EnabledFeatures {
+    depth_clamp: bool,
+    fill_mode_non_solid: bool,
+    logic_op: bool,
+    sampler_anisotropy: bool,
+    pipeline_statistics_query: bool,
+    mirror_clamp_to_edge: bool,
+    sampled_image_update_after_bind: bool,
+    storage_image_update_after_bind: bool,
+    storage_buffer_update_after_bind: bool,
+    uniform_buffer_update_after_bind: bool,
+    update_unused_while_pending: bool,
+}
+
+/// Vulkan handles which are destroyed together when dropped, pushed into a `DropList` to defer
+/// destruction until the GPU has finished with them
+struct VkResource {
+    device: Arc<DeviceInner>,
+    buffers: Vec<vk::Buffer>,
+    images: Vec<vk::Image>,
+    views: Vec<vk::ImageView>,
+    memory: Vec<vk::DeviceMemory>,
+    pipelines: Vec<vk::Pipeline>,
+    query_pools: Vec<vk::QueryPool>,
+}
+
+/// The kind of descriptor a set layout binding expects, derived from the `DescriptorBinding` and its `ResourceType`
+#[derive(Clone, Copy, Hash, PartialEq, Eq)]
+enum DescriptorKind {
+    Image {
+        storage: bool,
+        view_type: Option<vk::ImageViewType>,
+        multisampled: bool
+    },
+    Buffer {
+        uniform: bool
+    },
+    Sampler
+}
+
+/// A descriptor written into a `Heap` slot
+#[derive(Clone, Copy)]
+enum Descriptor {
+    Image {
+        view: vk::ImageView,
+        view_type: vk::ImageViewType,
+        storage: bool,
+        multisampled: bool
+    },
+    Buffer {
+        buffer: vk::Buffer,
+        offset: u64,
+        range: u64,
+        uniform: bool
+    }
+}
+
+/// A single binding within a descriptor set layout, `sampler` is the serialised `SamplerInfo` of an immutable sampler
+#[derive(Clone, Hash, PartialEq, Eq)]
+struct SetLayoutBinding {
+    binding: u32,
+    kind: DescriptorKind,
+    count: u32,
+    sampler: Option<String>,
+}
+
+/// A descriptor set layout for a single register space, owned by the device cache
+struct SetLayout {
+    layout: vk::DescriptorSetLayout,
+    bindings: Vec<SetLayoutBinding>,
+    pool_sizes: Vec<vk::DescriptorPoolSize>,
+    update_after_bind: bool,
+}
+
+/// Pipeline layout shared between a pipeline and the command buffers which bind it, layouts are not used by
+/// submitted work so they are destroyed when the last reference is dropped
+struct PipelineLayoutState {
+    device: Arc<DeviceInner>,
+    layout: vk::PipelineLayout,
+    /// Set layouts indexed by register space
+    set_layouts: Vec<Arc<SetLayout>>,
+    /// Byte offsets of push constants within the push constant range indexed by pipeline slot
+    push_constant_offsets: HashMap<u32, u32>,
+    push_constant_stages: vk::ShaderStageFlags,
+    bind_point: vk::PipelineBindPoint,
+}
+
+/// A descriptor set allocated for a set layout at an offset into a heap
+struct CachedSet {
+    pool: vk::DescriptorPool,
+    set: vk::DescriptorSet,
+    layout: Arc<SetLayout>,
+    offset: usize,
+}
+
+/// Descriptors stored in a heap and the descriptor sets created from them
+struct HeapState {
+    device: Arc<DeviceInner>,
+    entries: Vec<Option<Descriptor>>,
+    /// Views created by `Device::create_resource_view` which are owned by the heap slot
+    owned: HashMap<usize, VkResource>,
+    sets: HashMap<(vk::DescriptorSetLayout, usize), CachedSet>,
+}
+
+/// Timeline semaphore used to track frame completion
+struct TimelineSemaphore {
+    device: Arc<DeviceInner>,
+    semaphore: vk::Semaphore,
+}
+
+/// Command pool owned by a `CmdBuf`, each command buffer has its own pool so they can be recorded on any thread
+struct CommandPool {
+    device: Arc<DeviceInner>,
+    pool: vk::CommandPool,
+}
+
+/// Tracks the drop list and view allocations of a resource within a single heap
+struct HeapAllocs {
+    drop_list: DropListRef<VkResource>,
+    state: Arc<Mutex<HeapState>>,
+    indices: Vec<usize>,
+}
+
+/// Query results which are copied into a buffer once the current render pass has ended
+struct QueryCopy {
+    pool: vk::QueryPool,
+    index: u32,
+    buffer: vk::Buffer,
+    stride: u64,
+}
+
+#[derive(Clone)]
+pub struct Device {
+    inner: Arc<DeviceInner>,
+    adapter_info: AdapterInfo,
+    feature_flags: DeviceFeatureFlags,
+    shader_heap: Heap,
+    rtv_heap: Heap,
+    dsv_heap: Heap,
+    /// Deferred destruction of resources, pipelines and query heaps
+    drop_list: DropListRef<VkResource>,
+    /// Device level resources have no heap allocations, this remains empty
+    free_list: FreeListRef,
+}
+
+#[derive(Clone)]
+pub struct SwapChain {
+    device: Arc<DeviceInner>,
+    drop_list: DropListRef<VkResource>,
+    num_buffers: u32,
+    format: Format,
+    width: i32,
+    height: i32,
+    clear_colour: Option<ClearColour>,
+    bb_index: usize,
+    semaphore: Arc<TimelineSemaphore>,
+    fence_last_signalled_value: u64,
+    frame_fence_value: Vec<u64>,
+    /// Frame index starts at 1 because `DropList` uses 0 to signify an untagged resource
+    frame_index: usize,
+    backbuffer_textures: Vec<Texture>,
+    backbuffer_passes: Vec<RenderPass>,
+    backbuffer_passes_no_clear: Vec<RenderPass>,
+}
+
+#[derive(Clone)]
+pub struct CmdBuf {
+    device: Arc<DeviceInner>,
+    drop_list: DropListRef<VkResource>,
+    pool: Arc<CommandPool>,
+    command_buffers: Vec<vk::CommandBuffer>,
+    bb_index: usize,
+    in_render_pass: bool,
+    render_layout: Option<Arc<PipelineLayoutState>>,
+    compute_layout: Option<Arc<PipelineLayoutState>>,
+    pending_query_copies: Arc<Mutex<Vec<QueryCopy>>>,
+}
+
+pub struct Shader {
+    device: Arc<DeviceInner>,
+    module: vk::ShaderModule,
+    stage: vk::ShaderStageFlags,
+    entry_point: CString,
+}
+
+pub struct RenderPipeline {
+    pipeline: vk::Pipeline,
+    layout: Arc<PipelineLayoutState>,
+    slots: Vec<u32>,
+    slot_lookup: HashMap<SlotKey, PipelineSlotInfo>,
+    drop_list: DropListRef<VkResource>,
+}
+
+pub struct ComputePipeline {
+    pipeline: vk::Pipeline,
+    layout: Arc<PipelineLayoutState>,
+    slots: Vec<u32>,
+    slot_lookup: HashMap<SlotKey, PipelineSlotInfo>,
+    drop_list: DropListRef<VkResource>,
+}
+
+pub struct RaytracingPipeline {
+    slots: Vec<u32>,
+    slot_lookup: HashMap<SlotKey, PipelineSlotInfo>,
+}
+
+pub struct Texture {
+    info: TextureInfo,
+    image: vk::Image,
+    /// Single sample image for msaa textures which are resolved into
+    resolved_image: Option<vk::Image>,
+    format: vk::Format,
+    aspect: vk::ImageAspectFlags,
+    rtv_views: Vec<vk::ImageView>,
+    dsv_views: Vec<vk::ImageView>,
+    srv_index: Option<usize>,
+    msaa_srv_index: Option<usize>,
+    uav_index: Option<usize>,
+    subresource_uav_index: Vec<usize>,
+    rtv_index: Vec<usize>,
+    dsv_index: Vec<usize>,
+    heap_id: Option<u16>,
+    /// Owned vulkan handles, `None` for non-owning clones
+    resource: Option<VkResource>,
+    drop_list: DropListRef<VkResource>,
+    allocs: Vec<HeapAllocs>,
+}
+
+pub struct Buffer {
+    info: BufferInfo,
+    buffer: vk::Buffer,
+    size_bytes: usize,
+    /// Persistently mapped pointer for host visible memory, null otherwise
+    mapped: *mut u8,
+    srv_index: Option<usize>,
+    uav_index: Option<usize>,
+    cbv_index: Option<usize>,
+    counter_offset: Option<usize>,
+    resource: Option<VkResource>,
+    drop_list: DropListRef<VkResource>,
+    allocs: Option<HeapAllocs>,
+}
+
+pub struct ReadBackRequest {
+    semaphore: Arc<TimelineSemaphore>,
+    fence_value: u64,
+    mapped: *const u8,
+    format: Format,
+    size: usize,
+    row_pitch: usize,
+    slice_pitch: usize,
+    resource: Option<VkResource>,
+    drop_list: DropListRef<VkResource>,
+}
+
+#[derive(Clone)]
+pub struct RenderPass {
+    colour_views: Vec<vk::ImageView>,
+    depth_stencil_view: Option<vk::ImageView>,
+    has_stencil: bool,
+    colour_formats: Vec<vk::Format>,
+    depth_stencil_format: vk::Format,
+    extent: vk::Extent2D,
+    rt_clear: Option<ClearColour>,
+    ds_clear: Option<ClearDepthStencil>,
+    discard: bool,
+    sample_count: u32,
+    format_hash: u64,
+}
+
+#[derive(Clone)]
+pub struct Heap {
+    heap_type: HeapType,
+    capacity: usize,
+    offset: usize,
+    free_list: FreeListRef,
+    drop_list: DropListRef<VkResource>,
+    state: Arc<Mutex<HeapState>>,
+    id: u16,
+}
+
+pub struct QueryHeap {
+    pool: vk::QueryPool,
+    heap_type: QueryType,
+    alloc_index: usize,
+    capacity: usize,
+    resource: Option<VkResource>,
+    drop_list: DropListRef<VkResource>,
+}
+
+pub struct CommandSignature {
+    argument_type: IndirectArgumentType,
+    stride: u32,
+}
+
+pub struct RaytracingShaderBindingTable;
+pub struct RaytracingBLAS;
+pub struct RaytracingTLAS;
+
+/// A timeline semaphore which can be signalled and waited on by any of the device queues
+pub struct Fence {
+    semaphore: TimelineSemaphore
+}
+
+// buffers and read backs contain persistently mapped pointers which are only accessed through `&mut self` or after
+// the gpu has finished writing, the remaining members are vulkan handles which are safe to share
+unsafe impl Send for Buffer {}
+unsafe impl Sync for Buffer {}
+unsafe impl Send for ReadBackRequest {}
+unsafe impl Sync for ReadBackRequest {}
+
+fn report(info_queue: &InfoQueueRef, severity: &str, msg: String) {
+    info_queue.lock().unwrap().push(format!("hotline_rs::gfx::vk: [{}] {}", severity, msg));
+}
+
+/// Wraps a `vk::Result` with the name of the call which produced it
+fn vk_error(call: &str, result: vk::Result) -> Error {
+    Error {
+        msg: format!("hotline_rs::gfx::vk: {} failed: {}", call, result)
+    }
+}
+
+/// Receives messages from the validation layers and pushes them into the device info queue
+unsafe extern "system" fn debug_callback(
+    message_severity: vk::DebugUtilsMessageSeverityFlagsEXT,
+    message_type: vk::DebugUtilsMessageTypeFlagsEXT,
+    p_callback_data: *const vk::DebugUtilsMessengerCallbackDataEXT<'_>,
+    p_user_data: *mut c_void,
+) -> vk::Bool32 {
+    if p_callback_data.is_null() || p_user_data.is_null() {
+        return vk::FALSE;
+    }
+    let severity = if message_severity.contains(vk::DebugUtilsMessageSeverityFlagsEXT::ERROR) {
+        "error"
+    }
+    else if message_severity.contains(vk::DebugUtilsMessageSeverityFlagsEXT::WARNING) {
+        "warning"
+    }
+    else {
+        "info"
+    };
+    let msg = (*p_callback_data).message_as_c_str()
+        .map(|m| m.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // user data points at the `InfoQueueRef` owned by `DeviceInner` which outlives the messenger
+    let info_queue = &*(p_user_data as *const Mutex<Vec<String>>);
+    info_queue.lock().unwrap().push(format!("hotline_rs::gfx::vk: [{}] {}", severity, msg));
+    vk::FALSE
+}
+
+/// Convert hotline `Format` to a `vk::Format`
+pub fn to_vk_format(format: Format) -> vk::Format {
+    match format {
+        Format::Unknown => vk::Format::UNDEFINED,
+        Format::R16n => vk::Format::R16_UNORM,
+        Format::R16u => vk::Format::R16_UINT,
+        Format::R16i => vk::Format::R16_SINT,
+        Format::R16f => vk::Format::R16_SFLOAT,
+        Format::R32u => vk::Format::R32_UINT,
+        Format::R32i => vk::Format::R32_SINT,
+        Format::R32f => vk::Format::R32_SFLOAT,
+        Format::RG16f => vk::Format::R16G16_SFLOAT,
+        Format::RG16u => vk::Format::R16G16_UINT,
+        Format::RG16i => vk::Format::R16G16_SINT,
+        Format::RG32u => vk::Format::R32G32_UINT,
+        Format::RG32i => vk::Format::R32G32_SINT,
+        Format::RG32f => vk::Format::R32G32_SFLOAT,
+        Format::RGB32u => vk::Format::R32G32B32_UINT,
+        Format::RGB32i => vk::Format::R32G32B32_SINT,
+        Format::RGB32f => vk::Format::R32G32B32_SFLOAT,
+        Format::RGBA8nSRGB => vk::Format::R8G8B8A8_SRGB,
+        Format::RGBA8n => vk::Format::R8G8B8A8_UNORM,
+        Format::RGBA8u => vk::Format::R8G8B8A8_UINT,
+        Format::RGBA8i => vk::Format::R8G8B8A8_SINT,
+        Format::BGRA8n => vk::Format::B8G8R8A8_UNORM,
+        Format::BGRX8n => vk::Format::B8G8R8A8_UNORM,
+        Format::BGRA8nSRGB => vk::Format::B8G8R8A8_SRGB,
+        Format::BGRX8nSRGB => vk::Format::B8G8R8A8_SRGB,
+        Format::RGBA16n => vk::Format::R16G16B16A16_UNORM,
+        Format::RGBA16u => vk::Format::R16G16B16A16_UINT,
+        Format::RGBA16i => vk::Format::R16G16B16A16_SINT,
+        Format::RGBA16f => vk::Format::R16G16B16A16_SFLOAT,
+        Format::RGBA32u => vk::Format::R32G32B32A32_UINT,
+        Format::RGBA32i => vk::Format::R32G32B32A32_SINT,
+        Format::RGBA32f => vk::Format::R32G32B32A32_SFLOAT,
+        Format::D32fS8X24u => vk::Format::D32_SFLOAT_S8_UINT,
+        Format::D32f => vk::Format::D32_SFLOAT,
+        Format::D24nS8u => vk::Format::D24_UNORM_S8_UINT,
+        Format::D16n => vk::Format::D16_UNORM,
+        Format::BC1n => vk::Format::BC1_RGBA_UNORM_BLOCK,
+        Format::BC1nSRGB => vk::Format::BC1_RGBA_SRGB_BLOCK,
+        Format::BC2n => vk::Format::BC2_UNORM_BLOCK,
+        Format::BC2nSRGB => vk::Format::BC2_SRGB_BLOCK,
+        Format::BC3n => vk::Format::BC3_UNORM_BLOCK,
+        Format::BC3nSRGB => vk::Format::BC3_SRGB_BLOCK,
+        Format::BC4n => vk::Format::BC4_UNORM_BLOCK,
+        Format::BC5n => vk::Format::BC5_UNORM_BLOCK,
+    }
+}
+
+/// Returns true if the format contains a stencil component
+fn has_stencil(format: Format) -> bool {
+    matches!(format, Format::D32fS8X24u | Format::D24nS8u)
+}
+
+/// Returns the image aspects of a format used for attachments and copies
+fn to_vk_aspect(format: Format) -> vk::ImageAspectFlags {
+    if has_stencil(format) {
+        vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL
+    }
+    else if super::is_depth_format(format) {
+        vk::ImageAspectFlags::DEPTH
+    }
+    else {
+        vk::ImageAspectFlags::COLOR
+    }
+}
+
+/// Returns the image aspect which can be sampled in shaders, depth for depth stencil formats
+fn to_vk_sampled_aspect(format: Format) -> vk::ImageAspectFlags {
+    if super::is_depth_format(format) || has_stencil(format) {
+        vk::ImageAspectFlags::DEPTH
+    }
+    else {
+        vk::ImageAspectFlags::COLOR
+    }
+}
+
+fn to_vk_image_type(tex_type: TextureType) -> vk::ImageType {
+    match tex_type {
+        TextureType::Texture1D | TextureType::Texture1DArray => vk::ImageType::TYPE_1D,
+        TextureType::Texture3D => vk::ImageType::TYPE_3D,
+        _ => vk::ImageType::TYPE_2D
+    }
+}
+
+fn to_vk_image_view_type(info: &TextureInfo) -> vk::ImageViewType {
+    match info.tex_type {
+        TextureType::Texture1D => if info.array_layers > 1 {
+            vk::ImageViewType::TYPE_1D_ARRAY
+        }
+        else {
+            vk::ImageViewType::TYPE_1D
+        },
+        TextureType::Texture1DArray => vk::ImageViewType::TYPE_1D_ARRAY,
+        TextureType::Texture2D => if info.array_layers > 1 {
+            vk::ImageViewType::TYPE_2D_ARRAY
+        }
+        else {
+            vk::ImageViewType::TYPE_2D
+        },
+        TextureType::Texture2DArray => vk::ImageViewType::TYPE_2D_ARRAY,
+        TextureType::Texture3D => vk::ImageViewType::TYPE_3D,
+        TextureType::TextureCube => vk::ImageViewType::CUBE,
+        TextureType::TextureCubeArray => vk::ImageViewType::CUBE_ARRAY,
+    }
+}
+
+/// Returns the view type used for a single array slice or depth slice of a texture (render targets and subresource uavs)
+fn to_vk_slice_view_type(tex_type: TextureType) -> vk::ImageViewType {
+    match tex_type {
+        TextureType::Texture1D | TextureType::Texture1DArray => vk::ImageViewType::TYPE_1D,
+        _ => vk::ImageViewType::TYPE_2D
+    }
+}
+
+fn to_vk_sample_count(samples: u32) -> vk::SampleCountFlags {
+    match samples {
+        2 => vk::SampleCountFlags::TYPE_2,
+        4 => vk::SampleCountFlags::TYPE_4,
+        8 => vk::SampleCountFlags::TYPE_8,
+        16 => vk::SampleCountFlags::TYPE_16,
+        32 => vk::SampleCountFlags::TYPE_32,
+        64 => vk::SampleCountFlags::TYPE_64,
+        _ => vk::SampleCountFlags::TYPE_1
+    }
+}
+
+/// Image layout for a texture in `state`, buffer only states map to `GENERAL`
+fn to_vk_image_layout(state: ResourceState) -> vk::ImageLayout {
+    match state {
+        ResourceState::RenderTarget => vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+        ResourceState::DepthStencil => vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+        ResourceState::DepthStencilReadOnly => vk::ImageLayout::READ_ONLY_OPTIMAL,
+        ResourceState::ShaderResource => vk::ImageLayout::READ_ONLY_OPTIMAL,
+        // backbuffers are never presented, they stay copyable so they can be read back
+        ResourceState::Present => vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        ResourceState::ResolveSrc => vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        ResourceState::CopySrc => vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        ResourceState::ResolveDst => vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+        ResourceState::CopyDst => vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+        _ => vk::ImageLayout::GENERAL
+    }
+}
+
+fn to_vk_image_usage(info: &TextureInfo) -> vk::ImageUsageFlags {
+    let mut usage = vk::ImageUsageFlags::TRANSFER_SRC | vk::ImageUsageFlags::TRANSFER_DST;
+    if info.usage.contains(TextureUsage::SHADER_RESOURCE) {
+        usage |= vk::ImageUsageFlags::SAMPLED;
+    }
+    if info.usage.contains(TextureUsage::UNORDERED_ACCESS) {
+        usage |= vk::ImageUsageFlags::STORAGE;
+    }
+    if info.usage.contains(TextureUsage::RENDER_TARGET) {
+        usage |= vk::ImageUsageFlags::COLOR_ATTACHMENT;
+    }
+    if info.usage.contains(TextureUsage::DEPTH_STENCIL) {
+        usage |= vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT;
+    }
+    usage
+}
+
+fn to_vk_buffer_usage(usage: BufferUsage) -> vk::BufferUsageFlags {
+    let mut flags = vk::BufferUsageFlags::TRANSFER_SRC | vk::BufferUsageFlags::TRANSFER_DST;
+    if usage.contains(BufferUsage::VERTEX) {
+        flags |= vk::BufferUsageFlags::VERTEX_BUFFER;
+    }
+    if usage.contains(BufferUsage::INDEX) {
+        flags |= vk::BufferUsageFlags::INDEX_BUFFER;
+    }
+    if usage.contains(BufferUsage::CONSTANT_BUFFER) {
+        flags |= vk::BufferUsageFlags::UNIFORM_BUFFER;
+    }
+    if usage.intersects(BufferUsage::SHADER_RESOURCE | BufferUsage::UNORDERED_ACCESS | BufferUsage::APPEND_COUNTER) {
+        flags |= vk::BufferUsageFlags::STORAGE_BUFFER;
+    }
+    if usage.contains(BufferUsage::INDIRECT_ARGUMENT_BUFFER) {
+        flags |= vk::BufferUsageFlags::INDIRECT_BUFFER;
+    }
+    flags
+}
+
+fn to_vk_index_type(format: Format) -> vk::IndexType {
+    match format {
+        Format::R16u | Format::R16i => vk::IndexType::UINT16,
+        _ => vk::IndexType::UINT32
+    }
+}
+
+fn to_vk_compare_op(func: ComparisonFunc) -> vk::CompareOp {
+    match func {
+        ComparisonFunc::Never => vk::CompareOp::NEVER,
+        ComparisonFunc::Less => vk::CompareOp::LESS,
+        ComparisonFunc::Equal => vk::CompareOp::EQUAL,
+        ComparisonFunc::LessEqual => vk::CompareOp::LESS_OR_EQUAL,
+        ComparisonFunc::Greater => vk::CompareOp::GREATER,
+        ComparisonFunc::NotEqual => vk::CompareOp::NOT_EQUAL,
+        ComparisonFunc::GreaterEqual => vk::CompareOp::GREATER_OR_EQUAL,
+        ComparisonFunc::Always => vk::CompareOp::ALWAYS,
+    }
+}
+
+fn to_vk_stencil_op(op: StencilOp) -> vk::StencilOp {
+    match op {
+        StencilOp::Keep => vk::StencilOp::KEEP,
+        StencilOp::Zero => vk::StencilOp::ZERO,
+        StencilOp::Replace => vk::StencilOp::REPLACE,
+        StencilOp::IncrSat => vk::StencilOp::INCREMENT_AND_CLAMP,
+        StencilOp::DecrSat => vk::StencilOp::DECREMENT_AND_CLAMP,
+        StencilOp::Invert => vk::StencilOp::INVERT,
+        StencilOp::Incr => vk::StencilOp::INCREMENT_AND_WRAP,
+        StencilOp::Decr => vk::StencilOp::DECREMENT_AND_WRAP,
+    }
+}
+
+fn to_vk_stencil_op_state(info: &StencilInfo, read_mask: u8, write_mask: u8) -> vk::StencilOpState {
+    vk::StencilOpState {
+        fail_op: to_vk_stencil_op(info.fail),
+        pass_op: to_vk_stencil_op(info.pass),
+        depth_fail_op: to_vk_stencil_op(info.depth_fail),
+        compare_op: to_vk_compare_op(info.func),
+        compare_mask: read_mask as u32,
+        write_mask: write_mask as u32,
+        reference: 0
+    }
+}
+
+fn to_vk_blend_factor(factor: BlendFactor) -> vk::BlendFactor {
+    match factor {
+        BlendFactor::Zero => vk::BlendFactor::ZERO,
+        BlendFactor::One => vk::BlendFactor::ONE,
+        BlendFactor::SrcColour => vk::BlendFactor::SRC_COLOR,
+        BlendFactor::InvSrcColour => vk::BlendFactor::ONE_MINUS_SRC_COLOR,
+        BlendFactor::SrcAlpha => vk::BlendFactor::SRC_ALPHA,
+        BlendFactor::InvSrcAlpha => vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+        BlendFactor::DstAlpha => vk::BlendFactor::DST_ALPHA,
+        BlendFactor::InvDstAlpha => vk::BlendFactor::ONE_MINUS_DST_ALPHA,
+        BlendFactor::DstColour => vk::BlendFactor::DST_COLOR,
+        BlendFactor::InvDstColour => vk::BlendFactor::ONE_MINUS_DST_COLOR,
+        BlendFactor::SrcAlphaSat => vk::BlendFactor::SRC_ALPHA_SATURATE,
+        BlendFactor::BlendFactor => vk::BlendFactor::CONSTANT_COLOR,
+        BlendFactor::InvBlendFactor => vk::BlendFactor::ONE_MINUS_CONSTANT_COLOR,
+        BlendFactor::Src1Colour => vk::BlendFactor::SRC1_COLOR,
+        BlendFactor::InvSrc1Colour => vk::BlendFactor::ONE_MINUS_SRC1_COLOR,
+        BlendFactor::Src1Alpha => vk::BlendFactor::SRC1_ALPHA,
+        BlendFactor::InvSrc1Alpha => vk::BlendFactor::ONE_MINUS_SRC1_ALPHA,
+    }
+}
+
+fn to_vk_blend_op(op: BlendOp) -> vk::BlendOp {
+    match op {
+        BlendOp::Add => vk::BlendOp::ADD,
+        BlendOp::Subtract => vk::BlendOp::SUBTRACT,
+        BlendOp::RevSubtract => vk::BlendOp::REVERSE_SUBTRACT,
+        BlendOp::Min => vk::BlendOp::MIN,
+        BlendOp::Max => vk::BlendOp::MAX,
+    }
+}
+
+fn to_vk_logic_op(op: LogicOp) -> vk::LogicOp {
+    match op {
+        LogicOp::Clear => vk::LogicOp::CLEAR,
+        LogicOp::Set => vk::LogicOp::SET,
+        LogicOp::Copy => vk::LogicOp::COPY,
+        LogicOp::CopyInverted => vk::LogicOp::COPY_INVERTED,
+        LogicOp::NoOp => vk::LogicOp::NO_OP,
+        LogicOp::Invert => vk::LogicOp::INVERT,
+        LogicOp::And => vk::LogicOp::AND,
+        LogicOp::Nand => vk::LogicOp::NAND,
+        LogicOp::Or => vk::LogicOp::OR,
+        LogicOp::Nor => vk::LogicOp::NOR,
+        LogicOp::Xor => vk::LogicOp::XOR,
+        LogicOp::Equiv => vk::LogicOp::EQUIVALENT,
+        LogicOp::AndReverse => vk::LogicOp::AND_REVERSE,
+        LogicOp::AndInverted => vk::LogicOp::AND_INVERTED,
+        LogicOp::OrReverse => vk::LogicOp::OR_REVERSE,
+        LogicOp::OrInverted => vk::LogicOp::OR_INVERTED,
+    }
+}
+
+fn to_vk_colour_write_mask(mask: WriteMask) -> vk::ColorComponentFlags {
+    let mut flags = vk::ColorComponentFlags::empty();
+    if mask.contains(WriteMask::RED) {
+        flags |= vk::ColorComponentFlags::R;
+    }
+    if mask.contains(WriteMask::GREEN) {
+        flags |= vk::ColorComponentFlags::G;
+    }
+    if mask.contains(WriteMask::BLUE) {
+        flags |= vk::ColorComponentFlags::B;
+    }
+    if mask.contains(WriteMask::ALPHA) {
+        flags |= vk::ColorComponentFlags::A;
+    }
+    flags
+}
+
+fn to_vk_topology(topology: Topology) -> vk::PrimitiveTopology {
+    match topology {
+        Topology::Undefined => vk::PrimitiveTopology::TRIANGLE_LIST,
+        Topology::PointList => vk::PrimitiveTopology::POINT_LIST,
+        Topology::LineList => vk::PrimitiveTopology::LINE_LIST,
+        Topology::LineStrip => vk::PrimitiveTopology::LINE_STRIP,
+        Topology::TriangleList => vk::PrimitiveTopology::TRIANGLE_LIST,
+        Topology::TriangleStrip => vk::PrimitiveTopology::TRIANGLE_STRIP,
+        Topology::LineListAdj => vk::PrimitiveTopology::LINE_LIST_WITH_ADJACENCY,
+        Topology::LineStripAdj => vk::PrimitiveTopology::LINE_STRIP_WITH_ADJACENCY,
+        Topology::TriangleListAdj => vk::PrimitiveTopology::TRIANGLE_LIST_WITH_ADJACENCY,
+        Topology::TriangleStripAdj => vk::PrimitiveTopology::TRIANGLE_STRIP_WITH_ADJACENCY,
+        Topology::PatchList => vk::PrimitiveTopology::PATCH_LIST,
+    }
+}
+
+fn to_vk_filter(filter: SamplerFilter) -> (vk::Filter, vk::SamplerMipmapMode) {
+    match filter {
+        SamplerFilter::Point => (vk::Filter::NEAREST, vk::SamplerMipmapMode::NEAREST),
+        SamplerFilter::Linear => (vk::Filter::LINEAR, vk::SamplerMipmapMode::LINEAR),
+        SamplerFilter::Anisotropic => (vk::Filter::LINEAR, vk::SamplerMipmapMode::LINEAR),
+    }
+}
+
+fn to_vk_address_mode(mode: SamplerAddressMode, mirror_clamp_to_edge: bool) -> vk::SamplerAddressMode {
+    match mode {
+        SamplerAddressMode::Wrap => vk::SamplerAddressMode::REPEAT,
+        SamplerAddressMode::Mirror => vk::SamplerAddressMode::MIRRORED_REPEAT,
+        SamplerAddressMode::Clamp => vk::SamplerAddressMode::CLAMP_TO_EDGE,
+        SamplerAddressMode::Border => vk::SamplerAddressMode::CLAMP_TO_BORDER,
+        SamplerAddressMode::MirrorOnce => if mirror_clamp_to_edge {
+            vk::SamplerAddressMode::MIRROR_CLAMP_TO_EDGE
+        }
+        else {
+            vk::SamplerAddressMode::MIRRORED_REPEAT
+        }
+    }
+}
+
+/// Vulkan only supports fixed border colours without `VK_EXT_custom_border_color`, so the packed colour is
+/// snapped to transparent black, opaque white or opaque black
+fn to_vk_border_colour(colour: Option<u32>) -> vk::BorderColor {
+    match colour {
+        None | Some(0) => vk::BorderColor::FLOAT_TRANSPARENT_BLACK,
+        Some(0xffffffff) => vk::BorderColor::FLOAT_OPAQUE_WHITE,
+        _ => vk::BorderColor::FLOAT_OPAQUE_BLACK
+    }
+}
+
+/// Pipeline statistics in the same order as the members of `PipelineStatistics`
+fn pipeline_statistics_flags() -> vk::QueryPipelineStatisticFlags {
+    vk::QueryPipelineStatisticFlags::INPUT_ASSEMBLY_VERTICES |
+    vk::QueryPipelineStatisticFlags::INPUT_ASSEMBLY_PRIMITIVES |
+    vk::QueryPipelineStatisticFlags::VERTEX_SHADER_INVOCATIONS |
+    vk::QueryPipelineStatisticFlags::CLIPPING_PRIMITIVES |
+    vk::QueryPipelineStatisticFlags::COMPUTE_SHADER_INVOCATIONS
+}
+
+/// Returns the binding shift for the register kind used by `descriptor_type`
+fn binding_shift(descriptor_type: DescriptorType) -> u32 {
+    match descriptor_type {
+        DescriptorType::ShaderResource => SHADER_RESOURCE_BINDING_SHIFT,
+        DescriptorType::Sampler => SAMPLER_BINDING_SHIFT,
+        DescriptorType::UnorderedAccess => UNORDERED_ACCESS_BINDING_SHIFT,
+        DescriptorType::ConstantBuffer | DescriptorType::PushConstants => CONSTANT_BUFFER_BINDING_SHIFT,
+    }
+}
+
+/// Derives the descriptor kind of a binding, the reflected `resource_type` is used when available
+fn to_descriptor_kind(binding: &DescriptorBinding) -> Result<DescriptorKind, Error> {
+    let image = |storage, view_type, multisampled| Ok(DescriptorKind::Image {
+        storage,
+        view_type: Some(view_type),
+        multisampled
+    });
+    match binding.resource_type {
+        Some(ResourceType::Texture2D) => image(false, vk::ImageViewType::TYPE_2D, false),
+        Some(ResourceType::Texture2DArray) => image(false, vk::ImageViewType::TYPE_2D_ARRAY, false),
+        Some(ResourceType::Texture3D) => image(false, vk::ImageViewType::TYPE_3D, false),
+        Some(ResourceType::TextureCube) => image(false, vk::ImageViewType::CUBE, false),
+        Some(ResourceType::Texture2DMS) => image(false, vk::ImageViewType::TYPE_2D, true),
+        Some(ResourceType::RWTexture2D) => image(true, vk::ImageViewType::TYPE_2D, false),
+        Some(ResourceType::RWTexture3D) => image(true, vk::ImageViewType::TYPE_3D, false),
+        Some(ResourceType::StructuredBuffer) |
+        Some(ResourceType::RWStructuredBuffer) |
+        Some(ResourceType::AppendStructuredBuffer) |
+        Some(ResourceType::ConsumeStructuredBuffer) |
+        Some(ResourceType::ByteAddressBuffer) |
+        Some(ResourceType::RWByteAddressBuffer) => Ok(DescriptorKind::Buffer {
+            uniform: false
+        }),
+        Some(ResourceType::ConstantBuffer) => Ok(DescriptorKind::Buffer {
+            uniform: true
+        }),
+        Some(ResourceType::Buffer) | Some(ResourceType::RaytracingAccelerationStructure) => Err(Error {
+            msg: format!("hotline_rs::gfx::vk: resource type {:?} at register {} space {} is not supported",
+                binding.resource_type.unwrap(), binding.shader_register, binding.register_space)
+        }),
+        None => match binding.binding_type {
+            DescriptorType::ShaderResource => Ok(DescriptorKind::Image {
+                storage: false,
+                view_type: None,
+                multisampled: false
+            }),
+            DescriptorType::UnorderedAccess => Ok(DescriptorKind::Image {
+                storage: true,
+                view_type: None,
+                multisampled: false
+            }),
+            DescriptorType::ConstantBuffer => Ok(DescriptorKind::Buffer {
+                uniform: true
+            }),
+            DescriptorType::Sampler => Ok(DescriptorKind::Sampler),
+            DescriptorType::PushConstants => Err(Error {
+                msg: "hotline_rs::gfx::vk: push constants cannot be used as a descriptor binding".to_string()
+            })
+        }
+    }
+}
+
+impl DescriptorKind {
+    fn to_vk(self) -> vk::DescriptorType {
+        match self {
+            DescriptorKind::Image { storage: true, .. } => vk::DescriptorType::STORAGE_IMAGE,
+            DescriptorKind::Image { storage: false, .. } => vk::DescriptorType::SAMPLED_IMAGE,
+            DescriptorKind::Buffer { uniform: true } => vk::DescriptorType::UNIFORM_BUFFER,
+            DescriptorKind::Buffer { uniform: false } => vk::DescriptorType::STORAGE_BUFFER,
+            DescriptorKind::Sampler => vk::DescriptorType::SAMPLER,
+        }
+    }
+
+    /// Returns true if `descriptor` can be written to a binding of this kind, mismatched descriptors are left unbound
+    fn accepts(&self, descriptor: &Descriptor) -> bool {
+        match (self, descriptor) {
+            (DescriptorKind::Image { storage, view_type, multisampled }, Descriptor::Image { view_type: vt, storage: s, multisampled: ms, .. }) => {
+                storage == s && multisampled == ms && view_type.map(|t| t == *vt).unwrap_or(true)
+            }
+            (DescriptorKind::Buffer { uniform }, Descriptor::Buffer { uniform: u, .. }) => uniform == u,
+            _ => false
+        }
+    }
+
+    /// Returns true if the device supports updating bindings of this kind after they are bound
+    fn update_after_bind(&self, features: &EnabledFeatures) -> bool {
+        match self {
+            DescriptorKind::Image { storage: true, .. } => features.storage_image_update_after_bind,
+            DescriptorKind::Image { storage: false, .. } => features.sampled_image_update_after_bind,
+            DescriptorKind::Buffer { uniform: true } => features.uniform_buffer_update_after_bind,
+            DescriptorKind::Buffer { uniform: false } => features.storage_buffer_update_after_bind,
+            DescriptorKind::Sampler => false
+        }
+    }
+}
+
+/// Finds the entry point name and stage of a SPIR-V module from the first `OpEntryPoint`
+fn parse_spirv_entry_point(words: &[u32]) -> Option<(vk::ShaderStageFlags, CString)> {
+    let mut i = 5;
+    while i < words.len() {
+        let word_count = (words[i] >> 16) as usize;
+        let opcode = words[i] & 0xffff;
+        if word_count == 0 || i + word_count > words.len() {
+            return None;
+        }
+        if opcode == SPIRV_OP_ENTRY_POINT && word_count > 3 {
+            let stage = match words[i + 1] {
+                0 => vk::ShaderStageFlags::VERTEX,
+                1 => vk::ShaderStageFlags::TESSELLATION_CONTROL,
+                2 => vk::ShaderStageFlags::TESSELLATION_EVALUATION,
+                3 => vk::ShaderStageFlags::GEOMETRY,
+                4 => vk::ShaderStageFlags::FRAGMENT,
+                5 => vk::ShaderStageFlags::COMPUTE,
+                _ => return None
+            };
+            let mut name = Vec::new();
+            'name: for word in &words[i + 3..i + word_count] {
+                for byte in word.to_le_bytes() {
+                    if byte == 0 {
+                        break 'name;
+                    }
+                    name.push(byte);
+                }
+            }
+            return CString::new(name).ok().map(|name| (stage, name));
+        }
+        i += word_count;
+    }
+    None
+}
+
+fn to_vk_shader_stage(shader_type: ShaderType) -> vk::ShaderStageFlags {
+    match shader_type {
+        ShaderType::Vertex => vk::ShaderStageFlags::VERTEX,
+        ShaderType::Fragment => vk::ShaderStageFlags::FRAGMENT,
+        ShaderType::Compute => vk::ShaderStageFlags::COMPUTE,
+        _ => vk::ShaderStageFlags::ALL
+    }
+}
+
+/// Builds a slot lookup in the same order as the d3d12 root signature: push constants first, then bindings
+fn create_slot_lookup(layout: &PipelineLayout) -> (Vec<u32>, HashMap<SlotKey, PipelineSlotInfo>) {
+    let mut slots = Vec::new();
+    let mut lookup = HashMap::new();
+    let mut slot_iter = 0;
+    if let Some(push_constants) = &layout.push_constants {
+        for constants in push_constants {
+            lookup.insert((constants.shader_register, constants.register_space, DescriptorType::PushConstants), PipelineSlotInfo {
+                index: slot_iter,
+                count: Some(constants.num_values)
+            });
+            slots.push(slot_iter);
+            slot_iter += 1;
+        }
+    }
+    if let Some(bindings) = &layout.bindings {
+        for binding in bindings {
+            lookup.insert((binding.shader_register, binding.register_space, binding.binding_type), PipelineSlotInfo {
+                index: slot_iter,
+                count: binding.num_descriptors
+            });
+            slots.push(slot_iter);
+            slot_iter += 1;
+        }
+    }
+    (slots, lookup)
+}
+
+/// Returns the byte offset and dimensions of each subresource in the layer-major, mip-minor order used for texture data
+fn texture_subresource_offsets(info: &TextureInfo) -> Vec<(u64, u32, u32, vk::Extent3D)> {
+    let mut offsets = Vec::new();
+    let mut offset = 0;
+    let layers = if matches!(info.tex_type, TextureType::Texture3D) { 1 } else { info.array_layers.max(1) };
+    for layer in 0..layers {
+        let mut width = info.width;
+        let mut height = info.height;
+        let mut depth = info.depth.max(1);
+        for mip in 0..info.mip_levels.max(1) {
+            offsets.push((offset, mip, layer, vk::Extent3D {
+                width: width as u32,
+                height: height as u32,
+                depth
+            }));
+            offset += super::size_for_format(info.format, width, height, depth);
+            width = max(width / 2, 1);
+            height = max(height / 2, 1);
+            depth = max(depth / 2, 1);
+        }
+    }
+    offsets
+}
+
+impl DeviceInner {
+    /// Finds a memory type in `type_bits` with the `required` properties, preferring one which also has `preferred`
+    fn find_memory_type(&self, type_bits: u32, required: vk::MemoryPropertyFlags, preferred: vk::MemoryPropertyFlags) -> Option<u32> {
+        let count = self.memory_properties.memory_type_count as usize;
+        let types = &self.memory_properties.memory_types[..count];
+        let suitable = |i: usize, flags: vk::MemoryPropertyFlags| {
+            type_bits & (1 << i) != 0 && types[i].property_flags.contains(flags)
+        };
+        (0..count).find(|i| suitable(*i, required | preferred))
+            .or_else(|| (0..count).find(|i| suitable(*i, required)))
+            .map(|i| i as u32)
+    }
+
+    /// Allocates memory for `requirements`, returns the memory and a mapped pointer if the memory is host visible
+    fn allocate_memory(&self, requirements: vk::MemoryRequirements, required: vk::MemoryPropertyFlags, preferred: vk::MemoryPropertyFlags) -> Result<(vk::DeviceMemory, *mut u8), Error> {
+        let memory_type = self.find_memory_type(requirements.memory_type_bits, required, preferred).ok_or(Error {
+            msg: format!("hotline_rs::gfx::vk: no memory type with properties {:?}", required)
+        })?;
+        unsafe {
+            let memory = self.device.allocate_memory(&vk::MemoryAllocateInfo::default()
+                .allocation_size(requirements.size)
+                .memory_type_index(memory_type), None)
+                .map_err(|e| vk_error("vkAllocateMemory", e))?;
+            let flags = self.memory_properties.memory_types[memory_type as usize].property_flags;
+            let mapped = if flags.contains(vk::MemoryPropertyFlags::HOST_VISIBLE) {
+                self.device.map_memory(memory, 0, vk::WHOLE_SIZE, vk::MemoryMapFlags::empty())
+                    .map_err(|e| vk_error("vkMapMemory", e))? as *mut u8
+            }
+            else {
+                std::ptr::null_mut()
+            };
+            Ok((memory, mapped))
+        }
+    }
+
+    /// Creates a buffer with its own memory allocation, host visible memory is persistently mapped
+    fn create_buffer(&self, size: usize, usage: vk::BufferUsageFlags, required: vk::MemoryPropertyFlags, preferred: vk::MemoryPropertyFlags) -> Result<(vk::Buffer, vk::DeviceMemory, *mut u8), Error> {
+        unsafe {
+            let buffer = self.device.create_buffer(&vk::BufferCreateInfo::default()
+                .size(size.max(1) as u64)
+                .usage(usage)
+                .sharing_mode(vk::SharingMode::EXCLUSIVE), None)
+                .map_err(|e| vk_error("vkCreateBuffer", e))?;
+            let requirements = self.device.get_buffer_memory_requirements(buffer);
+            let (memory, mapped) = match self.allocate_memory(requirements, required, preferred) {
+                Ok(allocation) => allocation,
+                Err(err) => {
+                    self.device.destroy_buffer(buffer, None);
+                    return Err(err);
+                }
+            };
+            self.device.bind_buffer_memory(buffer, memory, 0)
+                .map_err(|e| vk_error("vkBindBufferMemory", e))?;
+            Ok((buffer, memory, mapped))
+        }
+    }
+
+    /// Records commands with `f` and submits them to the queue, waiting for them to complete before returning
+    fn immediate_submit<F: FnOnce(vk::CommandBuffer)>(&self, f: F) -> Result<(), Error> {
+        let pool = self.upload_pool.lock().unwrap();
+        unsafe {
+            let cmd = self.device.allocate_command_buffers(&vk::CommandBufferAllocateInfo::default()
+                .command_pool(*pool)
+                .level(vk::CommandBufferLevel::PRIMARY)
+                .command_buffer_count(1))
+                .map_err(|e| vk_error("vkAllocateCommandBuffers", e))?[0];
+            self.device.begin_command_buffer(cmd, &vk::CommandBufferBeginInfo::default()
+                .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT))
+                .map_err(|e| vk_error("vkBeginCommandBuffer", e))?;
+            f(cmd);
+            self.device.end_command_buffer(cmd)
+                .map_err(|e| vk_error("vkEndCommandBuffer", e))?;
+            let fence = self.device.create_fence(&vk::FenceCreateInfo::default(), None)
+                .map_err(|e| vk_error("vkCreateFence", e))?;
+            let cmd_infos = [vk::CommandBufferSubmitInfo::default().command_buffer(cmd)];
+            let submit = vk::SubmitInfo2::default().command_buffer_infos(&cmd_infos);
+            let result = {
+                let queue = self.queue.lock().unwrap();
+                self.device.queue_submit2(*queue, &[submit], fence)
+            }
+            .and_then(|_| self.device.wait_for_fences(&[fence], true, u64::MAX));
+            self.device.destroy_fence(fence, None);
+            self.device.free_command_buffers(*pool, &[cmd]);
+            result.map_err(|e| vk_error("immediate submit", e))
+        }
+    }
+
+    /// Returns the vulkan queue which work for `queue` is submitted to
+    fn get_queue(&self, queue: QueueType) -> &Mutex<vk::Queue> {
+        match (queue, &self.async_queue) {
+            (QueueType::Compute | QueueType::Copy, Some(async_queue)) => async_queue,
+            _ => &self.queue
+        }
+    }
+
+    /// Submits `submit` to `queue` reporting any errors to the info queue
+    fn submit(&self, queue: QueueType, submit: vk::SubmitInfo2) {
+        unsafe {
+            let queue = self.get_queue(queue).lock().unwrap();
+            if let Err(err) = self.device.queue_submit2(*queue, &[submit], vk::Fence::null()) {
+                report(&self.info_queue, "error", format!("vkQueueSubmit2 failed: {:?}", err));
+            }
+        }
+    }
+
+    /// Creates or returns a cached immutable sampler for the serialised `SamplerInfo` in `key`
+    fn get_sampler(&self, key: &str) -> Result<vk::Sampler, Error> {
+        let mut samplers = self.samplers.lock().unwrap();
+        if let Some(sampler) = samplers.get(key) {
+            return Ok(*sampler);
+        }
+        let info : SamplerInfo = serde_json::from_str(key).map_err(|e| Error {
+            msg: format!("hotline_rs::gfx::vk: failed to deserialise sampler info: {}", e)
+        })?;
+        let (filter, mipmap_mode) = to_vk_filter(info.filter);
+        let anisotropy = matches!(info.filter, SamplerFilter::Anisotropic) && self.features.sampler_anisotropy;
+        let mirror = self.features.mirror_clamp_to_edge;
+        let create_info = vk::SamplerCreateInfo::default()
+            .mag_filter(filter)
+            .min_filter(filter)
+            .mipmap_mode(mipmap_mode)
+            .address_mode_u(to_vk_address_mode(info.address_u, mirror))
+            .address_mode_v(to_vk_address_mode(info.address_v, mirror))
+            .address_mode_w(to_vk_address_mode(info.address_w, mirror))
+            .mip_lod_bias(info.mip_lod_bias)
+            .anisotropy_enable(anisotropy)
+            .max_anisotropy((info.max_aniso as f32).clamp(1.0, self.limits.max_sampler_anisotropy))
+            .compare_enable(info.comparison.is_some())
+            .compare_op(info.comparison.map(to_vk_compare_op).unwrap_or(vk::CompareOp::NEVER))
+            .min_lod(info.min_lod)
+            .max_lod(if info.max_lod == f32::MAX { vk::LOD_CLAMP_NONE } else { info.max_lod })
+            .border_color(to_vk_border_colour(info.border_colour));
+        let sampler = unsafe {
+            self.device.create_sampler(&create_info, None)
+                .map_err(|e| vk_error("vkCreateSampler", e))?
+        };
+        samplers.insert(key.to_string(), sampler);
+        Ok(sampler)
+    }
+
+    /// Creates or returns a cached descriptor set layout for `bindings`
+    fn get_set_layout(&self, bindings: Vec<SetLayoutBinding>) -> Result<Arc<SetLayout>, Error> {
+        let mut set_layouts = self.set_layouts.lock().unwrap();
+        if let Some(layout) = set_layouts.get(&bindings) {
+            return Ok(layout.clone());
+        }
+
+        let mut immutable_samplers = Vec::new();
+        for binding in &bindings {
+            immutable_samplers.push(match &binding.sampler {
+                Some(key) => vec![self.get_sampler(key)?],
+                None => Vec::new()
+            });
+        }
+
+        let mut vk_bindings = Vec::new();
+        let mut binding_flags = Vec::new();
+        let mut pool_sizes : Vec<vk::DescriptorPoolSize> = Vec::new();
+        let mut update_after_bind = false;
+        for (i, binding) in bindings.iter().enumerate() {
+            let descriptor_type = binding.kind.to_vk();
+            let mut vk_binding = vk::DescriptorSetLayoutBinding::default()
+                .binding(binding.binding)
+                .descriptor_type(descriptor_type)
+                .descriptor_count(binding.count)
+                .stage_flags(vk::ShaderStageFlags::ALL);
+            if !immutable_samplers[i].is_empty() {
+                vk_binding = vk_binding.immutable_samplers(&immutable_samplers[i]);
+            }
+            vk_bindings.push(vk_binding);
+
+            let mut flags = vk::DescriptorBindingFlags::PARTIALLY_BOUND;
+            if binding.kind.update_after_bind(&self.features) {
+                flags |= vk::DescriptorBindingFlags::UPDATE_AFTER_BIND;
+                update_after_bind = true;
+            }
+            if self.features.update_unused_while_pending && binding.sampler.is_none() {
+                flags |= vk::DescriptorBindingFlags::UPDATE_UNUSED_WHILE_PENDING;
+            }
+            binding_flags.push(flags);
+
+            if let Some(size) = pool_sizes.iter_mut().find(|s| s.ty == descriptor_type) {
+                size.descriptor_count += binding.count;
+            }
+            else {
+                pool_sizes.push(vk::DescriptorPoolSize {
+                    ty: descriptor_type,
+                    descriptor_count: binding.count
+                });
+            }
+        }
+
+        let mut flags_info = vk::DescriptorSetLayoutBindingFlagsCreateInfo::default()
+            .binding_flags(&binding_flags);
+        let create_info = vk::DescriptorSetLayoutCreateInfo::default()
+            .flags(if update_after_bind {
+                vk::DescriptorSetLayoutCreateFlags::UPDATE_AFTER_BIND_POOL
+            }
+            else {
+                vk::DescriptorSetLayoutCreateFlags::empty()
+            })
+            .bindings(&vk_bindings)
+            .push_next(&mut flags_info);
+        let layout = unsafe {
+            self.device.create_descriptor_set_layout(&create_info, None)
+                .map_err(|e| vk_error("vkCreateDescriptorSetLayout", e))?
+        };
+
+        let set_layout = Arc::new(SetLayout {
+            layout,
+            bindings: bindings.clone(),
+            pool_sizes,
+            update_after_bind
+        });
+        set_layouts.insert(bindings, set_layout.clone());
+        Ok(set_layout)
+    }
+
+    /// Creates a pipeline layout mapping each register space to a descriptor set and all push constants into a
+    /// single push constant range in the order they appear in `layout`
+    fn create_pipeline_layout(self: &Arc<Self>, layout: &PipelineLayout, slots: &HashMap<SlotKey, PipelineSlotInfo>, pipeline_type: PipelineType) -> Result<PipelineLayoutState, Error> {
+        // push constants
+        let mut push_constant_offsets = HashMap::new();
+        let mut push_constant_size = 0;
+        if let Some(push_constants) = &layout.push_constants {
+            for constants in push_constants {
+                let key = (constants.shader_register, constants.register_space, DescriptorType::PushConstants);
+                push_constant_offsets.insert(slots[&key].index, push_constant_size);
+                push_constant_size += constants.num_values * 4;
+            }
+        }
+        if push_constant_size > self.limits.max_push_constants_size {
+            return Err(Error {
+                msg: format!("hotline_rs::gfx::vk: push constants size ({}) exceeds device limit ({})",
+                    push_constant_size, self.limits.max_push_constants_size)
+            });
+        }
+
+        // bindings grouped by space
+        let mut spaces : BTreeMap<u32, Vec<SetLayoutBinding>> = BTreeMap::new();
+        if let Some(bindings) = &layout.bindings {
+            for binding in bindings {
+                spaces.entry(binding.register_space).or_default().push(SetLayoutBinding {
+                    binding: binding.shader_register + binding_shift(binding.binding_type),
+                    kind: to_descriptor_kind(binding)?,
+                    count: binding.num_descriptors.unwrap_or(self.unbounded_descriptor_count),
+                    sampler: None
+                });
+            }
+        }
+        if let Some(samplers) = &layout.static_samplers {
+            for sampler in samplers {
+                let key = serde_json::to_string(&sampler.sampler_info).map_err(|e| Error {
+                    msg: format!("hotline_rs::gfx::vk: failed to serialise sampler info: {}", e)
+                })?;
+                spaces.entry(sampler.register_space).or_default().push(SetLayoutBinding {
+                    binding: sampler.shader_register + SAMPLER_BINDING_SHIFT,
+                    kind: DescriptorKind::Sampler,
+                    count: 1,
+                    sampler: Some(key)
+                });
+            }
+        }
+
+        let num_sets = spaces.keys().last().map(|space| space + 1).unwrap_or(0);
+        if num_sets > self.limits.max_bound_descriptor_sets {
+            return Err(Error {
+                msg: format!("hotline_rs::gfx::vk: register space {} exceeds the device limit of {} descriptor sets",
+                    num_sets - 1, self.limits.max_bound_descriptor_sets)
+            });
+        }
+
+        // spaces with no bindings get an empty set layout so set indices match register spaces
+        let mut set_layouts = Vec::new();
+        for space in 0..num_sets {
+            let mut bindings = spaces.remove(&space).unwrap_or_default();
+            bindings.sort_by_key(|b| b.binding);
+            if let Some(dup) = bindings.windows(2).find(|w| w[0].binding == w[1].binding) {
+                return Err(Error {
+                    msg: format!("hotline_rs::gfx::vk: binding {} is declared more than once in space {}", dup[0].binding, space)
+                });
+            }
+            set_layouts.push(self.get_set_layout(bindings)?);
+        }
+
+        let (push_constant_stages, bind_point) = match pipeline_type {
+            PipelineType::Render => (vk::ShaderStageFlags::ALL_GRAPHICS, vk::PipelineBindPoint::GRAPHICS),
+            _ => (vk::ShaderStageFlags::COMPUTE, vk::PipelineBindPoint::COMPUTE)
+        };
+        let vk_set_layouts : Vec<vk::DescriptorSetLayout> = set_layouts.iter().map(|s| s.layout).collect();
+        let ranges = [vk::PushConstantRange {
+            stage_flags: push_constant_stages,
+            offset: 0,
+            size: push_constant_size
+        }];
+        let mut create_info = vk::PipelineLayoutCreateInfo::default()
+            .set_layouts(&vk_set_layouts);
+        if push_constant_size > 0 {
+            create_info = create_info.push_constant_ranges(&ranges);
+        }
+        let pipeline_layout = unsafe {
+            self.device.create_pipeline_layout(&create_info, None)
+                .map_err(|e| vk_error("vkCreatePipelineLayout", e))?
+        };
+
+        Ok(PipelineLayoutState {
+            device: self.clone(),
+            layout: pipeline_layout,
+            set_layouts,
+            push_constant_offsets,
+            push_constant_stages,
+            bind_point
+        })
+    }
+
+    /// Creates a view of `image`, `resource` takes ownership of the view
+    fn create_image_view(&self, resource: &mut VkResource, image: vk::Image, view_type: vk::ImageViewType, format: vk::Format, range: vk::ImageSubresourceRange) -> Result<vk::ImageView, Error> {
+        let view = unsafe {
+            self.device.create_image_view(&vk::ImageViewCreateInfo::default()
+                .image(image)
+                .view_type(view_type)
+                .format(format)
+                .subresource_range(range), None)
+                .map_err(|e| vk_error("vkCreateImageView", e))?
+        };
+        resource.views.push(view);
+        Ok(view)
+    }
+
+    /// Returns true if `format` supports all `features` with optimal tiling
+    fn format_supports(&self, format: vk::Format, features: vk::FormatFeatureFlags) -> bool {
+        unsafe {
+            self.instance.get_physical_device_format_properties(self.physical_device, format)
+                .optimal_tiling_features
+                .contains(features)
+        }
+    }
+}
+
+impl Drop for DeviceInner {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = self.device.device_wait_idle();
+            for (_, layout) in self.set_layouts.lock().unwrap().drain() {
+                self.device.destroy_descriptor_set_layout(layout.layout, None);
+            }
+            for (_, sampler) in self.samplers.lock().unwrap().drain() {
+                self.device.destroy_sampler(sampler, None);
+            }
+            self.device.destroy_command_pool(*self.upload_pool.lock().unwrap(), None);
+            self.device.destroy_device(None);
+            if let Some(debug_utils) = &self.debug_utils_instance {
+                debug_utils.destroy_debug_utils_messenger(self.debug_messenger, None);
+            }
+            self.instance.destroy_instance(None);
+        }
+    }
+}
+
+impl VkResource {
+    fn new(device: &Arc<DeviceInner>) -> Self {
+        VkResource {
+            device: device.clone(),
+            buffers: Vec::new(),
+            images: Vec::new(),
+            views: Vec::new(),
+            memory: Vec::new(),
+            pipelines: Vec::new(),
+            query_pools: Vec::new()
+        }
+    }
+}
+
+impl Drop for VkResource {
+    fn drop(&mut self) {
+        let device = &self.device.device;
+        unsafe {
+            for pipeline in &self.pipelines {
+                device.destroy_pipeline(*pipeline, None);
+            }
+            for view in &self.views {
+                device.destroy_image_view(*view, None);
+            }
+            for image in &self.images {
+                device.destroy_image(*image, None);
+            }
+            for buffer in &self.buffers {
+                device.destroy_buffer(*buffer, None);
+            }
+            for memory in &self.memory {
+                device.free_memory(*memory, None);
+            }
+            for pool in &self.query_pools {
+                device.destroy_query_pool(*pool, None);
+            }
+        }
+    }
+}
+
+impl Drop for TimelineSemaphore {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.device.destroy_semaphore(self.semaphore, None);
+        }
+    }
+}
+
+impl TimelineSemaphore {
+    fn get_value(&self) -> u64 {
+        unsafe {
+            self.device.device.get_semaphore_counter_value(self.semaphore).unwrap_or(0)
+        }
+    }
+
+    fn wait(&self, value: u64) {
+        let semaphores = [self.semaphore];
+        let values = [value];
+        unsafe {
+            self.device.device.wait_semaphores(&vk::SemaphoreWaitInfo::default()
+                .semaphores(&semaphores)
+                .values(&values), u64::MAX)
+                .expect("hotline_rs::gfx::vk: vkWaitSemaphores failed!");
+        }
+    }
+}
+
+impl Drop for CommandPool {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.device.destroy_command_pool(self.pool, None);
+        }
+    }
+}
+
+/// Fills `image_infos` and `buffer_infos` with the descriptors in `entries` accepted by `layout` and returns the
+/// (binding, array element, descriptor type, info index, is image) of each write
+fn collect_descriptor_writes(
+    layout: &SetLayout,
+    offset: usize,
+    entries: &[(usize, Descriptor)],
+    image_infos: &mut Vec<vk::DescriptorImageInfo>,
+    buffer_infos: &mut Vec<vk::DescriptorBufferInfo>
+) -> Vec<(u32, u32, vk::DescriptorType, usize, bool)> {
+    let mut writes = Vec::new();
+    for binding in &layout.bindings {
+        if binding.sampler.is_some() {
+            continue;
+        }
+        for (index, descriptor) in entries {
+            if *index < offset || *index - offset >= binding.count as usize || !binding.kind.accepts(descriptor) {
+                continue;
+            }
+            let element = (*index - offset) as u32;
+            match descriptor {
+                Descriptor::Image { view, storage, .. } => {
+                    writes.push((binding.binding, element, binding.kind.to_vk(), image_infos.len(), true));
+                    image_infos.push(vk::DescriptorImageInfo {
+                        sampler: vk::Sampler::null(),
+                        image_view: *view,
+                        image_layout: if *storage {
+                            vk::ImageLayout::GENERAL
+                        }
+                        else {
+                            vk::ImageLayout::READ_ONLY_OPTIMAL
+                        }
+                    });
+                }
+                Descriptor::Buffer { buffer, offset, range, .. } => {
+                    writes.push((binding.binding, element, binding.kind.to_vk(), buffer_infos.len(), false));
+                    buffer_infos.push(vk::DescriptorBufferInfo {
+                        buffer: *buffer,
+                        offset: *offset,
+                        range: *range
+                    });
+                }
+            }
+        }
+    }
+    writes
+}
+
+/// Writes the descriptors in `entries` which fall within the range of `set`
+fn write_descriptors(device: &ash::Device, set: &CachedSet, entries: &[(usize, Descriptor)]) {
+    let mut image_infos = Vec::new();
+    let mut buffer_infos = Vec::new();
+    let writes = collect_descriptor_writes(&set.layout, set.offset, entries, &mut image_infos, &mut buffer_infos);
+    if writes.is_empty() {
+        return;
+    }
+    let vk_writes : Vec<vk::WriteDescriptorSet> = writes.iter().map(|(binding, element, ty, info, image)| {
+        let write = vk::WriteDescriptorSet::default()
+            .dst_set(set.set)
+            .dst_binding(*binding)
+            .dst_array_element(*element)
+            .descriptor_type(*ty);
+        if *image {
+            write.image_info(&image_infos[*info..*info + 1])
+        }
+        else {
+            write.buffer_info(&buffer_infos[*info..*info + 1])
+        }
+    }).collect();
+    unsafe {
+        device.update_descriptor_sets(&vk_writes, &[]);
+    }
+}
+
+impl HeapState {
+    /// Writes `descriptor` into slot `index` and updates any descriptor sets which include the slot
+    fn set(&mut self, index: usize, descriptor: Descriptor) {
+        if self.entries.len() <= index {
+            self.entries.resize(index + 1, None);
+        }
+        self.entries[index] = Some(descriptor);
+        for set in self.sets.values() {
+            write_descriptors(&self.device.device, set, &[(index, descriptor)]);
+        }
+    }
+
+    /// Clears slots so they are not written into new descriptor sets, existing sets keep the stale descriptors
+    /// which is valid for `PARTIALLY_BOUND` bindings as long as shaders do not access them
+    fn clear(&mut self, indices: &[usize]) {
+        for index in indices {
+            if let Some(entry) = self.entries.get_mut(*index) {
+                *entry = None;
+            }
+        }
+    }
+
+    /// Returns a descriptor set for `layout` where element 0 of each binding maps to heap slot `offset`
+    fn get_descriptor_set(&mut self, layout: &Arc<SetLayout>, offset: usize) -> Result<vk::DescriptorSet, Error> {
+        if let Some(set) = self.sets.get(&(layout.layout, offset)) {
+            return Ok(set.set);
+        }
+        let device = &self.device.device;
+        let set = unsafe {
+            let mut pool_info = vk::DescriptorPoolCreateInfo::default()
+                .max_sets(1);
+            if layout.update_after_bind {
+                pool_info = pool_info.flags(vk::DescriptorPoolCreateFlags::UPDATE_AFTER_BIND);
+            }
+            if !layout.pool_sizes.is_empty() {
+                pool_info = pool_info.pool_sizes(&layout.pool_sizes);
+            }
+            let pool = device.create_descriptor_pool(&pool_info, None)
+                .map_err(|e| vk_error("vkCreateDescriptorPool", e))?;
+            let set_layouts = [layout.layout];
+            let set = match device.allocate_descriptor_sets(&vk::DescriptorSetAllocateInfo::default()
+                .descriptor_pool(pool)
+                .set_layouts(&set_layouts)) {
+                Ok(sets) => sets[0],
+                Err(e) => {
+                    device.destroy_descriptor_pool(pool, None);
+                    return Err(vk_error("vkAllocateDescriptorSets", e));
+                }
+            };
+            CachedSet {
+                pool,
+                set,
+                layout: layout.clone(),
+                offset
+            }
+        };
+        let entries : Vec<(usize, Descriptor)> = self.entries.iter()
+            .enumerate()
+            .filter_map(|(i, d)| d.map(|d| (i, d)))
+            .collect();
+        write_descriptors(device, &set, &entries);
+        let handle = set.set;
+        self.sets.insert((layout.layout, offset), set);
+        Ok(handle)
+    }
+}
+
+impl Drop for HeapState {
+    fn drop(&mut self) {
+        unsafe {
+            for set in self.sets.values() {
+                self.device.device.destroy_descriptor_pool(set.pool, None);
+            }
+        }
+    }
+}
+
+impl Heap {
+    fn create(device: &Arc<DeviceInner>, info: &HeapInfo) -> Heap {
+        Heap {
+            heap_type: info.heap_type,
+            capacity: info.num_descriptors,
+            offset: 0,
+            free_list: FreeList::new(),
+            drop_list: DropList::new(),
+            state: Arc::new(Mutex::new(HeapState {
+                device: device.clone(),
+                entries: Vec::new(),
+                owned: HashMap::new(),
+                sets: HashMap::new()
+            })),
+            id: HEAP_ID.fetch_add(1, Ordering::SeqCst),
+        }
+    }
+
+    /// Allocates a slot from the free list or grows the heap, returns an error if the heap is full
+    fn allocate(&mut self) -> Result<usize, Error> {
+        if let Some(index) = self.free_list.pop() {
+            Ok(index)
+        }
+        else if self.offset < self.capacity {
+            let index = self.offset;
+            self.offset += 1;
+            Ok(index)
+        }
+        else {
+            Err(Error {
+                msg: format!("hotline_rs::gfx::vk: heap {} is full! (capacity: {})", self.id, self.capacity)
+            })
+        }
+    }
+
+    /// Allocates a slot and writes `descriptor` into it
+    fn allocate_descriptor(&mut self, descriptor: Descriptor, allocs: &mut HeapAllocs) -> Result<usize, Error> {
+        let index = self.allocate()?;
+        self.state.lock().unwrap().set(index, descriptor);
+        allocs.indices.push(index);
+        Ok(index)
+    }
+
+    /// Returns the number of slots currently allocated in the heap
+    pub fn get_num_allocated(&self) -> usize {
+        self.offset - self.free_list.list.lock().unwrap().len()
+    }
+
+    /// Returns the total number of slots in the heap
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl HeapAllocs {
+    fn new(heap: &Heap) -> Self {
+        HeapAllocs {
+            drop_list: heap.drop_list.clone(),
+            state: heap.state.clone(),
+            indices: Vec::new(),
+        }
+    }
+
+    /// Replaces the allocation `index` with `replacement` if these are allocations from `heap`
+    fn replace(&mut self, heap: &Heap, index: usize, replacement: usize) {
+        if Arc::ptr_eq(&self.state, &heap.state) {
+            if let Some(alloc) = self.indices.iter_mut().find(|alloc| **alloc == index) {
+                *alloc = replacement;
+            }
+        }
+    }
+
+    /// Clears the heap slots and defers returning them to the free list until the gpu has finished with them
+    fn release(&self) {
+        if !self.indices.is_empty() {
+            self.state.lock().unwrap().clear(&self.indices);
+            self.drop_list.push(DropResource::new(Vec::new(), self.indices.clone()));
+        }
+    }
+}
+
+impl SwapChain {
+    fn create_backbuffers(&mut self, device: &mut Device) -> Result<(), Error> {
+        self.backbuffer_textures.clear();
+        self.backbuffer_passes.clear();
+        self.backbuffer_passes_no_clear.clear();
+        for _ in 0..self.num_buffers {
+            let texture = device.create_texture::<u8>(&TextureInfo {
+                tex_type: TextureType::Texture2D,
+                format: self.format,
+                width: self.width.max(1) as u64,
+                height: self.height.max(1) as u64,
+                depth: 1,
+                array_layers: 1,
+                mip_levels: 1,
+                samples: 1,
+                usage: TextureUsage::RENDER_TARGET,
+                initial_state: ResourceState::Present
+            }, None)?;
+            self.backbuffer_passes.push(device.create_render_pass(&RenderPassInfo {
+                render_targets: vec![&texture],
+                rt_clear: self.clear_colour,
+                depth_stencil: None,
+                ds_clear: None,
+                resolve: false,
+                discard: false,
+                array_slice: 0
+            })?);
+            self.backbuffer_passes_no_clear.push(device.create_render_pass(&RenderPassInfo {
+                render_targets: vec![&texture],
+                rt_clear: None,
+                depth_stencil: None,
+                ds_clear: None,
+                resolve: false,
+                discard: false,
+                array_slice: 0
+            })?);
+            self.backbuffer_textures.push(texture);
+        }
+        Ok(())
+    }
+
+    /// Returns the dimensions of the backbuffer (width, height)
+    pub fn get_size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    /// Returns the last fence value which has been completed by the gpu
+    pub fn get_completed_fence_value(&self) -> u64 {
+        self.semaphore.get_value()
+    }
+}
+
+impl super::SwapChain<Device> for SwapChain {
+    fn new_frame(&mut self) {
+        let fv = self.frame_fence_value[self.bb_index];
+        if fv > 0 {
+            self.semaphore.wait(fv);
+        }
+    }
+
+    fn update<A: App>(&mut self, device: &mut Device, window: &A::Window, cmd: &mut CmdBuf) -> bool {
+        let size = window.get_size();
+        if size.x != self.width || size.y != self.height {
+            self.width = size.x;
+            self.height = size.y;
+            // old backbuffers are released through the drop list once the gpu has finished with them
+            if let Err(err) = self.create_backbuffers(device) {
+                report(&device.inner.info_queue, "error", format!("failed to resize swap chain: {}", err.msg));
+            }
+            true
+        }
+        else {
+            false
+        }
+    }
+
+    fn wait_for_last_frame(&self) {
+        if self.fence_last_signalled_value > 0 {
+            self.semaphore.wait(self.fence_last_signalled_value);
+        }
+    }
+
+    fn get_frame_fence_value(&self) -> u64 {
+        self.frame_fence_value[self.bb_index]
+    }
+
+    fn get_num_buffers(&self) -> u32 {
+        self.num_buffers
+    }
+
+    fn get_backbuffer_index(&self) -> u32 {
+        self.bb_index as u32
+    }
+
+    fn get_backbuffer_texture(&self) -> &Texture {
+        &self.backbuffer_textures[self.bb_index]
+    }
+
+    fn get_backbuffer_pass(&self) -> &RenderPass {
+        &self.backbuffer_passes[self.bb_index]
+    }
+
+    fn get_backbuffer_pass_mut(&mut self) -> &mut RenderPass {
+        &mut self.backbuffer_passes[self.bb_index]
+    }
+
+    fn get_backbuffer_pass_no_clear(&self) -> &RenderPass {
+        &self.backbuffer_passes_no_clear[self.bb_index]
+    }
+
+    fn get_backbuffer_pass_no_clear_mut(&mut self) -> &mut RenderPass {
+        &mut self.backbuffer_passes_no_clear[self.bb_index]
+    }
+
+    fn swap(&mut self, device: &mut Device) {
+        // there is no surface to present to, signal the frame fence after all previously submitted work
+        let fv = self.fence_last_signalled_value + 1;
+        let signal_infos = [vk::SemaphoreSubmitInfo::default()
+            .semaphore(self.semaphore.semaphore)
+            .value(fv)
+            .stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)];
+        let submit = vk::SubmitInfo2::default()
+            .signal_semaphore_infos(&signal_infos);
+        unsafe {
+            let queue = device.inner.queue.lock().unwrap();
+            device.inner.device.queue_submit2(*queue, &[submit], vk::Fence::null())
+                .expect("hotline_rs::gfx::vk: vkQueueSubmit2 failed!");
+        }
+
+        // update fence tracking
+        self.fence_last_signalled_value = fv;
+        self.frame_fence_value[self.bb_index] = fv;
+
+        // swap buffers
+        self.frame_index += 1;
+        self.bb_index = (self.bb_index + 1) % self.num_buffers as usize;
+    }
+}
+
+impl CmdBuf {
+    fn cmd(&self) -> vk::CommandBuffer {
+        self.command_buffers[self.bb_index]
+    }
+
+    fn device(&self) -> &ash::Device {
+        &self.device.device
+    }
+
+    /// Records a barrier transitioning `image` between layouts, waiting on all prior work
+    fn image_barrier(&self, image: vk::Image, range: vk::ImageSubresourceRange, old_layout: vk::ImageLayout, new_layout: vk::ImageLayout) {
+        let barriers = [vk::ImageMemoryBarrier2::default()
+            .src_stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+            .src_access_mask(vk::AccessFlags2::MEMORY_WRITE)
+            .dst_stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+            .dst_access_mask(vk::AccessFlags2::MEMORY_READ | vk::AccessFlags2::MEMORY_WRITE)
+            .old_layout(old_layout)
+            .new_layout(new_layout)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(image)
+            .subresource_range(range)];
+        unsafe {
+            self.device().cmd_pipeline_barrier2(self.cmd(), &vk::DependencyInfo::default()
+                .image_memory_barriers(&barriers));
+        }
+    }
+
+    /// Records a global memory barrier between `src` and `dst`
+    fn memory_barrier(&self, src_stage: vk::PipelineStageFlags2, src_access: vk::AccessFlags2, dst_stage: vk::PipelineStageFlags2, dst_access: vk::AccessFlags2) {
+        let barriers = [vk::MemoryBarrier2::default()
+            .src_stage_mask(src_stage)
+            .src_access_mask(src_access)
+            .dst_stage_mask(dst_stage)
+            .dst_access_mask(dst_access)];
+        unsafe {
+            self.device().cmd_pipeline_barrier2(self.cmd(), &vk::DependencyInfo::default()
+                .memory_barriers(&barriers));
+        }
+    }
+
+    /// Copies query results into their resolve buffers and resets the queries so they can be reused next frame,
+    /// copies and resets are not allowed inside a render pass so they are deferred until the pass ends
+    fn flush_query_copies(&mut self) {
+        let copies = std::mem::take(&mut *self.pending_query_copies.lock().unwrap());
+        if copies.is_empty() {
+            return;
+        }
+        let cmd = self.cmd();
+        unsafe {
+            for copy in &copies {
+                self.device().cmd_copy_query_pool_results(
+                    cmd, copy.pool, copy.index, 1, copy.buffer, 0, copy.stride,
+                    vk::QueryResultFlags::TYPE_64 | vk::QueryResultFlags::WAIT);
+            }
+            self.memory_barrier(
+                vk::PipelineStageFlags2::ALL_TRANSFER, vk::AccessFlags2::TRANSFER_WRITE,
+                vk::PipelineStageFlags2::ALL_TRANSFER | vk::PipelineStageFlags2::HOST,
+                vk::AccessFlags2::TRANSFER_WRITE | vk::AccessFlags2::HOST_READ);
+            for copy in &copies {
+                self.device().cmd_reset_query_pool(cmd, copy.pool, copy.index, 1);
+            }
+        }
+    }
+
+    fn queue_query_copy(&mut self, copy: QueryCopy) {
+        self.pending_query_copies.lock().unwrap().push(copy);
+        if !self.in_render_pass {
+            self.flush_query_copies();
+        }
+    }
+
+    /// Pushes `num_values` 32-bit constants from `data` to the push constants in pipeline layout `slot`
+    fn push_constants<T: Sized>(&mut self, layout: Option<Arc<PipelineLayoutState>>, slot: u32, num_values: u32, dest_offset: u32, data: &[T]) -> Option<()> {
+        let layout = layout?;
+        let offset = layout.push_constant_offsets.get(&slot)? + dest_offset * 4;
+        let bytes = super::slice_as_u8_slice(data);
+        let size = (num_values as usize * 4).min(bytes.len());
+        unsafe {
+            self.device().cmd_push_constants(self.cmd(), layout.layout, layout.push_constant_stages, offset, &bytes[..size]);
+        }
+        Some(())
+    }
+
+    fn get_layout(&self, pipeline_type: PipelineType) -> Option<Arc<PipelineLayoutState>> {
+        match pipeline_type {
+            PipelineType::Render => self.render_layout.clone(),
+            _ => self.compute_layout.clone()
+        }
+    }
+}
+
+fn full_subresource_range(aspect: vk::ImageAspectFlags) -> vk::ImageSubresourceRange {
+    vk::ImageSubresourceRange {
+        aspect_mask: aspect,
+        base_mip_level: 0,
+        level_count: vk::REMAINING_MIP_LEVELS,
+        base_array_layer: 0,
+        layer_count: vk::REMAINING_ARRAY_LAYERS
+    }
+}
+
+fn subresource_layers(aspect: vk::ImageAspectFlags, mip: u32, layer: u32) -> vk::ImageSubresourceLayers {
+    vk::ImageSubresourceLayers {
+        aspect_mask: aspect,
+        mip_level: mip,
+        base_array_layer: layer,
+        layer_count: 1
+    }
+}
+
+/// Converts a packed colour as used by PIX markers into floats for debug labels
+fn unpack_marker_colour(colour: u32) -> [f32; 4] {
+    [
+        ((colour >> 16) & 0xff) as f32 / 255.0,
+        ((colour >> 8) & 0xff) as f32 / 255.0,
+        (colour & 0xff) as f32 / 255.0,
+        1.0
+    ]
+}
+
+impl super::CmdBuf<Device> for CmdBuf {
+    fn reset(&mut self, swap_chain: &SwapChain) {
+        self.bb_index = swap_chain.bb_index % self.command_buffers.len();
+        self.in_render_pass = false;
+        self.render_layout = None;
+        self.compute_layout = None;
+        self.pending_query_copies.lock().unwrap().clear();
+        unsafe {
+            let cmd = self.cmd();
+            self.device().reset_command_buffer(cmd, vk::CommandBufferResetFlags::empty())
+                .expect("hotline_rs::gfx::vk: vkResetCommandBuffer failed!");
+            self.device().begin_command_buffer(cmd, &vk::CommandBufferBeginInfo::default()
+                .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT))
+                .expect("hotline_rs::gfx::vk: vkBeginCommandBuffer failed!");
+        }
+    }
+
+    fn close(&mut self) -> Result<(), Error> {
+        if self.in_render_pass {
+            return Err(Error {
+                msg: "hotline_rs::gfx::vk: command buffer closed inside a render pass".to_string()
+            });
+        }
+        self.flush_query_copies();
+        unsafe {
+            self.device().end_command_buffer(self.cmd())
+                .map_err(|e| vk_error("vkEndCommandBuffer", e))
+        }
+    }
+
+    fn get_backbuffer_index(&self) -> u32 {
+        self.bb_index as u32
+    }
+
+    fn begin_render_pass(&mut self, render_pass: &RenderPass) {
+        let load_op = |clear: bool| {
+            if clear {
+                vk::AttachmentLoadOp::CLEAR
+            }
+            else if render_pass.discard {
+                vk::AttachmentLoadOp::DONT_CARE
+            }
+            else {
+                vk::AttachmentLoadOp::LOAD
+            }
+        };
+        let clear_colour = render_pass.rt_clear.map(|c| [c.r, c.g, c.b, c.a]).unwrap_or_default();
+        let colour_attachments : Vec<vk::RenderingAttachmentInfo> = render_pass.colour_views.iter().map(|view| {
+            vk::RenderingAttachmentInfo::default()
+                .image_view(*view)
+                .image_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+                .load_op(load_op(render_pass.rt_clear.is_some()))
+                .store_op(vk::AttachmentStoreOp::STORE)
+                .clear_value(vk::ClearValue {
+                    color: vk::ClearColorValue {
+                        float32: clear_colour
+                    }
+                })
+        }).collect();
+
+        let ds_clear = render_pass.ds_clear.unwrap_or(ClearDepthStencil {
+            depth: None,
+            stencil: None
+        });
+        let ds_clear_value = vk::ClearValue {
+            depth_stencil: vk::ClearDepthStencilValue {
+                depth: ds_clear.depth.unwrap_or(1.0),
+                stencil: ds_clear.stencil.unwrap_or(0) as u32
+            }
+        };
+        let depth_attachment = render_pass.depth_stencil_view.map(|view| {
+            vk::RenderingAttachmentInfo::default()
+                .image_view(view)
+                .image_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+                .load_op(load_op(ds_clear.depth.is_some()))
+                .store_op(vk::AttachmentStoreOp::STORE)
+                .clear_value(ds_clear_value)
+        });
+        let stencil_attachment = render_pass.depth_stencil_view.filter(|_| render_pass.has_stencil).map(|view| {
+            vk::RenderingAttachmentInfo::default()
+                .image_view(view)
+                .image_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+                .load_op(load_op(ds_clear.stencil.is_some()))
+                .store_op(vk::AttachmentStoreOp::STORE)
+                .clear_value(ds_clear_value)
+        });
+
+        let render_area = vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent: render_pass.extent
+        };
+        let mut rendering_info = vk::RenderingInfo::default()
+            .render_area(render_area)
+            .layer_count(1)
+            .color_attachments(&colour_attachments);
+        if let Some(depth_attachment) = &depth_attachment {
+            rendering_info = rendering_info.depth_attachment(depth_attachment);
+        }
+        if let Some(stencil_attachment) = &stencil_attachment {
+            rendering_info = rendering_info.stencil_attachment(stencil_attachment);
+        }
+
+        self.in_render_pass = true;
+        unsafe {
+            self.device().cmd_begin_rendering(self.cmd(), &rendering_info);
+        }
+
+        // viewport and scissor are dynamic state, default them to the full target
+        self.set_viewport(&Viewport {
+            x: 0.0,
+            y: 0.0,
+            width: render_pass.extent.width as f32,
+            height: render_pass.extent.height as f32,
+            min_depth: 0.0,
+            max_depth: 1.0
+        });
+        self.set_scissor_rect(&ScissorRect {
+            left: 0,
+            top: 0,
+            right: render_pass.extent.width as i32,
+            bottom: render_pass.extent.height as i32
+        });
+    }
+
+    fn end_render_pass(&mut self) {
+        unsafe {
+            self.device().cmd_end_rendering(self.cmd());
+        }
+        self.in_render_pass = false;
+        self.flush_query_copies();
+    }
+
+    fn begin_event(&mut self, colour: u32, name: &str) {
+        if let Some(debug_utils) = &self.device.debug_utils_device {
+            let name = CString::new(name).unwrap_or_default();
+            unsafe {
+                debug_utils.cmd_begin_debug_utils_label(self.cmd(), &vk::DebugUtilsLabelEXT::default()
+                    .label_name(&name)
+                    .color(unpack_marker_colour(colour)));
+            }
+        }
+    }
+
+    fn end_event(&mut self) {
+        if let Some(debug_utils) = &self.device.debug_utils_device {
+            unsafe {
+                debug_utils.cmd_end_debug_utils_label(self.cmd());
+            }
+        }
+    }
+
+    fn set_marker(&mut self, colour: u32, name: &str) {
+        if let Some(debug_utils) = &self.device.debug_utils_device {
+            let name = CString::new(name).unwrap_or_default();
+            unsafe {
+                debug_utils.cmd_insert_debug_utils_label(self.cmd(), &vk::DebugUtilsLabelEXT::default()
+                    .label_name(&name)
+                    .color(unpack_marker_colour(colour)));
+            }
+        }
+    }
+
+    fn timestamp_query(&mut self, heap: &mut QueryHeap, resolve_buffer: &mut Buffer) {
+        let index = heap.allocate();
+        unsafe {
+            self.device().cmd_write_timestamp2(self.cmd(), vk::PipelineStageFlags2::ALL_COMMANDS, heap.pool, index);
+        }
+        self.queue_query_copy(QueryCopy {
+            pool: heap.pool,
+            index,
+            buffer: resolve_buffer.buffer,
+            stride: std::mem::size_of::<u64>() as u64
+        });
+    }
+
+    fn begin_query(&mut self, heap: &mut QueryHeap, query_type: QueryType) -> usize {
+        let index = heap.allocate();
+        let flags = if query_type == QueryType::Occlusion {
+            vk::QueryControlFlags::PRECISE
+        }
+        else {
+            vk::QueryControlFlags::empty()
+        };
+        unsafe {
+            self.device().cmd_begin_query(self.cmd(), heap.pool, index, flags);
+        }
+        index as usize
+    }
+
+    fn end_query(&mut self, heap: &mut QueryHeap, query_type: QueryType, index: usize, resolve_buffer: &mut Buffer) {
+        unsafe {
+            self.device().cmd_end_query(self.cmd(), heap.pool, index as u32);
+        }
+        let stride = match query_type {
+            QueryType::PipelineStatistics => std::mem::size_of::<PipelineStatistics>(),
+            _ => std::mem::size_of::<u64>()
+        };
+        self.queue_query_copy(QueryCopy {
+            pool: heap.pool,
+            index: index as u32,
+            buffer: resolve_buffer.buffer,
+            stride: stride as u64
+        });
+    }
+
+    fn transition_barrier(&mut self, barrier: &TransitionBarrier<Device>) {
+        if let Some(tex) = barrier.texture {
+            self.image_barrier(
+                tex.image,
+                full_subresource_range(tex.aspect),
+                to_vk_image_layout(barrier.state_before),
+                to_vk_image_layout(barrier.state_after)
+            );
+        }
+        else if barrier.buffer.is_some() {
+            // buffers have no layout, a memory barrier makes prior writes visible to the next usage
+            self.memory_barrier(
+                vk::PipelineStageFlags2::ALL_COMMANDS, vk::AccessFlags2::MEMORY_WRITE,
+                vk::PipelineStageFlags2::ALL_COMMANDS, vk::AccessFlags2::MEMORY_READ | vk::AccessFlags2::MEMORY_WRITE);
+        }
+    }
+
+    fn transition_barrier_subresource(&mut self, barrier: &TransitionBarrier<Device>, subresource: Subresource) {
+        match subresource {
+            Subresource::Resource => self.transition_barrier(barrier),
+            Subresource::ResolveResource => {
+                if let Some(tex) = barrier.texture {
+                    if let Some(resolved) = tex.resolved_image {
+                        self.image_barrier(
+                            resolved,
+                            full_subresource_range(tex.aspect),
+                            to_vk_image_layout(barrier.state_before),
+                            to_vk_image_layout(barrier.state_after)
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn uav_barrier(&mut self, resource: UavResource<Device>) {
+        self.memory_barrier(
+            vk::PipelineStageFlags2::ALL_COMMANDS, vk::AccessFlags2::SHADER_WRITE,
+            vk::PipelineStageFlags2::ALL_COMMANDS, vk::AccessFlags2::SHADER_READ | vk::AccessFlags2::SHADER_WRITE);
+    }
+
+    fn set_viewport(&mut self, viewport: &Viewport) {
+        // flip y with a negative height so clip space matches d3d12 and metal (y up)
+        let vp = vk::Viewport {
+            x: viewport.x,
+            y: viewport.y + viewport.height,
+            width: viewport.width,
+            height: -viewport.height,
+            min_depth: viewport.min_depth,
+            max_depth: viewport.max_depth
+        };
+        unsafe {
+            self.device().cmd_set_viewport(self.cmd(), 0, &[vp]);
+        }
+    }
+
+    fn set_scissor_rect(&mut self, scissor_rect: &ScissorRect) {
+        let rect = vk::Rect2D {
+            offset: vk::Offset2D {
+                x: scissor_rect.left,
+                y: scissor_rect.top
+            },
+            extent: vk::Extent2D {
+                width: (scissor_rect.right - scissor_rect.left).max(0) as u32,
+                height: (scissor_rect.bottom - scissor_rect.top).max(0) as u32
+            }
+        };
+        unsafe {
+            self.device().cmd_set_scissor(self.cmd(), 0, &[rect]);
+        }
+    }
+
+    fn set_index_buffer(&mut self, buffer: &Buffer) {
+        unsafe {
+            self.device().cmd_bind_index_buffer(self.cmd(), buffer.buffer, 0, to_vk_index_type(buffer.info.format));
+        }
+    }
+
+    fn set_vertex_buffer(&mut self, buffer: &Buffer, slot: u32) {
+        unsafe {
+            self.device().cmd_bind_vertex_buffers2(
+                self.cmd(), slot, &[buffer.buffer], &[0], None, Some(&[buffer.info.stride as u64]));
+        }
+    }
+
+    fn set_render_pipeline(&mut self, pipeline: &RenderPipeline) {
+        unsafe {
+            self.device().cmd_bind_pipeline(self.cmd(), vk::PipelineBindPoint::GRAPHICS, pipeline.pipeline);
+        }
+        self.render_layout = Some(pipeline.layout.clone());
+    }
+
+    fn set_compute_pipeline(&mut self, pipeline: &ComputePipeline) {
+        unsafe {
+            self.device().cmd_bind_pipeline(self.cmd(), vk::PipelineBindPoint::COMPUTE, pipeline.pipeline);
+        }
+        self.compute_layout = Some(pipeline.layout.clone());
+    }
+
+    fn set_raytracing_pipeline(&mut self, pipeline: &RaytracingPipeline) {
+        // raytracing pipelines cannot be created on this backend
+    }
+
+    fn set_heap<T: Pipeline>(&mut self, pipeline: &T, heap: &Heap) {
+        let layout = match self.get_layout(T::get_pipeline_type()) {
+            Some(layout) => layout,
+            None => return
+        };
+        let mut state = heap.state.lock().unwrap();
+        for (space, set_layout) in layout.set_layouts.iter().enumerate() {
+            match state.get_descriptor_set(set_layout, 0) {
+                Ok(set) => unsafe {
+                    self.device().cmd_bind_descriptor_sets(
+                        self.cmd(), layout.bind_point, layout.layout, space as u32, &[set], &[]);
+                },
+                Err(err) => report(&self.device.info_queue, "error", err.msg)
+            }
+        }
+    }
+
+    fn set_binding<T: Pipeline>(&mut self, pipeline: &T, register: u32, space: u32, descriptor_type: DescriptorType, heap: &Heap, offset: usize) -> Option<()> {
+        pipeline.get_pipeline_slot(register, space, descriptor_type)?;
+        let layout = self.get_layout(T::get_pipeline_type())?;
+        let set_layout = layout.set_layouts.get(space as usize)?;
+        // all bindings in a space share a descriptor set, so the offset applies to the whole space
+        let set = heap.state.lock().unwrap().get_descriptor_set(set_layout, offset).ok()?;
+        unsafe {
+            self.device().cmd_bind_descriptor_sets(
+                self.cmd(), layout.bind_point, layout.layout, space, &[set], &[]);
+        }
+        Some(())
+    }
+
+    fn push_render_constants<P: Pipeline, T: Sized>(&mut self, pipeline: &P, register: u32, space: u32, num_values: u32, dest_offset: u32, data: &[T]) -> Option<()> {
+        let slot = pipeline.get_pipeline_slot(register, space, DescriptorType::PushConstants)?;
+        self.push_constants(self.render_layout.clone(), slot.index, num_values, dest_offset, data)
+    }
+
+    fn push_compute_constants<P: Pipeline, T: Sized>(&mut self, pipeline: &P, register: u32, space: u32, num_values: u32, dest_offset: u32, data: &[T]) -> Option<()> {
+        let slot = pipeline.get_pipeline_slot(register, space, DescriptorType::PushConstants)?;
+        self.push_constants(self.compute_layout.clone(), slot.index, num_values, dest_offset, data)
+    }
+
+    fn draw_instanced(
+        &mut self,
+        vertex_count: u32,
+        instance_count: u32,
+        start_vertex: u32,
+        start_instance: u32,
+    ) {
+        unsafe {
+            self.device().cmd_draw(self.cmd(), vertex_count, instance_count, start_vertex, start_instance);
+        }
+    }
+
+    fn draw_indexed_instanced(
+        &mut self,
+        index_count: u32,
+        instance_count: u32,
+        start_index: u32,
+        base_vertex: i32,
+        start_instance: u32,
+    ) {
+        unsafe {
+            self.device().cmd_draw_indexed(self.cmd(), index_count, instance_count, start_index, base_vertex, start_instance);
+        }
+    }
+
+    fn dispatch(&mut self, group_count: Size3, numthreads: Size3) {
+        unsafe {
+            self.device().cmd_dispatch(self.cmd(), group_count.x, group_count.y, group_count.z);
+        }
+    }
+
+    fn execute_indirect(
+        &mut self,
+        command: &CommandSignature,
+        max_command_count: u32,
+        argument_buffer: &Buffer,
+        argument_buffer_offset: usize,
+        counter_buffer: Option<&Buffer>,
+        counter_buffer_offset: usize
+    ) {
+        let cmd = self.cmd();
+        let offset = argument_buffer_offset as u64;
+        let device = &self.device.device;
+        unsafe {
+            match (command.argument_type, counter_buffer) {
+                (IndirectArgumentType::Draw, Some(counter)) => device.cmd_draw_indirect_count(
+                    cmd, argument_buffer.buffer, offset, counter.buffer, counter_buffer_offset as u64, max_command_count, command.stride),
+                (IndirectArgumentType::Draw, None) => device.cmd_draw_indirect(
+                    cmd, argument_buffer.buffer, offset, max_command_count, command.stride),
+                (IndirectArgumentType::DrawIndexed, Some(counter)) => device.cmd_draw_indexed_indirect_count(
+                    cmd, argument_buffer.buffer, offset, counter.buffer, counter_buffer_offset as u64, max_command_count, command.stride),
+                (IndirectArgumentType::DrawIndexed, None) => device.cmd_draw_indexed_indirect(
+                    cmd, argument_buffer.buffer, offset, max_command_count, command.stride),
+                (IndirectArgumentType::Dispatch, _) => {
+                    // there is no indirect count variant for dispatch so all commands are issued
+                    for i in 0..max_command_count as u64 {
+                        device.cmd_dispatch_indirect(cmd, argument_buffer.buffer, offset + i * command.stride as u64);
+                    }
+                }
+                _ => ()
+            }
+        }
+    }
+
+    fn dispatch_rays(&mut self, sbt: &RaytracingShaderBindingTable, numthreads: Size3) {
+        // raytracing is not supported on this backend
+    }
+
+    fn update_raytracing_tlas(&mut self, tlas: &RaytracingTLAS, instance_buffer: &Buffer, instance_count: usize, mode: AccelerationStructureRebuildMode) {
+        // raytracing is not supported on this backend
+    }
+
+    fn resolve_texture_subresource(&mut self, texture: &Texture, subresource: u32) -> Result<(), Error> {
+        let resolved = texture.resolved_image.ok_or(Error {
+            msg: "hotline_rs::gfx::vk: texture is not resolvable".to_string()
+        })?;
+        let mips = texture.info.mip_levels.max(1);
+        let layer = subresource / mips;
+        let mip = subresource % mips;
+        let region = vk::ImageResolve {
+            src_subresource: subresource_layers(texture.aspect, 0, layer),
+            src_offset: vk::Offset3D::default(),
+            dst_subresource: subresource_layers(texture.aspect, mip, layer),
+            dst_offset: vk::Offset3D::default(),
+            extent: vk::Extent3D {
+                width: texture.info.width as u32,
+                height: texture.info.height as u32,
+                depth: 1
+            }
+        };
+        unsafe {
+            self.device().cmd_resolve_image(
+                self.cmd(),
+                texture.image,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                resolved,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[region]
+            );
+        }
+        Ok(())
+    }
+
+    fn generate_mip_maps(&mut self, texture: &Texture, device: &Device, heap: &Heap) -> Result<(), Error> {
+        let mips = texture.info.mip_levels;
+        if mips <= 1 {
+            return Err(Error {
+                msg: "hotline_rs::gfx::vk: texture has no mip levels to generate".to_string()
+            });
+        }
+        let blit_features = vk::FormatFeatureFlags::BLIT_SRC | vk::FormatFeatureFlags::BLIT_DST | vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR;
+        if !device.inner.format_supports(texture.format, blit_features) {
+            return Err(Error {
+                msg: format!("hotline_rs::gfx::vk: format {:?} does not support linear blits required to generate mip maps", texture.info.format)
+            });
+        }
+
+        // blit each mip from the previous one, the texture is expected in `ShaderResource` and left in it
+        let image = texture.resolved_image.unwrap_or(texture.image);
+        let aspect = texture.aspect;
+        let layers = texture.info.array_layers.max(1);
+        let mip_range = |mip: u32| vk::ImageSubresourceRange {
+            aspect_mask: aspect,
+            base_mip_level: mip,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: layers
+        };
+        let mut width = texture.info.width as i32;
+        let mut height = texture.info.height as i32;
+        let mut depth = texture.info.depth.max(1) as i32;
+        for mip in 1..mips {
+            let src_layout = if mip == 1 {
+                vk::ImageLayout::READ_ONLY_OPTIMAL
+            }
+            else {
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL
+            };
+            self.image_barrier(image, mip_range(mip - 1), src_layout, vk::ImageLayout::TRANSFER_SRC_OPTIMAL);
+            self.image_barrier(image, mip_range(mip), vk::ImageLayout::READ_ONLY_OPTIMAL, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+            let (next_width, next_height, next_depth) = ((width / 2).max(1), (height / 2).max(1), (depth / 2).max(1));
+            let blit = vk::ImageBlit {
+                src_subresource: vk::ImageSubresourceLayers {
+                    aspect_mask: aspect,
+                    mip_level: mip - 1,
+                    base_array_layer: 0,
+                    layer_count: layers
+                },
+                src_offsets: [vk::Offset3D::default(), vk::Offset3D { x: width, y: height, z: depth }],
+                dst_subresource: vk::ImageSubresourceLayers {
+                    aspect_mask: aspect,
+                    mip_level: mip,
+                    base_array_layer: 0,
+                    layer_count: layers
+                },
+                dst_offsets: [vk::Offset3D::default(), vk::Offset3D { x: next_width, y: next_height, z: next_depth }],
+            };
+            unsafe {
+                self.device().cmd_blit_image(
+                    self.cmd(),
+                    image,
+                    vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                    image,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    &[blit],
+                    vk::Filter::LINEAR
+                );
+            }
+            width = next_width;
+            height = next_height;
+            depth = next_depth;
+        }
+        self.image_barrier(image, vk::ImageSubresourceRange {
+            level_count: mips - 1,
+            ..mip_range(0)
+        }, vk::ImageLayout::TRANSFER_SRC_OPTIMAL, vk::ImageLayout::READ_ONLY_OPTIMAL);
+        self.image_barrier(image, mip_range(mips - 1), vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::READ_ONLY_OPTIMAL);
+        Ok(())
+    }
+
+    fn read_back_backbuffer(&mut self, swap_chain: &SwapChain) -> Result<ReadBackRequest, Error> {
+        let bb = &swap_chain.backbuffer_textures[swap_chain.bb_index];
+        let row_pitch = super::row_pitch_for_format(bb.info.format, bb.info.width) as usize;
+        let size = super::size_for_format(bb.info.format, bb.info.width, bb.info.height, 1) as usize;
+        let (buffer, memory, mapped) = self.device.create_buffer(
+            size,
+            vk::BufferUsageFlags::TRANSFER_DST,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            vk::MemoryPropertyFlags::HOST_CACHED
+        )?;
+        let mut resource = VkResource::new(&self.device);
+        resource.buffers.push(buffer);
+        resource.memory.push(memory);
+
+        // backbuffers in the `Present` state are already in a copyable layout
+        let region = vk::BufferImageCopy {
+            buffer_offset: 0,
+            buffer_row_length: 0,
+            buffer_image_height: 0,
+            image_subresource: subresource_layers(bb.aspect, 0, 0),
+            image_offset: vk::Offset3D::default(),
+            image_extent: vk::Extent3D {
+                width: bb.info.width as u32,
+                height: bb.info.height as u32,
+                depth: 1
+            }
+        };
+        unsafe {
+            self.device().cmd_copy_image_to_buffer(
+                self.cmd(), bb.image, vk::ImageLayout::TRANSFER_SRC_OPTIMAL, buffer, &[region]);
+        }
+        self.memory_barrier(
+            vk::PipelineStageFlags2::ALL_TRANSFER, vk::AccessFlags2::TRANSFER_WRITE,
+            vk::PipelineStageFlags2::HOST, vk::AccessFlags2::HOST_READ);
+
+        Ok(ReadBackRequest {
+            semaphore: swap_chain.semaphore.clone(),
+            // the request is complete once the frame this command buffer is submitted in has been swapped
+            fence_value: swap_chain.fence_last_signalled_value + 1,
+            mapped,
+            format: bb.info.format,
+            size,
+            row_pitch,
+            slice_pitch: size,
+            resource: Some(resource),
+            drop_list: self.drop_list.clone()
+        })
+    }
+
+    fn copy_buffer_region(
+        &mut self,
+        dst_buffer: &Buffer,
+        dst_offset: usize,
+        src_buffer: &Buffer,
+        src_offset: usize,
+        num_bytes: usize
+    ) {
+        let region = vk::BufferCopy {
+            src_offset: src_offset as u64,
+            dst_offset: dst_offset as u64,
+            size: num_bytes as u64
+        };
+        unsafe {
+            self.device().cmd_copy_buffer(self.cmd(), src_buffer.buffer, dst_buffer.buffer, &[region]);
+        }
+    }
+
+    fn copy_texture_region(
+        &mut self,
+        dst_texture: &Texture,
+        subresource_index: u32,
+        dst_x: u32,
+        dst_y: u32,
+        dst_z: u32,
+        src_texture: &Texture,
+        src_region: Option<Region>
+    ) {
+        let mips = dst_texture.info.mip_levels.max(1);
+        let region = src_region.unwrap_or(Region {
+            left: 0,
+            top: 0,
+            front: 0,
+            right: src_texture.info.width as u32,
+            bottom: src_texture.info.height as u32,
+            back: src_texture.info.depth.max(1)
+        });
+        let copy = vk::ImageCopy {
+            src_subresource: subresource_layers(src_texture.aspect, 0, 0),
+            src_offset: vk::Offset3D {
+                x: region.left as i32,
+                y: region.top as i32,
+                z: region.front as i32
+            },
+            dst_subresource: subresource_layers(dst_texture.aspect, subresource_index % mips, subresource_index / mips),
+            dst_offset: vk::Offset3D {
+                x: dst_x as i32,
+                y: dst_y as i32,
+                z: dst_z as i32
+            },
+            extent: vk::Extent3D {
+                width: region.right - region.left,
+                height: region.bottom - region.top,
+                depth: (region.back - region.front).max(1)
+            }
+        };
+        unsafe {
+            self.device().cmd_copy_image(
+                self.cmd(),
+                src_texture.image,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                dst_texture.image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[copy]
+            );
+        }
+    }
+}
+
+impl Device {
+    /// Creates an image with its own device local memory allocation
+    fn create_image(&self, info: &TextureInfo, samples: u32, mip_levels: u32) -> Result<(vk::Image, vk::DeviceMemory), Error> {
+        let is_3d = matches!(info.tex_type, TextureType::Texture3D);
+        let mut flags = vk::ImageCreateFlags::empty();
+        if matches!(info.tex_type, TextureType::TextureCube | TextureType::TextureCubeArray) {
+            flags |= vk::ImageCreateFlags::CUBE_COMPATIBLE;
+        }
+        if is_3d && info.usage.intersects(TextureUsage::RENDER_TARGET | TextureUsage::DEPTH_STENCIL) {
+            // render target views of 3d textures address depth slices as array layers
+            flags |= vk::ImageCreateFlags::TYPE_2D_ARRAY_COMPATIBLE;
+        }
+        let create_info = vk::ImageCreateInfo::default()
+            .flags(flags)
+            .image_type(to_vk_image_type(info.tex_type))
+            .format(to_vk_format(info.format))
+            .extent(vk::Extent3D {
+                width: info.width as u32,
+                height: info.height as u32,
+                depth: if is_3d { info.depth.max(1) } else { 1 }
+            })
+            .mip_levels(mip_levels.max(1))
+            .array_layers(if is_3d { 1 } else { info.array_layers.max(1) })
+            .samples(to_vk_sample_count(samples))
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(to_vk_image_usage(info))
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED);
+        let device = &self.inner.device;
+        unsafe {
+            let image = device.create_image(&create_info, None)
+                .map_err(|e| vk_error("vkCreateImage", e))?;
+            let requirements = device.get_image_memory_requirements(image);
+            let (memory, _) = match self.inner.allocate_memory(requirements, vk::MemoryPropertyFlags::DEVICE_LOCAL, vk::MemoryPropertyFlags::empty()) {
+                Ok(allocation) => allocation,
+                Err(err) => {
+                    device.destroy_image(image, None);
+                    return Err(err);
+                }
+            };
+            device.bind_image_memory(image, memory, 0)
+                .map_err(|e| vk_error("vkBindImageMemory", e))?;
+            Ok((image, memory))
+        }
+    }
+
+    /// Allocates views for a buffer into `heap` based on its usage flags
+    fn create_buffer_views(info: &BufferInfo, heap: &mut Heap, buffer: &mut Buffer) -> Result<(), Error> {
+        if info.usage.contains(BufferUsage::BUFFER_ONLY) {
+            return Ok(());
+        }
+        let mut allocs = HeapAllocs::new(heap);
+        let range = (info.stride * info.num_elements) as u64;
+        if info.usage.contains(BufferUsage::CONSTANT_BUFFER) {
+            buffer.cbv_index = Some(heap.allocate_descriptor(Descriptor::Buffer {
+                buffer: buffer.buffer,
+                offset: 0,
+                range,
+                uniform: true
+            }, &mut allocs)?);
+        }
+        if info.usage.contains(BufferUsage::SHADER_RESOURCE) {
+            buffer.srv_index = Some(heap.allocate_descriptor(Descriptor::Buffer {
+                buffer: buffer.buffer,
+                offset: 0,
+                range,
+                uniform: false
+            }, &mut allocs)?);
+        }
+        if info.usage.contains(BufferUsage::UNORDERED_ACCESS) {
+            buffer.uav_index = Some(heap.allocate_descriptor(Descriptor::Buffer {
+                buffer: buffer.buffer,
+                offset: 0,
+                range,
+                uniform: false
+            }, &mut allocs)?);
+        }
+        buffer.allocs = Some(allocs);
+        Ok(())
+    }
+
+    /// Creates a buffer without any views, data is written through mapped memory or uploaded through a staging buffer
+    fn create_buffer_storage<T: Sized>(&self, info: &BufferInfo, data: Option<&[T]>) -> Result<Buffer, Error> {
+        let mut size_bytes = info.stride * info.num_elements;
+        let mut counter_offset = None;
+        if info.usage.contains(BufferUsage::APPEND_COUNTER) {
+            let offset = super::align_pow2(size_bytes as u64, COUNTER_ALIGNMENT as u64) as usize;
+            counter_offset = Some(offset);
+            size_bytes = offset + 4;
+        }
+
+        let (required, preferred) = if info.cpu_access.contains(CpuAccessFlags::READ) {
+            (vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT, vk::MemoryPropertyFlags::HOST_CACHED)
+        }
+        else if info.cpu_access.contains(CpuAccessFlags::WRITE) {
+            (vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT, vk::MemoryPropertyFlags::DEVICE_LOCAL)
+        }
+        else {
+            (vk::MemoryPropertyFlags::DEVICE_LOCAL, vk::MemoryPropertyFlags::empty())
+        };
+        let (buffer, memory, mapped) = self.inner.create_buffer(size_bytes, to_vk_buffer_usage(info.usage), required, preferred)?;
+        let mut resource = VkResource::new(&self.inner);
+        resource.buffers.push(buffer);
+        resource.memory.push(memory);
+
+        if let Some(data) = data {
+            let bytes = super::slice_as_u8_slice(data);
+            if bytes.len() > size_bytes {
+                return Err(Error {
+                    msg: format!("hotline_rs::gfx::vk: buffer data size {} exceeds buffer size {}", bytes.len(), size_bytes)
+                });
+            }
+            if !mapped.is_null() {
+                unsafe {
+                    std::ptr::copy_nonoverlapping(bytes.as_ptr(), mapped, bytes.len());
+                }
+            }
+            else {
+                self.upload_buffer_data(buffer, bytes)?;
+            }
+        }
+
+        Ok(Buffer {
+            info: *info,
+            buffer,
+            size_bytes,
+            mapped,
+            srv_index: None,
+            uav_index: None,
+            cbv_index: None,
+            counter_offset,
+            resource: Some(resource),
+            drop_list: self.drop_list.clone(),
+            allocs: None
+        })
+    }
+
+    /// Copies `bytes` into a device local buffer through a staging buffer
+    fn upload_buffer_data(&self, buffer: vk::Buffer, bytes: &[u8]) -> Result<(), Error> {
+        let (staging, staging_memory, mapped) = self.inner.create_buffer(
+            bytes.len(),
+            vk::BufferUsageFlags::TRANSFER_SRC,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            vk::MemoryPropertyFlags::empty()
+        )?;
+        let mut staging_resource = VkResource::new(&self.inner);
+        staging_resource.buffers.push(staging);
+        staging_resource.memory.push(staging_memory);
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), mapped, bytes.len());
+        }
+        let device = &self.inner.device;
+        self.inner.immediate_submit(|cmd| unsafe {
+            device.cmd_copy_buffer(cmd, staging, buffer, &[vk::BufferCopy {
+                src_offset: 0,
+                dst_offset: 0,
+                size: bytes.len() as u64
+            }]);
+        })
+    }
+
+    /// Uploads texture `data` into `image` and transitions it into `initial_state`
+    fn upload_texture_data(&self, info: &TextureInfo, image: vk::Image, aspect: vk::ImageAspectFlags, data: Option<&[u8]>) -> Result<(), Error> {
+        let device = &self.inner.device;
+        let range = full_subresource_range(aspect);
+        let initial_layout = to_vk_image_layout(info.initial_state);
+        let barrier = |cmd: vk::CommandBuffer, old_layout: vk::ImageLayout, new_layout: vk::ImageLayout| unsafe {
+            let barriers = [vk::ImageMemoryBarrier2::default()
+                .src_stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+                .src_access_mask(vk::AccessFlags2::MEMORY_WRITE)
+                .dst_stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+                .dst_access_mask(vk::AccessFlags2::MEMORY_READ | vk::AccessFlags2::MEMORY_WRITE)
+                .old_layout(old_layout)
+                .new_layout(new_layout)
+                .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .image(image)
+                .subresource_range(range)];
+            device.cmd_pipeline_barrier2(cmd, &vk::DependencyInfo::default().image_memory_barriers(&barriers));
+        };
+
+        if let Some(data) = data {
+            let (staging, staging_memory, mapped) = self.inner.create_buffer(
+                data.len(),
+                vk::BufferUsageFlags::TRANSFER_SRC,
+                vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+                vk::MemoryPropertyFlags::empty()
+            )?;
+            let mut staging_resource = VkResource::new(&self.inner);
+            staging_resource.buffers.push(staging);
+            staging_resource.memory.push(staging_memory);
+            unsafe {
+                std::ptr::copy_nonoverlapping(data.as_ptr(), mapped, data.len());
+            }
+            let copy_aspect = to_vk_sampled_aspect(info.format);
+            let regions : Vec<vk::BufferImageCopy> = texture_subresource_offsets(info).iter().map(|(offset, mip, layer, extent)| {
+                vk::BufferImageCopy {
+                    buffer_offset: *offset,
+                    buffer_row_length: 0,
+                    buffer_image_height: 0,
+                    image_subresource: subresource_layers(copy_aspect, *mip, *layer),
+                    image_offset: vk::Offset3D::default(),
+                    image_extent: *extent
+                }
+            }).collect();
+            self.inner.immediate_submit(|cmd| unsafe {
+                barrier(cmd, vk::ImageLayout::UNDEFINED, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+                device.cmd_copy_buffer_to_image(cmd, staging, image, vk::ImageLayout::TRANSFER_DST_OPTIMAL, &regions);
+                barrier(cmd, vk::ImageLayout::TRANSFER_DST_OPTIMAL, initial_layout);
+            })
+        }
+        else {
+            self.inner.immediate_submit(|cmd| {
+                barrier(cmd, vk::ImageLayout::UNDEFINED, initial_layout);
+            })
+        }
+    }
+
+    /// Returns the `vk::Instance`, `vk::PhysicalDevice` and `vk::Device` handles for interop
+    pub fn get_handles(&self) -> (vk::Instance, vk::PhysicalDevice, vk::Device) {
+        use ash::vk::Handle;
+        (self.inner.instance.handle(), self.inner.physical_device, self.inner.device.handle())
+    }
+}
+
+/// Returns the names of instance layers and extensions to enable, validation is enabled in debug builds when available
+fn instance_layers_and_extensions(entry: &ash::Entry) -> (Vec<CString>, Vec<CString>) {
+    let mut layers = Vec::new();
+    let mut extensions = Vec::new();
+    unsafe {
+        let available_layers = entry.enumerate_instance_layer_properties().unwrap_or_default();
+        let validation = c"VK_LAYER_KHRONOS_validation";
+        if cfg!(debug_assertions) && available_layers.iter().any(|l| l.layer_name_as_c_str() == Ok(validation)) {
+            layers.push(validation.to_owned());
+        }
+        let available_extensions = entry.enumerate_instance_extension_properties(None).unwrap_or_default();
+        let debug_utils = ash::ext::debug_utils::NAME;
+        if available_extensions.iter().any(|e| e.extension_name_as_c_str() == Ok(debug_utils)) {
+            extensions.push(debug_utils.to_owned());
+        }
+    }
+    (layers, extensions)
+}
+
+/// Selects a physical device by `adapter_name`, otherwise prefers the first hardware adapter and falls back to
+/// software adapters such as lavapipe
+fn select_physical_device(instance: &ash::Instance, adapter_name: &Option<String>) -> Result<(vk::PhysicalDevice, Vec<String>), Error> {
+    unsafe {
+        let physical_devices = instance.enumerate_physical_devices()
+            .map_err(|e| vk_error("vkEnumeratePhysicalDevices", e))?;
+        let names : Vec<String> = physical_devices.iter().map(|pd| {
+            instance.get_physical_device_properties(*pd)
+                .device_name_as_c_str()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        }).collect();
+        let supports_graphics = |pd: &vk::PhysicalDevice| {
+            let props = instance.get_physical_device_properties(*pd);
+            props.api_version >= vk::API_VERSION_1_3
+        };
+        let mut selected = None;
+        if let Some(adapter_name) = adapter_name {
+            selected = physical_devices.iter().zip(&names)
+                .find(|(pd, name)| *name == adapter_name && supports_graphics(pd))
+                .map(|(pd, _)| *pd);
+        }
+        if selected.is_none() {
+            selected = physical_devices.iter()
+                .find(|pd| supports_graphics(pd) &&
+                    instance.get_physical_device_properties(**pd).device_type != vk::PhysicalDeviceType::CPU)
+                .copied();
+        }
+        if selected.is_none() {
+            selected = physical_devices.iter().find(|pd| supports_graphics(pd)).copied();
+        }
+        let selected = selected.ok_or(Error {
+            msg: "hotline_rs::gfx::vk: no Vulkan 1.3 capable adapter found".to_string()
+        })?;
+        Ok((selected, names))
+    }
+}
+
+impl super::Device for Device {
+    type SwapChain = SwapChain;
+    type CmdBuf = CmdBuf;
+    type Shader = Shader;
+    type RenderPipeline = RenderPipeline;
+    type Texture = Texture;
+    type Buffer = Buffer;
+    type ReadBackRequest = ReadBackRequest;
+    type RenderPass = RenderPass;
+    type Heap = Heap;
+    type QueryHeap = QueryHeap;
+    type ComputePipeline = ComputePipeline;
+    type RaytracingPipeline = RaytracingPipeline;
+    type CommandSignature = CommandSignature;
+    type RaytracingShaderBindingTable = RaytracingShaderBindingTable;
+    type RaytracingBLAS = RaytracingBLAS;
+    type RaytracingTLAS = RaytracingTLAS;
+    type Fence = Fence;
+
+    fn create(info: &DeviceInfo) -> Result<Self, Error> {
+        unsafe {
+            let entry = ash::Entry::load().map_err(|e| Error {
+                msg: format!("hotline_rs::gfx::vk: failed to load the Vulkan loader: {}", e)
+            })?;
+
+            // instance
+            let (layers, extensions) = instance_layers_and_extensions(&entry);
+            let layer_ptrs : Vec<*const i8> = layers.iter().map(|l| l.as_ptr()).collect();
+            let extension_ptrs : Vec<*const i8> = extensions.iter().map(|e| e.as_ptr()).collect();
+            let app_info = vk::ApplicationInfo::default()
+                .application_name(c"hotline")
+                .engine_name(c"hotline")
+                .api_version(vk::API_VERSION_1_3);
+            let instance = entry.create_instance(&vk::InstanceCreateInfo::default()
+                .application_info(&app_info)
+                .enabled_layer_names(&layer_ptrs)
+                .enabled_extension_names(&extension_ptrs), None)
+                .map_err(|e| vk_error("vkCreateInstance", e))?;
+
+            // debug messenger feeds validation messages into the info queue
+            let info_queue : InfoQueueRef = Arc::new(Mutex::new(Vec::new()));
+            let debug_utils_enabled = !extensions.is_empty();
+            let (debug_utils_instance, debug_messenger) = if debug_utils_enabled {
+                let debug_utils = ash::ext::debug_utils::Instance::new(&entry, &instance);
+                let messenger = debug_utils.create_debug_utils_messenger(&vk::DebugUtilsMessengerCreateInfoEXT::default()
+                    .message_severity(vk::DebugUtilsMessageSeverityFlagsEXT::WARNING | vk::DebugUtilsMessageSeverityFlagsEXT::ERROR)
+                    .message_type(vk::DebugUtilsMessageTypeFlagsEXT::GENERAL |
+                        vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION |
+                        vk::DebugUtilsMessageTypeFlagsEXT::PERFORMANCE)
+                    .pfn_user_callback(Some(debug_callback))
+                    .user_data(Arc::as_ptr(&info_queue) as *mut c_void), None)
+                    .unwrap_or(vk::DebugUtilsMessengerEXT::null());
+                (Some(debug_utils), messenger)
+            }
+            else {
+                (None, vk::DebugUtilsMessengerEXT::null())
+            };
+
+            // destroys the instance when the device cannot be created, so callers can fall back to another backend
+            let destroy_instance = |error: Error| {
+                if let Some(debug_utils) = &debug_utils_instance {
+                    debug_utils.destroy_debug_utils_messenger(debug_messenger, None);
+                }
+                instance.destroy_instance(None);
+                error
+            };
+
+            // adapter
+            let (physical_device, adapter_names) = select_physical_device(&instance, &info.adapter_name)
+                .map_err(&destroy_instance)?;
+            let properties = instance.get_physical_device_properties(physical_device);
+            let memory_properties = instance.get_physical_device_memory_properties(physical_device);
+            let queue_families = instance.get_physical_device_queue_family_properties(physical_device);
+            let queue_family_index = queue_families
+                .iter()
+                .position(|q| q.queue_flags.contains(vk::QueueFlags::GRAPHICS | vk::QueueFlags::COMPUTE))
+                .ok_or(Error {
+                    msg: "hotline_rs::gfx::vk: adapter has no graphics and compute queue".to_string()
+                })
+                .map_err(&destroy_instance)? as u32;
+            let queue_count = queue_families[queue_family_index as usize].queue_count.min(2) as usize;
+
+            // query supported features and enable the ones the backend uses
+            let mut supported12 = vk::PhysicalDeviceVulkan12Features::default();
+            let mut supported13 = vk::PhysicalDeviceVulkan13Features::default();
+            let mut supported = vk::PhysicalDeviceFeatures2::default()
+                .push_next(&mut supported12)
+                .push_next(&mut supported13);
+            instance.get_physical_device_features2(physical_device, &mut supported);
+            let core = supported.features;
+
+            let required = [
+                ("dynamicRendering", supported13.dynamic_rendering),
+                ("synchronization2", supported13.synchronization2),
+                ("timelineSemaphore", supported12.timeline_semaphore),
+                ("hostQueryReset", supported12.host_query_reset),
+                ("runtimeDescriptorArray", supported12.runtime_descriptor_array),
+                ("descriptorBindingPartiallyBound", supported12.descriptor_binding_partially_bound),
+            ];
+            if let Some((name, _)) = required.iter().find(|(_, value)| *value == vk::FALSE) {
+                return Err(destroy_instance(Error {
+                    msg: format!("hotline_rs::gfx::vk: adapter does not support required feature {}", name)
+                }));
+            }
+
+            let features = EnabledFeatures {
+                depth_clamp: core.depth_clamp == vk::TRUE,
+                fill_mode_non_solid: core.fill_mode_non_solid == vk::TRUE,
+                logic_op: core.logic_op == vk::TRUE,
+                sampler_anisotropy: core.sampler_anisotropy == vk::TRUE,
+                pipeline_statistics_query: core.pipeline_statistics_query == vk::TRUE,
+                mirror_clamp_to_edge: supported12.sampler_mirror_clamp_to_edge == vk::TRUE,
+                sampled_image_update_after_bind: supported12.descriptor_binding_sampled_image_update_after_bind == vk::TRUE,
+                storage_image_update_after_bind: supported12.descriptor_binding_storage_image_update_after_bind == vk::TRUE,
+                storage_buffer_update_after_bind: supported12.descriptor_binding_storage_buffer_update_after_bind == vk::TRUE,
+                uniform_buffer_update_after_bind: supported12.descriptor_binding_uniform_buffer_update_after_bind == vk::TRUE,
+                update_unused_while_pending: supported12.descriptor_binding_update_unused_while_pending == vk::TRUE,
+            };
+
+            let enabled_core = vk::PhysicalDeviceFeatures {
+                depth_clamp: core.depth_clamp,
+                fill_mode_non_solid: core.fill_mode_non_solid,
+                logic_op: core.logic_op,
+                sampler_anisotropy: core.sampler_anisotropy,
+                pipeline_statistics_query: core.pipeline_statistics_query,
+                independent_blend: core.independent_blend,
+                dual_src_blend: core.dual_src_blend,
+                multi_draw_indirect: core.multi_draw_indirect,
+                texture_compression_bc: core.texture_compression_bc,
+                image_cube_array: core.image_cube_array,
+                shader_storage_image_write_without_format: core.shader_storage_image_write_without_format,
+                shader_storage_image_read_without_format: core.shader_storage_image_read_without_format,
+                fragment_stores_and_atomics: core.fragment_stores_and_atomics,
+                vertex_pipeline_stores_and_atomics: core.vertex_pipeline_stores_and_atomics,
+                ..Default::default()
+            };
+            let mut enabled12 = vk::PhysicalDeviceVulkan12Features::default()
+                .timeline_semaphore(true)
+                .host_query_reset(true)
+                .draw_indirect_count(supported12.draw_indirect_count == vk::TRUE)
+                .sampler_mirror_clamp_to_edge(features.mirror_clamp_to_edge)
+                .descriptor_indexing(supported12.descriptor_indexing == vk::TRUE)
+                .runtime_descriptor_array(true)
+                .descriptor_binding_partially_bound(true)
+                .descriptor_binding_sampled_image_update_after_bind(features.sampled_image_update_after_bind)
+                .descriptor_binding_storage_image_update_after_bind(features.storage_image_update_after_bind)
+                .descriptor_binding_storage_buffer_update_after_bind(features.storage_buffer_update_after_bind)
+                .descriptor_binding_uniform_buffer_update_after_bind(features.uniform_buffer_update_after_bind)
+                .descriptor_binding_update_unused_while_pending(features.update_unused_while_pending)
+                .shader_sampled_image_array_non_uniform_indexing(supported12.shader_sampled_image_array_non_uniform_indexing == vk::TRUE)
+                .shader_storage_image_array_non_uniform_indexing(supported12.shader_storage_image_array_non_uniform_indexing == vk::TRUE)
+                .shader_storage_buffer_array_non_uniform_indexing(supported12.shader_storage_buffer_array_non_uniform_indexing == vk::TRUE)
+                .shader_uniform_buffer_array_non_uniform_indexing(supported12.shader_uniform_buffer_array_non_uniform_indexing == vk::TRUE);
+            let mut enabled13 = vk::PhysicalDeviceVulkan13Features::default()
+                .dynamic_rendering(true)
+                .synchronization2(true);
+            let mut enabled = vk::PhysicalDeviceFeatures2::default()
+                .features(enabled_core)
+                .push_next(&mut enabled12)
+                .push_next(&mut enabled13);
+
+            // device and queues, async compute uses a second queue from the same family so resources
+            // can be shared between queues without ownership transfers
+            let priorities = [1.0, 1.0];
+            let queue_infos = [vk::DeviceQueueCreateInfo::default()
+                .queue_family_index(queue_family_index)
+                .queue_priorities(&priorities[..queue_count])];
+            let device = instance.create_device(physical_device, &vk::DeviceCreateInfo::default()
+                .queue_create_infos(&queue_infos)
+                .push_next(&mut enabled), None)
+                .map_err(|e| destroy_instance(vk_error("vkCreateDevice", e)))?;
+            let queue = device.get_device_queue(queue_family_index, 0);
+            let async_queue = if queue_count > 1 {
+                Some(Mutex::new(device.get_device_queue(queue_family_index, 1)))
+            }
+            else {
+                None
+            };
+            let debug_utils_device = if debug_utils_enabled {
+                Some(ash::ext::debug_utils::Device::new(&instance, &device))
+            }
+            else {
+                None
+            };
+
+            let upload_pool = device.create_command_pool(&vk::CommandPoolCreateInfo::default()
+                .flags(vk::CommandPoolCreateFlags::TRANSIENT)
+                .queue_family_index(queue_family_index), None)
+                .map_err(|e| {
+                    device.destroy_device(None);
+                    destroy_instance(vk_error("vkCreateCommandPool", e))
+                })?;
+
+            let inner = Arc::new(DeviceInner {
+                entry,
+                instance,
+                physical_device,
+                device,
+                queue: Mutex::new(queue),
+                async_queue,
+                queue_family_index,
+                memory_properties,
+                limits: properties.limits,
+                features,
+                debug_utils_instance,
+                debug_utils_device,
+                debug_messenger,
+                info_queue,
+                upload_pool: Mutex::new(upload_pool),
+                set_layouts: Mutex::new(HashMap::new()),
+                samplers: Mutex::new(HashMap::new()),
+                unbounded_descriptor_count: info.shader_heap_size.max(1) as u32
+            });
+
+            // adapter info
+            let name = properties.device_name_as_c_str()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let heaps = &memory_properties.memory_heaps[..memory_properties.memory_heap_count as usize];
+            let device_local : usize = heaps.iter()
+                .filter(|h| h.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL))
+                .map(|h| h.size as usize)
+                .sum();
+            let shared : usize = heaps.iter()
+                .filter(|h| !h.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL))
+                .map(|h| h.size as usize)
+                .sum();
+            let adapter_info = AdapterInfo {
+                name: name.clone(),
+                description: format!("{} (Vulkan {}.{}.{}, {:?})",
+                    name,
+                    vk::api_version_major(properties.api_version),
+                    vk::api_version_minor(properties.api_version),
+                    vk::api_version_patch(properties.api_version),
+                    properties.device_type
+                ),
+                dedicated_video_memory: device_local,
+                dedicated_system_memory: 0,
+                shared_system_memory: shared,
+                available: adapter_names
+            };
+
+            Ok(Device {
+                adapter_info,
+                feature_flags: DeviceFeatureFlags::NONE,
+                shader_heap: Heap::create(&inner, &HeapInfo {
+                    heap_type: HeapType::Shader,
+                    num_descriptors: info.shader_heap_size,
+                    debug_name: Some("device_shader_heap".to_string())
+                }),
+                rtv_heap: Heap::create(&inner, &HeapInfo {
+                    heap_type: HeapType::RenderTarget,
+                    num_descriptors: info.render_target_heap_size,
+                    debug_name: Some("device_rtv_heap".to_string())
+                }),
+                dsv_heap: Heap::create(&inner, &HeapInfo {
+                    heap_type: HeapType::DepthStencil,
+                    num_descriptors: info.depth_stencil_heap_size,
+                    debug_name: Some("device_dsv_heap".to_string())
+                }),
+                drop_list: DropList::new(),
+                free_list: FreeList::new(),
+                inner
+            })
+        }
+    }
+
+    fn create_heap(&mut self, info: &HeapInfo) -> Self::Heap {
+        Heap::create(&self.inner, info)
+    }
+
+    fn create_query_heap(&self, info: &QueryHeapInfo) -> Self::QueryHeap {
+        let (query_type, statistics) = match info.heap_type {
+            QueryType::Occlusion | QueryType::BinaryOcclusion => (vk::QueryType::OCCLUSION, vk::QueryPipelineStatisticFlags::empty()),
+            QueryType::Timestamp => (vk::QueryType::TIMESTAMP, vk::QueryPipelineStatisticFlags::empty()),
+            QueryType::PipelineStatistics => (vk::QueryType::PIPELINE_STATISTICS, pipeline_statistics_flags()),
+            QueryType::VideoDecodeStatistics => {
+                report(&self.inner.info_queue, "warning", "video decode statistics queries are not supported, using timestamps".to_string());
+                (vk::QueryType::TIMESTAMP, vk::QueryPipelineStatisticFlags::empty())
+            }
+        };
+        let count = info.num_queries.max(1) as u32;
+        let device = &self.inner.device;
+        let pool = unsafe {
+            let pool = device.create_query_pool(&vk::QueryPoolCreateInfo::default()
+                .query_type(query_type)
+                .query_count(count)
+                .pipeline_statistics(statistics), None)
+                .expect("hotline_rs::gfx::vk: failed to create query pool");
+            // queries must be reset before first use, after that they are reset when their results are copied
+            device.reset_query_pool(pool, 0, count);
+            pool
+        };
+        let mut resource = VkResource::new(&self.inner);
+        resource.query_pools.push(pool);
+        QueryHeap {
+            pool,
+            heap_type: info.heap_type,
+            alloc_index: 0,
+            capacity: count as usize,
+            resource: Some(resource),
+            drop_list: self.drop_list.clone()
+        }
+    }
+
+    fn create_swap_chain<A: App>(
+        &mut self,
+        info: &SwapChainInfo,
+        window: &A::Window,
+    ) -> Result<Self::SwapChain, Error> {
+        if info.num_buffers == 0 {
+            return Err(Error {
+                msg: "hotline_rs::gfx::vk: swap chain requires at least 1 buffer".to_string()
+            });
+        }
+        let semaphore = unsafe {
+            let mut type_info = vk::SemaphoreTypeCreateInfo::default()
+                .semaphore_type(vk::SemaphoreType::TIMELINE)
+                .initial_value(0);
+            self.inner.device.create_semaphore(&vk::SemaphoreCreateInfo::default()
+                .push_next(&mut type_info), None)
+                .map_err(|e| vk_error("vkCreateSemaphore", e))?
+        };
+        let size = window.get_size();
+        let mut swap_chain = SwapChain {
+            device: self.inner.clone(),
+            drop_list: self.drop_list.clone(),
+            num_buffers: info.num_buffers,
+            format: info.format,
+            width: size.x,
+            height: size.y,
+            clear_colour: info.clear_colour,
+            bb_index: 0,
+            semaphore: Arc::new(TimelineSemaphore {
+                device: self.inner.clone(),
+                semaphore
+            }),
+            fence_last_signalled_value: 0,
+            frame_fence_value: vec![0; info.num_buffers as usize],
+            frame_index: 1,
+            backbuffer_textures: Vec::new(),
+            backbuffer_passes: Vec::new(),
+            backbuffer_passes_no_clear: Vec::new()
+        };
+        swap_chain.create_backbuffers(self)?;
+        Ok(swap_chain)
+    }
+
+    fn create_cmd_buf_for_queue(&self, num_buffers: u32, _queue: QueueType) -> Self::CmdBuf {
+        // all queues are created from the same family so command buffers can be submitted to any of them
+        self.create_cmd_buf(num_buffers)
+    }
+
+    fn create_fence(&self) -> Result<Self::Fence, Error> {
+        let semaphore = unsafe {
+            let mut type_info = vk::SemaphoreTypeCreateInfo::default()
+                .semaphore_type(vk::SemaphoreType::TIMELINE)
+                .initial_value(0);
+            self.inner.device.create_semaphore(&vk::SemaphoreCreateInfo::default()
+                .push_next(&mut type_info), None)
+                .map_err(|e| vk_error("vkCreateSemaphore", e))?
+        };
+        Ok(Fence {
+            semaphore: TimelineSemaphore {
+                device: self.inner.clone(),
+                semaphore
+            }
+        })
+    }
+
+    fn create_cmd_buf(&self, num_buffers: u32) -> Self::CmdBuf {
+        let device = &self.inner.device;
+        unsafe {
+            let pool = device.create_command_pool(&vk::CommandPoolCreateInfo::default()
+                .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER)
+                .queue_family_index(self.inner.queue_family_index), None)
+                .expect("hotline_rs::gfx::vk: failed to create command pool");
+            let command_buffers = device.allocate_command_buffers(&vk::CommandBufferAllocateInfo::default()
+                .command_pool(pool)
+                .level(vk::CommandBufferLevel::PRIMARY)
+                .command_buffer_count(num_buffers.max(1)))
+                .expect("hotline_rs::gfx::vk: failed to allocate command buffers");
+            CmdBuf {
+                device: self.inner.clone(),
+                drop_list: self.drop_list.clone(),
+                pool: Arc::new(CommandPool {
+                    device: self.inner.clone(),
+                    pool
+                }),
+                command_buffers,
+                bb_index: 0,
+                in_render_pass: false,
+                render_layout: None,
+                compute_layout: None,
+                pending_query_copies: Arc::new(Mutex::new(Vec::new()))
+            }
+        }
+    }
+
+    fn create_shader<T: Sized>(&self, info: &ShaderInfo, src: &[T]) -> Result<Self::Shader, Error> {
+        if info.compile_info.is_some() {
+            return Err(Error {
+                msg: "hotline_rs::gfx::vk: runtime shader compilation is not supported, supply SPIR-V byte code".to_string()
+            });
+        }
+        let bytes = super::slice_as_u8_slice(src);
+        let words = ash::util::read_spv(&mut std::io::Cursor::new(bytes)).map_err(|e| Error {
+            msg: format!("hotline_rs::gfx::vk: shader is not valid SPIR-V: {}", e)
+        })?;
+        // shaders may be created with a generic type, the entry point in the module is authoritative
+        let (stage, entry_point) = parse_spirv_entry_point(&words)
+            .unwrap_or((to_vk_shader_stage(info.shader_type), c"main".to_owned()));
+        let module = unsafe {
+            self.inner.device.create_shader_module(&vk::ShaderModuleCreateInfo::default()
+                .code(&words), None)
+                .map_err(|e| vk_error("vkCreateShaderModule", e))?
+        };
+        Ok(Shader {
+            device: self.inner.clone(),
+            module,
+            stage,
+            entry_point
+        })
+    }
+
+    fn create_buffer<T: Sized>(
+        &mut self,
+        info: &BufferInfo,
+        data: Option<&[T]>,
+    ) -> Result<Self::Buffer, Error> {
+        let mut buffer = self.create_buffer_storage(info, data)?;
+        Self::create_buffer_views(info, &mut self.shader_heap, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn create_buffer_with_heap<T: Sized>(
+        &mut self,
+        info: &BufferInfo,
+        data: Option<&[T]>,
+        heap: &mut Self::Heap
+    ) -> Result<Self::Buffer, Error> {
+        let mut buffer = self.create_buffer_storage(info, data)?;
+        Self::create_buffer_views(info, heap, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn create_read_back_buffer(
+        &mut self,
+        size: usize,
+    ) -> Result<Self::Buffer, Error> {
+        self.create_buffer_storage::<u8>(&BufferInfo {
+            usage: BufferUsage::NONE,
+            cpu_access: CpuAccessFlags::READ,
+            format: Format::Unknown,
+            stride: size,
+            num_elements: 1,
+            initial_state: ResourceState::CopyDst
+        }, None)
+    }
+
+    fn create_texture<T: Sized>(
+        &mut self,
+        info: &TextureInfo,
+        data: Option<&[T]>,
+    ) -> Result<Self::Texture, Error> {
+        self.create_texture_with_heaps(info, TextureHeapInfo::default(), data)
+    }
+
+    fn create_texture_with_heaps<T: Sized>(
+        &mut self,
+        info: &TextureInfo,
+        heaps: TextureHeapInfo<Self>,
+        data: Option<&[T]>,
+    ) -> Result<Self::Texture, Error> {
+        if info.width == 0 || info.height == 0 {
+            return Err(Error {
+                msg: format!("hotline_rs::gfx::vk: invalid texture dimensions {}x{}", info.width, info.height)
+            });
+        }
+        let data = data.map(|data| super::slice_as_u8_slice(data));
+        if let Some(data) = data {
+            let expected = super::size_for_format_mipped(
+                info.format, info.width, info.height, info.depth, info.array_layers, info.mip_levels) as usize;
+            if data.len() != expected {
+                return Err(Error {
+                    msg: format!("hotline_rs::gfx::vk: data size: ({}) bytes does not match expected size: ({}) bytes",
+                        data.len(), expected)
+                });
+            }
+        }
+
+        let inner = self.inner.clone();
+        let format = to_vk_format(info.format);
+        let aspect = to_vk_aspect(info.format);
+        let mut resource = VkResource::new(&self.inner);
+
+        // msaa textures have a single mip, the resolved texture contains the full mip chain
+        let msaa = info.samples > 1;
+        let (image, memory) = self.create_image(info, info.samples, if msaa { 1 } else { info.mip_levels })?;
+        resource.images.push(image);
+        resource.memory.push(memory);
+        self.upload_texture_data(info, image, aspect, if msaa { None } else { data })?;
+
+        let resolved_image = if msaa {
+            let (resolved, resolved_memory) = self.create_image(info, 1, info.mip_levels)?;
+            resource.images.push(resolved);
+            resource.memory.push(resolved_memory);
+            self.upload_texture_data(info, resolved, aspect, data)?;
+            Some(resolved)
+        }
+        else {
+            None
+        };
+
+        let mut tex = Texture {
+            info: *info,
+            image,
+            resolved_image,
+            format,
+            aspect,
+            rtv_views: Vec::new(),
+            dsv_views: Vec::new(),
+            srv_index: None,
+            msaa_srv_index: None,
+            uav_index: None,
+            subresource_uav_index: Vec::new(),
+            rtv_index: Vec::new(),
+            dsv_index: Vec::new(),
+            heap_id: None,
+            resource: None,
+            drop_list: self.drop_list.clone(),
+            allocs: Vec::new()
+        };
+
+        let is_3d = matches!(info.tex_type, TextureType::Texture3D);
+        let num_slices = if is_3d { info.depth.max(1) } else { info.array_layers.max(1) };
+        let view_type = to_vk_image_view_type(info);
+        let sampled_aspect = to_vk_sampled_aspect(info.format);
+
+        // shader resource and unordered access views
+        let shader_heap = if let Some(heap) = heaps.shader { heap } else { &mut self.shader_heap };
+        let mut allocs = HeapAllocs::new(shader_heap);
+        if info.usage.contains(TextureUsage::SHADER_RESOURCE) {
+            let srv_image = resolved_image.unwrap_or(image);
+            let view = inner.create_image_view(&mut resource, srv_image, view_type, format, full_subresource_range(sampled_aspect))?;
+            tex.srv_index = Some(shader_heap.allocate_descriptor(Descriptor::Image {
+                view,
+                view_type,
+                storage: false,
+                multisampled: false
+            }, &mut allocs)?);
+            if msaa {
+                let view = inner.create_image_view(&mut resource, image, view_type, format, full_subresource_range(sampled_aspect))?;
+                tex.msaa_srv_index = Some(shader_heap.allocate_descriptor(Descriptor::Image {
+                    view,
+                    view_type,
+                    storage: false,
+                    multisampled: true
+                }, &mut allocs)?);
+            }
+        }
+        if info.usage.contains(TextureUsage::UNORDERED_ACCESS) && !msaa {
+            let view = inner.create_image_view(&mut resource, image, view_type, format, vk::ImageSubresourceRange {
+                level_count: 1,
+                ..full_subresource_range(sampled_aspect)
+            })?;
+            tex.uav_index = Some(shader_heap.allocate_descriptor(Descriptor::Image {
+                view,
+                view_type,
+                storage: true,
+                multisampled: false
+            }, &mut allocs)?);
+            let layers = if is_3d { 1 } else { info.array_layers.max(1) };
+            let subresource_view_type = if is_3d { vk::ImageViewType::TYPE_3D } else { to_vk_slice_view_type(info.tex_type) };
+            for layer in 0..layers {
+                for mip in 0..info.mip_levels.max(1) {
+                    let view = inner.create_image_view(&mut resource, image, subresource_view_type, format, vk::ImageSubresourceRange {
+                        aspect_mask: sampled_aspect,
+                        base_mip_level: mip,
+                        level_count: 1,
+                        base_array_layer: layer,
+                        layer_count: 1
+                    })?;
+                    tex.subresource_uav_index.push(shader_heap.allocate_descriptor(Descriptor::Image {
+                        view,
+                        view_type: subresource_view_type,
+                        storage: true,
+                        multisampled: false
+                    }, &mut allocs)?);
+                }
+            }
+        }
+        tex.heap_id = Some(shader_heap.id);
+        tex.allocs.push(allocs);
+
+        // render target views per array slice
+        if info.usage.contains(TextureUsage::RENDER_TARGET) {
+            let rtv_heap = if let Some(heap) = heaps.render_target { heap } else { &mut self.rtv_heap };
+            let mut allocs = HeapAllocs::new(rtv_heap);
+            for slice in 0..num_slices {
+                let view = inner.create_image_view(&mut resource, image, to_vk_slice_view_type(info.tex_type), format, vk::ImageSubresourceRange {
+                    aspect_mask: aspect,
+                    base_mip_level: 0,
+                    level_count: 1,
+                    base_array_layer: slice,
+                    layer_count: 1
+                })?;
+                tex.rtv_views.push(view);
+                let index = rtv_heap.allocate()?;
+                tex.rtv_index.push(index);
+                allocs.indices.push(index);
+            }
+            tex.allocs.push(allocs);
+        }
+
+        // depth stencil views per array slice
+        if info.usage.contains(TextureUsage::DEPTH_STENCIL) {
+            let dsv_heap = if let Some(heap) = heaps.depth_stencil { heap } else { &mut self.dsv_heap };
+            let mut allocs = HeapAllocs::new(dsv_heap);
+            for slice in 0..num_slices {
+                let view = inner.create_image_view(&mut resource, image, to_vk_slice_view_type(info.tex_type), format, vk::ImageSubresourceRange {
+                    aspect_mask: aspect,
+                    base_mip_level: 0,
+                    level_count: 1,
+                    base_array_layer: slice,
+                    layer_count: 1
+                })?;
+                tex.dsv_views.push(view);
+                let index = dsv_heap.allocate()?;
+                tex.dsv_index.push(index);
+                allocs.indices.push(index);
+            }
+            tex.allocs.push(allocs);
+        }
+
+        tex.resource = Some(resource);
+        Ok(tex)
+    }
+
+    fn create_render_pipeline(
+        &self,
+        info: &RenderPipelineInfo<Self>,
+    ) -> Result<Self::RenderPipeline, Error> {
+        let pass = info.pass.ok_or_else(|| Error {
+            msg: "hotline_rs::gfx::vk: render pipeline requires a pass to obtain render target formats".to_string()
+        })?;
+
+        // shader stages
+        let mut stages = Vec::new();
+        if let Some(vs) = info.vs {
+            if !vs.stage.contains(vk::ShaderStageFlags::VERTEX) {
+                return Err(Error {
+                    msg: "hotline_rs::gfx::vk: render pipeline vs is not a vertex shader".to_string()
+                });
+            }
+            stages.push(vk::PipelineShaderStageCreateInfo::default()
+                .stage(vk::ShaderStageFlags::VERTEX)
+                .module(vs.module)
+                .name(&vs.entry_point));
+        }
+        if let Some(fs) = info.fs {
+            stages.push(vk::PipelineShaderStageCreateInfo::default()
+                .stage(vk::ShaderStageFlags::FRAGMENT)
+                .module(fs.module)
+                .name(&fs.entry_point));
+        }
+
+        // vertex input, elements with an offset of u32::MAX are appended after the previous element in the slot
+        let mut slot_bindings : BTreeMap<u32, vk::VertexInputRate> = BTreeMap::new();
+        let mut slot_offsets : HashMap<u32, u32> = HashMap::new();
+        let mut attributes = Vec::new();
+        for (location, element) in info.input_layout.iter().enumerate() {
+            let rate = match element.input_slot_class {
+                InputSlotClass::PerVertex => vk::VertexInputRate::VERTEX,
+                InputSlotClass::PerInstance => vk::VertexInputRate::INSTANCE
+            };
+            slot_bindings.insert(element.input_slot, rate);
+            let next_offset = slot_offsets.entry(element.input_slot).or_insert(0);
+            let offset = if element.aligned_byte_offset == u32::MAX {
+                *next_offset
+            }
+            else {
+                element.aligned_byte_offset
+            };
+            *next_offset = offset + super::block_size_for_format(element.format);
+            attributes.push(vk::VertexInputAttributeDescription {
+                location: location as u32,
+                binding: element.input_slot,
+                format: to_vk_format(element.format),
+                offset
+            });
+        }
+        // strides are supplied when binding vertex buffers through dynamic state
+        let bindings : Vec<vk::VertexInputBindingDescription> = slot_bindings.iter().map(|(slot, rate)| {
+            vk::VertexInputBindingDescription {
+                binding: *slot,
+                stride: 0,
+                input_rate: *rate
+            }
+        }).collect();
+        let vertex_input = vk::PipelineVertexInputStateCreateInfo::default()
+            .vertex_binding_descriptions(&bindings)
+            .vertex_attribute_descriptions(&attributes);
+
+        let input_assembly = vk::PipelineInputAssemblyStateCreateInfo::default()
+            .topology(to_vk_topology(info.topology));
+        let tessellation = vk::PipelineTessellationStateCreateInfo::default()
+            .patch_control_points(info.patch_index.max(1));
+
+        let viewport = vk::PipelineViewportStateCreateInfo::default()
+            .viewport_count(1)
+            .scissor_count(1);
+
+        // rasteriser
+        let raster = &info.raster_info;
+        let polygon_mode = match raster.fill_mode {
+            FillMode::Wireframe if self.inner.features.fill_mode_non_solid => vk::PolygonMode::LINE,
+            _ => vk::PolygonMode::FILL
+        };
+        let cull_mode = match raster.cull_mode {
+            CullMode::None => vk::CullModeFlags::NONE,
+            CullMode::Front => vk::CullModeFlags::FRONT,
+            CullMode::Back => vk::CullModeFlags::BACK
+        };
+        let depth_bias_enable = raster.depth_bias != 0 || raster.slope_scaled_depth_bias != 0.0;
+        let rasterization = vk::PipelineRasterizationStateCreateInfo::default()
+            .depth_clamp_enable(!raster.depth_clip_enable && self.inner.features.depth_clamp)
+            .polygon_mode(polygon_mode)
+            .cull_mode(cull_mode)
+            .front_face(if raster.front_ccw { vk::FrontFace::COUNTER_CLOCKWISE } else { vk::FrontFace::CLOCKWISE })
+            .depth_bias_enable(depth_bias_enable)
+            .depth_bias_constant_factor(raster.depth_bias as f32)
+            .depth_bias_clamp(raster.depth_bias_clamp)
+            .depth_bias_slope_factor(raster.slope_scaled_depth_bias)
+            .line_width(1.0);
+
+        let sample_mask = [info.sample_mask];
+        let multisample = vk::PipelineMultisampleStateCreateInfo::default()
+            .rasterization_samples(to_vk_sample_count(pass.sample_count))
+            .sample_mask(&sample_mask)
+            .alpha_to_coverage_enable(info.blend_info.alpha_to_coverage_enabled);
+
+        // depth stencil
+        let ds = &info.depth_stencil_info;
+        let depth_stencil = vk::PipelineDepthStencilStateCreateInfo::default()
+            .depth_test_enable(ds.depth_enabled)
+            .depth_write_enable(ds.depth_enabled && ds.depth_write_mask == DepthWriteMask::All)
+            .depth_compare_op(to_vk_compare_op(ds.depth_func))
+            .stencil_test_enable(ds.stencil_enabled)
+            .front(to_vk_stencil_op_state(&ds.front_face, ds.stencil_read_mask, ds.stencil_write_mask))
+            .back(to_vk_stencil_op_state(&ds.back_face, ds.stencil_read_mask, ds.stencil_write_mask));
+
+        // blend, without independent blend the first render target state applies to all targets
+        let default_blend = RenderTargetBlendInfo::default();
+        let mut logic_op = None;
+        let attachments : Vec<vk::PipelineColorBlendAttachmentState> = (0..pass.colour_formats.len()).map(|i| {
+            let index = if info.blend_info.independent_blend_enabled { i } else { 0 };
+            let rt = info.blend_info.render_target.get(index).unwrap_or(&default_blend);
+            if rt.logic_op_enabled && self.inner.features.logic_op {
+                logic_op = Some(to_vk_logic_op(rt.logic_op));
+            }
+            vk::PipelineColorBlendAttachmentState {
+                blend_enable: rt.blend_enabled as vk::Bool32,
+                src_color_blend_factor: to_vk_blend_factor(rt.src_blend),
+                dst_color_blend_factor: to_vk_blend_factor(rt.dst_blend),
+                color_blend_op: to_vk_blend_op(rt.blend_op),
+                src_alpha_blend_factor: to_vk_blend_factor(rt.src_blend_alpha),
+                dst_alpha_blend_factor: to_vk_blend_factor(rt.dst_blend_alpha),
+                alpha_blend_op: to_vk_blend_op(rt.blend_op_alpha),
+                color_write_mask: to_vk_colour_write_mask(rt.write_mask)
+            }
+        }).collect();
+        let mut blend = vk::PipelineColorBlendStateCreateInfo::default()
+            .attachments(&attachments);
+        if let Some(op) = logic_op {
+            blend = blend.logic_op_enable(true).logic_op(op);
+        }
+
+        let dynamic_states = [
+            vk::DynamicState::VIEWPORT,
+            vk::DynamicState::SCISSOR,
+            vk::DynamicState::VERTEX_INPUT_BINDING_STRIDE
+        ];
+        let dynamic = vk::PipelineDynamicStateCreateInfo::default()
+            .dynamic_states(&dynamic_states);
+
+        // dynamic rendering formats from the pass
+        let mut rendering = vk::PipelineRenderingCreateInfo::default()
+            .color_attachment_formats(&pass.colour_formats)
+            .depth_attachment_format(pass.depth_stencil_format);
+        if pass.has_stencil {
+            rendering = rendering.stencil_attachment_format(pass.depth_stencil_format);
+        }
+
+        let (slots, slot_lookup) = create_slot_lookup(&info.pipeline_layout);
+        let layout = Arc::new(self.inner.create_pipeline_layout(&info.pipeline_layout, &slot_lookup, PipelineType::Render)?);
+
+        let mut create_info = vk::GraphicsPipelineCreateInfo::default()
+            .stages(&stages)
+            .vertex_input_state(&vertex_input)
+            .input_assembly_state(&input_assembly)
+            .viewport_state(&viewport)
+            .rasterization_state(&rasterization)
+            .multisample_state(&multisample)
+            .depth_stencil_state(&depth_stencil)
+            .color_blend_state(&blend)
+            .dynamic_state(&dynamic)
+            .layout(layout.layout)
+            .push_next(&mut rendering);
+        if matches!(info.topology, Topology::PatchList) {
+            create_info = create_info.tessellation_state(&tessellation);
+        }
+
+        let pipeline = unsafe {
+            self.inner.device.create_graphics_pipelines(vk::PipelineCache::null(), &[create_info], None)
+                .map_err(|(_, e)| vk_error("vkCreateGraphicsPipelines", e))?[0]
+        };
+
+        Ok(RenderPipeline {
+            pipeline,
+            layout,
+            slots,
+            slot_lookup,
+            drop_list: self.drop_list.clone()
+        })
+    }
+
+    fn create_render_pass(&self, info: &RenderPassInfo<Self>) -> Result<Self::RenderPass, Error> {
+        let mut sample_count = None;
+        let mut extent = vk::Extent2D::default();
+        let mut colour_views = Vec::new();
+        let mut colour_formats = Vec::new();
+        let mut formats = Vec::new();
+        for target in &info.render_targets {
+            if !target.info.usage.contains(TextureUsage::RENDER_TARGET) {
+                return Err(Error {
+                    msg: "hotline_rs::gfx::vk: render pass target was not created with TextureUsage::RENDER_TARGET".to_string()
+                });
+            }
+            if let Some(samples) = sample_count {
+                if samples != target.info.samples {
+                    return Err(Error {
+                        msg: format!("Sample counts must match on all targets: expected {} samples, found {}",
+                            samples,
+                            target.info.samples
+                        )
+                    });
+                }
+            }
+            sample_count = Some(target.info.samples);
+            let view = target.rtv_views.get(info.array_slice).ok_or_else(|| Error {
+                msg: format!("hotline_rs::gfx::vk: render pass array slice {} is out of range", info.array_slice)
+            })?;
+            colour_views.push(*view);
+            colour_formats.push(target.format);
+            formats.push(target.info.format);
+            extent = vk::Extent2D {
+                width: target.info.width as u32,
+                height: target.info.height as u32
+            };
+        }
+
+        let mut depth_stencil_view = None;
+        let mut depth_stencil_format = vk::Format::UNDEFINED;
+        let mut ds_format = None;
+        if let Some(ds) = info.depth_stencil {
+            if !ds.info.usage.contains(TextureUsage::DEPTH_STENCIL) {
+                return Err(Error {
+                    msg: "hotline_rs::gfx::vk: render pass depth stencil was not created with TextureUsage::DEPTH_STENCIL".to_string()
+                });
+            }
+            let view = ds.dsv_views.get(info.array_slice).ok_or_else(|| Error {
+                msg: format!("hotline_rs::gfx::vk: render pass array slice {} is out of range", info.array_slice)
+            })?;
+            depth_stencil_view = Some(*view);
+            depth_stencil_format = ds.format;
+            ds_format = Some(ds.info.format);
+            if info.render_targets.is_empty() {
+                sample_count = Some(ds.info.samples);
+                extent = vk::Extent2D {
+                    width: ds.info.width as u32,
+                    height: ds.info.height as u32
+                };
+            }
+        }
+
+        // hash together the rt, ds and sample count to get a unique hash for format combo
+        let mut fmthash = DefaultHasher::new();
+        let sample_count = sample_count.unwrap_or(1);
+        sample_count.hash(&mut fmthash);
+        ds_format.hash(&mut fmthash);
+        for format in &formats {
+            format.hash(&mut fmthash);
+        }
+
+        Ok(RenderPass {
+            colour_views,
+            depth_stencil_view,
+            has_stencil: ds_format.map(has_stencil).unwrap_or(false),
+            colour_formats,
+            depth_stencil_format,
+            extent,
+            rt_clear: info.rt_clear,
+            ds_clear: info.ds_clear,
+            discard: info.discard,
+            sample_count,
+            format_hash: fmthash.finish()
+        })
+    }
+
+    fn create_compute_pipeline(
+        &self,
+        info: &ComputePipelineInfo<Self>,
+    ) -> Result<Self::ComputePipeline, Error> {
+        if !info.cs.stage.contains(vk::ShaderStageFlags::COMPUTE) {
+            return Err(Error {
+                msg: "hotline_rs::gfx::vk: compute pipeline cs is not a compute shader".to_string()
+            });
+        }
+        let (slots, slot_lookup) = create_slot_lookup(&info.pipeline_layout);
+        let layout = Arc::new(self.inner.create_pipeline_layout(&info.pipeline_layout, &slot_lookup, PipelineType::Compute)?);
+        let create_info = vk::ComputePipelineCreateInfo::default()
+            .stage(vk::PipelineShaderStageCreateInfo::default()
+                .stage(vk::ShaderStageFlags::COMPUTE)
+                .module(info.cs.module)
+                .name(&info.cs.entry_point))
+            .layout(layout.layout);
+        let pipeline = unsafe {
+            self.inner.device.create_compute_pipelines(vk::PipelineCache::null(), &[create_info], None)
+                .map_err(|(_, e)| vk_error("vkCreateComputePipelines", e))?[0]
+        };
+        Ok(ComputePipeline {
+            pipeline,
+            layout,
+            slots,
+            slot_lookup,
+            drop_list: self.drop_list.clone()
+        })
+    }
+
+    fn create_raytracing_pipeline(
+        &self,
+        info: &RaytracingPipelineInfo<Self>,
+    ) -> Result<Self::RaytracingPipeline, Error> {
+        // slots are available so pipeline layouts can be inspected, but there is no pipeline to bind
+        let (slots, slot_lookup) = create_slot_lookup(&info.pipeline_layout);
+        Ok(RaytracingPipeline {
+            slots,
+            slot_lookup
+        })
+    }
+
+    fn create_raytracing_shader_binding_table(
+        &self,
+        info: &RaytracingShaderBindingTableInfo<Self>
+    ) -> Result<Self::RaytracingShaderBindingTable, Error> {
+        Err(Error {
+            msg: "hotline_rs::gfx::vk: raytracing is not supported".to_string()
+        })
+    }
+
+    fn create_raytracing_blas(
+        &mut self,
+        info: &RaytracingBLASInfo<Self>
+    ) -> Result<Self::RaytracingBLAS, Error> {
+        Err(Error {
+            msg: "hotline_rs::gfx::vk: raytracing is not supported".to_string()
+        })
+    }
+
+    fn create_upload_buffer<T: Sized>(
+        &mut self,
+        data: &[T]
+    ) -> Result<Buffer, Error> {
+        self.create_buffer_storage(&BufferInfo {
+            usage: BufferUsage::UPLOAD,
+            cpu_access: CpuAccessFlags::WRITE,
+            format: Format::Unknown,
+            stride: std::mem::size_of::<T>(),
+            num_elements: data.len(),
+            initial_state: ResourceState::GenericRead
+        }, Some(data))
+    }
+
+    fn create_raytracing_instance_buffer(
+        &mut self,
+        instances: &Vec<RaytracingInstanceInfo<Self>>
+    ) -> Result<Buffer, Error> {
+        Err(Error {
+            msg: "hotline_rs::gfx::vk: raytracing is not supported".to_string()
+        })
+    }
+
+    fn create_raytracing_tlas(
+        &mut self,
+        info: &RaytracingTLASInfo<Self>
+    ) -> Result<Self::RaytracingTLAS, Error> {
+        Err(Error {
+            msg: "hotline_rs::gfx::vk: raytracing is not supported".to_string()
+        })
+    }
+
+    fn create_resource_view(
+        &mut self,
+        info: &ResourceViewInfo,
+        resource: Resource<Device>,
+        heap: &mut Heap
+    ) -> Result<usize, super::Error> {
+        if heap.heap_type != HeapType::Shader {
+            return Err(Error {
+                msg: "hotline_rs::gfx::vk: resource views must be created in a heap of HeapType::Shader".to_string()
+            });
+        }
+        match resource {
+            Resource::Buffer(buffer) => {
+                let offset = info.first_element * info.structure_byte_size;
+                let range = info.num_elements * info.structure_byte_size;
+                if offset + range > buffer.size_bytes {
+                    return Err(Error {
+                        msg: format!("hotline_rs::gfx::vk: resource view range {} exceeds buffer size {}", offset + range, buffer.size_bytes)
+                    });
+                }
+                let index = heap.allocate()?;
+                heap.state.lock().unwrap().set(index, Descriptor::Buffer {
+                    buffer: buffer.buffer,
+                    offset: offset as u64,
+                    range: range as u64,
+                    uniform: matches!(info.view_type, ResourceView::ConstantBuffer)
+                });
+                Ok(index)
+            }
+            Resource::Texture(texture) => {
+                let storage = match info.view_type {
+                    ResourceView::ShaderResource => false,
+                    ResourceView::UnorderedAccess => true,
+                    ResourceView::ConstantBuffer => {
+                        return Err(Error {
+                            msg: "hotline_rs::gfx::vk: constant buffer views cannot be created for textures".to_string()
+                        });
+                    }
+                };
+                // views use the format of the view info, so typed views can reinterpret compatible formats
+                let format = if info.format == Format::Unknown { texture.format } else { to_vk_format(info.format) };
+                let view_type = to_vk_image_view_type(&texture.info);
+                let range = vk::ImageSubresourceRange {
+                    level_count: if storage { 1 } else { vk::REMAINING_MIP_LEVELS },
+                    ..full_subresource_range(to_vk_sampled_aspect(texture.info.format))
+                };
+                let image = if storage { texture.image } else { texture.resolved_image.unwrap_or(texture.image) };
+                let mut owned = VkResource::new(&self.inner);
+                let view = self.inner.create_image_view(&mut owned, image, view_type, format, range)?;
+                let index = heap.allocate()?;
+                let mut state = heap.state.lock().unwrap();
+                state.set(index, Descriptor::Image {
+                    view,
+                    view_type,
+                    storage,
+                    multisampled: false
+                });
+                state.owned.insert(index, owned);
+                Ok(index)
+            }
+        }
+    }
+
+    fn replace_texture_srv(
+        &mut self,
+        target: &mut Texture,
+        texture: &mut Texture,
+        heap: &mut Heap
+    ) -> Result<(), Error> {
+        let (target_index, index) = match (target.srv_index, texture.srv_index) {
+            (Some(target_index), Some(index)) if target.heap_id == Some(heap.id) && texture.heap_id == Some(heap.id) => {
+                (target_index, index)
+            }
+            _ => {
+                return Err(Error {
+                    msg: "hotline_rs::gfx::vk: replace_texture_srv requires both textures to have a shader resource view in `heap`".to_string()
+                });
+            }
+        };
+
+        // the descriptor of `texture` is written into the slot of `target`
+        let mut state = heap.state.lock().unwrap();
+        if let Some(descriptor) = state.entries.get(index).copied().flatten() {
+            state.set(target_index, descriptor);
+        }
+        drop(state);
+
+        // exchange ownership of the slots so they are released by the other texture
+        for allocs in &mut target.allocs {
+            allocs.replace(heap, target_index, index);
+        }
+        for allocs in &mut texture.allocs {
+            allocs.replace(heap, index, target_index);
+        }
+        target.srv_index = Some(index);
+        texture.srv_index = Some(target_index);
+        Ok(())
+    }
+
+    fn create_raytracing_tlas_with_heap(
+        &mut self,
+        info: &RaytracingTLASInfo<Self>,
+        heap: &mut Heap
+    ) -> Result<RaytracingTLAS, Error> {
+        Err(Error {
+            msg: "hotline_rs::gfx::vk: raytracing is not supported".to_string()
+        })
+    }
+
+    fn create_indirect_render_command<T: Sized>(
+        &mut self,
+        arguments: Vec<IndirectArgument>,
+        pipeline: Option<&Self::RenderPipeline>
+    ) -> Result<Self::CommandSignature, super::Error> {
+        // vulkan indirect commands cannot change bindings, so only a single draw or dispatch argument is supported
+        match arguments.as_slice() {
+            [argument] if matches!(argument.argument_type,
+                IndirectArgumentType::Draw | IndirectArgumentType::DrawIndexed | IndirectArgumentType::Dispatch) => {
+                Ok(CommandSignature {
+                    argument_type: argument.argument_type,
+                    stride: std::mem::size_of::<T>() as u32
+                })
+            }
+            _ => Err(Error {
+                msg: "hotline_rs::gfx::vk: indirect commands must contain a single Draw, DrawIndexed or Dispatch argument".to_string()
+            })
+        }
+    }
+
+    fn execute(&mut self, cmd: &Self::CmdBuf) {
+        self.execute_on_queue(QueueType::Graphics, cmd);
+    }
+
+    fn execute_on_queue(&mut self, queue: QueueType, cmd: &Self::CmdBuf) {
+        let command_buffer_infos = [vk::CommandBufferSubmitInfo::default()
+            .command_buffer(cmd.command_buffers[cmd.bb_index])];
+        let submit = vk::SubmitInfo2::default()
+            .command_buffer_infos(&command_buffer_infos);
+        self.inner.submit(queue, submit);
+    }
+
+    fn signal_fence(&mut self, queue: QueueType, fence: &Self::Fence, value: u64) {
+        let signal_infos = [vk::SemaphoreSubmitInfo::default()
+            .semaphore(fence.semaphore.semaphore)
+            .value(value)
+            .stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)];
+        self.inner.submit(queue, vk::SubmitInfo2::default().signal_semaphore_infos(&signal_infos));
+    }
+
+    fn wait_fence(&mut self, queue: QueueType, fence: &Self::Fence, value: u64) {
+        let wait_infos = [vk::SemaphoreSubmitInfo::default()
+            .semaphore(fence.semaphore.semaphore)
+            .value(value)
+            .stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)];
+        self.inner.submit(queue, vk::SubmitInfo2::default().wait_semaphore_infos(&wait_infos));
+    }
+
+    fn get_shader_heap(&self) -> &Self::Heap {
+        &self.shader_heap
+    }
+
+    fn get_shader_heap_mut(&mut self) -> &mut Self::Heap {
+        &mut self.shader_heap
+    }
+
+    fn cleanup_dropped_resources(&mut self, swap_chain: &Self::SwapChain) {
+        self.shader_heap.cleanup_dropped_resources(swap_chain);
+        self.rtv_heap.cleanup_dropped_resources(swap_chain);
+        self.dsv_heap.cleanup_dropped_resources(swap_chain);
+        self.drop_list.cleanup(swap_chain.frame_index, swap_chain.num_buffers as usize, &self.free_list);
+    }
+
+    fn get_adapter_info(&self) -> &AdapterInfo {
+        &self.adapter_info
+    }
+
+    fn get_feature_flags(&self) -> &DeviceFeatureFlags {
+        &self.feature_flags
+    }
+
+    fn read_buffer(&self, swap_chain: &Self::SwapChain, buffer: &Self::Buffer, size_bytes: usize, frame_written_fence: u64) -> Option<ReadBackData> {
+        if buffer.mapped.is_null() {
+            return None;
+        }
+        let rr = ReadBackRequest {
+            semaphore: swap_chain.semaphore.clone(),
+            fence_value: frame_written_fence,
+            mapped: buffer.mapped,
+            format: Format::Unknown,
+            size: size_bytes.min(buffer.size_bytes),
+            row_pitch: size_bytes,
+            slice_pitch: size_bytes,
+            resource: None,
+            drop_list: self.drop_list.clone()
+        };
+        if rr.is_complete(swap_chain) {
+            rr.map(&MapInfo {
+                subresource: 0,
+                read_start: 0,
+                read_end: size_bytes
+            }).ok()
+        }
+        else {
+            None
+        }
+    }
+
+    fn read_timestamps(&self, swap_chain: &Self::SwapChain, buffer: &Self::Buffer, size_bytes: usize, frame_written_fence: u64) -> Vec<f64> {
+        let mut results = Vec::new();
+        if let Some(data) = self.read_buffer(swap_chain, buffer, size_bytes, frame_written_fence) {
+            // timestamp period is the number of nanoseconds per tick
+            let period = self.inner.limits.timestamp_period as f64;
+            let elem_size = Self::get_timestamp_size_bytes();
+            for chunk in data.data.chunks_exact(elem_size) {
+                let value = u64::from_ne_bytes(chunk.try_into().unwrap());
+                results.push(value as f64 * period / 1_000_000_000.0);
+            }
+        }
+        results
+    }
+
+    fn read_pipeline_statistics(&self, swap_chain: &Self::SwapChain, buffer: &Self::Buffer, frame_written_fence: u64) -> Option<PipelineStatistics> {
+        let size = Self::get_pipeline_statistics_size_bytes();
+        let data = self.read_buffer(swap_chain, buffer, size, frame_written_fence)?;
+        if data.data.len() < size {
+            return None;
+        }
+        // results are written in bit order of `pipeline_statistics_flags`
+        let values : Vec<u64> = data.data[..size].chunks_exact(8)
+            .map(|c| u64::from_ne_bytes(c.try_into().unwrap()))
+            .collect();
+        Some(PipelineStatistics {
+            input_assembler_vertices: values[0],
+            input_assembler_primitives: values[1],
+            vertex_shader_invocations: values[2],
+            pixel_shader_primitives: values[3],
+            compute_shader_invocations: values[4]
+        })
+    }
+
+    fn report_live_objects(&self) -> Result<(), Error> {
+        println!("hotline_rs::gfx::vk: live views (shader: {}, render target: {}, depth stencil: {}), live device references: {}",
+            self.shader_heap.get_num_allocated(),
+            self.rtv_heap.get_num_allocated(),
+            self.dsv_heap.get_num_allocated(),
+            Arc::strong_count(&self.inner)
+        );
+        Ok(())
+    }
+
+    fn get_info_queue_messages(&self) -> Result<Vec<String>, Error> {
+        let mut info_queue = self.inner.info_queue.lock().unwrap();
+        Ok(std::mem::take(&mut *info_queue))
+    }
+
+    fn get_timestamp_size_bytes() -> usize {
+        std::mem::size_of::<u64>()
+    }
+
+    fn get_pipeline_statistics_size_bytes() -> usize {
+        std::mem::size_of::<PipelineStatistics>()
+    }
+
+    fn get_indirect_command_size(argument_type: IndirectArgumentType) -> usize {
+        match argument_type {
+            IndirectArgumentType::Draw => std::mem::size_of::<super::DrawArguments>(),
+            IndirectArgumentType::DrawIndexed => std::mem::size_of::<super::DrawIndexedArguments>(),
+            IndirectArgumentType::Dispatch => std::mem::size_of::<super::DispatchArguments>(),
+            IndirectArgumentType::VertexBuffer => std::mem::size_of::<VertexBufferView>(),
+            IndirectArgumentType::IndexBuffer => std::mem::size_of::<IndexBufferView>(),
+            IndirectArgumentType::PushConstants => std::mem::size_of::<u32>(),
+            IndirectArgumentType::ConstantBuffer => std::mem::size_of::<u64>(),
+            IndirectArgumentType::ShaderResource => std::mem::size_of::<u64>(),
+            IndirectArgumentType::UnorderedAccess => std::mem::size_of::<u64>()
+        }
+    }
+
+    fn get_counter_alignment() -> usize {
+        COUNTER_ALIGNMENT
+    }
+}
+
+impl Drop for Shader {
+    fn drop(&mut self) {
+        // modules are not referenced once pipelines have been created
+        unsafe {
+            self.device.device.destroy_shader_module(self.module, None);
+        }
+    }
+}
+
+impl Drop for PipelineLayoutState {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.device.destroy_pipeline_layout(self.layout, None);
+        }
+    }
+}
+
+impl Texture {
+    /// Returns the `TextureInfo` the texture was created with
+    pub fn get_info(&self) -> &TextureInfo {
+        &self.info
+    }
+
+    /// Returns the underlying `vk::Image`, for msaa textures this is the multi-sample image
+    pub fn get_image(&self) -> vk::Image {
+        self.image
+    }
+}
+
+/// Clones share the same images but do not own them or any heap allocations, so only the original releases them on drop
+impl Clone for Texture {
+    fn clone(&self) -> Self {
+        self.clone_inner()
+    }
+}
+
+impl super::Texture<Device> for Texture {
+    fn get_srv_index(&self) -> Option<usize> {
+        self.srv_index
+    }
+
+    fn get_uav_index(&self) -> Option<usize> {
+        self.uav_index
+    }
+
+    fn get_subresource_uav_index(&self, subresource: u32) -> Option<usize> {
+        self.subresource_uav_index.get(subresource as usize).copied()
+    }
+
+    fn get_msaa_srv_index(&self) -> Option<usize> {
+        self.msaa_srv_index
+    }
+
+    fn clone_inner(&self) -> Self {
+        Texture {
+            info: self.info,
+            image: self.image,
+            resolved_image: self.resolved_image,
+            format: self.format,
+            aspect: self.aspect,
+            rtv_views: self.rtv_views.clone(),
+            dsv_views: self.dsv_views.clone(),
+            srv_index: self.srv_index,
+            msaa_srv_index: self.msaa_srv_index,
+            uav_index: self.uav_index,
+            subresource_uav_index: self.subresource_uav_index.clone(),
+            rtv_index: self.rtv_index.clone(),
+            dsv_index: self.dsv_index.clone(),
+            heap_id: self.heap_id,
+            resource: None,
+            drop_list: self.drop_list.clone(),
+            allocs: Vec::new()
+        }
+    }
+
+    fn is_resolvable(&self) -> bool {
+        self.resolved_image.is_some()
+    }
+
+    fn get_shader_heap_id(&self) -> Option<u16> {
+        self.heap_id
+    }
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        for allocs in &self.allocs {
+            allocs.release();
+        }
+        if let Some(resource) = self.resource.take() {
+            self.drop_list.push(DropResource::new(vec![resource], Vec::new()));
+        }
+    }
+}
+
+impl Buffer {
+    /// Returns the size of the buffer in bytes, including any append counter
+    pub fn get_size_bytes(&self) -> usize {
+        self.size_bytes
+    }
+
+    /// Returns the underlying `vk::Buffer`
+    pub fn get_buffer(&self) -> vk::Buffer {
+        self.buffer
+    }
+}
+
+impl super::Buffer<Device> for Buffer {
+    fn update<T: Sized>(&mut self, offset: usize, data: &[T]) -> Result<(), Error> {
+        let bytes = super::slice_as_u8_slice(data);
+        if offset + bytes.len() > self.size_bytes {
+            return Err(Error {
+                msg: format!("hotline_rs::gfx::vk: buffer update of {} bytes at offset {} exceeds buffer size {}",
+                    bytes.len(), offset, self.size_bytes)
+            });
+        }
+        if self.mapped.is_null() {
+            return Err(Error {
+                msg: "hotline_rs::gfx::vk: buffer update requires a buffer created with CpuAccessFlags::WRITE".to_string()
+            });
+        }
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), self.mapped.add(offset), bytes.len());
+        }
+        Ok(())
+    }
+
+    fn write<T: Sized>(&mut self, offset: usize, data: &[T]) -> Result<(), Error> {
+        if !self.info.cpu_access.contains(CpuAccessFlags::PERSISTENTLY_MAPPED) {
+            return Err(Error {
+                msg: "hotline_rs::gfx::vk: buffer was not created with CpuAccessFlags::PERSISTENTLY_MAPPED".to_string()
+            });
+        }
+        self.update(offset, data)
+    }
+
+    fn map(&mut self, info: &MapInfo) -> *mut u8 {
+        // host visible memory is persistently mapped for the lifetime of the buffer
+        self.mapped
+    }
+
+    fn unmap(&mut self, info: &UnmapInfo) {
+    }
+
+    fn get_srv_index(&self) -> Option<usize> {
+        self.srv_index
+    }
+
+    fn get_cbv_index(&self) -> Option<usize> {
+        self.cbv_index
+    }
+
+    fn get_uav_index(&self) -> Option<usize> {
+        self.uav_index
+    }
+
+    fn get_vbv(&self) -> Option<VertexBufferView> {
+        use ash::vk::Handle;
+        if self.info.usage.contains(BufferUsage::VERTEX) {
+            Some(VertexBufferView {
+                location: self.buffer.as_raw(),
+                size_bytes: self.size_bytes as u32,
+                stride_bytes: self.info.stride as u32
+            })
+        }
+        else {
+            None
+        }
+    }
+
+    fn get_ibv(&self) -> Option<IndexBufferView> {
+        use ash::vk::Handle;
+        if self.info.usage.contains(BufferUsage::INDEX) {
+            Some(IndexBufferView {
+                location: self.buffer.as_raw(),
+                size_bytes: self.size_bytes as u32,
+                format: to_vk_index_type(self.info.format).as_raw() as u32
+            })
+        }
+        else {
+            None
+        }
+    }
+
+    fn get_counter_offset(&self) -> Option<usize> {
+        self.counter_offset
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        if let Some(allocs) = &self.allocs {
+            allocs.release();
+        }
+        if let Some(resource) = self.resource.take() {
+            self.drop_list.push(DropResource::new(vec![resource], Vec::new()));
+        }
+    }
+}
+
+impl super::Heap<Device> for Heap {
+    fn deallocate(&mut self, index: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.clear(&[index]);
+        // views created by `create_resource_view` are released with the slot once the gpu has finished with them
+        let owned = state.owned.remove(&index).map(|resource| vec![resource]).unwrap_or_default();
+        self.drop_list.push(DropResource::new(owned, vec![index]));
+    }
+
+    fn cleanup_dropped_resources(&mut self, swap_chain: &SwapChain) {
+        self.drop_list.cleanup(swap_chain.frame_index, swap_chain.num_buffers as usize, &self.free_list);
+    }
+
+    fn get_heap_id(&self) -> u16 {
+        self.id
+    }
+}
+
+impl QueryHeap {
+    /// Allocates the next query in the heap, wrapping around when the heap is full
+    fn allocate(&mut self) -> u32 {
+        let index = self.alloc_index % self.capacity;
+        self.alloc_index += 1;
+        index as u32
+    }
+}
+
+impl super::QueryHeap<Device> for QueryHeap {
+    fn reset(&mut self) {
+        self.alloc_index = 0;
+    }
+}
+
+impl Drop for QueryHeap {
+    fn drop(&mut self) {
+        if let Some(resource) = self.resource.take() {
+            self.drop_list.push(DropResource::new(vec![resource], Vec::new()));
+        }
+    }
+}
+
+impl super::ReadBackRequest<Device> for ReadBackRequest {
+    fn is_complete(&self, swap_chain: &SwapChain) -> bool {
+        self.semaphore.get_value() >= self.fence_value
+    }
+
+    fn map(&self, info: &MapInfo) -> Result<ReadBackData, Error> {
+        let start = info.read_start.min(self.size);
+        let end = info.read_end.min(self.size).max(start);
+        // memory is persistently mapped and remains valid while the request or the buffer it reads is alive
+        let slice = unsafe {
+            std::slice::from_raw_parts(self.mapped.add(start), end - start)
+        };
+        Ok(ReadBackData {
+            data: slice,
+            format: self.format,
+            size: end - start,
+            row_pitch: self.row_pitch,
+            slice_pitch: self.slice_pitch
+        })
+    }
+
+    fn unmap(&self) {
+    }
+}
+
+impl Drop for ReadBackRequest {
+    fn drop(&mut self) {
+        if let Some(resource) = self.resource.take() {
+            self.drop_list.push(DropResource::new(vec![resource], Vec::new()));
+        }
+    }
+}
+
+impl super::RenderPass<Device> for RenderPass {
+    fn get_format_hash(&self) -> u64 {
+        self.format_hash
+    }
+}
+
+impl super::Fence<Device> for Fence {
+    fn get_completed_value(&self) -> u64 {
+        self.semaphore.get_value()
+    }
+
+    fn wait_for_value(&self, value: u64) {
+        self.semaphore.wait(value)
+    }
+}
+
+impl super::RaytracingTLAS<Device> for RaytracingTLAS {
+    fn get_srv_index(&self) -> Option<usize> {
+        None
+    }
+
+    fn get_shader_heap_id(&self) -> u16 {
+        0
+    }
+}
+
+impl Drop for RenderPipeline {
+    fn drop(&mut self) {
+        let mut resource = VkResource::new(&self.layout.device);
+        resource.pipelines.push(self.pipeline);
+        self.drop_list.push(DropResource::new(vec![resource], Vec::new()));
+    }
+}
+
+impl Drop for ComputePipeline {
+    fn drop(&mut self) {
+        let mut resource = VkResource::new(&self.layout.device);
+        resource.pipelines.push(self.pipeline);
+        self.drop_list.push(DropResource::new(vec![resource], Vec::new()));
+    }
+}
+
+impl super::Pipeline for RenderPipeline {
+    fn get_pipeline_slot(&self, register: u32, space: u32, descriptor_type: DescriptorType) -> Option<&super::PipelineSlotInfo> {
+        self.slot_lookup.get(&(register, space, descriptor_type))
+    }
+
+    fn get_pipeline_slots(&self) -> &Vec<u32> {
+        &self.slots
+    }
+
+    fn get_pipeline_type() -> PipelineType {
+        PipelineType::Render
+    }
+}
+
+impl super::Pipeline for ComputePipeline {
+    fn get_pipeline_slot(&self, register: u32, space: u32, descriptor_type: DescriptorType) -> Option<&super::PipelineSlotInfo> {
+        self.slot_lookup.get(&(register, space, descriptor_type))
+    }
+
+    fn get_pipeline_slots(&self) -> &Vec<u32> {
+        &self.slots
+    }
+
+    fn get_pipeline_type() -> PipelineType {
+        PipelineType::Compute
+    }
+}
+
+impl super::Pipeline for RaytracingPipeline {
+    fn get_pipeline_slot(&self, register: u32, space: u32, descriptor_type: DescriptorType) -> Option<&super::PipelineSlotInfo> {
+        self.slot_lookup.get(&(register, space, descriptor_type))
+    }
+
+    fn get_pipeline_slots(&self) -> &Vec<u32> {
+        &self.slots
+    }
+
+    fn get_pipeline_type() -> PipelineType {
+        PipelineType::Compute
+    }
+}
+
+impl super::Shader<Device> for Shader {}
+impl super::RenderPipeline<Device> for RenderPipeline {}
+impl super::ComputePipeline<Device> for ComputePipeline {}
+impl super::RaytracingPipeline<Device> for RaytracingPipeline {}
+impl super::CommandSignature<Device> for CommandSignature {}
+impl super::RaytracingShaderBindingTable<Device> for RaytracingShaderBindingTable {}
+impl super::RaytracingBLAS<Device> for RaytracingBLAS {}
@@ -0,0 +1,573 @@
+use crate::gfx;
+use crate::os;
+use crate::imgui;
+use crate::imdraw;
+use crate::os::App;
+use crate::ecs_base::*;
+
+use bevy_ecs::prelude::*;
+use maths_rs::prelude::*;
+
+/// The gizmo size is this fraction of its distance from the camera so it stays a constant size on screen
+pub const GIZMO_SCREEN_SCALE: f32 = 0.15;
+
+/// How close, relative to the gizmo size, the mouse needs to be to a handle to select it
+pub const GIZMO_HANDLE_TOLERANCE: f32 = 0.08;
+
+/// Plane handles are squares between these fractions of the gizmo size along each of the planes axes
+pub const GIZMO_PLANE_HANDLE_RANGE: (f32, f32) = (0.2, 0.4);
+
+/// A ray with an `origin` and unit length `direction`
+#[derive(Clone, Copy, Debug)]
+pub struct Ray {
+    pub origin: Vec3f,
+    pub direction: Vec3f
+}
+
+impl Ray {
+    /// Returns the point `t` along the ray
+    pub fn at(&self, t: f32) -> Vec3f {
+        self.origin + self.direction * t
+    }
+}
+
+/// The transform a gizmo manipulates
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GizmoMode {
+    #[default]
+    Translate,
+    Rotate,
+    Scale
+}
+
+/// A handle of a gizmo, single axes or the planes between 2 axes which are only used by `GizmoMode::Translate`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GizmoAxis {
+    X,
+    Y,
+    Z,
+    XY,
+    XZ,
+    YZ
+}
+
+impl GizmoAxis {
+    pub const AXES: [GizmoAxis; 3] = [GizmoAxis::X, GizmoAxis::Y, GizmoAxis::Z];
+    pub const PLANES: [GizmoAxis; 3] = [GizmoAxis::XY, GizmoAxis::XZ, GizmoAxis::YZ];
+
+    /// Returns true for the single axis handles
+    pub fn is_axis(&self) -> bool {
+        matches!(self, GizmoAxis::X | GizmoAxis::Y | GizmoAxis::Z)
+    }
+
+    /// The world space direction of an axis, or the normal of a plane
+    pub fn direction(&self) -> Vec3f {
+        match self {
+            GizmoAxis::X | GizmoAxis::YZ => Vec3f::unit_x(),
+            GizmoAxis::Y | GizmoAxis::XZ => Vec3f::unit_y(),
+            GizmoAxis::Z | GizmoAxis::XY => Vec3f::unit_z()
+        }
+    }
+
+    /// The 2 axes spanning a plane, or 2 perpendicular axes spanning the plane an axis rotates in
+    pub fn tangents(&self) -> (Vec3f, Vec3f) {
+        match self {
+            GizmoAxis::X | GizmoAxis::YZ => (Vec3f::unit_y(), Vec3f::unit_z()),
+            GizmoAxis::Y | GizmoAxis::XZ => (Vec3f::unit_z(), Vec3f::unit_x()),
+            GizmoAxis::Z | GizmoAxis::XY => (Vec3f::unit_x(), Vec3f::unit_y())
+        }
+    }
+
+    fn colour(&self) -> Vec4f {
+        match self {
+            GizmoAxis::X | GizmoAxis::YZ => vec4f(1.0, 0.2, 0.2, 1.0),
+            GizmoAxis::Y | GizmoAxis::XZ => vec4f(0.2, 1.0, 0.2, 1.0),
+            GizmoAxis::Z | GizmoAxis::XY => vec4f(0.2, 0.4, 1.0, 1.0)
+        }
+    }
+}
+
+/// Increments gizmo edits snap to, `None` disables snapping
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GizmoSnap {
+    pub translate: Option<f32>,
+    pub rotate_degrees: Option<f32>,
+    pub scale: Option<f32>
+}
+
+/// Rounds `value` to the nearest multiple of `step`, if there is a step
+pub fn snap(value: f32, step: Option<f32>) -> f32 {
+    match step {
+        Some(step) if step > 0.0 => (value / step).round() * step,
+        _ => value
+    }
+}
+
+/// Creates a world space ray through the screen space position `pos`, for a camera with `view_proj` which renders into
+/// the screen space rect at `viewport_pos` with `viewport_size`. Returns `None` if `pos` is outside of the viewport
+pub fn screen_ray(pos: Vec2f, viewport_pos: Vec2f, viewport_size: Vec2f, view_proj: &Mat4f) -> Option<Ray> {
+    if viewport_size.x <= 0.0 || viewport_size.y <= 0.0 {
+        return None;
+    }
+    let ndc_x = ((pos.x - viewport_pos.x) / viewport_size.x) * 2.0 - 1.0;
+    let ndc_y = 1.0 - ((pos.y - viewport_pos.y) / viewport_size.y) * 2.0;
+    if ndc_x.abs() > 1.0 || ndc_y.abs() > 1.0 {
+        return None;
+    }
+    let inv = view_proj.inverse();
+    let near = inv * vec4f(ndc_x, ndc_y, 0.0, 1.0);
+    let far = inv * vec4f(ndc_x, ndc_y, 1.0, 1.0);
+    let near = near.xyz() / near.w;
+    let far = far.xyz() / far.w;
+    Some(Ray {
+        origin: near,
+        direction: normalize(far - near)
+    })
+}
+
+/// Returns the distance along `ray` to where it enters the aabb, or 0 if the ray starts inside
+pub fn ray_vs_aabb(ray: &Ray, aabb_min: Vec3f, aabb_max: Vec3f) -> Option<f32> {
+    let mut t_min = 0.0;
+    let mut t_max = f32::MAX;
+    for i in 0..3 {
+        if ray.direction[i].abs() < f32::EPSILON {
+            if ray.origin[i] < aabb_min[i] || ray.origin[i] > aabb_max[i] {
+                return None;
+            }
+        }
+        else {
+            let t0 = (aabb_min[i] - ray.origin[i]) / ray.direction[i];
+            let t1 = (aabb_max[i] - ray.origin[i]) / ray.direction[i];
+            t_min = f32::max(t_min, f32::min(t0, t1));
+            t_max = f32::min(t_max, f32::max(t0, t1));
+            if t_min > t_max {
+                return None;
+            }
+        }
+    }
+    Some(t_min)
+}
+
+/// Returns the distance along `ray` to where it hits the plane through `point` with `normal`
+pub fn ray_vs_plane(ray: &Ray, point: Vec3f, normal: Vec3f) -> Option<f32> {
+    let denom = dot(ray.direction, normal);
+    if denom.abs() < f32::EPSILON {
+        return None;
+    }
+    let t = dot(point - ray.origin, normal) / denom;
+    if t >= 0.0 {
+        Some(t)
+    }
+    else {
+        None
+    }
+}
+
+/// Returns the parameters of the closest points between `ray` and the infinite line through `origin` along `axis`, as
+/// (distance along the axis, distance along the ray). Returns `None` if they are parallel
+pub fn ray_closest_to_axis(ray: &Ray, origin: Vec3f, axis: Vec3f) -> Option<(f32, f32)> {
+    let w = ray.origin - origin;
+    let dd = dot(ray.direction, ray.direction);
+    let aa = dot(axis, axis);
+    let da = dot(ray.direction, axis);
+    let dw = dot(ray.direction, w);
+    let aw = dot(axis, w);
+    let denom = dd * aa - da * da;
+    if denom.abs() < f32::EPSILON {
+        return None;
+    }
+    Some(((dd * aw - da * dw) / denom, (da * aw - aa * dw) / denom))
+}
+
+/// Returns the shortest distance between `ray` and the line segment from `start` to `end`, and the distance along the
+/// ray to the closest point
+pub fn ray_vs_segment(ray: &Ray, start: Vec3f, end: Vec3f) -> (f32, f32) {
+    let axis = end - start;
+    let s = ray_closest_to_axis(ray, start, axis).map(|(s, _)| s.clamp(0.0, 1.0)).unwrap_or(0.0);
+    let point = start + axis * s;
+    let t = f32::max(dot(point - ray.origin, ray.direction), 0.0);
+    (length(ray.at(t) - point), t)
+}
+
+/// Transforms the aabb of `extents_min` and `extents_max` by `world_matrix` and returns the world space aabb
+/// which contains it
+pub fn world_aabb(world_matrix: &Mat34f, extents_min: Vec3f, extents_max: Vec3f) -> (Vec3f, Vec3f) {
+    let mut aabb_min = Vec3f::max_value();
+    let mut aabb_max = -Vec3f::max_value();
+    for i in 0..8 {
+        let corner = vec3f(
+            if i & 1 == 0 { extents_min.x } else { extents_max.x },
+            if i & 2 == 0 { extents_min.y } else { extents_max.y },
+            if i & 4 == 0 { extents_min.z } else { extents_max.z }
+        );
+        (aabb_min, aabb_max) = min_max(*world_matrix * corner, (aabb_min, aabb_max));
+    }
+    (aabb_min, aabb_max)
+}
+
+/// Returns the item with the nearest aabb hit by `ray` and the distance to it
+pub fn pick_nearest<T>(ray: &Ray, aabbs: impl IntoIterator<Item = (T, Vec3f, Vec3f)>) -> Option<(T, f32)> {
+    let mut nearest : Option<(T, f32)> = None;
+    for (item, aabb_min, aabb_max) in aabbs {
+        if let Some(t) = ray_vs_aabb(ray, aabb_min, aabb_max) {
+            if nearest.as_ref().map(|(_, nearest_t)| t < *nearest_t).unwrap_or(true) {
+                nearest = Some((item, t));
+            }
+        }
+    }
+    nearest
+}
+
+/// Returns the handle of a gizmo at `origin` with `size` hit by `ray`, the nearest if more than one is hit
+pub fn hit_test(mode: GizmoMode, ray: &Ray, origin: Vec3f, size: f32) -> Option<GizmoAxis> {
+    let tolerance = size * GIZMO_HANDLE_TOLERANCE;
+    let mut nearest : Option<(GizmoAxis, f32)> = None;
+    let mut hit = |axis: GizmoAxis, t: f32| {
+        if nearest.map(|(_, nearest_t)| t < nearest_t).unwrap_or(true) {
+            nearest = Some((axis, t));
+        }
+    };
+
+    match mode {
+        GizmoMode::Translate | GizmoMode::Scale => {
+            for axis in GizmoAxis::AXES {
+                let (distance, t) = ray_vs_segment(ray, origin, origin + axis.direction() * size);
+                if distance < tolerance {
+                    hit(axis, t);
+                }
+            }
+        }
+        GizmoMode::Rotate => {
+            for axis in GizmoAxis::AXES {
+                if let Some(t) = ray_vs_plane(ray, origin, axis.direction()) {
+                    if (length(ray.at(t) - origin) - size).abs() < tolerance {
+                        hit(axis, t);
+                    }
+                }
+            }
+        }
+    }
+
+    // plane handles are inside the axes so they take priority when both are hit
+    if mode == GizmoMode::Translate {
+        let (handle_min, handle_max) = (GIZMO_PLANE_HANDLE_RANGE.0 * size, GIZMO_PLANE_HANDLE_RANGE.1 * size);
+        for plane in GizmoAxis::PLANES {
+            if let Some(t) = ray_vs_plane(ray, origin, plane.direction()) {
+                let (u, v) = plane.tangents();
+                let p = ray.at(t) - origin;
+                let (pu, pv) = (dot(p, u), dot(p, v));
+                if pu >= handle_min && pu <= handle_max && pv >= handle_min && pv <= handle_max {
+                    nearest = Some((plane, t));
+                }
+            }
+        }
+    }
+
+    nearest.map(|(axis, _)| axis)
+}
+
+/// Returns the point where `ray` meets the constraint of the `axis` handle of a gizmo at `origin`. Translating and
+/// scaling along an axis are constrained to the axis line, translating on a plane and rotating to the plane
+pub fn constraint_point(mode: GizmoMode, axis: GizmoAxis, ray: &Ray, origin: Vec3f) -> Option<Vec3f> {
+    if mode == GizmoMode::Rotate || !axis.is_axis() {
+        ray_vs_plane(ray, origin, axis.direction()).map(|t| ray.at(t))
+    }
+    else {
+        ray_closest_to_axis(ray, origin, axis.direction()).map(|(s, _)| origin + axis.direction() * s)
+    }
+}
+
+/// Returns the translation of a drag on `axis` from constraint point `start` to `current`, snapped to `step`
+pub fn translation_delta(axis: GizmoAxis, start: Vec3f, current: Vec3f, step: Option<f32>) -> Vec3f {
+    let delta = current - start;
+    if axis.is_axis() {
+        axis.direction() * snap(dot(delta, axis.direction()), step)
+    }
+    else {
+        vec3f(snap(delta.x, step), snap(delta.y, step), snap(delta.z, step))
+    }
+}
+
+/// Returns the angle in radians of a drag around `axis` of a gizmo at `origin` from constraint point `start` to
+/// `current`, snapped to `step_degrees`
+pub fn rotation_delta(axis: GizmoAxis, origin: Vec3f, start: Vec3f, current: Vec3f, step_degrees: Option<f32>) -> f32 {
+    let a = start - origin;
+    let b = current - origin;
+    let angle = f32::atan2(dot(cross(a, b), axis.direction()), dot(a, b));
+    f32::deg_to_rad(snap(f32::rad_to_deg(angle), step_degrees))
+}
+
+/// Returns a rotation of `angle` radians around `axis`
+pub fn axis_rotation(axis: GizmoAxis, angle: f32) -> Quatf {
+    let euler = axis.direction() * angle;
+    Quatf::from_euler_angles(euler.x, euler.y, euler.z)
+}
+
+/// Returns the scale factor of a drag along `axis` of a gizmo at `origin` from constraint point `start` to `current`
+pub fn scale_factor(axis: GizmoAxis, origin: Vec3f, start: Vec3f, current: Vec3f) -> f32 {
+    let s0 = dot(start - origin, axis.direction());
+    let s1 = dot(current - origin, axis.direction());
+    if s0.abs() < f32::EPSILON {
+        1.0
+    }
+    else {
+        s1 / s0
+    }
+}
+
+/// State captured when a gizmo drag begins, edits are applied relative to this each frame
+#[derive(Clone, Copy, Debug)]
+pub struct GizmoDrag {
+    pub mode: GizmoMode,
+    pub axis: GizmoAxis,
+    /// World space origin of the gizmo
+    pub origin: Vec3f,
+    /// The `constraint_point` where the drag started
+    pub start: Vec3f,
+    pub position: Vec3f,
+    pub rotation: Quatf,
+    pub scale: Vec3f
+}
+
+/// Manipulator gizmo for the selected entity, which is also selected by clicking on entities with `Extents` in the
+/// main viewport. Lives in the world as a resource so it can be drawn by render systems
+#[derive(Resource, Default)]
+pub struct Gizmo {
+    pub mode: GizmoMode,
+    pub snap: GizmoSnap,
+    pub selected: Option<Entity>,
+    /// World space origin of the gizmo this frame, `None` if nothing with a transform is selected
+    pub origin: Option<Vec3f>,
+    pub size: f32,
+    /// World space aabb of the selected entity if it has `Extents`
+    pub selected_aabb: Option<(Vec3f, Vec3f)>,
+    /// The handle under the mouse or being dragged
+    pub hot: Option<GizmoAxis>,
+    pub drag: Option<GizmoDrag>,
+    mouse_down: bool
+}
+
+fn set_axis(v: Vec3f, axis: GizmoAxis, value: f32) -> Vec3f {
+    match axis {
+        GizmoAxis::X => vec3f(value, v.y, v.z),
+        GizmoAxis::Y => vec3f(v.x, value, v.z),
+        _ => vec3f(v.x, v.y, value)
+    }
+}
+
+fn add_circle_3d<D: gfx::Device>(imdraw: &mut imdraw::ImDraw<D>, origin: Vec3f, axis: GizmoAxis, radius: f32, col: Vec4f) {
+    let (u, v) = axis.tangents();
+    let segments = 32;
+    let step = f32::two_pi() / segments as f32;
+    for i in 0..segments {
+        let a0 = i as f32 * step;
+        let a1 = (i + 1) as f32 * step;
+        imdraw.add_line_3d(
+            origin + (u * f32::cos(a0) + v * f32::sin(a0)) * radius,
+            origin + (u * f32::cos(a1) + v * f32::sin(a1)) * radius,
+            col
+        );
+    }
+}
+
+impl Gizmo {
+    fn handle_colour(&self, axis: GizmoAxis) -> Vec4f {
+        if self.hot == Some(axis) {
+            vec4f(1.0, 1.0, 0.0, 1.0)
+        }
+        else {
+            axis.colour()
+        }
+    }
+
+    /// Adds lines for the gizmo and the aabb of the selected entity to `imdraw`
+    pub fn draw<D: gfx::Device>(&self, imdraw: &mut imdraw::ImDraw<D>) {
+        if let Some((aabb_min, aabb_max)) = self.selected_aabb {
+            imdraw.add_aabb_3d(aabb_min, aabb_max, vec4f(1.0, 1.0, 0.0, 1.0));
+        }
+
+        if let Some(origin) = self.origin {
+            let size = self.size;
+            match self.mode {
+                GizmoMode::Translate => {
+                    for axis in GizmoAxis::AXES {
+                        imdraw.add_line_3d(origin, origin + axis.direction() * size, self.handle_colour(axis));
+                    }
+                    let (handle_min, handle_max) = (GIZMO_PLANE_HANDLE_RANGE.0 * size, GIZMO_PLANE_HANDLE_RANGE.1 * size);
+                    for plane in GizmoAxis::PLANES {
+                        let (u, v) = plane.tangents();
+                        let corners = [
+                            origin + u * handle_min + v * handle_min,
+                            origin + u * handle_max + v * handle_min,
+                            origin + u * handle_max + v * handle_max,
+                            origin + u * handle_min + v * handle_max
+                        ];
+                        for i in 0..4 {
+                            imdraw.add_line_3d(corners[i], corners[(i + 1) % 4], self.handle_colour(plane));
+                        }
+                    }
+                }
+                GizmoMode::Rotate => {
+                    for axis in GizmoAxis::AXES {
+                        add_circle_3d(imdraw, origin, axis, size, self.handle_colour(axis));
+                    }
+                }
+                GizmoMode::Scale => {
+                    let handle = splat3f(size * GIZMO_HANDLE_TOLERANCE);
+                    for axis in GizmoAxis::AXES {
+                        let end = origin + axis.direction() * size;
+                        imdraw.add_line_3d(origin, end, self.handle_colour(axis));
+                        imdraw.add_aabb_3d(end - handle, end + handle, self.handle_colour(axis));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds widgets to select the gizmo mode and snapping to the current imgui window
+    pub fn ui<D: gfx::Device, A: os::App>(&mut self, imgui: &mut imgui::ImGui<D, A>) where D::RenderPipeline: gfx::Pipeline {
+        let modes = vec!["Translate".to_string(), "Rotate".to_string(), "Scale".to_string()];
+        let (_, selected) = imgui.combo_list("Gizmo", &modes, &format!("{:?}", self.mode));
+        self.mode = match selected.as_str() {
+            "Rotate" => GizmoMode::Rotate,
+            "Scale" => GizmoMode::Scale,
+            _ => GizmoMode::Translate
+        };
+
+        let snaps = [
+            ("Snap Translate", &mut self.snap.translate, 1.0),
+            ("Snap Rotate", &mut self.snap.rotate_degrees, 15.0),
+            ("Snap Scale", &mut self.snap.scale, 0.1)
+        ];
+        for (label, step, default_step) in snaps {
+            let mut enabled = step.is_some();
+            if imgui.checkbox(&format!("##{}", label), &mut enabled) {
+                *step = if enabled { Some(default_step) } else { None };
+            }
+            imgui.same_line();
+            if let Some(step) = step {
+                imgui.input_float(label, step);
+            }
+            else {
+                imgui.text(label);
+            }
+        }
+    }
+}
+
+/// The optional transform components of an entity the gizmo writes to
+type GizmoTransform<'a> = (Option<&'a mut Position>, Option<&'a mut Rotation>, Option<&'a mut Scale>, Option<&'a Parent>);
+
+/// Picks entities with `Extents` under the mouse in the main viewport and drags the handles of the `Gizmo` of the
+/// selected entity, writing the result to its `Position`, `Rotation` and `Scale`. Gizmos are only active with a
+/// `CameraType::Editor` main camera, which leaves the left mouse button free when alt or shift are not held.
+/// Translations are in world space and converted to the parents space, rotations are around the parents axes
+pub fn update_gizmo(
+    app: Res<AppRes>,
+    viewport: Res<ViewportInfo>,
+    mut gizmo: ResMut<Gizmo>,
+    camera_query: Query<(&Position, &Camera, &ViewProjectionMatrix), With<MainCamera>>,
+    pick_query: Query<(Entity, &WorldMatrix, &Extents)>,
+    matrix_query: Query<&WorldMatrix>,
+    mut transform_query: Query<GizmoTransform, Without<MainCamera>>) {
+    let gizmo = &mut *gizmo;
+
+    gizmo.origin = gizmo.selected.and_then(|entity| {
+        matrix_query.get(entity).ok().map(|world_matrix| world_matrix.get_column(3))
+            .or_else(|| transform_query.get(entity).ok().and_then(|(position, _, _, _)| position.map(|p| p.0)))
+    });
+    gizmo.selected_aabb = gizmo.selected.and_then(|entity| pick_query.get(entity).ok()).map(|(_, world_matrix, extents)| {
+        world_aabb(&world_matrix.0, extents.aabb_min, extents.aabb_max)
+    });
+
+    let (camera_pos, camera, view_proj) = if let Ok(camera) = camera_query.get_single() {
+        camera
+    }
+    else {
+        return;
+    };
+    if let Some(origin) = gizmo.origin {
+        gizmo.size = dist(camera_pos.0, origin) * GIZMO_SCREEN_SCALE;
+    }
+
+    let (_, enable_mouse) = app.get_input_enabled();
+    let camera_keys = app.is_sys_key_down(os::SysKey::Alt) || app.is_sys_key_down(os::SysKey::Shift);
+    let active = enable_mouse && !camera_keys && matches!(camera.camera_type, CameraType::Editor);
+    let mouse = app.get_mouse_pos();
+    let ray = screen_ray(
+        vec2f(mouse.x as f32, mouse.y as f32),
+        vec2f(viewport.pos.0, viewport.pos.1),
+        vec2f(viewport.size.0, viewport.size.1),
+        &view_proj.0
+    ).filter(|_| active);
+
+    let down = app.get_mouse_buttons()[os::MouseButton::Left as usize];
+    let pressed = down && !gizmo.mouse_down;
+    gizmo.mouse_down = down;
+    if !down {
+        gizmo.drag = None;
+    }
+
+    if let (Some(drag), Some(ray), Some(selected)) = (gizmo.drag, ray, gizmo.selected) {
+        // continue dragging
+        let current = constraint_point(drag.mode, drag.axis, &ray, drag.origin);
+        if let (Some(current), Ok((position, rotation, scale, parent))) = (current, transform_query.get_mut(selected)) {
+            match drag.mode {
+                GizmoMode::Translate => {
+                    if let Some(mut position) = position {
+                        let target = drag.origin + translation_delta(drag.axis, drag.start, current, gizmo.snap.translate);
+                        let parent_matrix = parent.and_then(|parent| matrix_query.get(parent.0).ok());
+                        position.0 = if let Some(parent_matrix) = parent_matrix {
+                            parent_matrix.0.inverse() * target
+                        }
+                        else {
+                            target
+                        };
+                    }
+                }
+                GizmoMode::Rotate => {
+                    if let Some(mut rotation) = rotation {
+                        let angle = rotation_delta(drag.axis, drag.origin, drag.start, current, gizmo.snap.rotate_degrees);
+                        rotation.0 = axis_rotation(drag.axis, angle) * drag.rotation;
+                    }
+                }
+                GizmoMode::Scale => {
+                    if let Some(mut scale) = scale {
+                        let axis_scale = drag.scale[drag.axis as usize] * scale_factor(drag.axis, drag.origin, drag.start, current);
+                        scale.0 = set_axis(drag.scale, drag.axis, snap(axis_scale, gizmo.snap.scale));
+                    }
+                }
+            }
+        }
+    }
+    else if gizmo.drag.is_none() {
+        gizmo.hot = match (ray, gizmo.origin) {
+            (Some(ray), Some(origin)) => hit_test(gizmo.mode, &ray, origin, gizmo.size),
+            _ => None
+        };
+
+        if let (true, Some(ray)) = (pressed, ray) {
+            if let (Some(axis), Some(origin), Some(selected)) = (gizmo.hot, gizmo.origin, gizmo.selected) {
+                // start dragging a handle
+                if let (Ok((position, rotation, scale, _)), Some(start)) = (transform_query.get(selected), constraint_point(gizmo.mode, axis, &ray, origin)) {
+                    gizmo.drag = Some(GizmoDrag {
+                        mode: gizmo.mode,
+                        axis,
+                        origin,
+                        start,
+                        position: position.map(|p| p.0).unwrap_or(Vec3f::zero()),
+                        rotation: rotation.map(|r| r.0).unwrap_or(Quatf::identity()),
+                        scale: scale.map(|s| s.0).unwrap_or(Vec3f::one())
+                    });
+                }
+            }
+            else {
+                // select the entity under the mouse, or deselect when clicking empty space
+                gizmo.selected = pick_nearest(&ray, pick_query.iter().map(|(entity, world_matrix, extents)| {
+                    let (aabb_min, aabb_max) = world_aabb(&world_matrix.0, extents.aabb_min, extents.aabb_max);
+                    (entity, aabb_min, aabb_max)
+                })).map(|(entity, _)| entity);
+            }
+        }
+    }
+}
@@ -0,0 +1,1043 @@
+use crate::gfx;
+use crate::pmfx;
+use crate::image;
+use crate::primitives;
+use crate::primitives::Vertex3D;
+
+use maths_rs::prelude::*;
+use serde::Deserialize;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Magic number at the start of a binary glTF (.glb) file 'glTF'
+const GLB_MAGIC: u32 = 0x46546c67;
+
+/// Chunk type for the JSON chunk of a .glb
+const GLB_CHUNK_JSON: u32 = 0x4e4f534a;
+
+/// Chunk type for the binary buffer chunk of a .glb
+const GLB_CHUNK_BIN: u32 = 0x004e4942;
+
+/// Primitive topology modes from the glTF spec, only triangle topologies are supported
+const MODE_TRIANGLES: u32 = 4;
+const MODE_TRIANGLE_STRIP: u32 = 5;
+const MODE_TRIANGLE_FAN: u32 = 6;
+
+/// Maximum number of elements in an accessor without a buffer view, these accessors are zero initialised so their
+/// count is not bounded by the size of a buffer
+const MAX_ZERO_ACCESSOR_COUNT: usize = 1 << 24;
+
+//
+// glTF json schema, only the subset of the spec required to load meshes, materials and the node hierarchy
+//
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct Root {
+    asset: Asset,
+    scene: Option<usize>,
+    scenes: Vec<Scene>,
+    nodes: Vec<Node>,
+    meshes: Vec<Mesh>,
+    accessors: Vec<Accessor>,
+    buffer_views: Vec<BufferView>,
+    buffers: Vec<Buffer>,
+    materials: Vec<Material>,
+    textures: Vec<Texture>,
+    images: Vec<Image>,
+    extensions_required: Vec<String>
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Asset {
+    version: String
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Scene {
+    nodes: Vec<usize>
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Node {
+    name: Option<String>,
+    children: Vec<usize>,
+    mesh: Option<usize>,
+    matrix: Option<[f32; 16]>,
+    translation: Option<[f32; 3]>,
+    rotation: Option<[f32; 4]>,
+    scale: Option<[f32; 3]>
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Mesh {
+    name: Option<String>,
+    primitives: Vec<Primitive>
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Primitive {
+    attributes: HashMap<String, usize>,
+    indices: Option<usize>,
+    material: Option<usize>,
+    mode: Option<u32>
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct Accessor {
+    buffer_view: Option<usize>,
+    byte_offset: usize,
+    component_type: u32,
+    normalized: bool,
+    count: usize,
+    #[serde(rename = "type")]
+    element_type: String,
+    sparse: Option<serde_json::Value>
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct BufferView {
+    buffer: usize,
+    byte_offset: usize,
+    byte_length: usize,
+    byte_stride: Option<usize>
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct Buffer {
+    uri: Option<String>,
+    byte_length: usize
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct Material {
+    name: Option<String>,
+    pbr_metallic_roughness: Option<PbrMetallicRoughness>,
+    normal_texture: Option<TextureRef>
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct PbrMetallicRoughness {
+    base_color_factor: Option<[f32; 4]>,
+    base_color_texture: Option<TextureRef>,
+    metallic_factor: Option<f32>,
+    roughness_factor: Option<f32>,
+    metallic_roughness_texture: Option<TextureRef>
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct TextureRef {
+    index: usize
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Texture {
+    source: Option<usize>
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct Image {
+    uri: Option<String>,
+    buffer_view: Option<usize>
+}
+
+/// A parsed glTF 2.0 document with all of its buffers resolved (embedded data uris, .glb binary chunk or external files).
+/// Use the `get_*` functions to extract cpu side data, none of which requires a `gfx::Device`.
+pub struct Gltf {
+    json: Root,
+    buffers: Vec<Vec<u8>>,
+    base_dir: PathBuf
+}
+
+/// A single drawable primitive of a glTF mesh, converted into hotline's `Vertex3D` layout in left handed coordinates.
+pub struct PrimitiveData {
+    /// Vertices with generated normals (flat) and tangents (from texcoords) when they are not supplied in the file
+    pub vertices: Vec<Vertex3D>,
+    /// Triangle list indices, strips and fans are converted to lists
+    pub indices: Vec<usize>,
+    /// Index into `Gltf::get_materials`
+    pub material: Option<usize>
+}
+
+/// A glTF mesh, containing 1 or more primitives which each have their own material
+pub struct MeshData {
+    pub name: String,
+    pub primitives: Vec<PrimitiveData>
+}
+
+/// Metallic roughness material parameters, texture members are indices into the glTF `images` array
+pub struct MaterialData {
+    pub name: String,
+    pub base_colour: Vec4f,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub albedo: Option<usize>,
+    pub normal: Option<usize>,
+    pub metallic_roughness: Option<usize>
+}
+
+/// A node from the default scene with its local transform decomposed into position, rotation and scale.
+pub struct NodeData {
+    pub name: String,
+    /// Index of the parent node within the array returned from `Gltf::get_nodes`, parents always come before their children
+    pub parent: Option<usize>,
+    /// Index into `Gltf::get_meshes`
+    pub mesh: Option<usize>,
+    pub position: Vec3f,
+    pub rotation: Quatf,
+    pub scale: Vec3f,
+    /// The concatenated parent to child transform of the node
+    pub world_matrix: Mat34f
+}
+
+/// Loads a .gltf or .glb file, external buffers and images are located relative to `filepath`
+pub fn load_from_file(filepath: &str) -> Result<Gltf, super::Error> {
+    let data = super::read_data(filepath)?;
+    let base_dir = Path::new(filepath).parent().map(|p| p.to_path_buf()).unwrap_or_default();
+    load_from_memory(&data, &base_dir)
+}
+
+/// Loads a glTF document from memory, the data can either be .gltf json or .glb binary and any external
+/// buffers or images are located relative to `base_dir`
+pub fn load_from_memory(data: &[u8], base_dir: &Path) -> Result<Gltf, super::Error> {
+    let (json, bin) = if read_u32(data, 0) == Some(GLB_MAGIC) {
+        parse_glb(data)?
+    }
+    else {
+        (data, None)
+    };
+
+    let json : Root = serde_json::from_slice(json)?;
+    if !json.asset.version.starts_with('2') {
+        return Err(super::Error {
+            msg: format!("hotline_rs::gltf:: unsupported glTF version '{}'", json.asset.version)
+        });
+    }
+
+    if !json.extensions_required.is_empty() {
+        return Err(super::Error {
+            msg: format!("hotline_rs::gltf:: unsupported required extensions {:?}", json.extensions_required)
+        });
+    }
+
+    let mut bin = bin;
+    let mut buffers = Vec::new();
+    for (i, buffer) in json.buffers.iter().enumerate() {
+        let data = if let Some(uri) = &buffer.uri {
+            load_uri(uri, base_dir)?
+        }
+        else if i == 0 && bin.is_some() {
+            bin.take().unwrap().to_vec()
+        }
+        else {
+            return Err(super::Error {
+                msg: format!("hotline_rs::gltf:: buffer {} has no uri and there is no .glb binary chunk", i)
+            });
+        };
+
+        if data.len() < buffer.byte_length {
+            return Err(super::Error {
+                msg: format!("hotline_rs::gltf:: buffer {} is {} bytes but expected {}", i, data.len(), buffer.byte_length)
+            });
+        }
+        buffers.push(data);
+    }
+
+    Ok(Gltf {
+        json,
+        buffers,
+        base_dir: base_dir.to_path_buf()
+    })
+}
+
+/// Splits a .glb into its json and optional binary chunk
+fn parse_glb(data: &[u8]) -> Result<(&[u8], Option<&[u8]>), super::Error> {
+    let version = read_u32(data, 4).unwrap_or(0);
+    if version != 2 {
+        return Err(super::Error {
+            msg: format!("hotline_rs::gltf:: unsupported .glb container version {}", version)
+        });
+    }
+
+    let length = (read_u32(data, 8).unwrap_or(0) as usize).min(data.len());
+    let mut json = None;
+    let mut bin = None;
+    let mut offset = 12;
+    while offset + 8 <= length {
+        let chunk_length = read_u32(data, offset).unwrap() as usize;
+        let chunk_type = read_u32(data, offset + 4).unwrap();
+        let start = offset + 8;
+        let end = start + chunk_length;
+        if end > length {
+            return Err(super::Error {
+                msg: "hotline_rs::gltf:: .glb chunk extends past the end of the file".to_string()
+            });
+        }
+        match chunk_type {
+            GLB_CHUNK_JSON => json = Some(&data[start..end]),
+            GLB_CHUNK_BIN => bin = Some(&data[start..end]),
+            _ => () // unknown chunks must be ignored
+        }
+        // chunks are 4 byte aligned
+        offset = end + ((4 - (chunk_length % 4)) % 4);
+    }
+
+    if let Some(json) = json {
+        Ok((json, bin))
+    }
+    else {
+        Err(super::Error {
+            msg: "hotline_rs::gltf:: .glb does not contain a json chunk".to_string()
+        })
+    }
+}
+
+/// Reads a little endian u32 at byte `offset` returning None if out of bounds
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Loads a uri which can be a base64 encoded data uri or a file relative to `base_dir`
+fn load_uri(uri: &str, base_dir: &Path) -> Result<Vec<u8>, super::Error> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        if let Some(pos) = data.find(";base64,") {
+            decode_base64(&data[pos + 8..])
+        }
+        else {
+            Err(super::Error {
+                msg: "hotline_rs::gltf:: only base64 encoded data uris are supported".to_string()
+            })
+        }
+    }
+    else {
+        super::read_data(base_dir.join(decode_percent(uri)))
+    }
+}
+
+/// Decodes %xx escapes in relative uris
+fn decode_percent(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Ok(v) = u8::from_str_radix(&uri[i + 1..i + 3], 16) {
+                out.push(v);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Decodes standard base64 ignoring whitespace, padding is optional
+fn decode_base64(text: &str) -> Result<Vec<u8>, super::Error> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for c in text.bytes() {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            b' ' | b'\n' | b'\r' | b'\t' => continue,
+            _ => {
+                return Err(super::Error {
+                    msg: format!("hotline_rs::gltf:: invalid base64 character '{}'", c as char)
+                });
+            }
+        };
+        acc = (acc << 6) | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Ok(out)
+}
+
+/// Returns the number of components for an accessor element type
+fn element_components(element_type: &str) -> Option<usize> {
+    match element_type {
+        "SCALAR" => Some(1),
+        "VEC2" => Some(2),
+        "VEC3" => Some(3),
+        "VEC4" => Some(4),
+        "MAT2" => Some(4),
+        "MAT3" => Some(9),
+        "MAT4" => Some(16),
+        _ => None
+    }
+}
+
+/// Returns the size in bytes of an accessor component type
+fn component_size(component_type: u32) -> Option<usize> {
+    match component_type {
+        5120 | 5121 => Some(1),
+        5122 | 5123 => Some(2),
+        5125 | 5126 => Some(4),
+        _ => None
+    }
+}
+
+/// Reads a single component at `bytes` converting it to f32, normalising integer types if required
+fn read_component(bytes: &[u8], component_type: u32, normalized: bool) -> f32 {
+    match component_type {
+        5120 => {
+            let v = bytes[0] as i8 as f32;
+            if normalized { (v / 127.0).max(-1.0) } else { v }
+        }
+        5121 => {
+            let v = bytes[0] as f32;
+            if normalized { v / 255.0 } else { v }
+        }
+        5122 => {
+            let v = i16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+            if normalized { (v / 32767.0).max(-1.0) } else { v }
+        }
+        5123 => {
+            let v = u16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+            if normalized { v / 65535.0 } else { v }
+        }
+        5125 => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32,
+        _ => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+}
+
+/// Converts a rotation matrix given as 3 rows into a quaternion (x, y, z, w)
+fn quat_from_rows(r: [Vec3f; 3]) -> Vec4f {
+    let trace = r[0].x + r[1].y + r[2].z;
+    if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        vec4f((r[2].y - r[1].z) / s, (r[0].z - r[2].x) / s, (r[1].x - r[0].y) / s, 0.25 * s)
+    }
+    else if r[0].x > r[1].y && r[0].x > r[2].z {
+        let s = (1.0 + r[0].x - r[1].y - r[2].z).sqrt() * 2.0;
+        vec4f(0.25 * s, (r[0].y + r[1].x) / s, (r[0].z + r[2].x) / s, (r[2].y - r[1].z) / s)
+    }
+    else if r[1].y > r[2].z {
+        let s = (1.0 + r[1].y - r[0].x - r[2].z).sqrt() * 2.0;
+        vec4f((r[0].y + r[1].x) / s, 0.25 * s, (r[1].z + r[2].y) / s, (r[0].z - r[2].x) / s)
+    }
+    else {
+        let s = (1.0 + r[2].z - r[0].x - r[1].y).sqrt() * 2.0;
+        vec4f((r[0].z + r[2].x) / s, (r[1].z + r[2].y) / s, 0.25 * s, (r[1].x - r[0].y) / s)
+    }
+}
+
+/// Decomposes a column major glTF matrix into translation, rotation quaternion (x, y, z, w) and scale
+fn decompose_matrix(m: &[f32; 16]) -> (Vec3f, Vec4f, Vec3f) {
+    let translation = vec3f(m[12], m[13], m[14]);
+    let c0 = vec3f(m[0], m[1], m[2]);
+    let c1 = vec3f(m[4], m[5], m[6]);
+    let c2 = vec3f(m[8], m[9], m[10]);
+    let mut scale = vec3f(mag(c0), mag(c1), mag(c2));
+    // negative determinant means the matrix contains a reflection, fold it into the x scale
+    if dot(cross(c0, c1), c2) < 0.0 {
+        scale.x = -scale.x;
+    }
+    let safe = |v: Vec3f, s: f32| if s.abs() > f32::EPSILON { v / s } else { v };
+    let c0 = safe(c0, scale.x);
+    let c1 = safe(c1, scale.y);
+    let c2 = safe(c2, scale.z);
+    let rows = [
+        vec3f(c0.x, c1.x, c2.x),
+        vec3f(c0.y, c1.y, c2.y),
+        vec3f(c0.z, c1.z, c2.z)
+    ];
+    (translation, quat_from_rows(rows), scale)
+}
+
+/// glTF is right handed with +z towards the viewer, hotline is left handed so the z axis is mirrored
+fn to_left_handed(v: Vec3f) -> Vec3f {
+    vec3f(v.x, v.y, -v.z)
+}
+
+/// Mirrors a right handed rotation quaternion (x, y, z, w) in the z axis
+fn to_left_handed_quat(q: Vec4f) -> Quatf {
+    Quatf::from(vec4f(-q.x, -q.y, q.z, q.w))
+}
+
+impl Gltf {
+    /// Returns the directory used to resolve external buffers and images
+    pub fn get_base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    /// Returns the bytes of accessor `index` as a flat array of f32 components along with the number of components per-element
+    fn read_accessor(&self, index: usize) -> Result<(Vec<f32>, usize), super::Error> {
+        let accessor = self.json.accessors.get(index).ok_or(super::Error {
+            msg: format!("hotline_rs::gltf:: accessor {} is out of range", index)
+        })?;
+
+        if accessor.sparse.is_some() {
+            return Err(super::Error {
+                msg: format!("hotline_rs::gltf:: accessor {} is sparse which is not supported", index)
+            });
+        }
+
+        let components = element_components(&accessor.element_type).ok_or(super::Error {
+            msg: format!("hotline_rs::gltf:: accessor {} has unknown type '{}'", index, accessor.element_type)
+        })?;
+
+        let size = component_size(accessor.component_type).ok_or(super::Error {
+            msg: format!("hotline_rs::gltf:: accessor {} has unknown component type {}", index, accessor.component_type)
+        })?;
+
+        // accessors without a buffer view are initialised to zero
+        let view = if let Some(view) = accessor.buffer_view {
+            view
+        }
+        else if accessor.count <= MAX_ZERO_ACCESSOR_COUNT {
+            return Ok((vec![0.0; accessor.count * components], components));
+        }
+        else {
+            return Err(super::Error {
+                msg: format!("hotline_rs::gltf:: accessor {} has count {} which exceeds the maximum of {}",
+                    index, accessor.count, MAX_ZERO_ACCESSOR_COUNT)
+            });
+        };
+
+        let view = self.json.buffer_views.get(view).ok_or(super::Error {
+            msg: format!("hotline_rs::gltf:: accessor {} references an invalid buffer view", index)
+        })?;
+
+        let buffer = self.buffers.get(view.buffer).ok_or(super::Error {
+            msg: format!("hotline_rs::gltf:: buffer view references invalid buffer {}", view.buffer)
+        })?;
+
+        let element_size = size * components;
+        let stride = view.byte_stride.unwrap_or(element_size).max(element_size);
+        let start = view.byte_offset.saturating_add(accessor.byte_offset);
+        if accessor.count > 0 {
+            // counts from the file are untrusted, so overflowing is the same as reading past the end
+            let end = stride.checked_mul(accessor.count - 1)
+                .and_then(|offset| offset.checked_add(start))
+                .and_then(|offset| offset.checked_add(element_size));
+            let view_end = view.byte_offset.saturating_add(view.byte_length);
+            if end.map(|end| end > view_end || end > buffer.len()).unwrap_or(true) {
+                return Err(super::Error {
+                    msg: format!("hotline_rs::gltf:: accessor {} reads past the end of its buffer view", index)
+                });
+            }
+        }
+
+        let mut out = Vec::with_capacity(accessor.count * components);
+        for i in 0..accessor.count {
+            let element = start + i * stride;
+            for c in 0..components {
+                let offset = element + c * size;
+                out.push(read_component(&buffer[offset..offset + size], accessor.component_type, accessor.normalized));
+            }
+        }
+
+        Ok((out, components))
+    }
+
+    /// Reads an accessor and checks it has the expected number of components
+    fn read_attribute(&self, index: usize, expected: usize, name: &str) -> Result<Vec<f32>, super::Error> {
+        let (data, components) = self.read_accessor(index)?;
+        if components < expected {
+            return Err(super::Error {
+                msg: format!("hotline_rs::gltf:: attribute {} has {} components but expected {}", name, components, expected)
+            });
+        }
+        Ok(data.chunks(components).flat_map(|c| c[..expected].to_vec()).collect())
+    }
+
+    /// Loads a single primitive into hotline vertex and index layout
+    fn get_primitive(&self, primitive: &Primitive) -> Result<PrimitiveData, super::Error> {
+        let positions = self.read_attribute(*primitive.attributes.get("POSITION").ok_or(super::Error {
+            msg: "hotline_rs::gltf:: primitive has no POSITION attribute".to_string()
+        })?, 3, "POSITION")?;
+
+        let count = positions.len() / 3;
+        let attribute = |name: &str, components: usize| -> Result<Option<Vec<f32>>, super::Error> {
+            if let Some(index) = primitive.attributes.get(name) {
+                let data = self.read_attribute(*index, components, name)?;
+                if data.len() / components != count {
+                    return Err(super::Error {
+                        msg: format!("hotline_rs::gltf:: attribute {} count does not match POSITION", name)
+                    });
+                }
+                Ok(Some(data))
+            }
+            else {
+                Ok(None)
+            }
+        };
+
+        let normals = attribute("NORMAL", 3)?;
+        let texcoords = attribute("TEXCOORD_0", 2)?;
+        let tangents = attribute("TANGENT", 4)?;
+
+        // indices, or implicit sequential indices
+        let indices : Vec<usize> = if let Some(index) = primitive.indices {
+            let (data, _) = self.read_accessor(index)?;
+            data.iter().map(|i| *i as usize).collect()
+        }
+        else {
+            (0..count).collect()
+        };
+
+        if let Some(i) = indices.iter().find(|i| **i >= count) {
+            return Err(super::Error {
+                msg: format!("hotline_rs::gltf:: index {} is out of range of {} vertices", i, count)
+            });
+        }
+
+        // convert topology to triangle lists
+        let mode = primitive.mode.unwrap_or(MODE_TRIANGLES);
+        let mut triangles = Vec::new();
+        match mode {
+            MODE_TRIANGLES => {
+                triangles.extend(indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]));
+            }
+            MODE_TRIANGLE_STRIP => {
+                for i in 2..indices.len() {
+                    if i % 2 == 0 {
+                        triangles.push([indices[i - 2], indices[i - 1], indices[i]]);
+                    }
+                    else {
+                        triangles.push([indices[i - 1], indices[i - 2], indices[i]]);
+                    }
+                }
+            }
+            MODE_TRIANGLE_FAN => {
+                for i in 2..indices.len() {
+                    triangles.push([indices[i - 1], indices[i], indices[0]]);
+                }
+            }
+            _ => {
+                return Err(super::Error {
+                    msg: format!("hotline_rs::gltf:: unsupported primitive mode {}, only triangles are supported", mode)
+                });
+            }
+        }
+
+        let mut vertices = (0..count).map(|i| {
+            Vertex3D {
+                position: vec3f(positions[i * 3], positions[i * 3 + 1], positions[i * 3 + 2]),
+                texcoord: texcoords.as_ref().map(|t| vec2f(t[i * 2], t[i * 2 + 1])).unwrap_or(Vec2f::zero()),
+                normal: normals.as_ref().map(|n| vec3f(n[i * 3], n[i * 3 + 1], n[i * 3 + 2])).unwrap_or(Vec3f::zero()),
+                tangent: Vec3f::zero(),
+                bitangent: Vec3f::zero()
+            }
+        }).collect::<Vec<Vertex3D>>();
+
+        // flat normals are required when normals are not supplied, which means vertices cannot be shared
+        if normals.is_none() {
+            let mut flat = Vec::with_capacity(triangles.len() * 3);
+            for t in &mut triangles {
+                let p = [vertices[t[0]].position, vertices[t[1]].position, vertices[t[2]].position];
+                let n = cross(p[1] - p[0], p[2] - p[0]);
+                let n = if mag(n) > 0.0 { normalize(n) } else { vec3f(0.0, 1.0, 0.0) };
+                for i in t.iter_mut() {
+                    let mut v = vertices[*i].clone();
+                    v.normal = n;
+                    *i = flat.len();
+                    flat.push(v);
+                }
+            }
+            vertices = flat;
+        }
+
+        if let (Some(tangents), true) = (&tangents, normals.is_some()) {
+            for (i, v) in vertices.iter_mut().enumerate() {
+                let t = vec3f(tangents[i * 4], tangents[i * 4 + 1], tangents[i * 4 + 2]);
+                v.tangent = t;
+                v.bitangent = cross(v.normal, t) * tangents[i * 4 + 3];
+            }
+        }
+        else {
+            let indices = triangles.iter().flatten().copied().collect::<Vec<usize>>();
+            primitives::calculate_tangents(&mut vertices, &indices);
+        }
+
+        // mirror into left handed space, which also requires reversing the winding order
+        for v in &mut vertices {
+            v.position = to_left_handed(v.position);
+            v.normal = to_left_handed(v.normal);
+            v.tangent = to_left_handed(v.tangent);
+            v.bitangent = to_left_handed(v.bitangent);
+        }
+
+        Ok(PrimitiveData {
+            vertices,
+            indices: triangles.iter().flat_map(|t| [t[0], t[2], t[1]]).collect(),
+            material: primitive.material
+        })
+    }
+
+    /// Returns all meshes in the document with vertex data in hotline `Vertex3D` layout
+    pub fn get_meshes(&self) -> Result<Vec<MeshData>, super::Error> {
+        let mut meshes = Vec::new();
+        for (i, mesh) in self.json.meshes.iter().enumerate() {
+            let mut primitives = Vec::new();
+            for primitive in &mesh.primitives {
+                primitives.push(self.get_primitive(primitive)?);
+            }
+            meshes.push(MeshData {
+                name: mesh.name.clone().unwrap_or(format!("mesh_{}", i)),
+                primitives
+            });
+        }
+        Ok(meshes)
+    }
+
+    /// Returns the image index from a texture reference
+    fn get_texture_image(&self, texture: &Option<TextureRef>) -> Option<usize> {
+        texture.as_ref()
+            .and_then(|t| self.json.textures.get(t.index))
+            .and_then(|t| t.source)
+    }
+
+    /// Returns all materials in the document, missing parameters are filled with the spec defaults
+    pub fn get_materials(&self) -> Vec<MaterialData> {
+        let default_pbr = PbrMetallicRoughness::default();
+        self.json.materials.iter().enumerate().map(|(i, material)| {
+            let pbr = material.pbr_metallic_roughness.as_ref().unwrap_or(&default_pbr);
+            let base_colour = pbr.base_color_factor.unwrap_or([1.0, 1.0, 1.0, 1.0]);
+            MaterialData {
+                name: material.name.clone().unwrap_or(format!("material_{}", i)),
+                base_colour: vec4f(base_colour[0], base_colour[1], base_colour[2], base_colour[3]),
+                metallic: pbr.metallic_factor.unwrap_or(1.0),
+                roughness: pbr.roughness_factor.unwrap_or(1.0),
+                albedo: self.get_texture_image(&pbr.base_color_texture),
+                normal: self.get_texture_image(&material.normal_texture),
+                metallic_roughness: self.get_texture_image(&pbr.metallic_roughness_texture)
+            }
+        }).collect()
+    }
+
+    /// Returns the nodes of the default scene (or all root nodes if the document has no scenes) ordered so that
+    /// parents always come before their children
+    pub fn get_nodes(&self) -> Vec<NodeData> {
+        let roots = if let Some(scene) = self.json.scenes.get(self.json.scene.unwrap_or(0)) {
+            scene.nodes.clone()
+        }
+        else {
+            let mut is_child = vec![false; self.json.nodes.len()];
+            for node in &self.json.nodes {
+                for child in &node.children {
+                    if let Some(c) = is_child.get_mut(*child) {
+                        *c = true;
+                    }
+                }
+            }
+            (0..self.json.nodes.len()).filter(|i| !is_child[*i]).collect()
+        };
+
+        let mut visited = vec![false; self.json.nodes.len()];
+        let mut nodes : Vec<NodeData> = Vec::new();
+        let mut stack : Vec<(usize, Option<usize>)> = roots.iter().rev().map(|n| (*n, None)).collect();
+        while let Some((index, parent)) = stack.pop() {
+            if index >= visited.len() || visited[index] {
+                continue;
+            }
+            visited[index] = true;
+
+            let node = &self.json.nodes[index];
+            let (translation, rotation, scale) = if let Some(matrix) = &node.matrix {
+                decompose_matrix(matrix)
+            }
+            else {
+                let t = node.translation.unwrap_or([0.0, 0.0, 0.0]);
+                let r = node.rotation.unwrap_or([0.0, 0.0, 0.0, 1.0]);
+                let s = node.scale.unwrap_or([1.0, 1.0, 1.0]);
+                (vec3f(t[0], t[1], t[2]), vec4f(r[0], r[1], r[2], r[3]), vec3f(s[0], s[1], s[2]))
+            };
+
+            let position = to_left_handed(translation);
+            let rotation = to_left_handed_quat(rotation);
+            let local_matrix = Mat34f::from_translation(position) * Mat34f::from(rotation) * Mat34f::from_scale(scale);
+            let world_matrix = if let Some(parent) = parent {
+                nodes[parent].world_matrix * local_matrix
+            }
+            else {
+                local_matrix
+            };
+
+            let output_index = nodes.len();
+            nodes.push(NodeData {
+                name: node.name.clone().unwrap_or(format!("node_{}", index)),
+                parent,
+                mesh: node.mesh,
+                position,
+                rotation,
+                scale,
+                world_matrix
+            });
+
+            for child in node.children.iter().rev() {
+                stack.push((*child, Some(output_index)));
+            }
+        }
+        nodes
+    }
+
+    /// Returns the encoded (png, jpg) bytes of image `index`, either from a buffer view, data uri or external file
+    pub fn get_image_data(&self, index: usize) -> Result<Vec<u8>, super::Error> {
+        let image = self.json.images.get(index).ok_or(super::Error {
+            msg: format!("hotline_rs::gltf:: image {} is out of range", index)
+        })?;
+
+        if let Some(view) = image.buffer_view {
+            let view = self.json.buffer_views.get(view).ok_or(super::Error {
+                msg: format!("hotline_rs::gltf:: image {} references an invalid buffer view", index)
+            })?;
+            let buffer = self.buffers.get(view.buffer).ok_or(super::Error {
+                msg: format!("hotline_rs::gltf:: image {} references an invalid buffer", index)
+            })?;
+            buffer.get(view.byte_offset..view.byte_offset + view.byte_length).map(|b| b.to_vec()).ok_or(super::Error {
+                msg: format!("hotline_rs::gltf:: image {} buffer view is out of range", index)
+            })
+        }
+        else if let Some(uri) = &image.uri {
+            load_uri(uri, &self.base_dir)
+        }
+        else {
+            Err(super::Error {
+                msg: format!("hotline_rs::gltf:: image {} has no uri or buffer view", index)
+            })
+        }
+    }
+
+    /// Decodes image `index` into RGBA8n `ImageData`
+    pub fn load_image(&self, index: usize) -> Result<image::ImageData, super::Error> {
+        image::load_from_memory(&self.get_image_data(index)?, Some(gfx::Format::RGBA8n))
+    }
+
+    /// Creates the cpu side data for the 3 material textures hotline shaders expect (albedo, normal, roughness_metallic)
+    /// material factors are baked into the texels and missing maps are replaced with 1x1 textures.
+    /// glTF packs roughness in G and metalness in B, they are repacked into R and G to match `load_material` maps
+    pub fn get_material_images(&self, material: &MaterialData) -> Result<[image::ImageData; 3], super::Error> {
+        let solid = |rgba: [u8; 4]| image::ImageData {
+            info: gfx::TextureInfo {
+                format: gfx::Format::RGBA8n,
+                width: 1,
+                height: 1,
+                ..Default::default()
+            },
+            data: rgba.to_vec()
+        };
+        let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+
+        let albedo = if let Some(index) = material.albedo {
+            let mut image = self.load_image(index)?;
+            let factor = [material.base_colour.x, material.base_colour.y, material.base_colour.z, material.base_colour.w];
+            if factor != [1.0; 4] {
+                for texel in image.data.chunks_exact_mut(4) {
+                    for c in 0..4 {
+                        texel[c] = to_u8(texel[c] as f32 / 255.0 * factor[c]);
+                    }
+                }
+            }
+            image
+        }
+        else {
+            let c = material.base_colour;
+            solid([to_u8(c.x), to_u8(c.y), to_u8(c.z), to_u8(c.w)])
+        };
+
+        let normal = if let Some(index) = material.normal {
+            self.load_image(index)?
+        }
+        else {
+            solid([128, 128, 255, 255])
+        };
+
+        let roughness_metallic = if let Some(index) = material.metallic_roughness {
+            let mut image = self.load_image(index)?;
+            for texel in image.data.chunks_exact_mut(4) {
+                let roughness = texel[1] as f32 / 255.0 * material.roughness;
+                let metallic = texel[2] as f32 / 255.0 * material.metallic;
+                texel[0] = to_u8(roughness);
+                texel[1] = to_u8(metallic);
+                texel[2] = 0;
+                texel[3] = 255;
+            }
+            image
+        }
+        else {
+            solid([to_u8(material.roughness), to_u8(material.metallic), 0, 255])
+        };
+
+        Ok([albedo, normal, roughness_metallic])
+    }
+}
+
+/// Creates gpu meshes for all `meshes`, the result contains one `pmfx::Mesh` per-primitive for each mesh
+pub fn create_meshes<D: gfx::Device>(dev: &mut D, meshes: &[MeshData]) -> Vec<Vec<pmfx::Mesh<D>>> {
+    meshes.iter().map(|mesh| {
+        mesh.primitives.iter().map(|primitive| {
+            primitives::create_mesh_3d(dev, primitive.vertices.clone(), primitive.indices.clone())
+        }).collect()
+    }).collect()
+}
+
+/// Creates the (albedo, normal, roughness_metallic) textures for `material` allocating shader resource views in `heap`
+pub fn create_material_textures<D: gfx::Device>(
+    dev: &mut D,
+    heap: &mut D::Heap,
+    gltf: &Gltf,
+    material: &MaterialData) -> Result<[D::Texture; 3], super::Error> {
+    let [albedo, normal, roughness_metallic] = gltf.get_material_images(material)?;
+    let mut create = |image: image::ImageData| {
+        dev.create_texture_with_heaps(
+            &image.info,
+            gfx::TextureHeapInfo {
+                shader: Some(&mut *heap),
+                ..Default::default()
+            },
+            crate::data![image.data.as_slice()]
+        )
+    };
+    Ok([create(albedo)?, create(normal)?, create(roughness_metallic)?])
+}
+
+/// The result of `spawn_scene`, materials are indexed by the `material_id` of each spawned entity's `InstanceIds`
+pub struct SpawnedScene {
+    /// One entity per-node in `Gltf::get_nodes` order, followed by an extra child entity for each additional primitive
+    pub entities: Vec<bevy_ecs::entity::Entity>,
+    pub materials: Vec<crate::ecs_base::MaterialResources>
+}
+
+/// Spawns the default scene of `gltf` as a hierarchy of entities. Every node gets `Name`, `Position`, `Rotation`, `Scale`
+/// and a `WorldMatrix` containing its concatenated world transform, child nodes also get a `Parent` so the world
+/// transforms are kept up to date by `ecs_base::propagate_world_matrices` as nodes move.
+/// Nodes with a mesh get a `MeshComponent`, `Extents` and `InstanceIds` for their first primitive, additional primitives
+/// are spawned as identity transformed children of the node.
+pub fn spawn_scene(
+    commands: &mut bevy_ecs::system::Commands,
+    device: &mut crate::gfx_platform::Device,
+    pmfx: &mut pmfx::Pmfx<crate::gfx_platform::Device>,
+    gltf: &Gltf) -> Result<SpawnedScene, super::Error> {
+    use crate::ecs_base::*;
+
+    let mesh_data = gltf.get_meshes()?;
+    let mut meshes = create_meshes(device, &mesh_data).into_iter().map(Some).collect::<Vec<_>>();
+
+    let mut materials = Vec::new();
+    for material in gltf.get_materials() {
+        let [albedo, normal, roughness] = create_material_textures(device, &mut pmfx.shader_heap, gltf, &material)?;
+        materials.push(MaterialResources {
+            albedo,
+            normal,
+            roughness
+        });
+    }
+
+    // primitives without a material use a default white material appended to the end
+    let default_material = materials.len() as u32;
+    if gltf.json.meshes.iter().flat_map(|m| &m.primitives).any(|p| p.material.is_none()) {
+        let default = MaterialData {
+            name: "default".to_string(),
+            base_colour: Vec4f::one(),
+            metallic: 1.0,
+            roughness: 1.0,
+            albedo: None,
+            normal: None,
+            metallic_roughness: None
+        };
+        let [albedo, normal, roughness] = create_material_textures(device, &mut pmfx.shader_heap, gltf, &default)?;
+        materials.push(MaterialResources {
+            albedo,
+            normal,
+            roughness
+        });
+    }
+
+    // entity ids index into world buffers, nodes come first followed by the additional primitives
+    let nodes = gltf.get_nodes();
+    let mut node_entities = Vec::new();
+    let mut extra = Vec::new();
+    for (node_index, node) in nodes.iter().enumerate() {
+        let mut entity = commands.spawn((
+            Name(node.name.clone()),
+            Position(node.position),
+            Rotation(node.rotation),
+            Scale(node.scale),
+            WorldMatrix(node.world_matrix)
+        ));
+
+        if let Some(parent) = node.parent {
+            entity.insert(Parent(node_entities[parent]));
+        }
+
+        if let Some(mesh_index) = node.mesh.filter(|m| *m < meshes.len()) {
+            // meshes are moved into the first node which uses them, other nodes instancing the same mesh create their own
+            let primitives = meshes[mesh_index].take().unwrap_or_else(|| {
+                create_meshes(device, std::slice::from_ref(&mesh_data[mesh_index])).remove(0)
+            });
+            for (i, mesh) in primitives.into_iter().enumerate() {
+                let material_id = gltf.json.meshes[mesh_index].primitives[i].material
+                    .map(|m| m as u32)
+                    .unwrap_or(default_material);
+                let entity_id = if i == 0 { node_index } else { nodes.len() + extra.len() };
+                let extents = Extents {
+                    aabb_min: mesh.aabb_min,
+                    aabb_max: mesh.aabb_max
+                };
+                let components = (
+                    MeshComponent(mesh),
+                    extents,
+                    InstanceIds {
+                        entity_id: entity_id as u32,
+                        material_id
+                    }
+                );
+                if i == 0 {
+                    entity.insert(components);
+                }
+                else {
+                    extra.push((node_index, components));
+                }
+            }
+        }
+
+        node_entities.push(entity.id());
+    }
+
+    let mut entities = node_entities.clone();
+    for (parent, components) in extra {
+        let entity = commands.spawn((
+            Name(format!("{}_primitive", nodes[parent].name)),
+            Position(Vec3f::zero()),
+            Rotation(Quatf::identity()),
+            Scale(Vec3f::one()),
+            WorldMatrix(nodes[parent].world_matrix),
+            Parent(node_entities[parent]),
+            components
+        )).id();
+        entities.push(entity);
+    }
+
+    Ok(SpawnedScene {
+        entities,
+        materials
+    })
+}
@@ -0,0 +1,476 @@
+use crate::gfx;
+use crate::os;
+use crate::imgui;
+use crate::scene;
+use crate::inspector;
+use crate::ecs_base::*;
+use crate::scene::SceneEntity;
+use crate::inspector::InspectorComponent;
+
+use bevy_ecs::prelude::*;
+use maths_rs::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+/// The maximum number of commands kept in a `History`, the oldest commands are dropped first
+pub const HISTORY_MAX_COMMANDS: usize = 256;
+
+/// The editable components of an entity, captured before and after an edit so it can be undone and redone.
+/// Entities are stored as `Entity::to_bits` so snapshots can be serialised
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EntitySnapshot {
+    pub entity: u64,
+    pub parent: Option<u64>,
+    /// Components in the same form they are saved in scenes, the `SceneEntity::parent` index is unused
+    pub components: SceneEntity
+}
+
+/// Captures the editable components of `entity`, returns `None` if the entity does not exist
+pub fn snapshot_entity(world: &World, entity: Entity) -> Option<EntitySnapshot> {
+    if !world.entities().contains(entity) {
+        return None;
+    }
+    Some(EntitySnapshot {
+        entity: entity.to_bits(),
+        parent: world.get::<Parent>(entity).map(|parent| parent.0.to_bits()),
+        components: scene::scene_entity_from_world(world, entity)
+    })
+}
+
+/// Captures `entity` and all of its descendants, parents are before their children
+pub fn snapshot_tree(world: &mut World, entity: Entity) -> Vec<EntitySnapshot> {
+    inspector::descendants(world, entity).into_iter()
+        .filter_map(|entity| snapshot_entity(world, entity))
+        .collect()
+}
+
+fn existing_entity(world: &World, bits: u64) -> Option<Entity> {
+    let entity = Entity::from_bits(bits);
+    if world.entities().contains(entity) {
+        Some(entity)
+    }
+    else {
+        None
+    }
+}
+
+fn entity_label(snapshot: &EntitySnapshot) -> String {
+    if let Some(name) = &snapshot.components.name {
+        name.to_string()
+    }
+    else {
+        format!("entity {}", Entity::from_bits(snapshot.entity).index())
+    }
+}
+
+/// Sets the components of the snapshot entity to match the snapshot, adding and removing components where they
+/// differ. Snapshots of entities which no longer exist are ignored
+fn restore_snapshot(world: &mut World, snapshot: &EntitySnapshot) {
+    let entity = if let Some(entity) = existing_entity(world, snapshot.entity) {
+        entity
+    }
+    else {
+        return;
+    };
+    let components = &snapshot.components;
+
+    for component in InspectorComponent::ALL {
+        let restored = match component {
+            InspectorComponent::Position => components.position.is_some(),
+            InspectorComponent::Rotation => components.rotation.is_some(),
+            InspectorComponent::Scale => components.scale.is_some(),
+            InspectorComponent::Colour => components.colour.is_some(),
+            InspectorComponent::Light => components.light.is_some(),
+            InspectorComponent::Camera => components.camera.is_some()
+        };
+        let current = inspector::has_component(world, entity, component);
+        if restored && !current {
+            inspector::add_component(world, entity, component);
+        }
+        else if !restored && current {
+            inspector::remove_component(world, entity, component);
+        }
+    }
+
+    let parent = snapshot.parent.and_then(|parent| existing_entity(world, parent));
+    let mut entity_mut = world.entity_mut(entity);
+    if let Some(name) = &components.name {
+        entity_mut.insert(Name(name.to_string()));
+    }
+    else {
+        entity_mut.remove::<Name>();
+    }
+    if let Some(parent) = parent {
+        entity_mut.insert(Parent(parent));
+    }
+    else {
+        entity_mut.remove::<Parent>();
+    }
+    if let Some(position) = components.position {
+        entity_mut.insert(Position(Vec3f::from(position)));
+    }
+    if let Some((x, y, z, w)) = components.rotation {
+        entity_mut.insert(Rotation(Quatf::from(vec4f(x, y, z, w))));
+    }
+    if let Some(scale) = components.scale {
+        entity_mut.insert(Scale(Vec3f::from(scale)));
+    }
+    if let Some((r, g, b, a)) = components.colour {
+        entity_mut.insert(Colour(vec4f(r, g, b, a)));
+    }
+    if let Some(restored) = &components.light {
+        if let Some(mut light) = entity_mut.get_mut::<LightComponent>() {
+            light.light_type = restored.light_type;
+            light.direction = Vec3f::from(restored.direction);
+            light.cutoff = restored.cutoff;
+            light.falloff = restored.falloff;
+            light.radius = restored.radius;
+        }
+    }
+    if let Some(restored) = &components.camera {
+        if let Some(mut camera) = entity_mut.get_mut::<Camera>() {
+            camera.camera_type = restored.camera_type;
+            camera.rot = Vec3f::from(restored.rot);
+            camera.focus = Vec3f::from(restored.focus);
+            camera.zoom = restored.zoom;
+        }
+    }
+    if let Some(mesh) = &components.mesh {
+        entity_mut.insert(MeshAsset(mesh.to_string()));
+    }
+    else {
+        entity_mut.remove::<MeshAsset>();
+    }
+    if let Some(material) = &components.material {
+        entity_mut.insert(MaterialAsset(material.to_string()));
+    }
+    else {
+        entity_mut.remove::<MaterialAsset>();
+    }
+}
+
+/// Spawns new entities for `snapshots` and restores their components, returns a map of the snapshot entities to the
+/// new entities which replace them
+fn respawn_snapshots(world: &mut World, snapshots: &[EntitySnapshot]) -> HashMap<u64, u64> {
+    let remap = snapshots.iter()
+        .map(|snapshot| (snapshot.entity, world.spawn_empty().id().to_bits()))
+        .collect::<HashMap<u64, u64>>();
+    for snapshot in snapshots {
+        restore_snapshot(world, &EntitySnapshot {
+            entity: remap[&snapshot.entity],
+            parent: snapshot.parent.map(|parent| remap.get(&parent).copied().unwrap_or(parent)),
+            components: snapshot.components.clone()
+        });
+    }
+    remap
+}
+
+fn despawn_snapshots(world: &mut World, snapshots: &[EntitySnapshot]) {
+    for snapshot in snapshots.iter().rev() {
+        if let Some(entity) = existing_entity(world, snapshot.entity) {
+            world.despawn(entity);
+        }
+    }
+}
+
+/// An undoable edit. Commands store the state before and after the edit rather than the operation, so undoing and
+/// redoing them is the same as restoring a state
+#[derive(Serialize, Deserialize, Clone)]
+pub enum EditCommand {
+    /// The components of an entity were edited
+    Edit {
+        before: EntitySnapshot,
+        after: EntitySnapshot
+    },
+    /// Entities were spawned
+    Spawn {
+        entities: Vec<EntitySnapshot>
+    },
+    /// An entity and its descendants were despawned, parents are before their children
+    Despawn {
+        entities: Vec<EntitySnapshot>
+    },
+    /// The `SessionInfo` changed, ie. the main camera moved or the active demo was switched
+    Session {
+        before: SessionInfo,
+        after: SessionInfo
+    }
+}
+
+impl EditCommand {
+    /// Display name of the command for the history panel
+    pub fn name(&self) -> String {
+        match self {
+            EditCommand::Edit { before, after } => {
+                let (b, a) = (&before.components, &after.components);
+                let changed = [
+                    ("Name", b.name != a.name),
+                    ("Parent", before.parent != after.parent),
+                    ("Position", b.position != a.position),
+                    ("Rotation", b.rotation != a.rotation),
+                    ("Scale", b.scale != a.scale),
+                    ("Colour", b.colour != a.colour),
+                    ("Light", b.light != a.light),
+                    ("Camera", b.camera != a.camera),
+                    ("Mesh", b.mesh != a.mesh),
+                    ("Material", b.material != a.material)
+                ].iter().filter(|(_, changed)| *changed).map(|(name, _)| *name).collect::<Vec<_>>();
+                format!("Edit {} {}", entity_label(after), changed.join(", "))
+            }
+            EditCommand::Spawn { entities } => {
+                format!("Spawn {}", entities.first().map(entity_label).unwrap_or_default())
+            }
+            EditCommand::Despawn { entities } => {
+                format!("Despawn {}", entities.first().map(entity_label).unwrap_or_default())
+            }
+            EditCommand::Session { before, after } => {
+                if before.active_demo != after.active_demo {
+                    format!("Demo {}", after.active_demo)
+                }
+                else if before.main_camera != after.main_camera {
+                    "Camera".to_string()
+                }
+                else if before.debug_draw_flags != after.debug_draw_flags {
+                    "Debug Draw".to_string()
+                }
+                else {
+                    "Session".to_string()
+                }
+            }
+        }
+    }
+
+    /// Restores the state before (`undo`) or after the command, returns a map of any entities which were replaced
+    /// when despawned entities are spawned again
+    fn apply(&self, world: &mut World, session: &mut SessionInfo, undo: bool) -> HashMap<u64, u64> {
+        match self {
+            EditCommand::Edit { before, after } => {
+                restore_snapshot(world, if undo { before } else { after });
+            }
+            EditCommand::Spawn { entities } if undo => {
+                despawn_snapshots(world, entities);
+            }
+            EditCommand::Despawn { entities } if !undo => {
+                despawn_snapshots(world, entities);
+            }
+            EditCommand::Spawn { entities } | EditCommand::Despawn { entities } => {
+                return respawn_snapshots(world, entities);
+            }
+            EditCommand::Session { before, after } => {
+                let restored = if undo { before } else { after };
+                *session = restored.clone();
+            }
+        }
+        HashMap::new()
+    }
+
+    /// Replaces references to entities in `remap`
+    fn remap(&mut self, remap: &HashMap<u64, u64>) {
+        let snapshots = match self {
+            EditCommand::Edit { before, after } => vec![before, after],
+            EditCommand::Spawn { entities } | EditCommand::Despawn { entities } => entities.iter_mut().collect(),
+            EditCommand::Session { .. } => Vec::new()
+        };
+        for snapshot in snapshots {
+            snapshot.entity = remap.get(&snapshot.entity).copied().unwrap_or(snapshot.entity);
+            snapshot.parent = snapshot.parent.map(|parent| remap.get(&parent).copied().unwrap_or(parent));
+        }
+    }
+}
+
+/// Detects changes to a value by comparing it with a checkpoint. Changes are only reported when input is idle and the
+/// value is the same as the previous update, so a drag or a value which is still changing is a single change
+struct ChangeTracker<T> {
+    checkpoint: Option<T>,
+    previous: Option<T>
+}
+
+impl<T> Default for ChangeTracker<T> {
+    fn default() -> Self {
+        ChangeTracker {
+            checkpoint: None,
+            previous: None
+        }
+    }
+}
+
+impl<T: Clone + PartialEq> ChangeTracker<T> {
+    /// Returns the checkpoint and `current` if they differ, `current` then becomes the checkpoint
+    fn update(&mut self, current: Option<T>, idle: bool) -> Option<(T, T)> {
+        let settled = current == self.previous;
+        self.previous = current.clone();
+        if !idle || !settled {
+            return None;
+        }
+        let change = match (self.checkpoint.take(), &current) {
+            (Some(before), Some(after)) if before != *after => Some((before, after.clone())),
+            _ => None
+        };
+        self.checkpoint = current;
+        change
+    }
+
+    fn reset(&mut self) {
+        self.checkpoint = None;
+        self.previous = None;
+    }
+}
+
+/// Undo and redo stack of `EditCommand`s. Component edits of the selected entity and changes to the `SessionInfo`
+/// are recorded by `track`, spawns and despawns are recorded by `spawn` and `despawn`. Despawned entities are spawned
+/// again with the components in an `EntitySnapshot`, other components such as meshes which are not created from a
+/// `MeshAsset` are not restored. The stack is serialisable so it can be kept across plugin reloads
+#[derive(Serialize, Deserialize, Default)]
+pub struct History {
+    commands: Vec<EditCommand>,
+    /// The number of commands which are applied, commands after the cursor have been undone and can be redone
+    cursor: usize,
+    #[serde(skip)]
+    entity_tracker: ChangeTracker<EntitySnapshot>,
+    #[serde(skip)]
+    session_tracker: ChangeTracker<SessionInfo>
+}
+
+impl History {
+    pub fn commands(&self) -> &[EditCommand] {
+        &self.commands
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.cursor > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.cursor < self.commands.len()
+    }
+
+    /// Adds a command which has already been applied, discarding any commands which could be redone
+    pub fn push(&mut self, command: EditCommand) {
+        self.commands.truncate(self.cursor);
+        self.commands.push(command);
+        if self.commands.len() > HISTORY_MAX_COMMANDS {
+            self.commands.remove(0);
+        }
+        self.cursor = self.commands.len();
+    }
+
+    fn apply(&mut self, index: usize, world: &mut World, session: &mut SessionInfo, undo: bool) {
+        let remap = self.commands[index].apply(world, session, undo);
+        if !remap.is_empty() {
+            for command in &mut self.commands {
+                command.remap(&remap);
+            }
+        }
+        // undoing and redoing should not be recorded as edits
+        self.entity_tracker.reset();
+        self.session_tracker.reset();
+    }
+
+    /// Undoes the last applied command, returns false if there is nothing to undo
+    pub fn undo(&mut self, world: &mut World, session: &mut SessionInfo) -> bool {
+        if !self.can_undo() {
+            return false;
+        }
+        self.cursor -= 1;
+        self.apply(self.cursor, world, session, true);
+        true
+    }
+
+    /// Redoes the last undone command, returns false if there is nothing to redo
+    pub fn redo(&mut self, world: &mut World, session: &mut SessionInfo) -> bool {
+        if !self.can_redo() {
+            return false;
+        }
+        self.cursor += 1;
+        self.apply(self.cursor - 1, world, session, false);
+        true
+    }
+
+    /// Undoes or redoes commands until `cursor` commands are applied
+    pub fn goto(&mut self, world: &mut World, session: &mut SessionInfo, cursor: usize) {
+        let cursor = cursor.min(self.commands.len());
+        while self.cursor > cursor {
+            self.undo(world, session);
+        }
+        while self.cursor < cursor {
+            self.redo(world, session);
+        }
+    }
+
+    /// Spawns an entity with `inspector::spawn_entity` and records it
+    pub fn spawn(&mut self, world: &mut World) -> Entity {
+        let entity = inspector::spawn_entity(world);
+        self.push(EditCommand::Spawn {
+            entities: snapshot_tree(world, entity)
+        });
+        entity
+    }
+
+    /// Despawns an entity and its descendants with `inspector::despawn_entity` and records them
+    pub fn despawn(&mut self, world: &mut World, entity: Entity) {
+        let entities = snapshot_tree(world, entity);
+        inspector::despawn_entity(world, entity);
+        self.push(EditCommand::Despawn {
+            entities
+        });
+    }
+
+    /// Call once per frame to record edits to the `selected` entity and the `session`. Edits are recorded when `idle`,
+    /// once the changes have stopped, so dragging a widget or a gizmo is recorded as a single edit. The `MainCamera`
+    /// is not tracked because it is recorded as part of the session
+    pub fn track(&mut self, world: &World, selected: Option<Entity>, session: &SessionInfo, idle: bool) {
+        let snapshot = selected
+            .filter(|entity| world.get::<MainCamera>(*entity).is_none())
+            .and_then(|entity| snapshot_entity(world, entity));
+        if let Some((before, after)) = self.entity_tracker.update(snapshot, idle) {
+            // a change of selection is not an edit
+            if before.entity == after.entity {
+                self.push(EditCommand::Edit {
+                    before,
+                    after
+                });
+            }
+        }
+
+        if let Some((before, after)) = self.session_tracker.update(Some(session.clone()), idle) {
+            self.push(EditCommand::Session {
+                before,
+                after
+            });
+        }
+    }
+
+    /// Adds undo and redo buttons and a list of the commands to the current imgui window, selecting a command undoes
+    /// or redoes commands so it is the last one applied
+    pub fn ui<D: gfx::Device, A: os::App>(&mut self, imgui: &mut imgui::ImGui<D, A>, world: &mut World, session: &mut SessionInfo)
+        where D::RenderPipeline: gfx::Pipeline {
+        if imgui.button(&format!("{} Undo", font_awesome::strs::UNDO)) {
+            self.undo(world, session);
+        }
+        imgui.same_line();
+        if imgui.button(&format!("{} Redo", font_awesome::strs::REDO)) {
+            self.redo(world, session);
+        }
+        imgui.separator();
+
+        let mut cursor = None;
+        if imgui.selectable("Start##history_0", self.cursor == 0, imgui_sys::ImGuiSelectableFlags_None as i32) {
+            cursor = Some(0);
+        }
+        for (i, command) in self.commands.iter().enumerate() {
+            let label = format!("{}##history_{}", command.name(), i + 1);
+            if imgui.selectable(&label, self.cursor == i + 1, imgui_sys::ImGuiSelectableFlags_None as i32) {
+                cursor = Some(i + 1);
+            }
+        }
+        if let Some(cursor) = cursor {
+            self.goto(world, session, cursor);
+        }
+    }
+}
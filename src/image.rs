@@ -20,20 +20,44 @@ pub struct ImageData {
     pub data: Vec<u8>,
 }
 
+/// Formats that images decoded via stb_image can be requested in, 8-bit and 16-bit unorm values are converted to
+/// and from float linearly without any gamma
+pub const DECODE_FORMATS: [gfx::Format; 4] = [
+    gfx::Format::RGBA8n,
+    gfx::Format::RGBA16n,
+    gfx::Format::RGBA16f,
+    gfx::Format::RGBA32f
+];
+
 /// Loads an image from file returning information in the ImageData struct
-/// supported formats are (png, tga, bmp, jpg, gif, dds)
-pub fn load_from_file(filename: &str) -> Result<ImageData, super::Error> {
+/// supported formats are (png, tga, bmp, jpg, gif, hdr, dds). Images decoded via stb_image are returned in the
+/// requested `format` which must be one of `DECODE_FORMATS`, or if `format` is `None` in the precision of the file:
+/// `RGBA32f` for hdr, `RGBA16n` for 16-bit png and `RGBA8n` for everything else. dds files are returned as stored
+/// and can only be requested in their own format
+pub fn load_from_file(filename: &str, format: Option<gfx::Format>) -> Result<ImageData, super::Error> {
     // read file
     let path = std::path::Path::new(filename);
     println!("hotline_rs::image:: loading: {}", path.display());
-    let mut f = fs::File::open(path).expect("hotline_rs::image:: File not found");
+    let mut f = fs::File::open(path).map_err(|err| super::Error {
+        msg: format!("hotline_rs::image:: failed to open {}: {}", filename, err)
+    })?;
     // dds file
     if filename.ends_with(".dds") {
-        let dds = DDS::read(f)?;
+        let dds = DDS::read(f).map_err(|err| super::Error {
+            msg: format!("hotline_rs::image:: failed to read dds {}: {}", filename, err)
+        })?;
+        let dds_format = to_gfx_format(&dds);
+        if let Some(format) = format {
+            if format != dds_format {
+                return Err(super::Error {
+                    msg: format!("hotline_rs::image:: cannot convert dds {} from {:?} to {:?}", filename, dds_format, format)
+                });
+            }
+        }
         Ok(ImageData {
             info: TextureInfo {
                 tex_type: to_gfx_texture_type(&dds),
-                format: to_gfx_format(&dds),
+                format: dds_format,
                 width: dds.get_width() as u64,
                 height: dds.get_height() as u64,
                 depth: dds.get_depth(),
@@ -50,49 +74,200 @@ pub fn load_from_file(filename: &str) -> Result<ImageData, super::Error> {
         // stb image
         let mut contents = vec![];
         f.read_to_end(&mut contents)?;
+        load_from_memory(&contents, format).map_err(|err| super::Error {
+            msg: format!("{}: {}", err.msg, filename)
+        })
+    }
+}
 
-        let mut x = 0;
-        let mut y = 0;
-        let mut comp = 0;
-        let mut data_out: Vec<u8> = Vec::new();
+/// Decodes an image from an encoded in-memory file (png, tga, bmp, jpg, gif, hdr) returning the result in an ImageData
+/// struct with 4 components per-pixel in the requested `format`, which must be one of `DECODE_FORMATS`. If `format`
+/// is `None` the image is returned in the precision it was encoded with: `RGBA32f` for hdr, `RGBA16n` for 16-bit
+/// and `RGBA8n` for 8-bit images
+pub fn load_from_memory(data: &[u8], format: Option<gfx::Format>) -> Result<ImageData, super::Error> {
+    if let Some(format) = format {
+        if !DECODE_FORMATS.contains(&format) {
+            return Err(super::Error {
+                msg: format!("hotline_rs::image:: cannot decode image as {:?}, expected one of {:?}", format, DECODE_FORMATS)
+            });
+        }
+    }
+
+    // stb_image_rust is built without its hdr decoder, so radiance files are decoded here
+    let (encoded_format, width, height, pixels) = if is_hdr(data) {
+        let (width, height, pixels) = decode_hdr(data)?;
+        (gfx::Format::RGBA32f, width, height, pixels)
+    }
+    else {
+        decode_stbi(data)?
+    };
 
-        unsafe {
-            // load image
+    let format = format.unwrap_or(encoded_format);
+    Ok(ImageData {
+        info: TextureInfo {
+            format,
+            width,
+            height,
+            ..Default::default()
+        },
+        data: convert_pixels(&pixels, encoded_format, format),
+    })
+}
+
+/// Decodes an 8 or 16-bit image via stb_image at the precision it was encoded with, returning the format, width,
+/// height and tightly packed rgba pixels
+fn decode_stbi(data: &[u8]) -> Result<(gfx::Format, u64, u64, Vec<u8>), super::Error> {
+    let mut contents = data.to_vec();
+    let len = contents.len() as i32;
+    let mut x = 0;
+    let mut y = 0;
+    let mut comp = 0;
+
+    unsafe {
+        let buffer = contents.as_mut_ptr();
+        let (encoded_format, pixels) = if stb_image_rust::stbi_is_16_bit_from_memory(buffer, len) != 0 {
+            let img = stb_image_rust::stbi_load_16_from_memory(
+                buffer, len, &mut x, &mut y, &mut comp, stb_image_rust::STBI_rgb_alpha);
+            (gfx::Format::RGBA16n, take_stbi_pixels(img, x, y, 8))
+        }
+        else {
             let img = stb_image_rust::stbi_load_from_memory(
-                contents.as_mut_ptr(),
-                contents.len() as i32,
-                &mut x,
-                &mut y,
-                &mut comp,
-                stb_image_rust::STBI_rgb_alpha,
-            );
+                buffer, len, &mut x, &mut y, &mut comp, stb_image_rust::STBI_rgb_alpha);
+            (gfx::Format::RGBA8n, take_stbi_pixels(img, x, y, 4))
+        };
+
+        let pixels = pixels.ok_or_else(|| super::Error {
+            msg: "hotline_rs::image:: failed to load image from memory via stb_image".to_string()
+        })?;
+        Ok((encoded_format, x as u64, y as u64, pixels))
+    }
+}
+
+/// Returns true if `data` is a radiance rgbe (.hdr) file
+fn is_hdr(data: &[u8]) -> bool {
+    data.starts_with(b"#?RADIANCE\n") || data.starts_with(b"#?RGBE\n")
+}
+
+/// Returns an error for a malformed radiance file
+fn hdr_error(reason: &str) -> super::Error {
+    super::Error {
+        msg: format!("hotline_rs::image:: failed to load hdr image from memory: {}", reason)
+    }
+}
 
-            if !img.is_null() {
-                // copy data
-                let data_size_bytes = x * y * 4;
-                data_out.resize(data_size_bytes as usize, 0);
-                std::ptr::copy_nonoverlapping(img, data_out.as_mut_ptr(), data_size_bytes as usize);
+/// Decodes a radiance rgbe (.hdr) file with flat or run length encoded scanlines in the standard `-Y height +X width`
+/// orientation, returning the width, height and tightly packed `RGBA32f` pixels with alpha set to 1
+fn decode_hdr(data: &[u8]) -> Result<(u64, u64, Vec<u8>), super::Error> {
+    // header lines are terminated by an empty line, followed by the resolution line
+    let mut lines = data.split(|b| *b == b'\n');
+    let mut header_size = 0;
+    loop {
+        let line = lines.next().ok_or_else(|| hdr_error("unterminated header"))?;
+        header_size += line.len() + 1;
+        if line.starts_with(b"FORMAT=") && line != b"FORMAT=32-bit_rle_rgbe" {
+            return Err(hdr_error("unsupported format, expected 32-bit_rle_rgbe"));
+        }
+        if line.is_empty() {
+            break;
+        }
+    }
+    let resolution = lines.next().ok_or_else(|| hdr_error("missing resolution"))?;
+    header_size += resolution.len() + 1;
+    let resolution = String::from_utf8_lossy(resolution);
+    let (width, height) = match resolution.split_whitespace().collect::<Vec<&str>>()[..] {
+        ["-Y", height, "+X", width] => (width.parse::<usize>().ok(), height.parse::<usize>().ok()),
+        _ => (None, None)
+    };
+    let (width, height) = width.zip(height).ok_or_else(|| hdr_error("unsupported resolution, expected -Y height +X width"))?;
 
-                // cleanup
-                stb_image_rust::c_runtime::free(img);
+    let num_pixels = width.checked_mul(height).filter(|n| *n > 0 && n.checked_mul(16).is_some())
+        .ok_or_else(|| hdr_error("invalid image dimensions"))?;
+    let mut rgbe = Vec::with_capacity(num_pixels.min(data.len()) * 4);
+    let mut src = data.get(header_size..).unwrap_or_default();
+    for _ in 0..height {
+        src = read_hdr_scanline(src, width, &mut rgbe)?;
+    }
+
+    let mut pixels = Vec::with_capacity(num_pixels * 16);
+    for texel in rgbe.chunks_exact(4) {
+        let scale = if texel[3] == 0 { 0.0 } else { 2.0f32.powi(texel[3] as i32 - (128 + 8)) };
+        for v in [texel[0] as f32 * scale, texel[1] as f32 * scale, texel[2] as f32 * scale, 1.0] {
+            pixels.extend(v.to_ne_bytes());
+        }
+    }
+    Ok((width as u64, height as u64, pixels))
+}
+
+/// Reads a single scanline of `width` rgbe texels from `src` into `rgbe`, returning the remaining data. Scanlines
+/// between 8 and 32767 texels wide may be run length encoded per channel, otherwise they are stored flat
+fn read_hdr_scanline<'a>(src: &'a [u8], width: usize, rgbe: &mut Vec<u8>) -> Result<&'a [u8], super::Error> {
+    let truncated = || hdr_error("truncated scanline");
+    let rle = (8..0x8000).contains(&width) && src.len() >= 4 && src[0] == 2 && src[1] == 2 && src[2] & 0x80 == 0;
+    if !rle {
+        let size = width * 4;
+        let scanline = src.get(..size).ok_or_else(truncated)?;
+        rgbe.extend_from_slice(scanline);
+        return Ok(&src[size..]);
+    }
 
-                Ok(ImageData {
-                    info: TextureInfo {
-                        format: gfx::Format::RGBA8n,
-                        width: x as u64,
-                        height: y as u64,
-                        ..Default::default()
-                    },
-                    data: data_out,
-                })
+    if ((src[2] as usize) << 8 | src[3] as usize) != width {
+        return Err(hdr_error("run length encoded scanline width does not match the image width"));
+    }
+    let mut src = &src[4..];
+    let mut channels = vec![0u8; width * 4];
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let count = *src.first().ok_or_else(truncated)? as usize;
+            let repeat = count > 128;
+            let (run, size) = if repeat { (count - 128, 1) } else { (count, count) };
+            if run == 0 || x + run > width {
+                return Err(hdr_error("invalid run length in scanline"));
             }
-            else {
-                Err(super::Error {
-                    msg: format!("hotline_rs::image:: failed to load image via stb_image: {}", filename)
-                })
+            let values = src.get(1..1 + size).ok_or_else(truncated)?;
+            for i in 0..run {
+                channels[(x + i) * 4 + channel] = if repeat { values[0] } else { values[i] };
             }
+            x += run;
+            src = &src[1 + size..];
         }
     }
+    rgbe.extend(channels);
+    Ok(src)
+}
+
+/// Copies the `x * y` pixels of `pixel_size_bytes` from an image decoded by stb_image and frees the image,
+/// returns `None` if stb_image failed to decode the image
+unsafe fn take_stbi_pixels<T>(img: *mut T, x: i32, y: i32, pixel_size_bytes: usize) -> Option<Vec<u8>> {
+    if img.is_null() {
+        return None;
+    }
+    let data_size_bytes = x as usize * y as usize * pixel_size_bytes;
+    let mut data = vec![0; data_size_bytes];
+    std::ptr::copy_nonoverlapping(img as *const u8, data.as_mut_ptr(), data_size_bytes);
+    stb_image_rust::c_runtime::free(img as *mut u8);
+    Some(data)
+}
+
+/// Converts tightly packed pixel `data` decoded by stb_image in `from` format (`RGBA8n`, `RGBA16n` or `RGBA32f`)
+/// into `to` format, which is one of `DECODE_FORMATS`
+fn convert_pixels(data: &[u8], from: gfx::Format, to: gfx::Format) -> Vec<u8> {
+    if from == to {
+        return data.to_vec();
+    }
+
+    let values : Vec<f32> = match from {
+        gfx::Format::RGBA8n => data.iter().map(|v| *v as f32 / 255.0).collect(),
+        gfx::Format::RGBA16n => data.chunks_exact(2).map(|v| u16::from_ne_bytes([v[0], v[1]]) as f32 / 65535.0).collect(),
+        _ => data.chunks_exact(4).map(|v| f32::from_ne_bytes([v[0], v[1], v[2], v[3]])).collect()
+    };
+
+    match to {
+        gfx::Format::RGBA8n => values.iter().map(|v| (v.clamp(0.0, 1.0) * 255.0 + 0.5) as u8).collect(),
+        gfx::Format::RGBA16n => values.iter().flat_map(|v| ((v.clamp(0.0, 1.0) * 65535.0 + 0.5) as u16).to_ne_bytes()).collect(),
+        gfx::Format::RGBA16f => values.iter().flat_map(|v| gfx::cpu::f32_to_f16(*v).to_ne_bytes()).collect(),
+        _ => values.iter().flat_map(|v| v.to_ne_bytes()).collect()
+    }
 }
 
 /// Loads an image from file and creates a shader resource on the specified heap, or on the device heap if `heap.is_none()`
@@ -100,7 +275,7 @@ pub fn load_texture_from_file(
     device: &mut crate::gfx_platform::Device,
     file: &str,
     heap: Option<&mut crate::gfx_platform::Heap>) -> Result<crate::gfx_platform::Texture, super::Error> {
-    let image = load_from_file(file)?;
+    let image = load_from_file(file, None)?;
     device.create_texture_with_heaps(
         &image.info,
         gfx::TextureHeapInfo {
@@ -261,7 +436,7 @@ fn to_gfx_format(dds: &DDS) -> gfx::Format {
             D3DFormat::DXT5 => panic!(),
             D3DFormat::R8G8_B8G8 => panic!(),
             D3DFormat::G8R8_G8B8 => panic!(),
-            D3DFormat::A16B16G16R16 => panic!(),
+            D3DFormat::A16B16G16R16 => gfx::Format::RGBA16n,
             D3DFormat::Q16W16V16U16 => panic!(),
             D3DFormat::R16F => gfx::Format::R16f,
             D3DFormat::G16R16F => gfx::Format::RG16f,
@@ -289,7 +464,7 @@ fn to_gfx_format(dds: &DDS) -> gfx::Format {
             DxgiFormat::R32G32B32_SInt => gfx::Format::RGB32i,
             DxgiFormat::R16G16B16A16_Typeless => panic!(),
             DxgiFormat::R16G16B16A16_Float => gfx::Format::RGBA16f,
-            DxgiFormat::R16G16B16A16_UNorm => panic!(),
+            DxgiFormat::R16G16B16A16_UNorm => gfx::Format::RGBA16n,
             DxgiFormat::R16G16B16A16_UInt => gfx::Format::RGBA16u,
             DxgiFormat::R16G16B16A16_SNorm => panic!(),
             DxgiFormat::R16G16B16A16_SInt => gfx::Format::RGBA16i,
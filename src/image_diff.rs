@@ -0,0 +1,328 @@
+use crate::gfx;
+use crate::image;
+use crate::image::ImageData;
+
+use serde::{Deserialize, Serialize};
+
+/// Directory `Client::run_once` writes its back buffer captures to
+pub const TEST_OUTPUT_DIR: &str = "target/test_output";
+
+/// Directory containing checked in reference images and optional per-image `DiffInfo` json files
+pub const REFERENCE_DIR: &str = "tests/reference";
+
+/// Environment variable which when set to `1` will bless (overwrite) references with the current output
+pub const BLESS_ENV_VAR: &str = "HOTLINE_BLESS";
+
+/// Rectangle in pixels which is excluded from comparison, useful for timers, stats or other non-deterministic output
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IgnoreRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32
+}
+
+impl IgnoreRegion {
+    fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && y >= self.y && x - self.x < self.width && y - self.y < self.height
+    }
+}
+
+/// Thresholds for comparing images, all thresholds must pass for images to be considered matching.
+/// Can be supplied per-reference as `<name>.json` next to the `<name>.png` reference image.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DiffInfo {
+    /// A pixel is counted as different if the absolute difference of any channel (0-255) exceeds this value
+    pub channel_tolerance: u8,
+    /// Maximum fraction (0-1) of compared pixels which are allowed to be different
+    pub max_different_pixels: f32,
+    /// Maximum root mean square error across all compared channels in 0-255 range
+    pub max_rmse: f32,
+    /// Maximum mean perceptual difference (CIE76 delta E) where ~2.3 is a just noticeable difference
+    pub max_mean_delta_e: f32,
+    /// Regions excluded from comparison
+    pub ignore_regions: Vec<IgnoreRegion>,
+    /// Ignore the alpha channel which is often undefined in a back buffer
+    pub ignore_alpha: bool
+}
+
+impl Default for DiffInfo {
+    fn default() -> Self {
+        DiffInfo {
+            channel_tolerance: 8,
+            max_different_pixels: 0.001,
+            max_rmse: 2.0,
+            max_mean_delta_e: 1.0,
+            ignore_regions: Vec::new(),
+            ignore_alpha: true
+        }
+    }
+}
+
+/// Results and metrics from comparing 2 images
+pub struct DiffResult {
+    pub width: u64,
+    pub height: u64,
+    /// Number of pixels compared (excludes ignored regions)
+    pub compared_pixels: usize,
+    /// Number of pixels with a channel difference larger than `DiffInfo::channel_tolerance`
+    pub different_pixels: usize,
+    /// Largest absolute difference of any channel
+    pub max_channel_delta: u8,
+    /// Root mean square error across all compared channels in 0-255 range
+    pub rmse: f32,
+    /// Mean CIE76 delta E of compared pixels
+    pub mean_delta_e: f32,
+    /// Largest CIE76 delta E of any compared pixel
+    pub max_delta_e: f32,
+    /// True if all `DiffInfo` thresholds passed
+    pub passed: bool,
+    /// RGBA8n image the same size as the inputs; a faded copy of the reference with different pixels highlighted in
+    /// red (scaled by their delta) and ignored regions tinted blue
+    pub diff_image: ImageData
+}
+
+impl DiffResult {
+    /// Returns the fraction (0-1) of compared pixels which are different
+    pub fn different_fraction(&self) -> f32 {
+        if self.compared_pixels > 0 {
+            self.different_pixels as f32 / self.compared_pixels as f32
+        }
+        else {
+            0.0
+        }
+    }
+
+    /// Returns a single line human readable summary of the result and thresholds
+    pub fn summary(&self, info: &DiffInfo) -> String {
+        format!(
+            "{} {}x{}: {}/{} pixels differ ({:.3}% / max {:.3}%), max channel delta {} (tolerance {}), rmse {:.3} (max {:.3}), mean delta e {:.3} (max {:.3}), max delta e {:.3}",
+            if self.passed { "passed" } else { "FAILED" },
+            self.width,
+            self.height,
+            self.different_pixels,
+            self.compared_pixels,
+            self.different_fraction() * 100.0,
+            info.max_different_pixels * 100.0,
+            self.max_channel_delta,
+            info.channel_tolerance,
+            self.rmse,
+            info.max_rmse,
+            self.mean_delta_e,
+            info.max_mean_delta_e,
+            self.max_delta_e
+        )
+    }
+}
+
+/// Converts an 8 bit sRGB colour to CIE L*a*b* with a D65 white point
+fn srgb_to_lab(rgb: [u8; 3]) -> [f32; 3] {
+    let linear = |c: u8| {
+        let c = c as f32 / 255.0;
+        if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    };
+    let (r, g, b) = (linear(rgb[0]), linear(rgb[1]), linear(rgb[2]));
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+    let f = |t: f32| if t > 0.008856 { t.cbrt() } else { 7.787 * t + 16.0 / 116.0 };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// CIE76 delta E between 2 sRGB colours
+fn delta_e(a: [u8; 3], b: [u8; 3]) -> f32 {
+    let a = srgb_to_lab(a);
+    let b = srgb_to_lab(b);
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+/// Checks an image is tightly packed RGBA8
+fn validate_rgba8(image: &ImageData, name: &str) -> Result<(), super::Error> {
+    let expected = (image.info.width * image.info.height * 4) as usize;
+    if image.data.len() != expected {
+        return Err(super::Error {
+            msg: format!(
+                "hotline_rs::image_diff:: {} image is {} bytes but {}x{} RGBA8 requires {}",
+                name, image.data.len(), image.info.width, image.info.height, expected
+            )
+        });
+    }
+    Ok(())
+}
+
+/// Compares tightly packed RGBA8 `output` against `reference` using the thresholds in `info`.
+/// Returns an error if the images are different sizes, otherwise the pass / fail state is in `DiffResult::passed`
+pub fn compare(reference: &ImageData, output: &ImageData, info: &DiffInfo) -> Result<DiffResult, super::Error> {
+    if reference.info.width != output.info.width || reference.info.height != output.info.height {
+        return Err(super::Error {
+            msg: format!(
+                "hotline_rs::image_diff:: size mismatch, reference is {}x{} and output is {}x{}",
+                reference.info.width, reference.info.height, output.info.width, output.info.height
+            )
+        });
+    }
+    validate_rgba8(reference, "reference")?;
+    validate_rgba8(output, "output")?;
+
+    let width = reference.info.width;
+    let height = reference.info.height;
+    let channels = if info.ignore_alpha { 3 } else { 4 };
+
+    let mut diff = vec![0u8; reference.data.len()];
+    let mut compared_pixels = 0;
+    let mut different_pixels = 0;
+    let mut max_channel_delta = 0;
+    let mut sum_sq = 0.0f64;
+    let mut sum_delta_e = 0.0f64;
+    let mut max_delta_e = 0.0f32;
+
+    for y in 0..height as u32 {
+        for x in 0..width as u32 {
+            let offset = ((y as u64 * width + x as u64) * 4) as usize;
+            let r = &reference.data[offset..offset + 4];
+            let o = &output.data[offset..offset + 4];
+            let luma = ((r[0] as u32 * 54 + r[1] as u32 * 183 + r[2] as u32 * 19) >> 8) as u8;
+            let faded = luma / 4;
+
+            if info.ignore_regions.iter().any(|region| region.contains(x, y)) {
+                diff[offset..offset + 4].copy_from_slice(&[faded, faded, faded.saturating_add(96), 255]);
+                continue;
+            }
+
+            compared_pixels += 1;
+            let mut pixel_delta = 0;
+            for c in 0..channels {
+                let delta = r[c].abs_diff(o[c]);
+                pixel_delta = pixel_delta.max(delta);
+                sum_sq += (delta as f64) * (delta as f64);
+            }
+            max_channel_delta = max_channel_delta.max(pixel_delta);
+
+            let de = delta_e([r[0], r[1], r[2]], [o[0], o[1], o[2]]);
+            sum_delta_e += de as f64;
+            max_delta_e = max_delta_e.max(de);
+
+            if pixel_delta > info.channel_tolerance {
+                different_pixels += 1;
+                diff[offset..offset + 4].copy_from_slice(&[128u8.saturating_add(pixel_delta / 2), 0, 0, 255]);
+            }
+            else {
+                diff[offset..offset + 4].copy_from_slice(&[faded, faded, faded, 255]);
+            }
+        }
+    }
+
+    let (rmse, mean_delta_e) = if compared_pixels > 0 {
+        (
+            (sum_sq / (compared_pixels * channels) as f64).sqrt() as f32,
+            (sum_delta_e / compared_pixels as f64) as f32
+        )
+    }
+    else {
+        (0.0, 0.0)
+    };
+
+    let mut result = DiffResult {
+        width,
+        height,
+        compared_pixels,
+        different_pixels,
+        max_channel_delta,
+        rmse,
+        mean_delta_e,
+        max_delta_e,
+        passed: false,
+        diff_image: ImageData {
+            info: reference.info,
+            data: diff
+        }
+    };
+    result.passed = result.different_fraction() <= info.max_different_pixels &&
+        result.rmse <= info.max_rmse &&
+        result.mean_delta_e <= info.max_mean_delta_e;
+    Ok(result)
+}
+
+/// Returns true if references should be blessed, set `HOTLINE_BLESS=1` to enable
+pub fn bless_requested() -> bool {
+    std::env::var(BLESS_ENV_VAR).map(|v| v == "1" || v.eq_ignore_ascii_case("true")).unwrap_or(false)
+}
+
+/// Loads the optional `<name>.json` `DiffInfo` from `reference_dir`, falling back to defaults if it does not exist
+pub fn load_diff_info(reference_dir: &str, name: &str) -> Result<DiffInfo, super::Error> {
+    let path = std::path::Path::new(reference_dir).join(format!("{}.json", name));
+    if path.exists() {
+        Ok(serde_json::from_slice(&super::read_data(path)?)?)
+    }
+    else {
+        Ok(DiffInfo::default())
+    }
+}
+
+/// Compares the image `<output_dir>/<name>.png` with `<reference_dir>/<name>.png`, on failure a diff image is written to
+/// `<output_dir>/<name>_diff.png` and an error containing the summary is returned. If `bless` is true the reference is
+/// overwritten with the output instead. If `require_reference` is true a missing reference is an error, otherwise the
+/// check is skipped with a message so new tests can land before their references are blessed and checked in
+pub fn check_golden_image(
+    output_dir: &str,
+    reference_dir: &str,
+    name: &str,
+    bless: bool,
+    require_reference: bool) -> Result<(), super::Error> {
+    let output_path = format!("{}/{}.png", output_dir, name);
+    let reference_path = format!("{}/{}.png", reference_dir, name);
+
+    if bless {
+        std::fs::create_dir_all(reference_dir)?;
+        std::fs::copy(&output_path, &reference_path)?;
+        println!("hotline_rs::image_diff:: blessed {}", reference_path);
+        return Ok(());
+    }
+
+    if !std::path::Path::new(&reference_path).exists() {
+        let msg = format!(
+            "hotline_rs::image_diff:: no reference for '{}', run with {}=1 to create {}",
+            name, BLESS_ENV_VAR, reference_path
+        );
+        if require_reference {
+            return Err(super::Error { msg });
+        }
+        println!("{}, skipping", msg);
+        return Ok(());
+    }
+
+    if !std::path::Path::new(&output_path).exists() {
+        return Err(super::Error {
+            msg: format!("hotline_rs::image_diff:: output image {} does not exist", output_path)
+        });
+    }
+
+    let info = load_diff_info(reference_dir, name)?;
+    let reference = image::load_from_file(&reference_path, Some(gfx::Format::RGBA8n))?;
+    let output = image::load_from_file(&output_path, Some(gfx::Format::RGBA8n))?;
+    let result = compare(&reference, &output, &info)?;
+    let summary = result.summary(&info);
+    println!("hotline_rs::image_diff:: {}: {}", name, summary);
+
+    if result.passed {
+        Ok(())
+    }
+    else {
+        let diff_path = format!("{}/{}_diff.png", output_dir, name);
+        image::write_to_file(&diff_path, result.width, result.height, 4, &result.diff_image.data)?;
+        Err(super::Error {
+            msg: format!(
+                "hotline_rs::image_diff:: '{}' does not match its reference: {} (diff: {}, run with {}=1 to bless)",
+                name, summary, diff_path, BLESS_ENV_VAR
+            )
+        })
+    }
+}
+
+/// Checks the output of `Client::run_once` for the instance `name` against the checked in references
+/// blessing them if `HOTLINE_BLESS=1` is set. Demos without a reference are skipped until one is checked in
+pub fn check_client_output(name: &str) -> Result<(), super::Error> {
+    check_golden_image(TEST_OUTPUT_DIR, REFERENCE_DIR, name, bless_requested(), false)
+}
@@ -1410,6 +1410,61 @@ impl<D, A> ImGui<D, A> where D: Device, A: App, D::RenderPipeline: gfx::Pipeline
         }
     }
 
+    /// Modify 3 floats (ie. a vector) via keyboard input
+    pub fn input_float3(&mut self, label: &str, v: &mut [f32; 3]) -> bool {
+        unsafe {
+            let null_label = CString::new(label).unwrap();
+            let fmt = CString::new("%.3f").unwrap();
+            igInputFloat3(
+                null_label.as_ptr(), v.as_mut_ptr(), fmt.as_ptr(), 0)
+        }
+    }
+
+    /// Modify an rgba 0-1 range colour with a colour picker
+    pub fn colour_edit4(&mut self, label: &str, col: &mut [f32; 4]) -> bool {
+        unsafe {
+            let null_label = CString::new(label).unwrap();
+            igColorEdit4(null_label.as_ptr(), col.as_mut_ptr(), 0)
+        }
+    }
+
+    /// Begin a collapsible tree node using ImGuiTreeNodeFlags_ flags, if this returns true populate the children
+    /// and then call `tree_pop`
+    pub fn tree_node_ex(&mut self, label: &str, flags: ImGuiTreeNodeFlags) -> bool {
+        unsafe {
+            let null_label = CString::new(label).unwrap();
+            igTreeNodeExStr(null_label.as_ptr(), flags)
+        }
+    }
+
+    /// Ends a tree node, pairs with `tree_node_ex` when it returns true
+    pub fn tree_pop(&mut self) {
+        unsafe {
+            igTreePop()
+        }
+    }
+
+    /// Returns true if the previous item was clicked with the left mouse button
+    pub fn is_item_clicked(&self) -> bool {
+        unsafe {
+            igIsItemClicked(ImGuiMouseButton_Left as i32)
+        }
+    }
+
+    /// Returns true if the previous tree node was opened or closed this frame
+    pub fn is_item_toggled_open(&self) -> bool {
+        unsafe {
+            igIsItemToggledOpen()
+        }
+    }
+
+    /// Returns true if any item is being interacted with, ie. a slider is being dragged or a text input has focus
+    pub fn is_any_item_active(&self) -> bool {
+        unsafe {
+            igIsAnyItemActive()
+        }
+    }
+
     /// Adds a dummy widget for custom padding
     pub fn dummy(&mut self, w: f32, h: f32) {
         unsafe {
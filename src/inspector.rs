@@ -0,0 +1,411 @@
+use crate::gfx;
+use crate::os;
+use crate::imgui;
+use crate::history;
+use crate::ecs_base::*;
+
+use bevy_ecs::prelude::*;
+use maths_rs::prelude::*;
+
+use std::collections::{HashMap, HashSet};
+
+/// Components which can be edited, added and removed by the `Inspector`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InspectorComponent {
+    Position,
+    Rotation,
+    Scale,
+    Colour,
+    Light,
+    Camera
+}
+
+impl InspectorComponent {
+    pub const ALL: [InspectorComponent; 6] = [
+        InspectorComponent::Position,
+        InspectorComponent::Rotation,
+        InspectorComponent::Scale,
+        InspectorComponent::Colour,
+        InspectorComponent::Light,
+        InspectorComponent::Camera
+    ];
+
+    /// Display name of the component
+    pub fn name(&self) -> &'static str {
+        match self {
+            InspectorComponent::Position => "Position",
+            InspectorComponent::Rotation => "Rotation",
+            InspectorComponent::Scale => "Scale",
+            InspectorComponent::Colour => "Colour",
+            InspectorComponent::Light => "Light",
+            InspectorComponent::Camera => "Camera"
+        }
+    }
+}
+
+/// Entities arranged by `Parent` for display, `roots` and `children` are in entity order
+pub struct EntityTree {
+    pub roots: Vec<Entity>,
+    pub children: HashMap<Entity, Vec<Entity>>
+}
+
+/// Returns true if `entity` has `component`
+pub fn has_component(world: &World, entity: Entity, component: InspectorComponent) -> bool {
+    match component {
+        InspectorComponent::Position => world.get::<Position>(entity).is_some(),
+        InspectorComponent::Rotation => world.get::<Rotation>(entity).is_some(),
+        InspectorComponent::Scale => world.get::<Scale>(entity).is_some(),
+        InspectorComponent::Colour => world.get::<Colour>(entity).is_some(),
+        InspectorComponent::Light => world.get::<LightComponent>(entity).is_some(),
+        InspectorComponent::Camera => world.get::<Camera>(entity).is_some()
+    }
+}
+
+/// Adds `component` to `entity` with a default value. When an entity has a position, rotation and scale it also gets a
+/// `WorldMatrix` so it joins the transform hierarchy, cameras get a `ViewProjectionMatrix` and are added with
+/// `CameraType::None` so they do not respond to input
+pub fn add_component(world: &mut World, entity: Entity, component: InspectorComponent) {
+    let mut entity_mut = world.entity_mut(entity);
+    match component {
+        InspectorComponent::Position => {
+            entity_mut.insert(Position(Vec3f::zero()));
+        }
+        InspectorComponent::Rotation => {
+            entity_mut.insert(Rotation(Quatf::identity()));
+        }
+        InspectorComponent::Scale => {
+            entity_mut.insert(Scale(Vec3f::one()));
+        }
+        InspectorComponent::Colour => {
+            entity_mut.insert(Colour(Vec4f::one()));
+        }
+        InspectorComponent::Light => {
+            entity_mut.insert(LightComponent::default());
+        }
+        InspectorComponent::Camera => {
+            entity_mut.insert((
+                Camera {
+                    rot: Vec3f::zero(),
+                    focus: Vec3f::zero(),
+                    zoom: 1.0,
+                    camera_type: CameraType::None
+                },
+                ViewProjectionMatrix(Mat4f::identity())
+            ));
+        }
+    }
+
+    if entity_mut.contains::<Position>() && entity_mut.contains::<Rotation>() && entity_mut.contains::<Scale>() &&
+        !entity_mut.contains::<WorldMatrix>() {
+        entity_mut.insert(WorldMatrix(Mat34f::identity()));
+    }
+}
+
+/// Removes `component` from `entity`. Removing part of a transform also removes the `LocalMatrix` so the entity keeps
+/// its last `WorldMatrix`, and removing a camera also removes its `ViewProjectionMatrix`
+pub fn remove_component(world: &mut World, entity: Entity, component: InspectorComponent) {
+    let mut entity_mut = world.entity_mut(entity);
+    match component {
+        InspectorComponent::Position => {
+            entity_mut.remove::<(Position, LocalMatrix)>();
+        }
+        InspectorComponent::Rotation => {
+            entity_mut.remove::<(Rotation, LocalMatrix)>();
+        }
+        InspectorComponent::Scale => {
+            entity_mut.remove::<(Scale, LocalMatrix)>();
+        }
+        InspectorComponent::Colour => {
+            entity_mut.remove::<Colour>();
+        }
+        InspectorComponent::Light => {
+            entity_mut.remove::<LightComponent>();
+        }
+        InspectorComponent::Camera => {
+            entity_mut.remove::<(Camera, ViewProjectionMatrix)>();
+        }
+    }
+}
+
+/// Returns the `Name` of `entity` or its index if it does not have one
+pub fn entity_label(world: &World, entity: Entity) -> String {
+    if let Some(name) = world.get::<Name>(entity) {
+        name.0.to_string()
+    }
+    else {
+        format!("entity {}", entity.index())
+    }
+}
+
+fn visit_descendants(stack: &mut Vec<Entity>, children: &HashMap<Entity, Vec<Entity>>, visited: &mut HashSet<Entity>) {
+    while let Some(entity) = stack.pop() {
+        if visited.insert(entity) {
+            if let Some(entity_children) = children.get(&entity) {
+                stack.extend(entity_children);
+            }
+        }
+    }
+}
+
+/// Builds an `EntityTree` of every entity in `world`. Entities whose parent does not exist are roots and entities in
+/// a parent cycle are listed as a root where the cycle is first found, so each entity appears once
+pub fn entity_tree(world: &mut World) -> EntityTree {
+    let mut entities = world.query::<(Entity, Option<&Parent>)>().iter(world)
+        .map(|(entity, parent)| (entity, parent.map(|parent| parent.0)))
+        .collect::<Vec<_>>();
+    entities.sort_by_key(|(entity, _)| *entity);
+
+    let mut roots = Vec::new();
+    let mut children : HashMap<Entity, Vec<Entity>> = HashMap::new();
+    for (entity, parent) in &entities {
+        match parent {
+            Some(parent) if *parent != *entity && world.entities().contains(*parent) => {
+                children.entry(*parent).or_default().push(*entity);
+            }
+            _ => roots.push(*entity)
+        }
+    }
+
+    // visit from the roots, anything not visited is in a cycle and is detached from its parent to become a root
+    let mut visited = HashSet::new();
+    let mut stack = roots.clone();
+    visit_descendants(&mut stack, &children, &mut visited);
+    for (entity, parent) in &entities {
+        if !visited.contains(entity) {
+            if let Some(siblings) = parent.and_then(|parent| children.get_mut(&parent)) {
+                siblings.retain(|child| child != entity);
+            }
+            roots.push(*entity);
+            stack.push(*entity);
+            visit_descendants(&mut stack, &children, &mut visited);
+        }
+    }
+
+    EntityTree {
+        roots,
+        children
+    }
+}
+
+/// Spawns a new named entity with a transform at the origin
+pub fn spawn_entity(world: &mut World) -> Entity {
+    let entity = world.spawn((
+        Position(Vec3f::zero()),
+        Rotation(Quatf::identity()),
+        Scale(Vec3f::one()),
+        WorldMatrix(Mat34f::identity())
+    )).id();
+    world.entity_mut(entity).insert(Name(format!("entity_{}", entity.index())));
+    entity
+}
+
+/// Returns `entity` followed by all of its descendants in the `Parent` hierarchy, parents are before their children
+pub fn descendants(world: &mut World, entity: Entity) -> Vec<Entity> {
+    let tree = entity_tree(world);
+    let mut entities = Vec::new();
+    let mut stack = vec![entity];
+    while let Some(next) = stack.pop() {
+        if let Some(children) = tree.children.get(&next) {
+            stack.extend(children);
+        }
+        entities.push(next);
+    }
+    entities
+}
+
+/// Despawns `entity` and all of its descendants in the `Parent` hierarchy
+pub fn despawn_entity(world: &mut World, entity: Entity) {
+    for next in descendants(world, entity) {
+        world.despawn(next);
+    }
+}
+
+/// Imgui panel listing the entities in a world as a tree with editors for the components of the selected entity
+#[derive(Default)]
+pub struct Inspector {
+    pub selected: Option<Entity>
+}
+
+impl Inspector {
+    /// Adds the inspector widgets to the current imgui window, spawns and despawns are recorded in `history`
+    pub fn ui<D: gfx::Device, A: os::App>(&mut self, imgui: &mut imgui::ImGui<D, A>, world: &mut World, history: &mut history::History)
+        where D::RenderPipeline: gfx::Pipeline {
+        // deselect entities which have been despawned or cleared by a reload
+        if let Some(selected) = self.selected {
+            if !world.entities().contains(selected) {
+                self.selected = None;
+            }
+        }
+
+        if imgui.button(&format!("{} Spawn", font_awesome::strs::PLUS)) {
+            self.selected = Some(history.spawn(world));
+        }
+
+        // the main camera belongs to the client so it can be edited but not despawned
+        let despawnable = self.selected.filter(|entity| world.get::<MainCamera>(*entity).is_none());
+        if let Some(selected) = despawnable {
+            imgui.same_line();
+            if imgui.button(&format!("{} Despawn", font_awesome::strs::TRASH)) {
+                history.despawn(world, selected);
+                self.selected = None;
+            }
+        }
+
+        imgui.separator();
+        let tree = entity_tree(world);
+        for root in &tree.roots {
+            self.entity_tree_ui(imgui, world, &tree, *root);
+        }
+
+        if let Some(selected) = self.selected {
+            imgui.separator();
+            self.components_ui(imgui, world, selected);
+        }
+    }
+
+    fn entity_tree_ui<D: gfx::Device, A: os::App>(
+        &mut self,
+        imgui: &mut imgui::ImGui<D, A>,
+        world: &World,
+        tree: &EntityTree,
+        entity: Entity) where D::RenderPipeline: gfx::Pipeline {
+        let children = tree.children.get(&entity);
+        let mut flags = imgui_sys::ImGuiTreeNodeFlags_OpenOnArrow | imgui_sys::ImGuiTreeNodeFlags_SpanAvailWidth;
+        if children.is_none() {
+            flags |= imgui_sys::ImGuiTreeNodeFlags_Leaf;
+        }
+        if self.selected == Some(entity) {
+            flags |= imgui_sys::ImGuiTreeNodeFlags_Selected;
+        }
+
+        // entity bits keep the imgui id unique when names are shared
+        let label = format!("{}##{}", entity_label(world, entity), entity.to_bits());
+        let open = imgui.tree_node_ex(&label, flags as i32);
+        if imgui.is_item_clicked() && !imgui.is_item_toggled_open() {
+            self.selected = Some(entity);
+        }
+        if open {
+            if let Some(children) = children {
+                for child in children {
+                    self.entity_tree_ui(imgui, world, tree, *child);
+                }
+            }
+            imgui.tree_pop();
+        }
+    }
+
+    fn components_ui<D: gfx::Device, A: os::App>(&mut self, imgui: &mut imgui::ImGui<D, A>, world: &mut World, entity: Entity)
+        where D::RenderPipeline: gfx::Pipeline {
+        imgui.text(&entity_label(world, entity));
+
+        if let Some(position) = world.get::<Position>(entity) {
+            let mut v = [position.x, position.y, position.z];
+            if imgui.input_float3("Position", &mut v) {
+                world.get_mut::<Position>(entity).unwrap().0 = vec3f(v[0], v[1], v[2]);
+            }
+        }
+
+        // edit rotation as euler angles in degrees
+        if let Some(rotation) = world.get::<Rotation>(entity) {
+            let (x, y, z) = rotation.0.to_euler_angles();
+            let mut v = [f32::rad_to_deg(x), f32::rad_to_deg(y), f32::rad_to_deg(z)];
+            if imgui.input_float3("Rotation", &mut v) {
+                world.get_mut::<Rotation>(entity).unwrap().0 = Quatf::from_euler_angles(
+                    f32::deg_to_rad(v[0]), f32::deg_to_rad(v[1]), f32::deg_to_rad(v[2]));
+            }
+        }
+
+        if let Some(scale) = world.get::<Scale>(entity) {
+            let mut v = [scale.x, scale.y, scale.z];
+            if imgui.input_float3("Scale", &mut v) {
+                world.get_mut::<Scale>(entity).unwrap().0 = vec3f(v[0], v[1], v[2]);
+            }
+        }
+
+        if let Some(colour) = world.get::<Colour>(entity) {
+            let mut v = [colour.x, colour.y, colour.z, colour.w];
+            if imgui.colour_edit4("Colour", &mut v) {
+                world.get_mut::<Colour>(entity).unwrap().0 = vec4f(v[0], v[1], v[2], v[3]);
+            }
+        }
+
+        if let Some(mut light) = world.get_mut::<LightComponent>(entity) {
+            imgui.text("Light");
+            let light_types = vec!["Point".to_string(), "Spot".to_string(), "Directional".to_string()];
+            let (_, selected) = imgui.combo_list("Light Type", &light_types, &format!("{:?}", light.light_type));
+            let light_type = match selected.as_str() {
+                "Point" => LightType::Point,
+                "Spot" => LightType::Spot,
+                _ => LightType::Directional
+            };
+            if light_type != light.light_type {
+                light.light_type = light_type;
+            }
+            let mut direction = [light.direction.x, light.direction.y, light.direction.z];
+            if imgui.input_float3("Direction", &mut direction) {
+                light.direction = vec3f(direction[0], direction[1], direction[2]);
+            }
+            let (mut cutoff, mut falloff, mut radius) = (light.cutoff, light.falloff, light.radius);
+            if imgui.slider_float("Cutoff", &mut cutoff, 0.0, f32::pi()) {
+                light.cutoff = cutoff;
+            }
+            if imgui.slider_float("Falloff", &mut falloff, 0.0, 1.0) {
+                light.falloff = falloff;
+            }
+            if imgui.input_float("Radius", &mut radius) {
+                light.radius = radius;
+            }
+        }
+
+        if let Some(mut camera) = world.get_mut::<Camera>(entity) {
+            imgui.text("Camera");
+            let camera_types = vec!["None".to_string(), "Fly".to_string(), "Orbit".to_string(), "Editor".to_string()];
+            let (_, selected) = imgui.combo_list("Camera Type", &camera_types, &format!("{:?}", camera.camera_type));
+            let camera_type = match selected.as_str() {
+                "Fly" => CameraType::Fly,
+                "Orbit" => CameraType::Orbit,
+                "Editor" => CameraType::Editor,
+                _ => CameraType::None
+            };
+            if camera_type != camera.camera_type {
+                camera.camera_type = camera_type;
+            }
+            let mut rot = [camera.rot.x, camera.rot.y, camera.rot.z];
+            if imgui.input_float3("Camera Rotation", &mut rot) {
+                camera.rot = vec3f(rot[0], rot[1], rot[2]);
+            }
+            let mut focus = [camera.focus.x, camera.focus.y, camera.focus.z];
+            if imgui.input_float3("Focus", &mut focus) {
+                camera.focus = vec3f(focus[0], focus[1], focus[2]);
+            }
+            let mut zoom = camera.zoom;
+            if imgui.input_float("Zoom", &mut zoom) {
+                camera.zoom = zoom;
+            }
+        }
+
+        // the main camera is spawned by the client with a fixed set of components
+        if world.get::<MainCamera>(entity).is_some() {
+            return;
+        }
+
+        // add / remove components
+        imgui.separator();
+        let missing = InspectorComponent::ALL.iter()
+            .filter(|component| !has_component(world, entity, **component))
+            .map(|component| component.name().to_string())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            let (_, selected) = imgui.combo_list("Add Component", &missing, "");
+            if let Some(component) = InspectorComponent::ALL.iter().find(|component| component.name() == selected) {
+                add_component(world, entity, *component);
+            }
+        }
+        for component in InspectorComponent::ALL {
+            if has_component(world, entity, component) &&
+                imgui.button(&format!("{} {}##remove", font_awesome::strs::TRASH, component.name())) {
+                remove_component(world, entity, component);
+            }
+        }
+    }
+}
@@ -10,6 +10,9 @@ pub mod av;
 /// Image reading/writing module support for (png, jpg, bmp, tiff, dds).
 pub mod image;
 
+/// Image comparison for golden image tests (tolerances, RMSE and perceptual metrics, diff images, blessing references).
+pub mod image_diff;
+
 /// Imgui rendering and platform implementation.
 pub mod imgui;
 
@@ -19,9 +22,15 @@ pub mod imdraw;
 /// High level graphics (data driven render pipelines, shaders, views).
 pub mod pmfx;
 
+/// Reflection sidecars for precompiled shaders, validated against pipeline and input layouts.
+pub mod shader_reflection;
+
 /// Primitive geometry meshes (quad, cube, sphere, etc).
 pub mod primitives;
 
+/// glTF 2.0 (.gltf, .glb) scene loading for meshes, materials and node hierarchies.
+pub mod gltf;
+
 /// Hotline clinet context contains an `App`, `Device`, `SwapChain` and main `Window` automatically setup
 /// It can load code dynamically from other `dylibs` or `dlls` abnd provides a very thin run loop for you to hook your own plugins into.
 pub mod client;
@@ -35,6 +44,18 @@ pub mod reloader;
 /// Shared types and resources for use with bevy ecs
 pub mod ecs_base;
 
+/// Scene serialisation for saving and loading ecs worlds to versioned json and binary files.
+pub mod scene;
+
+/// Entity inspector imgui panel for browsing entities and editing their components.
+pub mod inspector;
+
+/// Translate, rotate and scale gizmos with mouse picking for editing entity transforms in the main viewport.
+pub mod gizmo;
+
+/// Undo and redo history of entity and session edits.
+pub mod history;
+
 /// Use bitmask for flags
 #[macro_use]
 extern crate bitflags;
@@ -229,6 +250,7 @@ pub mod prelude {
 
         // macros
         hotline_plugin,
+        hotline_plugin_dependencies,
         system_func,
         demos,
         systems
@@ -266,6 +288,7 @@ pub mod prelude {
 
         // macros
         hotline_plugin,
+        hotline_plugin_dependencies,
         system_func,
         demos,
         systems
@@ -284,3 +307,57 @@ pub use gfx::mtl as gfx_platform;
 #[cfg(target_os = "macos")]
 pub use av::null as av_platform;
 
+#[cfg(target_os = "linux")]
+pub mod prelude {
+    #[doc(hidden)]
+    pub use crate::{
+        // modules
+        gfx,
+        os,
+        client,
+        plugin,
+        pmfx,
+        imgui,
+        image,
+
+        // platform specific
+        gfx_platform,
+        os_platform,
+        av_platform,
+
+        // traits
+        ecs_base::*,
+        gfx::{Device, SwapChain, CmdBuf, Texture, RenderPass, Pipeline, Buffer},
+        pmfx::{DrawData, MaterialData, PointLightData, SpotLightData, DirectionalLightData, WorldBufferReserveInfo, WorldBufferInfo},
+        os::{App, Window},
+        pmfx::Pmfx,
+        imgui::ImGui,
+        imdraw::ImDraw,
+        client::{Client, HotlineInfo, PluginInfo},
+        plugin::{Plugin},
+        av::{VideoPlayer},
+
+        // macros
+        hotline_plugin,
+        hotline_plugin_dependencies,
+        system_func,
+        demos,
+        systems
+    };
+}
+
+/// This is a hardcoded compile time selection of os backend for linux as null (headless)
+#[cfg(target_os = "linux")]
+pub use os::null as os_platform;
+
+/// This is a hardcoded compile time selection of gfx backend for linux as null (headless)
+#[cfg(all(target_os = "linux", not(feature = "vulkan")))]
+pub use gfx::null as gfx_platform;
+
+/// Selects the Vulkan gfx backend for linux when the `vulkan` feature is enabled
+#[cfg(all(target_os = "linux", feature = "vulkan"))]
+pub use gfx::vk as gfx_platform;
+
+/// This is a hardcoded compile time selection of av backend for linux as null
+#[cfg(target_os = "linux")]
+pub use av::null as av_platform;
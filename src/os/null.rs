@@ -1,13 +1,7 @@
 #![allow(warnings)]
 
-#[derive(Clone)]
-pub struct Window;
-
-#[derive(Clone)]
-pub struct NativeHandle;
-
-#[derive(Clone)]
-pub struct App;
+//! A headless os backend with a virtual window and monitor. Mouse and keyboard state is driven by a scripted
+//! timeline of `InputFrame`s (which can be loaded from json) so input can be played back deterministically in tests.
 
 use super::Point as Point;
 use super::Size as Size;
@@ -23,84 +17,460 @@ use super::Cursor;
 use super::MonitorInfo;
 use super::OpenFileDialogFlags;
 
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Virtual key codes match win32 so scripts and `App::get_key_code` are interchangeable between platforms
+const VK_BACK: i32 = 0x08;
+const VK_TAB: i32 = 0x09;
+const VK_RETURN: i32 = 0x0D;
+const VK_ESCAPE: i32 = 0x1B;
+const VK_SPACE: i32 = 0x20;
+const VK_PRIOR: i32 = 0x21;
+const VK_NEXT: i32 = 0x22;
+const VK_END: i32 = 0x23;
+const VK_HOME: i32 = 0x24;
+const VK_LEFT: i32 = 0x25;
+const VK_UP: i32 = 0x26;
+const VK_RIGHT: i32 = 0x27;
+const VK_DOWN: i32 = 0x28;
+const VK_INSERT: i32 = 0x2D;
+const VK_DELETE: i32 = 0x2E;
+
+/// Size of the single virtual monitor reported by `enumerate_display_monitors`
+const MONITOR_WIDTH: i32 = 1920;
+const MONITOR_HEIGHT: i32 = 1080;
+
+/// A single input event applied during `App::run`. In json events are tagged by `type`,
+/// ie. `{"type": "MouseMove", "x": 10, "y": 20}` or `{"type": "Key", "code": 87, "down": true}`
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum InputEvent {
+    /// Move the mouse to the absolute screen position
+    MouseMove { x: i32, y: i32 },
+    /// Press or release a mouse button, `button` indexes into `MouseButton` (0 = Left, 1 = Middle, 2 = Right, 3 = X1, 4 = X2)
+    MouseButton { button: usize, down: bool },
+    /// Scroll the vertical mouse wheel this frame
+    MouseWheel { delta: f32 },
+    /// Scroll the horizontal mouse wheel this frame
+    MouseHWheel { delta: f32 },
+    /// Press or release a key by virtual key code (win32 codes, ascii upper case for letters and numbers)
+    Key { code: u8, down: bool },
+    /// Press or release a system key, `key` indexes into `SysKey` (0 = Ctrl, 1 = Shift, 2 = Alt)
+    SysKey { key: usize, down: bool },
+    /// Text input which is returned utf-16 encoded from `get_utf16_input`
+    Text { text: String },
+    /// Resize the window at index `window` (in order of creation)
+    Resize {
+        #[serde(default)]
+        window: usize,
+        width: i32,
+        height: i32
+    },
+    /// Move the window at index `window` (in order of creation)
+    Move {
+        #[serde(default)]
+        window: usize,
+        x: i32,
+        y: i32
+    },
+    /// Change focus of the window at index `window` (in order of creation)
+    Focus {
+        #[serde(default)]
+        window: usize,
+        focused: bool
+    },
+    /// Request the window at index `window` to close
+    Close {
+        #[serde(default)]
+        window: usize
+    },
+    /// Request to exit the application, `run` will return false
+    Exit { code: i32 },
+}
+
+/// A list of events which are applied on the `frame` (the number of times `App::run` has been called, starting at 0)
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct InputFrame {
+    pub frame: u64,
+    pub events: Vec<InputEvent>,
+}
+
+/// Mutable window state shared between clones of a `Window` and the owning `App`
+struct WindowState {
+    title: String,
+    rect: Rect<i32>,
+    style: WindowStyleFlags,
+    visible: bool,
+    focused: bool,
+    minimised: bool,
+    dpi_scale: f32,
+}
+
+type WindowStateRef = Arc<Mutex<WindowState>>;
+
+#[derive(Clone)]
+pub struct Window {
+    id: usize,
+    state: WindowStateRef,
+    mouse_pos: Point<i32>,
+    events: WindowEventFlags,
+}
+
+#[derive(Clone)]
+pub struct NativeHandle {
+    id: usize,
+}
+
+#[derive(Clone)]
+pub struct App {
+    name: String,
+    dpi_scale: f32,
+    frame: u64,
+    exit_code: Option<i32>,
+    next_window_id: usize,
+    windows: Vec<(usize, WindowStateRef)>,
+    events: HashMap<usize, WindowEventFlags>,
+    script: Vec<InputFrame>,
+    pending: Vec<InputEvent>,
+    console_rect: Arc<Mutex<Rect<i32>>>,
+    mouse_pos: Point<i32>,
+    mouse_pos_delta: Point<i32>,
+    next_mouse_pos: Point<i32>,
+    mouse_down: [bool; MouseButton::Count as usize],
+    mouse_wheel: f32,
+    mouse_hwheel: f32,
+    utf16_inputs: Vec<u16>,
+    key_down: [bool; 256],
+    key_press: [bool; 256],
+    key_debounce: [bool; 256],
+    sys_key_down: [bool; SysKey::Count as usize],
+    sys_key_press: [bool; SysKey::Count as usize],
+    sys_key_debounce: [bool; SysKey::Count as usize],
+    keyboard_input_enabled: bool,
+    mouse_input_enabled: bool,
+}
+
+impl App {
+    /// Sets the scripted input timeline, events are applied when `run` reaches each `InputFrame::frame`
+    pub fn set_input_script(&mut self, script: Vec<InputFrame>) {
+        self.script = script;
+        self.script.sort_by_key(|f| f.frame);
+    }
+
+    /// Loads a scripted input timeline from a json file containing an array of `InputFrame`
+    pub fn load_input_script(&mut self, filepath: &str) -> Result<(), Error> {
+        let data = std::fs::read(filepath).map_err(|e| Error {
+            msg: format!("hotline_rs::os::null: failed to read input script {}: {}", filepath, e)
+        })?;
+        let script: Vec<InputFrame> = serde_json::from_slice(&data).map_err(|e| Error {
+            msg: format!("hotline_rs::os::null: failed to parse input script {}: {}", filepath, e)
+        })?;
+        self.set_input_script(script);
+        Ok(())
+    }
+
+    /// Queues events to be applied on the next call to `run`, regardless of the script timeline
+    pub fn push_input_events(&mut self, events: Vec<InputEvent>) {
+        self.pending.extend(events);
+    }
+
+    /// Returns the number of times `run` has been called
+    pub fn get_frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Returns the exit code if the app has been requested to exit
+    pub fn get_exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    fn window_state(&self, index: usize) -> Option<(usize, WindowStateRef)> {
+        self.windows.get(index).map(|(id, state)| (*id, state.clone()))
+    }
+
+    fn add_window_event(&mut self, id: usize, flags: WindowEventFlags) {
+        let events = self.events.entry(id).or_insert(WindowEventFlags::NONE);
+        *events |= flags;
+    }
+
+    fn apply_event(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::MouseMove { x, y } => {
+                self.next_mouse_pos = Point { x, y };
+            }
+            InputEvent::MouseButton { button, down } => {
+                if button < self.mouse_down.len() {
+                    self.mouse_down[button] = down;
+                }
+            }
+            InputEvent::MouseWheel { delta } => {
+                self.mouse_wheel += delta;
+            }
+            InputEvent::MouseHWheel { delta } => {
+                self.mouse_hwheel += delta;
+            }
+            InputEvent::Key { code, down } => {
+                self.key_down[code as usize] = down;
+            }
+            InputEvent::SysKey { key, down } => {
+                if key < self.sys_key_down.len() {
+                    self.sys_key_down[key] = down;
+                }
+            }
+            InputEvent::Text { text } => {
+                self.utf16_inputs.extend(text.encode_utf16());
+            }
+            InputEvent::Resize { window, width, height } => {
+                if let Some((id, state)) = self.window_state(window) {
+                    let mut state = state.lock().unwrap();
+                    state.rect.width = width;
+                    state.rect.height = height;
+                    state.minimised = width == 0 || height == 0;
+                    drop(state);
+                    self.add_window_event(id, WindowEventFlags::SIZE);
+                }
+            }
+            InputEvent::Move { window, x, y } => {
+                if let Some((id, state)) = self.window_state(window) {
+                    let mut state = state.lock().unwrap();
+                    state.rect.x = x;
+                    state.rect.y = y;
+                    drop(state);
+                    self.add_window_event(id, WindowEventFlags::MOVE);
+                }
+            }
+            InputEvent::Focus { window, focused } => {
+                if focused {
+                    for (_, state) in &self.windows {
+                        state.lock().unwrap().focused = false;
+                    }
+                }
+                if let Some((_, state)) = self.window_state(window) {
+                    state.lock().unwrap().focused = focused;
+                }
+            }
+            InputEvent::Close { window } => {
+                if let Some((id, _)) = self.window_state(window) {
+                    self.add_window_event(id, WindowEventFlags::CLOSE);
+                }
+            }
+            InputEvent::Exit { code } => {
+                super::App::exit(self, code);
+            }
+        }
+    }
+
+    fn update_input(&mut self) {
+        // reset input state
+        self.mouse_wheel = 0.0;
+        self.mouse_hwheel = 0.0;
+        self.utf16_inputs.clear();
+
+        // apply queued and scripted events for this frame
+        let mut events = std::mem::take(&mut self.pending);
+        for frame in &self.script {
+            if frame.frame == self.frame {
+                events.extend(frame.events.iter().cloned());
+            }
+        }
+        for event in events {
+            self.apply_event(event);
+        }
+
+        // mouse pos delta
+        self.mouse_pos_delta = Point {
+            x: self.next_mouse_pos.x - self.mouse_pos.x,
+            y: self.next_mouse_pos.y - self.mouse_pos.y,
+        };
+        self.mouse_pos = self.next_mouse_pos;
+
+        let debounce_keys = |count: usize, down: &[bool], press: &mut [bool], debounce: &mut [bool]| {
+            for i in 0..count {
+                // set the key press in the first instance of the frame
+                if down[i] && !press[i] && !debounce[i] {
+                    press[i] = true;
+                    debounce[i] = true;
+                }
+                else if press[i] {
+                    // unset the press
+                    press[i] = false;
+                }
+                else if !down[i] {
+                    // debounce the press
+                    debounce[i] = false;
+                }
+            }
+        };
+
+        debounce_keys(
+            256,
+            &self.key_down,
+            &mut self.key_press,
+            &mut self.key_debounce
+        );
+
+        debounce_keys(
+            SysKey::Count as usize,
+            &self.sys_key_down,
+            &mut self.sys_key_press,
+            &mut self.sys_key_debounce
+        );
+    }
+}
+
 impl super::Window<App> for Window {
     fn bring_to_front(&self) {
-        unimplemented!()
+        self.state.lock().unwrap().visible = true;
     }
+
     fn show(&self, show: bool, activate: bool)  {
-        unimplemented!()
+        let mut state = self.state.lock().unwrap();
+        state.visible = show;
+        if activate {
+            state.focused = true;
+        }
     }
+
     fn update(&mut self, app: &mut App) {
-        unimplemented!()
+        // take events
+        if let Some(window_events) = app.events.get_mut(&self.id) {
+            self.events = *window_events;
+            *window_events = WindowEventFlags::NONE;
+        }
+        self.mouse_pos = app.mouse_pos;
     }
+
     fn close(&mut self)  {
-        unimplemented!()
+        self.state.lock().unwrap().visible = false;
     }
+
     fn update_style(&mut self, flags: WindowStyleFlags, rect: Rect<i32>)  {
-        unimplemented!()
+        let mut state = self.state.lock().unwrap();
+        state.style = flags;
+        state.rect = rect;
     }
+
     fn is_focused(&self) -> bool {
-        unimplemented!()
+        self.state.lock().unwrap().focused
     }
+
     fn is_minimised(&self) -> bool {
-        unimplemented!()
+        self.state.lock().unwrap().minimised
     }
+
     fn set_focused(&self) {
-        unimplemented!()
+        self.state.lock().unwrap().focused = true;
     }
+
     fn is_mouse_hovered(&self) -> bool {
-        unimplemented!()
+        let rect = self.state.lock().unwrap().rect;
+        self.mouse_pos.x >= rect.x && self.mouse_pos.x < rect.x + rect.width &&
+        self.mouse_pos.y >= rect.y && self.mouse_pos.y < rect.y + rect.height
     }
+
     fn set_title(&self, title: String) {
-        unimplemented!()
+        self.state.lock().unwrap().title = title;
     }
+
     fn set_pos(&self, pos: Point<i32>) {
-        unimplemented!()
+        let mut state = self.state.lock().unwrap();
+        state.rect.x = pos.x;
+        state.rect.y = pos.y;
     }
+
     fn set_size(&self, size: Size<i32>) {
-        unimplemented!()
+        let mut state = self.state.lock().unwrap();
+        state.rect.width = size.x;
+        state.rect.height = size.y;
     }
+
     fn get_pos(&self) -> Point<i32> {
-        unimplemented!()
+        let rect = self.state.lock().unwrap().rect;
+        Point {
+            x: rect.x,
+            y: rect.y
+        }
     }
+
     fn get_viewport_rect(&self) -> Rect<i32> {
-        unimplemented!()
+        let rect = self.state.lock().unwrap().rect;
+        Rect {
+            x: 0,
+            y: 0,
+            width: rect.width,
+            height: rect.height
+        }
     }
+
     fn get_size(&self) -> Size<i32> {
-        unimplemented!()
+        let rect = self.state.lock().unwrap().rect;
+        Size {
+            x: rect.width,
+            y: rect.height
+        }
     }
+
     fn get_window_rect(&self) -> Rect<i32> {
-        unimplemented!()
+        self.state.lock().unwrap().rect
     }
+
     fn get_mouse_client_pos(&self, mouse_pos: Point<i32>) -> Point<i32> {
-        unimplemented!()
+        let rect = self.state.lock().unwrap().rect;
+        Point {
+            x: mouse_pos.x - rect.x,
+            y: mouse_pos.y - rect.y
+        }
     }
+
     fn get_dpi_scale(&self) -> f32 {
-        unimplemented!()
+        self.state.lock().unwrap().dpi_scale
     }
+
     fn get_native_handle(&self) -> NativeHandle {
-        unimplemented!()
+        NativeHandle {
+            id: self.id
+        }
     }
+
     fn get_events(&self) -> WindowEventFlags {
-        unimplemented!()
+        self.events
     }
+
     fn clear_events(&mut self)  {
-        unimplemented!()
+        self.events = WindowEventFlags::NONE;
     }
+
     fn as_ptr(&self) -> *const Self  {
-        unimplemented!()
+        self as *const Self
     }
+
     fn as_mut_ptr(&mut self) -> *mut Self  {
-        unimplemented!()
+        self as *mut Self
+    }
+}
+
+impl Window {
+    /// Returns the title set on the window
+    pub fn get_title(&self) -> String {
+        self.state.lock().unwrap().title.to_string()
+    }
+
+    /// Returns true if the window is currently shown
+    pub fn is_visible(&self) -> bool {
+        self.state.lock().unwrap().visible
     }
 }
 
 impl super::NativeHandle<App> for NativeHandle {
     fn get_isize(&self) -> isize {
-        unimplemented!()
+        self.id as isize
     }
+
     fn copy(&self) -> Self {
-        unimplemented!()
+        self.clone()
     }
 }
 
@@ -109,94 +479,178 @@ impl super::App for App {
     type NativeHandle = NativeHandle;
 
     fn create(info: super::AppInfo) -> Self {
-        unimplemented!()
+        App {
+            name: info.name,
+            dpi_scale: 1.0,
+            frame: 0,
+            exit_code: None,
+            next_window_id: 1,
+            windows: Vec::new(),
+            events: HashMap::new(),
+            script: Vec::new(),
+            pending: Vec::new(),
+            console_rect: Arc::new(Mutex::new(Rect {
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 0
+            })),
+            mouse_pos: Point::default(),
+            mouse_pos_delta: Point::default(),
+            next_mouse_pos: Point::default(),
+            mouse_down: [false; MouseButton::Count as usize],
+            mouse_wheel: 0.0,
+            mouse_hwheel: 0.0,
+            utf16_inputs: Vec::new(),
+            key_down: [false; 256],
+            key_press: [false; 256],
+            key_debounce: [false; 256],
+            sys_key_down: [false; SysKey::Count as usize],
+            sys_key_press: [false; SysKey::Count as usize],
+            sys_key_debounce: [false; SysKey::Count as usize],
+            keyboard_input_enabled: true,
+            mouse_input_enabled: true,
+        }
     }
 
     fn create_window(&mut self, info: WindowInfo<Self>) -> Self::Window {
-        unimplemented!()
+        let id = self.next_window_id;
+        self.next_window_id += 1;
+        let state = Arc::new(Mutex::new(WindowState {
+            title: info.title,
+            rect: info.rect,
+            style: info.style,
+            visible: true,
+            // the first window takes focus, as the os would give focus to a newly launched app
+            focused: self.windows.is_empty(),
+            minimised: false,
+            dpi_scale: self.dpi_scale,
+        }));
+        self.windows.push((id, state.clone()));
+        Window {
+            id,
+            state,
+            mouse_pos: self.mouse_pos,
+            events: WindowEventFlags::NONE,
+        }
     }
 
     fn destroy_window(&mut self, window: &Self::Window) {
-        unimplemented!()
+        self.windows.retain(|(id, _)| *id != window.id);
+        self.events.remove(&window.id);
     }
 
     fn run(&mut self) -> bool  {
-        unimplemented!()
+        self.update_input();
+        self.frame += 1;
+        self.exit_code.is_none()
     }
 
     fn exit(&mut self, exit_code: i32) {
-        unimplemented!()
+        println!("hotline_rs::os::null:: exiting with code ({})", exit_code);
+        self.exit_code = Some(exit_code);
     }
 
     fn get_mouse_pos(&self) -> Point<i32> {
-        unimplemented!()
+        self.mouse_pos
     }
 
     fn get_mouse_wheel(&self) -> f32 {
-        unimplemented!()
+        self.mouse_wheel
     }
 
     fn get_mouse_hwheel(&self) -> f32 {
-        unimplemented!()
+        self.mouse_hwheel
     }
 
     fn get_mouse_buttons(&self) -> [bool; MouseButton::Count as usize] {
-        unimplemented!()
+        self.mouse_down
     }
 
     fn get_mouse_pos_delta(&self) -> Size<i32> {
-        unimplemented!()
+        self.mouse_pos_delta
     }
 
     fn get_utf16_input(&self) -> Vec<u16> {
-        unimplemented!()
+        self.utf16_inputs.to_vec()
     }
 
     fn get_keys_down(&self) -> [bool; 256] {
-        unimplemented!()
+        self.key_down
     }
 
     fn get_keys_pressed(&self) -> [bool; 256] {
-        unimplemented!()
+        self.key_press
     }
 
     fn is_sys_key_down(&self, key: SysKey) -> bool {
-        unimplemented!()
+        self.sys_key_down[key as usize]
     }
 
     fn is_sys_key_pressed(&self, key: SysKey) -> bool {
-        unimplemented!()
+        self.sys_key_press[key as usize]
     }
 
     fn get_key_code(key: Key) -> i32 {
-        unimplemented!()
+        match key {
+            Key::Tab => VK_TAB,
+            Key::Left => VK_LEFT,
+            Key::Right => VK_RIGHT,
+            Key::Up => VK_UP,
+            Key::Down => VK_DOWN,
+            Key::PageUp => VK_PRIOR,
+            Key::PageDown => VK_NEXT,
+            Key::Home => VK_HOME,
+            Key::End => VK_END,
+            Key::Insert => VK_INSERT,
+            Key::Delete => VK_DELETE,
+            Key::Backspace => VK_BACK,
+            Key::Space => VK_SPACE,
+            Key::Enter => VK_RETURN,
+            Key::Escape => VK_ESCAPE,
+            Key::KeyPadEnter => VK_RETURN,
+        }
     }
 
     fn set_input_enabled(&mut self, keyboard: bool, mouse: bool) {
-        unimplemented!()
+        self.keyboard_input_enabled = keyboard;
+        self.mouse_input_enabled = mouse;
     }
 
     fn get_input_enabled(&self) -> (bool, bool) {
-        unimplemented!()
+        (self.keyboard_input_enabled, self.mouse_input_enabled)
     }
 
     fn enumerate_display_monitors(&self) -> Vec<MonitorInfo> {
-        unimplemented!()
+        let rect = Rect {
+            x: 0,
+            y: 0,
+            width: MONITOR_WIDTH,
+            height: MONITOR_HEIGHT
+        };
+        vec![MonitorInfo {
+            rect,
+            client_rect: rect,
+            dpi_scale: self.dpi_scale,
+            primary: true
+        }]
     }
 
     fn set_cursor(&self, cursor: &Cursor) {
-        unimplemented!()
+        // there is no cursor to display
     }
 
     fn open_file_dialog(flags: OpenFileDialogFlags, exts: Vec<&str>) -> Result<Vec<String>, Error> {
-        unimplemented!()
+        Err(Error {
+            msg: "hotline_rs::os::null: open_file_dialog is not available on a headless app".to_string()
+        })
     }
 
     fn get_console_window_rect(&self) -> Rect<i32> {
-        unimplemented!()
+        *self.console_rect.lock().unwrap()
     }
 
     fn set_console_window_rect(&self, rect: Rect<i32>) {
-        unimplemented!()
+        *self.console_rect.lock().unwrap() = rect;
     }
-}
\ No newline at end of file
+}
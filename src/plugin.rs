@@ -6,6 +6,10 @@ use crate::reloader;
 use std::process::ExitStatus;
 use std::process::Command;
 use std::io::{self, Write};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
 
 /// General dll plugin responder, will check for source code changes and run cargo build to re-build the library
 pub struct PluginReloadResponder {
@@ -16,7 +20,429 @@ pub struct PluginReloadResponder {
     /// Full path to the build binary dylib or dll
     pub output_filepath: String,
     /// Array of source code files to track and check for changes
-    pub files: Vec<String>
+    pub files: Vec<String>,
+    /// The plugin manifest which specifies the cargo package, profile and source / data files to watch
+    pub manifest: PluginManifest,
+    /// Compiler diagnostics from the last build
+    pub diagnostics: Vec<reloader::BuildDiagnostic>
+}
+
+/// File name of the plugin manifest which is located in the root directory of a plugin next to its `Cargo.toml`
+pub const MANIFEST_FILE: &str = "hotline_plugin.json";
+
+/// Serialisable plugin manifest loaded from `MANIFEST_FILE`, all members are optional and plugins without a manifest
+/// use the defaults, relative paths are relative to the directory containing the manifest.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PluginManifest {
+    /// Name of the plugin, defaults to the name of the plugin directory
+    #[serde(default)]
+    pub name: String,
+    /// Version of the plugin for information
+    pub version: Option<String>,
+    /// Cargo package name to build with `cargo build -p <package>`, defaults to `name`
+    pub package: Option<String>,
+    /// Cargo profile to build with, defaults to the profile the client was built with (`debug` or `release`)
+    pub profile: Option<String>,
+    /// Directory to run `cargo build` from, defaults to the nearest directory containing a cargo workspace
+    pub workspace: Option<String>,
+    /// Cargo target directory where built libs are located, defaults to `<workspace>/target`
+    pub target_dir: Option<String>,
+    /// Glob patterns of source files to watch which will trigger a rebuild when changed
+    #[serde(default = "default_manifest_sources")]
+    pub sources: Vec<String>,
+    /// Glob patterns of extra data files to watch which will trigger a rebuild when changed
+    #[serde(default)]
+    pub data: Vec<String>,
+    /// Names of other plugins this plugin depends on, combined with any exported by `hotline_plugin_dependencies!`
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    /// Requirement on the version of hotline the plugin is compatible with, ie: `"0.3"`, `">=0.3.1, <0.5"`
+    pub hotline_version: Option<String>,
+    /// Absolute directory of the plugin, which relative paths are resolved from
+    #[serde(skip)]
+    pub dir: String
+}
+
+fn default_manifest_sources() -> Vec<String> {
+    vec![
+        "src/**/*.rs".to_string()
+    ]
+}
+
+impl PluginManifest {
+    /// Returns the default manifest for a plugin located in `dir`
+    pub fn new(dir: &Path) -> Self {
+        let mut manifest : PluginManifest = serde_json::from_str("{}").unwrap();
+        manifest.set_dir(dir);
+        manifest
+    }
+
+    /// Loads a manifest from `path` which can be a plugin directory or a file inside the plugin directory, for example
+    /// the manifest itself or the `Cargo.toml`. If the plugin directory contains no manifest the defaults are used
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, super::Error> {
+        let path = path.as_ref();
+        let dir = if path.is_file() {
+            path.parent().unwrap_or(Path::new("."))
+        }
+        else {
+            path
+        };
+
+        if !dir.is_dir() {
+            return Err(super::Error {
+                msg: format!("hotline_rs::plugin:: plugin directory not found: {}", dir.display())
+            });
+        }
+
+        let manifest_path = dir.join(MANIFEST_FILE);
+        if manifest_path.exists() {
+            let data = super::read_data(&manifest_path)?;
+            let mut manifest : PluginManifest = serde_json::from_slice(&data).map_err(|e| super::Error {
+                msg: format!("hotline_rs::plugin:: failed to parse manifest {}: {}", manifest_path.display(), e)
+            })?;
+            manifest.set_dir(dir);
+            Ok(manifest)
+        }
+        else {
+            Ok(Self::new(dir))
+        }
+    }
+
+    /// Sets the plugin directory and fills in the default name if it was not specified
+    fn set_dir(&mut self, dir: &Path) {
+        let dir = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        self.dir = dir.to_str().unwrap().replace("\\\\?\\", "");
+        if self.name.is_empty() {
+            if let Some(name) = dir.file_name() {
+                self.name = name.to_string_lossy().to_string();
+            }
+        }
+    }
+
+    /// Resolves `path` relative to the plugin directory
+    fn resolve_path(&self, path: &str) -> PathBuf {
+        let path = PathBuf::from(path);
+        if path.is_absolute() {
+            path
+        }
+        else {
+            PathBuf::from(&self.dir).join(path)
+        }
+    }
+
+    /// Returns the cargo package name
+    pub fn get_package(&self) -> String {
+        self.package.clone().unwrap_or_else(|| self.name.to_string())
+    }
+
+    /// Returns the name of the built library without prefix or extension, cargo replaces `-` with `_`
+    pub fn get_lib_name(&self) -> String {
+        self.get_package().replace('-', "_")
+    }
+
+    /// Returns the cargo profile name used to build the plugin
+    pub fn get_profile(&self) -> String {
+        self.profile.clone().unwrap_or_else(|| super::get_config_name().to_string())
+    }
+
+    /// Returns the directory to run `cargo build` from
+    pub fn get_workspace_dir(&self) -> PathBuf {
+        if let Some(workspace) = &self.workspace {
+            self.resolve_path(workspace)
+        }
+        else {
+            find_cargo_workspace(Path::new(&self.dir)).unwrap_or_else(|| PathBuf::from(&self.dir))
+        }
+    }
+
+    /// Returns the directory the built library is output to
+    pub fn get_lib_dir(&self) -> PathBuf {
+        let target_dir = if let Some(target_dir) = &self.target_dir {
+            self.resolve_path(target_dir)
+        }
+        else {
+            self.get_workspace_dir().join("target")
+        };
+        // cargo outputs the `dev` and `test` profiles to `debug` and `bench` to `release`
+        let profile = self.get_profile();
+        let profile_dir = match profile.as_str() {
+            "dev" | "test" => "debug",
+            "bench" => "release",
+            _ => profile.as_str()
+        };
+        target_dir.join(profile_dir)
+    }
+
+    /// Returns the full path to the built library
+    pub fn get_lib_filepath(&self) -> PathBuf {
+        #[cfg(target_os = "windows")]
+        let lib_file = format!("{}.dll", self.get_lib_name());
+        #[cfg(target_os = "macos")]
+        let lib_file = format!("lib{}.dylib", self.get_lib_name());
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        let lib_file = format!("lib{}.so", self.get_lib_name());
+        self.get_lib_dir().join(lib_file)
+    }
+
+    /// Returns the existing files matching the `sources` and `data` globs, and the manifest itself if it exists
+    pub fn get_watch_files(&self) -> Vec<String> {
+        let mut patterns = self.sources.to_vec();
+        patterns.extend(self.data.to_vec());
+        let mut files = find_files(Path::new(&self.dir), &patterns);
+        let manifest_path = PathBuf::from(&self.dir).join(MANIFEST_FILE);
+        if manifest_path.exists() {
+            files.push(manifest_path.to_str().unwrap().to_string());
+        }
+        files
+    }
+
+    /// Returns an error if the plugin requires a version of hotline which is not compatible with this one
+    pub fn check_hotline_version(&self) -> Result<(), super::Error> {
+        if let Some(requirement) = &self.hotline_version {
+            let version = env!("CARGO_PKG_VERSION");
+            if !version_matches(requirement, version)? {
+                return Err(super::Error {
+                    msg: format!(
+                        "hotline_rs::plugin:: '{}' requires hotline version '{}' but this is {}",
+                        self.name, requirement, version
+                    )
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the plugin directory for plugin `name` located at `path`, `path` may be relative to `root` and can be the
+/// plugin directory, a file inside it or a directory containing `plugins/<name>` for configs saved by older versions
+pub fn resolve_plugin_dir(root: &str, name: &str, path: &str) -> PathBuf {
+    let path = PathBuf::from(path);
+    let path = if path.is_absolute() {
+        path
+    }
+    else {
+        PathBuf::from(root).join(path)
+    };
+    let path = if path.is_file() {
+        path.parent().unwrap_or(&path).to_path_buf()
+    }
+    else {
+        path
+    };
+    let nested = path.join("plugins").join(name);
+    if nested.is_dir() {
+        nested
+    }
+    else {
+        path
+    }
+}
+
+/// Searches `dir` and its ancestors for a `Cargo.toml` which defines a workspace
+pub fn find_cargo_workspace(dir: &Path) -> Option<PathBuf> {
+    for dir in dir.ancestors() {
+        if let Ok(toml) = std::fs::read_to_string(dir.join("Cargo.toml")) {
+            if toml.lines().any(|line| line.trim() == "[workspace]") {
+                return Some(dir.to_path_buf());
+            }
+        }
+    }
+    None
+}
+
+/// Matches `path` against a glob `pattern`, where paths use `/` separators. `*` matches any characters within a path
+/// component, `?` matches a single character and `**` matches zero or more whole path components
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    fn match_component(pattern: &[char], name: &[char]) -> bool {
+        match pattern.first() {
+            None => name.is_empty(),
+            Some('*') => (0..=name.len()).any(|i| match_component(&pattern[1..], &name[i..])),
+            Some('?') => !name.is_empty() && match_component(&pattern[1..], &name[1..]),
+            Some(c) => name.first() == Some(c) && match_component(&pattern[1..], &name[1..])
+        }
+    }
+
+    fn match_components(pattern: &[&str], path: &[&str]) -> bool {
+        match pattern.first() {
+            None => path.is_empty(),
+            Some(&"**") => (0..=path.len()).any(|i| match_components(&pattern[1..], &path[i..])),
+            Some(component) => {
+                !path.is_empty() && match_component(
+                    &component.chars().collect::<Vec<char>>(),
+                    &path[0].chars().collect::<Vec<char>>()
+                ) && match_components(&pattern[1..], &path[1..])
+            }
+        }
+    }
+
+    let pattern = pattern.replace('\\', "/");
+    let path = path.replace('\\', "/");
+    let pattern = pattern.split('/').filter(|c| !c.is_empty() && *c != ".").collect::<Vec<&str>>();
+    let path = path.split('/').filter(|c| !c.is_empty() && *c != ".").collect::<Vec<&str>>();
+    match_components(&pattern, &path)
+}
+
+/// Returns the files inside `dir` matching any of the glob `patterns`, which are relative to `dir`
+pub fn find_files(dir: &Path, patterns: &[String]) -> Vec<String> {
+    if patterns.is_empty() || !dir.is_dir() {
+        return Vec::new();
+    }
+    let mut files = super::get_files_recursive(dir.to_str().unwrap(), Vec::new());
+    files.sort();
+    files.into_iter().filter(|file| {
+        if let Ok(relative) = Path::new(file).strip_prefix(dir) {
+            let relative = relative.to_string_lossy();
+            patterns.iter().any(|pattern| glob_match(pattern, &relative))
+        }
+        else {
+            false
+        }
+    }).collect()
+}
+
+/// Checks if `version` satisfies the comma separated `requirement` with cargo style comparisons: `=`, `>`, `>=`, `<`,
+/// `<=`, `~`, `^` or `*`. Requirements without an operator are treated as `^` (compatible with)
+pub fn version_matches(requirement: &str, version: &str) -> Result<bool, super::Error> {
+    fn parse(version: &str, requirement: &str) -> Result<Vec<u64>, super::Error> {
+        // ignore pre-release and build metadata
+        let version = version.split(['-', '+']).next().unwrap_or("");
+        version.split('.').map(|v| v.trim().parse::<u64>()).collect::<Result<Vec<u64>, _>>().map_err(|_| super::Error {
+            msg: format!("hotline_rs::plugin:: invalid version requirement '{}'", requirement)
+        })
+    }
+
+    let version = parse(version, version)?;
+    let component = |v: &Vec<u64>, i: usize| v.get(i).copied().unwrap_or(0);
+    let cmp = |a: &Vec<u64>, b: &Vec<u64>| {
+        (0..3).map(|i| component(a, i).cmp(&component(b, i))).find(|o| o.is_ne()).unwrap_or(std::cmp::Ordering::Equal)
+    };
+
+    for comparator in requirement.split(',') {
+        let comparator = comparator.trim();
+        if comparator.is_empty() || comparator == "*" {
+            continue;
+        }
+        let (op, req) = ["<=", ">=", "=", "<", ">", "~", "^"].iter()
+            .find(|op| comparator.starts_with(*op))
+            .map(|op| (*op, &comparator[op.len()..]))
+            .unwrap_or(("^", comparator));
+        let req = parse(req.trim(), requirement)?;
+        if req.is_empty() || req.len() > 3 {
+            return Err(super::Error {
+                msg: format!("hotline_rs::plugin:: invalid version requirement '{}'", requirement)
+            });
+        }
+
+        // the exclusive upper bound for `~` and `^` requirements
+        let upper = |index: usize| {
+            let mut upper = req.to_vec();
+            upper.truncate(index + 1);
+            upper[index] += 1;
+            upper
+        };
+
+        let ordering = cmp(&version, &req);
+        let matches = match op {
+            "<=" => ordering.is_le(),
+            ">=" => ordering.is_ge(),
+            "<" => ordering.is_lt(),
+            ">" => ordering.is_gt(),
+            "=" => (0..req.len()).all(|i| component(&version, i) == req[i]),
+            "~" => ordering.is_ge() && cmp(&version, &upper(if req.len() > 1 { 1 } else { 0 })).is_lt(),
+            _ => {
+                // caret: the left most non-zero component must match
+                let index = req.iter().position(|v| *v != 0).unwrap_or(req.len() - 1);
+                ordering.is_ge() && cmp(&version, &upper(index)).is_lt()
+            }
+        };
+        if !matches {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Serialisable subset of a cargo `--message-format=json` message
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<RustcDiagnostic>
+}
+
+/// Serialisable subset of a rustc json diagnostic
+#[derive(Deserialize)]
+struct RustcDiagnostic {
+    message: String,
+    level: String,
+    #[serde(default)]
+    spans: Vec<RustcSpan>,
+    rendered: Option<String>
+}
+
+/// Serialisable subset of a rustc json diagnostic span
+#[derive(Deserialize)]
+struct RustcSpan {
+    file_name: String,
+    line_start: u32,
+    line_end: u32,
+    column_start: u32,
+    column_end: u32,
+    is_primary: bool
+}
+
+/// Parses compiler diagnostics from the stdout of `cargo build --message-format=json`, lines which are not json or
+/// are not compiler messages are ignored. Summary messages such as "aborting due to previous error" are skipped
+pub fn parse_cargo_diagnostics(output: &str) -> Vec<reloader::BuildDiagnostic> {
+    let mut diagnostics = Vec::new();
+    for line in output.lines() {
+        let line = line.trim();
+        if !line.starts_with('{') {
+            continue;
+        }
+        let message : CargoMessage = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(_) => continue
+        };
+        if message.reason != "compiler-message" {
+            continue;
+        }
+        if let Some(diagnostic) = message.message {
+            let level = if diagnostic.level.starts_with("error") {
+                reloader::DiagnosticLevel::Error
+            }
+            else if diagnostic.level.starts_with("warning") {
+                reloader::DiagnosticLevel::Warning
+            }
+            else if diagnostic.level == "help" {
+                reloader::DiagnosticLevel::Help
+            }
+            else {
+                reloader::DiagnosticLevel::Note
+            };
+
+            let span = diagnostic.spans.iter().find(|s| s.is_primary).or(diagnostic.spans.first());
+            if span.is_none() && (diagnostic.message.starts_with("aborting due to") || diagnostic.message.ends_with("emitted")) {
+                continue;
+            }
+
+            let build_diagnostic = reloader::BuildDiagnostic {
+                level,
+                message: diagnostic.message,
+                file: span.map(|s| s.file_name.to_string()),
+                line: span.map(|s| s.line_start).unwrap_or(0),
+                column: span.map(|s| s.column_start).unwrap_or(0),
+                line_end: span.map(|s| s.line_end).unwrap_or(0),
+                column_end: span.map(|s| s.column_end).unwrap_or(0),
+                rendered: diagnostic.rendered
+            };
+
+            // the same diagnostic can be reported for multiple targets
+            if !diagnostics.contains(&build_diagnostic) {
+                diagnostics.push(build_diagnostic);
+            }
+        }
+    }
+    diagnostics
 }
 
 /// Public trait for defining a plugin in a another library implement this trait and instantiate it with `hotline_plugin!`
@@ -31,6 +457,41 @@ pub trait Plugin<D: gfx::Device, A: os::App> {
     fn ui(&mut self, client: &mut Client<D, A>);
     // Called when the plugin is to be unloaded, this will clean up
     fn unload(&mut self, client: &mut Client<D, A>);
+    /// Version of the layout of the state returned by `save_state`, saved state is only restored into a reloaded
+    /// plugin with the same version. Bump this when the layout of the saved state changes
+    fn state_version(&self) -> u32 {
+        0
+    }
+    /// Called before `unload` when the plugin is reloading, return state to hand-off to the newly loaded instance
+    /// or an empty vec to start fresh. `serialise_state` can be used to serialise serde types
+    fn save_state(&mut self) -> Vec<u8> {
+        Vec::new()
+    }
+    /// Called on the newly loaded instance after a reload and before `setup` with the state from `save_state`.
+    /// If an error is returned the plugin is created again and starts fresh
+    fn restore_state(&mut self, _state: &[u8]) -> Result<(), super::Error> {
+        Ok(())
+    }
+}
+
+/// State saved by a plugin with `Plugin::save_state` before a reload, tagged with the `Plugin::state_version`
+pub struct SavedPluginState {
+    /// The state version of the plugin which saved the state
+    pub version: u32,
+    /// Opaque state data
+    pub data: Vec<u8>
+}
+
+/// Serialises `state` to be returned from `Plugin::save_state`, returns an empty vec if serialisation fails
+pub fn serialise_state<T: Serialize>(state: &T) -> Vec<u8> {
+    serde_json::to_vec(state).unwrap_or_default()
+}
+
+/// Deserialises `state` passed to `Plugin::restore_state` which was serialised with `serialise_state`
+pub fn deserialise_state<T: serde::de::DeserializeOwned>(state: &[u8]) -> Result<T, super::Error> {
+    serde_json::from_slice(state).map_err(|e| super::Error {
+        msg: format!("hotline_rs::plugin:: failed to deserialise plugin state: {}", e)
+    })
 }
 
 /// Utility function to build all plugins, this can be used to bootstrap them if they don't exist
@@ -68,15 +529,16 @@ impl reloader::ReloadResponder for PluginReloadResponder {
     }
 
     fn get_files(&self) -> Vec<String> {
-        // scan for new files so we can dd them and pickup changes
-        // TODO; this could be more easily be configured in a plugin meta data file
-        let src_path = self.path.to_string() + "/plugins/" + &self.name.to_string() + "/src";
-        let src_files = super::get_files_recursive(&src_path, Vec::new());
+        // scan for new files matching the manifest so we can add them and pickup changes
         let mut result = self.files.to_vec();
-        result.extend(src_files);
+        result.extend(self.manifest.get_watch_files());
         result
     }
 
+    fn get_diagnostics(&self) -> Vec<reloader::BuildDiagnostic> {
+        self.diagnostics.to_vec()
+    }
+
     fn get_last_mtime(&self) -> std::time::SystemTime {
         let meta = std::fs::metadata(&self.output_filepath);
         if meta.is_ok() {
@@ -87,32 +549,39 @@ impl reloader::ReloadResponder for PluginReloadResponder {
         }
     }
 
-    fn build(&mut self) -> ExitStatus {
-        let output = if super::get_config_name() == "release" {
-            Command::new("cargo")
-                .current_dir(&self.path)
-                .arg("build")
-                .arg("--release")
-                .arg("-p")
-                .arg(&self.name)
-                .output()
-                .expect("hotline::hot_lib:: hot lib failed to build!")
+    fn build(&mut self, _changed_files: &[String]) -> ExitStatus {
+        let mut command = Command::new("cargo");
+        command.current_dir(&self.path).arg("build");
+
+        let profile = self.manifest.get_profile();
+        match profile.as_str() {
+            "release" => {
+                command.arg("--release");
+            }
+            "debug" | "dev" => {
+                command.env("CARGO_TERM_COLOR", "always");
+            }
+            _ => {
+                command.arg("--profile").arg(&profile);
+            }
         }
-        else {
-            Command::new("cargo")
-                .current_dir(&self.path)
-                .arg("build")
-                .env("CARGO_TERM_COLOR", "always")
-                .arg("-p")
-                .arg(&self.name)
-                .output()
-                .expect("hotline::hot_lib:: hot lib failed to build!")
-        };
+
+        let output = command
+            .arg("-p")
+            .arg(self.manifest.get_package())
+            .arg("--message-format=json")
+            .output()
+            .expect("hotline::hot_lib:: hot lib failed to build!");
+
+        // stdout contains json messages, output the rendered diagnostics to the console
+        self.diagnostics = parse_cargo_diagnostics(&String::from_utf8_lossy(&output.stdout));
 
         let mut stdout = io::stdout().lock();
 
-        if !output.stdout.is_empty() {
-            stdout.write_all(&output.stdout).unwrap();
+        for diagnostic in &self.diagnostics {
+            if let Some(rendered) = &diagnostic.rendered {
+                stdout.write_all(rendered.as_bytes()).unwrap();
+            }
         }
 
         if !output.stderr.is_empty() {
@@ -147,6 +616,7 @@ macro_rules! hotline_plugin {
 
         // c-abi wrapper for `Plugin::update`
         #[no_mangle]
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
         pub extern "C" fn update(client_ptr: *mut client::Client<gfx_platform::Device, os_platform::App>, ptr: *mut core::ffi::c_void) {
             unsafe {
                 let plugin = ptr.cast::<$input>();
@@ -160,6 +630,7 @@ macro_rules! hotline_plugin {
 
         // c-abi wrapper for `Plugin::setup`
         #[no_mangle]
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
         pub extern "C" fn setup(client: *mut client::Client<gfx_platform::Device, os_platform::App>, ptr: *mut core::ffi::c_void) {
             unsafe {
                 let plugin = ptr.cast::<$input>();
@@ -170,6 +641,7 @@ macro_rules! hotline_plugin {
 
         // c-abi wrapper for `Plugin::unload`
         #[no_mangle]
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
         pub extern "C" fn unload(client: *mut client::Client<gfx_platform::Device, os_platform::App>, ptr: *mut core::ffi::c_void) {
             unsafe {
                 let plugin = ptr.cast::<$input>();
@@ -178,8 +650,58 @@ macro_rules! hotline_plugin {
             }
         }
 
+        // c-abi wrapper for `Plugin::state_version`
+        #[no_mangle]
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
+        pub extern "C" fn state_version(ptr: *mut core::ffi::c_void) -> u32 {
+            unsafe {
+                let plugin = ptr.cast::<$input>();
+                let plugin = plugin.as_mut().unwrap();
+                plugin.state_version()
+            }
+        }
+
+        // c-abi wrapper for `Plugin::save_state`, the saved state is written to `state`
+        #[no_mangle]
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
+        pub extern "C" fn save_state(ptr: *mut core::ffi::c_void, state: *mut Vec<u8>) {
+            unsafe {
+                let plugin = ptr.cast::<$input>();
+                let plugin = plugin.as_mut().unwrap();
+                *state = plugin.save_state();
+            }
+        }
+
+        // c-abi wrapper for `Plugin::restore_state`, returns false if the state could not be restored
+        #[no_mangle]
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
+        pub extern "C" fn restore_state(ptr: *mut core::ffi::c_void, data: *const u8, len: usize) -> bool {
+            unsafe {
+                let plugin = ptr.cast::<$input>();
+                let plugin = plugin.as_mut().unwrap();
+                match plugin.restore_state(std::slice::from_raw_parts(data, len)) {
+                    Ok(()) => true,
+                    Err(err) => {
+                        println!("hotline_rs::plugin:: [error] {}", err.msg);
+                        false
+                    }
+                }
+            }
+        }
+
+        // c-abi wrapper which replaces the instance in place with a fresh `Plugin::create`, dropping the old plugin
+        #[no_mangle]
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
+        pub extern "C" fn reset_state(ptr: *mut core::ffi::c_void) {
+            unsafe {
+                let plugin = ptr.cast::<$input>();
+                *plugin = $input::create();
+            }
+        }
+
         // c-abi wrapper for `Plugin::ui`
         #[no_mangle]
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
         pub extern "C" fn ui(client: *mut client::Client<gfx_platform::Device, os_platform::App>, ptr: *mut core::ffi::c_void, imgui_ctx: *mut core::ffi::c_void) {
             unsafe {
                 let plugin = ptr.cast::<$input>();
@@ -193,4 +715,132 @@ macro_rules! hotline_plugin {
 
 /// Plugin instances are crated by the `Plugin::create` function, created on the heap
 /// and passed around as a void* through the hotline_plugin macro to become a `Plugin` trait
-pub type PluginInstance = *mut core::ffi::c_void;
\ No newline at end of file
+pub type PluginInstance = *mut core::ffi::c_void;
+/// Name of the symbol a plugin library exports to declare the plugins it depends on, see `hotline_plugin_dependencies!`
+pub const DEPENDENCIES_SYMBOL: &str = "plugin_dependencies";
+
+/// Macro to declare the names of other plugins a plugin library depends on:
+/// hotline_plugin_dependencies!["ecs"];
+///
+/// Dependencies are setup before their dependents, and when a plugin is reloaded only the plugin itself and its
+/// dependents are unloaded and setup again. Plugins which use functions from a library without a plugin instance
+/// should depend on that library, so they are setup again and do not hold on to the old functions when it reloads.
+#[macro_export]
+macro_rules! hotline_plugin_dependencies {
+    ($($dependency:expr),* $(,)?) => {
+        // c-abi export of the plugin dependency names written to `dependencies`, looked up by `plugin::DEPENDENCIES_SYMBOL`
+        #[no_mangle]
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
+        pub extern "C" fn plugin_dependencies(dependencies: *mut Vec<String>) {
+            unsafe {
+                *dependencies = vec![$($dependency.to_string()),*];
+            }
+        }
+    }
+}
+
+/// Describes a plugin as a node in the dependency graph
+pub struct PluginDependencyInfo {
+    /// Name of the plugin
+    pub name: String,
+    /// Names of plugins this plugin depends on
+    pub dependencies: Vec<String>
+}
+
+/// Returns indices into `plugins` ordered so that each plugin comes after all of its dependencies, plugins without
+/// dependencies between them maintain their original order. Returns an error if a dependency is not loaded or if
+/// there is a cycle, with the cycle in the error message
+pub fn sort_plugin_dependencies(plugins: &[PluginDependencyInfo]) -> Result<Vec<usize>, super::Error> {
+    let lookup = plugins.iter().enumerate().map(|(i, p)| (p.name.as_str(), i)).collect::<HashMap<&str, usize>>();
+
+    // validate and resolve dependency names
+    let mut dependencies = Vec::new();
+    for plugin in plugins {
+        let mut resolved = Vec::new();
+        for dependency in &plugin.dependencies {
+            if let Some(index) = lookup.get(dependency.as_str()) {
+                resolved.push(*index);
+            }
+            else {
+                return Err(super::Error {
+                    msg: format!(
+                        "hotline_rs::plugin:: '{}' depends on '{}' which is not loaded",
+                        plugin.name, dependency
+                    )
+                });
+            }
+        }
+        dependencies.push(resolved);
+    }
+
+    // depth first post-order, tracking the current path to report cycles
+    #[derive(Clone, Copy, PartialEq)]
+    enum Visit {
+        None,
+        InProgress,
+        Done
+    }
+
+    fn visit(
+        node: usize,
+        dependencies: &[Vec<usize>],
+        plugins: &[PluginDependencyInfo],
+        state: &mut [Visit],
+        path: &mut Vec<usize>,
+        order: &mut Vec<usize>) -> Result<(), super::Error> {
+        match state[node] {
+            Visit::Done => Ok(()),
+            Visit::InProgress => {
+                let start = path.iter().position(|p| *p == node).unwrap_or(0);
+                let cycle = path[start..].iter()
+                    .chain(std::iter::once(&node))
+                    .map(|p| plugins[*p].name.as_str())
+                    .collect::<Vec<&str>>();
+                Err(super::Error {
+                    msg: format!("hotline_rs::plugin:: dependency cycle detected: {}", cycle.join(" -> "))
+                })
+            }
+            Visit::None => {
+                state[node] = Visit::InProgress;
+                path.push(node);
+                for dependency in &dependencies[node] {
+                    visit(*dependency, dependencies, plugins, state, path, order)?;
+                }
+                path.pop();
+                state[node] = Visit::Done;
+                order.push(node);
+                Ok(())
+            }
+        }
+    }
+
+    let mut state = vec![Visit::None; plugins.len()];
+    let mut order = Vec::new();
+    for i in 0..plugins.len() {
+        visit(i, &dependencies, plugins, &mut state, &mut Vec::new(), &mut order)?;
+    }
+    Ok(order)
+}
+
+/// Returns a mask of the plugins which need to be unloaded and setup again when the plugins in `changed` are reloaded.
+/// This includes the changed plugins and all of their dependents, so plugins holding functions from a library without
+/// an instance are setup again when it reloads. Unknown dependency names are ignored
+pub fn get_plugins_to_resetup(plugins: &[PluginDependencyInfo], changed: &[bool]) -> Vec<bool> {
+    let mut resetup = vec![false; plugins.len()];
+    let mut stack = changed.iter().enumerate().filter(|(_, c)| **c).map(|(i, _)| i).collect::<Vec<usize>>();
+    while let Some(index) = stack.pop() {
+        if resetup[index] {
+            continue;
+        }
+        resetup[index] = true;
+
+        // dependents
+        let name = &plugins[index].name;
+        for (i, plugin) in plugins.iter().enumerate() {
+            if plugin.dependencies.contains(name) {
+                stack.push(i);
+            }
+        }
+    }
+    resetup
+}
@@ -8,6 +8,9 @@ use crate::os;
 use crate::gfx;
 use crate::primitives;
 use crate::image;
+use crate::shader_reflection;
+use crate::shader_reflection::ReflectionError;
+use crate::shader_reflection::ReflectionErrorKind;
 
 use crate::gfx::{ResourceState, RenderPass, CmdBuf, Subresource, QueryHeap, SwapChain, Texture};
 use crate::reloader::{ReloadState, Reloader, ReloadResponder};
@@ -111,8 +114,8 @@ pub struct Mesh<D: gfx::Device> {
 
 /// Additional info to wrap with a texture for tracking changes from windwow sizes or other associated bounds
 struct TrackedTexture<D: gfx::Device>  {
-    /// The texture itself
-    texture: D::Texture,
+    /// The texture itself, transient textures which alias one another share the same texture
+    texture: Arc<D::Texture>,
     /// Optional ratio, which will contain window name and scale info if present
     ratio: Option<TextureSizeRatio>,
     /// Tuple of (width, height, depth) to track the current size of the texture and compare for updates
@@ -127,6 +130,78 @@ struct PmfxTrackingInfo {
     filepath: std::path::PathBuf,
     /// Modified time of the .pmfx file this instance is associated with
     modified_time: SystemTime,
+    /// Normalised paths of the source files the pmfx was built from, to find the pmfx affected by changed files
+    dependencies: HashSet<String>,
+    /// Names of the objects declared in the pmfx, only these are checked for changes when the pmfx reloads
+    contents: PmfxContents
+}
+
+/// Information to track changes to the source image of a texture loaded from `TextureInfo::filepath`
+struct TextureFileTrackingInfo {
+    /// Path to the image file the texture was loaded from
+    filepath: String,
+    /// Modified time of the image file when the texture was loaded
+    modified_time: SystemTime
+}
+
+/// Extensions of the image files which textures can be loaded from, changes to these are reloaded without a data build
+const TEXTURE_FILE_EXTENSIONS: [&str; 8] = ["png", "tga", "jpg", "jpeg", "bmp", "gif", "hdr", "dds"];
+
+/// Returns true if `path` is an image file which a texture can be loaded from
+fn is_texture_file(path: &str) -> bool {
+    Path::new(path).extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| TEXTURE_FILE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Returns the modified time of the file at `path` or now if the file does not exist
+fn get_modified_time(path: &Path) -> SystemTime {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .unwrap_or(SystemTime::now())
+}
+
+/// Names of the objects declared in a single pmfx `File`
+#[derive(Default)]
+struct PmfxContents {
+    shaders: HashSet<String>,
+    pipelines: HashSet<String>,
+    textures: HashSet<String>,
+    views: HashSet<String>
+}
+
+impl PmfxContents {
+    fn from_file(file: &File) -> Self {
+        PmfxContents {
+            shaders: file.shaders.keys().cloned().collect(),
+            pipelines: file.pipelines.keys().cloned().collect(),
+            textures: file.textures.keys().cloned().collect(),
+            views: file.views.keys().cloned().collect()
+        }
+    }
+}
+
+/// Returns `path` in a normalised form so paths to the same file from different sources can be compared
+fn normalise_dependency_path(path: &str) -> String {
+    fs::canonicalize(path)
+        .map(|p| p.to_string_lossy().replace("\\\\?\\", ""))
+        .unwrap_or(path.to_string())
+        .replace('\\', "/")
+}
+
+/// The names of the objects recreated by `Pmfx::reload`
+#[derive(Clone, Default, Debug)]
+pub struct PmfxReloadInfo {
+    /// Names of the pmfx which were reloaded
+    pub pmfx: Vec<String>,
+    /// Textures which were recreated
+    pub textures: Vec<String>,
+    /// Views which were recreated
+    pub views: Vec<String>,
+    /// Shaders which were recreated
+    pub shaders: Vec<String>,
+    /// Pipelines which were recreated
+    pub pipelines: Vec<String>
 }
 
 // pipelines (name) > permutation (mask : u32) which is tuple (build_hash, pipeline)
@@ -147,6 +222,8 @@ pub struct Pmfx<D: gfx::Device> {
     pmfx: File,
     /// Tracking info for check on data reloads, grouped by pmfx name
     pmfx_tracking: HashMap<String, PmfxTrackingInfo>,
+    /// Tracking info for textures loaded from image files, grouped by texture name
+    texture_files: HashMap<String, TextureFileTrackingInfo>,
     /// Folder paths for
     pmfx_folders: HashMap<String, String>,
     /// Updated by calling 'update_window' this will cause any tracked textures to check for resizes and rebuild textures if necessary
@@ -161,6 +238,10 @@ pub struct Pmfx<D: gfx::Device> {
     shaders: HashMap<String, (PmfxHash, D::Shader)>,
     /// Texture map of tracked texture info
     textures: HashMap<String, (PmfxHash, TrackedTexture<D>)>,
+    /// Transient textures which share the texture of another transient texture (alias > owner)
+    texture_aliases: HashMap<String, String>,
+    /// Memory usage of transient textures in the active render graph
+    transient_stats: TransientTextureStats,
     /// Built views that are used in view function dispatches, the source view name which was used to generate the instnace is stored in .2 for hash checking
     views: HashMap<String, TrackedView<D>>,
     // Built compute passes that contain a command buffer and other compute dispatch info
@@ -173,8 +254,16 @@ pub struct Pmfx<D: gfx::Device> {
     world_buffers: DynamicWorldBuffers<D>,
     /// Auto-generated barriers to insert between view passes to ensure correct resource states
     barriers: HashMap<String, D::CmdBuf>,
+    /// Describes what each of the auto-generated `barriers` does, for exporting the built render graph
+    barrier_info: HashMap<String, RenderGraphNode>,
     /// Vector of view names to execute in designated order
     command_queue: Vec<String>,
+    /// Compute passes in the `command_queue` which execute on the async compute queue
+    async_passes: HashSet<String>,
+    /// Nodes in the `command_queue` which must wait for async compute passes to complete before executing
+    async_waits: HashMap<String, Vec<String>>,
+    /// Fences to syncronise the graphics and async compute queues, created when a render graph first uses async compute
+    queue_sync: Option<QueueSync<D>>,
     /// Tracking texture references of views
     view_texture_refs: HashMap<String, HashSet<String>>,
     /// Container to hold overall GPU stats
@@ -195,6 +284,28 @@ pub struct Pmfx<D: gfx::Device> {
     pub push_constant_user_data: [u32; 4],
 }
 
+/// Fences and the last signalled values used to syncronise the graphics queue with the async compute queue
+struct QueueSync<D: gfx::Device> {
+    graphics_fence: D::Fence,
+    graphics_value: u64,
+    compute_fence: D::Fence,
+    compute_value: u64
+}
+
+/// Memory usage of textures marked `transient` in the active render graph, transient textures with compatible
+/// dimensions, format and usage whose lifetimes do not overlap share the same texture
+#[derive(Clone, Default, Debug)]
+pub struct TransientTextureStats {
+    /// Number of transient textures used by the render graph
+    pub transient_textures: usize,
+    /// Number of textures actually allocated to back the transient textures
+    pub physical_textures: usize,
+    /// Size in bytes the transient textures would use without aliasing
+    pub transient_bytes: u64,
+    /// Size in bytes saved by aliasing
+    pub saved_bytes: u64
+}
+
 /// Contains frame statistics from the GPU for all pmfx jobs
 pub struct TotalStats {
     /// Total GPU time spent in milliseconds
@@ -335,6 +446,7 @@ struct TextureInfo {
     cubemap: bool,
     format: gfx::Format,
     usage: Vec<ResourceState>,
+    transient: Option<bool>,
     hash: u64,
 }
 
@@ -434,7 +546,195 @@ struct GraphPassInfo {
     /// The name of a resource a compute shader wil distrubute work into
     target_dimension: Option<String>,
     /// Signify we want cubemap rendering
-    cubemap: Option<bool>
+    cubemap: Option<bool>,
+    /// The queue a compute pass executes on, `async_compute` passes run alongside the graphics queue and are
+    /// syncronised with the passes that follow them automatically
+    queue: Option<gfx::QueueType>
+}
+
+/// Returns true if the graph pass `info` is a compute pass which executes on the async compute queue
+fn is_async_compute_pass(info: &GraphPassInfo) -> bool {
+    info.view.is_none() && info.queue == Some(gfx::QueueType::Compute)
+}
+
+/// Severity of a `GraphDiagnostic`, errors will cause `create_render_graph` to fail or produce incorrect results
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GraphDiagnosticSeverity {
+    Warning,
+    Error
+}
+
+/// The kind of problem found by `Pmfx::validate_render_graph`, containing the name of the offending item
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GraphDiagnosticKind {
+    /// The requested render graph does not exist
+    MissingRenderGraph(String),
+    /// Passes which depend on one another in a loop, listed in dependency order
+    DependencyCycle(Vec<String>),
+    /// A pass `depends_on` a pass which is not in the graph
+    MissingDependency(String),
+    /// A pass references a view which does not exist
+    MissingView(String),
+    /// A pass references a pipeline which does not exist
+    MissingPipeline(String),
+    /// A compute or raytracing pipeline used in a view pass, or a render pipeline used in a compute pass
+    PipelineTypeMismatch(String),
+    /// A pass or view references a texture which does not exist
+    MissingTexture(String),
+    /// A view references a camera which has not been registered with `update_camera_constants`
+    MissingCamera(String),
+    /// A texture is read by a pass which does not depend on any of the passes that write it
+    ReadBeforeWrite(String),
+    /// A texture or pipeline is incompatible with the render targets of a view
+    FormatMismatch(String),
+    /// A texture which is not referenced by any render graph or view
+    UnusedTexture(String),
+    /// A pass requests a queue which cannot execute it, view passes and copy queue passes run on the graphics queue
+    UnsupportedQueue(String)
+}
+
+/// A single problem found by `Pmfx::validate_render_graph`
+#[derive(Clone, Debug)]
+pub struct GraphDiagnostic {
+    pub severity: GraphDiagnosticSeverity,
+    /// The graph pass the problem was found in, `None` for problems with the graph as a whole
+    pub pass: Option<String>,
+    pub kind: GraphDiagnosticKind,
+    /// Human readable description of the problem
+    pub message: String
+}
+
+impl std::fmt::Display for GraphDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            GraphDiagnosticSeverity::Warning => "warning",
+            GraphDiagnosticSeverity::Error => "error"
+        };
+        if let Some(pass) = &self.pass {
+            write!(f, "[{}] {}: {}", severity, pass, self.message)
+        }
+        else {
+            write!(f, "[{}] {}", severity, self.message)
+        }
+    }
+}
+
+/// The type of work a `RenderGraphNode` performs
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum RenderGraphNodeKind {
+    /// A view or compute pass from the .pmfx `render_graphs`
+    #[default]
+    Pass,
+    /// An auto-generated transition barrier
+    Transition,
+    /// An auto-generated msaa resolve
+    Resolve,
+    /// An auto-generated mip map generation step
+    GenerateMips
+}
+
+/// A single entry in the execute order of a built render graph, for passes `view`, `pipelines`, `depends_on` and `uses`
+/// are populated and for auto-generated steps `texture` and the states are populated
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RenderGraphNode {
+    pub name: String,
+    pub kind: RenderGraphNodeKind,
+    pub view: Option<String>,
+    pub pipelines: Vec<String>,
+    pub depends_on: Vec<String>,
+    /// Pairs of (texture, usage) where usage is one of `Write`, `Read`, `ReadMsaa` or `ReadMips`
+    pub uses: Vec<(String, String)>,
+    pub texture: Option<String>,
+    pub state_before: Option<ResourceState>,
+    pub state_after: Option<ResourceState>,
+    /// The queue the node executes on
+    #[serde(default)]
+    pub queue: gfx::QueueType,
+    /// Async compute passes which must complete before the node can execute
+    #[serde(default)]
+    pub waits_on: Vec<String>
+}
+
+/// A built render graph as it will be executed, returned from `Pmfx::export_render_graph`
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RenderGraphExport {
+    pub name: String,
+    /// Nodes in execute order
+    pub nodes: Vec<RenderGraphNode>
+}
+
+impl RenderGraphExport {
+    /// Returns the graph serialised as pretty printed json
+    pub fn to_json(&self) -> Result<String, super::Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Returns the graph in Graphviz DOT format. Passes are boxes, auto-generated steps are ellipses and textures
+    /// are notes, solid edges are `depends_on`, dashed edges are execute order, dotted edges are resource `uses` and
+    /// bold edges are waits on async compute passes
+    pub fn to_dot(&self) -> String {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut dot = format!("digraph {} {{\n    rankdir=LR;\n", quote(&self.name));
+
+        let mut textures = Vec::new();
+        for node in &self.nodes {
+            let (shape, label) = match node.kind {
+                RenderGraphNodeKind::Pass => {
+                    let mut label = node.name.to_string();
+                    if let Some(view) = &node.view {
+                        label += &format!("\\nview: {}", view);
+                    }
+                    if !node.pipelines.is_empty() {
+                        label += &format!("\\npipelines: {}", node.pipelines.join(", "));
+                    }
+                    if node.queue != gfx::QueueType::Graphics {
+                        label += &format!("\\nqueue: {:?}", node.queue);
+                    }
+                    ("box", label)
+                }
+                RenderGraphNodeKind::GenerateMips => {
+                    ("ellipse", format!("generate_mip_maps\\n{}", node.texture.as_deref().unwrap_or("")))
+                }
+                _ => {
+                    let states = match (node.state_before, node.state_after) {
+                        (Some(before), Some(after)) => format!("{} -> {}", before, after),
+                        _ => String::new()
+                    };
+                    ("ellipse", format!("{:?}: {}\\n{}", node.kind, node.texture.as_deref().unwrap_or(""), states))
+                }
+            };
+            dot += &format!("    {} [shape={}, label={}];\n", quote(&node.name), shape, quote(&label));
+
+            for dep in &node.depends_on {
+                dot += &format!("    {} -> {};\n", quote(dep), quote(&node.name));
+            }
+            for pass in &node.waits_on {
+                dot += &format!("    {} -> {} [style=bold, color=red, label=\"wait\"];\n", quote(pass), quote(&node.name));
+            }
+            for (texture, usage) in &node.uses {
+                if !textures.contains(texture) {
+                    textures.push(texture.to_string());
+                }
+                if usage == "Write" {
+                    dot += &format!("    {} -> {} [style=dotted, label={}];\n", quote(&node.name), quote(texture), quote(usage));
+                }
+                else {
+                    dot += &format!("    {} -> {} [style=dotted, label={}];\n", quote(texture), quote(&node.name), quote(usage));
+                }
+            }
+        }
+
+        for texture in textures {
+            dot += &format!("    {} [shape=note];\n", quote(&texture));
+        }
+
+        for pair in self.nodes.windows(2) {
+            dot += &format!("    {} -> {} [style=dashed, color=grey];\n", quote(&pair[0].name), quote(&pair[1].name));
+        }
+
+        dot += "}\n";
+        dot
+    }
 }
 
 /// A GPU buffer type which can resize and stretch like a vector
@@ -777,13 +1077,17 @@ pub fn cubemap_camera_face(face: usize, pos: Vec3f, near: f32, far: f32) -> Came
     }
 }
 
-/// creates a shader from an option of filename, returning optional shader back
+/// creates a shader from an option of filename, returning optional shader back. the shader type is taken from the
+/// reflection sidecar of the shader if it has one
 fn create_shader_from_file<D: gfx::Device>(device: &D, folder: &Path, file: Option<String>) -> Result<Option<D::Shader>, super::Error> {
     if let Some(shader) = file {
         let shader_filepath = folder.join(shader);
-        let shader_data = fs::read(shader_filepath)?;
+        let shader_data = fs::read(&shader_filepath)?;
+        let shader_type = shader_reflection::load_reflection(&shader_filepath)?
+            .map(|reflection| reflection.stage)
+            .unwrap_or(gfx::ShaderType::Vertex);
         let shader_info = gfx::ShaderInfo {
-            shader_type: gfx::ShaderType::Vertex,
+            shader_type,
             compile_info: None
         };
         Ok(Some(device.create_shader(&shader_info, &shader_data)?))
@@ -793,6 +1097,33 @@ fn create_shader_from_file<D: gfx::Device>(device: &D, folder: &Path, file: Opti
     }
 }
 
+/// Validates the shaders of `pipeline` in `folder` against their reflection sidecars, shaders without one are skipped
+fn validate_pipeline_reflection(folder: &Path, pipeline: &Pipeline) -> Result<Vec<ReflectionError>, super::Error> {
+    let mut errors = Vec::new();
+    let stages = [
+        (&pipeline.vs, gfx::ShaderType::Vertex),
+        (&pipeline.ps, gfx::ShaderType::Fragment),
+        (&pipeline.cs, gfx::ShaderType::Compute)
+    ];
+    for (file, stage) in stages {
+        if let Some(file) = file {
+            match shader_reflection::load_reflection(&folder.join(file))? {
+                Some(reflection) => {
+                    errors.extend(reflection.validate(file, stage, &pipeline.pipeline_layout, pipeline.vertex_layout.as_ref()));
+                }
+                None => {
+                    errors.push(ReflectionError {
+                        shader: file.to_string(),
+                        kind: ReflectionErrorKind::MissingReflection(file.to_string()),
+                        message: format!("missing reflection sidecar {}", shader_reflection::get_reflection_filepath(&folder.join(file)).display())
+                    });
+                }
+            }
+        }
+    }
+    Ok(errors)
+}
+
 /// get gfx info from a pmfx state, returning default if it does not exist
 fn info_from_state<T: Default + Clone>(name: &Option<String>, map: &HashMap<String, T>) -> Result<T, super::Error> {
     if let Some(name) = &name {
@@ -989,6 +1320,58 @@ fn get_shader_entry_point_name(shader_name: Option<String>) -> Option<String> {
     }
 }
 
+/// Depth first search through `depends_on` from `pass`, a dependency which is still on the stack closes a cycle
+fn find_cycles_from(
+    pass: &str,
+    graph: &HashMap<String, GraphPassInfo>,
+    stack: &mut Vec<String>,
+    visited: &mut HashSet<String>,
+    cycles: &mut Vec<Vec<String>>) {
+    stack.push(pass.to_string());
+    visited.insert(pass.to_string());
+    if let Some(depends_on) = &graph[pass].depends_on {
+        for dep in depends_on {
+            if let Some(pos) = stack.iter().position(|p| p == dep) {
+                cycles.push(stack[pos..].to_vec());
+            }
+            else if graph.contains_key(dep) && !visited.contains(dep) {
+                find_cycles_from(dep, graph, stack, visited, cycles);
+            }
+        }
+    }
+    stack.pop();
+}
+
+/// Returns all dependency cycles in `graph`, each pass in a cycle depends on the next and the last depends on the first
+fn find_dependency_cycles(graph: &HashMap<String, GraphPassInfo>) -> Vec<Vec<String>> {
+    let mut names = graph.keys().collect::<Vec<&String>>();
+    names.sort();
+    let mut visited = HashSet::new();
+    let mut cycles = Vec::new();
+    for name in names {
+        if !visited.contains(name) {
+            find_cycles_from(name, graph, &mut Vec::new(), &mut visited, &mut cycles);
+        }
+    }
+    cycles
+}
+
+/// Returns the names of all passes `pass` directly or indirectly depends on
+fn get_pass_dependencies(pass: &str, graph: &HashMap<String, GraphPassInfo>) -> HashSet<String> {
+    let mut dependencies = HashSet::new();
+    let mut stack = vec![pass.to_string()];
+    while let Some(name) = stack.pop() {
+        if let Some(depends_on) = graph.get(&name).and_then(|info| info.depends_on.as_ref()) {
+            for dep in depends_on {
+                if dependencies.insert(dep.to_string()) {
+                    stack.push(dep.to_string());
+                }
+            }
+        }
+    }
+    dependencies
+}
+
 impl<D> Pmfx<D> where D: gfx::Device {
     /// Create a new empty pmfx instance
     pub fn create(device: &mut D, shader_heap_size: usize) -> Self {
@@ -1001,18 +1384,25 @@ impl<D> Pmfx<D> where D: gfx::Device {
         Pmfx {
             pmfx: File::new(),
             pmfx_tracking: HashMap::new(),
+            texture_files: HashMap::new(),
             pmfx_folders: HashMap::new(),
             render_pipelines: HashMap::new(),
             compute_pipelines: HashMap::new(),
             raytracing_pipelines: HashMap::new(),
             shaders: HashMap::new(),
             textures: HashMap::new(),
+            texture_aliases: HashMap::new(),
+            transient_stats: TransientTextureStats::default(),
             views: HashMap::new(),
             compute_passes: HashMap::new(),
             pass_stats: HashMap::new(),
             cameras: HashMap::new(),
             barriers: HashMap::new(),
+            barrier_info: HashMap::new(),
             command_queue: Vec::new(),
+            async_passes: HashSet::new(),
+            async_waits: HashMap::new(),
+            queue_sync: None,
             view_texture_refs: HashMap::new(),
             window_sizes: HashMap::new(),
             active_render_graph: String::new(),
@@ -1087,7 +1477,9 @@ impl<D> Pmfx<D> where D: gfx::Device {
              let file_metadata = fs::metadata(&info_filepath)?;
              e.insert(PmfxTrackingInfo {
                  modified_time: file_metadata.modified()?,
-                 filepath: info_filepath
+                 filepath: info_filepath,
+                 dependencies: file.dependencies.iter().map(|dep| normalise_dependency_path(dep)).collect(),
+                 contents: PmfxContents::from_file(&file)
              });
 
              // add files from pmfx for tracking
@@ -1218,6 +1610,24 @@ impl<D> Pmfx<D> where D: gfx::Device {
 
     /// Creates a texture if it has not already been created from information specified in .pmfx file
     pub fn create_texture(&mut self, device: &mut D, texture_name: &str) -> Result<(), super::Error> {
+        // aliased transient textures share the texture of their owner
+        if let Some(owner) = self.texture_aliases.get(texture_name).cloned() {
+            if !self.textures.contains_key(texture_name) && self.pmfx.textures.contains_key(texture_name) {
+                self.create_texture(device, &owner)?;
+                if let Some((_, owner_texture)) = self.textures.get(&owner) {
+                    let tracked = TrackedTexture {
+                        texture: owner_texture.texture.clone(),
+                        ratio: owner_texture.ratio.clone(),
+                        size: owner_texture.size,
+                        _tex_type: owner_texture._tex_type
+                    };
+                    println!("hotline_rs::pmfx:: aliasing texture: {} with {}", texture_name, owner);
+                    self.textures.insert(texture_name.to_string(), (self.pmfx.textures[texture_name].hash, tracked));
+                    return Ok(());
+                }
+            }
+        }
+
         if !self.textures.contains_key(texture_name) && self.pmfx.textures.contains_key(texture_name) {
             // create texture from info specified in .pmfx file
             println!("hotline_rs::pmfx:: creating texture: {}", texture_name);
@@ -1237,7 +1647,18 @@ impl<D> Pmfx<D> where D: gfx::Device {
                     super::get_data_path(filepath)
                 };
 
-                let img = image::load_from_file(&data_path)?;
+                let img = image::load_from_file(&data_path, None)?;
+
+                // track the image file so the texture reloads when it is modified
+                let tracked = self.texture_files.values().any(|tracking| tracking.filepath == data_path);
+                if !tracked {
+                    self.reloader.add_file(&data_path);
+                }
+                self.texture_files.insert(texture_name.to_string(), TextureFileTrackingInfo {
+                    modified_time: get_modified_time(Path::new(&data_path)),
+                    filepath: data_path
+                });
+
                 (device.create_texture_with_heaps::<u8>(
                     &img.info,
                     gfx::TextureHeapInfo {
@@ -1261,7 +1682,7 @@ impl<D> Pmfx<D> where D: gfx::Device {
             };
 
             self.textures.insert(texture_name.to_string(), (pmfx_tex.hash, TrackedTexture {
-                texture: tex,
+                texture: Arc::new(tex),
                 ratio: self.pmfx.textures[texture_name].ratio.clone(),
                 size: (size.0, size.1, pmfx_tex.depth),
                 _tex_type: tex_type
@@ -1273,7 +1694,7 @@ impl<D> Pmfx<D> where D: gfx::Device {
     /// Returns a texture reference if the texture exists or none otherwise
     pub fn get_texture<'stack>(&'stack self, texture_name: &str) -> Option<&'stack D::Texture> {
         if self.textures.contains_key(texture_name) {
-            Some(&self.textures[texture_name].1.texture)
+            Some(self.textures[texture_name].1.texture.as_ref())
         }
         else {
             None
@@ -1661,10 +2082,18 @@ impl<D> Pmfx<D> where D: gfx::Device {
         );
 
         // allocate cmd_buf for this compute pass
-        self.cmd_bufs.lock().unwrap().insert(graph_pass_name.to_string(), device.create_cmd_buf(2));
+        if is_async_compute_pass(info) {
+            // pipeline statistics queries are not supported on compute queues, so async passes are not tracked
+            self.cmd_bufs.lock().unwrap().insert(
+                graph_pass_name.to_string(), device.create_cmd_buf_for_queue(2, gfx::QueueType::Compute));
+            self.pass_stats.remove(graph_pass_name);
+        }
+        else {
+            self.cmd_bufs.lock().unwrap().insert(graph_pass_name.to_string(), device.create_cmd_buf(2));
 
-        // create stats
-        self.pass_stats.insert(graph_pass_name.to_string(), PassStats::new(device, 2));
+            // create stats
+            self.pass_stats.insert(graph_pass_name.to_string(), PassStats::new(device, 2));
+        }
 
         Ok(())
     }
@@ -1737,6 +2166,14 @@ impl<D> Pmfx<D> where D: gfx::Device {
             cmd_buf.end_event();
             cmd_buf.close()?;
             self.barriers.insert(barrier_name.to_string(), cmd_buf);
+            self.barrier_info.insert(barrier_name.to_string(), RenderGraphNode {
+                name: barrier_name.to_string(),
+                kind: RenderGraphNodeKind::GenerateMips,
+                texture: Some(texture_name.to_string()),
+                state_before: Some(ResourceState::ShaderResource),
+                state_after: Some(ResourceState::ShaderResource),
+                ..Default::default()
+            });
             // add barrier placeholder in the command_queue
             self.command_queue.push(barrier_name);
         }
@@ -1750,8 +2187,9 @@ impl<D> Pmfx<D> where D: gfx::Device {
         view_name: &str,
         texture_name: &str,
         target_state: ResourceState) -> Result<(), super::Error> {
-        if texture_barriers.contains_key(texture_name) {
-            let state = texture_barriers[texture_name];
+        let state_name = self.get_texture_barrier_name(texture_name);
+        if texture_barriers.contains_key(&state_name) {
+            let state = texture_barriers[&state_name];
             let barrier_name = format!("barrier_resolve-{}-{} ({:?})", view_name, texture_name, target_state);
             if let Some(tex) = self.get_texture(texture_name) {
                 // prevent resolving non msaa surfaces
@@ -1800,10 +2238,17 @@ impl<D> Pmfx<D> where D: gfx::Device {
                 // insert barrier
                 cmd_buf.close()?;
                 self.barriers.insert(barrier_name.to_string(), cmd_buf);
+                self.barrier_info.insert(barrier_name.to_string(), RenderGraphNode {
+                    name: barrier_name.to_string(),
+                    kind: RenderGraphNodeKind::Resolve,
+                    texture: Some(texture_name.to_string()),
+                    state_before: Some(state),
+                    state_after: Some(target_state),
+                    ..Default::default()
+                });
 
                 // update track state
-                texture_barriers.remove(texture_name);
-                texture_barriers.insert(texture_name.to_string(), ResourceState::ResolveSrc);
+                texture_barriers.insert(state_name, ResourceState::ResolveSrc);
             }
 
             // add barrier placeholder in the command_queue
@@ -1819,8 +2264,9 @@ impl<D> Pmfx<D> where D: gfx::Device {
         view_name: &str,
         texture_name: &str,
         target_state: ResourceState) -> Result<(), super::Error> {
-        if texture_barriers.contains_key(texture_name) {
-            let state = texture_barriers[texture_name];
+        let state_name = self.get_texture_barrier_name(texture_name);
+        if texture_barriers.contains_key(&state_name) {
+            let state = texture_barriers[&state_name];
             if state != target_state {
                 // add barrier placeholder in the command_queue
                 let barrier_name = format!("barrier_{}-{} ({:?})", view_name, texture_name, target_state);
@@ -1840,11 +2286,18 @@ impl<D> Pmfx<D> where D: gfx::Device {
                 });
                 cmd_buf.end_event();
                 cmd_buf.close()?;
+                self.barrier_info.insert(barrier_name.to_string(), RenderGraphNode {
+                    name: barrier_name.to_string(),
+                    kind: RenderGraphNodeKind::Transition,
+                    texture: Some(texture_name.to_string()),
+                    state_before: Some(state),
+                    state_after: Some(target_state),
+                    ..Default::default()
+                });
                 self.barriers.insert(barrier_name, cmd_buf);
 
                 // update track state
-                texture_barriers.remove(texture_name);
-                texture_barriers.insert(texture_name.to_string(), target_state);
+                texture_barriers.insert(state_name, target_state);
             }
         }
         Ok(())
@@ -1857,200 +2310,346 @@ impl<D> Pmfx<D> where D: gfx::Device {
         self.views.clear();
     }
 
+    /// Returns the passes of `graph_name` in the order they will execute, each pass is placed after all passes it
+    /// `depends_on` and passes which are otherwise unordered are sorted by name. Passes with missing views are skipped
+    /// and missing dependencies are ignored
+    pub fn get_render_graph_pass_order(&self, graph_name: &str) -> Vec<String> {
+        let graph = if let Some(graph) = self.pmfx.render_graphs.get(graph_name) {
+            graph
+        }
+        else {
+            return Vec::new();
+        };
+
+        let mut names = graph.iter().filter(|(_, pass)| {
+            match &pass.view {
+                Some(view) => self.pmfx.views.contains_key(view),
+                None => true
+            }
+        }).map(|(name, _)| name.to_string()).collect::<Vec<String>>();
+        names.sort();
+
+        let mut order: Vec<String> = Vec::new();
+        loop {
+            let mut progress = false;
+            for name in &names {
+                if order.contains(name) {
+                    continue;
+                }
+                let ready = graph[name].depends_on.iter().flatten().all(|dep| {
+                    order.contains(dep) || !names.contains(dep)
+                });
+                if ready {
+                    order.push(name.to_string());
+                    progress = true;
+                }
+            }
+            if !progress {
+                break;
+            }
+        }
+        order
+    }
+
+    /// Returns the (first, last) index into `pass_order` that each texture is used by a view target or resource use
+    fn get_texture_lifetimes(&self, graph_name: &str, pass_order: &[String]) -> HashMap<String, (usize, usize)> {
+        let mut lifetimes: HashMap<String, (usize, usize)> = HashMap::new();
+        for (i, pass_name) in pass_order.iter().enumerate() {
+            let pass = &self.pmfx.render_graphs[graph_name][pass_name];
+            let mut textures = Vec::new();
+            if let Some(view) = pass.view.as_ref().and_then(|view| self.pmfx.views.get(view)) {
+                textures.extend(view.render_target.iter().chain(view.depth_stencil.iter()));
+            }
+            textures.extend(pass.uses.iter().flatten().map(|(texture, _)| texture));
+            textures.extend(pass.target_dimension.iter());
+
+            for texture in textures {
+                let lifetime = lifetimes.entry(texture.to_string()).or_insert((i, i));
+                lifetime.1 = i;
+            }
+        }
+        lifetimes
+    }
+
+    /// Returns true if the textures `a` and `b` can share the same memory
+    fn is_alias_compatible(a: &TextureInfo, b: &TextureInfo) -> bool {
+        let size_compatible = match (&a.ratio, &b.ratio) {
+            (Some(ra), Some(rb)) => ra.window == rb.window && ra.scale == rb.scale,
+            (None, None) => a.width == b.width && a.height == b.height,
+            _ => false
+        };
+        size_compatible &&
+            a.format == b.format &&
+            a.depth == b.depth &&
+            a.mip_levels == b.mip_levels &&
+            a.array_layers == b.array_layers &&
+            a.samples == b.samples &&
+            a.cubemap == b.cubemap &&
+            a.usage.len() == b.usage.len() &&
+            a.usage.iter().all(|usage| b.usage.contains(usage))
+    }
+
+    /// Assigns textures marked `transient` which have not been created yet to share the texture of another transient
+    /// texture with compatible info whose lifetime in `pass_order` does not overlap, and updates `transient_stats`.
+    /// Existing aliases which now overlap are removed so the texture and any views using it are recreated
+    fn alias_transient_textures(&mut self, graph_name: &str, pass_order: &[String]) {
+        let lifetimes = self.get_texture_lifetimes(graph_name, pass_order);
+
+        // break existing aliases which are used at the same time in this graph
+        let overlapping = self.texture_aliases.iter().filter(|(alias, owner)| {
+            match (lifetimes.get(*alias), lifetimes.get(*owner)) {
+                (Some(a), Some(o)) => a.0 <= o.1 && o.0 <= a.1,
+                _ => false
+            }
+        }).map(|(alias, _)| alias.to_string()).collect::<Vec<String>>();
+
+        for alias in overlapping {
+            println!("hotline_rs::pmfx:: texture {} overlaps {} and will no longer alias it", alias, self.texture_aliases[&alias]);
+            self.texture_aliases.remove(&alias);
+            self.textures.remove(&alias);
+            for view_name in self.get_view_texture_refs(&alias) {
+                self.views.remove(&view_name);
+                self.pass_stats.remove(&view_name);
+            }
+        }
+
+        // new transient textures sorted by first use
+        let mut transient = lifetimes.iter().filter(|(name, _)| {
+            !self.textures.contains_key(*name) && self.pmfx.textures.get(*name).is_some_and(|tex| {
+                tex.transient.unwrap_or(false) && tex.filepath.is_none()
+            })
+        }).map(|(name, lifetime)| (*lifetime, name.to_string())).collect::<Vec<_>>();
+        transient.sort();
+
+        // greedily place each texture in the first compatible slot which is free, slots are (owner, last use)
+        let mut slots: Vec<(String, usize)> = Vec::new();
+        for ((first, last), name) in transient {
+            let slot = slots.iter_mut().find(|(owner, owner_last)| {
+                *owner_last < first && Self::is_alias_compatible(&self.pmfx.textures[owner], &self.pmfx.textures[&name])
+            });
+            if let Some(slot) = slot {
+                self.texture_aliases.insert(name, slot.0.to_string());
+                slot.1 = last;
+            }
+            else {
+                self.texture_aliases.remove(&name);
+                slots.push((name, last));
+            }
+        }
+
+        // memory stats for all transient textures in the graph
+        let mut stats = TransientTextureStats::default();
+        for name in lifetimes.keys() {
+            if let Some(tex) = self.pmfx.textures.get(name) {
+                if tex.transient.unwrap_or(false) && tex.filepath.is_none() {
+                    let (width, height) = self.get_texture_size_from_ratio(tex).unwrap_or((tex.width, tex.height));
+                    let size = gfx::size_for_format_mipped(
+                        tex.format, width, height, tex.depth, tex.array_layers, tex.mip_levels) * tex.samples.max(1) as u64;
+                    stats.transient_textures += 1;
+                    stats.transient_bytes += size;
+                    if self.texture_aliases.get(name).is_some_and(|owner| lifetimes.contains_key(owner)) {
+                        stats.saved_bytes += size;
+                    }
+                    else {
+                        stats.physical_textures += 1;
+                    }
+                }
+            }
+        }
+        self.transient_stats = stats;
+    }
+
+    /// Returns the name of the texture which owns the memory for `texture_name`, aliased textures share barrier states
+    fn get_texture_barrier_name(&self, texture_name: &str) -> String {
+        self.texture_aliases.get(texture_name).map_or(texture_name, |owner| owner.as_str()).to_string()
+    }
+
+    /// Returns memory usage of transient textures in the active render graph
+    pub fn get_transient_texture_stats(&self) -> &TransientTextureStats {
+        &self.transient_stats
+    }
+
     /// Create a render graph wih automatic resource barrier generation from info specified insie .pmfx file
     pub fn create_render_graph(&mut self, device: &mut D, graph_name: &str) -> Result<(), super::Error> {
         // go through the graph sequentially, as the command lists are executed in order but generated
         if self.pmfx.render_graphs.contains_key(graph_name) {
 
+            // passes in a cycle can never be ordered
+            if let Some(cycle) = find_dependency_cycles(&self.pmfx.render_graphs[graph_name]).first() {
+                return Err(super::Error {
+                    msg: format!("hotline_rs::pmfx:: render graph {} has a dependency cycle: {} -> {}",
+                        graph_name, cycle.join(" -> "), cycle[0]),
+                });
+            }
+
+            // allow missing views and dependencies to be safely handled
+            let pmfx_graph = self.pmfx.render_graphs[graph_name].clone();
+            for (graph_pass_name, instance) in &pmfx_graph {
+                if let Some(view) = &instance.view {
+                    if !self.pmfx.views.contains_key(view) {
+                        println!("hotline_rs::pmfx:: [warning] missing view {}", view);
+                    }
+                }
+                if let Some(depends_on) = &instance.depends_on {
+                    for d in depends_on {
+                        if !pmfx_graph.contains_key(d) {
+                            println!("hotline_rs::pmfx:: [warning] graph pass {} missing dependency {}. ignoring",
+                                graph_pass_name, d);
+                        }
+                    }
+                }
+                if let Some(queue) = instance.queue {
+                    if queue != gfx::QueueType::Graphics && !is_async_compute_pass(instance) {
+                        println!("hotline_rs::pmfx:: [warning] graph pass {} cannot execute on the {:?} queue. using graphics",
+                            graph_pass_name, queue);
+                    }
+                }
+            }
+
+            // passes in depends on order, the same order is used for texture lifetimes and the command queue
+            let pass_order = self.get_render_graph_pass_order(graph_name);
+
+            // share memory between transient textures before any are created for views
+            self.alias_transient_textures(graph_name, &pass_order);
+
             // create views for any nodes in the graph
             self.create_render_graph_views(device, graph_name)?;
 
             // currently we just have 1 single execute graph and barrier set
             self.barriers.clear();
+            self.barrier_info.clear();
             self.command_queue.clear();
 
+            // aliased textures share the tracked state of their owner
             let mut barriers = self.pmfx.textures.iter().filter(|tex|{
+                !self.texture_aliases.contains_key(tex.0) && (
                 tex.1.usage.contains(&ResourceState::ShaderResource) ||
                 tex.1.usage.contains(&ResourceState::RenderTarget) ||
-                tex.1.usage.contains(&ResourceState::DepthStencil)
+                tex.1.usage.contains(&ResourceState::DepthStencil))
             }).map(|tex|{
               (tex.0.to_string(), ResourceState::ShaderResource)
             }).collect::<HashMap<String, ResourceState>>();
 
-            // loop over the graph multiple times adding views in depends on order, until we add all the views
-            let mut to_add = self.pmfx.render_graphs[graph_name].len();
-
-            let mut added = 0;
-            let mut dependencies = HashSet::new();
-            while added < to_add {
-                let pmfx_graph = self.pmfx.render_graphs[graph_name].clone();
-                for (graph_pass_name, instance) in &pmfx_graph {
-
-                    if let Some(view) = &instance.view {
-                        // allow missing views to be safely handled
-                        if !self.pmfx.views.contains_key(view) {
-                            println!("hotline_rs::pmfx:: [warning] missing view {}", view);
-                            to_add -= 1;
-                            continue;
-                        }
-                    }
+            for graph_pass_name in &pass_order {
+                let instance = &pmfx_graph[graph_pass_name];
 
-                    // already added this pass
-                    if dependencies.contains(graph_pass_name) {
-                        continue;
-                    }
+                if let Some(uses) = &instance.uses {
+                    // check resource uses
+                    for u in uses {
+                        let mut resolve = false;
+                        let mut gen_mips = false;
 
-                    // wait for dependencies
-                    if let Some(depends_on) = &instance.depends_on {
-                        let mut passes = false;
-                        if !depends_on.is_empty() {
-                            for d in depends_on {
-                                if !pmfx_graph.contains_key(d) {
-                                    passes = true;
-                                    println!("hotline_rs::pmfx:: [warning] graph pass {} missing dependency {}. ignoring",
-                                        graph_pass_name, d);
-                                }
-                                else if dependencies.contains(d) {
-                                    passes = true;
-                                }
-                                else {
-                                    passes = false;
-                                }
-                            }
+                        let resolvable = if let Some(tex) = self.get_texture(&u.0) {
+                            tex.is_resolvable()
                         }
-
-                        if !passes {
-                            continue;
+                        else {
+                            false
+                        };
+
+                        let res_state = match u.1 {
+                            ResourceUsage::Write => {
+                                ResourceState::UnorderedAccess
+                            },
+                            ResourceUsage::Read => {
+                                resolve = resolvable;
+                                ResourceState::ShaderResource
+                            },
+                            ResourceUsage::ReadMips => {
+                                resolve = resolvable;
+                                gen_mips = true;
+                                ResourceState::ShaderResource
+                            },
+                            ResourceUsage::ReadMsaa => {
+                                ResourceState::ShaderResource
+                            },
+                        };
+
+                        // resolve and generate mips
+                        if resolve {
+                            self.create_resolve_transition(
+                                device,
+                                &mut barriers,
+                                graph_pass_name,
+                                &u.0,
+                                ResourceState::ShaderResource,
+                            )?;
                         }
-                    }
-
-                    if let Some(uses) = &instance.uses {
-                        // check resource uses
-                        for u in uses {
-                            let mut resolve = false;
-                            let mut gen_mips = false;
-
-                            let resolvable = if let Some(tex) = self.get_texture(&u.0) {
-                                if tex.is_resolvable() {
-                                    true
-                                }
-                                else {
-                                    false
-                                }
-                            }
-                            else {
-                                false
-                            };
-
-                            let res_state = match u.1 {
-                                ResourceUsage::Write => {
-                                    ResourceState::UnorderedAccess
-                                },
-                                ResourceUsage::Read => {
-                                    resolve = resolvable;
-                                    ResourceState::ShaderResource
-                                },
-                                ResourceUsage::ReadMips => {
-                                    resolve = resolvable;
-                                    gen_mips = true;
-                                    ResourceState::ShaderResource
-                                },
-                                ResourceUsage::ReadMsaa => {
-                                    ResourceState::ShaderResource
-                                },
-                            };
-
-                            // resolve and generate mips
-                            if resolve {
-                                self.create_resolve_transition(
-                                    device,
-                                    &mut barriers,
-                                    &graph_pass_name,
-                                    &u.0,
-                                    ResourceState::ShaderResource,
-                                )?;
-                            }
-
-                            // generate mips on non msaa resources
-                            if gen_mips {
-                                // generate_mip_maps mips expects us to be in ShaderResource state
-                                self.create_texture_transition_barrier(
-                                    device,
-                                    &mut barriers,
-                                    &graph_pass_name,
-                                    &u.0,
-                                    ResourceState::ShaderResource)?;
 
-                                self.generate_mip_maps(device, &u.0)?;
-
-                                // generate_mip_maps transitions to ShaderResource
-                                *barriers.get_mut(&u.0).unwrap() = ResourceState::ShaderResource;
-                            }
-
-                            // transition to target state
+                        // generate mips on non msaa resources
+                        if gen_mips {
+                            // generate_mip_maps mips expects us to be in ShaderResource state
                             self.create_texture_transition_barrier(
                                 device,
                                 &mut barriers,
                                 &graph_pass_name,
                                 &u.0,
-                                res_state)?;
+                                ResourceState::ShaderResource)?;
+
+                            self.generate_mip_maps(device, &u.0)?;
+
+                            // generate_mip_maps transitions to ShaderResource
+                            *barriers.get_mut(&self.get_texture_barrier_name(&u.0)).unwrap() = ResourceState::ShaderResource;
                         }
+
+                        // transition to target state
+                        self.create_texture_transition_barrier(
+                            device,
+                            &mut barriers,
+                            graph_pass_name,
+                            &u.0,
+                            res_state)?;
                     }
+                }
 
-                    if let Some(view) = &instance.view {
-                        // create transitions by inspecting view info
-                        let pmfx_view = self.pmfx.views[view].clone();
+                if let Some(view) = &instance.view {
+                    // create transitions by inspecting view info
+                    let pmfx_view = self.pmfx.views[view].clone();
 
-                        // if we need to write to a target we must make sure it is transitioned into render target state
-                        for rt_name in pmfx_view.render_target {
-                            self.create_texture_transition_barrier(
-                                device, &mut barriers, view, &rt_name, ResourceState::RenderTarget)?;
+                    // if we need to write to a target we must make sure it is transitioned into render target state
+                    for rt_name in pmfx_view.render_target {
+                        self.create_texture_transition_barrier(
+                            device, &mut barriers, view, &rt_name, ResourceState::RenderTarget)?;
 
-                        }
+                    }
 
-                        // same for depth stencils
-                        for ds_name in pmfx_view.depth_stencil {
-                            self.create_texture_transition_barrier(
-                                device, &mut barriers, view, &ds_name, ResourceState::DepthStencil)?;
+                    // same for depth stencils
+                    for ds_name in pmfx_view.depth_stencil {
+                        self.create_texture_transition_barrier(
+                            device, &mut barriers, view, &ds_name, ResourceState::DepthStencil)?;
 
-                        }
+                    }
 
-                        // create pipelines requested for this view instance with the pass format
-                        if let Some(pipelines) = &instance.pipelines {
-                            for pipeline in pipelines {
-                                let view = self.get_view(graph_pass_name)?;
-                                let view = view.clone();
-                                let view = view.lock().unwrap();
-                                self.create_render_pipeline(device, pipeline, &view.pass)?;
-                            }
+                    // create pipelines requested for this view instance with the pass format
+                    if let Some(pipelines) = &instance.pipelines {
+                        for pipeline in pipelines {
+                            let view = self.get_view(graph_pass_name)?;
+                            let view = view.clone();
+                            let view = view.lock().unwrap();
+                            self.create_render_pipeline(device, pipeline, &view.pass)?;
                         }
                     }
-                    else if let Some(pipelines) = &instance.pipelines {
-                        for pipeline in pipelines {
-                            let pipeline_type = self.get_pipeline_type(pipeline);
-                            match pipeline_type {
-                                PipelineType::Compute => self.create_compute_pipeline(device, pipeline)?,
-                                PipelineType::Raytracing => self.create_raytracing_pipeline(device, pipeline)?,
-                                _ => panic!("hotline::pmfx: error: compute views require compute or raytracing pipelines")
-                            }
+                }
+                else if let Some(pipelines) = &instance.pipelines {
+                    for pipeline in pipelines {
+                        let pipeline_type = self.get_pipeline_type(pipeline);
+                        match pipeline_type {
+                            PipelineType::Compute => self.create_compute_pipeline(device, pipeline)?,
+                            PipelineType::Raytracing => self.create_raytracing_pipeline(device, pipeline)?,
+                            _ => panic!("hotline::pmfx: error: compute views require compute or raytracing pipelines")
                         }
                     }
+                }
 
-                    // add single pass
-                    self.command_queue.push(graph_pass_name.to_string());
+                // add single pass
+                self.command_queue.push(graph_pass_name.to_string());
 
-                    // add additional 5 passes for cubemaps
-                    if let Some(cubemap) = instance.cubemap {
-                        if cubemap {
-                            for i in 1..6 {
-                                self.command_queue.push(format!("{}_{}", graph_pass_name, i));
-                            }
+                // add additional 5 passes for cubemaps
+                if let Some(cubemap) = instance.cubemap {
+                    if cubemap {
+                        for i in 1..6 {
+                            self.command_queue.push(format!("{}_{}", graph_pass_name, i));
                         }
                     }
-
-                    // push a view on
-                    added += 1;
-                    dependencies.insert(graph_pass_name.to_string());
                 }
             }
 
@@ -2071,6 +2670,9 @@ impl<D> Pmfx<D> where D: gfx::Device {
                     device, &mut barriers, "eof", &name, ResourceState::ShaderResource)?;
             }
 
+            // syncronise passes on the async compute queue with the graphics queue
+            self.create_async_compute_waits(device, &pmfx_graph)?;
+
             // track the current render graph for if we need to rebuild due to resize, or file modification
             self.active_render_graph = graph_name.to_string();
 
@@ -2083,6 +2685,351 @@ impl<D> Pmfx<D> where D: gfx::Device {
         }
     }
 
+    /// Returns the physical textures a graph pass accesses through `uses` or the targets of its view
+    fn get_pass_textures(&self, info: &GraphPassInfo) -> HashSet<String> {
+        let mut textures = info.uses.iter().flatten().map(|(texture, _)| {
+            self.get_texture_barrier_name(texture)
+        }).collect::<HashSet<String>>();
+        if let Some(view) = info.view.as_ref().and_then(|view| self.pmfx.views.get(view)) {
+            for target in view.render_target.iter().chain(view.depth_stencil.iter()) {
+                textures.insert(self.get_texture_barrier_name(target));
+            }
+        }
+        textures
+    }
+
+    /// Finds the async compute passes in the `command_queue` and for each one the first graphics node which must wait
+    /// for it to complete, because the node depends on the pass or accesses any of the textures the pass uses.
+    /// Nodes which come after an async pass and do not touch its resources are free to run alongside it
+    fn create_async_compute_waits(&mut self, device: &mut D, graph: &HashMap<String, GraphPassInfo>) -> Result<(), super::Error> {
+        self.async_passes = self.command_queue.iter()
+            .filter(|name| graph.get(*name).is_some_and(is_async_compute_pass))
+            .cloned()
+            .collect();
+        self.async_waits.clear();
+
+        if self.async_passes.is_empty() {
+            return Ok(());
+        }
+
+        if self.queue_sync.is_none() {
+            self.queue_sync = Some(QueueSync {
+                graphics_fence: device.create_fence()?,
+                graphics_value: 0,
+                compute_fence: device.create_fence()?,
+                compute_value: 0
+            });
+        }
+
+        let mut async_waits: HashMap<String, Vec<String>> = HashMap::new();
+        for (i, async_pass) in self.command_queue.iter().enumerate() {
+            if !self.async_passes.contains(async_pass) {
+                continue;
+            }
+            let textures = self.get_pass_textures(&graph[async_pass]);
+            let waiter = self.command_queue[i+1..].iter().find(|node| {
+                if self.async_passes.contains(*node) {
+                    // async passes execute in order on the same queue
+                    false
+                }
+                else if let Some(info) = self.barrier_info.get(*node) {
+                    info.texture.as_ref().is_some_and(|texture| textures.contains(&self.get_texture_barrier_name(texture)))
+                }
+                else if let Some(info) = graph.get(*node) {
+                    get_pass_dependencies(node, graph).contains(async_pass) ||
+                        !self.get_pass_textures(info).is_disjoint(&textures)
+                }
+                else {
+                    false
+                }
+            });
+            if let Some(waiter) = waiter {
+                async_waits.entry(waiter.to_string()).or_default().push(async_pass.to_string());
+            }
+        }
+        self.async_waits = async_waits;
+
+        Ok(())
+    }
+
+    /// Validates the render graph `graph_name` without creating any resources. Detects dependency cycles, missing views,
+    /// pipelines, textures and cameras, reads of textures which are not guaranteed to have been written yet and
+    /// textures or pipelines which are incompatible with the targets of a view. Diagnostics are sorted by pass name
+    pub fn validate_render_graph(&self, graph_name: &str) -> Vec<GraphDiagnostic> {
+        let mut diagnostics = Vec::new();
+        let graph = if let Some(graph) = self.pmfx.render_graphs.get(graph_name) {
+            graph
+        }
+        else {
+            diagnostics.push(GraphDiagnostic {
+                severity: GraphDiagnosticSeverity::Error,
+                pass: None,
+                kind: GraphDiagnosticKind::MissingRenderGraph(graph_name.to_string()),
+                message: format!("could not find render graph: {}", graph_name)
+            });
+            return diagnostics;
+        };
+
+        for cycle in find_dependency_cycles(graph) {
+            let mut path = cycle.clone();
+            path.push(cycle[0].to_string());
+            diagnostics.push(GraphDiagnostic {
+                severity: GraphDiagnosticSeverity::Error,
+                pass: None,
+                kind: GraphDiagnosticKind::DependencyCycle(cycle),
+                message: format!("dependency cycle: {}", path.join(" -> "))
+            });
+        }
+
+        // passes which write each texture, either as a render target / depth stencil or via `uses`
+        let mut writers: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (pass_name, pass) in graph {
+            if let Some(view) = pass.view.as_ref().and_then(|view| self.pmfx.views.get(view)) {
+                for target in view.render_target.iter().chain(view.depth_stencil.iter()) {
+                    writers.entry(target.as_str()).or_default().push(pass_name);
+                }
+            }
+            if let Some(uses) = &pass.uses {
+                for (texture, usage) in uses {
+                    if matches!(usage, ResourceUsage::Write) {
+                        writers.entry(texture.as_str()).or_default().push(pass_name);
+                    }
+                }
+            }
+        }
+
+        let mut pass_names = graph.keys().collect::<Vec<&String>>();
+        pass_names.sort();
+        for pass_name in pass_names {
+            let pass = &graph[pass_name];
+            let mut diagnose = |severity, kind, message| {
+                diagnostics.push(GraphDiagnostic {
+                    severity,
+                    pass: Some(pass_name.to_string()),
+                    kind,
+                    message
+                });
+            };
+
+            if let Some(depends_on) = &pass.depends_on {
+                for dep in depends_on {
+                    if !graph.contains_key(dep) {
+                        diagnose(GraphDiagnosticSeverity::Warning,
+                            GraphDiagnosticKind::MissingDependency(dep.to_string()),
+                            format!("missing dependency {}, it will be ignored", dep));
+                    }
+                }
+            }
+
+            if let Some(queue) = pass.queue {
+                if queue != gfx::QueueType::Graphics && !is_async_compute_pass(pass) {
+                    diagnose(GraphDiagnosticSeverity::Warning,
+                        GraphDiagnosticKind::UnsupportedQueue(format!("{:?}", queue)),
+                        format!("cannot execute on the {:?} queue, it will execute on the graphics queue", queue));
+                }
+            }
+
+            // view targets, camera and the pipelines which will render into them
+            let view = if let Some(view_name) = &pass.view {
+                let view = self.pmfx.views.get(view_name);
+                if view.is_none() {
+                    diagnose(GraphDiagnosticSeverity::Warning,
+                        GraphDiagnosticKind::MissingView(view_name.to_string()),
+                        format!("missing view {}, the pass will be skipped", view_name));
+                }
+                view
+            }
+            else {
+                None
+            };
+
+            let mut view_samples = None;
+            if let Some(view) = view {
+                if !view.camera.is_empty() && !self.cameras.contains_key(&view.camera) {
+                    diagnose(GraphDiagnosticSeverity::Warning,
+                        GraphDiagnosticKind::MissingCamera(view.camera.to_string()),
+                        format!("missing camera {}", view.camera));
+                }
+
+                let targets = view.render_target.iter().map(|t| (t, false))
+                    .chain(view.depth_stencil.iter().map(|t| (t, true)));
+
+                for (target, depth) in targets {
+                    if let Some(tex) = self.pmfx.textures.get(target) {
+                        if depth && (!gfx::is_depth_format(tex.format) || !tex.usage.contains(&ResourceState::DepthStencil)) {
+                            diagnose(GraphDiagnosticSeverity::Error,
+                                GraphDiagnosticKind::FormatMismatch(target.to_string()),
+                                format!("{} is used as a depth stencil but has format {:?} and usage {:?}",
+                                    target, tex.format, tex.usage));
+                        }
+                        else if !depth && (gfx::is_depth_format(tex.format) || !tex.usage.contains(&ResourceState::RenderTarget)) {
+                            diagnose(GraphDiagnosticSeverity::Error,
+                                GraphDiagnosticKind::FormatMismatch(target.to_string()),
+                                format!("{} is used as a render target but has format {:?} and usage {:?}",
+                                    target, tex.format, tex.usage));
+                        }
+
+                        let samples = tex.samples.max(1);
+                        if let Some(view_samples) = view_samples {
+                            if view_samples != samples {
+                                diagnose(GraphDiagnosticSeverity::Error,
+                                    GraphDiagnosticKind::FormatMismatch(target.to_string()),
+                                    format!("{} has {} samples but other targets in the view have {}",
+                                        target, samples, view_samples));
+                            }
+                        }
+                        else {
+                            view_samples = Some(samples);
+                        }
+                    }
+                    else {
+                        diagnose(GraphDiagnosticSeverity::Error,
+                            GraphDiagnosticKind::MissingTexture(target.to_string()),
+                            format!("missing texture {}", target));
+                    }
+                }
+            }
+
+            if let Some(pipelines) = &pass.pipelines {
+                for pipeline_name in pipelines {
+                    let permutation = self.pmfx.pipelines.get(pipeline_name)
+                        .and_then(|permutations| permutations.values().next());
+
+                    let pipeline = if let Some(pipeline) = permutation {
+                        pipeline
+                    }
+                    else {
+                        diagnose(GraphDiagnosticSeverity::Error,
+                            GraphDiagnosticKind::MissingPipeline(pipeline_name.to_string()),
+                            format!("missing pipeline {}", pipeline_name));
+                        continue;
+                    };
+
+                    let pipeline_type = self.get_pipeline_type(pipeline_name);
+                    if pass.view.is_some() && !matches!(pipeline_type, PipelineType::Render) {
+                        diagnose(GraphDiagnosticSeverity::Error,
+                            GraphDiagnosticKind::PipelineTypeMismatch(pipeline_name.to_string()),
+                            format!("{} is not a render pipeline and cannot be used in a view pass", pipeline_name));
+                    }
+                    else if pass.view.is_none() && !matches!(pipeline_type, PipelineType::Compute | PipelineType::Raytracing) {
+                        diagnose(GraphDiagnosticSeverity::Error,
+                            GraphDiagnosticKind::PipelineTypeMismatch(pipeline_name.to_string()),
+                            format!("{} is not a compute or raytracing pipeline and cannot be used in a pass without a view", pipeline_name));
+                    }
+
+                    if let Some(view) = view {
+                        let depth_stencil_state = pipeline.depth_stencil_state.as_ref()
+                            .and_then(|state| self.pmfx.depth_stencil_states.get(state));
+                        if let Some(state) = depth_stencil_state {
+                            if (state.depth_enabled || state.stencil_enabled) && view.depth_stencil.is_empty() {
+                                diagnose(GraphDiagnosticSeverity::Error,
+                                    GraphDiagnosticKind::FormatMismatch(pipeline_name.to_string()),
+                                    format!("{} enables depth or stencil testing but view {} has no depth stencil",
+                                        pipeline_name, pass.view.as_ref().unwrap()));
+                            }
+                        }
+
+                        let blend_state = pipeline.blend_state.as_ref()
+                            .and_then(|state| self.pmfx.blend_states.get(state));
+                        if let Some(state) = blend_state {
+                            if state.independent_blend_enabled && state.render_target.len() != view.render_target.len() {
+                                diagnose(GraphDiagnosticSeverity::Warning,
+                                    GraphDiagnosticKind::FormatMismatch(pipeline_name.to_string()),
+                                    format!("{} blends {} render targets but view {} has {}",
+                                        pipeline_name, state.render_target.len(), pass.view.as_ref().unwrap(), view.render_target.len()));
+                            }
+                        }
+                    }
+                }
+            }
+
+            // resource uses, reads must come after a write in the dependency chain
+            let dependencies = get_pass_dependencies(pass_name, graph);
+            if let Some(uses) = &pass.uses {
+                for (texture, usage) in uses {
+                    let tex = if let Some(tex) = self.pmfx.textures.get(texture) {
+                        tex
+                    }
+                    else {
+                        diagnose(GraphDiagnosticSeverity::Error,
+                            GraphDiagnosticKind::MissingTexture(texture.to_string()),
+                            format!("missing texture {}", texture));
+                        continue;
+                    };
+
+                    let (usage_ok, requirement) = match usage {
+                        ResourceUsage::Write => (tex.usage.contains(&ResourceState::UnorderedAccess), "UnorderedAccess usage"),
+                        ResourceUsage::Read | ResourceUsage::ReadMips => (tex.usage.contains(&ResourceState::ShaderResource), "ShaderResource usage"),
+                        ResourceUsage::ReadMsaa => (tex.samples > 1, "more than 1 sample")
+                    };
+                    if !usage_ok {
+                        diagnose(GraphDiagnosticSeverity::Error,
+                            GraphDiagnosticKind::FormatMismatch(texture.to_string()),
+                            format!("{} is used as {:?} which requires {}", texture, usage, requirement));
+                    }
+
+                    // textures loaded from file or data are initialised on creation
+                    if matches!(usage, ResourceUsage::Write) || tex.filepath.is_some() || tex.src_data.is_some() {
+                        continue;
+                    }
+
+                    match writers.get(texture.as_str()) {
+                        None => {
+                            diagnose(GraphDiagnosticSeverity::Warning,
+                                GraphDiagnosticKind::ReadBeforeWrite(texture.to_string()),
+                                format!("{} is read but never written in this graph", texture));
+                        }
+                        Some(passes) => {
+                            if !passes.iter().any(|p| dependencies.contains(*p)) {
+                                let mut passes = passes.clone();
+                                passes.sort();
+                                diagnose(GraphDiagnosticSeverity::Error,
+                                    GraphDiagnosticKind::ReadBeforeWrite(texture.to_string()),
+                                    format!("{} is read before it is written, add depends_on one of: {}",
+                                        texture, passes.join(", ")));
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(target_dimension) = &pass.target_dimension {
+                if !self.pmfx.textures.contains_key(target_dimension) {
+                    diagnose(GraphDiagnosticSeverity::Error,
+                        GraphDiagnosticKind::MissingTexture(target_dimension.to_string()),
+                        format!("missing target_dimension texture {}", target_dimension));
+                }
+            }
+        }
+
+        // textures which nothing will ever write or read
+        let mut referenced = HashSet::new();
+        for view in self.pmfx.views.values() {
+            referenced.extend(view.render_target.iter().chain(view.depth_stencil.iter()));
+        }
+        for pass in self.pmfx.render_graphs.values().flat_map(|graph| graph.values()) {
+            if let Some(uses) = &pass.uses {
+                referenced.extend(uses.iter().map(|(texture, _)| texture));
+            }
+            if let Some(target_dimension) = &pass.target_dimension {
+                referenced.insert(target_dimension);
+            }
+        }
+
+        let mut unused = self.pmfx.textures.keys().filter(|t| !referenced.contains(t)).collect::<Vec<&String>>();
+        unused.sort();
+        for texture in unused {
+            diagnostics.push(GraphDiagnostic {
+                severity: GraphDiagnosticSeverity::Warning,
+                pass: None,
+                kind: GraphDiagnosticKind::UnusedTexture(texture.to_string()),
+                message: format!("texture {} is not used by any view or render graph", texture)
+            });
+        }
+
+        diagnostics
+    }
+
     /// Returns the pipeline type based on the config setup from pmfx, a vs indicates render pipeline (ps may be null), cs for compute and lib for raytracing. Returns `PipelineType::None` if the pipeline is not found or is invalid
     pub fn get_pipeline_type(&self, pipeline_name: &str) -> PipelineType {
         if self.pmfx.pipelines.contains_key(pipeline_name) {
@@ -2109,9 +3056,48 @@ impl<D> Pmfx<D> where D: gfx::Device {
         }
     }
 
+    /// Validates the shaders of all permutations of `pipeline_name` against their reflection sidecars, checking the
+    /// shader stages, that the pipeline layout contains the bindings and push constants the shaders use and that the
+    /// input layout contains the vertex inputs. Shaders without a reflection sidecar are reported as
+    /// `ReflectionErrorKind::MissingReflection`
+    pub fn validate_pipeline(&self, pipeline_name: &str) -> Result<Vec<ReflectionError>, super::Error> {
+        let permutations = self.pmfx.pipelines.get(pipeline_name).ok_or_else(|| super::Error {
+            msg: format!("hotline_rs::pmfx:: could not find pipeline: {}", pipeline_name),
+        })?;
+        let folder = self.pmfx_folders.get(pipeline_name).ok_or_else(|| super::Error {
+            msg: format!("hotline_rs::pmfx:: expected to find pipeline {} in pmfx_folders", pipeline_name),
+        })?;
+        let mut errors = Vec::new();
+        let mut names = permutations.keys().collect::<Vec<&String>>();
+        names.sort();
+        for name in names {
+            errors.extend(validate_pipeline_reflection(Path::new(folder), &permutations[name])?);
+        }
+        Ok(errors)
+    }
+
+    /// Returns an error listing the mismatches if the shaders of `pipeline_name` do not match their reflection.
+    /// Sidecars are optional when creating pipelines, so shaders without one are not treated as a mismatch here
+    fn check_pipeline_reflection(&self, pipeline_name: &str) -> Result<(), super::Error> {
+        let errors = self.validate_pipeline(pipeline_name)?.into_iter()
+            .filter(|e| !matches!(e.kind, ReflectionErrorKind::MissingReflection(_)))
+            .collect::<Vec<ReflectionError>>();
+        if errors.is_empty() {
+            Ok(())
+        }
+        else {
+            Err(super::Error {
+                msg: format!("hotline_rs::pmfx:: pipeline {} does not match shader reflection:\n{}",
+                    pipeline_name, errors.iter().map(|e| e.to_string()).collect::<Vec<String>>().join("\n"))
+            })
+        }
+    }
+
     /// Create a ComputePipeline instance for the combination of pmfx_pipeline settings
     pub fn create_compute_pipeline(&mut self, device: &D, pipeline_name: &str) -> Result<(), super::Error> {
         if self.pmfx.pipelines.contains_key(pipeline_name) {
+            self.check_pipeline_reflection(pipeline_name)?;
+
             // first create shaders if necessary
             let folder = self.pmfx_folders.get(pipeline_name)
                 .unwrap_or_else(|| panic!("hotline_rs::pmfx:: expected to find pipeline {} in pmfx_folders", pipeline_name)).to_string();
@@ -2149,6 +3135,13 @@ impl<D> Pmfx<D> where D: gfx::Device {
     /// Create a RenderPipeline instance for the combination of pmfx_pipeline settings and an associated RenderPass
     pub fn create_render_pipeline(&mut self, device: &D, pipeline_name: &str, pass: &D::RenderPass) -> Result<(), super::Error> {
         if self.pmfx.pipelines.contains_key(pipeline_name) {
+            // validate shaders against the layouts before the pipeline is created for the first time
+            let created = self.render_pipelines.get(&pass.get_format_hash())
+                .is_some_and(|pipelines| pipelines.contains_key(pipeline_name));
+            if !created {
+                self.check_pipeline_reflection(pipeline_name)?;
+            }
+
             // first create shaders if necessary
             let folder = self.pmfx_folders.get(pipeline_name)
                 .unwrap_or_else(|| panic!("hotline_rs::pmfx:: expected to find pipeline {} in pmfx_folders", pipeline_name)).to_string();
@@ -2367,7 +3360,8 @@ impl<D> Pmfx<D> where D: gfx::Device {
         if self.reloader.check_for_reload() == ReloadState::Available {
             // wait for last GPU frame so we can drop the resources
             swap_chain.wait_for_last_frame();
-            self.reload(device)?;
+            let changed_files = self.reloader.get_changed_files();
+            self.reload(device, &changed_files)?;
             self.reloader.complete_reload();
         }
 
@@ -2387,131 +3381,251 @@ impl<D> Pmfx<D> where D: gfx::Device {
         Ok(())
     }
 
-    /// Reload all active resources based on hashes
-    pub fn reload(&mut self, device: &mut D) -> Result<(), super::Error> {
-        let reload_paths = self.pmfx_tracking.iter_mut().filter(|(_, tracking)| {
-            fs::metadata(&tracking.filepath).unwrap().modified().unwrap() > tracking.modified_time
-        }).map(|tracking| {
-            tracking.1.filepath.to_string_lossy().to_string()
-        }).collect::<Vec<String>>();
+    /// Returns the names of the loaded pmfx which need reloading because of `changed_files`, a pmfx is affected if it
+    /// depends on one of the files or if it is the pmfx file itself. If `changed_files` is empty the pmfx files which
+    /// have been modified since they were loaded are returned
+    pub fn get_pmfx_affected_by(&self, changed_files: &[String]) -> Vec<String> {
+        let mut affected = if changed_files.is_empty() {
+            self.pmfx_tracking.iter().filter(|(_, tracking)| {
+                fs::metadata(&tracking.filepath)
+                    .and_then(|meta| meta.modified())
+                    .map(|modified| modified > tracking.modified_time)
+                    .unwrap_or(false)
+            }).map(|(name, _)| name.to_string()).collect::<Vec<String>>()
+        }
+        else {
+            let changed = changed_files.iter().map(|file| normalise_dependency_path(file)).collect::<HashSet<String>>();
+            self.pmfx_tracking.iter().filter(|(_, tracking)| {
+                tracking.dependencies.iter().any(|dep| changed.contains(dep)) ||
+                changed.contains(&normalise_dependency_path(&tracking.filepath.to_string_lossy()))
+            }).map(|(name, _)| name.to_string()).collect::<Vec<String>>()
+        };
+        affected.sort();
+        affected
+    }
 
+    /// Reload resources affected by `changed_files`, which can be the source files a pmfx depends on or the pmfx
+    /// files themselves. Only the affected pmfx are reloaded and only the objects they declare are checked for changes
+    /// by hash, along with views which use recreated textures. Textures loaded from image files which changed are
+    /// reloaded into the same shader heap slot. If `changed_files` is empty all pmfx and image files which have
+    /// been modified are reloaded. Returns the names of the objects which were recreated
+    pub fn reload(&mut self, device: &mut D, changed_files: &[String]) -> Result<PmfxReloadInfo, super::Error> {
+        let mut info = PmfxReloadInfo::default();
         let mut rebuild_graph = false;
-        for reload_filepath in reload_paths {
-            if !reload_filepath.is_empty() {
-                println!("hotline_rs::pmfx:: reload from {}", reload_filepath);
-                let pmfx_data = fs::read(&reload_filepath).expect("hotline_rs::pmfx:: failed to read file");
+        for pmfx_name in self.get_pmfx_affected_by(changed_files) {
+            let reload_filepath = self.pmfx_tracking[&pmfx_name].filepath.to_path_buf();
+            println!("hotline_rs::pmfx:: reload from {}", reload_filepath.display());
+            let pmfx_data = fs::read(&reload_filepath)?;
+
+            let file : File = serde_json::from_slice(&pmfx_data)?;
+            let contents = PmfxContents::from_file(&file);
+
+            // track any new dependencies
+            let dependencies = file.dependencies.iter().map(|dep| normalise_dependency_path(dep)).collect::<HashSet<String>>();
+            for dep in &file.dependencies {
+                if !self.pmfx_tracking[&pmfx_name].dependencies.contains(&normalise_dependency_path(dep)) {
+                    self.reloader.add_file(dep);
+                }
+            }
 
-                let file : File = serde_json::from_slice(&pmfx_data)?;
-                self.merge_pmfx(file, PathBuf::from(&reload_filepath).parent().unwrap().to_str().unwrap());
+            self.merge_pmfx(file, reload_filepath.parent().unwrap().to_str().unwrap());
 
-                // remove stale states
-                // self.remove_stale();
+            // remove stale states
+            // self.remove_stale();
 
-                // find textures that need reloading
-                let reload_textures = self.textures.iter().filter(|(k, v)| {
-                    self.pmfx.textures.get(*k).map_or_else(|| false, |src| {
-                        src.hash != v.0
-                    })
-                }).map(|(k, _)| {
-                    k.to_string()
-                }).collect::<HashSet<String>>();
-
-                // Get views to reload from changed textures
-                let reload_texture_views = reload_textures.iter().fold(HashSet::new(), |mut v, t|{
-                    v.extend(self.get_view_texture_refs(t));
-                    v
-                });
-
-                // Find views that have changed by hash
-                let mut reload_views = Vec::new();
-                for (name, view) in &self.views {
-                    if self.pmfx.views.contains_key(&view.2) &&
-                    (self.pmfx.views.get(&view.2).unwrap().hash != view.0 || reload_texture_views.contains(name)) {
-                        reload_views.push((view.2.to_string(), name.to_string()));
-                    }
-                }
+            // find textures that need reloading
+            let reload_textures = self.textures.iter().filter(|(k, v)| {
+                contents.textures.contains(*k) && self.pmfx.textures.get(*k).map_or_else(|| false, |src| {
+                    src.hash != v.0
+                })
+            }).map(|(k, _)| {
+                k.to_string()
+            }).collect::<HashSet<String>>();
 
-                // find pipelines that need reloading
-                let mut reload_pipelines = Vec::new();
-                for (hash, formats) in &self.render_pipelines {
-                    for (name, permutations) in formats {
-                        for (mask, pipeline) in permutations {
+            // Get views to reload from changed textures
+            let reload_texture_views = reload_textures.iter().fold(HashSet::new(), |mut v, t|{
+                v.extend(self.get_view_texture_refs(t));
+                v
+            });
 
-                            let build_hash = self.pmfx.pipelines
-                                .get(name).unwrap()
-                                .get(&mask.to_string()).unwrap()
-                                .hash;
+            // Find views that have changed by hash
+            let mut reload_views = Vec::new();
+            for (name, view) in &self.views {
+                let changed = contents.views.contains(&view.2) && self.pmfx.views.contains_key(&view.2) &&
+                    self.pmfx.views.get(&view.2).unwrap().hash != view.0;
+                if changed || reload_texture_views.contains(name) {
+                    reload_views.push((view.2.to_string(), name.to_string()));
+                }
+            }
 
-                            if pipeline.0 != build_hash {
-                                reload_pipelines.push((*hash, name.to_string(), *mask));
-                            }
+            // find pipelines that need reloading
+            let mut reload_pipelines = Vec::new();
+            for (hash, formats) in &self.render_pipelines {
+                for (name, permutations) in formats {
+                    if !contents.pipelines.contains(name) {
+                        continue;
+                    }
+                    for (mask, pipeline) in permutations {
+                        let build_hash = self.pmfx.pipelines
+                            .get(name).unwrap()
+                            .get(&mask.to_string()).unwrap()
+                            .hash;
+
+                        if pipeline.0 != build_hash {
+                            reload_pipelines.push((*hash, name.to_string(), *mask));
                         }
                     }
                 }
+            }
 
-                // find shaders that need reloading
-                let mut reload_shaders = Vec::new();
-                for (name, shader) in &self.shaders {
-                    if self.pmfx.shaders.contains_key(name) {
-                        if *self.pmfx.shaders.get(name).unwrap() != shader.0 {
-                            reload_shaders.push(name.to_string());
-                        }
-                    }
+            // find shaders that need reloading
+            let mut reload_shaders = Vec::new();
+            for (name, shader) in &self.shaders {
+                if contents.shaders.contains(name) && self.pmfx.shaders.get(name).is_some_and(|hash| *hash != shader.0) {
+                    reload_shaders.push(name.to_string());
                 }
+            }
 
-                // reload textures
-                self.recreate_textures(device, &reload_textures)?;
+            // reload textures
+            self.recreate_textures(device, &reload_textures)?;
 
-                // reload views
-                for view in &reload_views {
-                    println!("hotline::pmfx:: reloading view: {}", view.1);
-                    self.views.remove(&view.1);
-                    self.pass_stats.remove(&view.1);
-                    rebuild_graph = true;
-                }
+            // reload views
+            for view in &reload_views {
+                println!("hotline::pmfx:: reloading view: {}", view.1);
+                self.views.remove(&view.1);
+                self.pass_stats.remove(&view.1);
+                rebuild_graph = true;
+            }
 
-                // reload shaders
-                for shader in &reload_shaders {
-                    println!("hotline::pmfx:: reloading shader: {}", shader);
-                    self.shaders.remove(shader);
+            // reload shaders
+            for shader in &reload_shaders {
+                println!("hotline::pmfx:: reloading shader: {}", shader);
+                self.shaders.remove(shader);
+            }
+
+            // reload pipelines tuple = (format_hash, pipeline_name, permutation_mask)
+            for pipeline in &reload_pipelines {
+                println!("hotline::pmfx:: reloading pipeline: {}", pipeline.1);
+
+                // TODO: here we could only remove affected permutations
+                let format_pipelines = self.render_pipelines.get_mut(&pipeline.0).unwrap();
+                format_pipelines.remove(&pipeline.1);
+
+                // find first with the same format
+                let compatiblew_view = self.views.iter().find(|(_, view)| {
+                    let pass = &view.1.lock().unwrap().pass;
+                    pass.get_format_hash() == pipeline.0
+                }).map(|v| v.0);
+
+                // create pipeline with the pass from compatible view
+                if let Some(compatiblew_view) = compatiblew_view {
+                    let view = self.get_view(compatiblew_view)?.clone();
+                    let view = view.lock().unwrap();
+                    self.create_render_pipeline(device, &pipeline.1, &view.pass)?;
+                }
+                else {
+                    println!("hotline::pmfx:: warning pipeline was not reloaded: {}", pipeline.1);
                 }
+            }
 
-                // reload pipelines tuple = (format_hash, pipeline_name, permutation_mask)
-                for pipeline in &reload_pipelines {
-                    println!("hotline::pmfx:: reloading pipeline: {}", pipeline.1);
+            // update the tracking info
+            if let Some(tracking) = self.pmfx_tracking.get_mut(&pmfx_name) {
+                tracking.modified_time = fs::metadata(&reload_filepath)
+                    .and_then(|meta| meta.modified())
+                    .unwrap_or(SystemTime::now());
+                tracking.dependencies = dependencies;
+                tracking.contents = contents;
+            }
 
-                    // TODO: here we could only remove affected permutations
-                    let format_pipelines = self.render_pipelines.get_mut(&pipeline.0).unwrap();
-                    format_pipelines.remove(&pipeline.1);
+            info.pmfx.push(pmfx_name);
+            info.textures.extend(reload_textures);
+            info.views.extend(reload_views.into_iter().map(|view| view.1));
+            info.shaders.extend(reload_shaders);
+            info.pipelines.extend(reload_pipelines.into_iter().map(|pipeline| pipeline.1));
+        }
 
-                    // find first with the same format
-                    let compatiblew_view = self.views.iter().find(|(_, view)| {
-                        let pass = &view.1.lock().unwrap().pass;
-                        pass.get_format_hash() == pipeline.0
-                    }).map(|v| v.0);
+        // reload textures from image files, textures recreated from a changed pmfx are already up to date
+        for texture_name in self.get_texture_files_affected_by(changed_files) {
+            if info.textures.contains(&texture_name) {
+                continue;
+            }
+            if let Err(err) = self.reload_texture_file(device, &texture_name) {
+                // the image may be partially written, it will be reloaded again when it next changes
+                println!("hotline_rs::pmfx:: failed to reload texture: {}: {}", texture_name, err.msg);
+                continue;
+            }
+            for view_name in self.get_view_texture_refs(&texture_name) {
+                self.views.remove(&view_name);
+                self.pass_stats.remove(&view_name);
+                info.views.push(view_name);
+                rebuild_graph = true;
+            }
+            info.textures.push(texture_name);
+        }
 
-                    // create pipeline with the pass from compatible view
-                    if let Some(compatiblew_view) = compatiblew_view {
-                        let view = self.get_view(compatiblew_view)?.clone();
-                        let view = view.lock().unwrap();
-                        self.create_render_pipeline(device, &pipeline.1, &view.pass)?;
-                    }
-                    else {
-                        println!("hotline::pmfx:: warning pipeline was not reloaded: {}", pipeline.1);
-                    }
-                }
+        if rebuild_graph {
+            self.create_render_graph(device, &self.active_render_graph.to_string())?;
+        }
 
-                // update the timestamp on the tracking info
-                self.pmfx_tracking.get_mut(&reload_filepath).map(|t| {
-                    t.modified_time = SystemTime::now();
-                    t
-                });
+        info.textures.sort();
+        info.views.sort();
+        info.views.dedup();
+        info.shaders.sort();
+        info.pipelines.sort();
+        info.pipelines.dedup();
+        Ok(info)
+    }
+
+    /// Returns the names of the textures loaded from image files which need reloading because of `changed_files`. If
+    /// `changed_files` is empty the textures whose image file has been modified since it was loaded are returned
+    fn get_texture_files_affected_by(&self, changed_files: &[String]) -> Vec<String> {
+        let changed = changed_files.iter().map(|file| normalise_dependency_path(file)).collect::<HashSet<String>>();
+        let mut affected = self.texture_files.iter().filter(|(name, tracking)| {
+            if !self.textures.contains_key(*name) {
+                false
+            }
+            else if changed.is_empty() {
+                fs::metadata(&tracking.filepath)
+                    .and_then(|meta| meta.modified())
+                    .map(|modified| modified > tracking.modified_time)
+                    .unwrap_or(false)
+            }
+            else {
+                changed.contains(&normalise_dependency_path(&tracking.filepath))
             }
+        }).map(|(name, _)| name.to_string()).collect::<Vec<String>>();
+        affected.sort();
+        affected
+    }
 
-            //
-            if rebuild_graph {
-                self.create_render_graph(device, &self.active_render_graph.to_string())?;
+    /// Reloads the texture `texture_name` from its image file, the new texture takes the shader heap slot of the old
+    /// texture so srv indices held by materials remain valid
+    fn reload_texture_file(&mut self, device: &mut D, texture_name: &str) -> Result<(), super::Error> {
+        let filepath = self.texture_files[texture_name].filepath.to_string();
+        if let Some(tracking) = self.texture_files.get_mut(texture_name) {
+            tracking.modified_time = get_modified_time(Path::new(&filepath));
+        }
+
+        println!("hotline_rs::pmfx:: reloading texture: {}", texture_name);
+        let img = image::load_from_file(&filepath, None)?;
+        let mut tex = device.create_texture_with_heaps::<u8>(
+            &img.info,
+            gfx::TextureHeapInfo {
+                shader: Some(&mut self.shader_heap),
+                ..Default::default()
+            },
+            super::data![&img.data]
+        )?;
+
+        if let Some((_, tracked)) = self.textures.get_mut(texture_name) {
+            if let Some(target) = Arc::get_mut(&mut tracked.texture) {
+                device.replace_texture_srv(target, &mut tex, &mut self.shader_heap)?;
             }
+            else {
+                println!("hotline_rs::pmfx:: warning: texture {} is shared, its srv index will change", texture_name);
+            }
+            tracked.texture = Arc::new(tex);
+            tracked.size = (img.info.width, img.info.height, img.info.depth);
         }
         Ok(())
     }
@@ -2519,10 +3633,12 @@ impl<D> Pmfx<D> where D: gfx::Device {
     /// Recreate the textures in `texture_names` call this when you know size / sample count has changed
     /// and the tracking info is updated
     fn recreate_textures(&mut self, device: &mut D, texture_names: &HashSet<String>) -> Result<(), super::Error> {
+        // remove the old and destroy, all are removed first so aliases do not share a stale owner
         for texture_name in texture_names {
-            // remove the old and destroy
             self.textures.remove(texture_name);
-            // create with new dimensions from 'window_sizes'
+        }
+        // create with new dimensions from 'window_sizes'
+        for texture_name in texture_names {
             self.create_texture(device, texture_name)?;
         }
         Ok(())
@@ -2714,6 +3830,60 @@ impl<D> Pmfx<D> where D: gfx::Device {
         }
     }
 
+    /// Returns the render graph built by the last call to `create_render_graph` in execute order, including the
+    /// auto-generated transitions, resolves and mip map generation steps
+    pub fn export_render_graph(&self) -> RenderGraphExport {
+        let graph = self.pmfx.render_graphs.get(&self.active_render_graph);
+        let nodes = self.command_queue.iter().map(|name| {
+            let mut node = self.export_render_graph_node(graph, name);
+            node.waits_on = self.async_waits.get(name).cloned().unwrap_or_default();
+            node
+        }).collect();
+
+        RenderGraphExport {
+            name: self.active_render_graph.to_string(),
+            nodes
+        }
+    }
+
+    /// Returns the export info for the node `name` in the `command_queue`
+    fn export_render_graph_node(&self, graph: Option<&HashMap<String, GraphPassInfo>>, name: &str) -> RenderGraphNode {
+        if let Some(info) = self.barrier_info.get(name) {
+            return info.clone();
+        }
+
+        // cubemaps push 5 additional passes named `pass_1..5`
+        let pass = graph.and_then(|graph| {
+            graph.get(name).or_else(|| name.rsplit_once('_').and_then(|(base, _)| graph.get(base)))
+        });
+
+        if let Some(pass) = pass {
+            RenderGraphNode {
+                name: name.to_string(),
+                kind: RenderGraphNodeKind::Pass,
+                view: pass.view.clone(),
+                pipelines: pass.pipelines.clone().unwrap_or_default(),
+                depends_on: pass.depends_on.clone().unwrap_or_default(),
+                uses: pass.uses.iter().flatten().map(|(texture, usage)| {
+                    (texture.to_string(), format!("{:?}", usage))
+                }).collect(),
+                queue: if self.async_passes.contains(name) {
+                    gfx::QueueType::Compute
+                }
+                else {
+                    gfx::QueueType::Graphics
+                },
+                ..Default::default()
+            }
+        }
+        else {
+            RenderGraphNode {
+                name: name.to_string(),
+                ..Default::default()
+            }
+        }
+    }
+
     pub fn get_render_graph_execute_order(&self) -> &Vec<String> {
         &self.command_queue
     }
@@ -2723,7 +3893,38 @@ impl<D> Pmfx<D> where D: gfx::Device {
         &mut self,
         device: &mut D) {
         let mut cmd_bufs = self.cmd_bufs.lock().unwrap();
+        let mut queue_sync = self.queue_sync.take();
+        let mut async_values : HashMap<String, u64> = HashMap::new();
+        let mut waited_value = 0;
         for node in &self.command_queue.clone() {
+            if let Some(sync) = &mut queue_sync {
+                // graphics waits for any async compute passes this node depends on
+                if let Some(waits) = self.async_waits.get(node) {
+                    let value = waits.iter().filter_map(|pass| async_values.get(pass)).copied().max().unwrap_or(0);
+                    if value > waited_value {
+                        device.wait_fence(gfx::QueueType::Graphics, &sync.compute_fence, value);
+                        waited_value = value;
+                    }
+                }
+
+                if self.async_passes.contains(node) {
+                    if let Some(cmd_buf) = cmd_bufs.get_mut(node) {
+                        // async compute waits for all previously submitted graphics work and then runs alongside it
+                        sync.graphics_value += 1;
+                        device.signal_fence(gfx::QueueType::Graphics, &sync.graphics_fence, sync.graphics_value);
+                        device.wait_fence(gfx::QueueType::Compute, &sync.graphics_fence, sync.graphics_value);
+
+                        cmd_buf.close().unwrap();
+                        device.execute_on_queue(gfx::QueueType::Compute, cmd_buf);
+
+                        sync.compute_value += 1;
+                        device.signal_fence(gfx::QueueType::Compute, &sync.compute_fence, sync.compute_value);
+                        async_values.insert(node.to_string(), sync.compute_value);
+                    }
+                    continue;
+                }
+            }
+
             if self.barriers.contains_key(node) {
                 // transition barriers
                 device.execute(&self.barriers[node]);
@@ -2739,6 +3940,15 @@ impl<D> Pmfx<D> where D: gfx::Device {
                 device.execute(cmd_buf);
             }
         }
+
+        // the swap chain only tracks the graphics queue, so the frame must also wait for all async compute work
+        if let Some(sync) = &queue_sync {
+            let value = async_values.values().copied().max().unwrap_or(0);
+            if value > waited_value {
+                device.wait_fence(gfx::QueueType::Graphics, &sync.compute_fence, value);
+            }
+        }
+        self.queue_sync = queue_sync;
     }
 
     /// Log an error with an assosiated view and message.
@@ -2767,7 +3977,7 @@ impl<D, A> imgui::UserInterface<D, A> for Pmfx<D> where D: gfx::Device, A: os::A
                     let w = thumb_size * aspect;
                     let h = thumb_size;
 
-                    imgui.image(&texture.1.texture, w, h);
+                    imgui.image(texture.1.texture.as_ref(), w, h);
 
                     imgui.same_line();
                     imgui.spacing();
@@ -2804,6 +4014,16 @@ impl<D, A> imgui::UserInterface<D, A> for Pmfx<D> where D: gfx::Device, A: os::A
                     imgui.text(camera);
                 }
                 imgui.separator();
+
+                imgui.text("Diagnostics");
+                imgui.separator();
+                for diagnostic in self.validate_render_graph(&self.active_render_graph) {
+                    match diagnostic.severity {
+                        GraphDiagnosticSeverity::Error => imgui.colour_text(&diagnostic.to_string(), vec4f(1.0, 0.3, 0.3, 1.0)),
+                        GraphDiagnosticSeverity::Warning => imgui.colour_text(&diagnostic.to_string(), vec4f(1.0, 0.8, 0.3, 1.0))
+                    }
+                }
+                imgui.separator();
             }
             imgui.end();
 
@@ -2817,6 +4037,13 @@ impl<D, A> imgui::UserInterface<D, A> for Pmfx<D> where D: gfx::Device, A: os::A
                 imgui.text(&format!("vertex_shader_invocations: {}", self.total_stats.pipeline_stats.vertex_shader_invocations));
                 imgui.text(&format!("pixel_shader_primitives: {}", self.total_stats.pipeline_stats.pixel_shader_primitives));
                 imgui.text(&format!("compute_shader_invocations: {}", self.total_stats.pipeline_stats.compute_shader_invocations));
+                imgui.separator();
+                imgui.text("transient textures");
+                imgui.separator();
+                let mb = 1024.0 * 1024.0;
+                imgui.text(&format!("textures: {} (allocated: {})", self.transient_stats.transient_textures, self.transient_stats.physical_textures));
+                imgui.text(&format!("memory: {:.2} (mb)", self.transient_stats.transient_bytes as f64 / mb));
+                imgui.text(&format!("saved: {:.2} (mb)", self.transient_stats.saved_bytes as f64 / mb));
             }
             imgui.end();
 
@@ -2855,7 +4082,12 @@ impl ReloadResponder for PmfxReloadResponder {
         self.start_time
     }
 
-    fn build(&mut self) -> std::process::ExitStatus {
+    fn build(&mut self, changed_files: &[String]) -> std::process::ExitStatus {
+        // image files are loaded directly by `Pmfx::reload` so they do not need a data build
+        if !changed_files.is_empty() && changed_files.iter().all(|file| is_texture_file(file)) {
+            return std::process::ExitStatus::default();
+        }
+
         // Shader/data compilation is hooked into the crate's build.rs (under the `build_data`
         // feature), so reuse that single path here. `cargo build --lib` re-runs the build script -
         // which recompiles the data - while skipping the example/bin targets; when only files under
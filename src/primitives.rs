@@ -1,5 +1,6 @@
 use crate::prelude::*;
 use maths_rs::prelude::*;
+use std::collections::HashMap;
 
 /// Generic structure for 3D lit geometry meshes
 #[derive(Clone)]
@@ -45,6 +46,51 @@ fn basis_from_axis(axis: Vec3f) -> (Vec3f, Vec3f, Vec3f) {
     (right, up, at)
 }
 
+/// Calculates smooth area weighted normals for an indexed triangle list, overwriting any existing normals
+pub fn calculate_normals(vertices: &mut [Vertex3D], indices: &[usize]) {
+    let mut normals = vec![Vec3f::zero(); vertices.len()];
+    for t in indices.chunks_exact(3) {
+        let p0 = vertices[t[0]].position;
+        let n = cross(vertices[t[1]].position - p0, vertices[t[2]].position - p0);
+        for i in t {
+            normals[*i] += n;
+        }
+    }
+    for (v, n) in vertices.iter_mut().zip(normals) {
+        v.normal = if mag(n) > 0.0 { normalize(n) } else { Vec3f::unit_y() };
+    }
+}
+
+/// Calculates tangents and bitangents for an indexed triangle list from the texcoords, tangents are orthogonalised
+/// against the existing normals and vertices with no usable texcoords get an arbitrary tangent perpendicular to the normal
+pub fn calculate_tangents(vertices: &mut [Vertex3D], indices: &[usize]) {
+    let mut tangents = vec![(Vec3f::zero(), Vec3f::zero()); vertices.len()];
+    for t in indices.chunks_exact(3) {
+        let (v0, v1, v2) = (&vertices[t[0]], &vertices[t[1]], &vertices[t[2]]);
+        let e1 = v1.position - v0.position;
+        let e2 = v2.position - v0.position;
+        let uv1 = v1.texcoord - v0.texcoord;
+        let uv2 = v2.texcoord - v0.texcoord;
+        let det = uv1.x * uv2.y - uv2.x * uv1.y;
+        if det.abs() < f32::EPSILON {
+            continue;
+        }
+        let r = 1.0 / det;
+        let tangent = (e1 * uv2.y - e2 * uv1.y) * r;
+        let bitangent = (e2 * uv1.x - e1 * uv2.x) * r;
+        for i in t {
+            tangents[*i].0 += tangent;
+            tangents[*i].1 += bitangent;
+        }
+    }
+    for (v, (t, b)) in vertices.iter_mut().zip(tangents) {
+        let t = t - v.normal * dot(v.normal, t);
+        v.tangent = if mag(t) > f32::EPSILON { normalize(t) } else { basis_from_axis(v.normal).0 };
+        let handedness = if dot(cross(v.normal, v.tangent), b) < 0.0 { -1.0 } else { 1.0 };
+        v.bitangent = cross(v.normal, v.tangent) * handedness;
+    }
+}
+
 /// Subdivides a single quad into 4 evenly distributed smaller quads, adjusting uv's and maintaining normals and tangents
 pub fn subdivide_quad(q0: &Vertex3D, q1: &Vertex3D, q2: &Vertex3D, q3: &Vertex3D, order: u32, max_order: u32) -> Vec<Vertex3D> {
     if order == max_order {
@@ -3516,4 +3562,294 @@ pub fn create_teapot_mesh<D: gfx::Device>(dev: &mut D, tessellation: usize) -> p
     }
 
     create_mesh_3d(dev, vertices, indices)
-}
\ No newline at end of file
+}
+/// Material parameters parsed from a wavefront .mtl file, texture maps are paths relative to the .mtl file
+#[derive(Clone)]
+pub struct ObjMaterial {
+    pub name: String,
+    /// Diffuse colour `Kd`
+    pub diffuse: Vec3f,
+    /// Specular colour `Ks`
+    pub specular: Vec3f,
+    /// Specular exponent `Ns`
+    pub shininess: f32,
+    /// Opacity from `d` or `1 - Tr`
+    pub opacity: f32,
+    /// `map_Kd`
+    pub diffuse_map: Option<String>,
+    /// `map_Ks`
+    pub specular_map: Option<String>,
+    /// `map_Bump`, `bump` or `norm`
+    pub normal_map: Option<String>
+}
+
+/// A mesh from an .obj file, a new mesh is started for each object `o`, group `g` or material change `usemtl`
+pub struct ObjMesh {
+    pub name: String,
+    /// Material name which can be looked up with `ObjData::get_material`
+    pub material: Option<String>,
+    pub vertices: Vec<Vertex3D>,
+    pub indices: Vec<usize>
+}
+
+/// Cpu side data for a wavefront .obj file and its materials, which can be passed to `create_obj_meshes`
+pub struct ObjData {
+    pub meshes: Vec<ObjMesh>,
+    pub materials: Vec<ObjMaterial>,
+    /// Material library files referenced by `mtllib`
+    pub material_libs: Vec<String>
+}
+
+impl ObjData {
+    /// Returns the material named `name` if it was loaded from one of the `material_libs`
+    pub fn get_material(&self, name: &str) -> Option<&ObjMaterial> {
+        self.materials.iter().find(|m| m.name == name)
+    }
+}
+
+/// Tracks the unique vertices of the mesh currently being parsed
+struct ObjMeshBuilder {
+    mesh: ObjMesh,
+    lookup: HashMap<(usize, Option<usize>, Option<usize>), usize>,
+    missing_normals: bool
+}
+
+impl ObjMeshBuilder {
+    fn new(name: String, material: Option<String>) -> Self {
+        ObjMeshBuilder {
+            mesh: ObjMesh {
+                name,
+                material,
+                vertices: Vec::new(),
+                indices: Vec::new()
+            },
+            lookup: HashMap::new(),
+            missing_normals: false
+        }
+    }
+
+    /// Finishes the mesh, generating normals and tangents and converting into hotline's left handed coordinate system
+    fn finish(mut self, meshes: &mut Vec<ObjMesh>) {
+        if self.mesh.indices.is_empty() {
+            return;
+        }
+
+        // obj is conventionally right handed with texcoords starting at the bottom, so mirror z, reverse winding and flip v
+        for v in &mut self.mesh.vertices {
+            v.position.z = -v.position.z;
+            v.normal.z = -v.normal.z;
+            v.texcoord.y = 1.0 - v.texcoord.y;
+        }
+        for t in self.mesh.indices.chunks_exact_mut(3) {
+            t.swap(1, 2);
+        }
+
+        if self.missing_normals {
+            calculate_normals(&mut self.mesh.vertices, &self.mesh.indices);
+        }
+        calculate_tangents(&mut self.mesh.vertices, &self.mesh.indices);
+        meshes.push(self.mesh);
+    }
+}
+
+/// Resolves a 1 based (or negative relative) obj index into a 0 based index into an array of `len`
+fn resolve_obj_index(token: &str, len: usize, line: usize) -> Result<usize, super::Error> {
+    let index = token.parse::<i64>().map_err(|_| super::Error {
+        msg: format!("hotline_rs::primitives:: obj line {}: invalid index '{}'", line, token)
+    })?;
+    let resolved = if index > 0 {
+        index - 1
+    }
+    else {
+        len as i64 + index
+    };
+    if index == 0 || resolved < 0 || resolved >= len as i64 {
+        return Err(super::Error {
+            msg: format!("hotline_rs::primitives:: obj line {}: index {} is out of range", line, index)
+        });
+    }
+    Ok(resolved as usize)
+}
+
+/// Parses `count` floats from an obj or mtl line, missing values are taken from `default`
+fn parse_obj_floats<'a>(tokens: impl Iterator<Item=&'a str>, default: &[f32], line: usize) -> Result<Vec<f32>, super::Error> {
+    let mut values = default.to_vec();
+    for (i, token) in tokens.take(default.len()).enumerate() {
+        values[i] = token.parse::<f32>().map_err(|_| super::Error {
+            msg: format!("hotline_rs::primitives:: obj line {}: invalid number '{}'", line, token)
+        })?;
+    }
+    Ok(values)
+}
+
+/// Parses the contents of a wavefront .obj file, polygons are triangulated as fans and unique position, texcoord and
+/// normal combinations are de-duplicated into indexed vertices. Normals are generated when not present and tangents
+/// are always generated from the texcoords. Materials are not loaded, use `load_obj` or `parse_mtl` with `material_libs`
+pub fn parse_obj(source: &str) -> Result<ObjData, super::Error> {
+    let mut positions = Vec::new();
+    let mut texcoords = Vec::new();
+    let mut normals = Vec::new();
+    let mut material_libs = Vec::new();
+    let mut meshes = Vec::new();
+    let mut builder = ObjMeshBuilder::new("default".to_string(), None);
+
+    for (line_index, line) in source.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let v = parse_obj_floats(tokens, &[0.0, 0.0, 0.0], line_number)?;
+                positions.push(vec3f(v[0], v[1], v[2]));
+            }
+            Some("vt") => {
+                let v = parse_obj_floats(tokens, &[0.0, 0.0], line_number)?;
+                texcoords.push(vec2f(v[0], v[1]));
+            }
+            Some("vn") => {
+                let v = parse_obj_floats(tokens, &[0.0, 0.0, 0.0], line_number)?;
+                normals.push(vec3f(v[0], v[1], v[2]));
+            }
+            Some("o") | Some("g") => {
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                let material = builder.mesh.material.clone();
+                builder.finish(&mut meshes);
+                builder = ObjMeshBuilder::new(name, material);
+            }
+            Some("usemtl") => {
+                let material = tokens.collect::<Vec<_>>().join(" ");
+                if builder.mesh.indices.is_empty() {
+                    builder.mesh.material = Some(material);
+                }
+                else {
+                    let name = builder.mesh.name.clone();
+                    builder.finish(&mut meshes);
+                    builder = ObjMeshBuilder::new(name, Some(material));
+                }
+            }
+            Some("mtllib") => {
+                material_libs.extend(tokens.map(|t| t.to_string()));
+            }
+            Some("f") => {
+                let mut polygon = Vec::new();
+                for vertex in tokens {
+                    let mut parts = vertex.split('/');
+                    let p = resolve_obj_index(parts.next().unwrap_or(""), positions.len(), line_number)?;
+                    let t = match parts.next() {
+                        Some(t) if !t.is_empty() => Some(resolve_obj_index(t, texcoords.len(), line_number)?),
+                        _ => None
+                    };
+                    let n = match parts.next() {
+                        Some(n) if !n.is_empty() => Some(resolve_obj_index(n, normals.len(), line_number)?),
+                        _ => None
+                    };
+
+                    let vertices = &mut builder.mesh.vertices;
+                    let index = *builder.lookup.entry((p, t, n)).or_insert_with(|| {
+                        vertices.push(Vertex3D {
+                            position: positions[p],
+                            texcoord: t.map(|t| texcoords[t]).unwrap_or(Vec2f::zero()),
+                            normal: n.map(|n| normals[n]).unwrap_or(Vec3f::zero()),
+                            tangent: Vec3f::zero(),
+                            bitangent: Vec3f::zero()
+                        });
+                        vertices.len() - 1
+                    });
+                    builder.missing_normals |= n.is_none();
+                    polygon.push(index);
+                }
+
+                if polygon.len() < 3 {
+                    return Err(super::Error {
+                        msg: format!("hotline_rs::primitives:: obj line {}: face has less than 3 vertices", line_number)
+                    });
+                }
+
+                for i in 1..polygon.len() - 1 {
+                    builder.mesh.indices.extend([polygon[0], polygon[i], polygon[i + 1]]);
+                }
+            }
+            // lines, points, smoothing groups and anything else are ignored
+            _ => ()
+        }
+    }
+    builder.finish(&mut meshes);
+
+    Ok(ObjData {
+        meshes,
+        materials: Vec::new(),
+        material_libs
+    })
+}
+
+/// Parses the contents of a wavefront .mtl file. Texture map options are skipped and the last token is used as the filename
+pub fn parse_mtl(source: &str) -> Result<Vec<ObjMaterial>, super::Error> {
+    let mut materials : Vec<ObjMaterial> = Vec::new();
+    for (line_index, line) in source.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut tokens = line.split_whitespace();
+        let key = if let Some(key) = tokens.next() {
+            key
+        }
+        else {
+            continue;
+        };
+
+        if key == "newmtl" {
+            materials.push(ObjMaterial {
+                name: tokens.collect::<Vec<_>>().join(" "),
+                diffuse: Vec3f::one(),
+                specular: Vec3f::zero(),
+                shininess: 0.0,
+                opacity: 1.0,
+                diffuse_map: None,
+                specular_map: None,
+                normal_map: None
+            });
+            continue;
+        }
+
+        let material = materials.last_mut().ok_or(super::Error {
+            msg: format!("hotline_rs::primitives:: mtl line {}: '{}' appears before newmtl", line_number, key)
+        })?;
+
+        match key {
+            "Kd" => {
+                let v = parse_obj_floats(tokens, &[1.0, 1.0, 1.0], line_number)?;
+                material.diffuse = vec3f(v[0], v[1], v[2]);
+            }
+            "Ks" => {
+                let v = parse_obj_floats(tokens, &[0.0, 0.0, 0.0], line_number)?;
+                material.specular = vec3f(v[0], v[1], v[2]);
+            }
+            "Ns" => material.shininess = parse_obj_floats(tokens, &[0.0], line_number)?[0],
+            "d" => material.opacity = parse_obj_floats(tokens, &[1.0], line_number)?[0],
+            "Tr" => material.opacity = 1.0 - parse_obj_floats(tokens, &[0.0], line_number)?[0],
+            "map_Kd" => material.diffuse_map = tokens.last().map(|t| t.to_string()),
+            "map_Ks" => material.specular_map = tokens.last().map(|t| t.to_string()),
+            "map_Bump" | "map_bump" | "bump" | "norm" => material.normal_map = tokens.last().map(|t| t.to_string()),
+            _ => ()
+        }
+    }
+    Ok(materials)
+}
+
+/// Loads a wavefront .obj file and any .mtl material libraries it references, which are located relative to `filepath`
+pub fn load_obj(filepath: &str) -> Result<ObjData, super::Error> {
+    let source = String::from_utf8(super::read_data(filepath)?)?;
+    let mut obj = parse_obj(&source)?;
+    let dir = std::path::Path::new(filepath).parent().unwrap_or(std::path::Path::new(""));
+    for lib in &obj.material_libs {
+        let mtl = String::from_utf8(super::read_data(dir.join(lib))?)?;
+        obj.materials.extend(parse_mtl(&mtl)?);
+    }
+    Ok(obj)
+}
+
+/// Creates a `pmfx::Mesh` for each mesh in `obj`
+pub fn create_obj_meshes<D: gfx::Device>(dev: &mut D, obj: &ObjData) -> Vec<pmfx::Mesh<D>> {
+    obj.meshes.iter().map(|mesh| {
+        create_mesh_3d(dev, mesh.vertices.clone(), mesh.indices.clone())
+    }).collect()
+}
@@ -6,6 +6,17 @@ use std::sync::Mutex;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::thread::JoinHandle;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Interval to re-query the files tracked by a `ReloadResponder`, so new files are picked up by the `FileWatcher`
+const WATCH_REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+/// Time the `FileWatcher` waits for changes on each iteration of the file watcher thread
+const WATCH_WAIT_TIMEOUT: Duration = Duration::from_millis(16);
+/// After a change, further changes within this time are collected into the same build (ie. an editor saving all files)
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(50);
+/// Maximum time changes are collected for a single build, so files which are written continuously do not starve builds
+pub const WATCH_DEBOUNCE_MAX: Duration = Duration::from_millis(500);
 
 /// Basic Reloader which can check timestamps on files and then callback functions supplied by the reload responder
 pub struct Reloader {
@@ -19,6 +30,14 @@ pub struct Reloader {
     shutdown: Arc<AtomicBool>,
     /// handle to the file watcher thread, joined on drop
     thread: Option<JoinHandle<()>>,
+    /// diagnostics from the most recent build
+    diagnostics: Arc<Mutex<Vec<BuildDiagnostic>>>,
+    /// true if the most recent build failed, reloading is blocked until a build succeeds
+    build_failed: Arc<AtomicBool>,
+    /// the file watcher backend, moved into the file watcher thread on `start`
+    watcher: Option<Box<dyn FileWatcher>>,
+    /// files which changed and were built since the last `complete_reload`
+    changed_files: Arc<Mutex<Vec<String>>>,
 }
 
 /// Query reload status with a responder:
@@ -33,6 +52,58 @@ pub enum ReloadState {
     Available,
 }
 
+/// Severity of a `BuildDiagnostic`
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum DiagnosticLevel {
+    Error,
+    Warning,
+    Note,
+    Help
+}
+
+/// A diagnostic message emitted by a build with the location in the source code it refers to
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct BuildDiagnostic {
+    /// Severity of the diagnostic
+    pub level: DiagnosticLevel,
+    /// The diagnostic message
+    pub message: String,
+    /// Source file of the primary span if the diagnostic has a location
+    pub file: Option<String>,
+    /// 1-based line of the start of the primary span
+    pub line: u32,
+    /// 1-based column of the start of the primary span
+    pub column: u32,
+    /// 1-based line of the end of the primary span
+    pub line_end: u32,
+    /// 1-based column of the end of the primary span
+    pub column_end: u32,
+    /// The full message as rendered by the compiler including source snippets, if available
+    pub rendered: Option<String>
+}
+
+impl std::fmt::Display for DiagnosticLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DiagnosticLevel::Error => write!(f, "error"),
+            DiagnosticLevel::Warning => write!(f, "warning"),
+            DiagnosticLevel::Note => write!(f, "note"),
+            DiagnosticLevel::Help => write!(f, "help")
+        }
+    }
+}
+
+impl std::fmt::Display for BuildDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:{}:{}: {}: {}", file, self.line, self.column, self.level, self.message)
+        }
+        else {
+            write!(f, "{}: {}", self.level, self.message)
+        }
+    }
+}
+
 /// Trait to be implemented for custom reloader responses
 pub trait ReloadResponder: Send + Sync {
     /// Add a file which is tracked and the time stamp compared for changes
@@ -41,19 +112,332 @@ pub trait ReloadResponder: Send + Sync {
     fn get_files(&self) -> Vec<String>;
     /// Retuns the current modified time of the built resource
     fn get_last_mtime(&self) -> SystemTime;
-    /// Called when a tracked file is modified more recently than get_base_mtime
-    fn build(&mut self) -> ExitStatus;
+    /// Called when tracked files are modified more recently than get_base_mtime, with the list of files which changed
+    fn build(&mut self, changed_files: &[String]) -> ExitStatus;
+    /// Returns the diagnostics from the last call to `build`
+    fn get_diagnostics(&self) -> Vec<BuildDiagnostic> {
+        Vec::new()
+    }
+}
+
+/// Backend used by the `Reloader` to detect changes to the files tracked by a `ReloadResponder`, watchers are `Sync`
+/// so a `Reloader` can be held in a bevy `Resource`
+pub trait FileWatcher: Send + Sync {
+    /// Sets the files to watch, this is called periodically with all of the files tracked by the responder. Files
+    /// which are added after the first call and already exist are reported as changed by the next call to `wait`
+    fn watch(&mut self, files: &[String]);
+    /// Waits for up to `timeout` and returns the watched files which have changed since the last call
+    fn wait(&mut self, timeout: Duration) -> Vec<String>;
+}
+
+/// Collects further changes from `watcher` until no changes arrive within `debounce`, `max_wait` has elapsed or
+/// `shutdown` is set. Returns the changed files, which may contain duplicates
+pub fn debounce_changes(watcher: &mut dyn FileWatcher, debounce: Duration, max_wait: Duration, shutdown: &AtomicBool) -> Vec<String> {
+    let start = std::time::Instant::now();
+    let mut changed = Vec::new();
+    while !shutdown.load(Ordering::Relaxed) {
+        let remaining = max_wait.saturating_sub(start.elapsed());
+        if remaining.is_zero() {
+            break;
+        }
+        let more = watcher.wait(debounce.min(remaining));
+        if more.is_empty() {
+            break;
+        }
+        changed.extend(more);
+    }
+    changed
+}
+
+/// Resolves `file` to a canonical path, relative paths are relative to the data directory. Returns `None` if the file
+/// does not exist
+fn resolve_filepath(file: &str) -> Option<PathBuf> {
+    let path = Path::new(file);
+    if path.is_absolute() {
+        std::fs::canonicalize(path).ok()
+    }
+    else {
+        std::fs::canonicalize(PathBuf::from(super::get_data_path(".")).join(path)).ok()
+    }
+}
+
+/// Returns the modified time of `file` or `None` if it does not exist
+fn get_mtime(file: &str) -> Option<SystemTime> {
+    resolve_filepath(file).and_then(|path| std::fs::metadata(path).ok()).and_then(|meta| meta.modified().ok())
+}
+
+/// File watcher which polls the modified time of each file, this is used when there is no event driven implementation
+/// for the platform
+#[derive(Default)]
+pub struct PollingWatcher {
+    mtimes: HashMap<String, Option<SystemTime>>,
+    initialised: bool
+}
+
+impl PollingWatcher {
+    /// Create a new polling file watcher
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl FileWatcher for PollingWatcher {
+    fn watch(&mut self, files: &[String]) {
+        let files = files.iter().collect::<HashSet<&String>>();
+        self.mtimes.retain(|file, _| files.contains(file));
+        for file in files {
+            if !self.mtimes.contains_key(file) {
+                // files added later have no previous time, so they are reported as changed
+                let mtime = if self.initialised {
+                    None
+                }
+                else {
+                    get_mtime(file)
+                };
+                self.mtimes.insert(file.to_string(), mtime);
+            }
+        }
+        self.initialised = true;
+    }
+
+    fn wait(&mut self, timeout: Duration) -> Vec<String> {
+        thread::sleep(timeout);
+        let mut changed = Vec::new();
+        for (file, prev) in &mut self.mtimes {
+            let mtime = get_mtime(file);
+            if mtime.is_some() && mtime > *prev {
+                changed.push(file.to_string());
+            }
+            if mtime.is_some() {
+                *prev = mtime;
+            }
+        }
+        changed.sort();
+        changed
+    }
+}
+
+/// Event driven file watcher using inotify, watches the directories containing the tracked files so files which
+/// are replaced (as many editors do when saving) are detected
+#[cfg(target_os = "linux")]
+pub struct InotifyWatcher {
+    fd: i32,
+    /// watch descriptor to watched directory
+    dirs: HashMap<i32, PathBuf>,
+    /// canonical path of tracked files to the path supplied by the responder
+    files: HashMap<PathBuf, String>,
+    /// paths of events in watched directories which are not tracked (yet), new files created before `watch` is called
+    untracked: HashSet<PathBuf>,
+    /// changes detected during `watch` to report on the next `wait`
+    pending: Vec<String>,
+    initialised: bool
+}
+
+#[cfg(target_os = "linux")]
+impl InotifyWatcher {
+    /// Create a new inotify file watcher, returns an error if inotify is not available
+    pub fn new() -> Result<Self, super::Error> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(super::Error {
+                msg: format!("hotline_rs::reloader: inotify_init1 failed: {}", std::io::Error::last_os_error())
+            });
+        }
+        Ok(Self {
+            fd,
+            dirs: HashMap::new(),
+            files: HashMap::new(),
+            untracked: HashSet::new(),
+            pending: Vec::new(),
+            initialised: false
+        })
+    }
+
+    /// Reads all available events and returns the paths they refer to, or `None` if the event queue overflowed
+    fn read_events(&mut self) -> Option<Vec<PathBuf>> {
+        const EVENT_SIZE: usize = std::mem::size_of::<libc::inotify_event>();
+        let mut paths = Vec::new();
+        let mut overflow = false;
+        // u64 storage to keep events aligned
+        let mut buffer = [0u64; 1024];
+        loop {
+            let len = unsafe {
+                libc::read(self.fd, buffer.as_mut_ptr().cast(), std::mem::size_of_val(&buffer))
+            };
+            if len <= 0 {
+                break;
+            }
+            let bytes = unsafe {
+                std::slice::from_raw_parts(buffer.as_ptr().cast::<u8>(), len as usize)
+            };
+            let mut offset = 0;
+            while offset + EVENT_SIZE <= bytes.len() {
+                let event = unsafe {
+                    std::ptr::read_unaligned(bytes[offset..].as_ptr().cast::<libc::inotify_event>())
+                };
+                let name_start = offset + EVENT_SIZE;
+                let name_end = (name_start + event.len as usize).min(bytes.len());
+                offset = name_end;
+
+                if event.mask & libc::IN_Q_OVERFLOW != 0 {
+                    overflow = true;
+                    continue;
+                }
+
+                if event.mask & libc::IN_IGNORED != 0 {
+                    // the directory was removed or unwatched
+                    self.dirs.remove(&event.wd);
+                    continue;
+                }
+
+                if let Some(dir) = self.dirs.get(&event.wd) {
+                    let name = &bytes[name_start..name_end];
+                    let name = name.split(|b| *b == 0).next().unwrap_or(name);
+                    if !name.is_empty() {
+                        paths.push(dir.join(String::from_utf8_lossy(name).to_string()));
+                    }
+                }
+            }
+        }
+        if overflow {
+            None
+        }
+        else {
+            Some(paths)
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl FileWatcher for InotifyWatcher {
+    fn watch(&mut self, files: &[String]) {
+        // resolve tracked files, new files which have had events since they were created are reported as changed
+        let mut tracked = HashMap::new();
+        for file in files {
+            if let Some(path) = resolve_filepath(file) {
+                if self.initialised && !self.files.contains_key(&path) && self.untracked.contains(&path) {
+                    self.pending.push(file.to_string());
+                }
+                tracked.insert(path, file.to_string());
+            }
+        }
+        self.files = tracked;
+        self.untracked.clear();
+        self.initialised = true;
+
+        // watch the directories containing tracked files
+        let dirs = self.files.keys().filter_map(|path| path.parent()).map(|dir| dir.to_path_buf()).collect::<HashSet<PathBuf>>();
+        let mut watched = HashSet::new();
+        for (wd, dir) in &self.dirs {
+            if !dirs.contains(dir) {
+                unsafe {
+                    libc::inotify_rm_watch(self.fd, *wd);
+                }
+            }
+            else {
+                watched.insert(dir.to_path_buf());
+            }
+        }
+        self.dirs.retain(|_, dir| dirs.contains(dir));
+
+        let mask = libc::IN_CLOSE_WRITE | libc::IN_MODIFY | libc::IN_MOVED_TO | libc::IN_CREATE;
+        for dir in dirs {
+            if watched.contains(&dir) {
+                continue;
+            }
+            if let Ok(null_dir) = std::ffi::CString::new(dir.to_string_lossy().as_bytes()) {
+                let wd = unsafe {
+                    libc::inotify_add_watch(self.fd, null_dir.as_ptr(), mask)
+                };
+                if wd >= 0 {
+                    self.dirs.insert(wd, dir);
+                }
+                else {
+                    println!("hotline_rs::reloader: failed to watch {}: {}", dir.display(), std::io::Error::last_os_error());
+                }
+            }
+        }
+    }
+
+    fn wait(&mut self, timeout: Duration) -> Vec<String> {
+        let mut changed = std::mem::take(&mut self.pending);
+        let mut poll_fd = libc::pollfd {
+            fd: self.fd,
+            events: libc::POLLIN,
+            revents: 0
+        };
+        let timeout_ms = if changed.is_empty() {
+            timeout.as_millis().min(i32::MAX as u128) as i32
+        }
+        else {
+            0
+        };
+        let ready = unsafe {
+            libc::poll(&mut poll_fd, 1, timeout_ms)
+        };
+        if ready > 0 {
+            match self.read_events() {
+                Some(paths) => {
+                    for path in paths {
+                        if let Some(file) = self.files.get(&path) {
+                            changed.push(file.to_string());
+                        }
+                        else {
+                            self.untracked.insert(path);
+                        }
+                    }
+                }
+                None => {
+                    // events were lost, assume everything changed
+                    changed.extend(self.files.values().cloned());
+                }
+            }
+        }
+        changed.sort();
+        changed.dedup();
+        changed
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for InotifyWatcher {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// Returns the best available `FileWatcher` for the platform, falling back to a `PollingWatcher`
+pub fn create_file_watcher() -> Box<dyn FileWatcher> {
+    #[cfg(target_os = "linux")]
+    {
+        match InotifyWatcher::new() {
+            Ok(watcher) => return Box::new(watcher),
+            Err(err) => println!("{}, falling back to polling", err.msg)
+        }
+    }
+    Box::new(PollingWatcher::new())
 }
 
 impl Reloader {
     /// Create a new instance of a reload with the designated ReloadResponder and start waiting for file changes
     pub fn create(responder: Box<dyn ReloadResponder>) -> Self {
+        Self::create_with_watcher(responder, create_file_watcher())
+    }
+
+    /// Create a new instance of a reloader which uses the supplied `FileWatcher` to detect changes
+    pub fn create_with_watcher(responder: Box<dyn ReloadResponder>, watcher: Box<dyn FileWatcher>) -> Self {
         Self {
             lock: Arc::new(Mutex::new(ReloadState::None)),
             responder: Arc::new(Mutex::new(responder)),
             hot: false,
             shutdown: Arc::new(AtomicBool::new(false)),
             thread: None,
+            diagnostics: Arc::new(Mutex::new(Vec::new())),
+            build_failed: Arc::new(AtomicBool::new(false)),
+            watcher: Some(watcher),
+            changed_files: Arc::new(Mutex::new(Vec::new())),
         }.start()
     }
 
@@ -62,6 +446,16 @@ impl Reloader {
         self.hot
     }
 
+    /// Returns the diagnostics from the most recent build
+    pub fn get_diagnostics(&self) -> Vec<BuildDiagnostic> {
+        self.diagnostics.lock().unwrap().to_vec()
+    }
+
+    /// Returns true if the most recent build failed, a reload will not become available until a build succeeds
+    pub fn is_build_failed(&self) -> bool {
+        self.build_failed.load(Ordering::Relaxed)
+    }
+
     /// Add files to check in a thread safe manner
     pub fn add_file(&mut self, path: &str) {
         let mut responder = self.responder.lock().unwrap();
@@ -76,6 +470,7 @@ impl Reloader {
     }
 
     /// Call this each frame, if ReloadResult::Reload you must then clean up any data in preperation for a reload
+    /// When a reload is `Available` the files which changed can be obtained from `get_changed_files`
     pub fn check_for_reload(&mut self) -> ReloadState {
         let lock = self.lock.lock().unwrap();
         if *lock != ReloadState::None {
@@ -84,9 +479,16 @@ impl Reloader {
         *lock
     }
 
+    /// Returns the files which changed and have been built successfully since the last `complete_reload`, these are the
+    /// changes included in an `Available` reload
+    pub fn get_changed_files(&self) -> Vec<String> {
+        self.changed_files.lock().unwrap().to_vec()
+    }
+
     /// Once data is cleaned up and it is safe to proceed this functions must be called
     pub fn complete_reload(&mut self) {
         let mut lock = self.lock.lock().unwrap();
+        self.changed_files.lock().unwrap().clear();
         // signal it is safe to proceed and reload the new code
         *lock = ReloadState::None;
         self.hot = false;
@@ -94,62 +496,83 @@ impl Reloader {
         println!("hotline_rs::reloader: reload complete");
     }
 
-    /// Returns the latest timestamp of all the files tracked by the reloader
-    fn file_watcher_thread_check_mtime(responder: &Arc<Mutex<Box<dyn ReloadResponder>>>, cur_mtime: SystemTime) -> SystemTime {
-        let responder = responder.lock().unwrap();
-        let files = responder.get_files();
-        for file in &files {
-            let filepath = super::get_data_path(file);
-            if let Ok(meta) = std::fs::metadata(&filepath) {
-                if let Ok(mtime) = meta.modified() {
-                    if mtime > cur_mtime {
-                        return mtime;
-                    }
-                }
-            }
-            else {
-                println!("hotline_rs::reloader: {filepath} not found!")
-            }
-        };
-        cur_mtime
+    /// Returns the files tracked by the reloader which have been modified more recently than `mtime`
+    fn get_files_modified_since(files: &[String], mtime: SystemTime) -> Vec<String> {
+        files.iter().filter(|file| {
+            get_mtime(file).map(|file_mtime| file_mtime > mtime).unwrap_or(false)
+        }).cloned().collect()
     }
 
-    /// Background thread will watch for changed filestamps among the registered files from the responder
-    fn file_watcher_thread(&self) -> JoinHandle<()> {
+    /// Background thread will wait for changes to the registered files from the responder using the `FileWatcher`
+    fn file_watcher_thread(&mut self) -> JoinHandle<()> {
         let lock = self.lock.clone();
         let shutdown = self.shutdown.clone();
-        let mut cur_mtime = SystemTime::now();
-        let mut first_time_check = true;
         let responder = self.responder.clone();
+        let diagnostics = self.diagnostics.clone();
+        let build_failed = self.build_failed.clone();
+        let changed_files = self.changed_files.clone();
+        let mut watcher = self.watcher.take().unwrap_or_else(create_file_watcher);
         thread::Builder::new().name("hotline_rs::reloader::file_watcher_thread".to_string()).spawn(move || {
+            // check base mtime of the output lib, it might be old / stale when we run with a fresh client
+            let files = responder.lock().unwrap().get_files();
+            let last_mtime = responder.lock().unwrap().get_last_mtime();
+            let mut changed = Self::get_files_modified_since(&files, last_mtime);
+            watcher.watch(&files);
+            let mut last_refresh = std::time::Instant::now();
+
+            // changes from failed builds are included in the next build
+            let mut unbuilt = Vec::new();
+
             while !shutdown.load(Ordering::Relaxed) {
-                // check base mtime of the output lib, it might be old / stale when we run with a fresh client
-                if first_time_check {
-                    cur_mtime = responder.lock().unwrap().get_last_mtime();
-                    first_time_check = false;
+                // re-query the files to watch new files
+                if last_refresh.elapsed() > WATCH_REFRESH_INTERVAL {
+                    let files = responder.lock().unwrap().get_files();
+                    watcher.watch(&files);
+                    last_refresh = std::time::Instant::now();
+                }
+
+                changed.extend(watcher.wait(WATCH_WAIT_TIMEOUT));
+                if changed.is_empty() {
+                    continue;
                 }
 
-                let mtime = Self::file_watcher_thread_check_mtime(&responder, cur_mtime);
-                if mtime > cur_mtime {
-                    // signal we are building (this might take a while)
+                // debounce, bursts of writes are collected into a single build
+                changed.extend(debounce_changes(watcher.as_mut(), WATCH_DEBOUNCE, WATCH_DEBOUNCE_MAX, &shutdown));
+                changed.append(&mut unbuilt);
+                changed.sort();
+                changed.dedup();
+
+                // signal we are building (this might take a while)
+                let mut a = lock.lock().unwrap();
+                println!("hotline_rs::reloader: changes detected, building");
+                for file in &changed {
+                    println!("hotline_rs::reloader:     {}", file);
+                }
+                *a = ReloadState::Building;
+                drop(a);
+                let mut responder = responder.lock().unwrap();
+                let success = responder.build(&changed).success();
+                *diagnostics.lock().unwrap() = responder.get_diagnostics();
+                build_failed.store(!success, Ordering::Relaxed);
+
+                if success {
+                    // signal reload is ready, changes accumulate until the reload completes
                     let mut a = lock.lock().unwrap();
-                    println!("hotline_rs::reloader: changes detected, building");
-                    *a = ReloadState::Building;
-                    drop(a);
-                    let mut responder = responder.lock().unwrap();
-                    if responder.build().success() {
-                        // signal reload is ready
-                        let mut a = lock.lock().unwrap();
-                        println!("hotline_rs::reloader: build success, reload available");
-                        *a = ReloadState::Available;
-                        drop(a);
+                    let mut pending = changed_files.lock().unwrap();
+                    for file in changed.drain(..) {
+                        if !pending.contains(&file) {
+                            pending.push(file);
+                        }
                     }
-                    else {
-                        println!("hotline_rs::reloader: build failed");
-                    }
-                    cur_mtime = mtime;
+                    drop(pending);
+                    println!("hotline_rs::reloader: build success, reload available");
+                    *a = ReloadState::Available;
+                    drop(a);
+                }
+                else {
+                    println!("hotline_rs::reloader: build failed");
+                    unbuilt.append(&mut changed);
                 }
-                thread::sleep(Duration::from_millis(16));
             }
         }).expect("hotline_rs::reloader: failed to spawn file watcher thread")
     }
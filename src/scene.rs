@@ -0,0 +1,649 @@
+use crate::gfx;
+use crate::pmfx;
+use crate::primitives;
+use crate::ecs_base::*;
+
+use bevy_ecs::prelude::*;
+use maths_rs::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+/// The current version of the scene format. When the schema changes bump this and add an upgrade from the previous
+/// version to `migrate_scene_json` and `read_binary_entity`
+pub const SCENE_VERSION: u32 = 1;
+
+/// Identifier at the start of binary scene files
+pub const SCENE_BINARY_MAGIC: [u8; 4] = *b"HSCN";
+
+/// File formats a `Scene` can be saved in
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SceneFormat {
+    /// Human readable json for authoring and diffing
+    Json,
+    /// Compact little endian binary beginning with `SCENE_BINARY_MAGIC`
+    Binary
+}
+
+/// Serialisable `LightComponent`, shadow map info is allocated at runtime and not saved
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SceneLight {
+    pub light_type: LightType,
+    pub direction: (f32, f32, f32),
+    pub cutoff: f32,
+    pub falloff: f32,
+    pub radius: f32
+}
+
+/// Serialisable `Camera`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SceneCamera {
+    pub camera_type: CameraType,
+    pub rot: (f32, f32, f32),
+    pub focus: (f32, f32, f32),
+    pub zoom: f32
+}
+
+/// A single entity in a scene, components which are `None` are not added to the entity
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SceneEntity {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Index of the parent entity in `Scene::entities`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<(f32, f32, f32)>,
+    /// Quaternion (x, y, z, w)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<(f32, f32, f32, f32)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<(f32, f32, f32)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub colour: Option<(f32, f32, f32, f32)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub light: Option<SceneLight>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub camera: Option<SceneCamera>,
+    /// Name of a mesh asset, see `create_mesh_asset`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mesh: Option<String>,
+    /// Name of a material asset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material: Option<String>
+}
+
+/// A versioned collection of entities which can be saved to and loaded from json or binary files
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Scene {
+    pub version: u32,
+    pub entities: Vec<SceneEntity>
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Scene {
+            version: SCENE_VERSION,
+            entities: Vec::new()
+        }
+    }
+}
+
+bitflags! {
+    /// Components present in a binary scene entity
+    struct SceneEntityFlags: u16 {
+        const NAME = 1<<0;
+        const PARENT = 1<<1;
+        const POSITION = 1<<2;
+        const ROTATION = 1<<3;
+        const SCALE = 1<<4;
+        const COLOUR = 1<<5;
+        const LIGHT = 1<<6;
+        const CAMERA = 1<<7;
+        const MESH = 1<<8;
+        const MATERIAL = 1<<9;
+    }
+}
+
+fn version_error(version: u32) -> super::Error {
+    if version > SCENE_VERSION {
+        super::Error {
+            msg: format!("hotline_rs::scene:: scene version {} is newer than the supported version {}", version, SCENE_VERSION)
+        }
+    }
+    else {
+        super::Error {
+            msg: format!("hotline_rs::scene:: unsupported scene version {}", version)
+        }
+    }
+}
+
+/// Upgrades the json of a scene saved with `version` to the layout of `SCENE_VERSION`
+fn migrate_scene_json(value: serde_json::Value, version: u32) -> Result<serde_json::Value, super::Error> {
+    match version {
+        SCENE_VERSION => Ok(value),
+        _ => Err(version_error(version))
+    }
+}
+
+fn light_type_to_u8(light_type: LightType) -> u8 {
+    match light_type {
+        LightType::Point => 0,
+        LightType::Spot => 1,
+        LightType::Directional => 2
+    }
+}
+
+fn camera_type_to_u8(camera_type: CameraType) -> u8 {
+    match camera_type {
+        CameraType::None => 0,
+        CameraType::Fly => 1,
+        CameraType::Orbit => 2,
+        CameraType::Editor => 3
+    }
+}
+
+/// Little endian writer for binary scenes
+struct BinaryWriter {
+    data: Vec<u8>
+}
+
+impl BinaryWriter {
+    fn u8(&mut self, v: u8) {
+        self.data.push(v);
+    }
+
+    fn u16(&mut self, v: u16) {
+        self.data.extend(v.to_le_bytes());
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.data.extend(v.to_le_bytes());
+    }
+
+    fn f32s(&mut self, v: &[f32]) {
+        for f in v {
+            self.data.extend(f.to_le_bytes());
+        }
+    }
+
+    fn string(&mut self, v: &str) {
+        self.u32(v.len() as u32);
+        self.data.extend(v.as_bytes());
+    }
+}
+
+/// Bounds checked little endian reader for binary scenes
+struct BinaryReader<'a> {
+    data: &'a [u8],
+    pos: usize
+}
+
+impl<'a> BinaryReader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], super::Error> {
+        if self.data.len() - self.pos < len {
+            return Err(super::Error {
+                msg: format!("hotline_rs::scene:: binary scene is truncated at offset {}", self.pos)
+            });
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, super::Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, super::Error> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, super::Error> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn f32(&mut self) -> Result<f32, super::Error> {
+        let b = self.bytes(4)?;
+        Ok(f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn vec3(&mut self) -> Result<(f32, f32, f32), super::Error> {
+        Ok((self.f32()?, self.f32()?, self.f32()?))
+    }
+
+    fn vec4(&mut self) -> Result<(f32, f32, f32, f32), super::Error> {
+        Ok((self.f32()?, self.f32()?, self.f32()?, self.f32()?))
+    }
+
+    fn string(&mut self) -> Result<String, super::Error> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.bytes(len)?.to_vec()).map_err(|_| super::Error {
+            msg: format!("hotline_rs::scene:: invalid utf-8 string at offset {}", self.pos - len)
+        })
+    }
+
+    fn light_type(&mut self) -> Result<LightType, super::Error> {
+        match self.u8()? {
+            0 => Ok(LightType::Point),
+            1 => Ok(LightType::Spot),
+            2 => Ok(LightType::Directional),
+            v => Err(super::Error {
+                msg: format!("hotline_rs::scene:: invalid light type {}", v)
+            })
+        }
+    }
+
+    fn camera_type(&mut self) -> Result<CameraType, super::Error> {
+        match self.u8()? {
+            0 => Ok(CameraType::None),
+            1 => Ok(CameraType::Fly),
+            2 => Ok(CameraType::Orbit),
+            3 => Ok(CameraType::Editor),
+            v => Err(super::Error {
+                msg: format!("hotline_rs::scene:: invalid camera type {}", v)
+            })
+        }
+    }
+}
+
+/// Reads an entity from a binary scene saved with `version`
+fn read_binary_entity(reader: &mut BinaryReader, version: u32) -> Result<SceneEntity, super::Error> {
+    if version != SCENE_VERSION {
+        return Err(version_error(version));
+    }
+    let flags = SceneEntityFlags::from_bits(reader.u16()?).ok_or_else(|| super::Error {
+        msg: "hotline_rs::scene:: invalid entity flags in binary scene".to_string()
+    })?;
+    let mut entity = SceneEntity::default();
+    if flags.contains(SceneEntityFlags::NAME) {
+        entity.name = Some(reader.string()?);
+    }
+    if flags.contains(SceneEntityFlags::PARENT) {
+        entity.parent = Some(reader.u32()?);
+    }
+    if flags.contains(SceneEntityFlags::POSITION) {
+        entity.position = Some(reader.vec3()?);
+    }
+    if flags.contains(SceneEntityFlags::ROTATION) {
+        entity.rotation = Some(reader.vec4()?);
+    }
+    if flags.contains(SceneEntityFlags::SCALE) {
+        entity.scale = Some(reader.vec3()?);
+    }
+    if flags.contains(SceneEntityFlags::COLOUR) {
+        entity.colour = Some(reader.vec4()?);
+    }
+    if flags.contains(SceneEntityFlags::LIGHT) {
+        entity.light = Some(SceneLight {
+            light_type: reader.light_type()?,
+            direction: reader.vec3()?,
+            cutoff: reader.f32()?,
+            falloff: reader.f32()?,
+            radius: reader.f32()?
+        });
+    }
+    if flags.contains(SceneEntityFlags::CAMERA) {
+        entity.camera = Some(SceneCamera {
+            camera_type: reader.camera_type()?,
+            rot: reader.vec3()?,
+            focus: reader.vec3()?,
+            zoom: reader.f32()?
+        });
+    }
+    if flags.contains(SceneEntityFlags::MESH) {
+        entity.mesh = Some(reader.string()?);
+    }
+    if flags.contains(SceneEntityFlags::MATERIAL) {
+        entity.material = Some(reader.string()?);
+    }
+    Ok(entity)
+}
+
+impl Scene {
+    /// Serialises the scene to pretty printed json
+    pub fn to_json(&self) -> Result<String, super::Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parses a json scene, upgrading scenes saved with older versions of the format
+    pub fn from_json(json: &str) -> Result<Scene, super::Error> {
+        let value : serde_json::Value = serde_json::from_str(json).map_err(|err| super::Error {
+            msg: format!("hotline_rs::scene:: failed to parse scene json: {}", err)
+        })?;
+        let version = value.get("version").and_then(|v| v.as_u64()).ok_or_else(|| super::Error {
+            msg: "hotline_rs::scene:: scene json is missing a version".to_string()
+        })?;
+        let value = migrate_scene_json(value, version as u32)?;
+        let scene : Scene = serde_json::from_value(value).map_err(|err| super::Error {
+            msg: format!("hotline_rs::scene:: invalid scene json: {}", err)
+        })?;
+        scene.validate()?;
+        Ok(scene)
+    }
+
+    /// Serialises the scene to the compact binary format
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut writer = BinaryWriter {
+            data: SCENE_BINARY_MAGIC.to_vec()
+        };
+        writer.u32(SCENE_VERSION);
+        writer.u32(self.entities.len() as u32);
+        for entity in &self.entities {
+            let mut flags = SceneEntityFlags::empty();
+            flags.set(SceneEntityFlags::NAME, entity.name.is_some());
+            flags.set(SceneEntityFlags::PARENT, entity.parent.is_some());
+            flags.set(SceneEntityFlags::POSITION, entity.position.is_some());
+            flags.set(SceneEntityFlags::ROTATION, entity.rotation.is_some());
+            flags.set(SceneEntityFlags::SCALE, entity.scale.is_some());
+            flags.set(SceneEntityFlags::COLOUR, entity.colour.is_some());
+            flags.set(SceneEntityFlags::LIGHT, entity.light.is_some());
+            flags.set(SceneEntityFlags::CAMERA, entity.camera.is_some());
+            flags.set(SceneEntityFlags::MESH, entity.mesh.is_some());
+            flags.set(SceneEntityFlags::MATERIAL, entity.material.is_some());
+            writer.u16(flags.bits());
+            if let Some(name) = &entity.name {
+                writer.string(name);
+            }
+            if let Some(parent) = entity.parent {
+                writer.u32(parent);
+            }
+            if let Some((x, y, z)) = entity.position {
+                writer.f32s(&[x, y, z]);
+            }
+            if let Some((x, y, z, w)) = entity.rotation {
+                writer.f32s(&[x, y, z, w]);
+            }
+            if let Some((x, y, z)) = entity.scale {
+                writer.f32s(&[x, y, z]);
+            }
+            if let Some((r, g, b, a)) = entity.colour {
+                writer.f32s(&[r, g, b, a]);
+            }
+            if let Some(light) = &entity.light {
+                writer.u8(light_type_to_u8(light.light_type));
+                writer.f32s(&[light.direction.0, light.direction.1, light.direction.2]);
+                writer.f32s(&[light.cutoff, light.falloff, light.radius]);
+            }
+            if let Some(camera) = &entity.camera {
+                writer.u8(camera_type_to_u8(camera.camera_type));
+                writer.f32s(&[camera.rot.0, camera.rot.1, camera.rot.2]);
+                writer.f32s(&[camera.focus.0, camera.focus.1, camera.focus.2]);
+                writer.f32s(&[camera.zoom]);
+            }
+            if let Some(mesh) = &entity.mesh {
+                writer.string(mesh);
+            }
+            if let Some(material) = &entity.material {
+                writer.string(material);
+            }
+        }
+        writer.data
+    }
+
+    /// Parses a binary scene, `data` must begin with `SCENE_BINARY_MAGIC`
+    pub fn from_binary(data: &[u8]) -> Result<Scene, super::Error> {
+        let mut reader = BinaryReader {
+            data,
+            pos: 0
+        };
+        if reader.bytes(4).ok() != Some(SCENE_BINARY_MAGIC.as_slice()) {
+            return Err(super::Error {
+                msg: "hotline_rs::scene:: data is not a binary scene".to_string()
+            });
+        }
+        let version = reader.u32()?;
+        if version == 0 || version > SCENE_VERSION {
+            return Err(version_error(version));
+        }
+        let count = reader.u32()?;
+        let mut entities = Vec::new();
+        for _ in 0..count {
+            entities.push(read_binary_entity(&mut reader, version)?);
+        }
+        if reader.pos != data.len() {
+            return Err(super::Error {
+                msg: format!("hotline_rs::scene:: unexpected data at the end of binary scene at offset {}", reader.pos)
+            });
+        }
+        let scene = Scene {
+            version: SCENE_VERSION,
+            entities
+        };
+        scene.validate()?;
+        Ok(scene)
+    }
+
+    /// Parses a scene from either format, detected by the `SCENE_BINARY_MAGIC`
+    pub fn from_bytes(data: &[u8]) -> Result<Scene, super::Error> {
+        if data.starts_with(&SCENE_BINARY_MAGIC) {
+            Scene::from_binary(data)
+        }
+        else {
+            let json = std::str::from_utf8(data).map_err(|_| super::Error {
+                msg: "hotline_rs::scene:: scene is neither binary or utf-8 json".to_string()
+            })?;
+            Scene::from_json(json)
+        }
+    }
+
+    /// Checks that parent indices reference other entities in the scene
+    fn validate(&self) -> Result<(), super::Error> {
+        for (i, entity) in self.entities.iter().enumerate() {
+            if let Some(parent) = entity.parent {
+                if parent as usize >= self.entities.len() || parent as usize == i {
+                    return Err(super::Error {
+                        msg: format!("hotline_rs::scene:: entity {} has invalid parent {}", i, parent)
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Loads a json or binary scene from `filepath`
+pub fn load_scene(filepath: &str) -> Result<Scene, super::Error> {
+    let data = super::read_data(filepath)?;
+    Scene::from_bytes(&data).map_err(|err| super::Error {
+        msg: format!("{} ({})", err.msg, filepath)
+    })
+}
+
+/// Saves `scene` to `filepath` in `format`
+pub fn save_scene(filepath: &str, scene: &Scene, format: SceneFormat) -> Result<(), super::Error> {
+    let data = match format {
+        SceneFormat::Json => scene.to_json()?.into_bytes(),
+        SceneFormat::Binary => scene.to_binary()
+    };
+    std::fs::write(filepath, data).map_err(|err| super::Error {
+        msg: format!("hotline_rs::scene:: failed to write {}: {}", filepath, err)
+    })
+}
+
+/// Captures the components of `entity` which are saved in scenes. The `parent` is left empty because it is an index
+/// into `Scene::entities`, which is assigned by `scene_from_world`
+pub fn scene_entity_from_world(world: &World, entity: Entity) -> SceneEntity {
+    SceneEntity {
+        name: world.get::<Name>(entity).map(|name| name.0.to_string()),
+        parent: None,
+        position: world.get::<Position>(entity).map(|p| (p.x, p.y, p.z)),
+        rotation: world.get::<Rotation>(entity).map(|r| {
+            let q = r.0.as_slice();
+            (q[0], q[1], q[2], q[3])
+        }),
+        scale: world.get::<Scale>(entity).map(|s| (s.x, s.y, s.z)),
+        colour: world.get::<Colour>(entity).map(|c| (c.x, c.y, c.z, c.w)),
+        light: world.get::<LightComponent>(entity).map(|light| SceneLight {
+            light_type: light.light_type,
+            direction: (light.direction.x, light.direction.y, light.direction.z),
+            cutoff: light.cutoff,
+            falloff: light.falloff,
+            radius: light.radius
+        }),
+        camera: world.get::<Camera>(entity).map(|camera| SceneCamera {
+            camera_type: camera.camera_type,
+            rot: (camera.rot.x, camera.rot.y, camera.rot.z),
+            focus: (camera.focus.x, camera.focus.y, camera.focus.z),
+            zoom: camera.zoom
+        }),
+        mesh: world.get::<MeshAsset>(entity).map(|mesh| mesh.0.to_string()),
+        material: world.get::<MaterialAsset>(entity).map(|material| material.0.to_string())
+    }
+}
+
+/// Builds a scene from every entity in `world` with a `Name` except the `MainCamera`, which belongs to the client.
+/// Parents which are not saved are dropped and meshes and materials are saved by their `MeshAsset` and
+/// `MaterialAsset` names
+pub fn scene_from_world(world: &mut World) -> Scene {
+    let mut query = world.query_filtered::<(Entity, Option<&Parent>), (With<Name>, Without<MainCamera>)>();
+    let mut items = query.iter(world)
+        .map(|(entity, parent)| (entity, parent.map(|parent| parent.0)))
+        .collect::<Vec<_>>();
+    items.sort_by_key(|item| item.0);
+
+    let indices = items.iter().enumerate()
+        .map(|(i, item)| (item.0, i as u32))
+        .collect::<HashMap<Entity, u32>>();
+
+    let entities = items.iter().map(|(entity, parent)| {
+        SceneEntity {
+            parent: parent.and_then(|parent| indices.get(&parent).copied()),
+            ..scene_entity_from_world(world, *entity)
+        }
+    }).collect();
+
+    Scene {
+        version: SCENE_VERSION,
+        entities
+    }
+}
+
+/// Spawns the entities of `scene` tagged with a `SceneSource` of `source` so they can be found and reloaded later,
+/// returning the spawned entities in the same order as `Scene::entities`. Entities with any of position, rotation or
+/// scale get all 3 and a `WorldMatrix` so they are part of the transform hierarchy, cameras get a
+/// `ViewProjectionMatrix` and mesh and material assets are added as `MeshAsset` and `MaterialAsset` to be resolved
+pub fn spawn_scene(commands: &mut Commands, scene: &Scene, source: &str) -> Vec<Entity> {
+    let mut entities = Vec::new();
+    for scene_entity in &scene.entities {
+        let mut entity = commands.spawn(SceneSource(source.to_string()));
+        if let Some(name) = &scene_entity.name {
+            entity.insert(Name(name.to_string()));
+        }
+        if scene_entity.position.is_some() || scene_entity.rotation.is_some() || scene_entity.scale.is_some() {
+            entity.insert((
+                Position(scene_entity.position.map(Vec3f::from).unwrap_or(Vec3f::zero())),
+                Rotation(scene_entity.rotation.map(|(x, y, z, w)| Quatf::from(vec4f(x, y, z, w))).unwrap_or(Quatf::identity())),
+                Scale(scene_entity.scale.map(Vec3f::from).unwrap_or(Vec3f::one())),
+                WorldMatrix(Mat34f::identity())
+            ));
+        }
+        if let Some((r, g, b, a)) = scene_entity.colour {
+            entity.insert(Colour(vec4f(r, g, b, a)));
+        }
+        if let Some(light) = &scene_entity.light {
+            entity.insert(LightComponent {
+                light_type: light.light_type,
+                direction: Vec3f::from(light.direction),
+                cutoff: light.cutoff,
+                falloff: light.falloff,
+                radius: light.radius,
+                ..Default::default()
+            });
+        }
+        if let Some(camera) = &scene_entity.camera {
+            entity.insert((
+                Camera {
+                    rot: Vec3f::from(camera.rot),
+                    focus: Vec3f::from(camera.focus),
+                    zoom: camera.zoom,
+                    camera_type: camera.camera_type
+                },
+                ViewProjectionMatrix(Mat4f::identity())
+            ));
+        }
+        if let Some(mesh) = &scene_entity.mesh {
+            entity.insert(MeshAsset(mesh.to_string()));
+        }
+        if let Some(material) = &scene_entity.material {
+            entity.insert(MaterialAsset(material.to_string()));
+        }
+        entities.push(entity.id());
+    }
+
+    for (entity, scene_entity) in entities.iter().zip(&scene.entities) {
+        if let Some(parent) = scene_entity.parent.and_then(|parent| entities.get(parent as usize)) {
+            commands.entity(*entity).insert(Parent(*parent));
+        }
+    }
+
+    entities
+}
+
+/// Watches the files of entities with a `SceneSource` and when one is modified despawns its entities and spawns the
+/// scene again. If the modified scene fails to load the error is printed and the current entities are kept
+pub fn reload_scenes(
+    mut commands: Commands,
+    query: Query<(Entity, &SceneSource)>,
+    mut modified_times: Local<HashMap<String, Option<SystemTime>>>) {
+    let mut sources : HashMap<&str, Vec<Entity>> = HashMap::new();
+    for (entity, source) in &query {
+        sources.entry(source.0.as_str()).or_default().push(entity);
+    }
+
+    for (source, entities) in sources {
+        let modified_time = std::fs::metadata(source).and_then(|m| m.modified()).ok();
+        let previous = modified_times.insert(source.to_string(), modified_time);
+        if previous.is_none() || previous == Some(modified_time) || modified_time.is_none() {
+            continue;
+        }
+        println!("hotline_rs::scene:: reloading scene: {}", source);
+        match load_scene(source) {
+            Ok(scene) => {
+                for entity in entities {
+                    commands.entity(entity).despawn();
+                }
+                spawn_scene(&mut commands, &scene, source);
+            }
+            Err(err) => {
+                println!("hotline_rs::scene:: failed to reload scene: {}", err.msg);
+            }
+        }
+    }
+}
+
+/// Creates a mesh from the name of a built in primitive (ie. `cube`, `sphere`, `plane`) or an `.obj` file relative
+/// to the data directory, where the first mesh in the file is used
+pub fn create_mesh_asset<D: gfx::Device>(dev: &mut D, name: &str) -> Result<pmfx::Mesh<D>, super::Error> {
+    if name.ends_with(".obj") {
+        let obj = primitives::load_obj(&super::get_data_path(name))?;
+        return primitives::create_obj_meshes(dev, &obj).into_iter().next().ok_or_else(|| super::Error {
+            msg: format!("hotline_rs::scene:: obj mesh asset {} has no meshes", name)
+        });
+    }
+    match name {
+        "billboard" => Ok(primitives::create_billboard_mesh(dev)),
+        "capsule" => Ok(primitives::create_capsule_mesh(dev, 16)),
+        "cone" => Ok(primitives::create_cone_mesh(dev, 16)),
+        "cube" => Ok(primitives::create_cube_mesh(dev)),
+        "cylinder" => Ok(primitives::create_cylinder_mesh(dev, 16)),
+        "dodecahedron" => Ok(primitives::create_dodecahedron_mesh(dev)),
+        "icosahedron" => Ok(primitives::create_icosahedron_mesh(dev)),
+        "octahedron" => Ok(primitives::create_octahedron_mesh(dev)),
+        "plane" => Ok(primitives::create_plane_mesh(dev, 1)),
+        "pyramid" => Ok(primitives::create_pyramid_mesh(dev, 4, false, true)),
+        "quad" => Ok(primitives::create_unit_quad_mesh(dev)),
+        "sphere" => Ok(primitives::create_sphere_mesh(dev, 16)),
+        "teapot" => Ok(primitives::create_teapot_mesh(dev, 4)),
+        "tetrahedron" => Ok(primitives::create_tetrahedron_mesh(dev)),
+        "torus" => Ok(primitives::create_tourus_mesh(dev, 16)),
+        "triangle" => Ok(primitives::create_triangle_mesh(dev)),
+        _ => Err(super::Error {
+            msg: format!("hotline_rs::scene:: unknown mesh asset {}", name)
+        })
+    }
+}
@@ -0,0 +1,257 @@
+use crate::gfx;
+
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Extension appended to the file name of a shader to find its reflection sidecar, ie. `mesh.vsc` > `mesh.vsc.json`
+pub const REFLECTION_EXT: &str = "json";
+
+/// A resource binding declared in a shader
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ShaderBinding {
+    /// Name of the resource in the shader
+    pub name: String,
+    /// Register index the resource is bound to
+    pub shader_register: u32,
+    /// Register space the resource is bound to
+    pub register_space: u32,
+    /// Type of descriptor the resource requires
+    pub binding_type: gfx::DescriptorType,
+    /// Number of descriptors in the array, `0` for unbounded arrays
+    #[serde(default = "default_count")]
+    pub count: u32
+}
+
+/// Push constants declared in a shader
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ShaderPushConstants {
+    /// Name of the constants in the shader
+    pub name: String,
+    /// Register index the constants are bound to
+    pub shader_register: u32,
+    /// Register space the constants are bound to
+    pub register_space: u32,
+    /// Number of 32-bit values the shader reads
+    pub num_values: u32
+}
+
+/// A vertex input read by a vertex shader
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ShaderVertexInput {
+    /// Semantic of the input ie. POSITION, TEXCOORD, COLOR etc.
+    pub semantic: String,
+    /// Index of the semantic ie. TEXCOORD0, TEXCOORD1 etc.
+    #[serde(default)]
+    pub index: u32,
+    /// Format the shader expects the input in
+    pub format: gfx::Format
+}
+
+/// Reflection info emitted alongside a precompiled shader blob (SPIR-V, DXIL or metallib)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ShaderReflection {
+    /// Name of the entry point function
+    pub entry_point: String,
+    /// The stage the shader was compiled for
+    pub stage: gfx::ShaderType,
+    /// Resources the shader accesses through descriptors
+    #[serde(default)]
+    pub bindings: Vec<ShaderBinding>,
+    /// Push constants the shader reads
+    #[serde(default)]
+    pub push_constants: Vec<ShaderPushConstants>,
+    /// Inputs read from vertex buffers, only for vertex shaders
+    #[serde(default)]
+    pub vertex_inputs: Vec<ShaderVertexInput>
+}
+
+/// The kind of mismatch found by `ShaderReflection::validate`, containing the name of the offending item
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReflectionErrorKind {
+    /// The shader was compiled for a different stage than the pipeline uses it for
+    StageMismatch(String),
+    /// A binding in the shader which is not covered by the pipeline layout
+    MissingBinding(String),
+    /// Push constants in the shader which are not in the pipeline layout
+    MissingPushConstants(String),
+    /// Push constants in the pipeline layout which are smaller than the shader reads
+    PushConstantsSize(String),
+    /// A binding or push constants in the pipeline layout which are not visible to the shader stage
+    Visibility(String),
+    /// A vertex input the shader reads which is not in the input layout
+    MissingVertexInput(String),
+    /// A vertex input with a different format in the input layout
+    VertexInputFormat(String),
+    /// The shader does not have a reflection sidecar so it could not be validated
+    MissingReflection(String)
+}
+
+/// A single mismatch between a shader and the pipeline it is used in
+#[derive(Clone, Debug)]
+pub struct ReflectionError {
+    /// The shader file the mismatch was found in
+    pub shader: String,
+    pub kind: ReflectionErrorKind,
+    /// Human readable description of the mismatch
+    pub message: String
+}
+
+impl std::fmt::Display for ReflectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.shader, self.message)
+    }
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+/// Returns true if the range of `binding` is within the range of the layout binding at `shader_register` and
+/// `register_space` with `num_descriptors`, where `None` is unbounded
+fn binding_in_range(binding: &ShaderBinding, shader_register: u32, register_space: u32, num_descriptors: Option<u32>) -> bool {
+    if binding.register_space != register_space || binding.shader_register < shader_register {
+        return false;
+    }
+    match num_descriptors {
+        None => true,
+        Some(_) if binding.count == 0 => false,
+        Some(num) => binding.shader_register as u64 + binding.count as u64 <= shader_register as u64 + num as u64
+    }
+}
+
+/// Returns true if resources with `visibility` can be accessed from `stage`
+fn is_visible(visibility: gfx::ShaderVisibility, stage: gfx::ShaderType) -> bool {
+    match visibility {
+        gfx::ShaderVisibility::All => true,
+        gfx::ShaderVisibility::Vertex => stage == gfx::ShaderType::Vertex,
+        gfx::ShaderVisibility::Fragment => stage == gfx::ShaderType::Fragment,
+        gfx::ShaderVisibility::Compute => stage == gfx::ShaderType::Compute
+    }
+}
+
+impl ShaderReflection {
+    /// Validates the shader `shader` used as `stage` against the `pipeline_layout` and `input_layout` of a pipeline,
+    /// returning all of the mismatches found. Constant buffers can be supplied as push constants and samplers as
+    /// static samplers. Vertex inputs are only checked for vertex shaders, system value `SV_` semantics are ignored
+    pub fn validate(
+        &self,
+        shader: &str,
+        stage: gfx::ShaderType,
+        pipeline_layout: &gfx::PipelineLayout,
+        input_layout: Option<&gfx::InputLayout>) -> Vec<ReflectionError> {
+        let mut errors = Vec::new();
+        let mut error = |kind: ReflectionErrorKind, message: String| {
+            errors.push(ReflectionError {
+                shader: shader.to_string(),
+                kind,
+                message
+            });
+        };
+
+        if self.stage != stage {
+            error(ReflectionErrorKind::StageMismatch(shader.to_string()),
+                format!("compiled as a {:?} shader but used as a {:?} shader", self.stage, stage));
+        }
+
+        let layout_bindings = pipeline_layout.bindings.as_deref().unwrap_or_default();
+        let layout_push_constants = pipeline_layout.push_constants.as_deref().unwrap_or_default();
+        let layout_samplers = pipeline_layout.static_samplers.as_deref().unwrap_or_default();
+
+        for binding in &self.bindings {
+            // visibility of each layout entry which covers the binding
+            let mut visibility = layout_bindings.iter()
+                .filter(|b| b.binding_type == binding.binding_type &&
+                    binding_in_range(binding, b.shader_register, b.register_space, b.num_descriptors))
+                .map(|b| b.visibility)
+                .collect::<Vec<_>>();
+
+            if matches!(binding.binding_type, gfx::DescriptorType::ConstantBuffer | gfx::DescriptorType::PushConstants) {
+                visibility.extend(layout_push_constants.iter()
+                    .filter(|p| binding_in_range(binding, p.shader_register, p.register_space, Some(1)))
+                    .map(|p| p.visibility));
+            }
+
+            if binding.binding_type == gfx::DescriptorType::Sampler {
+                visibility.extend(layout_samplers.iter()
+                    .filter(|s| binding_in_range(binding, s.shader_register, s.register_space, Some(1)))
+                    .map(|s| s.visibility));
+            }
+
+            if visibility.is_empty() {
+                error(ReflectionErrorKind::MissingBinding(binding.name.to_string()),
+                    format!("{:?} binding `{}` (register: {}, space: {}) is not in the pipeline layout",
+                        binding.binding_type, binding.name, binding.shader_register, binding.register_space));
+            }
+            else if !visibility.iter().any(|v| is_visible(*v, stage)) {
+                error(ReflectionErrorKind::Visibility(binding.name.to_string()),
+                    format!("binding `{}` is not visible to the {:?} stage in the pipeline layout", binding.name, stage));
+            }
+        }
+
+        for constants in &self.push_constants {
+            let layout_constants = layout_push_constants.iter().find(|p| {
+                p.shader_register == constants.shader_register && p.register_space == constants.register_space
+            });
+            if let Some(layout_constants) = layout_constants {
+                if layout_constants.num_values < constants.num_values {
+                    error(ReflectionErrorKind::PushConstantsSize(constants.name.to_string()),
+                        format!("push constants `{}` read {} values but the pipeline layout has {}",
+                            constants.name, constants.num_values, layout_constants.num_values));
+                }
+                if !is_visible(layout_constants.visibility, stage) {
+                    error(ReflectionErrorKind::Visibility(constants.name.to_string()),
+                        format!("push constants `{}` are not visible to the {:?} stage in the pipeline layout", constants.name, stage));
+                }
+            }
+            else {
+                error(ReflectionErrorKind::MissingPushConstants(constants.name.to_string()),
+                    format!("push constants `{}` (register: {}, space: {}) are not in the pipeline layout",
+                        constants.name, constants.shader_register, constants.register_space));
+            }
+        }
+
+        if stage == gfx::ShaderType::Vertex {
+            let elements = input_layout.map(|layout| layout.as_slice()).unwrap_or_default();
+            for input in self.vertex_inputs.iter().filter(|input| !input.semantic.to_uppercase().starts_with("SV_")) {
+                let name = format!("{}{}", input.semantic, input.index);
+                let element = elements.iter().find(|e| {
+                    e.semantic.eq_ignore_ascii_case(&input.semantic) && e.index == input.index
+                });
+                if let Some(element) = element {
+                    if element.format != input.format {
+                        error(ReflectionErrorKind::VertexInputFormat(name.to_string()),
+                            format!("vertex input `{}` is {:?} but the input layout has {:?}", name, input.format, element.format));
+                    }
+                }
+                else {
+                    error(ReflectionErrorKind::MissingVertexInput(name.to_string()),
+                        format!("vertex input `{}` is not in the input layout", name));
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+/// Returns the path of the reflection sidecar for the shader at `shader_filepath`
+pub fn get_reflection_filepath(shader_filepath: &Path) -> PathBuf {
+    let mut filepath = shader_filepath.as_os_str().to_os_string();
+    filepath.push(".");
+    filepath.push(REFLECTION_EXT);
+    PathBuf::from(filepath)
+}
+
+/// Loads the reflection sidecar for the shader at `shader_filepath`, returns `None` if the shader does not have one
+pub fn load_reflection(shader_filepath: &Path) -> Result<Option<ShaderReflection>, super::Error> {
+    let filepath = get_reflection_filepath(shader_filepath);
+    if !filepath.exists() {
+        return Ok(None);
+    }
+    let data = fs::read(&filepath)?;
+    serde_json::from_slice(&data).map(Some).map_err(|err| super::Error {
+        msg: format!("hotline_rs::shader_reflection:: failed to parse {}: {}", filepath.display(), err)
+    })
+}
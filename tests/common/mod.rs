@@ -0,0 +1,145 @@
+// shared fixtures for the integration tests, each test crate only uses some of them
+#![allow(dead_code)]
+
+use hotline_rs::os;
+use hotline_rs::gfx;
+use hotline_rs::gfx::null;
+use hotline_rs::pmfx;
+use hotline_rs::shader_reflection;
+use hotline_rs::shader_reflection::ReflectionErrorKind;
+use hotline_rs::ecs_base::WorldMatrix;
+use hotline_rs::gfx::Device;
+use hotline_rs::os::App;
+
+use maths_rs::prelude::*;
+
+use std::path::PathBuf;
+
+pub fn create_null_app(name: &str) -> os::null::App {
+    os::null::App::create(os::AppInfo {
+        name: String::from(name),
+        window: false,
+        num_buffers: 0,
+        dpi_aware: true,
+    })
+}
+
+pub fn create_null_device(shader_heap_size: usize) -> null::Device {
+    null::Device::create(&gfx::DeviceInfo {
+        adapter_name: None,
+        shader_heap_size,
+        render_target_heap_size: 4,
+        depth_stencil_heap_size: 4,
+    }).unwrap()
+}
+
+/// Returns an empty temp directory `name` for a test to write its files to, removing anything left by a previous run
+pub fn test_folder(name: &str) -> Result<PathBuf, hotline_rs::Error> {
+    let folder = std::env::temp_dir().join("hotline_tests").join(name);
+    let _ = std::fs::remove_dir_all(&folder);
+    std::fs::create_dir_all(&folder)?;
+    Ok(folder)
+}
+
+/// Writes `pmfx_json` into a fresh test folder `name` and loads it into a `Pmfx` on a null device
+pub fn load_test_pmfx(name: &str, pmfx_json: &str) -> Result<(null::Device, pmfx::Pmfx<null::Device>, PathBuf), hotline_rs::Error> {
+    let folder = test_folder(name)?;
+    std::fs::write(folder.join(format!("{}.json", name)), pmfx_json)?;
+    let mut dev = create_null_device(16);
+    let mut pmfx = pmfx::Pmfx::<null::Device>::create(&mut dev, 16);
+    pmfx.load(folder.to_str().unwrap())?;
+    Ok((dev, pmfx, folder))
+}
+
+pub fn approx_vec3(a: Vec3f, b: Vec3f) -> bool {
+    (a.x - b.x).abs() < 0.0001 && (a.y - b.y).abs() < 0.0001 && (a.z - b.z).abs() < 0.0001
+}
+
+/// Sets the modified time of `path` in the future so changes are detected regardless of file system time resolution
+pub fn touch_file(path: &std::path::Path, seconds: u64) -> std::io::Result<()> {
+    let file = std::fs::OpenOptions::new().append(true).open(path)?;
+    file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(seconds))
+}
+
+pub fn reflection_error_kinds(errors: &[shader_reflection::ReflectionError]) -> Vec<ReflectionErrorKind> {
+    errors.iter().map(|e| e.kind.clone()).collect()
+}
+
+pub fn world_position(world: &bevy_ecs::world::World, entity: bevy_ecs::entity::Entity) -> Vec3f {
+    world.get::<WorldMatrix>(entity).unwrap().0.get_column(3)
+}
+
+// minimal pmfx as emitted by pmfx-shader, the "valid" graph is well formed and "invalid" contains one of each problem,
+// "export", "transient" and "async" have no pipelines so they can be built without compiled shaders
+pub const TEST_PMFX: &str = r#"{
+    "shaders": {},
+    "pipelines": {
+        "mesh": {"0": {"vs": "mesh.vsc", "ps": "mesh.psc", "pipeline_layout": {}, "topology": "TriangleList", "sample_mask": 4294967295, "hash": 1}},
+        "blur": {"0": {"cs": "blur.csc", "pipeline_layout": {}, "topology": "Undefined", "sample_mask": 4294967295, "hash": 2}}
+    },
+    "depth_stencil_states": {},
+    "raster_states": {},
+    "blend_states": {},
+    "render_target_blend_states": {},
+    "textures": {
+        "colour": {"width": 8, "height": 8, "depth": 1, "mip_levels": 1, "array_layers": 1, "samples": 1, "cubemap": false,
+            "format": "RGBA8n", "usage": ["RenderTarget", "ShaderResource"], "hash": 3},
+        "depth": {"width": 8, "height": 8, "depth": 1, "mip_levels": 1, "array_layers": 1, "samples": 1, "cubemap": false,
+            "format": "D32f", "usage": ["DepthStencil", "ShaderResource"], "hash": 4},
+        "blurred": {"width": 8, "height": 8, "depth": 1, "mip_levels": 1, "array_layers": 1, "samples": 1, "cubemap": false,
+            "format": "RGBA8n", "usage": ["UnorderedAccess", "ShaderResource"], "hash": 5},
+        "unused": {"width": 8, "height": 8, "depth": 1, "mip_levels": 1, "array_layers": 1, "samples": 1, "cubemap": false,
+            "format": "RGBA8n", "usage": ["ShaderResource"], "hash": 6},
+        "transient_a": {"width": 8, "height": 8, "depth": 1, "mip_levels": 1, "array_layers": 1, "samples": 1, "cubemap": false,
+            "format": "RGBA8n", "usage": ["RenderTarget", "ShaderResource"], "transient": true, "hash": 9},
+        "transient_b": {"width": 8, "height": 8, "depth": 1, "mip_levels": 1, "array_layers": 1, "samples": 1, "cubemap": false,
+            "format": "RGBA8n", "usage": ["RenderTarget", "ShaderResource"], "transient": true, "hash": 10},
+        "transient_c": {"width": 8, "height": 8, "depth": 1, "mip_levels": 1, "array_layers": 1, "samples": 1, "cubemap": false,
+            "format": "RGBA8n", "usage": ["RenderTarget", "ShaderResource"], "transient": true, "hash": 11}
+    },
+    "views": {
+        "main_view": {"render_target": ["colour"], "depth_stencil": ["depth"], "viewport": [0, 0, 1, 1, 0, 1],
+            "scissor": [0, 0, 1, 1], "camera": "", "hash": 7},
+        "bad_view": {"render_target": ["depth"], "depth_stencil": [], "viewport": [0, 0, 1, 1, 0, 1],
+            "scissor": [0, 0, 1, 1], "camera": "", "hash": 8},
+        "view_a": {"render_target": ["transient_a"], "depth_stencil": [], "viewport": [0, 0, 1, 1, 0, 1],
+            "scissor": [0, 0, 1, 1], "camera": "", "hash": 12},
+        "view_b": {"render_target": ["transient_b"], "depth_stencil": [], "viewport": [0, 0, 1, 1, 0, 1],
+            "scissor": [0, 0, 1, 1], "camera": "", "hash": 13},
+        "view_c": {"render_target": ["transient_c"], "depth_stencil": [], "viewport": [0, 0, 1, 1, 0, 1],
+            "scissor": [0, 0, 1, 1], "camera": "", "hash": 14}
+    },
+    "render_graphs": {
+        "valid": {
+            "meshes": {"view": "main_view", "pipelines": ["mesh"], "function": "render_meshes"},
+            "blur": {"pipelines": ["blur"], "function": "dispatch_compute", "depends_on": ["meshes"],
+                "uses": [["colour", "Read"], ["blurred", "Write"]], "target_dimension": "blurred"}
+        },
+        "invalid": {
+            "a": {"pipelines": ["blur"], "function": "dispatch_compute", "depends_on": ["b"]},
+            "b": {"pipelines": ["blur"], "function": "dispatch_compute", "depends_on": ["a"]},
+            "missing": {"view": "no_view", "function": "render_meshes"},
+            "wrong_type": {"view": "bad_view", "pipelines": ["blur", "no_pipeline"], "function": "render_meshes"},
+            "early_read": {"pipelines": ["blur"], "function": "dispatch_compute", "uses": [["colour", "Read"], ["blurred", "Write"]]},
+            "writer": {"view": "main_view", "pipelines": ["mesh"], "function": "render_meshes"}
+        },
+        "export": {
+            "meshes": {"view": "main_view", "function": "render_meshes"},
+            "blur": {"function": "dispatch_compute", "depends_on": ["meshes"],
+                "uses": [["colour", "Read"], ["blurred", "Write"]], "target_dimension": "blurred"}
+        },
+        "transient": {
+            "pass_a": {"view": "view_a", "function": "render_meshes"},
+            "pass_b": {"view": "view_b", "function": "blit", "uses": [["transient_a", "Read"]], "depends_on": ["pass_a"]},
+            "pass_c": {"view": "view_c", "function": "blit", "uses": [["transient_b", "Read"]], "depends_on": ["pass_b"]}
+        },
+        "async": {
+            "meshes": {"view": "main_view", "function": "render_meshes"},
+            "blur": {"function": "dispatch_compute", "depends_on": ["meshes"], "queue": "async_compute",
+                "uses": [["colour", "Read"], ["blurred", "Write"]], "target_dimension": "blurred"},
+            "debug": {"view": "view_a", "function": "render_meshes", "depends_on": ["meshes"]},
+            "present": {"view": "view_b", "function": "blit", "uses": [["blurred", "Read"]], "depends_on": ["blur"]}
+        }
+    },
+    "dependencies": []
+}"#;
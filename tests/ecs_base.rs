@@ -0,0 +1,74 @@
+// tests for the transform hierarchy systems
+
+mod common;
+
+use hotline_rs::ecs_base;
+use hotline_rs::ecs_base::{Name, Parent, Position, Rotation, Scale, LocalMatrix, WorldMatrix};
+
+use maths_rs::prelude::*;
+
+fn spawn_transform(world: &mut bevy_ecs::world::World, position: Vec3f, scale: f32) -> bevy_ecs::entity::Entity {
+    world.spawn((
+        Position(position),
+        Rotation(Quatf::identity()),
+        Scale(splat3f(scale)),
+        WorldMatrix(Mat34f::identity())
+    )).id()
+}
+
+#[test]
+fn ecs_transform_hierarchy() {
+    let mut world = bevy_ecs::world::World::new();
+    let mut schedule = bevy_ecs::schedule::Schedule::default();
+    schedule.add_systems(ecs_base::transform_hierarchy_systems());
+
+    // children are spawned before their parents so propagation can not rely on spawn order
+    let grandchild = spawn_transform(&mut world, vec3f(0.0, 0.0, 1.0), 1.0);
+    let child = spawn_transform(&mut world, vec3f(0.0, 1.0, 0.0), 2.0);
+    let root = spawn_transform(&mut world, vec3f(1.0, 0.0, 0.0), 1.0);
+    world.entity_mut(grandchild).insert(Parent(child));
+    world.entity_mut(child).insert(Parent(root));
+
+    // a static parent with only a world matrix, and an instance batch parent with no transform
+    let fixed = world.spawn(WorldMatrix(Mat34f::from_translation(vec3f(0.0, 10.0, 0.0)))).id();
+    let attached = spawn_transform(&mut world, vec3f(1.0, 0.0, 0.0), 1.0);
+    world.entity_mut(attached).insert(Parent(fixed));
+    let batch = world.spawn(Name("batch".to_string())).id();
+    let instance = spawn_transform(&mut world, vec3f(5.0, 0.0, 0.0), 1.0);
+    world.entity_mut(instance).insert(Parent(batch));
+
+    schedule.run(&mut world);
+    assert!(world.get::<LocalMatrix>(root).is_some());
+    assert!(world.get::<LocalMatrix>(fixed).is_none());
+    assert!(common::approx_vec3(common::world_position(&world, root), vec3f(1.0, 0.0, 0.0)));
+    assert!(common::approx_vec3(common::world_position(&world, child), vec3f(1.0, 1.0, 0.0)));
+    assert!(common::approx_vec3(common::world_position(&world, grandchild), vec3f(1.0, 1.0, 2.0)));
+    assert!(common::approx_vec3(common::world_position(&world, fixed), vec3f(0.0, 10.0, 0.0)));
+    assert!(common::approx_vec3(common::world_position(&world, attached), vec3f(1.0, 10.0, 0.0)));
+    assert!(common::approx_vec3(common::world_position(&world, instance), vec3f(5.0, 0.0, 0.0)));
+
+    // moving a parent moves all of its descendants
+    world.get_mut::<Position>(root).unwrap().0 = vec3f(2.0, 0.0, 0.0);
+    schedule.run(&mut world);
+    assert!(common::approx_vec3(common::world_position(&world, grandchild), vec3f(2.0, 1.0, 2.0)));
+
+    // reparenting and unparenting take effect on the next update
+    world.entity_mut(grandchild).insert(Parent(fixed));
+    schedule.run(&mut world);
+    assert!(common::approx_vec3(common::world_position(&world, grandchild), vec3f(0.0, 10.0, 1.0)));
+    world.entity_mut(grandchild).remove::<Parent>();
+    schedule.run(&mut world);
+    assert!(common::approx_vec3(common::world_position(&world, grandchild), vec3f(0.0, 0.0, 1.0)));
+
+    // children of a despawned parent become roots
+    world.despawn(root);
+    schedule.run(&mut world);
+    assert!(common::approx_vec3(common::world_position(&world, child), vec3f(0.0, 1.0, 0.0)));
+
+    // entities in a parent cycle fall back to their local matrix
+    world.entity_mut(child).insert(Parent(grandchild));
+    world.entity_mut(grandchild).insert(Parent(child));
+    schedule.run(&mut world);
+    assert!(common::approx_vec3(common::world_position(&world, child), vec3f(0.0, 1.0, 0.0)));
+    assert!(common::approx_vec3(common::world_position(&world, grandchild), vec3f(0.0, 0.0, 1.0)));
+}
@@ -0,0 +1,138 @@
+// tests for gizmo picking and manipulation
+
+mod common;
+
+use hotline_rs::gizmo;
+use hotline_rs::gizmo::{Ray, GizmoMode, GizmoAxis};
+
+use maths_rs::prelude::*;
+
+#[test]
+fn gizmo_screen_ray() {
+    let view = Mat4f::from_translation(vec3f(0.0, 0.0, -10.0)).inverse();
+    let proj = Mat4f::create_perspective_projection_lh_yup(f32::deg_to_rad(60.0), 2.0, 0.1, 1000.0);
+    let view_proj = proj * view;
+    let viewport_pos = vec2f(100.0, 50.0);
+    let viewport_size = vec2f(800.0, 400.0);
+
+    // rays through the projected positions of points must pass back through the points
+    for point in [vec3f(0.0, 0.0, 0.0), vec3f(1.0, 0.5, 0.0), vec3f(-2.0, 1.0, 3.0)] {
+        let clip = view_proj * vec4f(point.x, point.y, point.z, 1.0);
+        let ndc = clip.xyz() / clip.w;
+        let screen = vec2f(
+            viewport_pos.x + (ndc.x * 0.5 + 0.5) * viewport_size.x,
+            viewport_pos.y + (0.5 - ndc.y * 0.5) * viewport_size.y
+        );
+        let ray = gizmo::screen_ray(screen, viewport_pos, viewport_size, &view_proj).unwrap();
+        assert!(common::approx_vec3(normalize(ray.direction), ray.direction));
+        assert!(length(cross(point - ray.origin, ray.direction)) < 0.001);
+    }
+
+    // outside of the viewport or with an empty viewport there is no ray
+    assert!(gizmo::screen_ray(vec2f(50.0, 100.0), viewport_pos, viewport_size, &view_proj).is_none());
+    assert!(gizmo::screen_ray(vec2f(500.0, 500.0), viewport_pos, viewport_size, &view_proj).is_none());
+    assert!(gizmo::screen_ray(vec2f(100.0, 50.0), viewport_pos, vec2f(0.0, 0.0), &view_proj).is_none());
+}
+
+#[test]
+fn gizmo_ray_vs_aabb() {
+    let ray = Ray { origin: vec3f(0.0, 0.0, -10.0), direction: vec3f(0.0, 0.0, 1.0) };
+    assert_eq!(ray.at(2.0), vec3f(0.0, 0.0, -8.0));
+
+    // hit from the outside, miss, behind the ray and starting inside
+    assert!((gizmo::ray_vs_aabb(&ray, splat3f(-1.0), splat3f(1.0)).unwrap() - 9.0).abs() < 0.001);
+    assert!(gizmo::ray_vs_aabb(&ray, vec3f(2.0, -1.0, -1.0), vec3f(4.0, 1.0, 1.0)).is_none());
+    assert!(gizmo::ray_vs_aabb(&ray, vec3f(-1.0, -1.0, -20.0), vec3f(1.0, 1.0, -15.0)).is_none());
+    assert_eq!(gizmo::ray_vs_aabb(&ray, splat3f(-20.0), splat3f(20.0)), Some(0.0));
+
+    // diagonal ray through a corner region
+    let diagonal = Ray { origin: vec3f(-5.0, -5.0, 0.0), direction: normalize(vec3f(1.0, 1.0, 0.0)) };
+    assert!(gizmo::ray_vs_aabb(&diagonal, splat3f(-1.0), splat3f(1.0)).is_some());
+    assert!(gizmo::ray_vs_aabb(&diagonal, vec3f(2.0, -1.0, -1.0), vec3f(3.0, 0.0, 1.0)).is_none());
+
+    // extents are transformed into a world space aabb
+    let world_matrix = Mat34f::from_translation(vec3f(5.0, 0.0, 0.0)) * Mat34f::from_scale(vec3f(2.0, 1.0, 1.0));
+    let (aabb_min, aabb_max) = gizmo::world_aabb(&world_matrix, splat3f(-1.0), splat3f(1.0));
+    assert!(common::approx_vec3(aabb_min, vec3f(3.0, -1.0, -1.0)));
+    assert!(common::approx_vec3(aabb_max, vec3f(7.0, 1.0, 1.0)));
+
+    // the nearest hit aabb is picked
+    let aabbs = vec![
+        ("far", vec3f(-1.0, -1.0, 4.0), vec3f(1.0, 1.0, 6.0)),
+        ("miss", vec3f(3.0, 3.0, -6.0), vec3f(4.0, 4.0, -4.0)),
+        ("near", vec3f(-1.0, -1.0, -6.0), vec3f(1.0, 1.0, -4.0))
+    ];
+    let (picked, t) = gizmo::pick_nearest(&ray, aabbs).unwrap();
+    assert_eq!(picked, "near");
+    assert!((t - 4.0).abs() < 0.001);
+    assert!(gizmo::pick_nearest(&ray, vec![("miss", splat3f(3.0), splat3f(4.0))]).is_none());
+}
+
+#[test]
+fn gizmo_hit_test() {
+    let origin = vec3f(0.0, 0.0, 0.0);
+    let size = 1.0;
+    let down = |x: f32, z: f32| Ray { origin: vec3f(x, 10.0, z), direction: vec3f(0.0, -1.0, 0.0) };
+
+    // translate axes, the plane handle between them and empty space
+    assert_eq!(gizmo::hit_test(GizmoMode::Translate, &down(0.8, 0.0), origin, size), Some(GizmoAxis::X));
+    assert_eq!(gizmo::hit_test(GizmoMode::Translate, &down(0.0, 0.8), origin, size), Some(GizmoAxis::Z));
+    assert_eq!(gizmo::hit_test(GizmoMode::Translate, &down(0.3, 0.3), origin, size), Some(GizmoAxis::XZ));
+    assert_eq!(gizmo::hit_test(GizmoMode::Translate, &down(0.8, 0.8), origin, size), None);
+    assert_eq!(gizmo::hit_test(GizmoMode::Translate, &down(1.5, 0.0), origin, size), None);
+
+    // scale has no plane handles
+    assert_eq!(gizmo::hit_test(GizmoMode::Scale, &down(0.8, 0.0), origin, size), Some(GizmoAxis::X));
+    assert_eq!(gizmo::hit_test(GizmoMode::Scale, &down(0.3, 0.3), origin, size), None);
+
+    // rotate circles, looking down y hits the y circle but not inside or outside of it
+    assert_eq!(gizmo::hit_test(GizmoMode::Rotate, &down(0.6, 0.8), origin, size), Some(GizmoAxis::Y));
+    assert_eq!(gizmo::hit_test(GizmoMode::Rotate, &down(0.3, 0.3), origin, size), None);
+    assert_eq!(gizmo::hit_test(GizmoMode::Rotate, &down(1.5, 0.0), origin, size), None);
+
+    // the gizmo is offset by its origin
+    let offset = vec3f(10.0, 0.0, 0.0);
+    assert_eq!(gizmo::hit_test(GizmoMode::Translate, &down(10.8, 0.0), offset, size), Some(GizmoAxis::X));
+    assert_eq!(gizmo::hit_test(GizmoMode::Translate, &down(0.8, 0.0), offset, size), None);
+}
+
+#[test]
+fn gizmo_drag() {
+    let origin = vec3f(1.0, 0.0, 0.0);
+    let down = |x: f32, z: f32| Ray { origin: vec3f(x, 10.0, z), direction: vec3f(0.0, -1.0, 0.0) };
+
+    // axis constraints project onto the axis line, planes onto the plane
+    let start = gizmo::constraint_point(GizmoMode::Translate, GizmoAxis::X, &down(1.5, 0.2), origin).unwrap();
+    let current = gizmo::constraint_point(GizmoMode::Translate, GizmoAxis::X, &down(3.2, 0.9), origin).unwrap();
+    assert!(common::approx_vec3(start, vec3f(1.5, 0.0, 0.0)));
+    assert!(common::approx_vec3(current, vec3f(3.2, 0.0, 0.0)));
+    let plane = gizmo::constraint_point(GizmoMode::Translate, GizmoAxis::XZ, &down(3.2, 0.9), origin).unwrap();
+    assert!(common::approx_vec3(plane, vec3f(3.2, 0.0, 0.9)));
+    assert!(gizmo::constraint_point(GizmoMode::Translate, GizmoAxis::Y, &down(3.2, 0.9), origin).is_none());
+
+    // translation along an axis and on a plane, with and without snapping
+    assert!(common::approx_vec3(gizmo::translation_delta(GizmoAxis::X, start, current, None), vec3f(1.7, 0.0, 0.0)));
+    assert!(common::approx_vec3(gizmo::translation_delta(GizmoAxis::X, start, current, Some(0.5)), vec3f(1.5, 0.0, 0.0)));
+    let plane_start = vec3f(1.0, 0.0, 0.0);
+    assert!(common::approx_vec3(gizmo::translation_delta(GizmoAxis::XZ, plane_start, plane, Some(1.0)), vec3f(2.0, 0.0, 1.0)));
+
+    // rotation around y from +x to +z, snapped to 15 degrees
+    let rot_start = gizmo::constraint_point(GizmoMode::Rotate, GizmoAxis::Y, &down(2.0, 0.0), origin).unwrap();
+    let rot_current = gizmo::constraint_point(GizmoMode::Rotate, GizmoAxis::Y, &down(1.0, 1.0), origin).unwrap();
+    let angle = gizmo::rotation_delta(GizmoAxis::Y, origin, rot_start, rot_current, None);
+    assert!((angle.abs() - f32::deg_to_rad(90.0)).abs() < 0.001);
+    let rot_current = gizmo::constraint_point(GizmoMode::Rotate, GizmoAxis::Y, &down(2.0, 0.4), origin).unwrap();
+    let angle = gizmo::rotation_delta(GizmoAxis::Y, origin, rot_start, rot_current, Some(15.0));
+    assert!((f32::rad_to_deg(angle).abs() - 15.0).abs() < 0.001);
+
+    // scaling is relative to the distance of the start point from the origin
+    assert!((gizmo::scale_factor(GizmoAxis::X, origin, vec3f(2.0, 0.0, 0.0), vec3f(3.0, 0.0, 0.0)) - 2.0).abs() < 0.001);
+    assert!((gizmo::scale_factor(GizmoAxis::X, origin, vec3f(3.0, 0.0, 0.0), vec3f(2.0, 0.0, 0.0)) - 0.5).abs() < 0.001);
+    assert_eq!(gizmo::scale_factor(GizmoAxis::X, origin, origin, vec3f(2.0, 0.0, 0.0)), 1.0);
+
+    // snapping
+    assert_eq!(gizmo::snap(1.26, Some(0.25)), 1.25);
+    assert_eq!(gizmo::snap(-1.3, Some(0.5)), -1.5);
+    assert_eq!(gizmo::snap(1.26, None), 1.26);
+    assert_eq!(gizmo::snap(1.26, Some(0.0)), 1.26);
+}
@@ -0,0 +1,226 @@
+// tests for loading gltf and glb files into meshes
+
+mod common;
+
+use hotline_rs::gltf;
+
+use maths_rs::prelude::*;
+
+fn encode_base64(data: &[u8]) -> String {
+    let table = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(table[((n >> (18 - i * 6)) & 63) as usize] as char);
+            }
+            else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Builds the binary buffer for the test scene: an interleaved (position, normal) quad with texcoords and u16 indices,
+/// a non-indexed triangle with no normals and some (fake) encoded image bytes
+fn create_test_gltf_buffer() -> Vec<u8> {
+    let mut buffer = Vec::new();
+    let floats = |buffer: &mut Vec<u8>, v: &[f32]| v.iter().for_each(|f| buffer.extend(f.to_le_bytes()));
+    // view 0: interleaved quad positions and normals
+    for p in [[-1.0, -1.0, 0.5], [1.0, -1.0, 0.5], [1.0, 1.0, 0.5], [-1.0, 1.0, 0.5]] {
+        floats(&mut buffer, &p);
+        floats(&mut buffer, &[0.0, 0.0, 1.0]);
+    }
+    // view 1: texcoords
+    floats(&mut buffer, &[0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0]);
+    // view 2: indices + padding
+    [0u16, 1, 2, 0, 2, 3].iter().for_each(|i| buffer.extend(i.to_le_bytes()));
+    // view 3: triangle positions
+    floats(&mut buffer, &[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+    // view 4: image
+    buffer.extend(b"not_png!");
+    buffer
+}
+
+fn create_test_gltf_json(buffer_uri: Option<String>, buffer_length: usize) -> String {
+    let uri = buffer_uri.map(|uri| format!(r#""uri": "{}","#, uri)).unwrap_or_default();
+    format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "scene": 0,
+        "scenes": [{{ "nodes": [0, 2] }}],
+        "nodes": [
+            {{ "name": "root", "translation": [1.0, 2.0, 3.0], "children": [1] }},
+            {{ "name": "child", "mesh": 0, "matrix": [2,0,0,0, 0,2,0,0, 0,0,2,0, 0,0,1,1] }},
+            {{ "name": "triangle", "mesh": 1 }}
+        ],
+        "meshes": [
+            {{ "name": "quad", "primitives": [{{ "attributes": {{ "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 }}, "indices": 3, "material": 0 }}] }},
+            {{ "primitives": [{{ "attributes": {{ "POSITION": 4 }} }}] }}
+        ],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3" }},
+            {{ "bufferView": 0, "byteOffset": 12, "componentType": 5126, "count": 4, "type": "VEC3" }},
+            {{ "bufferView": 1, "componentType": 5126, "count": 4, "type": "VEC2" }},
+            {{ "bufferView": 2, "componentType": 5123, "count": 6, "type": "SCALAR" }},
+            {{ "bufferView": 3, "componentType": 5126, "count": 3, "type": "VEC3" }}
+        ],
+        "bufferViews": [
+            {{ "buffer": 0, "byteOffset": 0, "byteLength": 96, "byteStride": 24 }},
+            {{ "buffer": 0, "byteOffset": 96, "byteLength": 32 }},
+            {{ "buffer": 0, "byteOffset": 128, "byteLength": 12 }},
+            {{ "buffer": 0, "byteOffset": 140, "byteLength": 36 }},
+            {{ "buffer": 0, "byteOffset": 176, "byteLength": 8 }}
+        ],
+        "buffers": [{{ {} "byteLength": {} }}],
+        "materials": [{{
+            "name": "red",
+            "pbrMetallicRoughness": {{ "baseColorFactor": [1.0, 0.0, 0.0, 1.0], "metallicFactor": 0.5, "baseColorTexture": {{ "index": 0 }} }},
+            "normalTexture": {{ "index": 1 }}
+        }}],
+        "textures": [{{ "source": 0 }}, {{ "source": 1 }}],
+        "images": [{{ "bufferView": 4, "mimeType": "image/png" }}, {{ "uri": "data:image/png;base64,aGVsbG8=" }}]
+    }}"#, uri, buffer_length)
+}
+
+fn create_test_glb() -> Vec<u8> {
+    let buffer = create_test_gltf_buffer();
+    let mut json = create_test_gltf_json(None, buffer.len()).into_bytes();
+    while json.len() & 3 != 0 {
+        json.push(b' ');
+    }
+    let mut glb = Vec::new();
+    glb.extend(0x46546c67u32.to_le_bytes());
+    glb.extend(2u32.to_le_bytes());
+    glb.extend(((12 + 8 + json.len() + 8 + buffer.len()) as u32).to_le_bytes());
+    glb.extend((json.len() as u32).to_le_bytes());
+    glb.extend(0x4e4f534au32.to_le_bytes());
+    glb.extend(json);
+    glb.extend((buffer.len() as u32).to_le_bytes());
+    glb.extend(0x004e4942u32.to_le_bytes());
+    glb.extend(buffer);
+    glb
+}
+
+fn check_test_gltf(doc: &gltf::Gltf) -> Result<(), hotline_rs::Error> {
+    // quad is mirrored into left handed space with reversed winding and generated tangents
+    let meshes = doc.get_meshes()?;
+    assert_eq!(meshes.len(), 2);
+    assert_eq!(meshes[0].name, "quad");
+    let quad = &meshes[0].primitives[0];
+    assert_eq!(quad.material, Some(0));
+    assert_eq!(quad.vertices.len(), 4);
+    assert_eq!(quad.indices, vec![0, 2, 1, 0, 3, 2]);
+    assert!(common::approx_vec3(quad.vertices[2].position, vec3f(1.0, 1.0, -0.5)));
+    assert!(common::approx_vec3(quad.vertices[2].normal, vec3f(0.0, 0.0, -1.0)));
+    assert!(common::approx_vec3(quad.vertices[2].tangent, vec3f(1.0, 0.0, 0.0)));
+    assert_eq!(quad.vertices[2].texcoord.x, 1.0);
+    assert_eq!(quad.vertices[2].texcoord.y, 0.0);
+
+    // triangle has no normals or indices so gets flat normals
+    let triangle = &meshes[1].primitives[0];
+    assert_eq!(meshes[1].name, "mesh_1");
+    assert_eq!(triangle.material, None);
+    assert_eq!(triangle.indices, vec![0, 2, 1]);
+    assert!(triangle.vertices.iter().all(|v| common::approx_vec3(v.normal, vec3f(0.0, 0.0, -1.0))));
+
+    // nodes are parent first
+    let nodes = doc.get_nodes();
+    assert_eq!(nodes.iter().map(|n| n.name.as_str()).collect::<Vec<_>>(), vec!["root", "child", "triangle"]);
+    assert_eq!(nodes.iter().map(|n| n.parent).collect::<Vec<_>>(), vec![None, Some(0), None]);
+    assert_eq!(nodes.iter().map(|n| n.mesh).collect::<Vec<_>>(), vec![None, Some(0), Some(1)]);
+    assert!(common::approx_vec3(nodes[0].position, vec3f(1.0, 2.0, -3.0)));
+    assert!(common::approx_vec3(nodes[1].position, vec3f(0.0, 0.0, -1.0)));
+    assert!(common::approx_vec3(nodes[1].scale, vec3f(2.0, 2.0, 2.0)));
+
+    // materials fill in spec defaults
+    let materials = doc.get_materials();
+    assert_eq!(materials.len(), 1);
+    assert_eq!(materials[0].name, "red");
+    assert_eq!(materials[0].metallic, 0.5);
+    assert_eq!(materials[0].roughness, 1.0);
+    assert_eq!(materials[0].base_colour.y, 0.0);
+    assert_eq!(materials[0].albedo, Some(0));
+    assert_eq!(materials[0].normal, Some(1));
+    assert_eq!(materials[0].metallic_roughness, None);
+
+    // images from buffer views and data uris
+    assert_eq!(doc.get_image_data(0)?, b"not_png!".to_vec());
+    assert_eq!(doc.get_image_data(1)?, b"hello".to_vec());
+    Ok(())
+}
+
+#[test]
+fn gltf_load_embedded() -> Result<(), hotline_rs::Error> {
+    let buffer = create_test_gltf_buffer();
+    let uri = format!("data:application/octet-stream;base64,{}", encode_base64(&buffer));
+    let json = create_test_gltf_json(Some(uri), buffer.len());
+    let doc = gltf::load_from_memory(json.as_bytes(), std::path::Path::new(""))?;
+    check_test_gltf(&doc)
+}
+
+#[test]
+fn gltf_load_glb_and_external_files() -> Result<(), hotline_rs::Error> {
+    let folder = common::test_folder("gltf")?;
+
+    let glb_path = folder.join("test.glb");
+    std::fs::write(&glb_path, create_test_glb())?;
+    check_test_gltf(&gltf::load_from_file(glb_path.to_str().unwrap())?)?;
+
+    let buffer = create_test_gltf_buffer();
+    std::fs::write(folder.join("test buffer.bin"), &buffer)?;
+    let gltf_path = folder.join("test.gltf");
+    std::fs::write(&gltf_path, create_test_gltf_json(Some("test%20buffer.bin".to_string()), buffer.len()))?;
+    check_test_gltf(&gltf::load_from_file(gltf_path.to_str().unwrap())?)
+}
+
+#[test]
+fn gltf_errors() -> Result<(), hotline_rs::Error> {
+    let buffer = create_test_gltf_buffer();
+    let uri = format!("data:application/octet-stream;base64,{}", encode_base64(&buffer));
+
+    // buffer shorter than declared length
+    let json = create_test_gltf_json(Some(uri.clone()), buffer.len() + 4);
+    assert!(gltf::load_from_memory(json.as_bytes(), std::path::Path::new("")).is_err());
+
+    // required extensions are unsupported
+    let json = create_test_gltf_json(Some(uri.clone()), buffer.len())
+        .replacen("\"asset\"", "\"extensionsRequired\": [\"KHR_draco_mesh_compression\"], \"asset\"", 1);
+    assert!(gltf::load_from_memory(json.as_bytes(), std::path::Path::new("")).is_err());
+
+    // accessor reading past the end of its buffer view
+    let json = create_test_gltf_json(Some(uri.clone()), buffer.len())
+        .replacen("\"count\": 6", "\"count\": 7", 1);
+    let doc = gltf::load_from_memory(json.as_bytes(), std::path::Path::new(""))?;
+    assert!(doc.get_meshes().is_err());
+
+    // accessor count which overflows the size of its buffer view
+    let json = create_test_gltf_json(Some(uri.clone()), buffer.len())
+        .replacen("\"count\": 6", &format!("\"count\": {}", usize::MAX), 1);
+    let doc = gltf::load_from_memory(json.as_bytes(), std::path::Path::new(""))?;
+    assert!(doc.get_meshes().is_err());
+
+    // zero initialised accessor without a buffer view is too large to allocate
+    let json = create_test_gltf_json(Some(uri), buffer.len())
+        .replacen("\"bufferView\": 3, \"componentType\": 5126, \"count\": 3", "\"componentType\": 5126, \"count\": 4000000000000", 1);
+    let doc = gltf::load_from_memory(json.as_bytes(), std::path::Path::new(""))?;
+    assert!(doc.get_meshes().is_err());
+    Ok(())
+}
+
+#[test]
+fn gltf_create_meshes() -> Result<(), hotline_rs::Error> {
+    let glb = create_test_glb();
+    let doc = gltf::load_from_memory(&glb, std::path::Path::new(""))?;
+
+    let mut dev = common::create_null_device(16);
+    let meshes = gltf::create_meshes(&mut dev, &doc.get_meshes()?);
+    assert_eq!(meshes.len(), 2);
+    assert_eq!(meshes[0][0].num_indices, 6);
+    assert_eq!(meshes[0][0].num_vertices, 4);
+    assert_eq!(meshes[1][0].num_indices, 3);
+    assert!(common::approx_vec3(meshes[0][0].aabb_min, vec3f(-1.0, -1.0, -0.5)));
+    Ok(())
+}
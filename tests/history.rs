@@ -0,0 +1,168 @@
+// tests for undo and redo history
+
+mod common;
+
+use hotline_rs::plugin;
+use hotline_rs::inspector;
+use hotline_rs::history;
+use hotline_rs::ecs_base;
+use hotline_rs::ecs_base::{Name, Parent, Position, WorldMatrix, SessionInfo};
+use hotline_rs::inspector::InspectorComponent;
+use hotline_rs::history::History;
+
+use maths_rs::prelude::*;
+
+/// Tracks for 2 idle frames so any changes have settled and are recorded
+fn track_idle(history: &mut History, world: &bevy_ecs::world::World, selected: Option<bevy_ecs::entity::Entity>, session: &SessionInfo) {
+    history.track(world, selected, session, true);
+    history.track(world, selected, session, true);
+}
+
+fn find_named(world: &mut bevy_ecs::world::World, name: &str) -> Option<bevy_ecs::entity::Entity> {
+    world.query::<(bevy_ecs::entity::Entity, &Name)>().iter(world)
+        .find(|(_, entity_name)| entity_name.0 == name)
+        .map(|(entity, _)| entity)
+}
+
+#[test]
+fn history_component_edits() {
+    let mut world = bevy_ecs::world::World::new();
+    let mut session = SessionInfo::default();
+    let mut history = History::default();
+    let entity = inspector::spawn_entity(&mut world);
+    track_idle(&mut history, &world, Some(entity), &session);
+    assert!(history.commands().is_empty());
+
+    // changes are recorded as a single edit once input is idle and they have stopped changing
+    world.get_mut::<Position>(entity).unwrap().0 = vec3f(1.0, 2.0, 3.0);
+    history.track(&world, Some(entity), &session, false);
+    world.get_mut::<Position>(entity).unwrap().0 = vec3f(4.0, 5.0, 6.0);
+    history.track(&world, Some(entity), &session, false);
+    inspector::add_component(&mut world, entity, InspectorComponent::Colour);
+    history.track(&world, Some(entity), &session, true);
+    assert!(history.commands().is_empty());
+    history.track(&world, Some(entity), &session, true);
+    assert_eq!(history.commands().len(), 1);
+    assert_eq!(history.commands()[0].name(), format!("Edit entity_{} Position, Colour", entity.index()));
+
+    // undo restores values and removes added components, and is not recorded as an edit itself
+    assert!(history.undo(&mut world, &mut session));
+    assert_eq!(world.get::<Position>(entity).unwrap().0, Vec3f::zero());
+    assert!(world.get::<ecs_base::Colour>(entity).is_none());
+    assert!(!history.undo(&mut world, &mut session));
+    track_idle(&mut history, &world, Some(entity), &session);
+    track_idle(&mut history, &world, Some(entity), &session);
+    assert_eq!(history.commands().len(), 1);
+
+    assert!(history.redo(&mut world, &mut session));
+    assert_eq!(world.get::<Position>(entity).unwrap().0, vec3f(4.0, 5.0, 6.0));
+    assert!(world.get::<ecs_base::Colour>(entity).is_some());
+    assert!(!history.redo(&mut world, &mut session));
+
+    // changing the selection is not an edit
+    track_idle(&mut history, &world, Some(entity), &session);
+    let other = inspector::spawn_entity(&mut world);
+    track_idle(&mut history, &world, Some(other), &session);
+    assert_eq!(history.commands().len(), 1);
+
+    // a new edit discards commands which could be redone
+    history.undo(&mut world, &mut session);
+    track_idle(&mut history, &world, Some(other), &session);
+    world.get_mut::<Name>(other).unwrap().0 = "renamed".to_string();
+    track_idle(&mut history, &world, Some(other), &session);
+    assert_eq!(history.commands().len(), 1);
+    assert_eq!(history.commands()[0].name(), "Edit renamed Name");
+    assert!(!history.can_redo());
+
+    // the main camera is recorded with the session rather than as an entity
+    let camera = world.spawn((Position(Vec3f::zero()), ecs_base::MainCamera)).id();
+    track_idle(&mut history, &world, Some(camera), &session);
+    world.get_mut::<Position>(camera).unwrap().0 = vec3f(1.0, 0.0, 0.0);
+    track_idle(&mut history, &world, Some(camera), &session);
+    assert_eq!(history.commands().len(), 1);
+}
+
+#[test]
+fn history_spawn_despawn() {
+    let mut world = bevy_ecs::world::World::new();
+    let mut session = SessionInfo::default();
+    let mut history = History::default();
+    let entity_count = |world: &mut bevy_ecs::world::World| world.query::<bevy_ecs::entity::Entity>().iter(world).count();
+
+    let parent = history.spawn(&mut world);
+    let child = history.spawn(&mut world);
+    world.entity_mut(child).insert(Parent(parent));
+    world.get_mut::<Position>(child).unwrap().0 = vec3f(1.0, 0.0, 0.0);
+    assert_eq!(history.commands().len(), 2);
+    assert_eq!(history.commands()[0].name(), format!("Spawn entity_{}", parent.index()));
+    let parent_name = format!("entity_{}", parent.index());
+    let child_name = format!("entity_{}", child.index());
+
+    // despawning removes descendants, undo spawns them again with their components and hierarchy
+    history.despawn(&mut world, parent);
+    assert_eq!(entity_count(&mut world), 0);
+    assert_eq!(history.commands()[2].name(), format!("Despawn {}", parent_name));
+    history.undo(&mut world, &mut session);
+    let restored_parent = find_named(&mut world, &parent_name).unwrap();
+    let restored_child = find_named(&mut world, &child_name).unwrap();
+    assert_eq!(world.get::<Parent>(restored_child).unwrap().0, restored_parent);
+    assert_eq!(world.get::<Position>(restored_child).unwrap().0, vec3f(1.0, 0.0, 0.0));
+    assert!(world.get::<WorldMatrix>(restored_parent).is_some());
+
+    // earlier commands refer to the entities which replaced the despawned ones
+    history.undo(&mut world, &mut session);
+    assert!(!world.entities().contains(restored_child));
+    assert!(world.entities().contains(restored_parent));
+    history.undo(&mut world, &mut session);
+    assert_eq!(entity_count(&mut world), 0);
+
+    // redoing spawns and despawns them again
+    history.goto(&mut world, &mut session, 3);
+    assert_eq!(history.cursor(), 3);
+    assert_eq!(entity_count(&mut world), 0);
+    history.goto(&mut world, &mut session, 2);
+    let restored_parent = find_named(&mut world, &parent_name).unwrap();
+    let restored_child = find_named(&mut world, &child_name).unwrap();
+    assert_eq!(world.get::<Parent>(restored_child).unwrap().0, restored_parent);
+    assert_eq!(entity_count(&mut world), 2);
+}
+
+#[test]
+fn history_session() -> Result<(), hotline_rs::Error> {
+    let mut world = bevy_ecs::world::World::new();
+    let mut session = SessionInfo::default();
+    let mut history = History::default();
+    track_idle(&mut history, &world, None, &session);
+
+    session.active_demo = "draw_indexed".to_string();
+    track_idle(&mut history, &world, None, &session);
+    session.main_camera = Some(ecs_base::CameraInfo::default());
+    track_idle(&mut history, &world, None, &session);
+    let names = history.commands().iter().map(|command| command.name()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["Demo draw_indexed", "Camera"]);
+
+    history.undo(&mut world, &mut session);
+    assert!(session.main_camera.is_none());
+    assert_eq!(session.active_demo, "draw_indexed");
+    history.undo(&mut world, &mut session);
+    assert!(session.active_demo.is_empty());
+
+    // the stack can be handed over to a reloaded plugin with the state saved by `export_plugin_state`
+    let data = plugin::serialise_state(&(&history,));
+    let (mut restored,) : (History,) = plugin::deserialise_state(&data)?;
+    assert_eq!(restored.cursor(), 0);
+    assert_eq!(restored.commands().len(), 2);
+    restored.goto(&mut world, &mut session, 2);
+    assert_eq!(session.active_demo, "draw_indexed");
+    assert!(session.main_camera.is_some());
+
+    // the oldest commands are dropped
+    for i in 0..history::HISTORY_MAX_COMMANDS + 10 {
+        session.active_demo = format!("demo_{}", i);
+        track_idle(&mut restored, &world, None, &session);
+    }
+    assert_eq!(restored.commands().len(), history::HISTORY_MAX_COMMANDS);
+    assert_eq!(restored.cursor(), history::HISTORY_MAX_COMMANDS);
+    assert_eq!(restored.commands().last().unwrap().name(), format!("Demo demo_{}", history::HISTORY_MAX_COMMANDS + 9));
+    Ok(())
+}
@@ -0,0 +1,189 @@
+// tests for loading and writing image files
+
+mod common;
+
+use hotline_rs::gfx;
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// Encodes a 16-bit rgba png with the image data in a single uncompressed deflate block
+fn create_test_png16(width: u32, height: u32, rgba: &[u16]) -> Vec<u8> {
+    let mut raw = Vec::new();
+    for row in rgba.chunks_exact(width as usize * 4) {
+        raw.push(0);
+        raw.extend(row.iter().flat_map(|v| v.to_be_bytes()));
+    }
+    let (a, b) = raw.iter().fold((1u32, 0u32), |(a, b), v| ((a + *v as u32) % 65521, (b + a + *v as u32) % 65521));
+    let mut zlib = vec![0x78, 0x01, 0x01];
+    zlib.extend((raw.len() as u16).to_le_bytes());
+    zlib.extend((!(raw.len() as u16)).to_le_bytes());
+    zlib.extend(&raw);
+    zlib.extend(((b << 16) | a).to_be_bytes());
+
+    let mut ihdr = Vec::new();
+    ihdr.extend(width.to_be_bytes());
+    ihdr.extend(height.to_be_bytes());
+    ihdr.extend([16, 6, 0, 0, 0]);
+
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+    for (chunk_type, data) in [(b"IHDR", ihdr), (b"IDAT", zlib), (b"IEND", Vec::new())] {
+        png.extend((data.len() as u32).to_be_bytes());
+        let mut chunk = chunk_type.to_vec();
+        chunk.extend(data);
+        png.extend(&chunk);
+        png.extend(crc32(&chunk).to_be_bytes());
+    }
+    png
+}
+
+/// Encodes a flat (not run length encoded) radiance hdr from rgbe pixels, width must be less than 8
+fn create_test_hdr(width: u32, height: u32, rgbe: &[[u8; 4]]) -> Vec<u8> {
+    let mut hdr = format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n", height, width).into_bytes();
+    hdr.extend(rgbe.iter().flatten());
+    hdr
+}
+
+fn image_f32s(image: &hotline_rs::image::ImageData) -> Vec<f32> {
+    image.data.chunks_exact(4).map(|v| f32::from_ne_bytes([v[0], v[1], v[2], v[3]])).collect()
+}
+
+fn image_u16s(image: &hotline_rs::image::ImageData) -> Vec<u16> {
+    image.data.chunks_exact(2).map(|v| u16::from_ne_bytes([v[0], v[1]])).collect()
+}
+
+#[test]
+fn image_load_8_bit() -> Result<(), hotline_rs::Error> {
+    let folder = common::test_folder("image_load_8_bit")?;
+    let filepath = folder.join("rgba8.png");
+    let filepath = filepath.to_str().unwrap();
+    hotline_rs::image::write_to_file(filepath, 2, 1, 4, &[255, 0, 0, 255, 0, 51, 255, 255])?;
+
+    let img = hotline_rs::image::load_from_file(filepath, None)?;
+    assert_eq!(img.info.format, gfx::Format::RGBA8n);
+    assert_eq!((img.info.width, img.info.height), (2, 1));
+    assert_eq!(img.data, vec![255, 0, 0, 255, 0, 51, 255, 255]);
+
+    // unorm values are converted linearly
+    let img = hotline_rs::image::load_from_file(filepath, Some(gfx::Format::RGBA32f))?;
+    assert_eq!(img.info.format, gfx::Format::RGBA32f);
+    assert_eq!(image_f32s(&img), vec![1.0, 0.0, 0.0, 1.0, 0.0, 0.2, 1.0, 1.0]);
+
+    let img = hotline_rs::image::load_from_file(filepath, Some(gfx::Format::RGBA16n))?;
+    assert_eq!(image_u16s(&img), vec![65535, 0, 0, 65535, 0, 13107, 65535, 65535]);
+
+    let img = hotline_rs::image::load_from_file(filepath, Some(gfx::Format::RGBA16f))?;
+    assert_eq!(img.data.len(), 2 * 4 * 2);
+    assert_eq!(image_u16s(&img)[0..4], [0x3c00, 0, 0, 0x3c00]);
+    Ok(())
+}
+
+#[test]
+fn image_load_16_bit() -> Result<(), hotline_rs::Error> {
+    let folder = common::test_folder("image_load_16_bit")?;
+    let filepath = folder.join("rgba16.png");
+    let rgba = [65535, 0, 257, 65535, 1, 32896, 65534, 65535, 0, 0, 0, 0, 12345, 54321, 4096, 65535];
+    std::fs::write(&filepath, create_test_png16(2, 2, &rgba))?;
+    let filepath = filepath.to_str().unwrap();
+
+    // full precision is kept by default
+    let img = hotline_rs::image::load_from_file(filepath, None)?;
+    assert_eq!(img.info.format, gfx::Format::RGBA16n);
+    assert_eq!((img.info.width, img.info.height), (2, 2));
+    assert_eq!(image_u16s(&img), rgba.to_vec());
+
+    let img = hotline_rs::image::load_from_file(filepath, Some(gfx::Format::RGBA8n))?;
+    assert_eq!(img.info.format, gfx::Format::RGBA8n);
+    assert_eq!(img.data[0..8], [255, 0, 1, 255, 0, 128, 255, 255]);
+
+    let img = hotline_rs::image::load_from_file(filepath, Some(gfx::Format::RGBA32f))?;
+    let values = image_f32s(&img);
+    for (value, expected) in values.iter().zip(rgba) {
+        assert!((value - expected as f32 / 65535.0).abs() < 0.000001);
+    }
+    Ok(())
+}
+
+#[test]
+fn image_load_hdr() -> Result<(), hotline_rs::Error> {
+    let folder = common::test_folder("image_load_hdr")?;
+    let filepath = folder.join("env.hdr");
+
+    // rgbe 1.0 and (2.0, 1.0, 0.5)
+    std::fs::write(&filepath, create_test_hdr(2, 1, &[[128, 128, 128, 129], [128, 64, 32, 130]]))?;
+    let filepath = filepath.to_str().unwrap();
+
+    let img = hotline_rs::image::load_from_file(filepath, None)?;
+    assert_eq!(img.info.format, gfx::Format::RGBA32f);
+    assert_eq!((img.info.width, img.info.height), (2, 1));
+    assert_eq!(image_f32s(&img), vec![1.0, 1.0, 1.0, 1.0, 2.0, 1.0, 0.5, 1.0]);
+
+    let img = hotline_rs::image::load_from_file(filepath, Some(gfx::Format::RGBA16f))?;
+    assert_eq!(img.info.format, gfx::Format::RGBA16f);
+    assert_eq!(image_u16s(&img), vec![0x3c00, 0x3c00, 0x3c00, 0x3c00, 0x4000, 0x3c00, 0x3800, 0x3c00]);
+
+    // values above 1 are clamped when requesting unorm formats
+    let img = hotline_rs::image::load_from_file(filepath, Some(gfx::Format::RGBA8n))?;
+    assert_eq!(img.data, vec![255, 255, 255, 255, 255, 255, 128, 255]);
+    Ok(())
+}
+
+#[test]
+fn image_load_hdr_rle() -> Result<(), hotline_rs::Error> {
+    // an 8 texel scanline with runs for red, blue and exponent and literal values for green
+    let mut hdr = format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n", 1, 8).into_bytes();
+    hdr.extend([2, 2, 0, 8]);
+    hdr.extend([128 + 8, 128]);
+    hdr.extend([8, 128, 64, 128, 64, 128, 64, 128, 64]);
+    hdr.extend([128 + 8, 32]);
+    hdr.extend([128 + 8, 129]);
+
+    let img = hotline_rs::image::load_from_memory(&hdr, None)?;
+    assert_eq!(img.info.format, gfx::Format::RGBA32f);
+    assert_eq!((img.info.width, img.info.height), (8, 1));
+    let texels = image_f32s(&img);
+    assert_eq!(texels[0..8], [1.0, 1.0, 0.25, 1.0, 1.0, 0.5, 0.25, 1.0]);
+    assert_eq!(texels[28..32], [1.0, 0.5, 0.25, 1.0]);
+
+    let img = hotline_rs::image::load_from_memory(&hdr, Some(gfx::Format::RGBA16f))?;
+    assert_eq!(img.data.len(), 8 * 4 * 2);
+    assert_eq!(image_u16s(&img)[0..8], [0x3c00, 0x3c00, 0x3400, 0x3c00, 0x3c00, 0x3800, 0x3400, 0x3c00]);
+
+    // truncated scanlines are an error
+    assert!(hotline_rs::image::load_from_memory(&hdr[..hdr.len() - 1], None).is_err());
+    Ok(())
+}
+
+#[test]
+fn image_load_errors() -> Result<(), hotline_rs::Error> {
+    let folder = common::test_folder("image_load_errors")?;
+
+    let missing = folder.join("missing.png");
+    let _ = std::fs::remove_file(&missing);
+    let err = hotline_rs::image::load_from_file(missing.to_str().unwrap(), None).err().unwrap();
+    assert!(err.msg.contains("failed to open"));
+
+    let corrupt = folder.join("corrupt.png");
+    std::fs::write(&corrupt, b"not an image")?;
+    let err = hotline_rs::image::load_from_file(corrupt.to_str().unwrap(), None).err().unwrap();
+    assert!(err.msg.contains("corrupt.png"));
+
+    let corrupt_dds = folder.join("corrupt.dds");
+    std::fs::write(&corrupt_dds, b"DDS ")?;
+    assert!(hotline_rs::image::load_from_file(corrupt_dds.to_str().unwrap(), None).is_err());
+
+    // only formats stb_image output can be converted to are supported
+    let png = create_test_png16(1, 1, &[0, 0, 0, 0]);
+    assert!(hotline_rs::image::load_from_memory(&png, Some(gfx::Format::R32f)).is_err());
+    assert!(hotline_rs::image::load_from_memory(&png, Some(gfx::Format::RGBA16n)).is_ok());
+    assert!(hotline_rs::image::load_from_memory(&[], None).is_err());
+    Ok(())
+}
@@ -0,0 +1,112 @@
+// tests for image comparison and golden image checks
+
+mod common;
+
+use hotline_rs::gfx;
+use hotline_rs::image_diff;
+
+fn create_test_image(width: u64, height: u64, rgba: [u8; 4]) -> hotline_rs::image::ImageData {
+    hotline_rs::image::ImageData {
+        info: gfx::TextureInfo {
+            format: gfx::Format::RGBA8n,
+            width,
+            height,
+            ..Default::default()
+        },
+        data: rgba.repeat((width * height) as usize)
+    }
+}
+
+#[test]
+fn image_diff_compare() -> Result<(), hotline_rs::Error> {
+    let reference = create_test_image(8, 8, [100, 150, 200, 255]);
+    let info = image_diff::DiffInfo::default();
+
+    // identical images, alpha is ignored by default
+    let mut output = create_test_image(8, 8, [100, 150, 200, 0]);
+    let result = image_diff::compare(&reference, &output, &info)?;
+    assert!(result.passed);
+    assert_eq!(result.compared_pixels, 64);
+    assert_eq!(result.different_pixels, 0);
+    assert_eq!(result.rmse, 0.0);
+    assert_eq!(result.max_delta_e, 0.0);
+
+    // small differences within the channel tolerance are not counted
+    output.data[0] = 104;
+    let result = image_diff::compare(&reference, &output, &info)?;
+    assert_eq!(result.different_pixels, 0);
+    assert_eq!(result.max_channel_delta, 4);
+    assert!(result.rmse > 0.0 && result.mean_delta_e > 0.0);
+
+    // a single pixel over tolerance fails the pixel threshold and is highlighted in the diff image
+    let offset = (3 * 8 + 5) * 4;
+    output.data[offset + 1] = 20;
+    let result = image_diff::compare(&reference, &output, &info)?;
+    assert!(!result.passed);
+    assert_eq!(result.different_pixels, 1);
+    assert_eq!(result.max_channel_delta, 130);
+    assert!(result.max_delta_e > 2.3);
+    assert_eq!(result.diff_image.data[offset], 128 + 65);
+    assert_eq!(result.diff_image.data[offset + 1], 0);
+    assert!(result.summary(&info).starts_with("FAILED 8x8: 1/64 pixels differ"));
+
+    // ignoring the region makes it pass again
+    let info = image_diff::DiffInfo {
+        ignore_regions: vec![image_diff::IgnoreRegion { x: 4, y: 2, width: 2, height: 2 }],
+        ..Default::default()
+    };
+    let result = image_diff::compare(&reference, &output, &info)?;
+    assert!(result.passed);
+    assert_eq!(result.compared_pixels, 60);
+
+    // looser thresholds also pass
+    let info = image_diff::DiffInfo {
+        max_different_pixels: 0.05,
+        max_rmse: 20.0,
+        max_mean_delta_e: 5.0,
+        ..Default::default()
+    };
+    assert!(image_diff::compare(&reference, &output, &info)?.passed);
+
+    // alpha can be compared
+    let info = image_diff::DiffInfo {
+        ignore_alpha: false,
+        ..Default::default()
+    };
+    let result = image_diff::compare(&reference, &create_test_image(8, 8, [100, 150, 200, 0]), &info)?;
+    assert!(!result.passed);
+    assert_eq!(result.different_pixels, 64);
+
+    // size mismatch is an error
+    assert!(image_diff::compare(&reference, &create_test_image(4, 8, [0; 4]), &info).is_err());
+    Ok(())
+}
+
+#[test]
+fn image_diff_info_json() -> Result<(), hotline_rs::Error> {
+    let folder = common::test_folder("image_diff")?;
+    std::fs::write(folder.join("demo.json"), r#"{"max_rmse": 4.5, "ignore_regions": [{"x": 1, "y": 2, "width": 3, "height": 4}]}"#)?;
+
+    let info = image_diff::load_diff_info(folder.to_str().unwrap(), "demo")?;
+    assert_eq!(info.max_rmse, 4.5);
+    assert_eq!(info.channel_tolerance, image_diff::DiffInfo::default().channel_tolerance);
+    assert_eq!(info.ignore_regions, vec![image_diff::IgnoreRegion { x: 1, y: 2, width: 3, height: 4 }]);
+
+    // missing info uses defaults
+    let info = image_diff::load_diff_info(folder.to_str().unwrap(), "no_demo")?;
+    assert_eq!(info.max_rmse, image_diff::DiffInfo::default().max_rmse);
+
+    // a missing reference fails unless it is blessed or not required
+    let output = folder.join("output");
+    let reference = folder.join("reference");
+    let _ = std::fs::remove_dir_all(&reference);
+    std::fs::create_dir_all(&output)?;
+    let image = create_test_image(4, 4, [10, 20, 30, 255]);
+    hotline_rs::image::write_to_file(output.join("demo.png").to_str().unwrap(), 4, 4, 4, &image.data)?;
+    let (output, reference) = (output.to_str().unwrap(), reference.to_str().unwrap());
+    assert!(image_diff::check_golden_image(output, reference, "demo", false, true).is_err());
+    image_diff::check_golden_image(output, reference, "demo", false, false)?;
+    assert!(!std::path::Path::new(reference).join("demo.png").exists());
+    image_diff::check_golden_image(output, reference, "demo", true, true)?;
+    image_diff::check_golden_image(output, reference, "demo", false, true)
+}
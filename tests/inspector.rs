@@ -0,0 +1,96 @@
+// tests for the inspector entity tree and component editing
+
+mod common;
+
+use hotline_rs::inspector;
+use hotline_rs::ecs_base;
+use hotline_rs::ecs_base::{Name, Parent, LocalMatrix, WorldMatrix};
+use hotline_rs::inspector::InspectorComponent;
+
+#[test]
+fn inspector_entity_tree() {
+    let mut world = bevy_ecs::world::World::new();
+    let root = world.spawn(Name("root".to_string())).id();
+    let child_b = world.spawn_empty().id();
+    let child_a = world.spawn(Name("child_a".to_string())).id();
+    let grandchild = world.spawn(Parent(child_a)).id();
+    world.entity_mut(child_b).insert(Parent(root));
+    world.entity_mut(child_a).insert(Parent(root));
+
+    // parents which have been despawned and parent cycles are listed as roots
+    let orphan_parent = world.spawn_empty().id();
+    let orphan = world.spawn(Parent(orphan_parent)).id();
+    let cycle_a = world.spawn_empty().id();
+    let cycle_b = world.spawn(Parent(cycle_a)).id();
+    world.entity_mut(cycle_a).insert(Parent(cycle_b));
+    world.despawn(orphan_parent);
+
+    let tree = inspector::entity_tree(&mut world);
+    assert_eq!(tree.roots, vec![root, orphan, cycle_a]);
+    assert_eq!(tree.children[&root], vec![child_b, child_a]);
+    assert_eq!(tree.children[&child_a], vec![grandchild]);
+    assert_eq!(tree.children[&cycle_a], vec![cycle_b]);
+    assert!(tree.children.get(&cycle_b).map(|c| c.is_empty()).unwrap_or(true));
+
+    assert_eq!(inspector::entity_label(&world, root), "root");
+    assert_eq!(inspector::entity_label(&world, child_b), format!("entity {}", child_b.index()));
+}
+
+#[test]
+fn inspector_add_remove_components() {
+    let mut world = bevy_ecs::world::World::new();
+    let entity = world.spawn(Name("entity".to_string())).id();
+    for component in InspectorComponent::ALL {
+        assert!(!inspector::has_component(&world, entity, component));
+    }
+
+    // a complete transform joins the hierarchy
+    inspector::add_component(&mut world, entity, InspectorComponent::Position);
+    inspector::add_component(&mut world, entity, InspectorComponent::Rotation);
+    assert!(world.get::<WorldMatrix>(entity).is_none());
+    inspector::add_component(&mut world, entity, InspectorComponent::Scale);
+    assert!(world.get::<WorldMatrix>(entity).is_some());
+
+    inspector::add_component(&mut world, entity, InspectorComponent::Colour);
+    inspector::add_component(&mut world, entity, InspectorComponent::Light);
+    inspector::add_component(&mut world, entity, InspectorComponent::Camera);
+    for component in InspectorComponent::ALL {
+        assert!(inspector::has_component(&world, entity, component));
+    }
+    assert!(world.get::<ecs_base::ViewProjectionMatrix>(entity).is_some());
+    assert_eq!(world.get::<ecs_base::Camera>(entity).unwrap().camera_type, ecs_base::CameraType::None);
+
+    let mut schedule = bevy_ecs::schedule::Schedule::default();
+    schedule.add_systems(ecs_base::transform_hierarchy_systems());
+    schedule.run(&mut world);
+    assert!(world.get::<LocalMatrix>(entity).is_some());
+
+    inspector::remove_component(&mut world, entity, InspectorComponent::Scale);
+    assert!(world.get::<LocalMatrix>(entity).is_none());
+    assert!(world.get::<WorldMatrix>(entity).is_some());
+    inspector::remove_component(&mut world, entity, InspectorComponent::Camera);
+    assert!(world.get::<ecs_base::ViewProjectionMatrix>(entity).is_none());
+    inspector::remove_component(&mut world, entity, InspectorComponent::Light);
+    assert!(!inspector::has_component(&world, entity, InspectorComponent::Light));
+    assert!(inspector::has_component(&world, entity, InspectorComponent::Colour));
+}
+
+#[test]
+fn inspector_spawn_despawn() {
+    let mut world = bevy_ecs::world::World::new();
+    let entity = inspector::spawn_entity(&mut world);
+    assert_eq!(inspector::entity_label(&world, entity), format!("entity_{}", entity.index()));
+    assert!(world.get::<WorldMatrix>(entity).is_some());
+
+    // despawning removes descendants but not siblings
+    let child = inspector::spawn_entity(&mut world);
+    let grandchild = inspector::spawn_entity(&mut world);
+    let sibling = inspector::spawn_entity(&mut world);
+    world.entity_mut(child).insert(Parent(entity));
+    world.entity_mut(grandchild).insert(Parent(child));
+    inspector::despawn_entity(&mut world, entity);
+    assert!(!world.entities().contains(entity));
+    assert!(!world.entities().contains(child));
+    assert!(!world.entities().contains(grandchild));
+    assert!(world.entities().contains(sibling));
+}
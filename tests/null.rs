@@ -0,0 +1,377 @@
+// tests for the headless null os and gfx backends, these run on all platforms without a gpu or window server
+
+mod common;
+
+use hotline_rs::os;
+use hotline_rs::gfx;
+use hotline_rs::gfx::null;
+use hotline_rs::image_diff;
+use hotline_rs::os::{App, Window};
+use hotline_rs::gfx::{Device, SwapChain, CmdBuf, Buffer, ReadBackRequest, Texture};
+
+
+fn buffer_info(usage: gfx::BufferUsage, num_elements: usize) -> gfx::BufferInfo {
+    gfx::BufferInfo {
+        usage,
+        cpu_access: gfx::CpuAccessFlags::NONE,
+        format: gfx::Format::Unknown,
+        stride: 4,
+        num_elements,
+        initial_state: gfx::ResourceState::CopyDst,
+    }
+}
+
+#[test]
+fn null_buffer_update_bounds() -> Result<(), hotline_rs::Error> {
+    let mut dev = common::create_null_device(4);
+    let mut buf = dev.create_buffer::<u32>(&buffer_info(gfx::BufferUsage::CONSTANT_BUFFER, 4), None)?;
+    assert_eq!(buf.get_size_bytes(), 16);
+    assert!(buf.get_cbv_index().is_some());
+    buf.update(4, &[7u32, 8u32])?;
+    assert!(buf.update(12, &[1u32, 2u32]).is_err());
+    assert_eq!(buf.get_data()[4..12], [7, 0, 0, 0, 8, 0, 0, 0]);
+    Ok(())
+}
+
+#[test]
+fn null_heap_full() -> Result<(), hotline_rs::Error> {
+    let mut dev = common::create_null_device(1);
+    let _buf = dev.create_buffer::<u8>(&buffer_info(gfx::BufferUsage::CONSTANT_BUFFER, 1), None)?;
+    let full = dev.create_buffer::<u8>(&buffer_info(gfx::BufferUsage::CONSTANT_BUFFER, 1), None);
+    assert!(full.is_err());
+    Ok(())
+}
+
+#[test]
+fn null_replace_texture_srv() -> Result<(), hotline_rs::Error> {
+    let mut dev = common::create_null_device(4);
+    let mut heap = dev.create_heap(&gfx::HeapInfo {
+        heap_type: gfx::HeapType::Shader,
+        num_descriptors: 4,
+        debug_name: None
+    });
+    let info = gfx::TextureInfo {
+        width: 2,
+        height: 2,
+        format: gfx::Format::RGBA8n,
+        usage: gfx::TextureUsage::SHADER_RESOURCE,
+        ..Default::default()
+    };
+    let mut old = dev.create_texture_with_heaps::<u8>(&info, gfx::TextureHeapInfo {
+        shader: Some(&mut heap),
+        ..Default::default()
+    }, None)?;
+    let mut new = dev.create_texture_with_heaps::<u8>(&info, gfx::TextureHeapInfo {
+        shader: Some(&mut heap),
+        ..Default::default()
+    }, Some([255u8; 16].as_slice()))?;
+    let old_index = old.get_srv_index();
+    let new_index = new.get_srv_index();
+    assert_ne!(old_index, new_index);
+
+    // the replacement takes the slot of the old texture, which takes the other slot to release it when dropped
+    dev.replace_texture_srv(&mut old, &mut new, &mut heap)?;
+    assert_eq!(new.get_srv_index(), old_index);
+    assert_eq!(old.get_srv_index(), new_index);
+    drop(old);
+    assert_eq!(heap.get_num_allocated(), 2);
+
+    // both textures must have a shader resource view in the heap
+    let mut other = dev.create_texture::<u8>(&info, None)?;
+    assert!(dev.replace_texture_srv(&mut other, &mut new, &mut heap).is_err());
+    Ok(())
+}
+
+#[test]
+fn null_cmd_buf_validation() -> Result<(), hotline_rs::Error> {
+    let mut dev = common::create_null_device(4);
+    let tex = dev.create_texture::<u8>(&gfx::TextureInfo {
+        format: gfx::Format::RGBA8n,
+        width: 4,
+        height: 4,
+        usage: gfx::TextureUsage::RENDER_TARGET,
+        ..Default::default()
+    }, None)?;
+    let pass = dev.create_render_pass(&gfx::RenderPassInfo {
+        render_targets: vec![&tex],
+        rt_clear: Some(gfx::ClearColour { r: 1.0, g: 0.0, b: 0.0, a: 1.0 }),
+        depth_stencil: None,
+        ds_clear: None,
+        resolve: false,
+        discard: false,
+        array_slice: 0
+    })?;
+
+    let mut cmd = dev.create_cmd_buf(1);
+    cmd.begin_render_pass(&pass);
+    cmd.draw_instanced(3, 1, 0, 0);
+    assert!(cmd.close().is_err());
+
+    let messages = dev.get_info_queue_messages()?;
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].contains("no render pipeline set"));
+
+    let names : Vec<&str> = cmd.get_commands().iter().map(|c| c.name()).collect();
+    assert_eq!(names, vec!["begin_render_pass", "draw_instanced"]);
+
+    cmd.end_render_pass();
+    cmd.close()?;
+    dev.execute(&cmd);
+    assert_eq!(tex.get_data()[0..4], [255, 0, 0, 255]);
+    Ok(())
+}
+
+#[test]
+fn null_copy_buffer_region() -> Result<(), hotline_rs::Error> {
+    let mut dev = common::create_null_device(4);
+    let src = dev.create_buffer(&buffer_info(gfx::BufferUsage::BUFFER_ONLY, 4), Some(&[1u32, 2, 3, 4]))?;
+    let dst = dev.create_buffer::<u32>(&buffer_info(gfx::BufferUsage::BUFFER_ONLY, 4), None)?;
+    let mut cmd = dev.create_cmd_buf(1);
+    cmd.copy_buffer_region(&dst, 0, &src, 8, 8);
+    cmd.close()?;
+    dev.execute(&cmd);
+    assert_eq!(dst.get_data()[0..8], [3, 0, 0, 0, 4, 0, 0, 0]);
+    assert!(dev.get_info_queue_messages()?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn null_app_scripted_input() -> Result<(), hotline_rs::Error> {
+    let mut app = common::create_null_app("null_app_scripted_input");
+    let mut win = app.create_window(os::WindowInfo::default());
+
+    let script : Vec<os::null::InputFrame> = serde_json::from_str(r#"[
+        {"frame": 0, "events": [{"type": "MouseMove", "x": 110, "y": 120}, {"type": "Key", "code": 87, "down": true}]},
+        {"frame": 1, "events": [{"type": "MouseMove", "x": 115, "y": 100}, {"type": "Text", "text": "hi"}]},
+        {"frame": 2, "events": [{"type": "Resize", "width": 640, "height": 480}, {"type": "Exit", "code": 0}]}
+    ]"#)?;
+    app.set_input_script(script);
+
+    // frame 0: key goes down and is pressed
+    assert!(app.run());
+    win.update(&mut app);
+    assert!(app.get_keys_down()[87]);
+    assert!(app.get_keys_pressed()[87]);
+    assert_eq!(win.get_mouse_client_pos(app.get_mouse_pos()).x, 10);
+    assert!(win.is_mouse_hovered());
+
+    // frame 1: key remains down but press is debounced
+    assert!(app.run());
+    win.update(&mut app);
+    assert!(app.get_keys_down()[87]);
+    assert!(!app.get_keys_pressed()[87]);
+    assert_eq!(app.get_mouse_pos_delta().x, 5);
+    assert_eq!(app.get_mouse_pos_delta().y, -20);
+    assert_eq!(app.get_utf16_input(), "hi".encode_utf16().collect::<Vec<u16>>());
+
+    // frame 2: resize is delivered to the window and exit ends the run loop
+    assert!(!app.run());
+    win.update(&mut app);
+    assert!(win.get_events().contains(os::WindowEventFlags::SIZE));
+    assert_eq!(win.get_size().x, 640);
+    assert!(app.get_utf16_input().is_empty());
+    Ok(())
+}
+
+#[test]
+fn null_swap_chain_read_back() -> Result<(), hotline_rs::Error> {
+    let mut app = common::create_null_app("null_swap_chain_read_back");
+    let win = app.create_window(os::WindowInfo {
+        rect: os::Rect { x: 0, y: 0, width: 8, height: 8 },
+        ..Default::default()
+    });
+    let mut dev = common::create_null_device(4);
+    let mut swap_chain = dev.create_swap_chain::<os::null::App>(&gfx::SwapChainInfo {
+        num_buffers: 2,
+        format: gfx::Format::RGBA8n,
+        clear_colour: Some(gfx::ClearColour { r: 0.0, g: 1.0, b: 0.0, a: 1.0 }),
+    }, &win)?;
+    let mut cmd = dev.create_cmd_buf(2);
+
+    cmd.reset(&swap_chain);
+    cmd.begin_render_pass(swap_chain.get_backbuffer_pass());
+    cmd.end_render_pass();
+    let rr = cmd.read_back_backbuffer(&swap_chain)?;
+    cmd.close()?;
+    dev.execute(&cmd);
+    assert!(!rr.is_complete(&swap_chain));
+
+    swap_chain.swap(&mut dev);
+    assert!(rr.is_complete(&swap_chain));
+    let data = rr.map(&gfx::MapInfo { subresource: 0, read_start: 0, read_end: usize::MAX })?;
+    assert_eq!(data.size, 8 * 8 * 4);
+    assert_eq!(data.data[0..4], [0, 255, 0, 255]);
+    Ok(())
+}
+
+/// Rasterises a red triangle covering the lower left half in front of a blue quad into `size` x `size` colour and
+/// depth targets, with a front facing triangle culled
+fn rasterise_test_scene(dev: &mut null::Device, size: u64) -> Result<(null::Texture, null::Texture), hotline_rs::Error> {
+    let rt = dev.create_texture::<u8>(&gfx::TextureInfo {
+        format: gfx::Format::RGBA8n,
+        width: size,
+        height: size,
+        usage: gfx::TextureUsage::RENDER_TARGET,
+        ..Default::default()
+    }, None)?;
+    let ds = dev.create_texture::<u8>(&gfx::TextureInfo {
+        format: gfx::Format::D32f,
+        width: size,
+        height: size,
+        usage: gfx::TextureUsage::DEPTH_STENCIL,
+        ..Default::default()
+    }, None)?;
+    let pass = dev.create_render_pass(&gfx::RenderPassInfo {
+        render_targets: vec![&rt],
+        rt_clear: Some(gfx::ClearColour { r: 0.0, g: 0.0, b: 0.0, a: 1.0 }),
+        depth_stencil: Some(&ds),
+        ds_clear: Some(gfx::ClearDepthStencil { depth: Some(1.0), stencil: None }),
+        resolve: false,
+        discard: false,
+        array_slice: 0
+    })?;
+
+    // imdraw vs_3d layout: float3 position, float4 colour
+    let input_layout = vec![
+        gfx::InputElementInfo {
+            semantic: String::from("POSITION"),
+            index: 0,
+            format: gfx::Format::RGB32f,
+            input_slot: 0,
+            aligned_byte_offset: 0,
+            input_slot_class: gfx::InputSlotClass::PerVertex,
+            step_rate: 0,
+        },
+        gfx::InputElementInfo {
+            semantic: String::from("TEXCOORD"),
+            index: 0,
+            format: gfx::Format::RGBA32f,
+            input_slot: 0,
+            aligned_byte_offset: 12,
+            input_slot_class: gfx::InputSlotClass::PerVertex,
+            step_rate: 0,
+        }
+    ];
+
+    let vs = dev.create_shader(&gfx::ShaderInfo { shader_type: gfx::ShaderType::Vertex, compile_info: None }, &[0u8])?;
+    let fs = dev.create_shader(&gfx::ShaderInfo { shader_type: gfx::ShaderType::Fragment, compile_info: None }, &[0u8])?;
+    let create_pipeline = |dev: &null::Device, cull_mode: gfx::CullMode| {
+        dev.create_render_pipeline(&gfx::RenderPipelineInfo {
+            vs: Some(&vs),
+            fs: Some(&fs),
+            input_layout: input_layout.clone(),
+            pipeline_layout: gfx::PipelineLayout {
+                push_constants: Some(vec![gfx::PushConstantInfo {
+                    visibility: gfx::ShaderVisibility::Vertex,
+                    shader_register: 0,
+                    register_space: 0,
+                    num_values: 16
+                }]),
+                ..Default::default()
+            },
+            raster_info: gfx::RasterInfo {
+                cull_mode,
+                ..Default::default()
+            },
+            depth_stencil_info: gfx::DepthStencilInfo {
+                depth_enabled: true,
+                depth_write_mask: gfx::DepthWriteMask::All,
+                depth_func: gfx::ComparisonFunc::Less,
+                ..Default::default()
+            },
+            blend_info: gfx::BlendInfo {
+                alpha_to_coverage_enabled: false,
+                independent_blend_enabled: false,
+                render_target: vec![gfx::RenderTargetBlendInfo::default()]
+            },
+            topology: gfx::Topology::TriangleList,
+            patch_index: 0,
+            sample_mask: u32::MAX,
+            pass: Some(&pass),
+        })
+    };
+    let pipeline = create_pipeline(dev, gfx::CullMode::Back)?;
+    let pipeline_cull_front = create_pipeline(dev, gfx::CullMode::Front)?;
+
+    // red triangle covering the lower left half at z 0.5, then a blue quad behind it at z 0.8
+    let red = [1.0f32, 0.0, 0.0, 1.0];
+    let blue = [0.0f32, 0.0, 1.0, 1.0];
+    let mut vertices = Vec::new();
+    for (pos, col) in [
+        ([-1.0f32, -1.0, 0.5], red), ([-1.0, 1.0, 0.5], red), ([1.0, -1.0, 0.5], red),
+        ([-1.0, 1.0, 0.8], blue), ([1.0, 1.0, 0.8], blue), ([1.0, -1.0, 0.8], blue),
+        ([-1.0, 1.0, 0.8], blue), ([1.0, -1.0, 0.8], blue), ([-1.0, -1.0, 0.8], blue),
+    ] {
+        vertices.extend_from_slice(&pos);
+        vertices.extend_from_slice(&col);
+    }
+    let vb = dev.create_buffer(&gfx::BufferInfo {
+        usage: gfx::BufferUsage::VERTEX,
+        cpu_access: gfx::CpuAccessFlags::NONE,
+        format: gfx::Format::Unknown,
+        stride: 28,
+        num_elements: 9,
+        initial_state: gfx::ResourceState::VertexConstantBuffer,
+    }, Some(&vertices))?;
+
+    let identity = [
+        1.0f32, 0.0, 0.0, 0.0,
+        0.0, 1.0, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0
+    ];
+
+    let mut cmd = dev.create_cmd_buf(1);
+    cmd.begin_render_pass(&pass);
+    cmd.set_render_pipeline(&pipeline_cull_front);
+    cmd.push_render_constants(&pipeline_cull_front, 0, 0, 16, 0, &identity);
+    cmd.set_vertex_buffer(&vb, 0);
+    cmd.draw_instanced(3, 1, 0, 0);
+    cmd.set_render_pipeline(&pipeline);
+    cmd.push_render_constants(&pipeline, 0, 0, 16, 0, &identity);
+    cmd.draw_instanced(3, 1, 0, 0);
+    cmd.draw_instanced(6, 1, 3, 0);
+    cmd.end_render_pass();
+    cmd.close()?;
+    dev.execute(&cmd);
+    assert!(dev.get_info_queue_messages()?.is_empty());
+    Ok((rt, ds))
+}
+
+#[test]
+fn null_rasterise_depth_test() -> Result<(), hotline_rs::Error> {
+    let mut dev = common::create_null_device(4);
+    let (rt, ds) = rasterise_test_scene(&mut dev, 8)?;
+
+    let texel = |x: usize, y: usize| {
+        let offset = (y * 8 + x) * 4;
+        rt.get_data()[offset..offset + 4].to_vec()
+    };
+    assert_eq!(texel(0, 7), vec![255, 0, 0, 255]);
+    assert_eq!(texel(2, 6), vec![255, 0, 0, 255]);
+    assert_eq!(texel(7, 0), vec![0, 0, 255, 255]);
+    assert_eq!(texel(6, 2), vec![0, 0, 255, 255]);
+
+    // D32f texels are `block_size_for_format` bytes apart
+    let depth_block_size = gfx::block_size_for_format(gfx::Format::D32f) as usize;
+    let depth = |x: usize, y: usize| {
+        let offset = (y * 8 + x) * depth_block_size;
+        let data = ds.get_data();
+        f32::from_ne_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+    };
+    assert_eq!(depth(0, 7), 0.5);
+    assert_eq!(depth(7, 0), 0.8);
+    Ok(())
+}
+
+#[test]
+fn null_rasterise_golden_image() -> Result<(), hotline_rs::Error> {
+    let mut dev = common::create_null_device(4);
+    let (rt, _) = rasterise_test_scene(&mut dev, 64)?;
+
+    // compare with the checked in reference, or bless with HOTLINE_BLESS=1
+    let name = "null_rasterise";
+    std::fs::create_dir_all(image_diff::TEST_OUTPUT_DIR)?;
+    hotline_rs::image::write_to_file(&format!("{}/{}.png", image_diff::TEST_OUTPUT_DIR, name), 64, 64, 4, &rt.get_data())?;
+    image_diff::check_golden_image(image_diff::TEST_OUTPUT_DIR, image_diff::REFERENCE_DIR, name, image_diff::bless_requested(), true)
+}
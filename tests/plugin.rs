@@ -0,0 +1,253 @@
+// tests for plugin manifests, dependencies, state serialisation and build diagnostics
+
+mod common;
+
+use hotline_rs::plugin;
+use hotline_rs::reloader;
+
+fn plugin_dependency_info(name: &str, dependencies: &[&str]) -> plugin::PluginDependencyInfo {
+    plugin::PluginDependencyInfo {
+        name: name.to_string(),
+        dependencies: dependencies.iter().map(|d| d.to_string()).collect()
+    }
+}
+
+#[test]
+fn plugin_dependency_order() -> Result<(), hotline_rs::Error> {
+    // loaded in the wrong order, dependencies must come first and independent plugins keep their order
+    let plugins = vec![
+        plugin_dependency_info("ecs", &["ecs_examples"]),
+        plugin_dependency_info("empty", &[]),
+        plugin_dependency_info("tools", &["ecs", "ecs_examples"]),
+        plugin_dependency_info("ecs_examples", &[]),
+    ];
+    let order = plugin::sort_plugin_dependencies(&plugins)?;
+    let names = order.iter().map(|i| plugins[*i].name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["ecs_examples", "ecs", "empty", "tools"]);
+
+    // cycles are reported with the path
+    let cycle = vec![
+        plugin_dependency_info("a", &["b"]),
+        plugin_dependency_info("b", &["c"]),
+        plugin_dependency_info("c", &["a"]),
+    ];
+    let err = plugin::sort_plugin_dependencies(&cycle).unwrap_err();
+    assert!(err.msg.contains("dependency cycle detected: a -> b -> c -> a"), "{}", err.msg);
+
+    // self dependency is a cycle too
+    let err = plugin::sort_plugin_dependencies(&[plugin_dependency_info("a", &["a"])]).unwrap_err();
+    assert!(err.msg.contains("a -> a"), "{}", err.msg);
+
+    // missing dependencies
+    let err = plugin::sort_plugin_dependencies(&[plugin_dependency_info("ecs", &["ecs_examples"])]).unwrap_err();
+    assert!(err.msg.contains("'ecs' depends on 'ecs_examples' which is not loaded"), "{}", err.msg);
+    Ok(())
+}
+
+#[test]
+fn plugin_dependency_resetup() -> Result<(), hotline_rs::Error> {
+    let plugins = vec![
+        plugin_dependency_info("ecs", &["ecs_examples"]),
+        plugin_dependency_info("ecs_examples", &[]),
+        plugin_dependency_info("empty", &[]),
+        plugin_dependency_info("ecs_tools", &["ecs"]),
+    ];
+
+    // independent plugins only re-setup themselves
+    assert_eq!(plugin::get_plugins_to_resetup(&plugins, &[false, false, true, false]), vec![false, false, true, false]);
+
+    // dependents re-setup with their dependencies
+    assert_eq!(plugin::get_plugins_to_resetup(&plugins, &[true, false, false, false]), vec![true, false, false, true]);
+
+    // dependencies are not setup again when a dependent changes
+    assert_eq!(plugin::get_plugins_to_resetup(&plugins, &[false, false, false, true]), vec![false, false, false, true]);
+
+    // a library without an instance re-setups the plugins which depend on it, and so their dependents
+    assert_eq!(plugin::get_plugins_to_resetup(&plugins, &[false, true, false, false]), vec![true, true, false, true]);
+
+    // nothing changed
+    assert_eq!(plugin::get_plugins_to_resetup(&plugins, &[false; 4]), vec![false; 4]);
+    Ok(())
+}
+
+#[test]
+fn plugin_glob_match() {
+    assert!(plugin::glob_match("src/**/*.rs", "src/lib.rs"));
+    assert!(plugin::glob_match("src/**/*.rs", "src/systems/draw/mesh.rs"));
+    assert!(plugin::glob_match("./src/*.rs", "src\\lib.rs"));
+    assert!(!plugin::glob_match("src/*.rs", "src/systems/mesh.rs"));
+    assert!(!plugin::glob_match("src/**/*.rs", "src/lib.rsx"));
+    assert!(!plugin::glob_match("src/**/*.rs", "data/lib.rs"));
+    assert!(plugin::glob_match("**/*.json", "config.json"));
+    assert!(plugin::glob_match("data/level_?.json", "data/level_1.json"));
+    assert!(!plugin::glob_match("data/level_?.json", "data/level_10.json"));
+    assert!(plugin::glob_match("Cargo.toml", "Cargo.toml"));
+}
+
+#[test]
+fn plugin_version_matches() -> Result<(), hotline_rs::Error> {
+    assert!(plugin::version_matches("0.3", "0.3.2")?);
+    assert!(plugin::version_matches("^0.3.1", "0.3.2")?);
+    assert!(!plugin::version_matches("0.3", "0.4.0")?);
+    assert!(!plugin::version_matches("^0.3.3", "0.3.2")?);
+    assert!(plugin::version_matches("1.2", "1.9.0")?);
+    assert!(!plugin::version_matches("1.2", "2.0.0")?);
+    assert!(plugin::version_matches("~1.2", "1.2.9")?);
+    assert!(!plugin::version_matches("~1.2", "1.3.0")?);
+    assert!(plugin::version_matches(">=0.3.1, <0.5", "0.4.7")?);
+    assert!(!plugin::version_matches(">=0.3.1, <0.5", "0.5.0")?);
+    assert!(plugin::version_matches("=0.3", "0.3.9")?);
+    assert!(!plugin::version_matches("=0.3.1", "0.3.2")?);
+    assert!(plugin::version_matches("*", "12.0.0")?);
+    assert!(plugin::version_matches("0.3", "0.3.2-beta+build")?);
+    assert!(plugin::version_matches("", "0.3.2")?);
+
+    let err = plugin::version_matches(">=zero", "0.3.2").unwrap_err();
+    assert!(err.msg.contains("invalid version requirement '>=zero'"), "{}", err.msg);
+    Ok(())
+}
+
+#[test]
+fn plugin_manifest() -> Result<(), hotline_rs::Error> {
+    let folder = common::test_folder("plugin_manifest")?;
+    let workspace = folder.join("workspace");
+    let plugin_dir = workspace.join("plugins").join("my_plugin");
+    std::fs::create_dir_all(plugin_dir.join("src").join("systems"))?;
+    std::fs::create_dir_all(plugin_dir.join("data"))?;
+    std::fs::write(workspace.join("Cargo.toml"), "[workspace]\nmembers = [\"plugins/my_plugin\"]\n")?;
+    std::fs::write(plugin_dir.join("Cargo.toml"), "[package]\nname = \"my-plugin\"\n")?;
+    std::fs::write(plugin_dir.join("src").join("lib.rs"), "")?;
+    std::fs::write(plugin_dir.join("src").join("systems").join("draw.rs"), "")?;
+    std::fs::write(plugin_dir.join("src").join("notes.txt"), "")?;
+    std::fs::write(plugin_dir.join("data").join("config.json"), "{}")?;
+
+    // defaults without a manifest
+    let manifest = plugin::PluginManifest::load(&plugin_dir)?;
+    let workspace = std::fs::canonicalize(&workspace)?;
+    assert_eq!(manifest.name, "my_plugin");
+    assert_eq!(manifest.get_package(), "my_plugin");
+    assert_eq!(manifest.get_profile(), hotline_rs::get_config_name());
+    assert_eq!(manifest.get_workspace_dir(), workspace);
+    assert_eq!(manifest.get_lib_dir(), workspace.join("target").join(hotline_rs::get_config_name()));
+    assert!(manifest.check_hotline_version().is_ok());
+    let files = manifest.get_watch_files();
+    assert_eq!(files.len(), 2);
+    assert!(files.iter().any(|f| f.ends_with("lib.rs")));
+    assert!(files.iter().any(|f| f.ends_with("draw.rs")));
+
+    // loading from a file inside the plugin directory, or a directory containing `plugins/<name>`
+    assert_eq!(plugin::PluginManifest::load(plugin_dir.join("Cargo.toml"))?, manifest);
+    assert_eq!(plugin::resolve_plugin_dir(workspace.to_str().unwrap(), "my_plugin", "."), workspace.join(".").join("plugins").join("my_plugin"));
+    assert_eq!(plugin::resolve_plugin_dir(workspace.to_str().unwrap(), "my_plugin", "plugins/my_plugin"), workspace.join("plugins/my_plugin"));
+
+    // with a manifest
+    std::fs::write(plugin_dir.join(plugin::MANIFEST_FILE), r#"{
+        "name": "custom",
+        "version": "1.0.0",
+        "package": "my-plugin",
+        "profile": "dev",
+        "workspace": "../..",
+        "target_dir": "../../build",
+        "sources": ["src/*.rs"],
+        "data": ["data/**/*.json"],
+        "dependencies": ["ecs"],
+        "hotline_version": ">=0.1"
+    }"#)?;
+    let manifest = plugin::PluginManifest::load(&plugin_dir)?;
+    assert_eq!(manifest.name, "custom");
+    assert_eq!(manifest.version, Some("1.0.0".to_string()));
+    assert_eq!(manifest.get_package(), "my-plugin");
+    assert_eq!(manifest.get_lib_name(), "my_plugin");
+    assert_eq!(manifest.dependencies, vec!["ecs".to_string()]);
+    assert_eq!(manifest.get_lib_dir(), std::fs::canonicalize(&plugin_dir)?.join("../../build").join("debug"));
+    assert!(manifest.get_lib_filepath().file_name().unwrap().to_str().unwrap().contains("my_plugin"));
+    assert!(manifest.check_hotline_version().is_ok());
+    let files = manifest.get_watch_files();
+    assert_eq!(files.len(), 3);
+    assert!(files.iter().any(|f| f.ends_with("lib.rs")));
+    assert!(files.iter().any(|f| f.ends_with("config.json")));
+    assert!(files.iter().any(|f| f.ends_with(plugin::MANIFEST_FILE)));
+
+    // incompatible hotline version and parse errors
+    std::fs::write(plugin_dir.join(plugin::MANIFEST_FILE), r#"{ "hotline_version": "99.0" }"#)?;
+    let err = plugin::PluginManifest::load(&plugin_dir)?.check_hotline_version().unwrap_err();
+    assert!(err.msg.contains("'my_plugin' requires hotline version '99.0'"), "{}", err.msg);
+
+    std::fs::write(plugin_dir.join(plugin::MANIFEST_FILE), r#"{ "sources": "src" }"#)?;
+    let err = plugin::PluginManifest::load(&plugin_dir).unwrap_err();
+    assert!(err.msg.contains("failed to parse manifest"), "{}", err.msg);
+
+    let err = plugin::PluginManifest::load(folder.join("missing")).unwrap_err();
+    assert!(err.msg.contains("plugin directory not found"), "{}", err.msg);
+    Ok(())
+}
+
+#[test]
+fn plugin_state_serialisation() -> Result<(), hotline_rs::Error> {
+    #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+    struct State {
+        active_demo: String,
+        frame: u64
+    }
+
+    let state = State {
+        active_demo: "draw_indexed".to_string(),
+        frame: 42
+    };
+
+    // round trip, including the tuple layout generated by `export_plugin_state`
+    let data = plugin::serialise_state(&state);
+    assert!(!data.is_empty());
+    assert_eq!(plugin::deserialise_state::<State>(&data)?, state);
+
+    let data = plugin::serialise_state(&(&state.active_demo, &state.frame,));
+    let (active_demo, frame,) : (String, u64,) = plugin::deserialise_state(&data)?;
+    assert_eq!(active_demo, state.active_demo);
+    assert_eq!(frame, state.frame);
+
+    // incompatible layouts are errors rather than panics
+    let err = plugin::deserialise_state::<State>(b"[1, 2]").unwrap_err();
+    assert!(err.msg.contains("failed to deserialise plugin state"), "{}", err.msg);
+    assert!(plugin::deserialise_state::<State>(&[]).is_err());
+    Ok(())
+}
+
+#[test]
+fn plugin_parse_cargo_diagnostics() {
+    let output = [
+        r#"{"reason":"compiler-artifact","package_id":"ecs 0.1.0","target":{"name":"ecs"},"fresh":true}"#,
+        r#"{"reason":"compiler-message","package_id":"ecs 0.1.0","message":{"message":"unused variable: `x`","code":{"code":"unused_variables"},"level":"warning","spans":[{"file_name":"plugins/ecs/src/lib.rs","byte_start":10,"byte_end":11,"line_start":12,"line_end":12,"column_start":9,"column_end":10,"is_primary":true,"text":[],"label":null}],"children":[],"rendered":"warning: unused variable: `x`\n"}}"#,
+        r#"{"reason":"compiler-message","package_id":"ecs 0.1.0","message":{"message":"mismatched types","code":{"code":"E0308"},"level":"error","spans":[{"file_name":"plugins/ecs/src/other.rs","byte_start":0,"byte_end":4,"line_start":3,"line_end":3,"column_start":1,"column_end":5,"is_primary":false,"text":[],"label":"expected due to this"},{"file_name":"plugins/ecs/src/lib.rs","byte_start":20,"byte_end":25,"line_start":40,"line_end":41,"column_start":17,"column_end":2,"is_primary":true,"text":[],"label":null}],"children":[],"rendered":"error[E0308]: mismatched types\n"}}"#,
+        r#"{"reason":"compiler-message","package_id":"ecs 0.1.0","message":{"message":"mismatched types","code":{"code":"E0308"},"level":"error","spans":[{"file_name":"plugins/ecs/src/lib.rs","byte_start":20,"byte_end":25,"line_start":40,"line_end":41,"column_start":17,"column_end":2,"is_primary":true,"text":[],"label":null}],"children":[],"rendered":"error[E0308]: mismatched types\n"}}"#,
+        r#"{"reason":"compiler-message","package_id":"ecs 0.1.0","message":{"message":"linking with `cc` failed: 100%","code":null,"level":"error","spans":[],"children":[],"rendered":null}}"#,
+        r#"{"reason":"compiler-message","package_id":"ecs 0.1.0","message":{"message":"aborting due to 1 previous error","code":null,"level":"error","spans":[],"children":[],"rendered":"error: aborting due to 1 previous error\n"}}"#,
+        r#"{"reason":"compiler-message","package_id":"ecs 0.1.0","message":{"message":"1 warning emitted","code":null,"level":"warning","spans":[],"children":[],"rendered":"warning: 1 warning emitted\n"}}"#,
+        r#"{"reason":"build-finished","success":false}"#,
+        "   Compiling ecs v0.1.0",
+        "{ not json",
+    ].join("\n");
+
+    let diagnostics = plugin::parse_cargo_diagnostics(&output);
+    assert_eq!(diagnostics.len(), 3);
+
+    assert_eq!(diagnostics[0].level, reloader::DiagnosticLevel::Warning);
+    assert_eq!(diagnostics[0].message, "unused variable: `x`");
+    assert_eq!(diagnostics[0].file, Some("plugins/ecs/src/lib.rs".to_string()));
+    assert_eq!((diagnostics[0].line, diagnostics[0].column), (12, 9));
+    assert_eq!(diagnostics[0].rendered, Some("warning: unused variable: `x`\n".to_string()));
+    assert_eq!(diagnostics[0].to_string(), "plugins/ecs/src/lib.rs:12:9: warning: unused variable: `x`");
+
+    // the primary span is used for the location and duplicates are removed
+    assert_eq!(diagnostics[1].level, reloader::DiagnosticLevel::Error);
+    assert_eq!(diagnostics[1].file, Some("plugins/ecs/src/lib.rs".to_string()));
+    assert_eq!(
+        (diagnostics[1].line, diagnostics[1].column, diagnostics[1].line_end, diagnostics[1].column_end),
+        (40, 17, 41, 2)
+    );
+
+    // diagnostics without a location
+    assert_eq!(diagnostics[2].file, None);
+    assert_eq!(diagnostics[2].to_string(), "error: linking with `cc` failed: 100%");
+
+    assert!(plugin::parse_cargo_diagnostics("").is_empty());
+}
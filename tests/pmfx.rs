@@ -0,0 +1,277 @@
+// tests for pmfx loading, render graph validation, export and hot reloading
+
+mod common;
+
+use hotline_rs::gfx;
+use hotline_rs::gfx::null;
+use hotline_rs::pmfx;
+use hotline_rs::pmfx::{GraphDiagnosticKind, GraphDiagnosticSeverity, RenderGraphNodeKind};
+use hotline_rs::shader_reflection::ReflectionErrorKind;
+use hotline_rs::gfx::{Device, Texture};
+
+
+#[test]
+fn pmfx_validate_render_graph() -> Result<(), hotline_rs::Error> {
+    let (mut dev, mut pmfx, _) = common::load_test_pmfx("pmfx_validate", common::TEST_PMFX)?;
+
+    let kinds = |graph: &str| {
+        pmfx.validate_render_graph(graph).into_iter().map(|d| d.kind).collect::<Vec<_>>()
+    };
+
+    assert_eq!(kinds("valid"), vec![GraphDiagnosticKind::UnusedTexture("unused".to_string())]);
+    assert_eq!(kinds("nope"), vec![GraphDiagnosticKind::MissingRenderGraph("nope".to_string())]);
+
+    let invalid = pmfx.validate_render_graph("invalid");
+    let kinds = invalid.iter().map(|d| d.kind.clone()).collect::<Vec<_>>();
+    assert!(kinds.contains(&GraphDiagnosticKind::DependencyCycle(vec!["a".to_string(), "b".to_string()])));
+    assert!(kinds.contains(&GraphDiagnosticKind::MissingView("no_view".to_string())));
+    assert!(kinds.contains(&GraphDiagnosticKind::MissingPipeline("no_pipeline".to_string())));
+    assert!(kinds.contains(&GraphDiagnosticKind::PipelineTypeMismatch("blur".to_string())));
+    assert!(kinds.contains(&GraphDiagnosticKind::FormatMismatch("depth".to_string())));
+    assert!(kinds.contains(&GraphDiagnosticKind::ReadBeforeWrite("colour".to_string())));
+    assert!(invalid.iter().any(|d| d.severity == GraphDiagnosticSeverity::Error && d.pass.as_deref() == Some("early_read")));
+
+    // a cycle is an error rather than a hang when building
+    assert!(pmfx.create_render_graph(&mut dev, "invalid").is_err());
+    Ok(())
+}
+
+#[test]
+fn pmfx_export_render_graph() -> Result<(), hotline_rs::Error> {
+    let (mut dev, mut pmfx, _) = common::load_test_pmfx("pmfx_export", common::TEST_PMFX)?;
+    pmfx.create_render_graph(&mut dev, "export")?;
+
+    let export = pmfx.export_render_graph();
+    assert_eq!(export.name, "export");
+    assert_eq!(export.nodes.len(), pmfx.get_render_graph_execute_order().len());
+
+    let position = |name: &str| export.nodes.iter().position(|n| n.name == name).unwrap();
+    assert!(position("meshes") < position("blur"));
+    assert_eq!(export.nodes[position("blur")].depends_on, vec!["meshes".to_string()]);
+
+    // colour starts as a shader resource, is transitioned for rendering and back again before blur reads it
+    let colour_transitions = export.nodes.iter().enumerate().filter(|(_, n)| {
+        n.kind == RenderGraphNodeKind::Transition && n.texture.as_deref() == Some("colour")
+    }).collect::<Vec<_>>();
+    assert!(colour_transitions.len() >= 2);
+    assert_eq!(colour_transitions[0].1.state_after, Some(gfx::ResourceState::RenderTarget));
+    assert!(colour_transitions[0].0 < position("meshes"));
+    assert_eq!(colour_transitions[1].1.state_after, Some(gfx::ResourceState::ShaderResource));
+    assert!(colour_transitions[1].0 < position("blur"));
+
+    let dot = export.to_dot();
+    assert!(dot.starts_with("digraph \"export\""));
+    assert!(dot.contains("\"meshes\" -> \"blur\";"));
+
+    let json: pmfx::RenderGraphExport = serde_json::from_str(&export.to_json()?)?;
+    assert_eq!(json.nodes.len(), export.nodes.len());
+    Ok(())
+}
+
+#[test]
+fn pmfx_async_compute() -> Result<(), hotline_rs::Error> {
+    let (mut dev, mut pmfx, _) = common::load_test_pmfx("pmfx_async", common::TEST_PMFX)?;
+    pmfx.create_render_graph(&mut dev, "async")?;
+
+    assert_eq!(pmfx.get_render_graph_pass_order("async"), vec!["meshes", "blur", "debug", "present"]);
+
+    // debug does not touch blur's resources so it runs alongside, the transition of blurred for present waits
+    let export = pmfx.export_render_graph();
+    let node = |name: &str| export.nodes.iter().find(|n| n.name == name).unwrap();
+    assert_eq!(node("blur").queue, gfx::QueueType::Compute);
+    assert_eq!(node("meshes").queue, gfx::QueueType::Graphics);
+    assert!(node("debug").waits_on.is_empty());
+    let waits = export.nodes.iter().filter(|n| !n.waits_on.is_empty()).collect::<Vec<_>>();
+    assert_eq!(waits.len(), 1);
+    assert_eq!(waits[0].waits_on, vec!["blur".to_string()]);
+    assert_eq!(waits[0].texture.as_deref(), Some("blurred"));
+    assert!(export.to_dot().contains("\"blur\" -> \"barrier_present-blurred (ShaderResource)\" [style=bold"));
+
+    pmfx.execute(&mut dev);
+
+    // graphics work is flushed before blur executes on the compute queue, then graphics waits once before blurred is used
+    let ops = dev.get_queue_ops();
+    let position = |op: &dyn Fn(&null::QueueOp) -> bool| ops.iter().position(op).unwrap();
+    let compute_execute = position(&|o| *o == null::QueueOp::Execute(gfx::QueueType::Compute));
+    let compute_wait = position(&|o| matches!(o, null::QueueOp::Wait { queue: gfx::QueueType::Compute, value: 1, .. }));
+    let compute_signal = position(&|o| matches!(o, null::QueueOp::Signal { queue: gfx::QueueType::Compute, value: 1, .. }));
+    let graphics_wait = position(&|o| matches!(o, null::QueueOp::Wait { queue: gfx::QueueType::Graphics, value: 1, .. }));
+    assert!(compute_wait < compute_execute && compute_execute < compute_signal && compute_signal < graphics_wait);
+    assert_eq!(ops.iter().filter(|o| matches!(o, null::QueueOp::Wait { queue: gfx::QueueType::Graphics, .. })).count(), 1);
+    assert_eq!(ops.iter().filter(|o| **o == null::QueueOp::Execute(gfx::QueueType::Compute)).count(), 1);
+    assert!(!dev.get_info_queue_messages()?.iter().any(|msg| msg.contains("[error]")));
+    Ok(())
+}
+
+#[test]
+fn pmfx_transient_texture_aliasing() -> Result<(), hotline_rs::Error> {
+    let (mut dev, mut pmfx, _) = common::load_test_pmfx("pmfx_transient", common::TEST_PMFX)?;
+    pmfx.create_render_graph(&mut dev, "transient")?;
+
+    assert_eq!(pmfx.get_render_graph_pass_order("transient"), vec!["pass_a", "pass_b", "pass_c"]);
+
+    // a is last used by pass_b so c can share it, b is used at the same time as both
+    let a = pmfx.get_texture("transient_a").unwrap();
+    let b = pmfx.get_texture("transient_b").unwrap();
+    let c = pmfx.get_texture("transient_c").unwrap();
+    assert!(std::ptr::eq(a, c));
+    assert!(!std::ptr::eq(a, b));
+
+    let stats = pmfx.get_transient_texture_stats();
+    let size = gfx::size_for_format(gfx::Format::RGBA8n, 8, 8, 1);
+    assert_eq!(stats.transient_textures, 3);
+    assert_eq!(stats.physical_textures, 2);
+    assert_eq!(stats.transient_bytes, size * 3);
+    assert_eq!(stats.saved_bytes, size);
+
+    // the shared texture is transitioned to a render target for pass_c after being read by pass_b
+    let export = pmfx.export_render_graph();
+    let position = |name: &str| export.nodes.iter().position(|n| n.name == name).unwrap();
+    let c_target = export.nodes.iter().position(|n| {
+        n.kind == RenderGraphNodeKind::Transition &&
+        n.texture.as_deref() == Some("transient_c") &&
+        n.state_before == Some(gfx::ResourceState::ShaderResource) &&
+        n.state_after == Some(gfx::ResourceState::RenderTarget)
+    }).unwrap();
+    assert!(position("pass_b") < c_target && c_target < position("pass_c"));
+    Ok(())
+}
+
+#[test]
+fn pmfx_reload_changed_files() -> Result<(), hotline_rs::Error> {
+    let folder = common::test_folder("pmfx_reload_changed_files")?;
+    let path = |p: &std::path::Path| p.to_str().unwrap().to_string();
+
+    // two pmfx files each with their own shader source dependency
+    let dep_a = folder.join("a.hlsl");
+    let dep_b = folder.join("b.hlsl");
+    std::fs::write(&dep_a, "")?;
+    std::fs::write(&dep_b, "")?;
+    let pmfx_a = common::TEST_PMFX.replace("\"dependencies\": []", &format!("\"dependencies\": [{:?}]", path(&dep_a)));
+    let pmfx_b = |hash: u64| format!(r#"{{
+        "shaders": {{}}, "pipelines": {{}}, "depth_stencil_states": {{}}, "raster_states": {{}}, "blend_states": {{}},
+        "render_target_blend_states": {{}}, "views": {{}}, "render_graphs": {{}},
+        "textures": {{
+            "b_texture": {{"width": 16, "height": 16, "depth": 1, "mip_levels": 1, "array_layers": 1, "samples": 1, "cubemap": false,
+                "format": "RGBA8n", "usage": ["ShaderResource"], "hash": {}}}
+        }},
+        "dependencies": [{:?}]
+    }}"#, hash, path(&dep_b));
+
+    let folder_a = folder.join("pmfx_reload_a");
+    let folder_b = folder.join("pmfx_reload_b");
+    std::fs::create_dir_all(&folder_a)?;
+    std::fs::create_dir_all(&folder_b)?;
+    let file_a = folder_a.join("pmfx_reload_a.json");
+    let file_b = folder_b.join("pmfx_reload_b.json");
+    std::fs::write(&file_a, &pmfx_a)?;
+    std::fs::write(&file_b, pmfx_b(1))?;
+
+    let mut dev = common::create_null_device(16);
+    let mut pmfx = pmfx::Pmfx::<null::Device>::create(&mut dev, 16);
+    pmfx.load(folder_a.to_str().unwrap())?;
+    pmfx.load(folder_b.to_str().unwrap())?;
+    pmfx.create_render_graph(&mut dev, "export")?;
+
+    // changed files map back to the pmfx which depend on them
+    assert_eq!(pmfx.get_pmfx_affected_by(&[path(&dep_a)]), vec!["pmfx_reload_a".to_string()]);
+    assert_eq!(pmfx.get_pmfx_affected_by(&[path(&dep_b)]), vec!["pmfx_reload_b".to_string()]);
+    assert_eq!(pmfx.get_pmfx_affected_by(&[path(&file_b)]), vec!["pmfx_reload_b".to_string()]);
+    assert!(pmfx.get_pmfx_affected_by(&[path(&folder.join("other.hlsl"))]).is_empty());
+
+    // only the pmfx affected by the change is reloaded, even though b has also changed on disk
+    std::fs::write(&file_a, pmfx_a.replace("\"hash\": 3}", "\"hash\": 33}"))?;
+    std::fs::write(&file_b, pmfx_b(2))?;
+    common::touch_file(&file_b, 10)?;
+    let info = pmfx.reload(&mut dev, &[path(&dep_a)])?;
+    assert_eq!(info.pmfx, vec!["pmfx_reload_a".to_string()]);
+    assert_eq!(info.textures, vec!["colour".to_string()]);
+    assert!(!info.views.is_empty());
+
+    // without a change set files are checked by modified time
+    let info = pmfx.reload(&mut dev, &[])?;
+    assert_eq!(info.pmfx, vec!["pmfx_reload_b".to_string()]);
+    assert!(info.textures.is_empty() || info.textures == vec!["b_texture".to_string()]);
+    assert!(pmfx.reload(&mut dev, &[])?.pmfx.is_empty());
+    Ok(())
+}
+
+#[test]
+fn pmfx_reload_texture_file() -> Result<(), hotline_rs::Error> {
+    let folder = common::test_folder("pmfx_reload_texture_file")?;
+    std::fs::create_dir_all(folder.join("pmfx_texture_file"))?;
+    let path = |p: &std::path::Path| p.to_str().unwrap().to_string();
+
+    // a pmfx with a single texture loaded from an image file
+    let image_path = folder.join("albedo.png");
+    hotline_rs::image::write_to_file(&path(&image_path), 2, 2, 4, &[255u8; 16])?;
+    let pmfx_json = format!(r#"{{
+        "shaders": {{}}, "pipelines": {{}}, "depth_stencil_states": {{}}, "raster_states": {{}}, "blend_states": {{}},
+        "render_target_blend_states": {{}}, "views": {{}}, "render_graphs": {{}}, "dependencies": [],
+        "textures": {{
+            "albedo": {{"filepath": {:?}, "src_data": true, "width": 2, "height": 2, "depth": 1, "mip_levels": 1,
+                "array_layers": 1, "samples": 1, "cubemap": false, "format": "RGBA8n", "usage": ["ShaderResource"], "hash": 1}}
+        }}
+    }}"#, path(&image_path));
+    std::fs::write(folder.join("pmfx_texture_file").join("pmfx_texture_file.json"), pmfx_json)?;
+
+    let mut dev = common::create_null_device(16);
+    let mut pmfx = pmfx::Pmfx::<null::Device>::create(&mut dev, 16);
+    pmfx.load(&path(&folder.join("pmfx_texture_file")))?;
+    pmfx.create_texture(&mut dev, "albedo")?;
+    let srv_index = pmfx.get_texture("albedo").unwrap().get_srv_index();
+    assert!(srv_index.is_some());
+    assert_eq!(pmfx.get_texture_2d_size("albedo"), Some((2, 2)));
+
+    // modifying the image reloads the texture into the same shader heap slot
+    hotline_rs::image::write_to_file(&path(&image_path), 4, 4, 4, &[128u8; 64])?;
+    common::touch_file(&image_path, 10)?;
+    let info = pmfx.reload(&mut dev, &[path(&image_path)])?;
+    assert!(info.pmfx.is_empty());
+    assert_eq!(info.textures, vec!["albedo".to_string()]);
+    assert_eq!(pmfx.get_texture_2d_size("albedo"), Some((4, 4)));
+    assert_eq!(pmfx.get_texture("albedo").unwrap().get_srv_index(), srv_index);
+
+    // without a change set the image is checked by modified time
+    assert!(pmfx.reload(&mut dev, &[])?.textures.is_empty());
+    hotline_rs::image::write_to_file(&path(&image_path), 2, 2, 4, &[64u8; 16])?;
+    common::touch_file(&image_path, 20)?;
+    assert_eq!(pmfx.reload(&mut dev, &[])?.textures, vec!["albedo".to_string()]);
+    assert_eq!(pmfx.get_texture_2d_size("albedo"), Some((2, 2)));
+    assert_eq!(pmfx.get_texture("albedo").unwrap().get_srv_index(), srv_index);
+
+    // an image which fails to load keeps the previous texture
+    std::fs::write(&image_path, "not an image")?;
+    common::touch_file(&image_path, 30)?;
+    assert!(pmfx.reload(&mut dev, &[path(&image_path)])?.textures.is_empty());
+    assert_eq!(pmfx.get_texture_2d_size("albedo"), Some((2, 2)));
+    assert_eq!(pmfx.get_texture("albedo").unwrap().get_srv_index(), srv_index);
+    Ok(())
+}
+
+#[test]
+fn pmfx_validate_pipeline_reflection() -> Result<(), hotline_rs::Error> {
+    let (dev, mut pmfx, folder) = common::load_test_pmfx("pmfx_reflection", common::TEST_PMFX)?;
+
+    // shaders without a sidecar are reported, but do not prevent the pipeline being created
+    let _ = std::fs::remove_file(folder.join("blur.csc.json"));
+    let errors = pmfx.validate_pipeline("blur")?;
+    assert_eq!(common::reflection_error_kinds(&errors), vec![ReflectionErrorKind::MissingReflection("blur.csc".to_string())]);
+    assert!(errors[0].message.contains("blur.csc.json"));
+    assert!(pmfx.validate_pipeline("missing").is_err());
+
+    // the blur pipeline has an empty layout so the output texture can not be bound
+    std::fs::write(folder.join("blur.csc.json"), r#"{
+        "entry_point": "cs_blur",
+        "stage": "Compute",
+        "bindings": [{"name": "output", "shader_register": 0, "register_space": 0, "binding_type": "UnorderedAccess"}]
+    }"#)?;
+    let errors = pmfx.validate_pipeline("blur")?;
+    assert_eq!(common::reflection_error_kinds(&errors), vec![ReflectionErrorKind::MissingBinding("output".to_string())]);
+    assert_eq!(errors[0].shader, "blur.csc");
+
+    let err = pmfx.create_compute_pipeline(&dev, "blur").err().unwrap();
+    assert!(err.msg.contains("does not match shader reflection"));
+    assert!(err.msg.contains("blur.csc: UnorderedAccess binding `output`"));
+    Ok(())
+}
@@ -0,0 +1,105 @@
+// tests for obj parsing and loading
+
+mod common;
+
+use hotline_rs::primitives;
+
+use maths_rs::prelude::*;
+
+const TEST_OBJ: &str = r#"
+# quad with texcoords and normals, followed by a pentagon with no normals using negative indices
+mtllib test.mtl
+o quad
+v -1.0 -1.0 0.5
+v 1.0 -1.0 0.5
+v 1.0 1.0 0.5
+v -1.0 1.0 0.5
+vt 0.0 0.0
+vt 1.0 0.0
+vt 1.0 1.0
+vt 0.0 1.0
+vn 0.0 0.0 1.0
+usemtl red
+f 1/1/1 2/2/1 3/3/1
+f 1/1/1 3/3/1 4/4/1
+o pentagon
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 1.0 1.0 0.0
+v 0.5 1.5 0.0
+v 0.0 1.0 0.0
+usemtl blue
+f -5 -4 -3 -2 -1
+"#;
+
+const TEST_MTL: &str = r#"
+newmtl red
+Kd 1.0 0.0 0.0
+Ns 32
+map_Kd -s 1 1 1 red albedo.png
+newmtl blue
+Kd 0.0 0.0 1.0
+Tr 0.25
+bump blue_normal.png
+"#;
+
+#[test]
+fn primitives_parse_obj() -> Result<(), hotline_rs::Error> {
+    let obj = primitives::parse_obj(TEST_OBJ)?;
+    assert_eq!(obj.material_libs, vec!["test.mtl"]);
+    assert_eq!(obj.meshes.len(), 2);
+
+    // shared vertices are de-duplicated, mirrored into left handed space with reversed winding and flipped v
+    let quad = &obj.meshes[0];
+    assert_eq!(quad.name, "quad");
+    assert_eq!(quad.material.as_deref(), Some("red"));
+    assert_eq!(quad.vertices.len(), 4);
+    assert_eq!(quad.indices, vec![0, 2, 1, 0, 3, 2]);
+    assert!(common::approx_vec3(quad.vertices[2].position, vec3f(1.0, 1.0, -0.5)));
+    assert!(common::approx_vec3(quad.vertices[2].normal, vec3f(0.0, 0.0, -1.0)));
+    assert!(common::approx_vec3(quad.vertices[2].tangent, vec3f(1.0, 0.0, 0.0)));
+    assert!(common::approx_vec3(quad.vertices[2].bitangent, vec3f(0.0, -1.0, 0.0)));
+    assert_eq!(quad.vertices[2].texcoord.y, 0.0);
+
+    // n-gons are triangulated as fans and missing normals are generated
+    let pentagon = &obj.meshes[1];
+    assert_eq!(pentagon.name, "pentagon");
+    assert_eq!(pentagon.material.as_deref(), Some("blue"));
+    assert_eq!(pentagon.vertices.len(), 5);
+    assert_eq!(pentagon.indices, vec![0, 2, 1, 0, 3, 2, 0, 4, 3]);
+    assert!(pentagon.vertices.iter().all(|v| common::approx_vec3(v.normal, vec3f(0.0, 0.0, -1.0))));
+    assert!(pentagon.vertices.iter().all(|v| (dot(v.normal, v.tangent)).abs() < 0.0001));
+
+    // invalid indices and degenerate faces are errors
+    assert!(primitives::parse_obj("v 0 0 0\nf 1 2 3").is_err());
+    assert!(primitives::parse_obj("v 0 0 0\nv 1 0 0\nf 1 2").is_err());
+    assert!(primitives::parse_obj("v 0 0 0\nf 0 1 1").is_err());
+    Ok(())
+}
+
+#[test]
+fn primitives_load_obj() -> Result<(), hotline_rs::Error> {
+    let folder = common::test_folder("primitives_obj")?;
+    std::fs::write(folder.join("test.obj"), TEST_OBJ)?;
+    std::fs::write(folder.join("test.mtl"), TEST_MTL)?;
+
+    let obj = primitives::load_obj(folder.join("test.obj").to_str().unwrap())?;
+    assert_eq!(obj.materials.len(), 2);
+    let red = obj.get_material("red").unwrap();
+    assert!(common::approx_vec3(red.diffuse, vec3f(1.0, 0.0, 0.0)));
+    assert_eq!(red.shininess, 32.0);
+    assert_eq!(red.opacity, 1.0);
+    assert_eq!(red.diffuse_map.as_deref(), Some("albedo.png"));
+    let blue = obj.get_material("blue").unwrap();
+    assert_eq!(blue.opacity, 0.75);
+    assert_eq!(blue.normal_map.as_deref(), Some("blue_normal.png"));
+    assert!(obj.get_material("green").is_none());
+
+    let mut dev = common::create_null_device(16);
+    let meshes = primitives::create_obj_meshes(&mut dev, &obj);
+    assert_eq!(meshes.len(), 2);
+    assert_eq!(meshes[0].num_indices, 6);
+    assert_eq!(meshes[1].num_indices, 9);
+    assert_eq!(meshes[1].num_vertices, 5);
+    Ok(())
+}
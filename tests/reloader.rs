@@ -0,0 +1,169 @@
+// tests for file watchers and the reloader
+
+mod common;
+
+use hotline_rs::reloader;
+
+fn wait_for_changes(watcher: &mut dyn reloader::FileWatcher) -> Vec<String> {
+    let start = std::time::Instant::now();
+    while start.elapsed() < std::time::Duration::from_secs(5) {
+        let changed = watcher.wait(std::time::Duration::from_millis(20));
+        if !changed.is_empty() {
+            return changed;
+        }
+    }
+    Vec::new()
+}
+
+fn test_file_watcher(watcher: &mut dyn reloader::FileWatcher, name: &str) -> Result<(), hotline_rs::Error> {
+    let folder = common::test_folder(name)?;
+    std::fs::create_dir_all(folder.join("sub"))?;
+    let a = folder.join("a.txt");
+    let b = folder.join("sub").join("b.txt");
+    let c = folder.join("c.txt");
+    std::fs::write(&a, "a")?;
+    std::fs::write(&b, "b")?;
+    let path = |p: &std::path::Path| p.to_str().unwrap().to_string();
+
+    // no changes initially
+    watcher.watch(&[path(&a), path(&b)]);
+    assert!(watcher.wait(std::time::Duration::from_millis(20)).is_empty());
+
+    // modify a single file
+    std::fs::write(&b, "bb")?;
+    common::touch_file(&b, 10)?;
+    assert_eq!(wait_for_changes(watcher), vec![path(&b)]);
+
+    // files which are not watched are not reported
+    std::fs::write(folder.join("untracked.txt"), "x")?;
+    assert!(watcher.wait(std::time::Duration::from_millis(50)).is_empty());
+
+    // new files are reported once they are watched
+    std::fs::write(&c, "c")?;
+    common::touch_file(&c, 20)?;
+    watcher.watch(&[path(&a), path(&b), path(&c)]);
+    assert_eq!(wait_for_changes(watcher), vec![path(&c)]);
+
+    // files replaced by rename, as editors do when saving
+    let tmp = folder.join("a.txt.tmp");
+    std::fs::write(&tmp, "aa")?;
+    let file = std::fs::OpenOptions::new().append(true).open(&tmp)?;
+    file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(30))?;
+    drop(file);
+    std::fs::rename(&tmp, &a)?;
+    assert_eq!(wait_for_changes(watcher), vec![path(&a)]);
+    Ok(())
+}
+
+#[test]
+fn reloader_polling_watcher() -> Result<(), hotline_rs::Error> {
+    test_file_watcher(&mut reloader::PollingWatcher::new(), "reloader_polling_watcher")
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn reloader_inotify_watcher() -> Result<(), hotline_rs::Error> {
+    test_file_watcher(&mut reloader::InotifyWatcher::new()?, "reloader_inotify_watcher")
+}
+
+struct TestReloadResponder {
+    files: Vec<String>,
+    changed: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    builds: std::sync::Arc<std::sync::atomic::AtomicUsize>
+}
+
+impl reloader::ReloadResponder for TestReloadResponder {
+    fn add_file(&mut self, path: &str) {
+        self.files.push(path.to_string());
+    }
+
+    fn get_files(&self) -> Vec<String> {
+        self.files.to_vec()
+    }
+
+    fn get_last_mtime(&self) -> std::time::SystemTime {
+        std::time::SystemTime::now() + std::time::Duration::from_secs(5)
+    }
+
+    fn build(&mut self, changed_files: &[String]) -> std::process::ExitStatus {
+        self.changed.lock().unwrap().extend(changed_files.iter().cloned());
+        self.builds.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        std::process::ExitStatus::default()
+    }
+}
+
+#[test]
+fn reloader_debounce() -> Result<(), hotline_rs::Error> {
+    let folder = common::test_folder("reloader_debounce")?;
+    let files = (0..3).map(|i| folder.join(format!("{}.txt", i))).collect::<Vec<_>>();
+    for file in &files {
+        std::fs::write(file, "")?;
+    }
+
+    let changed = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let builds = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let responder = TestReloadResponder {
+        files: files.iter().map(|f| f.to_str().unwrap().to_string()).collect(),
+        changed: changed.clone(),
+        builds: builds.clone()
+    };
+    let mut reloader = reloader::Reloader::create_with_watcher(Box::new(responder), Box::new(reloader::PollingWatcher::new()));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert!(reloader.check_for_reload() == reloader::ReloadState::None);
+
+    // a burst of writes results in a single build
+    for (i, file) in files.iter().enumerate().take(2) {
+        std::fs::write(file, "changed")?;
+        common::touch_file(file, 60 + i as u64)?;
+    }
+
+    let start = std::time::Instant::now();
+    while reloader.check_for_reload() != reloader::ReloadState::Available {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5), "timed out waiting for reload");
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    // the changed files are passed to the build and kept until the reload is complete
+    let expected = files[..2].iter().map(|f| f.to_str().unwrap().to_string()).collect::<Vec<_>>();
+    let mut available = reloader.get_changed_files();
+    available.sort();
+    assert_eq!(available, expected);
+    reloader.complete_reload();
+    assert!(reloader.get_changed_files().is_empty());
+
+    assert_eq!(builds.load(std::sync::atomic::Ordering::SeqCst), 1);
+    let mut changed = changed.lock().unwrap().to_vec();
+    changed.sort();
+    assert_eq!(changed, expected);
+    Ok(())
+}
+
+/// A watcher for a file which is written continuously, every wait returns a change after a short delay
+struct ContinuousWatcher;
+
+impl reloader::FileWatcher for ContinuousWatcher {
+    fn watch(&mut self, _files: &[String]) {
+    }
+
+    fn wait(&mut self, timeout: std::time::Duration) -> Vec<String> {
+        std::thread::sleep(timeout / 4);
+        vec!["build.log".to_string()]
+    }
+}
+
+#[test]
+fn reloader_debounce_max_wait() {
+    let shutdown = std::sync::atomic::AtomicBool::new(false);
+    let debounce = std::time::Duration::from_millis(20);
+    let max_wait = std::time::Duration::from_millis(100);
+
+    // continuous changes stop being collected after the max wait
+    let start = std::time::Instant::now();
+    let changed = reloader::debounce_changes(&mut ContinuousWatcher, debounce, max_wait, &shutdown);
+    assert!(!changed.is_empty());
+    assert!(start.elapsed() < std::time::Duration::from_secs(1));
+
+    // shutdown stops collecting immediately
+    shutdown.store(true, std::sync::atomic::Ordering::Relaxed);
+    assert!(reloader::debounce_changes(&mut ContinuousWatcher, debounce, max_wait, &shutdown).is_empty());
+}
@@ -0,0 +1,175 @@
+// tests for saving, loading and reloading scenes
+
+mod common;
+
+use hotline_rs::scene;
+use hotline_rs::ecs_base;
+use hotline_rs::ecs_base::{Name, Parent, WorldMatrix};
+use hotline_rs::scene::{Scene, SceneFormat};
+
+use maths_rs::prelude::*;
+
+const TEST_SCENE: &str = r#"{
+    "version": 1,
+    "entities": [
+        {
+            "name": "light",
+            "light": {
+                "light_type": "Spot",
+                "direction": [0.0, -1.0, 0.0],
+                "cutoff": 0.4,
+                "falloff": 0.5,
+                "radius": 32.0
+            }
+        },
+        {
+            "name": "root",
+            "position": [1.0, 2.0, 3.0],
+            "rotation": [0.0, 0.0, 0.0, 1.0],
+            "scale": [2.0, 2.0, 2.0],
+            "mesh": "cube",
+            "material": "checker"
+        },
+        {
+            "name": "child",
+            "parent": 1,
+            "position": [0.0, 1.0, 0.0],
+            "rotation": [0.0, 0.0, 0.0, 1.0],
+            "scale": [1.0, 1.0, 1.0],
+            "colour": [1.0, 0.5, 0.25, 1.0],
+            "mesh": "sphere"
+        },
+        {
+            "name": "camera",
+            "position": [0.0, 0.0, -10.0],
+            "rotation": [0.0, 0.0, 0.0, 1.0],
+            "scale": [1.0, 1.0, 1.0],
+            "camera": {
+                "camera_type": "Orbit",
+                "rot": [-30.0, 0.0, 0.0],
+                "focus": [0.0, 0.0, 0.0],
+                "zoom": 10.0
+            }
+        }
+    ]
+}"#;
+
+/// Spawns `scene` into `world` by applying the commands immediately
+fn spawn_test_scene(world: &mut bevy_ecs::world::World, scene: &Scene, source: &str) -> Vec<bevy_ecs::entity::Entity> {
+    let mut queue = bevy_ecs::world::CommandQueue::default();
+    let entities = {
+        let mut commands = bevy_ecs::system::Commands::new(&mut queue, world);
+        scene::spawn_scene(&mut commands, scene, source)
+    };
+    queue.apply(world);
+    entities
+}
+
+#[test]
+fn scene_json_binary_round_trip() -> Result<(), hotline_rs::Error> {
+    let scene = Scene::from_json(TEST_SCENE)?;
+    assert_eq!(scene.version, scene::SCENE_VERSION);
+    assert_eq!(scene.entities.len(), 4);
+    assert_eq!(scene.entities[2].parent, Some(1));
+    assert_eq!(scene.entities[0].light.unwrap().light_type, ecs_base::LightType::Spot);
+    assert_eq!(scene.entities[3].camera.unwrap().camera_type, ecs_base::CameraType::Orbit);
+
+    assert_eq!(Scene::from_json(&scene.to_json()?)?, scene);
+
+    let binary = scene.to_binary();
+    assert!(binary.starts_with(&scene::SCENE_BINARY_MAGIC));
+    assert!(binary.len() < TEST_SCENE.len());
+    assert_eq!(Scene::from_binary(&binary)?, scene);
+
+    // both formats are detected when loading from file
+    let folder = common::test_folder("scene")?;
+    for (name, format) in [("scene.json", SceneFormat::Json), ("scene.bin", SceneFormat::Binary)] {
+        let filepath = folder.join(name);
+        scene::save_scene(filepath.to_str().unwrap(), &scene, format)?;
+        assert_eq!(scene::load_scene(filepath.to_str().unwrap())?, scene);
+    }
+    Ok(())
+}
+
+#[test]
+fn scene_errors() -> Result<(), hotline_rs::Error> {
+    let newer = TEST_SCENE.replace("\"version\": 1", "\"version\": 2");
+    assert!(Scene::from_json(&newer).err().unwrap().msg.contains("newer than the supported version"));
+    let unsupported = TEST_SCENE.replace("\"version\": 1", "\"version\": 0");
+    assert!(Scene::from_json(&unsupported).err().unwrap().msg.contains("unsupported scene version"));
+    assert!(Scene::from_json(r#"{"entities": []}"#).err().unwrap().msg.contains("missing a version"));
+
+    // parents must reference another entity in the scene
+    let invalid_parent = TEST_SCENE.replace("\"parent\": 1", "\"parent\": 4");
+    assert!(Scene::from_json(&invalid_parent).err().unwrap().msg.contains("invalid parent"));
+    let self_parent = TEST_SCENE.replace("\"parent\": 1", "\"parent\": 2");
+    assert!(Scene::from_json(&self_parent).err().unwrap().msg.contains("invalid parent"));
+
+    // every truncation of a binary scene fails to load rather than panicking
+    let binary = Scene::from_json(TEST_SCENE)?.to_binary();
+    for len in 0..binary.len() {
+        assert!(Scene::from_binary(&binary[..len]).is_err());
+    }
+    let mut newer = binary.clone();
+    newer[4] = 2;
+    assert!(Scene::from_binary(&newer).err().unwrap().msg.contains("newer than the supported version"));
+    assert!(Scene::from_bytes(b"HSCX").is_err());
+    Ok(())
+}
+
+#[test]
+fn scene_spawn_save() -> Result<(), hotline_rs::Error> {
+    let scene = Scene::from_json(TEST_SCENE)?;
+    let mut world = bevy_ecs::world::World::new();
+    let entities = spawn_test_scene(&mut world, &scene, "test_scene.json");
+    assert_eq!(entities.len(), 4);
+    assert_eq!(world.get::<Parent>(entities[2]).unwrap().0, entities[1]);
+    assert_eq!(world.get::<ecs_base::MeshAsset>(entities[1]).unwrap().0, "cube");
+    assert_eq!(world.get::<ecs_base::SceneSource>(entities[0]).unwrap().0, "test_scene.json");
+    assert!(world.get::<WorldMatrix>(entities[1]).is_some());
+    assert!(world.get::<WorldMatrix>(entities[0]).is_none());
+    assert!(world.get::<ecs_base::ViewProjectionMatrix>(entities[3]).is_some());
+
+    // spawned scenes are part of the transform hierarchy
+    let mut schedule = bevy_ecs::schedule::Schedule::default();
+    schedule.add_systems(ecs_base::transform_hierarchy_systems());
+    schedule.run(&mut world);
+    assert!(common::approx_vec3(common::world_position(&world, entities[2]), vec3f(1.0, 4.0, 3.0)));
+
+    // the main camera belongs to the client and is not saved
+    world.spawn((Name("main_camera".to_string()), ecs_base::MainCamera));
+    assert_eq!(scene::scene_from_world(&mut world), scene);
+    Ok(())
+}
+
+#[test]
+fn scene_reload() -> Result<(), hotline_rs::Error> {
+    let folder = common::test_folder("scene_reload")?;
+    let filepath = folder.join("scene.json");
+    let source = filepath.to_str().unwrap().to_string();
+    std::fs::write(&filepath, TEST_SCENE)?;
+
+    let mut world = bevy_ecs::world::World::new();
+    spawn_test_scene(&mut world, &scene::load_scene(&source)?, &source);
+    let mut schedule = bevy_ecs::schedule::Schedule::default();
+    schedule.add_systems(scene::reload_scenes);
+    schedule.run(&mut world);
+
+    // a scene which fails to load keeps the current entities
+    std::fs::write(&filepath, "{ \"version\": 1, ")?;
+    common::touch_file(&filepath, 1)?;
+    schedule.run(&mut world);
+    assert_eq!(scene::scene_from_world(&mut world), Scene::from_json(TEST_SCENE)?);
+
+    // a modified scene replaces the entities spawned from it
+    std::fs::write(&filepath, TEST_SCENE.replace("\"root\"", "\"renamed_root\""))?;
+    common::touch_file(&filepath, 2)?;
+    schedule.run(&mut world);
+    let reloaded = scene::scene_from_world(&mut world);
+    let find = |name: &str| reloaded.entities.iter().position(|e| e.name.as_deref() == Some(name));
+    assert_eq!(reloaded.entities.len(), 4);
+    assert!(find("root").is_none());
+    let root = find("renamed_root").unwrap();
+    assert_eq!(reloaded.entities[find("child").unwrap()].parent, Some(root as u32));
+    Ok(())
+}
@@ -0,0 +1,124 @@
+// tests for shader reflection loading and validation against pipelines
+
+mod common;
+
+use hotline_rs::gfx;
+use hotline_rs::shader_reflection;
+use hotline_rs::shader_reflection::ReflectionErrorKind;
+
+const TEST_REFLECTION_LAYOUT: &str = r#"{
+    "bindings": [
+        {"visibility": "Fragment", "shader_register": 0, "register_space": 0, "binding_type": "ShaderResource", "num_descriptors": null},
+        {"visibility": "Vertex", "shader_register": 1, "register_space": 0, "binding_type": "ConstantBuffer", "num_descriptors": 2}
+    ],
+    "push_constants": [
+        {"visibility": "Vertex", "shader_register": 0, "register_space": 0, "num_values": 16}
+    ],
+    "static_samplers": [
+        {"visibility": "Fragment", "shader_register": 0, "register_space": 0, "sampler_info": {
+            "filter": "Linear", "address_u": "Wrap", "address_v": "Wrap", "address_w": "Wrap", "comparison": null,
+            "border_colour": null, "mip_lod_bias": 0.0, "max_aniso": 0, "min_lod": -1.0, "max_lod": -1.0}}
+    ]
+}"#;
+
+const TEST_REFLECTION_INPUT_LAYOUT: &str = r#"[
+    {"semantic": "POSITION", "index": 0, "format": "RGB32f", "input_slot": 0, "aligned_byte_offset": 0,
+        "input_slot_class": "PerVertex", "step_rate": 0},
+    {"semantic": "TEXCOORD", "index": 0, "format": "RG32f", "input_slot": 0, "aligned_byte_offset": 12,
+        "input_slot_class": "PerVertex", "step_rate": 0}
+]"#;
+
+const TEST_REFLECTION_VS: &str = r#"{
+    "entry_point": "vs_main",
+    "stage": "Vertex",
+    "bindings": [
+        {"name": "per_pass", "shader_register": 1, "register_space": 0, "binding_type": "ConstantBuffer"},
+        {"name": "per_draw", "shader_register": 0, "register_space": 0, "binding_type": "ConstantBuffer"}
+    ],
+    "push_constants": [
+        {"name": "world_matrix", "shader_register": 0, "register_space": 0, "num_values": 16}
+    ],
+    "vertex_inputs": [
+        {"semantic": "position", "format": "RGB32f"},
+        {"semantic": "TEXCOORD", "index": 0, "format": "RG32f"},
+        {"semantic": "SV_VertexID", "format": "R32u"}
+    ]
+}"#;
+
+const TEST_REFLECTION_PS: &str = r#"{
+    "entry_point": "ps_main",
+    "stage": "Fragment",
+    "bindings": [
+        {"name": "textures", "shader_register": 0, "register_space": 0, "binding_type": "ShaderResource", "count": 0},
+        {"name": "sampler_wrap_linear", "shader_register": 0, "register_space": 0, "binding_type": "Sampler"}
+    ]
+}"#;
+
+#[test]
+fn shader_reflection_validate() -> Result<(), hotline_rs::Error> {
+    let layout : gfx::PipelineLayout = serde_json::from_str(TEST_REFLECTION_LAYOUT)?;
+    let input_layout : gfx::InputLayout = serde_json::from_str(TEST_REFLECTION_INPUT_LAYOUT)?;
+    let vs : shader_reflection::ShaderReflection = serde_json::from_str(TEST_REFLECTION_VS)?;
+    let ps : shader_reflection::ShaderReflection = serde_json::from_str(TEST_REFLECTION_PS)?;
+
+    // cbuffers may come from push constants, samplers from static samplers and unbounded ranges cover arrays
+    assert!(vs.validate("test.vsc", gfx::ShaderType::Vertex, &layout, Some(&input_layout)).is_empty());
+    assert!(ps.validate("test.psc", gfx::ShaderType::Fragment, &layout, None).is_empty());
+
+    // the vertex shader used in the fragment stage can not see any of its vertex only resources
+    let errors = vs.validate("test.vsc", gfx::ShaderType::Fragment, &layout, Some(&input_layout));
+    assert_eq!(common::reflection_error_kinds(&errors), vec![
+        ReflectionErrorKind::StageMismatch("test.vsc".to_string()),
+        ReflectionErrorKind::Visibility("per_pass".to_string()),
+        ReflectionErrorKind::Visibility("per_draw".to_string()),
+        ReflectionErrorKind::Visibility("world_matrix".to_string()),
+    ]);
+    assert!(errors[0].to_string().starts_with("test.vsc: "));
+
+    // missing layout entries and input elements
+    let errors = vs.validate("test.vsc", gfx::ShaderType::Vertex, &gfx::PipelineLayout::default(), None);
+    assert_eq!(common::reflection_error_kinds(&errors), vec![
+        ReflectionErrorKind::MissingBinding("per_pass".to_string()),
+        ReflectionErrorKind::MissingBinding("per_draw".to_string()),
+        ReflectionErrorKind::MissingPushConstants("world_matrix".to_string()),
+        ReflectionErrorKind::MissingVertexInput("position0".to_string()),
+        ReflectionErrorKind::MissingVertexInput("TEXCOORD0".to_string()),
+    ]);
+
+    // bounded ranges which are too small, push constants too small and mismatched formats
+    let mut small_layout = layout.clone();
+    small_layout.bindings.as_mut().unwrap()[0].num_descriptors = Some(4);
+    small_layout.push_constants.as_mut().unwrap()[0].num_values = 4;
+    let mut vec4_input_layout = input_layout.clone();
+    vec4_input_layout[1].format = gfx::Format::RGBA32f;
+    let errors = vs.validate("test.vsc", gfx::ShaderType::Vertex, &small_layout, Some(&vec4_input_layout));
+    assert_eq!(common::reflection_error_kinds(&errors), vec![
+        ReflectionErrorKind::PushConstantsSize("world_matrix".to_string()),
+        ReflectionErrorKind::VertexInputFormat("TEXCOORD0".to_string()),
+    ]);
+    let errors = ps.validate("test.psc", gfx::ShaderType::Fragment, &small_layout, None);
+    assert_eq!(common::reflection_error_kinds(&errors), vec![ReflectionErrorKind::MissingBinding("textures".to_string())]);
+    Ok(())
+}
+
+#[test]
+fn shader_reflection_load() -> Result<(), hotline_rs::Error> {
+    let folder = common::test_folder("shader_reflection_load")?;
+    let shader = folder.join("test.vsc");
+    let sidecar = shader_reflection::get_reflection_filepath(&shader);
+    assert_eq!(sidecar, folder.join("test.vsc.json"));
+
+    let _ = std::fs::remove_file(&sidecar);
+    assert!(shader_reflection::load_reflection(&shader)?.is_none());
+
+    std::fs::write(&sidecar, TEST_REFLECTION_VS)?;
+    let reflection = shader_reflection::load_reflection(&shader)?.unwrap();
+    assert_eq!(reflection.entry_point, "vs_main");
+    assert_eq!(reflection.stage, gfx::ShaderType::Vertex);
+    assert_eq!(reflection.bindings[0].count, 1);
+    assert_eq!(reflection.vertex_inputs.len(), 3);
+
+    std::fs::write(&sidecar, "{\"entry_point\": ")?;
+    assert!(shader_reflection::load_reflection(&shader).is_err());
+    Ok(())
+}
@@ -22,7 +22,7 @@ fn create_app() {
 }
 
 #[test]
-fn create_d3d12_device() {
+fn create_d3d12_device() -> Result<(), hotline_rs::Error> {
     let _ = os_platform::App::create(os::AppInfo {
         name: String::from("create_d3d12_device"),
         window: false,
@@ -34,7 +34,8 @@ fn create_d3d12_device() {
         shader_heap_size: 1,
         render_target_heap_size: 1,
         depth_stencil_heap_size: 1,
-    });
+    })?;
+    Ok(())
 }
 
 #[test]
@@ -78,7 +79,7 @@ fn swap_chain_buffer() -> Result<(), hotline_rs::Error> {
         shader_heap_size: 0,
         render_target_heap_size: 2,
         depth_stencil_heap_size: 0,
-    });
+    })?;
     let mut win = app.create_window(os::WindowInfo {
         title: String::from("swap chain buffering"),
         rect: os::Rect {
@@ -174,7 +175,7 @@ fn draw_triangle() -> Result<(), hotline_rs::Error> {
     let mut device = gfx_platform::Device::create(&gfx::DeviceInfo {
         render_target_heap_size: num_buffers,
         ..Default::default()
-    });
+    })?;
 
     let mut window = app.create_window(os::WindowInfo {
         title: String::from("triangle!"),
@@ -602,7 +603,10 @@ fn boot_client_ecs_plugin_demo(demo_name: &str) -> Result<(), hotline_rs::Error>
     }).unwrap();
     
     // run
-    ctx.run_once()
+    ctx.run_once()?;
+
+    // compare with the golden image reference, or bless with HOTLINE_BLESS=1
+    hotline_rs::image_diff::check_client_output(demo_name)
 }
 
 //
@@ -0,0 +1,116 @@
+// tests for the vulkan backend, these require a vulkan 1.3 driver and run headless under mesa lavapipe, they are
+// skipped with a message on machines without a vulkan loader or driver
+#![cfg(feature = "vulkan")]
+
+use hotline_rs::os;
+use hotline_rs::gfx;
+use hotline_rs::gfx::vk;
+use hotline_rs::os::App;
+use hotline_rs::gfx::{Device, SwapChain, CmdBuf, Buffer, ReadBackRequest};
+
+fn create_vk_device(shader_heap_size: usize) -> Result<vk::Device, hotline_rs::Error> {
+    vk::Device::create(&gfx::DeviceInfo {
+        adapter_name: None,
+        shader_heap_size,
+        render_target_heap_size: 4,
+        depth_stencil_heap_size: 4,
+    })
+}
+
+/// Returns a vulkan device, or `None` with a message so tests are skipped on machines without a loader or driver
+fn create_vk_device_or_skip(test_name: &str, shader_heap_size: usize) -> Option<vk::Device> {
+    match create_vk_device(shader_heap_size) {
+        Ok(dev) => Some(dev),
+        Err(err) => {
+            println!("skipping {}: {}", test_name, err.msg);
+            None
+        }
+    }
+}
+
+#[test]
+fn vk_swap_chain_clear_read_back() -> Result<(), hotline_rs::Error> {
+    let mut app = os::null::App::create(os::AppInfo {
+        name: String::from("vk_swap_chain_clear_read_back"),
+        window: false,
+        num_buffers: 0,
+        dpi_aware: true,
+    });
+    let win = app.create_window(os::WindowInfo {
+        rect: os::Rect { x: 0, y: 0, width: 8, height: 8 },
+        ..Default::default()
+    });
+    let mut dev = match create_vk_device_or_skip("vk_swap_chain_clear_read_back", 16) {
+        Some(dev) => dev,
+        None => return Ok(())
+    };
+    let mut swap_chain = dev.create_swap_chain::<os::null::App>(&gfx::SwapChainInfo {
+        num_buffers: 2,
+        format: gfx::Format::RGBA8n,
+        clear_colour: Some(gfx::ClearColour { r: 0.0, g: 1.0, b: 0.0, a: 1.0 }),
+    }, &win)?;
+    let mut cmd = dev.create_cmd_buf(2);
+
+    swap_chain.new_frame();
+    cmd.reset(&swap_chain);
+    cmd.transition_barrier(&gfx::TransitionBarrier {
+        texture: Some(swap_chain.get_backbuffer_texture()),
+        buffer: None,
+        state_before: gfx::ResourceState::Present,
+        state_after: gfx::ResourceState::RenderTarget,
+    });
+    cmd.begin_render_pass(swap_chain.get_backbuffer_pass());
+    cmd.end_render_pass();
+    cmd.transition_barrier(&gfx::TransitionBarrier {
+        texture: Some(swap_chain.get_backbuffer_texture()),
+        buffer: None,
+        state_before: gfx::ResourceState::RenderTarget,
+        state_after: gfx::ResourceState::Present,
+    });
+    let rr = cmd.read_back_backbuffer(&swap_chain)?;
+    cmd.close()?;
+    dev.execute(&cmd);
+    swap_chain.swap(&mut dev);
+    swap_chain.wait_for_last_frame();
+
+    assert!(rr.is_complete(&swap_chain));
+    let data = rr.map(&gfx::MapInfo { subresource: 0, read_start: 0, read_end: usize::MAX })?;
+    assert_eq!(data.size, 8 * 8 * 4);
+    assert_eq!(data.data[0..4], [0, 255, 0, 255]);
+    Ok(())
+}
+
+#[test]
+fn vk_buffer_write_read_back() -> Result<(), hotline_rs::Error> {
+    let mut dev = match create_vk_device_or_skip("vk_buffer_write_read_back", 16) {
+        Some(dev) => dev,
+        None => return Ok(())
+    };
+    let mut buf = dev.create_buffer::<u32>(&gfx::BufferInfo {
+        usage: gfx::BufferUsage::SHADER_RESOURCE,
+        cpu_access: gfx::CpuAccessFlags::WRITE | gfx::CpuAccessFlags::PERSISTENTLY_MAPPED,
+        format: gfx::Format::Unknown,
+        stride: 4,
+        num_elements: 4,
+        initial_state: gfx::ResourceState::ShaderResource,
+    }, None)?;
+    assert!(buf.get_srv_index().is_some());
+    buf.write(4, &[7u32, 8u32])?;
+    assert!(buf.write(12, &[1u32, 2u32]).is_err());
+    Ok(())
+}
+
+#[test]
+fn vk_device_create_falls_back_to_null() -> Result<(), hotline_rs::Error> {
+    // machines without a vulkan loader or a vulkan 1.3 adapter return an error instead of panicking
+    let adapter = match create_vk_device(16) {
+        Ok(dev) => dev.get_adapter_info().name.to_string(),
+        Err(err) => {
+            assert!(err.msg.starts_with("hotline_rs::gfx::vk:"), "{}", err.msg);
+            let dev = gfx::null::Device::create(&gfx::DeviceInfo::default())?;
+            dev.get_adapter_info().name.to_string()
+        }
+    };
+    assert!(!adapter.is_empty());
+    Ok(())
+}